@@ -240,3 +240,32 @@ CREATE TABLE IF NOT EXISTS overall_stats (
     stats_cache_date         TEXT,                  -- ISO8601 date: YYYY-MM-DD
     synced_at                TEXT DEFAULT (datetime('now'))
 );
+
+-- ============================================================
+-- FAVORITES TABLE
+-- Messages starred in the dashboard, with an optional note.
+-- ============================================================
+CREATE TABLE IF NOT EXISTS favorites (
+    message_id INTEGER PRIMARY KEY,
+    note       TEXT,
+    created_at TEXT DEFAULT (datetime('now')),
+
+    FOREIGN KEY (message_id)
+        REFERENCES messages (id)
+        ON DELETE CASCADE
+);
+
+-- ============================================================
+-- SESSION TAGS TABLE
+-- User-defined labels on sessions, set in the dashboard. No foreign key:
+-- tags stay put if a session is deleted and later re-ingested.
+-- ============================================================
+CREATE TABLE IF NOT EXISTS session_tags (
+    session_id TEXT NOT NULL,
+    tag        TEXT NOT NULL,
+    created_at TEXT DEFAULT (datetime('now')),
+
+    PRIMARY KEY (session_id, tag)
+);
+
+CREATE INDEX IF NOT EXISTS idx_session_tags_tag ON session_tags (tag);
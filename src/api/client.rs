@@ -1,5 +1,6 @@
 //! HTTP client for the dashboard API.
 
+use crate::graph::ids;
 use crate::graph::types::{GraphData, GraphEdge, GraphNode, NeighborhoodSummaryData, PartialSummaryData, SemanticFilter, SessionSummaryData};
 use std::collections::HashMap;
 use crate::mail::MailNetworkData;
@@ -7,7 +8,8 @@ use reqwest::blocking::Client;
 use serde::Deserialize;
 use std::time::Duration;
 
-fn api_base() -> String {
+/// Base URL of the Python API (`API_BASE_URL`, default localhost:8000)
+pub fn api_base() -> String {
     std::env::var("API_BASE_URL").unwrap_or_else(|_| "http://127.0.0.1:8000".to_string())
 }
 
@@ -96,6 +98,19 @@ pub struct ProximityEdgesResponse {
     pub query: String,
 }
 
+impl ProximityEdgesResponse {
+    /// Message ids as `msg:` node ids; the backend sends bare row ids
+    fn normalized(self) -> Self {
+        Self {
+            edges: self.edges.into_iter()
+                .map(|e| ProximityEdgeResponse { source: ids::normalize(e.source), target: ids::normalize(e.target), ..e })
+                .collect(),
+            scores: self.scores.into_iter().map(|(id, score)| (ids::normalize(id), score)).collect(),
+            ..self
+        }
+    }
+}
+
 /// Progress for semantic filter categorization
 #[derive(Debug, Clone, Deserialize)]
 pub struct FilterStatusResponse {
@@ -190,12 +205,9 @@ impl ApiClient {
             .json()
             .map_err(|e| format!("Failed to parse response: {}", e))?;
 
-        Ok(GraphData {
-            nodes: graph_resp.nodes,
-            edges: graph_resp.edges,
-            beads: Vec::new(),
-            mail: Vec::new(),
-        })
+        let mut data = GraphData::new(graph_resp.nodes, graph_resp.edges);
+        ids::normalize_graph(&mut data);
+        Ok(data)
     }
 
     /// Fetch partial summary for a session up to a specific timestamp
@@ -556,7 +568,7 @@ impl ApiClient {
         let url = format!("{}/semantic-filters/{}/categorize-visible", self.base_url, filter_id);
 
         let ids: Vec<i64> = message_ids.iter()
-            .filter_map(|id| id.parse::<i64>().ok())
+            .filter_map(|id| ids::message_id(id))
             .collect();
 
         let resp = self
@@ -645,7 +657,7 @@ impl ApiClient {
         let url = format!("{}/embeddings/generate-visible", self.base_url);
 
         let ids: Vec<i64> = message_ids.iter()
-            .filter_map(|id| id.parse::<i64>().ok())
+            .filter_map(|id| ids::message_id(id))
             .collect();
 
         let resp = self
@@ -702,7 +714,7 @@ impl ApiClient {
             .json()
             .map_err(|e| format!("Failed to parse response: {}", e))?;
 
-        Ok(response)
+        Ok(response.normalized())
     }
 
     /// Compute the visible set of message IDs based on semantic filter modes.
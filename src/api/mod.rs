@@ -2,4 +2,4 @@
 
 mod client;
 
-pub use client::{ApiClient, EmbeddingGenResult, EmbeddingStats, FilterStatusResponse, ImportanceStats, IngestResult, ProximityEdgesResponse, RescoreEvent, RescoreProgress, RescoreResult};
+pub use client::{api_base, ApiClient, EmbeddingGenResult, EmbeddingStats, FilterStatusResponse, ImportanceStats, IngestResult, ProximityEdgesResponse, RescoreEvent, RescoreProgress, RescoreResult};
@@ -1,45 +1,177 @@
 //! Main application state and UI.
 
 use crate::api::{ApiClient, EmbeddingGenResult, EmbeddingStats, FilterStatusResponse, IngestResult, RescoreEvent, RescoreProgress, RescoreResult};
-use crate::db::DbClient;
-use crate::graph::types::{ColorMode, FilterMode, GraphEdge, NeighborhoodSummaryData, PartialSummaryData, SemanticFilter, SemanticFilterMode, SessionSummaryData};
-use crate::graph::{ForceLayout, GraphState};
+use crate::beads_dir::{self, BeadsSearch};
+use crate::bundle::{BundleEvents, BundleGraph, DebugBundle, Manifest};
+use crate::cli::{expand_home, CliArgs};
+use crate::command::{self, AppCommand, CommandQueue, CommandTarget};
+use crate::compare::{self, SessionStats};
+use crate::content_kind::ContentKind;
+use crate::cost::{self, CostSummary, TokenRates, TokenTotals};
+use crate::db::{self, DbClient, Favorite, LoadChunk};
+use crate::diff::{self, DiffClass, GraphDiff};
+use crate::discover::{self, Discovered};
+use crate::graph::ids;
+use crate::graph::types::{ColorMode, FeedEntry, FeedKind, FilterMode, GraphData, GraphEdge, HistogramState, HueAssignment, HueTarget, IssueStatus, Role, StackOrder, TokenDisplayMode, NeighborhoodSummaryData, PartialSummaryData, SemanticFilter, SemanticFilterMode, SessionSummaryData, format_idle_gap, idle_gap_cooling, lerp_color, parse_iso_timestamp};
+use crate::graph::community::{self, Communities};
+use crate::graph::spatial::{self, Declutter, DeclutterLevel, HitTest, SpatialGrid};
+use crate::graph::scene::{ArrowStyle, EdgeKind, LineStyle};
+use crate::graph::{scene, ForceLayout, GraphState, LayoutAlgorithm};
+use crate::history::History;
+use crate::log_buffer;
 use crate::mail::{MailNetworkState, render_mail_network};
+use crate::memory::{self, MemoryEstimate};
+use crate::notify::{ActivityKind, ActivityWatch, NewActivity, Notifier};
+use crate::obsidian::{self, MessageRef};
+use crate::overview::{self, OverviewSizing, OverviewState};
+use crate::profiler::{self, FrameProfiler, FrameTimes, Phase};
+use crate::tags::{self, SessionTags, TagFilter};
+use crate::text_layout::TextLayoutCache;
 use crate::project_tree::{self, CheckState, ProjectTreeNode};
-use crate::settings::{Preset, Settings, SidebarTab, SizingPreset};
+use crate::rules::Rule;
+use crate::setup::{self, Environment};
+use crate::settings::{CorruptSettings, FilterPill, HoverScrubModifier, LastView, NodeInfoSource, Preset, RoleFilter, SavedView, Settings, SidebarTab, SizingPreset, ViewMode, ViewSettings};
+use crate::shortcuts::{self, Action, Category, Shortcut, SHORTCUTS};
+use crate::stats::{self, ActivityStats};
+use crate::stats_export::{ActiveFilters, StatsExport};
+use crate::text::{self, truncate};
+use crate::text_diff::{self, ContentDiff, LineChange};
+use crate::snapshot::{self, LayoutSnapshot, SnapshotHistory};
+use crate::topics;
+use crate::transcript::{self, Transcript};
 use crate::theme;
+use crate::theme_file;
+use crate::time_expr;
+use crate::view_string::ViewString;
+use crate::watchdog::{self, Watchdog};
+use crate::write_queue::{WriteError, WriteOp, WriteQueue};
 use eframe::egui::{self, Color32, Pos2, Stroke, Vec2};
 use std::collections::{HashMap, HashSet};
 use std::sync::mpsc::{self, Receiver};
 use std::time::{Instant, SystemTime};
 
-/// Fixed 8-color palette for proximity query edges
-const QUERY_COLORS: [Color32; 8] = [
-    Color32::from_rgb(6, 182, 212),    // Cyan (original)
-    Color32::from_rgb(249, 115, 22),   // Orange
-    Color32::from_rgb(168, 85, 247),   // Purple
-    Color32::from_rgb(34, 197, 94),    // Green
-    Color32::from_rgb(239, 68, 68),    // Red
-    Color32::from_rgb(234, 179, 8),    // Yellow
-    Color32::from_rgb(236, 72, 153),   // Pink
-    Color32::from_rgb(59, 130, 246),   // Blue
-];
+/// Maximum number of undo steps kept for settings changes
+const SETTINGS_HISTORY_LIMIT: usize = 100;
+
+/// How long an applied preset stays highlighted in the preset list
+const PRESET_FLASH_SECS: f32 = 1.2;
+
+/// Upper bound for the auto-refresh interval after repeated failures
+const AUTO_REFRESH_MAX_BACKOFF_SECS: f32 = 300.0;
+
+/// Graph-space center the session overview's layout settles around
+const OVERVIEW_CENTER: Pos2 = Pos2::new(400.0, 300.0);
+
+/// Quiet period after the last control change before a requested reload runs
+const RELOAD_DEBOUNCE: std::time::Duration = std::time::Duration::from_millis(400);
+
+/// Most layout steps run at once when reduced motion settles a graph instead of animating it
+const PRESETTLE_MAX_STEPS: usize = 600;
+
+/// Repaint interval while waiting on background work with reduced motion on
+const REDUCED_MOTION_REPAINT: std::time::Duration = std::time::Duration::from_millis(250);
+
+/// A session counts as ended once it has been quiet this long
+const SESSION_IDLE_SECS: f64 = 300.0;
+
+/// How far back the "Recently Ended" list looks
+const RECENTLY_ENDED_SECS: f64 = 3600.0;
+
+/// Cmd+drag on the graph canvas selecting a time window (screen positions)
+#[derive(Debug, Clone, Copy)]
+struct TimeDrag {
+    origin: Pos2,
+    current: Pos2,
+    /// (start_position, position) before the drag, restored on Escape
+    restore: (f32, f32),
+}
+
+/// Narrowest canvas drag, in screen pixels, that selects a time window
+const TIME_DRAG_MIN_PX: f32 = 4.0;
+
+/// How close, in screen pixels, the pointer must be to a gap edge to hover it
+const GAP_HOVER_PX: f32 = 5.0;
+
+/// Longest time range the range slider offers, in hours
+pub(crate) const MAX_RANGE_HOURS: f32 = 2160.0;
+
+/// Smallest on-screen node radius that gets a content badge
+const CONTENT_BADGE_MIN_RADIUS: f32 = 7.0;
+
+/// Zoom past which long conversation edges get their idle gap label
+const IDLE_GAP_LABEL_MIN_ZOOM: f32 = 1.5;
+
+/// Repaint interval while live sessions are animating; slow enough to stay cheap
+const LIVE_REPAINT: std::time::Duration = std::time::Duration::from_millis(250);
+
+/// How far (pixels per second at zoom 1) a live tail edge's dashes march
+const LIVE_MARCH_SPEED: f32 = 6.0;
+
+/// Temporal and similarity links listed under a node's forces
+const NODE_FORCE_LINKS: usize = 5;
+
+/// Closed beads laid out at first, and added per "Show more"
+const BEADS_CLOSED_PAGE: usize = 100;
+
+/// Frames averaged for the FPS readout
+const FPS_WINDOW: usize = 60;
+/// Frame time sparkline guides: 60 fps and 30 fps budgets, in ms
+const FRAME_BUDGET_60_MS: f32 = 1000.0 / 60.0;
+const FRAME_BUDGET_30_MS: f32 = 1000.0 / 30.0;
+
+/// Vault scan result: note nodes and edges, and each note's vault-relative path
+type VaultScan = Result<(GraphData, HashMap<String, String>), String>;
+
+/// Proximity query result: the query's similarity edges and each node's score
+type ProximityResult = Result<(Vec<GraphEdge>, HashMap<String, f32>), String>;
+
+/// Database figures fetched beside a load (see `start_load_stats`)
+struct LoadStats {
+    importance: Option<db::ImportanceStats>,
+    /// Newest message anywhere, looked up only when the load came back empty
+    latest_message_time: Option<f64>,
+}
+
+/// Edge pass output kept between frames; reused while `key` still matches
+struct EdgeFrame {
+    /// `edge_scene_key` it was built for (None when it animates and can't be reused)
+    key: Option<u64>,
+    shapes: Vec<egui::Shape>,
+    /// Screen segments of drawn gap edges, for hover
+    gap_segments: Vec<(usize, Pos2, Pos2)>,
+    /// Midpoint and length of idle gaps to label
+    idle_labels: Vec<(Pos2, f64)>,
+}
 
 /// A single proximity (semantic edge) query with its own color, scores, and edges
 struct ProximityQuery {
     query: String,
-    color: Color32,
     scores: HashMap<String, f32>,
     edges: Vec<GraphEdge>,
     edge_count: usize,
     active: bool,
     loading: bool,
-    rx: Option<Receiver<Result<(Vec<GraphEdge>, HashMap<String, f32>), String>>>,
+    rx: Option<Receiver<ProximityResult>>,
     // Per-query settings
     opacity: f32,
     visible: bool,
 }
 
+/// A soloed project tree node and what to restore when it is un-soloed
+struct ProjectSolo {
+    path: String,
+    previous_selection: HashSet<String>,
+    previous_pushdown: Vec<String>,
+}
+
+/// The parts of a pasted view string that need its data, held until the
+/// reload it started has finished
+struct PendingViewString {
+    /// Selection and timeline window
+    view: LastView,
+    hidden_projects: Vec<String>,
+}
+
 /// Which edge popup is currently open (gear icon popups)
 #[derive(Debug, Clone, Copy, PartialEq)]
 enum EdgePopup {
@@ -50,6 +182,81 @@ enum EdgePopup {
     ProximityQuery(usize),
 }
 
+/// The "Diff contents" window
+enum ContentDiffState {
+    /// Fetching full text or diffing large contents on a worker thread
+    Running(Receiver<Result<ContentDiff, String>>),
+    Done(ContentDiff),
+    Failed(String),
+}
+
+/// Sidebar/popup sections with their own reset-to-defaults button
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum SettingsSection {
+    Display,
+    Colors,
+    NodeSizing,
+    SizePhysics,
+    Physics,
+    LayoutShaping,
+    TemporalClustering,
+    Importance,
+    Project,
+    ToolUses,
+    SemanticFilters,
+}
+
+/// What a pill in the bar above the graph toggles
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum PillAction {
+    Role(RoleFilter),
+    ToolCalls,
+    HighImportance,
+    Semantic(i32),
+}
+
+/// One pill in the bar above the graph, built fresh each frame from filter state
+struct PillSpec {
+    action: PillAction,
+    label: String,
+    hover: String,
+    on: bool,
+}
+
+/// A pending change from the preset list, applied after the list is drawn
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum PresetAction {
+    Apply(usize),
+    StartRename(usize),
+    CommitRename(usize),
+    Overwrite(usize),
+    ConfirmDelete(usize),
+    Delete(usize),
+    Swap(usize, usize),
+    ToggleStartup(usize),
+    ToggleViewDefault(usize, ViewMode),
+}
+
+/// Temporal edge caps offered in the sidebar, ascending
+const TEMPORAL_EDGE_LIMITS: [(usize, &str); 6] = [
+    (10_000, "10k"),
+    (50_000, "50k"),
+    (100_000, "100k"),
+    (250_000, "250k"),
+    (500_000, "500k"),
+    (1_000_000, "1M"),
+];
+
+/// Per-node temporal edge caps offered in the sidebar (0 = unlimited)
+const TEMPORAL_NEIGHBOR_LIMITS: [(usize, &str); 6] = [
+    (10, "10"),
+    (20, "20"),
+    (50, "50"),
+    (100, "100"),
+    (200, "200"),
+    (0, "Unlimited"),
+];
+
 /// Time range options for filtering
 /// Format hours into a human-readable time range label
 fn format_hours_label(hours: f32) -> String {
@@ -81,6 +288,37 @@ fn format_hours_label(hours: f32) -> String {
     }
 }
 
+/// Area new nodes are scattered over when a load places them
+fn load_bounds() -> egui::Rect {
+    egui::Rect::from_center_size(Pos2::new(400.0, 300.0), Vec2::new(600.0, 400.0))
+}
+
+/// "42,000 / ~180,000 messages" for a chunked load in progress
+fn load_progress_text(loaded: usize, expected: usize) -> String {
+    format!("{} / ~{} messages", format_count(loaded as i64), format_count(expected as i64))
+}
+
+/// Format a count with thousands separators (48200 -> "48,200")
+fn format_count(n: i64) -> String {
+    let digits = n.unsigned_abs().to_string();
+    let mut out = String::with_capacity(digits.len() + digits.len() / 3 + 1);
+    if n < 0 {
+        out.push('-');
+    }
+    for (i, c) in digits.chars().enumerate() {
+        if i > 0 && (digits.len() - i).is_multiple_of(3) {
+            out.push(',');
+        }
+        out.push(c);
+    }
+    out
+}
+
+/// Cache hit ratio as a percentage, "—" when there's nothing to measure
+fn format_ratio(ratio: Option<f64>) -> String {
+    ratio.map(|r| format!("{:.0}%", r * 100.0)).unwrap_or_else(|| "—".to_string())
+}
+
 /// Get histogram bin duration in seconds for a given time range in hours.
 /// Snaps to the nearest "nice" interval, targeting ~20 bins.
 fn bin_duration_for_hours(hours: f32) -> f64 {
@@ -104,6 +342,29 @@ fn bin_duration_for_hours(hours: f32) -> f64 {
         .unwrap_or(raw_bin)
 }
 
+/// Messages per `bin_duration` bucket from `min_time`; the last bucket also takes `max_time`
+fn timeline_bin_counts(timestamps: &[f64], min_time: f64, max_time: f64, bin_duration: f64) -> Vec<usize> {
+    let time_span = max_time - min_time;
+    if time_span <= 0.0 || bin_duration <= 0.0 {
+        return Vec::new();
+    }
+    let num_bins = ((time_span / bin_duration).ceil() as usize).max(1);
+    let mut bin_counts = vec![0; num_bins];
+    for &t in timestamps {
+        let bin_idx = ((t - min_time) / bin_duration) as usize;
+        bin_counts[bin_idx.min(num_bins - 1)] += 1;
+    }
+    bin_counts
+}
+
+/// Move a zoomed timeline window (in scrubber positions) so it's centered on
+/// `center`, keeping its width and staying inside 0..1
+fn pan_timeline_zoom((start, end): (f32, f32), center: f32) -> (f32, f32) {
+    let span = (end - start).clamp(0.0, 1.0);
+    let start = (center - span / 2.0).clamp(0.0, 1.0 - span);
+    (start, start + span)
+}
+
 /// Importance scoring statistics
 #[derive(Debug, Clone)]
 pub struct ImportanceStats {
@@ -150,6 +411,10 @@ pub struct DashboardApp {
     db: Option<DbClient>,
     db_connected: bool,
     db_error: Option<String>,
+    db_path_override: Option<String>, // from --db-path
+
+    // Restrict loads to a single session (from --session)
+    session_filter: Option<String>,
 
     // Graph state
     graph: GraphState,
@@ -159,11 +424,20 @@ pub struct DashboardApp {
     sidebar_tab: SidebarTab,
     time_range_hours: f32,       // currently loaded time range
     slider_hours: f32,           // pending slider value (before confirm)
+    hours_from_cli: bool,        // range came from --hours; not saved until changed
     node_size: f32,
     show_arrows: bool,
     loading: bool,
     timeline_enabled: bool,
     timeline_histogram_mode: bool,
+    /// Scrubber positions shown across the track when zoomed in (None: the full range)
+    timeline_zoom: Option<(f32, f32)>,
+    /// "Jump to" box text, its last hint or error, and the scrubber spot to flash
+    jump_input: String,
+    jump_hint: Option<(String, bool)>, // (message, is_error)
+    jump_flash: Option<(f32, Instant)>,
+    /// Where the minimap bracket was grabbed, relative to its center
+    timeline_minimap_grab: Option<f32>,
     hover_scrubs_timeline: bool,
 
     // Node sizing (unified formula)
@@ -194,26 +468,122 @@ pub struct DashboardApp {
     selected_projects: HashSet<String>,
     available_projects: Vec<String>,
     project_tree: Option<ProjectTreeNode>,
+    exclude_pattern_input: String,
     /// Tracks which tree nodes are expanded in the UI (by full_path).
     project_tree_expanded: HashSet<String>,
+    /// Saved project selection, applied by the first full load
+    pending_project_selection: Option<HashSet<String>>,
+    /// Previous session's selection and timeline window, applied after the first full load
+    pending_last_view: Option<LastView>,
+    /// Pasted view string waiting on the reload it started
+    pending_view_string: Option<PendingViewString>,
+    /// Last view written to disk, so periodic saves skip unchanged camera state
+    saved_last_view: LastView,
+    /// Filter text for the project tree (matching branches are shown expanded)
+    project_tree_search: String,
+    /// Projects pushed down into the next loads (empty = all projects)
+    project_pushdown: Vec<String>,
+    /// Set while a project tree node is soloed (double-click)
+    project_solo: Option<ProjectSolo>,
+    /// Newest message in the database (epoch seconds), looked up when a load comes back empty
+    latest_message_time: Option<f64>,
+    /// Loaded nodes per project, computed once per load
+    project_node_counts: HashMap<String, usize>,
+    /// Nodes per project inside the current timeline window
+    project_window_counts: HashMap<String, usize>,
+    /// Token totals behind the Cost section (window, today, per project)
+    cost_summary: CostSummary,
+    /// Charts behind the Stats section; None until first shown after a load
+    activity_stats: Option<ActivityStats>,
+    /// Approximate bytes held by the loaded graph
+    memory_estimate: MemoryEstimate,
+    /// Hover hit-testing grid over the effective visible nodes (indices into
+    /// `graph.data.nodes`), kept only for large graphs
+    hit_grid: Option<SpatialGrid>,
+    hit_grid_dirty: bool,
+    hit_test: HitTest,
+    /// On-screen radius each node was drawn at last frame (0 when not drawn)
+    node_radii: Vec<f32>,
+    /// Nodes drawn under smart declutter at the current zoom step; dropped
+    /// whenever the visible set changes
+    declutter: Option<(DeclutterLevel, Declutter)>,
+    /// Last frame's edge geometry
+    edge_frame: Option<EdgeFrame>,
+    /// Community detection running on a worker thread, tagged with the graph's
+    /// community generation when it started
+    community_rx: Option<Receiver<(u64, Communities)>>,
+    /// Legend click: other communities are dimmed
+    community_focus: Option<usize>,
+    /// "Find similar" running on a worker thread
+    similar_rx: Option<Receiver<Result<discover::Found, String>>>,
+    /// Nodes and edges added by "Find similar", kept across reloads
+    discovered: Discovered,
+    /// Node ids to frame on the next graph frame
+    fit_request: Option<Vec<String>>,
+    /// Line diff of two messages' contents, from the node context menu
+    content_diff: Option<ContentDiffState>,
+    /// Transcripts dropped onto the window, each its own in-memory session
+    dropped_transcripts: Vec<Transcript>,
+    drop_status: Option<(String, bool)>, // (message, is_error)
+    /// Why the last load switched to leaner settings
+    memory_notice: Option<String>,
+    /// Selected node that fell out of the last load: (id, its timestamp)
+    lost_selection: Option<(String, f64)>,
+    /// Sessions with a message inside the live window, most recent first
+    live_sessions: Vec<String>,
+    /// Latest message of each live session
+    live_tails: HashSet<String>,
+    /// Node nearest the scrubber, keyed by the scrubber position it was found for
+    scrubber_node: Option<(f32, Option<usize>)>,
 
     // Debug tooltip
     debug_tooltip: bool,
 
+    /// `?` overlay listing the shortcut registry
+    shortcut_help_open: bool,
+
+    // Colors for the active theme (refreshed every frame)
+    palette: theme::Palette,
+
+    // User theme file load result
+    theme_file_status: Option<(String, bool)>, // (message, is_error)
+    theme_file_errors: Vec<theme_file::LineError>,
+    // Settings file that failed to parse at startup (banner until dismissed)
+    settings_recovered: Option<CorruptSettings>,
+    // First-run overlay: what detection found, detection and ingest in flight,
+    // and the outcome of the last step taken
+    onboarding_open: bool,
+    onboarding_env: Option<Environment>,
+    onboarding_receiver: Option<Receiver<Environment>>,
+    onboarding_task: Option<Receiver<Result<String, String>>>,
+    onboarding_status: Option<(String, bool)>, // (message, is_error)
+
     // Viewport state
     pan_offset: Vec2,
     zoom: f32,
     dragging: bool,
     drag_start: Option<Pos2>,
+    time_drag: Option<TimeDrag>,
 
     // Timeline dragging state
     timeline_dragging: bool,
+    /// Hover-scrub position requested this frame (graph or scrubber hover)
+    hover_scrub_target: Option<f32>,
     last_playback_time: Instant,
+    /// Loads, selection, timeline and preset changes requested this frame
+    commands: CommandQueue,
 
     // Performance tracking
     last_frame: Instant,
-    frame_times: Vec<f32>,
+    frame_times: FrameTimes,
     fps: f32,
+    profiler: FrameProfiler,
+    /// Frames that blocked the UI thread past the stall threshold
+    watchdog: Watchdog,
+    /// Per-phase timing overlay on the graph
+    profiler_overlay: bool,
+    /// Laid-out tooltip, badge and axis label text reused across frames
+    text_layout: TextLayoutCache,
 
     // Summary panel state (point-in-time)
     summary_node_id: Option<String>,
@@ -241,6 +611,8 @@ pub struct DashboardApp {
 
     // Cmd+Hover neighborhood preview
     cmd_hover_neighbors: HashSet<String>,
+    /// Gap edge under the pointer, by index into the graph's edges
+    hovered_gap: Option<usize>,
 
     // Floating summary window state
     summary_window_open: bool,
@@ -262,15 +634,77 @@ pub struct DashboardApp {
     settings_dirty: bool,
     last_settings_save: Instant,
 
+    // Undo/redo of tunable settings (snapshots stored as unnamed presets)
+    settings_history: History<Preset>,
+    settings_history_pending: bool,
+
     // Preset management
     preset_name_input: String,
+    preset_include_analysis: bool,
+
+    // Token usage panel (below the timeline)
+    token_histogram: HistogramState,
+    token_histogram_key: Option<(usize, u64, u64, ColorMode, bool)>,
+    token_brush: Option<(f64, f64)>, // (anchor time, pointer time) while brushing
+    token_csv_status: Option<(String, bool)>, // (message, is_error)
+    stats_json_status: Option<(String, bool)>, // (message, is_error)
+    /// Leave message text out of exported debug bundles
+    bundle_anonymize: bool,
+    bundle_status: Option<(String, bool)>, // (message, is_error)
+    /// Directory of the debug bundle shown instead of the database
+    bundle_source: Option<String>,
+    /// Named layout snapshots (positions, camera, visibility settings)
+    snapshots: SnapshotHistory,
+    snapshot_name_input: String,
+    snapshot_status: Option<(String, bool)>, // (message, is_error)
+    /// Starred messages, newest first, and their node ids for drawing the star
+    favorites: Vec<Favorite>,
+    favorite_ids: HashSet<String>,
+    /// Favorite whose note is being edited: (message id, text)
+    favorite_note_edit: Option<(i32, String)>,
+    favorites_status: Option<(String, bool)>, // (message, is_error)
+    /// User-defined tags per session, read from the database on each load
+    session_tags: SessionTags,
+    /// Shows only sessions with one of these tags
+    tag_filter: TagFilter,
+    /// Text being typed into the session tag editor
+    tag_input: String,
+    tags_error: Option<String>,
+    /// Node to select and frame once a load brings it in
+    pending_focus: Option<String>,
+
+    // UI scale slider value while dragging (committed on release)
+    ui_scale_drag: Option<f32>,
+    ui_scale_dirty: bool,
     selected_preset_index: Option<usize>,
+    preset_renaming: Option<(usize, String)>,
+    /// Saved view last applied or saved; shown as modified once the selection diverges
+    active_saved_view: Option<String>,
+    /// Inline name editor for saved views: no index for a new view, else the one being renamed
+    saved_view_editing: Option<(Option<usize>, String)>,
+    preset_delete_confirm: Option<usize>,
+    preset_flash: Option<(usize, Instant)>,
+
+    // Shareable view strings
+    view_string_input: String,
+    view_string_status: Option<(String, bool)>, // (message, is_error)
+    missing_preset_warnings: HashSet<String>,
 
     // Semantic filters
     semantic_filters: Vec<SemanticFilter>,
     semantic_filter_modes: HashMap<i32, SemanticFilterMode>,
     new_filter_input: String,
     semantic_filter_loading: bool,
+    /// Filter writes that failed and are waiting to be replayed
+    write_queue: WriteQueue,
+    write_queue_open: bool,
+    /// Tooltip field editor window
+    tooltip_config_open: bool,
+    edge_styles_open: bool,
+    /// Project or session whose hue is being picked
+    hue_picker: Option<HueTarget>,
+    /// Reduced motion: settle the freshly loaded layout in one go, then stop physics
+    presettle_pending: bool,
     categorizing_filter_id: Option<i32>,
     categorization_receiver: Option<Receiver<Result<(), String>>>,
     categorization_progress_rx: Option<Receiver<FilterStatusResponse>>,
@@ -280,6 +714,9 @@ pub struct DashboardApp {
     // Backend-computed semantic filter visible set (message IDs from API, None = no filtering)
     semantic_visible_ids: Option<HashSet<i64>>,
     semantic_filter_pending: bool,
+    // Rule filters evaluated locally: filter id -> matching node ids, or the parse error
+    local_rule_matches: HashMap<i32, HashSet<String>>,
+    rule_errors: HashMap<i32, String>,
     semantic_filter_rx: Option<Receiver<Result<Option<Vec<i64>>, String>>>,
 
     // Expanded filter detail panels (toggled by clicking filter name)
@@ -302,8 +739,37 @@ pub struct DashboardApp {
 
     // Refresh & sync state
     last_synced: Option<Instant>,
+    refresh_rx: Option<Receiver<Result<GraphData, String>>>,
+    refresh_anchor: Instant,
+    refresh_failures: u32,
+    /// Last control change asking for a reload; the load runs once changes stop
+    reload_requested_at: Option<Instant>,
+    /// Full load running on a background thread, delivered in chunks: the
+    /// first is installed non-incrementally, later ones merged in
+    load_rx: Option<Receiver<Result<LoadChunk, String>>>,
+    /// (messages loaded, expected) while a chunked load is partway through
+    load_progress: Option<(usize, usize)>,
+    load_stats_rx: Option<Receiver<LoadStats>>,
+    /// Sessions and important messages seen so far, and what arrived since
+    /// the badge was last clicked
+    activity_watch: ActivityWatch,
+    notifier: Notifier,
+    new_activity: Vec<NewActivity>,
     beads_last_check: Instant,
     beads_last_mtime: Option<SystemTime>,
+    /// Closed beads shown before "Show more"
+    beads_closed_limit: usize,
+    beads_search: BeadsSearch,
+    beads_path_input: String,
+    /// Note nodes and edges from the latest vault scan, re-attached to each load
+    obsidian_notes: GraphData,
+    /// Vault-relative path per note node, for the note window title
+    obsidian_note_paths: HashMap<String, String>,
+    obsidian_rx: Option<Receiver<VaultScan>>,
+    obsidian_status: Option<String>,
+    obsidian_vault_input: String,
+    /// Summary topics per session, for topic nodes (empty while they're off)
+    session_topics: HashMap<String, Vec<String>>,
 
     // Mail network graph (agent communication)
     mail_network_state: Option<MailNetworkState>,
@@ -313,6 +779,16 @@ pub struct DashboardApp {
     // Collapsible side panels
     beads_panel_open: bool,
     mail_panel_open: bool,
+    /// Messages, beads and mail in time order, rebuilt per load
+    activity_feed: Vec<FeedEntry>,
+    /// Playhead time the feed last scrolled to while following it
+    activity_scrolled_to: Option<f64>,
+
+    // Panel sizes (persisted)
+    sidebar_width: f32,
+    timeline_panel_height: f32,
+    beads_panel_width: f32,
+    mail_panel_width: f32,
 
     // Token histogram panel
     histogram_panel_enabled: bool,
@@ -327,6 +803,35 @@ pub struct DashboardApp {
     histogram_session_filter: Option<String>, // session_id to isolate
     session_metadata_cache: HashMap<String, (f64, usize)>,
 
+    // Session comparison (split central view)
+    /// Sessions shown side by side (None = normal graph)
+    compare_sessions: Option<[String; 2]>,
+    /// Session picked from a node menu, waiting for a second one
+    compare_pick: Option<String>,
+    /// Sessions chosen in the sidebar picker
+    compare_choice: [Option<String>; 2],
+    /// Node indices per side (all filters but the timeline), rebuilt with the visible set
+    compare_nodes: [Vec<usize>; 2],
+    /// Shared vertical pan/zoom of both sides
+    compare_pan_y: f32,
+    compare_zoom: f32,
+    /// Node under the pointer when the graph's context menu opened
+    context_menu_node: Option<String>,
+    /// Session-level graph shown instead of messages (None = message view)
+    overview: Option<OverviewState>,
+
+    // Timeline diff (window A vs the current window B)
+    diff_mode: bool,
+    /// Pinned window A; None follows the span just before B
+    diff_window_a: Option<(f64, f64)>,
+    /// Per-node classification, rebuilt with the visible set while diffing
+    graph_diff: Option<GraphDiff>,
+
+    /// Recency size factor per node (indexed like the graph's nodes), and the
+    /// scrubber time, time range and decay it was computed for
+    recency_factors: Vec<f32>,
+    recency_key: Option<(f64, f64, f64, f32)>,
+
     // Layout shaping (directed stiffness + recency centering)
     layout_shaping_enabled: bool,
 
@@ -343,12 +848,19 @@ pub struct DashboardApp {
     // hover detection, physics, counters, and temporal edge rebuilds.
     effective_visible_nodes: HashSet<String>,
     effective_visible_count: usize,
+    /// Bumped on every rebuild of the effective visible set
+    effective_visible_generation: u64,
     effective_visible_dirty: bool,
     temporal_edges_dirty: bool,
 }
 
 impl DashboardApp {
-    pub fn new(cc: &eframe::CreationContext<'_>) -> Self {
+    pub fn new(cc: &eframe::CreationContext<'_>, args: CliArgs, bundle: Option<DebugBundle>) -> Self {
+        Self::with_context(&cc.egui_ctx, args, bundle)
+    }
+
+    /// `new` without eframe's creation context, so frames can run headless
+    fn with_context(egui_ctx: &egui::Context, args: CliArgs, bundle: Option<DebugBundle>) -> Self {
         // Configure fonts - add emoji support
         // egui's default font doesn't include emoji glyphs, so we load NotoEmoji
         // as a fallback font for both Proportional and Monospace families.
@@ -370,10 +882,25 @@ impl DashboardApp {
             .entry(egui::FontFamily::Monospace)
             .or_default()
             .push("NotoEmoji".to_owned());
-        cc.egui_ctx.set_fonts(fonts);
+        egui_ctx.set_fonts(fonts);
+
+        // Load saved settings; a debug bundle brings the settings it was
+        // written with
+        let (settings, settings_recovered) = match bundle {
+            Some(ref bundle) => (bundle.settings.clone(), None),
+            None => Settings::load_recovering(),
+        };
 
-        // Load saved settings
-        let settings = Settings::load();
+        // Apply the saved UI scale before the first frame so the window doesn't flash
+        egui_ctx.set_zoom_factor(settings.ui_scale);
+        let semantic_filter_modes = settings.semantic_filter_modes.clone();
+        let project_tree_expanded: HashSet<String> = settings.project_tree_expanded.iter().cloned().collect();
+        let beads_search = locate_beads_dir(settings.beads_path.as_deref());
+        let last_view = settings.last_view.clone();
+        let beads_path_input = settings.beads_path.clone().unwrap_or_default();
+        let obsidian_vault_input = settings.obsidian_vault.clone().unwrap_or_default();
+        let pending_project_selection = (!settings.selected_projects.is_empty())
+            .then(|| settings.selected_projects.iter().cloned().collect::<HashSet<String>>());
 
         // Create layout with saved physics settings
         let mut layout = ForceLayout::default();
@@ -385,35 +912,57 @@ impl DashboardApp {
         layout.directed_stiffness = settings.directed_stiffness;
         layout.recency_centering = settings.recency_centering;
         layout.momentum = settings.momentum;
+        layout.algorithm = layout_algorithm_for(settings.view_mode);
 
         // Create graph state with saved settings
         let mut graph = GraphState::new();
         graph.physics_enabled = settings.physics_enabled;
         graph.color_mode = settings.color_mode;
+        graph.hue_assignment = settings.hue_assignment;
+        graph.hue_overrides = settings.hue_overrides.clone();
         graph.temporal_attraction_enabled = settings.temporal_attraction_enabled;
         graph.temporal_window_secs = settings.temporal_window_mins as f64 * 60.0;
         graph.max_temporal_edges = settings.max_temporal_edges;
+        graph.max_temporal_neighbors = settings.max_temporal_neighbors;
+        graph.excluded_projects = settings.excluded_projects.clone();
 
         // Try to connect to database
-        let (db, db_connected, db_error) = match DbClient::new() {
-            Ok(client) => (Some(client), true, None),
-            Err(e) => (None, false, Some(e)),
+        let (db, db_connected, db_error) = if bundle.is_some() {
+            (None, false, None)
+        } else {
+            match DbClient::new(args.db_path.as_deref()) {
+                Ok(client) => (Some(client), true, None),
+                Err(e) => (None, false, Some(e)),
+            }
         };
 
+        // Baseline snapshot for undo history
+        let initial_snapshot = settings_snapshot(&settings, &graph);
+        let onboarding_open = !settings.onboarding_completed && bundle.is_none();
+
         let mut app = Self {
             db,
             db_connected,
             db_error,
+            db_path_override: args.db_path,
+            session_filter: args.session,
             graph,
             layout,
             sidebar_tab: settings.sidebar_tab,
-            time_range_hours: settings.time_range_hours,
-            slider_hours: settings.time_range_hours,
+            // The command-line range takes precedence but isn't saved
+            time_range_hours: args.hours.unwrap_or(settings.time_range_hours),
+            slider_hours: args.hours.unwrap_or(settings.time_range_hours),
+            hours_from_cli: args.hours.is_some(),
             node_size: settings.node_size,
             show_arrows: settings.show_arrows,
             loading: false,
             timeline_enabled: settings.timeline_enabled,
             timeline_histogram_mode: false, // Default to notch view
+            timeline_zoom: None,
+            jump_input: String::new(),
+            jump_hint: None,
+            jump_flash: None,
+            timeline_minimap_grab: None,
             hover_scrubs_timeline: settings.hover_scrubs_timeline,
             sizing_preset: settings.sizing_preset,
             w_importance: settings.w_importance,
@@ -433,18 +982,68 @@ impl DashboardApp {
             project_filter: settings.project_filter,
             selected_projects: HashSet::new(),
             project_tree: None,
-            project_tree_expanded: HashSet::new(),
+            exclude_pattern_input: String::new(),
+            project_tree_expanded,
+            pending_project_selection,
+            pending_last_view: Some(last_view.clone()),
+            pending_view_string: None,
+            saved_last_view: last_view.clone(),
+            project_tree_search: String::new(),
+            project_pushdown: Vec::new(),
+            project_solo: None,
+            latest_message_time: None,
+            project_node_counts: HashMap::new(),
+            project_window_counts: HashMap::new(),
+            cost_summary: CostSummary::default(),
+            activity_stats: None,
+            memory_estimate: MemoryEstimate::default(),
+            hit_grid: None,
+            edge_frame: None,
+            hit_grid_dirty: true,
+            hit_test: HitTest::Linear,
+            node_radii: Vec::new(),
+            declutter: None,
+            community_rx: None,
+            community_focus: None,
+            similar_rx: None,
+            discovered: Discovered::default(),
+            fit_request: None,
+            content_diff: None,
+            dropped_transcripts: Vec::new(),
+            drop_status: None,
+            memory_notice: None,
+            lost_selection: None,
+            live_sessions: Vec::new(),
+            live_tails: HashSet::new(),
+            scrubber_node: None,
             available_projects: Vec::new(),
             debug_tooltip: false,
-            pan_offset: Vec2::ZERO,
-            zoom: 1.0,
+            shortcut_help_open: false,
+            palette: theme::DARK,
+            theme_file_status: None,
+            theme_file_errors: Vec::new(),
+            settings_recovered,
+            onboarding_open,
+            onboarding_env: None,
+            onboarding_receiver: None,
+            onboarding_task: None,
+            onboarding_status: None,
+            pan_offset: Vec2::new(last_view.pan[0], last_view.pan[1]),
+            zoom: last_view.zoom.clamp(0.005, 5.0),
             dragging: false,
             drag_start: None,
+            time_drag: None,
             timeline_dragging: false,
+            hover_scrub_target: None,
             last_playback_time: Instant::now(),
+            commands: CommandQueue::default(),
             last_frame: Instant::now(),
-            frame_times: Vec::with_capacity(60),
+            frame_times: FrameTimes::default(),
             fps: 0.0,
+            profiler: FrameProfiler::default(),
+            watchdog: Watchdog::start(),
+            profiler_overlay: false,
+            text_layout: TextLayoutCache::default(),
 
             // Summary panel state (point-in-time)
             summary_node_id: None,
@@ -472,6 +1071,7 @@ impl DashboardApp {
 
             // Cmd+Hover neighborhood preview
             cmd_hover_neighbors: HashSet::new(),
+            hovered_gap: None,
 
             // Floating summary window state
             summary_window_open: false,
@@ -490,8 +1090,16 @@ impl DashboardApp {
 
             // Collapsible side panels (read before settings move)
             beads_panel_open: settings.beads_panel_open,
+            activity_feed: Vec::new(),
+            activity_scrolled_to: None,
             mail_panel_open: settings.mail_panel_open,
 
+            // Panel sizes
+            sidebar_width: settings.sidebar_width,
+            timeline_panel_height: settings.timeline_panel_height,
+            beads_panel_width: settings.beads_panel_width,
+            mail_panel_width: settings.mail_panel_width,
+
             // Token histogram panel
             histogram_panel_enabled: settings.histogram_panel_enabled,
             histogram_split_ratio: settings.histogram_split_ratio,
@@ -505,20 +1113,76 @@ impl DashboardApp {
             histogram_session_filter: None,
             session_metadata_cache: HashMap::new(),
 
+            // Session comparison
+            compare_sessions: None,
+            compare_pick: None,
+            compare_choice: [None, None],
+            compare_nodes: [Vec::new(), Vec::new()],
+            compare_pan_y: 0.0,
+            compare_zoom: 1.0,
+            context_menu_node: None,
+            overview: None,
+
+            // Timeline diff
+            diff_mode: false,
+            diff_window_a: None,
+            graph_diff: None,
+
             // Settings persistence
             settings,
             settings_dirty: false,
             last_settings_save: Instant::now(),
 
+            // Undo/redo history
+            settings_history: History::new(initial_snapshot, SETTINGS_HISTORY_LIMIT),
+            settings_history_pending: false,
+
             // Preset management
             preset_name_input: String::new(),
+            preset_include_analysis: true,
+            token_histogram: HistogramState::default(),
+            token_histogram_key: None,
+            token_brush: None,
+            token_csv_status: None,
+            stats_json_status: None,
+            bundle_anonymize: true,
+            bundle_status: None,
+            bundle_source: args.load_bundle.clone(),
+            snapshots: SnapshotHistory::default(),
+            snapshot_name_input: String::new(),
+            snapshot_status: None,
+            favorites: Vec::new(),
+            favorite_ids: HashSet::new(),
+            favorite_note_edit: None,
+            favorites_status: None,
+            session_tags: SessionTags::default(),
+            tag_filter: TagFilter::default(),
+            tag_input: String::new(),
+            tags_error: None,
+            pending_focus: None,
+            ui_scale_drag: None,
+            ui_scale_dirty: false,
             selected_preset_index: None,
+            preset_renaming: None,
+            active_saved_view: None,
+            saved_view_editing: None,
+            preset_delete_confirm: None,
+            preset_flash: None,
+            view_string_input: String::new(),
+            view_string_status: None,
+            missing_preset_warnings: HashSet::new(),
 
             // Semantic filters
             semantic_filters: Vec::new(),
-            semantic_filter_modes: HashMap::new(),
+            semantic_filter_modes,
             new_filter_input: String::new(),
             semantic_filter_loading: false,
+            write_queue: WriteQueue::load(),
+            write_queue_open: false,
+            tooltip_config_open: false,
+            edge_styles_open: false,
+            hue_picker: None,
+            presettle_pending: false,
             categorizing_filter_id: None,
             categorization_receiver: None,
             categorization_progress_rx: None,
@@ -526,6 +1190,8 @@ impl DashboardApp {
             categorization_done_flag: None,
             semantic_visible_ids: None,
             semantic_filter_pending: false,
+            local_rule_matches: HashMap::new(),
+            rule_errors: HashMap::new(),
             semantic_filter_rx: None,
             expanded_filter_ids: HashSet::new(),
 
@@ -546,8 +1212,27 @@ impl DashboardApp {
 
             // Refresh & sync state
             last_synced: None,
+            refresh_rx: None,
+            refresh_anchor: Instant::now(),
+            reload_requested_at: None,
+            load_rx: None,
+            load_progress: None,
+            load_stats_rx: None,
+            refresh_failures: 0,
+            activity_watch: ActivityWatch::default(),
+            notifier: Notifier::default(),
+            new_activity: Vec::new(),
             beads_last_check: Instant::now(),
             beads_last_mtime: None,
+            beads_closed_limit: BEADS_CLOSED_PAGE,
+            beads_search,
+            beads_path_input,
+            obsidian_notes: GraphData::default(),
+            obsidian_note_paths: HashMap::new(),
+            obsidian_rx: None,
+            obsidian_status: None,
+            obsidian_vault_input,
+            session_topics: HashMap::new(),
 
             // Mail network graph
             mail_network_state: None,
@@ -568,20 +1253,83 @@ impl DashboardApp {
             // Effective visible set
             effective_visible_nodes: HashSet::new(),
             effective_visible_count: 0,
+            effective_visible_generation: 0,
+            recency_factors: Vec::new(),
+            recency_key: None,
             effective_visible_dirty: true,
             temporal_edges_dirty: false,
         };
 
-        // Load initial data if connected
-        if app.db_connected {
-            app.load_graph();
+        // Pick up a user theme file, if any
+        app.reload_theme_file();
+
+        // Apply the startup (or active view's default) preset before the first load
+        // so physics parameters are right from the start. A bundle's settings
+        // already are what was on screen.
+        if bundle.is_some() {
+            app.settings.apply_active_view_settings();
+            app.sync_ui_from_settings();
+        } else {
+            app.apply_startup_preset();
+        }
+        app.settings_history = History::new(
+            settings_snapshot(&app.settings, &app.graph),
+            SETTINGS_HISTORY_LIMIT,
+        );
+
+        // Load initial data if connected; recent activity shows first
+        if let Some(bundle) = bundle {
+            app.install_bundle(bundle);
+        } else if app.db_connected {
+            app.start_background_load();
+        }
+        if app.onboarding_open {
+            app.start_environment_detection();
         }
 
         app
     }
 
+    /// Show a debug bundle's graph where it was laid out
+    fn install_bundle(&mut self, bundle: DebugBundle) {
+        let (data, positions) = bundle.graph.into_graph_data();
+        self.apply_graph_data(data, false);
+        for (id, pos) in positions {
+            if let Some(slot) = self.graph.positions.get_mut(&id) {
+                *slot = pos;
+            }
+        }
+        self.graph.velocities.values_mut().for_each(|v| *v = Vec2::ZERO);
+    }
+
+    /// Write the graph, settings and recent problems to a new directory in
+    /// the downloads (or home) directory
+    fn export_debug_bundle(&mut self) -> Result<std::path::PathBuf, String> {
+        let dir = dirs::download_dir()
+            .or_else(dirs::home_dir)
+            .ok_or_else(|| "Could not determine a directory to save to".to_string())?;
+        self.sync_settings_from_ui();
+        let active_preset = self.selected_preset_index
+            .and_then(|i| self.settings.presets.get(i))
+            .map(|p| p.name.clone());
+        let bundle = DebugBundle {
+            manifest: Manifest::new(self.bundle_anonymize, active_preset),
+            graph: BundleGraph::from_state(&self.graph, self.bundle_anonymize),
+            settings: self.settings.clone(),
+            events: BundleEvents {
+                stalls: self.watchdog.events().map(|e| e.describe()).collect(),
+                log: log_buffer::events().iter().map(|e| e.describe()).collect(),
+            },
+        };
+        let name = format!("dashboard-debug-{}", chrono::Local::now().format("%Y%m%d-%H%M%S"));
+        let path = dir.join(name);
+        bundle.write(&path)?;
+        tracing::info!("Exported debug bundle to {:?}", path);
+        Ok(path)
+    }
+
     fn reconnect_db(&mut self) {
-        match DbClient::new() {
+        match DbClient::new(self.db_path_override.as_deref()) {
             Ok(client) => {
                 self.db = Some(client);
                 self.db_connected = true;
@@ -598,11 +1346,402 @@ impl DashboardApp {
     /// Mark settings as needing to be saved
     fn mark_settings_dirty(&mut self) {
         self.settings_dirty = true;
+        self.settings_history_pending = true;
+    }
+
+    /// Record pending settings changes as one undo step.
+    /// Waits until no pointer button is held so a slider drag coalesces into a single entry.
+    fn commit_settings_history(&mut self, ctx: &egui::Context) {
+        if self.settings_history_pending && !ctx.input(|i| i.pointer.any_down()) {
+            self.record_settings_history();
+        }
+    }
+
+    /// Push the current settings onto the undo stack (no-op if unchanged)
+    fn record_settings_history(&mut self) {
+        self.settings_history_pending = false;
+        self.sync_settings_from_ui();
+        let snapshot = settings_snapshot(&self.settings, &self.graph);
+        self.settings_history.record(snapshot);
+    }
+
+    /// Revert to the previous settings snapshot
+    fn undo_settings(&mut self) {
+        // Flush any uncommitted change so it can be undone too
+        if self.settings_history_pending {
+            self.record_settings_history();
+        }
+        if let Some(snapshot) = self.settings_history.undo().cloned() {
+            self.restore_settings_snapshot(&snapshot);
+        }
+    }
+
+    /// Re-apply the next settings snapshot after an undo
+    fn redo_settings(&mut self) {
+        if let Some(snapshot) = self.settings_history.redo().cloned() {
+            self.restore_settings_snapshot(&snapshot);
+        }
+    }
+
+    /// Run a global keyboard shortcut
+    fn handle_shortcut(&mut self, action: Action) {
+        match action {
+            Action::ToggleHelp => self.shortcut_help_open = !self.shortcut_help_open,
+            Action::Cancel => self.shortcut_help_open = false,
+            Action::Undo => self.undo_settings(),
+            Action::Redo => self.redo_settings(),
+            Action::ToggleBeadsPanel => {
+                self.beads_panel_open = !self.beads_panel_open;
+                self.mark_settings_dirty();
+            }
+            Action::ToggleMailPanel => {
+                self.mail_panel_open = !self.mail_panel_open;
+                self.mark_settings_dirty();
+            }
+            Action::ToggleActivityPanel => {
+                self.settings.activity_panel_open = !self.settings.activity_panel_open;
+                self.mark_settings_dirty();
+            }
+            Action::ToggleHighContrast => {
+                self.settings.high_contrast = !self.settings.high_contrast;
+                self.mark_settings_dirty();
+            }
+            Action::FindSimilar => {
+                if let Some(id) = self.graph.selected_node.clone() {
+                    self.find_similar(&id);
+                }
+            }
+        }
+    }
+
+    /// Centered overlay listing every binding in the shortcut registry, by category.
+    /// Escape or a click outside closes it.
+    fn render_shortcut_help(&mut self, ctx: &egui::Context) {
+        if !self.shortcut_help_open {
+            return;
+        }
+        let frame = egui::Frame::window(&ctx.style()).fill(self.palette.bg.panel.gamma_multiply(0.92));
+        let response = egui::Window::new("Keyboard shortcuts")
+            .anchor(egui::Align2::CENTER_CENTER, Vec2::ZERO)
+            .collapsible(false)
+            .resizable(false)
+            .frame(frame)
+            .show(ctx, |ui| {
+                for category in Category::all() {
+                    let entries: Vec<&Shortcut> = SHORTCUTS.iter().filter(|s| s.category == *category).collect();
+                    if entries.is_empty() {
+                        continue;
+                    }
+                    ui.add_space(4.0);
+                    ui.label(egui::RichText::new(category.label()).strong());
+                    egui::Grid::new(("shortcut_help", category.label()))
+                        .num_columns(2)
+                        .min_col_width(140.0)
+                        .show(ui, |ui| {
+                            for shortcut in entries {
+                                ui.label(egui::RichText::new(shortcuts::trigger_label(ctx, &shortcut.trigger)).monospace());
+                                ui.label(shortcut.description);
+                                ui.end_row();
+                            }
+                        });
+                }
+            });
+
+        let clicked_outside = ctx.input(|i| {
+            i.pointer.any_click()
+                && i.pointer.interact_pos().is_some_and(|pos| {
+                    response.as_ref().is_some_and(|r| !r.response.rect.contains(pos))
+                })
+        });
+        if clicked_outside {
+            self.shortcut_help_open = false;
+        }
+    }
+
+    /// Look for session data on a worker thread for the first-run overlay
+    fn start_environment_detection(&mut self) {
+        let db_path = crate::db::db_path(self.db_path_override.as_deref());
+        let (tx, rx) = mpsc::channel();
+        std::thread::spawn(move || {
+            let _ = tx.send(setup::detect_environment(&db_path, &crate::api::api_base(), &setup::projects_dir()));
+        });
+        self.onboarding_env = None;
+        self.onboarding_receiver = Some(rx);
+    }
+
+    /// Run the native ingest into the dashboard database on a worker thread
+    fn start_onboarding_ingest(&mut self) {
+        let db_path = crate::db::db_path(self.db_path_override.as_deref());
+        let (tx, rx) = mpsc::channel();
+        std::thread::spawn(move || {
+            let _ = tx.send(setup::run_ingest(&db_path));
+        });
+        self.onboarding_status = Some(("Importing transcripts…".to_string(), false));
+        self.onboarding_task = Some(rx);
+    }
+
+    fn start_onboarding_backend(&mut self) {
+        self.onboarding_status = Some(match setup::start_backend(&crate::api::api_base()) {
+            Ok(()) => ("Starting the backend; check again in a few seconds".to_string(), false),
+            Err(e) => (e, true),
+        });
+    }
+
+    /// Close the first-run overlay for good
+    fn finish_onboarding(&mut self) {
+        self.onboarding_open = false;
+        self.settings.onboarding_completed = true;
+        self.mark_settings_dirty();
+    }
+
+    /// First-run overlay: what `setup::detect_environment` found and the next
+    /// step for it. Shown until skipped or a step hands off to the graph.
+    fn render_onboarding(&mut self, ctx: &egui::Context) {
+        if !self.onboarding_open {
+            return;
+        }
+        let poll = std::time::Duration::from_millis(100);
+        if let Some(ref rx) = self.onboarding_receiver {
+            match rx.try_recv() {
+                Ok(env) => {
+                    self.onboarding_env = Some(env);
+                    self.onboarding_receiver = None;
+                }
+                Err(mpsc::TryRecvError::Empty) => ctx.request_repaint_after(poll),
+                Err(mpsc::TryRecvError::Disconnected) => self.onboarding_receiver = None,
+            }
+        }
+        if let Some(ref rx) = self.onboarding_task {
+            match rx.try_recv() {
+                Ok(result) => {
+                    self.onboarding_task = None;
+                    self.onboarding_status = Some(match result {
+                        Ok(summary) => (format!("Import finished. {}", summary), false),
+                        Err(e) => (e, true),
+                    });
+                    self.commands.push(AppCommand::Reload);
+                    self.start_environment_detection();
+                }
+                Err(mpsc::TryRecvError::Empty) => ctx.request_repaint_after(poll),
+                Err(mpsc::TryRecvError::Disconnected) => self.onboarding_task = None,
+            }
+        }
+
+        let mut done = false;
+        egui::Window::new("Getting started")
+            .anchor(egui::Align2::CENTER_CENTER, Vec2::ZERO)
+            .collapsible(false)
+            .resizable(false)
+            .show(ctx, |ui| {
+                ui.set_max_width(440.0);
+                match self.onboarding_env.clone() {
+                    None => {
+                        ui.horizontal(|ui| {
+                            ui.spinner();
+                            ui.label("Looking for session data…");
+                        });
+                    }
+                    Some(Environment::DatabaseReady { path, messages }) => {
+                        ui.label(format!("Found {} messages in {}.", format_count(messages), path));
+                        if ui.button("Open the graph").clicked() {
+                            if self.graph.data.nodes.is_empty() {
+                                self.commands.push(AppCommand::Reload);
+                            }
+                            done = true;
+                        }
+                    }
+                    Some(Environment::ApiAvailable { url }) => {
+                        ui.label(format!("The database is empty, but the backend at {} is running and can import your sessions.", url));
+                        if ui.add_enabled(!self.ingest_loading, egui::Button::new("Import sessions")).clicked() {
+                            self.start_ingest();
+                            done = true;
+                        }
+                    }
+                    Some(Environment::TranscriptsOnly { dir, files }) => {
+                        ui.label(format!(
+                            "Found {} transcript{} in {}, but none are imported yet.",
+                            format_count(files as i64),
+                            if files == 1 { "" } else { "s" },
+                            dir.display(),
+                        ));
+                        ui.horizontal(|ui| {
+                            let running = self.onboarding_task.is_some();
+                            if ui.add_enabled(!running, egui::Button::new("Run ingest"))
+                                .on_hover_text("Import them with ingest.py into the dashboard database")
+                                .clicked()
+                            {
+                                self.start_onboarding_ingest();
+                            }
+                            if running {
+                                ui.spinner();
+                            }
+                            if ui.button("Start backend").on_hover_text("Run the Python API and import through it").clicked() {
+                                self.start_onboarding_backend();
+                            }
+                        });
+                    }
+                    Some(Environment::NothingFound { url, dir }) => {
+                        ui.label(format!(
+                            "No sessions yet: the database is empty, nothing answers at {}, and {} has no transcripts.",
+                            url,
+                            dir.display(),
+                        ));
+                        ui.label(
+                            egui::RichText::new("Use Claude Code for a while, or point API_BASE_URL at a running backend.")
+                                .small()
+                                .color(self.palette.text.muted),
+                        );
+                        if ui.button("Start backend").clicked() {
+                            self.start_onboarding_backend();
+                        }
+                    }
+                }
+                if let Some((ref message, is_error)) = self.onboarding_status {
+                    let color = if is_error { self.palette.accent.red } else { self.palette.text.secondary };
+                    ui.label(egui::RichText::new(message).small().color(color));
+                }
+                ui.separator();
+                ui.horizontal(|ui| {
+                    if ui.add_enabled(self.onboarding_receiver.is_none(), egui::Button::new("Check again")).clicked() {
+                        self.start_environment_detection();
+                    }
+                    if ui.button("Skip").clicked() {
+                        done = true;
+                    }
+                });
+            });
+        if done {
+            self.finish_onboarding();
+        }
+    }
+
+    /// Full markdown of the selected Obsidian note. Closing it clears the selection.
+    fn render_note_window(&mut self, ctx: &egui::Context) {
+        let Some(node) = self.graph.selected_node.as_ref()
+            .and_then(|id| self.graph.get_node(id))
+            .filter(|n| n.role == Role::Obsidian)
+        else {
+            return;
+        };
+        let title = self.obsidian_note_paths.get(&node.id).unwrap_or(&node.id).clone();
+        let body = node.full_content.clone().unwrap_or_else(|| node.content_preview.clone());
+
+        let mut open = true;
+        egui::Window::new(format!("Note: {}", title))
+            .id(egui::Id::new("obsidian_note"))
+            .open(&mut open)
+            .default_width(420.0)
+            .default_height(360.0)
+            .show(ctx, |ui| {
+                egui::ScrollArea::vertical().show(ui, |ui| {
+                    self.render_markdown(ui, &body);
+                });
+            });
+        if !open {
+            self.graph.selected_node = None;
+        }
+    }
+
+    /// Added and removed lines of a content diff, with copy buttons for each side
+    fn render_content_diff(&mut self, ctx: &egui::Context) {
+        let Some(ref state) = self.content_diff else {
+            return;
+        };
+        let mut open = true;
+        egui::Window::new("Diff contents")
+            .id(egui::Id::new("content_diff"))
+            .open(&mut open)
+            .default_width(560.0)
+            .default_height(420.0)
+            .show(ctx, |ui| match state {
+                ContentDiffState::Running(_) => {
+                    ui.horizontal(|ui| {
+                        ui.spinner();
+                        ui.label("Diffing…");
+                    });
+                    ctx.request_repaint_after(REDUCED_MOTION_REPAINT);
+                }
+                ContentDiffState::Failed(e) => {
+                    ui.colored_label(theme::state::ERROR, format!("Diff failed: {}", e));
+                }
+                ContentDiffState::Done(content) => {
+                    ui.horizontal(|ui| {
+                        ui.label(egui::RichText::new(content.diff.summary()).strong());
+                        for (side, label) in ["Copy first", "Copy second"].into_iter().enumerate() {
+                            let hover = self.graph.get_node(&content.ids[side])
+                                .map(|n| format!("{} ({})", n.content_preview, n.role.label()))
+                                .unwrap_or_else(|| content.ids[side].clone());
+                            if ui.small_button(label).on_hover_text(truncate(&hover, 80)).clicked() {
+                                ui.output_mut(|o| o.copied_text = content.texts[side].clone());
+                            }
+                        }
+                    });
+                    ui.separator();
+                    egui::ScrollArea::vertical().auto_shrink([false, false]).show(ui, |ui| {
+                        for line in &content.diff.lines {
+                            let (prefix, color) = match line.change {
+                                LineChange::Same => (' ', self.palette.text.muted),
+                                LineChange::Added => ('+', theme::state::SUCCESS),
+                                LineChange::Removed => ('−', theme::state::ERROR),
+                            };
+                            ui.label(egui::RichText::new(format!("{} {}", prefix, line.text)).monospace().small().color(color));
+                        }
+                    });
+                }
+            });
+        if !open {
+            self.content_diff = None;
+        }
+    }
+
+    /// Light markdown rendering: headings, bullets, fenced code and paragraphs
+    fn render_markdown(&self, ui: &mut egui::Ui, markdown: &str) {
+        let mut in_code = false;
+        for line in markdown.lines() {
+            let trimmed = line.trim_start();
+            if trimmed.starts_with("```") {
+                in_code = !in_code;
+                continue;
+            }
+            if in_code {
+                ui.label(egui::RichText::new(line).monospace().background_color(self.palette.bg.surface));
+            } else if trimmed.is_empty() {
+                ui.add_space(6.0);
+            } else if let Some(heading) = trimmed.strip_prefix('#') {
+                let level = 1 + heading.chars().take_while(|c| *c == '#').count();
+                let size = (20.0 - level as f32 * 2.0).max(13.0);
+                ui.label(egui::RichText::new(heading.trim_start_matches('#').trim()).strong().size(size));
+            } else if let Some(item) = trimmed.strip_prefix("- ").or_else(|| trimmed.strip_prefix("* ")) {
+                let indent = (line.len() - trimmed.len()) as f32 * 4.0;
+                ui.horizontal_wrapped(|ui| {
+                    ui.add_space(indent);
+                    ui.label(format!("• {}", item));
+                });
+            } else {
+                ui.label(line);
+            }
+        }
+    }
+
+    /// Apply a history snapshot without recording a new undo step
+    fn restore_settings_snapshot(&mut self, snapshot: &Preset) {
+        // Snapshots don't track the sidebar tab; stay on the current one
+        let tab = self.sidebar_tab;
+        snapshot.apply_to(&mut self.settings, &mut self.graph);
+        self.sync_ui_from_settings();
+        self.sidebar_tab = tab;
+        self.settings.sidebar_tab = tab;
+        self.recompute_bypass_edges();
+        self.effective_visible_dirty = true;
+        self.temporal_edges_dirty = true;
+        self.settings_dirty = true;
     }
 
     /// Copy current UI state to settings struct
     fn sync_settings_from_ui(&mut self) {
-        self.settings.time_range_hours = self.time_range_hours;
+        if !self.hours_from_cli {
+            self.settings.time_range_hours = self.time_range_hours;
+        }
         self.settings.node_size = self.node_size;
         self.settings.show_arrows = self.show_arrows;
         self.settings.timeline_enabled = self.timeline_enabled;
@@ -612,6 +1751,20 @@ impl DashboardApp {
         self.settings.importance_filter = self.importance_filter;
         self.settings.tool_use_filter = self.tool_use_filter;
         self.settings.project_filter = self.project_filter;
+        self.settings.selected_projects = self.persisted_project_selection();
+        let mut expanded: Vec<String> = self.project_tree_expanded.iter().cloned().collect();
+        expanded.sort();
+        self.settings.project_tree_expanded = expanded;
+        // Until the first load restores the saved view, keep it as is
+        if self.pending_last_view.is_none() {
+            self.settings.last_view = self.current_last_view();
+        }
+        self.settings.semantic_filter_modes = self
+            .semantic_filter_modes
+            .iter()
+            .filter(|(_, mode)| **mode != SemanticFilterMode::Off)
+            .map(|(&id, &mode)| (id, mode))
+            .collect();
         self.settings.sizing_preset = self.sizing_preset;
         self.settings.w_importance = self.w_importance;
         self.settings.w_tokens = self.w_tokens;
@@ -630,6 +1783,7 @@ impl DashboardApp {
         self.settings.temporal_window_mins = (self.graph.temporal_window_secs / 60.0) as f32;
         self.settings.temporal_edge_opacity = self.temporal_edge_opacity;
         self.settings.max_temporal_edges = self.graph.max_temporal_edges;
+        self.settings.max_temporal_neighbors = self.graph.max_temporal_neighbors;
         self.settings.proximity_edge_opacity = self.proximity_edge_opacity;
         self.settings.proximity_stiffness = self.proximity_stiffness;
         self.settings.proximity_delta = self.graph.score_proximity_delta;
@@ -638,9 +1792,14 @@ impl DashboardApp {
         self.settings.max_neighbors_per_node = self.graph.max_neighbors_per_node;
         self.settings.beads_panel_open = self.beads_panel_open;
         self.settings.mail_panel_open = self.mail_panel_open;
+        self.settings.sidebar_width = self.sidebar_width;
+        self.settings.timeline_panel_height = self.timeline_panel_height;
+        self.settings.beads_panel_width = self.beads_panel_width;
+        self.settings.mail_panel_width = self.mail_panel_width;
         self.settings.histogram_panel_enabled = self.histogram_panel_enabled;
         self.settings.histogram_split_ratio = self.histogram_split_ratio;
         self.settings.sidebar_tab = self.sidebar_tab;
+        self.settings.save_to_active_view_settings();
     }
 
     /// Copy settings values to UI fields (used when loading a preset)
@@ -651,6 +1810,10 @@ impl DashboardApp {
         self.timeline_enabled = self.settings.timeline_enabled;
         self.hover_scrubs_timeline = self.settings.hover_scrubs_timeline;
         self.graph.color_mode = self.settings.color_mode;
+        self.apply_hue_assignment();
+        if self.graph.collapse_tool_results != self.settings.collapse_tool_results {
+            self.graph.set_collapse_tool_results(self.settings.collapse_tool_results);
+        }
         self.graph.timeline.speed = self.settings.timeline_speed;
         self.importance_threshold = self.settings.importance_threshold;
         self.importance_filter = self.settings.importance_filter;
@@ -674,6 +1837,7 @@ impl DashboardApp {
         self.graph.temporal_window_secs = (self.settings.temporal_window_mins * 60.0) as f64;
         self.temporal_edge_opacity = self.settings.temporal_edge_opacity;
         self.graph.max_temporal_edges = self.settings.max_temporal_edges;
+        self.graph.max_temporal_neighbors = self.settings.max_temporal_neighbors;
         self.proximity_edge_opacity = self.settings.proximity_edge_opacity;
         self.proximity_stiffness = self.settings.proximity_stiffness;
         self.graph.score_proximity_delta = self.settings.proximity_delta;
@@ -687,1097 +1851,2599 @@ impl DashboardApp {
         self.sidebar_tab = self.settings.sidebar_tab;
     }
 
-    /// Save settings if dirty and enough time has passed (debounce)
+    /// Recolor the loaded graph if the hue assignment setting changed
+    fn apply_hue_assignment(&mut self) {
+        if self.graph.hue_assignment != self.settings.hue_assignment {
+            self.graph.hue_assignment = self.settings.hue_assignment;
+            self.graph.assign_hues();
+        }
+    }
+
+    /// Save settings if dirty and enough time has passed (debounce)
     fn maybe_save_settings(&mut self) {
         if self.settings_dirty && self.last_settings_save.elapsed().as_secs() >= 2 {
-            self.sync_settings_from_ui();
-            self.settings.save();
-            self.settings_dirty = false;
-            self.last_settings_save = Instant::now();
+            self.flush_settings();
         }
     }
 
-    fn load_graph(&mut self) {
-        let Some(ref db) = self.db else {
-            self.db_error = Some("Database not connected".to_string());
+    /// Sync and write settings now if anything changed, including the camera
+    /// (which moves without marking settings dirty)
+    fn flush_settings(&mut self) {
+        // A bundle's settings belong to whoever sent it
+        if self.bundle_source.is_some() {
+            self.settings_dirty = false;
             return;
-        };
-
-        self.loading = true;
-
-        match db.fetch_graph(self.time_range_hours, None) {
-            Ok(data) => {
-                // Initialize with centered bounds
-                let bounds = egui::Rect::from_center_size(
-                    Pos2::new(400.0, 300.0),
-                    Vec2::new(600.0, 400.0),
-                );
-                self.graph.load(data, bounds);
-                self.loading = false;
-                self.semantic_visible_ids = None;
-                self.effective_visible_dirty = true;
-
-                // Extract available projects from nodes
-                let projects: HashSet<String> = self.graph.data.nodes.iter()
-                    .map(|n| n.project.clone())
-                    .filter(|p| !p.is_empty())
-                    .collect();
-                let mut sorted_projects: Vec<String> = projects.into_iter().collect();
-                sorted_projects.sort();
-                self.available_projects = sorted_projects;
-                // Select all projects by default
-                self.selected_projects = self.available_projects.iter().cloned().collect();
-                // Build hierarchical tree for project filter UI
-                self.project_tree = Some(ProjectTreeNode::build(&self.available_projects));
-                self.project_tree_expanded.clear();
-
-                // Populate session metadata cache for histogram sorting
-                self.session_metadata_cache.clear();
-                for node in &self.graph.data.nodes {
-                    let entry = self.session_metadata_cache
-                        .entry(node.session_id.clone())
-                        .or_insert((f64::MAX, 0));
-                    if let Some(ts) = node.timestamp_secs() {
-                        if ts < entry.0 { entry.0 = ts; }
-                    }
-                    entry.1 += 1;
-                }
+        }
+        self.sync_settings_from_ui();
+        if !self.settings_dirty && self.settings.last_view == self.saved_last_view {
+            return;
+        }
+        if let Err(e) = self.settings.save() {
+            tracing::warn!("{}", e);
+        }
+        self.saved_last_view = self.settings.last_view.clone();
+        self.settings_dirty = false;
+        self.last_settings_save = Instant::now();
+    }
 
-                // Fetch importance stats
-                if let Ok(stats) = db.fetch_importance_stats() {
-                    self.importance_stats = Some(ImportanceStats {
-                        total_messages: stats.total_messages,
-                        scored_messages: stats.scored_messages,
-                    });
-                }
+    /// Camera, selection and timeline window as they should be persisted
+    fn current_last_view(&self) -> LastView {
+        let timeline = &self.graph.timeline;
+        let (timeline_start, timeline_end) = if self.timeline_enabled && !timeline.is_degenerate() {
+            (
+                Some(timeline.time_at_position(timeline.start_position)),
+                Some(timeline.time_at_position(timeline.position)),
+            )
+        } else {
+            (None, None)
+        };
+        LastView {
+            pan: [self.pan_offset.x, self.pan_offset.y],
+            zoom: self.zoom,
+            selected_node: self.graph.selected_node.clone(),
+            timeline_start,
+            timeline_end,
+        }
+    }
 
-                // Load semantic filters from API
-                self.load_semantic_filters();
+    /// Selected projects as persisted: sorted, empty when every project is
+    /// selected. Until a load applies a pending selection, that one is used.
+    fn persisted_project_selection(&self) -> Vec<String> {
+        let mut selected: Vec<String> = if let Some(pending) = &self.pending_project_selection {
+            pending.iter().cloned().collect()
+        } else if self.selected_projects.len() == self.available_projects.len()
+            && self.available_projects.iter().all(|p| self.selected_projects.contains(p))
+        {
+            Vec::new()
+        } else {
+            self.selected_projects.iter().cloned().collect()
+        };
+        selected.sort();
+        selected
+    }
 
-                // Load embedding stats
-                self.load_embedding_stats();
+    /// The current data selection and active preset as a saved view
+    fn current_saved_view(&self, name: String) -> SavedView {
+        SavedView {
+            name,
+            time_range_hours: self.time_range_hours,
+            projects: self.persisted_project_selection(),
+            session: self.session_filter.clone(),
+            preset: self.selected_preset_index
+                .and_then(|idx| self.settings.presets.get(idx))
+                .map(|p| p.name.clone()),
+        }
+    }
 
-                // Update last synced timestamp
-                self.last_synced = Some(Instant::now());
-            }
-            Err(e) => {
-                self.db_error = Some(e);
-                self.loading = false;
+    /// Apply a saved view: its preset first, then its project selection,
+    /// reloading when the time range or session differs from what is loaded
+    fn apply_saved_view(&mut self, idx: usize) {
+        let Some(view) = self.settings.saved_views.get(idx).cloned() else {
+            return;
+        };
+        if let Some(preset) = &view.preset {
+            self.apply_named_preset(preset);
+        }
+        let reload = (view.time_range_hours - self.time_range_hours).abs() >= 0.5
+            || view.session != self.session_filter;
+        self.pending_project_selection = None;
+        if view.projects.is_empty() {
+            self.selected_projects = self.available_projects.iter().cloned().collect();
+        } else {
+            self.selected_projects = view.projects.iter().cloned().collect();
+            // A full load resets the selection, so hand it over as pending
+            if reload {
+                self.pending_project_selection = Some(self.selected_projects.clone());
             }
         }
+        self.effective_visible_dirty = true;
+        if reload {
+            self.commands.push(AppCommand::LoadGraph { hours: view.time_range_hours, session: view.session });
+        }
+        self.active_saved_view = Some(view.name);
+        self.mark_settings_dirty();
+    }
 
-        // Recompute bypass edges after data load (outside match to avoid borrow conflict)
-        if !self.loading {
-            self.recompute_bypass_edges();
-            // Re-request semantic filter computation if filters are active
-            if self.has_active_semantic_filters() {
-                self.request_semantic_filter_compute();
+    /// Restore the previous session's selection and timeline window onto freshly loaded data
+    fn restore_last_view(&mut self, view: LastView) {
+        // Saved before ids were namespaced, a selection may be a bare row id
+        self.graph.selected_node = view.selected_node
+            .map(ids::normalize)
+            .filter(|id| self.graph.get_node(id).is_some());
+        if let (Some(start), Some(end)) = (view.timeline_start, view.timeline_end) {
+            if self.timeline_enabled && !self.graph.timeline.is_degenerate() {
+                let timeline = &mut self.graph.timeline;
+                timeline.start_position = timeline.position_at_time(start).clamp(0.0, 1.0);
+                timeline.position = timeline.position_at_time(end).clamp(timeline.start_position, 1.0);
+                self.graph.update_visible_nodes();
             }
         }
     }
 
-    /// Check if .beads/ directory has changed since last check
-    /// Returns true if changes detected and we should reload
-    fn check_beads_changed(&mut self) -> bool {
-        // Only check if auto-refresh is enabled
-        if !self.settings.auto_refresh_enabled {
-            return false;
+    /// Apply view state that waited for a full load to finish: the previous
+    /// session's last view, then a pasted view string
+    fn restore_pending_views(&mut self) {
+        if let Some(view) = self.pending_last_view.take() {
+            self.restore_last_view(view);
         }
-
-        // Check if enough time has passed since last check
-        let now = Instant::now();
-        let interval = std::time::Duration::from_secs_f32(self.settings.auto_refresh_interval_secs);
-        if now.duration_since(self.beads_last_check) < interval {
-            return false;
+        if let Some(pending) = self.pending_view_string.take() {
+            self.restore_view_string(pending);
         }
-        self.beads_last_check = now;
+    }
 
-        // Try to get the modification time of the .beads/ directory
-        // We look for a common file like the redirect or any files in the directory
-        let beads_path = std::path::Path::new(".beads");
-        if !beads_path.exists() {
-            return false;
-        }
+    /// Show every loaded project but the view string's hidden ones, then its
+    /// selection and timeline window
+    fn restore_view_string(&mut self, pending: PendingViewString) {
+        self.selected_projects = self.available_projects.iter()
+            .filter(|p| !pending.hidden_projects.contains(*p))
+            .cloned()
+            .collect();
+        self.restore_last_view(pending.view);
+        self.effective_visible_dirty = true;
+    }
 
-        // Get the latest modification time from any file in .beads/
-        let current_mtime = match std::fs::read_dir(beads_path) {
-            Ok(entries) => {
-                entries
-                    .filter_map(|e| e.ok())
-                    .filter_map(|e| e.metadata().ok())
-                    .filter_map(|m| m.modified().ok())
-                    .max()
-            }
-            Err(_) => None,
+    /// Reload far enough back to include the messages behind a gap edge
+    fn widen_range_for_gap(&mut self, edge_index: usize) {
+        let Some(edge) = self.graph.data.edges.get(edge_index) else {
+            return;
         };
-
-        // If we can't get mtime, fall back to directory mtime
-        let current_mtime = current_mtime.or_else(|| {
-            std::fs::metadata(beads_path)
-                .ok()
-                .and_then(|m| m.modified().ok())
-        });
-
-        // Compare with previous
-        let changed = match (current_mtime, self.beads_last_mtime) {
-            (Some(current), Some(previous)) => current > previous,
-            (Some(_), None) => true, // First check, consider changed to trigger initial state
-            _ => false,
+        let Some(ref db) = self.db else {
+            return;
         };
-
-        // Update stored mtime
-        self.beads_last_mtime = current_mtime;
-
-        changed
-    }
-
-    /// Load mail network data from API
-    fn load_mail_network(&mut self) {
-        self.mail_network_loading = true;
-        self.mail_network_error = None;
-
-        let api = ApiClient::new();
-        match api.fetch_mail_network() {
-            Ok(data) => {
-                // Initialize state with positions in a circle
-                let center = Pos2::new(125.0, 100.0);  // Center of mini-panel
-                let radius = 60.0;
-                self.mail_network_state = Some(MailNetworkState::new(data, center, radius));
-                self.mail_network_loading = false;
-            }
+        let start = match db.fetch_gap_start(&edge.source, &edge.target) {
+            Ok(start) => start.as_deref().and_then(parse_iso_timestamp),
             Err(e) => {
-                self.mail_network_error = Some(e);
-                self.mail_network_loading = false;
+                self.db_error = Some(e);
+                return;
             }
+        };
+        if let Some(start) = start {
+            self.widen_range_to(start);
         }
     }
 
-    fn update_fps(&mut self) {
-        let now = Instant::now();
-        let frame_time = now.duration_since(self.last_frame).as_secs_f32();
-        self.last_frame = now;
-
-        self.frame_times.push(frame_time);
-        if self.frame_times.len() > 60 {
-            self.frame_times.remove(0);
+    /// Reload far enough back to include messages from `start` (epoch seconds).
+    /// False when the range already reaches that far.
+    fn widen_range_to(&mut self, start: f64) -> bool {
+        // An hour of slack so the oldest missing message isn't right on the edge
+        let hours = (((unix_now() - start) / 3600.0).ceil() as f32 + 1.0).min(MAX_RANGE_HOURS);
+        if hours > self.time_range_hours {
+            self.time_range_hours = hours;
+            self.slider_hours = hours;
+            self.hours_from_cli = false;
+            self.schedule_reload();
+            self.mark_settings_dirty();
+            return true;
         }
+        false
+    }
 
-        if !self.frame_times.is_empty() {
-            let avg_frame_time: f32 = self.frame_times.iter().sum::<f32>() / self.frame_times.len() as f32;
-            self.fps = 1.0 / avg_frame_time;
+    fn load_favorites(&mut self) {
+        let Some(ref db) = self.db else {
+            return;
+        };
+        match db.fetch_favorites() {
+            Ok(favorites) => {
+                self.favorite_ids = favorites.iter().map(Favorite::node_id).collect();
+                self.favorites = favorites;
+            }
+            Err(e) => self.favorites_status = Some((e, true)),
         }
     }
 
-    /// Find the node closest to the current scrubber position
-    fn find_node_at_scrubber(&self) -> Option<crate::graph::types::GraphNode> {
-        if self.graph.data.nodes.is_empty() {
-            return None;
+    /// Star or unstar a stored message. The star shows or goes at once; the
+    /// list is re-read to pick up the new row.
+    fn set_favorite(&mut self, node_id: &str, favorite: bool) {
+        let Some(ref db) = self.db else {
+            return;
+        };
+        if let Err(e) = db.set_favorite(node_id, favorite) {
+            self.favorites_status = Some((e, true));
+            return;
+        }
+        if favorite {
+            self.favorite_ids.insert(node_id.to_string());
+            self.load_favorites();
+        } else {
+            self.favorite_ids.remove(node_id);
+            self.favorites.retain(|f| f.node_id() != node_id);
         }
+        self.favorites_status = None;
+    }
 
-        let scrubber_time = self.graph.timeline.time_at_position(self.graph.timeline.position);
-        let mut closest_node: Option<&crate::graph::types::GraphNode> = None;
-        let mut min_distance = f64::MAX;
+    /// Select and frame a favorite, widening the range and reloading first if
+    /// it's older than the current window
+    fn go_to_favorite(&mut self, node_id: String, timestamp: Option<f64>) {
+        self.pending_focus = None;
+        if self.graph.node_index.contains_key(&node_id) {
+            self.graph.selected_node = Some(node_id.clone());
+            self.fit_request = Some(vec![node_id]);
+            self.favorites_status = None;
+            return;
+        }
+        if timestamp.is_some_and(|t| self.widen_range_to(t)) {
+            self.pending_focus = Some(node_id);
+            self.favorites_status = Some(("Widening the range to load it…".to_string(), false));
+        } else {
+            self.favorites_status = Some(("Not in this load: its project or session is filtered out".to_string(), true));
+        }
+    }
 
-        for node in &self.graph.data.nodes {
-            if let Some(node_time) = node.timestamp_secs() {
-                let distance = (scrubber_time - node_time).abs();
-                if distance < min_distance {
-                    min_distance = distance;
-                    closest_node = Some(node);
-                }
-            }
+    /// Shared setup for a full load; false if there is no database
+    fn begin_load(&mut self) -> bool {
+        self.apply_memory_guardrails();
+        if self.db.is_none() {
+            self.db_error = Some("Database not connected".to_string());
+            return false;
         }
 
-        closest_node.cloned()
+        self.loading = true;
+        // A manual reload resets the auto-refresh timer and any backoff, and
+        // supersedes anything pending
+        self.refresh_anchor = Instant::now();
+        self.refresh_failures = 0;
+        self.refresh_rx = None;
+        self.reload_requested_at = None;
+        // Dropping the receiver stops a chunked load at its next chunk
+        self.load_rx = None;
+        self.load_progress = None;
+        true
     }
 
-    /// Collect node IDs into inactive and filtered sets based on active filters.
-    /// First-match-wins order: tool_use → importance → project.
-    fn collect_filter_sets(&self) -> (HashSet<String>, HashSet<String>) {
-        let mut inactive = HashSet::new();
-        let mut filtered = HashSet::new();
-
-        for node in &self.graph.data.nodes {
-            // Tool use filter
-            if self.tool_use_filter.is_active() && node.has_tool_usage {
-                match self.tool_use_filter {
-                    FilterMode::Inactive => { inactive.insert(node.id.clone()); }
-                    FilterMode::Filtered => { filtered.insert(node.id.clone()); }
-                    FilterMode::Off => {}
-                }
-                continue;
-            }
-            // Importance filter
-            if self.importance_filter.is_active() {
-                if let Some(score) = node.importance_score {
-                    if score < self.importance_threshold {
-                        match self.importance_filter {
-                            FilterMode::Inactive => { inactive.insert(node.id.clone()); }
-                            FilterMode::Filtered => { filtered.insert(node.id.clone()); }
-                            FilterMode::Off => {}
-                        }
-                        continue;
-                    }
-                }
-            }
-            // Project filter
-            if self.project_filter.is_active() && !self.selected_projects.contains(&node.project) {
-                match self.project_filter {
-                    FilterMode::Inactive => { inactive.insert(node.id.clone()); }
-                    FilterMode::Filtered => { filtered.insert(node.id.clone()); }
-                    FilterMode::Off => {}
-                }
-            }
-        }
+    fn install_loaded_graph(&mut self, mut data: GraphData) {
+        self.load_session_topics();
+        self.load_favorites();
+        self.load_session_tags();
+        self.attach_derived_nodes(&mut data);
+        self.apply_graph_data(data, false);
+        self.start_obsidian_scan();
+    }
 
-        (inactive, filtered)
+    /// Ask for a reload after the controls settle. Repeated requests inside
+    /// `RELOAD_DEBOUNCE` coalesce into one load.
+    fn schedule_reload(&mut self) {
+        self.reload_requested_at = Some(Instant::now());
     }
 
-    /// Compute bypass edges that bridge over inactive nodes.
-    /// Walks session chains (non-temporal, non-similarity edges),
-    /// bridges over inactive_ids, stops at filtered_ids.
-    fn compute_bypass_edges(&self, inactive: &HashSet<String>, filtered: &HashSet<String>) -> Vec<GraphEdge> {
-        if inactive.is_empty() {
-            return Vec::new();
+    /// Start a requested reload once the controls have been quiet long enough
+    fn poll_scheduled_reload(&mut self, ctx: &egui::Context) {
+        let Some(requested) = self.reload_requested_at else {
+            return;
+        };
+        let waited = requested.elapsed();
+        if waited < RELOAD_DEBOUNCE {
+            ctx.request_repaint_after(RELOAD_DEBOUNCE - waited);
+            return;
         }
+        // Cleared here too: begin_load bails out before clearing it when
+        // there is no database, and the request would fire every frame
+        self.reload_requested_at = None;
+        self.start_background_load();
+    }
 
-        // Build per-node successor map from session edges (non-temporal only)
-        let mut next: HashMap<&str, &str> = HashMap::new();
-        for edge in &self.graph.data.edges {
-            if !edge.is_temporal && !edge.is_similarity {
-                next.insert(&edge.source, &edge.target);
-            }
+    /// Full load with the fetch on a background thread, newest messages first
+    /// in chunks. `poll_background_load` installs the first chunk from scratch
+    /// and merges the rest in as they arrive.
+    fn start_background_load(&mut self) {
+        if !self.begin_load() {
+            return;
         }
+        let Some(db) = self.db.clone() else {
+            return;
+        };
+        let hours = self.time_range_hours;
+        let session = self.session_filter.clone();
+        let projects = self.project_pushdown.clone();
+        let excluded = self.settings.excluded_projects.clone();
+        let content_lazy = self.settings.content_lazy;
 
-        let mut bypass = Vec::new();
-        let mut seen_pairs: HashSet<(String, String)> = HashSet::new();
+        let (tx, rx) = mpsc::channel();
+        std::thread::spawn(move || {
+            let result = db.fetch_graph_progressive(
+                hours,
+                session.as_deref(),
+                &projects,
+                &excluded,
+                content_lazy,
+                db::LOAD_CHUNK_ROWS,
+                // A dropped receiver means the load was cancelled or superseded
+                |chunk| tx.send(Ok(chunk)).is_ok(),
+            );
+            if let Err(e) = result {
+                let _ = tx.send(Err(e));
+            }
+        });
+        self.load_rx = Some(rx);
+    }
 
-        for node in &self.graph.data.nodes {
-            if inactive.contains(&node.id) || filtered.contains(&node.id) {
-                continue; // Skip hidden nodes as sources
-            }
-
-            // Walk the chain from this node
-            let mut cursor = node.id.as_str();
-            while let Some(&successor) = next.get(cursor) {
-                if filtered.contains(successor) {
-                    break; // Stop at truly removed nodes
-                }
-                if !inactive.contains(successor) {
-                    // Successor is visible — if we skipped any nodes, create bypass edge
-                    if cursor != node.id.as_str() {
-                        let pair = (node.id.clone(), successor.to_string());
-                        if seen_pairs.insert(pair) {
-                            bypass.push(GraphEdge {
-                                source: node.id.clone(),
-                                target: successor.to_string(),
-                                session_id: node.session_id.clone(),
-                                timestamp: None,
-                                is_obsidian: false,
-                                is_topic: false,
-                                is_similarity: false,
-                                is_temporal: false,
-                                similarity: None,
-                                query_index: None,
-                            });
-                        }
-                    }
+    fn poll_background_load(&mut self) {
+        let Some(ref rx) = self.load_rx else {
+            return;
+        };
+        // Everything waiting goes in at once
+        let mut latest: Option<LoadChunk> = None;
+        let mut finished = false;
+        loop {
+            match rx.try_recv() {
+                Ok(Ok(chunk)) => match latest {
+                    Some(ref mut waiting) => waiting.append(chunk),
+                    None => latest = Some(chunk),
+                },
+                Ok(Err(e)) => {
+                    self.db_error = Some(e);
+                    finished = true;
+                    break;
+                }
+                Err(mpsc::TryRecvError::Empty) => break,
+                Err(mpsc::TryRecvError::Disconnected) => {
+                    finished = true;
                     break;
                 }
-                cursor = successor;
             }
         }
-
-        bypass
+        if let Some(chunk) = latest {
+            finished |= chunk.done;
+            self.apply_load_chunk(chunk);
+        }
+        if finished {
+            self.load_rx = None;
+            self.load_progress = None;
+            self.loading = false;
+        }
     }
 
-    /// Convenience: collect filter sets and recompute bypass edges
-    fn recompute_bypass_edges(&mut self) {
-        let (inactive, filtered) = self.collect_filter_sets();
-        self.bypass_edges = self.compute_bypass_edges(&inactive, &filtered);
-    }
+    /// Install one chunk of a progressive load. Later chunks merge in and keep
+    /// the scrubber on the same times; a handle at either end stays there so
+    /// the window follows the history filling in.
+    fn apply_load_chunk(&mut self, chunk: LoadChunk) {
+        let first = self.load_progress.is_none();
+        self.load_progress = Some((chunk.loaded, chunk.expected));
+        // Tells apply_graph_data whether more chunks follow
+        self.loading = !chunk.done;
+        if first {
+            self.install_loaded_graph(chunk.data);
+        } else {
+            let timeline = &self.graph.timeline;
+            let start = (timeline.start_position > 0.0).then(|| timeline.time_at_position(timeline.start_position));
+            let end = (timeline.position < 1.0).then(|| timeline.time_at_position(timeline.position));
+
+            // Chunks carry only their own messages. A discovered neighbour may
+            // already stand in for one of them.
+            let mut data = self.graph.data.clone();
+            data.nodes.extend(chunk.data.nodes.into_iter().filter(|n| !self.graph.node_index.contains_key(&n.id)));
+            data.edges.extend(chunk.data.edges);
+            self.attach_derived_nodes(&mut data);
+            if chunk.done {
+                self.apply_graph_data(data, true);
+            } else {
+                self.merge_partial_load(data);
+            }
 
-    /// Returns true if a node is hidden by any active filter (tool_use, importance, project).
-    /// Does NOT check timeline or semantic filters (those are handled separately).
-    fn is_node_hidden(&self, node: &crate::graph::types::GraphNode) -> bool {
-        if self.tool_use_filter.is_active() && node.has_tool_usage {
-            return true;
-        }
-        if self.importance_filter.is_active() {
-            if let Some(score) = node.importance_score {
-                if score < self.importance_threshold {
-                    return true;
+            if start.is_some() || end.is_some() {
+                let timeline = &mut self.graph.timeline;
+                if let Some(start) = start {
+                    timeline.start_position = timeline.position_at_time(start).clamp(0.0, 1.0);
+                }
+                if let Some(end) = end {
+                    timeline.position = timeline.position_at_time(end).clamp(timeline.start_position, 1.0);
                 }
+                self.graph.update_visible_items();
             }
         }
-        if self.project_filter.is_active() && !self.selected_projects.contains(&node.project) {
-            return true;
+        if chunk.done {
+            self.restore_pending_views();
         }
-        false
+        // apply_graph_data marks the load finished; more chunks may follow
+        self.loading = !chunk.done;
     }
 
-    /// Check if any semantic filters are active (not Off)
-    fn has_active_semantic_filters(&self) -> bool {
-        self.semantic_filter_modes.values()
-            .any(|mode| *mode != SemanticFilterMode::Off)
+    /// Merge a chunk that more will follow. Only the graph and what it shows
+    /// change; the project tree, session summaries and database stats wait for
+    /// the last chunk, which goes through `apply_graph_data`.
+    fn merge_partial_load(&mut self, data: GraphData) {
+        let fresh: HashSet<String> = data.nodes.iter()
+            .filter(|n| !n.project.is_empty() && self.available_projects.binary_search(&n.project).is_err())
+            .map(|n| n.project.clone())
+            .collect();
+        self.scrubber_node = None;
+        self.graph.merge(data, load_bounds());
+        self.effective_visible_dirty = true;
+        self.recency_key = None;
+        self.select_arrived_nodes();
+        self.presettle_pending = !theme::motion_enabled();
+        self.token_histogram.mark_dirty();
+        self.activity_stats = None;
+        // New projects show up selected, as on any incremental load
+        for project in fresh {
+            if let Err(at) = self.available_projects.binary_search(&project) {
+                self.available_projects.insert(at, project.clone());
+            }
+            self.selected_projects.insert(project);
+        }
     }
 
-    /// Build adjacency list from graph edges for BFS neighbor lookup
-    fn build_adjacency_list(&self, include_temporal: bool) -> HashMap<String, Vec<String>> {
-        build_adjacency_list(&self.graph.data.edges, include_temporal)
+    /// Note for a selected node the last load left out, with a way to get it back
+    fn render_lost_selection(&mut self, ui: &mut egui::Ui) {
+        let Some((_, timestamp)) = self.lost_selection else {
+            return;
+        };
+        // Otherwise it went for some other reason (deleted, excluded, session filter)
+        let before_range = timestamp < unix_now() - self.time_range_hours as f64 * 3600.0;
+        ui.horizontal(|ui| {
+            let note = if before_range { "Selected message is outside the range" } else { "Selected message is no longer loaded" };
+            ui.label(egui::RichText::new(note).small().color(theme::state::WARNING));
+            if before_range && ui.small_button("Widen range")
+                .on_hover_text("Reload far enough back to include it and select it again")
+                .clicked()
+            {
+                self.widen_range_to(timestamp);
+            }
+            if ui.small_button("✖").on_hover_text("Dismiss").clicked() {
+                self.lost_selection = None;
+            }
+        });
     }
 
-    /// Expand a set of nodes to include neighbors up to given depth using BFS
-    fn expand_to_neighbors(&self, seeds: &HashSet<String>, depth: usize, adj: &HashMap<String, Vec<String>>) -> HashSet<String> {
-        expand_to_neighbors(seeds, depth, adj)
+    /// Stop a chunked load, keeping the messages it has delivered
+    fn cancel_background_load(&mut self) {
+        self.load_rx = None;
+        self.load_progress = None;
+        self.loading = false;
     }
 
-    /// Fire a backend request to compute the semantic filter visible set.
-    /// The result arrives asynchronously via semantic_filter_rx.
-    fn request_semantic_filter_compute(&mut self) {
-        // Build filter_modes map: filter_id -> mode string
-        let filter_modes: HashMap<i32, String> = self.semantic_filter_modes.iter()
-            .map(|(&id, mode)| {
-                let mode_str = match mode {
-                    SemanticFilterMode::Off => "off",
-                    SemanticFilterMode::Exclude => "exclude",
-                    SemanticFilterMode::Include => "include",
-                    SemanticFilterMode::IncludePlus1 => "include_plus_1",
-                    SemanticFilterMode::IncludePlus2 => "include_plus_2",
-                };
-                (id, mode_str.to_string())
-            })
-            .collect();
-
-        let hours = self.time_range_hours;
-        self.semantic_filter_pending = true;
+    /// Project the next full load's memory use from a count preview. Over budget,
+    /// switch to content-lazy mode and, if that isn't enough, lower the temporal edge cap.
+    fn apply_memory_guardrails(&mut self) {
+        let Some(ref db) = self.db else {
+            return;
+        };
+        let preview = match db.preview_load(
+            self.time_range_hours,
+            self.session_filter.as_deref(),
+            &self.project_pushdown,
+            &self.settings.excluded_projects,
+        ) {
+            Ok(preview) => preview,
+            Err(e) => {
+                tracing::warn!("Load preview failed: {}", e);
+                return;
+            }
+        };
+        self.memory_notice = None;
+        let budget = (self.settings.memory_budget_mb.max(64.0) as f64 * 1024.0 * 1024.0) as usize;
+        let caps = TEMPORAL_EDGE_LIMITS.map(|(cap, _)| cap);
+        let current_cap = self.graph.max_temporal_edges;
+        let Some((lazy, cap)) = memory::fit_to_budget(
+            preview.messages,
+            preview.content_bytes,
+            self.settings.content_lazy,
+            current_cap,
+            &caps,
+            budget,
+        ) else {
+            return;
+        };
 
-        let (tx, rx) = mpsc::channel();
-        std::thread::spawn(move || {
-            let api = ApiClient::new();
-            let result = api.compute_visible_set(&filter_modes, hours);
-            let _ = tx.send(result);
-        });
-        self.semantic_filter_rx = Some(rx);
+        let projected = MemoryEstimate::projected(preview.messages, preview.content_bytes, self.settings.content_lazy, current_cap);
+        let mut changes = Vec::new();
+        if lazy && !self.settings.content_lazy {
+            self.settings.content_lazy = true;
+            changes.push("loading previews only".to_string());
+        }
+        if cap < current_cap {
+            self.graph.max_temporal_edges = cap;
+            self.temporal_edges_dirty = true;
+            changes.push(format!("temporal edges capped at {}", format_count(cap as i64)));
+        }
+        if !changes.is_empty() {
+            self.memory_notice = Some(format!(
+                "{} messages would use ~{}, over the {} budget: {}",
+                format_count(preview.messages as i64),
+                memory::format_bytes(projected.total()),
+                memory::format_bytes(budget),
+                changes.join(", "),
+            ));
+            self.mark_settings_dirty();
+        }
     }
 
-    /// Check if a node is a "same-project future node" (outside timeline but same project as selected/hovered)
-    fn is_same_project_future_node(&self, node: &crate::graph::types::GraphNode) -> bool {
-        // Must be outside timeline window (future)
-        if self.graph.is_node_visible(&node.id) {
-            return false;
+    /// Install freshly fetched graph data. Incremental loads keep node positions,
+    /// colors, and project selection; full loads reset them.
+    fn apply_graph_data(&mut self, data: GraphData, incremental: bool) {
+        let bounds = load_bounds();
+        let more_chunks = self.loading && self.load_progress.is_some();
+        let previous_projects: HashSet<String> = self.available_projects.iter().cloned().collect();
+        let selected_before = self.graph.selected_node.as_ref()
+            .and_then(|id| Some((id.clone(), self.graph.get_node(id)?.timestamp_secs()?)));
+        self.scrubber_node = None;
+        if incremental {
+            self.graph.merge(data, bounds);
+        } else {
+            self.graph.load(data, bounds);
+            self.semantic_visible_ids = None;
+            self.timeline_zoom = None;
+            self.community_focus = None;
+        }
+        self.loading = false;
+        self.effective_visible_dirty = true;
+        self.recency_key = None;
+        if let Some(lost) = selected_before.filter(|_| self.graph.selected_node.is_none()) {
+            self.lost_selection = Some(lost);
+        }
+        self.select_arrived_nodes();
+        // Loading turns physics back on; reduced motion settles once and stops it again
+        self.presettle_pending = !theme::motion_enabled();
+        self.token_histogram.mark_dirty();
+        self.activity_stats = None;
+        self.memory_estimate = MemoryEstimate::of_graph(&self.graph);
+        self.refresh_live_sessions();
+        self.activity_feed = self.graph.timeline.feed().collect();
+        self.activity_scrolled_to = None;
+        self.latest_message_time = None;
+        if !more_chunks {
+            self.start_load_stats();
+        }
+
+        // Extract available projects from nodes
+        let projects: HashSet<String> = self.graph.data.nodes.iter()
+            .map(|n| n.project.clone())
+            .filter(|p| !p.is_empty())
+            .collect();
+        let mut sorted_projects: Vec<String> = projects.into_iter().collect();
+        sorted_projects.sort();
+        self.available_projects = sorted_projects;
+        if incremental {
+            // Keep the user's selection (including projects that dropped out of
+            // the data, so they come back selected); newly appeared projects start selected
+            for project in &self.available_projects {
+                if !previous_projects.contains(project) {
+                    self.selected_projects.insert(project.clone());
+                }
+            }
+        } else if let Some(saved) = self.pending_project_selection.take() {
+            // Restore the persisted selection; paths missing from this load stay selected
+            self.selected_projects = saved;
+        } else {
+            // Select all projects by default
+            self.selected_projects = self.available_projects.iter().cloned().collect();
+        }
+        // Build hierarchical tree for project filter UI
+        self.project_tree = Some(ProjectTreeNode::build(&self.available_projects));
+        self.project_node_counts.clear();
+        for node in &self.graph.data.nodes {
+            *self.project_node_counts.entry(node.project.clone()).or_default() += 1;
         }
 
-        // Check if same project as selected or hovered node
-        let reference_project = self.graph.selected_node.as_ref()
-            .or(self.graph.hovered_node.as_ref())
-            .and_then(|id| self.graph.get_node(id))
-            .map(|n| &n.project);
+        // Populate session metadata cache for histogram sorting
+        self.session_metadata_cache.clear();
+        for node in &self.graph.data.nodes {
+            let entry = self.session_metadata_cache
+                .entry(node.session_id.clone())
+                .or_insert((f64::MAX, 0));
+            if let Some(ts) = node.timestamp_secs() {
+                if ts < entry.0 { entry.0 = ts; }
+            }
+            entry.1 += 1;
+        }
 
-        if let Some(ref_project) = reference_project {
-            return &node.project == ref_project;
+        if !incremental {
+            // Deliver queued filter writes first so the list below includes them
+            self.replay_queued_writes();
+            // Load semantic filters from API
+            self.load_semantic_filters();
+
+            // Load embedding stats
+            self.load_embedding_stats();
         }
 
-        false
+        // Update last synced timestamp
+        self.last_synced = Some(Instant::now());
+        self.track_new_activity(incremental);
+
+        self.recompute_bypass_edges();
+        self.evaluate_local_rules();
+        self.rebuild_overview();
+        // Re-request semantic filter computation if filters are active.
+        // A full load computes it before returning so the first frame is already filtered.
+        if self.has_active_semantic_filters() {
+            if incremental {
+                self.request_semantic_filter_compute();
+            } else {
+                self.compute_semantic_filter_now();
+            }
+        }
     }
 
-    /// Debug: Check if node is after the current playhead position
-    fn is_after_playhead(&self, node: &crate::graph::types::GraphNode) -> bool {
-        if !self.timeline_enabled {
-            return false;
+    /// Select what a load brought back: a selection an earlier load lost, or
+    /// a favorite waiting on a wider range
+    fn select_arrived_nodes(&mut self) {
+        if let Some((id, _)) = self.lost_selection.take_if(|(id, _)| self.graph.node_index.contains_key(id)) {
+            // Back in range: select it again
+            self.graph.selected_node = Some(id);
         }
-        let scrubber_time = self.graph.timeline.time_at_position(self.graph.timeline.position);
-        if let Some(node_time) = node.timestamp_secs() {
-            node_time > scrubber_time
-        } else {
-            false
+        if let Some(id) = self.pending_focus.take_if(|id| self.graph.node_index.contains_key(id)) {
+            self.graph.selected_node = Some(id.clone());
+            self.fit_request = Some(vec![id]);
+            self.favorites_status = None;
         }
     }
 
-    /// Debug: Check if node is in same session as selected node
-    fn is_same_session_as_selected(&self, node: &crate::graph::types::GraphNode) -> bool {
-        if let Some(ref selected_id) = self.graph.selected_node {
-            if let Some(selected_node) = self.graph.get_node(selected_id) {
-                return node.session_id == selected_node.session_id;
-            }
-        }
-        false
+    /// Fetch the importance scoring coverage on a background thread and, when
+    /// the load came back empty, the newest message, so the empty state can say
+    /// whether the range or the database is empty
+    fn start_load_stats(&mut self) {
+        let Some(db) = self.db.clone() else {
+            return;
+        };
+        let empty = self.graph.data.nodes.is_empty();
+        let (tx, rx) = mpsc::channel();
+        std::thread::spawn(move || {
+            let latest_message_time = if empty {
+                db.latest_message_time().ok().flatten().and_then(|ts| parse_iso_timestamp(&ts))
+            } else {
+                None
+            };
+            let importance = db.fetch_importance_stats().ok();
+            let _ = tx.send(LoadStats { importance, latest_message_time });
+        });
+        self.load_stats_rx = Some(rx);
     }
 
-    /// Debug: Check if node is in same project as selected node
-    fn is_same_project_as_selected(&self, node: &crate::graph::types::GraphNode) -> bool {
-        if let Some(ref selected_id) = self.graph.selected_node {
-            if let Some(selected_node) = self.graph.get_node(selected_id) {
-                return node.project == selected_node.project;
+    /// Poll for the stats `start_load_stats` is fetching
+    fn poll_load_stats(&mut self) {
+        let Some(ref rx) = self.load_stats_rx else {
+            return;
+        };
+        match rx.try_recv() {
+            Ok(stats) => {
+                self.load_stats_rx = None;
+                self.latest_message_time = stats.latest_message_time;
+                if let Some(stats) = stats.importance {
+                    self.importance_stats = Some(ImportanceStats {
+                        total_messages: stats.total_messages,
+                        scored_messages: stats.scored_messages,
+                    });
+                }
+            }
+            Err(mpsc::TryRecvError::Empty) => {}
+            Err(mpsc::TryRecvError::Disconnected) => {
+                self.load_stats_rx = None;
             }
         }
-        false
     }
 
-    /// Compute which nodes should participate in physics simulation.
-    /// Uses the effective visible set + adds same-project future nodes.
-    /// Returns None if no filtering is active (simulate all nodes).
-    fn compute_physics_visible_nodes(&self) -> Option<HashSet<String>> {
-        if !self.any_filter_active() && !self.any_proximity_active() {
-            return None;
+    /// Diff sessions and important messages against earlier loads. Refreshes feed the
+    /// "N new" badge and, when enabled, a rate-limited desktop notification; full
+    /// loads only reset the baseline.
+    fn track_new_activity(&mut self, incremental: bool) {
+        let threshold = self.settings.notify_importance_threshold;
+        // Older history filling in during a chunked load isn't new activity
+        if !incremental || self.load_progress.is_some() {
+            self.activity_watch.prime(&self.graph.data.nodes, threshold);
+            self.new_activity.clear();
+            return;
         }
+        let fresh = self.activity_watch.diff(&self.graph.data.nodes, threshold);
+        if self.settings.notifications_enabled {
+            let min_interval = std::time::Duration::from_secs_f32(self.settings.notify_min_interval_secs.max(1.0));
+            self.notifier.notify(&fresh, min_interval);
+        }
+        self.new_activity.extend(fresh);
+    }
 
-        // Clone effective visible set and add same-project future nodes
-        let mut visible = self.effective_visible_nodes.clone();
-        for node in &self.graph.data.nodes {
-            if self.is_same_project_future_node(node) {
-                visible.insert(node.id.clone());
+    /// Select the most recent new node and clear the badge
+    fn acknowledge_new_activity(&mut self) {
+        if let Some(latest) = self.new_activity.pop() {
+            if self.graph.get_node(&latest.node_id).is_some() {
+                self.graph.selected_node = Some(latest.node_id);
             }
         }
+        self.new_activity.clear();
+    }
 
-        Some(visible)
+    /// Fetch the graph on a background thread; the result is merged incrementally
+    /// by `poll_background_refresh` so the layout doesn't reset.
+    fn start_background_refresh(&mut self) {
+        let Some(db) = self.db.clone() else {
+            return;
+        };
+        let hours = self.time_range_hours;
+        let session = self.session_filter.clone();
+        let projects = self.project_pushdown.clone();
+        let excluded = self.settings.excluded_projects.clone();
+        let content_lazy = self.settings.content_lazy;
+
+        let (tx, rx) = mpsc::channel();
+        std::thread::spawn(move || {
+            let _ = tx.send(db.fetch_graph(hours, session.as_deref(), &projects, &excluded, content_lazy));
+        });
+        self.refresh_rx = Some(rx);
     }
 
-    /// Compute node sizes for physics simulation
-    /// Returns None if size_physics_weight is 0 (uniform masses)
-    /// Returns Some(HashMap) with node_id -> size when physics uses variable mass
-    fn compute_node_sizes(&self) -> Option<std::collections::HashMap<String, f32>> {
-        // If weight is ~0, return None for uniform masses (optimization)
-        if self.layout.size_physics_weight < 0.001 {
-            return None;
+    /// Poll for a finished background refresh
+    fn poll_background_refresh(&mut self) {
+        let Some(ref rx) = self.refresh_rx else {
+            return;
+        };
+        match rx.try_recv() {
+            Ok(Ok(mut data)) => {
+                self.refresh_rx = None;
+                self.refresh_failures = 0;
+                self.refresh_anchor = Instant::now();
+                self.load_session_topics();
+                self.attach_derived_nodes(&mut data);
+                self.apply_graph_data(data, true);
+                self.start_obsidian_scan();
+                self.replay_queued_writes();
+            }
+            Ok(Err(e)) => {
+                self.refresh_rx = None;
+                self.refresh_failures = self.refresh_failures.saturating_add(1);
+                self.refresh_anchor = Instant::now();
+                tracing::warn!(
+                    "Auto-refresh failed (retrying in {:.0}s): {}",
+                    self.auto_refresh_interval().as_secs_f32(),
+                    e
+                );
+            }
+            Err(mpsc::TryRecvError::Empty) => {}
+            Err(mpsc::TryRecvError::Disconnected) => {
+                self.refresh_rx = None;
+            }
         }
+    }
 
-        let mut sizes = std::collections::HashMap::new();
+    /// Install finished community detection and start it again once the graph's
+    /// communities are stale. Small graphs are detected inline.
+    fn update_communities(&mut self) {
+        if let Some(ref rx) = self.community_rx {
+            match rx.try_recv() {
+                Ok((generation, communities)) => {
+                    self.community_rx = None;
+                    // A result for an older graph falls through and restarts
+                    if generation == self.graph.community_generation {
+                        self.graph.communities = Some(communities);
+                    }
+                }
+                Err(mpsc::TryRecvError::Empty) => return,
+                Err(mpsc::TryRecvError::Disconnected) => {
+                    self.community_rx = None;
+                }
+            }
+        }
+        if self.graph.communities.is_some() || self.graph.data.nodes.is_empty() {
+            return;
+        }
+        if self.graph.data.nodes.len() < community::WORKER_MIN_NODES {
+            self.graph.communities = Some(community::detect_graph(&self.graph.data.nodes, &self.graph.data.edges));
+            return;
+        }
+        let generation = self.graph.community_generation;
+        let ids: Vec<String> = self.graph.data.nodes.iter().map(|n| n.id.clone()).collect();
+        let links = community::structural_links(&self.graph.data.edges);
+        let (tx, rx) = mpsc::channel();
+        std::thread::spawn(move || {
+            let communities = community::detect(ids.iter().map(String::as_str), &links);
+            let _ = tx.send((generation, communities));
+        });
+        self.community_rx = Some(rx);
+    }
 
-        for node in &self.graph.data.nodes {
-            // Same formula as visual sizing, but for ALL nodes
-            // (physics may simulate nodes not currently drawn)
+    /// Search the whole database for messages like `node_id`, fetch the ones
+    /// that aren't loaded, and link them to it
+    fn find_similar(&mut self, node_id: &str) {
+        let Some(node) = self.graph.get_node(node_id) else {
+            return;
+        };
+        if matches!(node.role, Role::Topic | Role::Obsidian) || self.similar_rx.is_some() {
+            return;
+        }
+        let Some(db) = self.db.clone() else {
+            return;
+        };
+        let source = node.id.clone();
+        let content = node.full_content.clone();
+        let preview = node.content_preview.clone();
+        let loaded: HashSet<String> = self.graph.node_index.keys().cloned().collect();
+        let content_lazy = self.settings.content_lazy;
+        let delta = self.graph.score_proximity_delta;
+        let max_edges = self.graph.max_proximity_edges;
+        let max_neighbors = self.graph.max_neighbors_per_node;
 
-            // 1. Importance factor (0-1, default 0.5)
-            let importance = node.importance_score.unwrap_or(0.5);
-            let imp_factor = (self.w_importance * importance).exp();
-
-            // 2. Token factor (log-normalized 0-1)
-            let tokens_norm = self.graph.normalize_tokens(node);
-            let tok_factor = (self.w_tokens * tokens_norm).exp();
-
-            // 3. Time/recency factor (distance from scrubber, 0-1)
-            let time_factor = if self.graph.timeline.max_time > self.graph.timeline.min_time {
-                if let Some(node_time) = node.timestamp_secs() {
-                    let time_range = self.graph.timeline.max_time - self.graph.timeline.min_time;
-                    let scrubber_time = self.graph.timeline.time_at_position(self.graph.timeline.position);
-                    let distance = (scrubber_time - node_time).abs();
-                    let normalized_distance = (distance / time_range).clamp(0.0, 1.0) as f32;
-                    (-self.w_time * normalized_distance).exp()
-                } else {
-                    1.0
+        let (tx, rx) = mpsc::channel();
+        std::thread::spawn(move || {
+            let result = (|| -> Result<discover::Found, String> {
+                // Lazy loads only carry the preview; search on the whole message
+                let query = match content {
+                    Some(content) => content,
+                    None => db.fetch_messages(std::slice::from_ref(&source), false)?
+                        .pop()
+                        .and_then(|n| n.full_content)
+                        .unwrap_or(preview),
+                };
+                let response = ApiClient::new().fetch_proximity_edges(&query, delta, max_edges, max_neighbors)?;
+                let neighbours = discover::top_k(&response.scores, &source, discover::TOP_K);
+                let missing: Vec<String> = neighbours.iter()
+                    .map(|(id, _)| id.clone())
+                    .filter(|id| !loaded.contains(id))
+                    .collect();
+                let fetched = db.fetch_messages(&missing, content_lazy)?;
+                Ok(discover::Found { source, neighbours, fetched })
+            })();
+            let _ = tx.send(result);
+        });
+        self.similar_rx = Some(rx);
+    }
+
+    /// Merge a finished "Find similar" into the graph and frame the result
+    fn poll_find_similar(&mut self) {
+        let Some(ref rx) = self.similar_rx else {
+            return;
+        };
+        match rx.try_recv() {
+            Ok(Ok(found)) => {
+                self.similar_rx = None;
+                let source = found.source.clone();
+                let mut framed = vec![source.clone()];
+                framed.extend(found.neighbours.iter().map(|(id, _)| id.clone()));
+                let fetched: Vec<String> = found.fetched.iter().map(|n| n.id.clone()).collect();
+                self.discovered.add(found);
+
+                let mut data = self.graph.data.clone();
+                self.attach_derived_nodes(&mut data);
+                self.apply_graph_data(data, true);
+                // Neighbours from other sessions have no anchor; start them around the source
+                if let Some(anchor) = self.graph.get_pos(&source) {
+                    let step = std::f32::consts::TAU / fetched.len().max(1) as f32;
+                    for (i, id) in fetched.iter().enumerate() {
+                        let angle = step * i as f32;
+                        self.graph.positions.insert(id.clone(), anchor + Vec2::angled(angle) * 60.0);
+                    }
                 }
-            } else {
-                1.0
-            };
+                self.fit_request = Some(framed);
+            }
+            Ok(Err(e)) => {
+                self.similar_rx = None;
+                tracing::warn!("Find similar failed: {}", e);
+            }
+            Err(mpsc::TryRecvError::Empty) => {}
+            Err(mpsc::TryRecvError::Disconnected) => {
+                self.similar_rx = None;
+            }
+        }
+    }
 
-            // Combine factors multiplicatively (same as visual sizing)
-            let size = imp_factor * tok_factor * time_factor;
-            sizes.insert(node.id.clone(), size);
+    /// Diff two messages' contents. Lazy loads fetch the full text first, and
+    /// that or a large diff runs on a worker thread.
+    fn start_content_diff(&mut self, ids: [String; 2]) {
+        let texts = ids.clone().map(|id| self.graph.get_node(&id).map(|n| (n.full_content.clone(), n.content_preview.clone())));
+        let [Some(first), Some(second)] = texts else {
+            return;
+        };
+        if let (Some(a), Some(b)) = (&first.0, &second.0) {
+            if !text_diff::is_large(a, b) {
+                let texts = [a.clone(), b.clone()];
+                self.content_diff = Some(ContentDiffState::Done(ContentDiff::new(ids, texts)));
+                return;
+            }
         }
 
-        Some(sizes)
+        let db = self.db.clone();
+        let (tx, rx) = mpsc::channel();
+        std::thread::spawn(move || {
+            let result = (|| -> Result<ContentDiff, String> {
+                let missing: Vec<String> = ids.iter()
+                    .zip([&first, &second])
+                    .filter(|(_, (full, _))| full.is_none())
+                    .map(|(id, _)| id.clone())
+                    .collect();
+                let fetched = match (&db, missing.is_empty()) {
+                    (Some(db), false) => db.fetch_messages(&missing, false)?,
+                    _ => Vec::new(),
+                };
+                let text = |id: &str, (full, preview): (Option<String>, String)| {
+                    full.or_else(|| fetched.iter().find(|n| n.id == id).and_then(|n| n.full_content.clone()))
+                        .unwrap_or(preview)
+                };
+                let texts = [text(&ids[0], first), text(&ids[1], second)];
+                Ok(ContentDiff::new(ids, texts))
+            })();
+            let _ = tx.send(result);
+        });
+        self.content_diff = Some(ContentDiffState::Running(rx));
     }
 
-    /// Trigger summary fetch for a double-clicked node
-    fn trigger_summary_for_node(&mut self, node_id: String) {
-        if let Some(node) = self.graph.get_node(&node_id) {
-            let session_id = node.session_id.clone();
-            let timestamp = node.timestamp.clone();
+    fn poll_content_diff(&mut self) {
+        let Some(ContentDiffState::Running(ref rx)) = self.content_diff else {
+            return;
+        };
+        match rx.try_recv() {
+            Ok(Ok(diff)) => self.content_diff = Some(ContentDiffState::Done(diff)),
+            Ok(Err(e)) => self.content_diff = Some(ContentDiffState::Failed(e)),
+            Err(mpsc::TryRecvError::Empty) => {}
+            Err(mpsc::TryRecvError::Disconnected) => self.content_diff = None,
+        }
+    }
 
-            // Store state
-            self.summary_node_id = Some(node_id);
-            self.summary_session_id = Some(session_id.clone());
-            self.summary_timestamp = timestamp.clone();
-            self.summary_loading = true; // Enable point-in-time summary via API
-            self.summary_data = None;
-            self.summary_error = None;
+    /// Remove every node and edge "Find similar" added
+    fn clear_discovered(&mut self) {
+        let mut data = self.graph.data.clone();
+        std::mem::take(&mut self.discovered).detach(&mut data);
+        self.apply_graph_data(data, true);
+    }
 
-            // Reset session summary state
-            self.session_summary_data = None;
-            self.session_summary_loading = true;
+    /// Pan and zoom so the given nodes fill the graph view
+    fn apply_fit_request(&mut self, rect: egui::Rect, ui_zoom: f32) {
+        let Some(ids) = self.fit_request.take() else {
+            return;
+        };
+        let points: Vec<Pos2> = ids.iter().filter_map(|id| self.graph.get_pos(id)).collect();
+        if points.is_empty() {
+            return;
+        }
+        let bounds = egui::Rect::from_points(&points).expand(40.0);
+        let zoom = (rect.width() / bounds.width()).min(rect.height() / bounds.height()) * 0.9;
+        self.zoom = (zoom * ui_zoom).clamp(0.005, 5.0);
+        // Screen position is center + (pos - center) * zoom + pan
+        self.pan_offset = (rect.center() - bounds.center()) * (self.zoom / ui_zoom);
+    }
 
-            // Open floating window
-            if !self.summary_window_dragged {
-                self.summary_window_dragged = false;
+    /// Follow a Cmd+drag on the canvas. In timeline view the dragged x extent
+    /// sets the timeline window live; in force view the window becomes the time
+    /// span of the nodes inside the dragged box on release. Escape restores the
+    /// window from before the drag.
+    fn update_time_drag(&mut self, response: &egui::Response, center: Pos2, zoom: f32, cancel: bool) {
+        let Some(mut drag) = self.time_drag else {
+            return;
+        };
+        if cancel {
+            let timeline = &mut self.graph.timeline;
+            (timeline.start_position, timeline.position) = drag.restore;
+            self.graph.update_visible_items();
+            self.effective_visible_dirty = true;
+            self.time_drag = None;
+            return;
+        }
+        if let Some(p) = response.interact_pointer_pos() {
+            drag.current = p;
+        }
+        self.time_drag = Some(drag);
+
+        let live = self.layout.algorithm == LayoutAlgorithm::Timeline;
+        if live || response.drag_stopped() {
+            if let Some((start, end)) = self.time_drag_window(&drag, center, zoom) {
+                self.commands.push(AppCommand::SetTimelineWindow { start, end });
             }
-            self.summary_window_open = true;
+        }
+        if response.drag_stopped() {
+            self.time_drag = None;
+        }
+    }
 
-            // Create channels for both summaries
-            let (partial_tx, partial_rx) = mpsc::channel();
-            let (session_tx, session_rx) = mpsc::channel();
-            self.summary_receiver = Some(partial_rx);
-            self.session_summary_receiver = Some(session_rx);
-
-            // Fetch point-in-time summary via API (with AI generation)
-            if let Some(ref ts) = timestamp {
-                let api = ApiClient::new();
-                let sid = session_id.clone();
-                let ts_clone = ts.clone();
-                std::thread::spawn(move || {
-                    let result = std::panic::catch_unwind(std::panic::AssertUnwindSafe(|| {
-                        api.fetch_partial_summary(&sid, &ts_clone)
-                    }));
-                    match result {
-                        Ok(r) => { let _ = partial_tx.send(r); }
-                        Err(_) => { let _ = partial_tx.send(Err("Thread panicked".to_string())); }
-                    }
-                });
-            } else {
-                // No timestamp - can't do point-in-time summary
-                self.summary_loading = false;
+    /// Time window (epoch seconds) a canvas drag selects; None while the drag
+    /// is too narrow or, in force view, the box holds no timestamped node
+    fn time_drag_window(&self, drag: &TimeDrag, center: Pos2, zoom: f32) -> Option<(f64, f64)> {
+        let to_graph = |p: Pos2| center + (p - center - self.pan_offset) / zoom;
+        let (a, b) = (to_graph(drag.origin), to_graph(drag.current));
+        let timeline = &self.graph.timeline;
+        if self.layout.algorithm == LayoutAlgorithm::Timeline {
+            if (drag.current.x - drag.origin.x).abs() < TIME_DRAG_MIN_PX {
+                return None;
             }
-
-            // Fetch session summary via API (with generate_if_missing=true)
-            let api = ApiClient::new();
-            std::thread::spawn(move || {
-                let result = std::panic::catch_unwind(std::panic::AssertUnwindSafe(|| {
-                    api.fetch_session_summary(&session_id, true)
-                }));
-                match result {
-                    Ok(r) => { let _ = session_tx.send(r); }
-                    Err(_) => { let _ = session_tx.send(Err("Thread panicked".to_string())); }
-                }
-            });
+            let time_at = |x: f32| {
+                let fraction = self.layout.timeline_fraction_at(center.x, x) as f64;
+                timeline.min_time + fraction * (timeline.max_time - timeline.min_time)
+            };
+            let (t0, t1) = (time_at(a.x), time_at(b.x));
+            return Some((t0.min(t1), t0.max(t1)));
         }
-    }
 
-    /// Trigger neighborhood summary for a Ctrl+Clicked node and its direct neighbors
-    fn trigger_neighborhood_summary(&mut self, node_id: String) {
-        // Build adjacency list and find neighbors at configured depth
-        let adj = self.build_adjacency_list(self.neighborhood_include_temporal);
-        let mut seeds = HashSet::new();
-        seeds.insert(node_id.clone());
-        let neighbor_ids = self.expand_to_neighbors(&seeds, self.neighborhood_depth, &adj);
+        let bounds = egui::Rect::from_two_pos(a, b);
+        let any_filter = self.any_filter_active();
+        self.graph.data.nodes.iter()
+            .filter(|n| !any_filter || self.effective_visible_nodes.contains(&n.id))
+            .filter(|n| self.graph.get_pos(&n.id).is_some_and(|p| bounds.contains(p)))
+            .filter_map(|n| n.timestamp_secs())
+            .fold(None, |hull, t| Some(hull.map_or((t, t), |(lo, hi): (f64, f64)| (lo.min(t), hi.max(t)))))
+    }
 
-        let message_ids: Vec<String> = neighbor_ids.into_iter().collect();
-        let count = message_ids.len();
+    /// Preview of a canvas time drag: a full-height band in timeline view, the
+    /// dragged box in force view, labelled with the window it selects
+    fn render_time_drag(&self, painter: &egui::Painter, rect: egui::Rect, drag: &TimeDrag, zoom: f32) {
+        let area = if self.layout.algorithm == LayoutAlgorithm::Timeline {
+            egui::Rect::from_x_y_ranges(drag.origin.x.min(drag.current.x)..=drag.origin.x.max(drag.current.x), rect.y_range())
+        } else {
+            egui::Rect::from_two_pos(drag.origin, drag.current)
+        };
+        painter.rect_filled(area, 2.0, self.palette.accent.orange_subtle);
+        painter.rect_stroke(area, 2.0, Stroke::new(1.0, self.palette.timeline.bar_selected));
+
+        let timeline = &self.graph.timeline;
+        let label = match self.time_drag_window(drag, rect.center(), zoom) {
+            Some((start, end)) => format!("{} → {}", timeline.format_time(start), timeline.format_time(end)),
+            None if self.layout.algorithm == LayoutAlgorithm::Timeline => return,
+            None => "No messages in box".to_string(),
+        };
+        painter.text(
+            Pos2::new(area.center().x, area.top() + 6.0),
+            egui::Align2::CENTER_TOP,
+            label,
+            egui::FontId::proportional(11.0),
+            self.palette.text.primary,
+        );
+    }
 
-        // Set loading state
-        self.neighborhood_summary_data = None;
-        self.neighborhood_summary_loading = true;
-        self.neighborhood_summary_error = None;
-        self.neighborhood_summary_center_node = Some(node_id);
-        self.neighborhood_summary_count = count;
+    /// Whether a legend focus on another community dims this node
+    fn community_dimmed(&self, node: &crate::graph::types::GraphNode) -> bool {
+        self.graph.color_mode == ColorMode::Community
+            && self.community_focus.is_some_and(|focus| self.graph.node_community(&node.id).is_some_and(|c| c != focus))
+    }
 
-        // Open neighborhood window
-        if !self.neighborhood_window_dragged {
-            self.neighborhood_window_dragged = false;
-        }
-        self.neighborhood_window_open = true;
+    /// Scan the Obsidian vault on a background thread for notes modified inside the
+    /// loaded window and link them against the loaded messages
+    fn start_obsidian_scan(&mut self) {
+        let Some(vault) = self.settings.obsidian_vault.clone() else {
+            return;
+        };
+        let since = chrono::Utc::now().timestamp() as f64 - self.time_range_hours as f64 * 3600.0;
+        let messages: Vec<MessageRef> = self.graph.data.nodes.iter()
+            .filter(|n| !matches!(n.role, Role::Obsidian | Role::Topic))
+            .map(MessageRef::from_node)
+            .collect();
 
-        // Create channel and spawn background thread
         let (tx, rx) = mpsc::channel();
-        self.neighborhood_summary_receiver = Some(rx);
-
-        let api = ApiClient::new();
         std::thread::spawn(move || {
-            let result = std::panic::catch_unwind(std::panic::AssertUnwindSafe(|| {
-                api.fetch_neighborhood_summary(message_ids)
-            }));
-            match result {
-                Ok(r) => { let _ = tx.send(r); }
-                Err(_) => { let _ = tx.send(Err("Thread panicked".to_string())); }
-            }
+            let vault = std::path::PathBuf::from(vault);
+            let project = obsidian::vault_project(&vault);
+            let result = obsidian::scan_vault(&vault, since)
+                .map(|notes| (obsidian::link_notes(&notes, &messages, &project), obsidian::note_paths(&notes)));
+            let _ = tx.send(result);
         });
+        self.obsidian_rx = Some(rx);
     }
 
-    /// Load semantic filters from the API
-    fn load_semantic_filters(&mut self) {
-        self.semantic_filter_loading = true;
-
-        let api = ApiClient::new();
-        match api.fetch_semantic_filters() {
-            Ok(filters) => {
-                self.semantic_filters = filters;
-                self.semantic_filter_loading = false;
+    /// Poll for a finished vault scan
+    fn poll_obsidian_scan(&mut self) {
+        let Some(ref rx) = self.obsidian_rx else {
+            return;
+        };
+        match rx.try_recv() {
+            Ok(Ok((notes, paths))) => {
+                self.obsidian_rx = None;
+                self.obsidian_status = Some(format!(
+                    "{} notes, {} links",
+                    format_count(notes.nodes.len() as i64),
+                    format_count(notes.edges.len() as i64),
+                ));
+                self.obsidian_notes = notes;
+                self.obsidian_note_paths = paths;
+                self.merge_derived_nodes();
             }
-            Err(e) => {
-                eprintln!("Failed to load semantic filters: {}", e);
-                self.semantic_filter_loading = false;
+            Ok(Err(e)) => {
+                self.obsidian_rx = None;
+                self.obsidian_status = Some(e);
+            }
+            Err(mpsc::TryRecvError::Empty) => {}
+            Err(mpsc::TryRecvError::Disconnected) => {
+                self.obsidian_rx = None;
             }
         }
     }
 
-    /// Create a new semantic filter (LLM-scored)
-    fn create_semantic_filter(&mut self) {
-        let name = self.new_filter_input.trim().to_string();
-        if name.is_empty() {
+    fn load_session_tags(&mut self) {
+        let Some(ref db) = self.db else {
             return;
-        }
-
-        let api = ApiClient::new();
-        match api.create_semantic_filter(&name, &name, "semantic") {
-            Ok(filter) => {
-                self.semantic_filters.push(filter);
-                self.new_filter_input.clear();
-            }
-            Err(e) => {
-                eprintln!("Failed to create semantic filter: {}", e);
+        };
+        match db.fetch_session_tags() {
+            Ok(rows) => {
+                self.session_tags = SessionTags::from_rows(rows);
+                self.tag_filter.retain_existing(&self.session_tags);
+                self.tags_error = None;
             }
+            Err(e) => self.tags_error = Some(e),
         }
     }
 
-    /// Create a rule-based filter (auto-scored, no LLM)
-    fn create_rule_filter(&mut self, name: &str, query_text: &str) {
-        let api = ApiClient::new();
-        match api.create_semantic_filter(name, query_text, "rule") {
-            Ok(filter) => {
-                self.semantic_filters.push(filter);
-                self.semantic_visible_ids = None;
-                self.load_graph();
-            }
-            Err(e) => {
-                eprintln!("Failed to create rule filter: {}", e);
+    /// Add or remove a session tag in the database, then in the loaded copy
+    fn set_session_tag(&mut self, session_id: &str, tag: &str, tagged: bool) {
+        let Some(ref db) = self.db else {
+            return;
+        };
+        let result = if tagged {
+            db.add_session_tag(session_id, tag)
+        } else {
+            db.remove_session_tag(session_id, tag)
+        };
+        if let Err(e) = result {
+            self.tags_error = Some(e);
+            return;
+        }
+        self.tags_error = None;
+        if tagged {
+            self.session_tags.insert(session_id, tag);
+        } else {
+            self.session_tags.remove(session_id, tag);
+            // A filter on a tag nobody has any more would hide everything
+            let before = self.tag_filter.tags.len();
+            self.tag_filter.retain_existing(&self.session_tags);
+            if self.tag_filter.tags.len() != before {
+                self.effective_visible_dirty = true;
             }
         }
+        if self.tag_filter.is_active() {
+            self.effective_visible_dirty = true;
+        }
     }
 
-    /// Delete a semantic filter
-    fn delete_semantic_filter(&mut self, filter_id: i32) {
-        let api = ApiClient::new();
-        match api.delete_semantic_filter(filter_id) {
-            Ok(()) => {
-                self.semantic_filters.retain(|f| f.id != filter_id);
-                self.semantic_filter_modes.remove(&filter_id);
-            }
-            Err(e) => {
-                eprintln!("Failed to delete semantic filter: {}", e);
-            }
+    /// Re-read summary topics from the database when topic nodes are on
+    fn load_session_topics(&mut self) {
+        if !self.settings.topic_nodes_enabled {
+            self.session_topics.clear();
+            return;
+        }
+        let Some(ref db) = self.db else {
+            return;
+        };
+        match db.fetch_session_topics() {
+            Ok(topics) => self.session_topics = topics,
+            Err(e) => tracing::warn!("Failed to load session topics: {}", e),
         }
     }
 
-    /// Trigger categorization for a semantic filter (runs in background)
-    fn trigger_categorization(&mut self, filter_id: i32) {
-        self.categorizing_filter_id = Some(filter_id);
-        self.categorization_progress = None;
+    /// Add the nodes that aren't part of the load (discovered neighbours, vault
+    /// notes, summary topics) to fetched data
+    fn attach_derived_nodes(&self, data: &mut GraphData) {
+        discover::attach(data, &self.discovered);
+        obsidian::attach(data, &self.obsidian_notes);
+        topics::attach(data, &self.session_topics);
+        transcript::attach(data, &self.dropped_transcripts);
+    }
 
-        // Run categorization in background thread
-        let (tx, rx) = mpsc::channel();
+    /// Open `.jsonl` transcripts dropped onto the window as new sessions and
+    /// frame them. Other files and unreadable ones are reported, not fatal.
+    fn handle_dropped_files(&mut self, ctx: &egui::Context) {
+        let hovering = ctx.input(|i| !i.raw.hovered_files.is_empty());
+        if hovering {
+            let painter = ctx.layer_painter(egui::LayerId::new(egui::Order::Foreground, egui::Id::new("drop_overlay")));
+            let screen = ctx.screen_rect();
+            painter.rect_filled(screen, 0.0, Color32::from_black_alpha(160));
+            painter.text(
+                screen.center(),
+                egui::Align2::CENTER_CENTER,
+                "Drop .jsonl transcripts to open them",
+                egui::FontId::proportional(20.0),
+                Color32::WHITE,
+            );
+        }
 
-        std::thread::spawn(move || {
-            let api = ApiClient::new();
-            let result = api.trigger_categorization(filter_id);
-            let _ = tx.send(result);
-        });
+        let files = ctx.input(|i| i.raw.dropped_files.clone());
+        if files.is_empty() {
+            return;
+        }
+        let mut lines = Vec::new();
+        let mut any_error = false;
+        let mut added: Vec<String> = Vec::new();
+        for file in files {
+            let Some(path) = file.path else {
+                continue;
+            };
+            let name = path.file_name().map(|n| n.to_string_lossy().to_string()).unwrap_or_else(|| file.name.clone());
+            if path.extension().and_then(|e| e.to_str()) != Some("jsonl") {
+                lines.push(format!("{}: not a .jsonl transcript", name));
+                any_error = true;
+                continue;
+            }
+            let text = match std::fs::read_to_string(&path) {
+                Ok(text) => text,
+                Err(e) => {
+                    lines.push(format!("{}: {}", name, e));
+                    any_error = true;
+                    continue;
+                }
+            };
+            let taken: HashSet<&str> = self.dropped_transcripts.iter().map(|t| t.session_id.as_str()).collect();
+            let session_id = transcript::session_id_for(&name, &taken);
+            let parsed = transcript::parse(&text, &name, &session_id);
+            lines.push(parsed.describe());
+            if parsed.nodes.is_empty() {
+                any_error = true;
+                continue;
+            }
+            any_error |= parsed.failed_lines > 0;
+            added.extend(parsed.nodes.iter().map(|n| n.id.clone()));
+            self.dropped_transcripts.push(parsed);
+        }
+        if !added.is_empty() {
+            self.merge_derived_nodes();
+            self.fit_request = Some(added);
+        }
+        if !lines.is_empty() {
+            self.drop_status = Some((lines.join("\n"), any_error));
+        }
+    }
 
-        // Poll progress in a separate thread
-        let (progress_tx, progress_rx) = mpsc::channel();
-        let done_flag = std::sync::Arc::new(std::sync::atomic::AtomicBool::new(false));
-        let done_flag_clone = done_flag.clone();
+    /// Remove every dropped transcript's session
+    fn clear_dropped_transcripts(&mut self) {
+        let mut data = self.graph.data.clone();
+        transcript::detach(&mut data, &std::mem::take(&mut self.dropped_transcripts));
+        self.drop_status = None;
+        self.apply_graph_data(data, true);
+    }
 
-        std::thread::spawn(move || {
-            let api = ApiClient::new();
-            while !done_flag_clone.load(std::sync::atomic::Ordering::Relaxed) {
-                if let Ok(status) = api.fetch_filter_status(filter_id) {
-                    if progress_tx.send(status).is_err() {
-                        break; // receiver dropped
+    /// Dropped transcripts and what the last drop found
+    fn render_dropped_transcripts(&mut self, ui: &mut egui::Ui) {
+        let mut clear = false;
+        egui::Frame::group(ui.style()).show(ui, |ui| {
+            if !self.dropped_transcripts.is_empty() {
+                ui.horizontal(|ui| {
+                    ui.label(egui::RichText::new(format!("Dropped transcripts: {}", self.dropped_transcripts.len())).small().strong());
+                    if ui.small_button("Clear").on_hover_text("Remove the dropped sessions from the graph").clicked() {
+                        clear = true;
                     }
-                }
-                std::thread::sleep(std::time::Duration::from_millis(500));
+                });
+            }
+            if let Some((message, is_error)) = &self.drop_status {
+                let color = if *is_error { theme::state::WARNING } else { self.palette.text.muted };
+                ui.label(egui::RichText::new(message).small().color(color));
             }
         });
-
-        // Store the done flag so we can stop polling when categorization finishes
-        self.categorization_done_flag = Some(done_flag);
-        self.categorization_receiver = Some(rx);
-        self.categorization_progress_rx = Some(progress_rx);
+        if clear {
+            self.clear_dropped_transcripts();
+        }
     }
 
-    /// Check if any proximity query is active (has results)
-    fn any_proximity_active(&self) -> bool {
-        self.proximity_queries.iter().any(|q| q.active)
+    /// Re-derive note and topic nodes on the loaded graph through the incremental merge
+    fn merge_derived_nodes(&mut self) {
+        let mut data = self.graph.data.clone();
+        self.attach_derived_nodes(&mut data);
+        self.apply_graph_data(data, true);
     }
 
-    /// Get a reference to the effective visible set for passing to graph methods.
-    /// Returns Some(&HashSet) when any filter is active, None otherwise.
-    fn effective_visible_set(&self) -> Option<&HashSet<String>> {
-        if self.any_filter_active() {
-            Some(&self.effective_visible_nodes)
-        } else {
-            None
+    /// Current auto-refresh interval, doubled per consecutive failure up to a cap
+    /// Find sessions with a message inside the live window. Only while
+    /// auto-refreshing, since otherwise nothing new would ever arrive.
+    fn refresh_live_sessions(&mut self) {
+        self.live_sessions.clear();
+        self.live_tails.clear();
+        if !self.settings.auto_refresh_enabled || !self.settings.live_highlight {
+            return;
+        }
+        let window = self.settings.live_window_minutes as f64 * 60.0;
+        for session_id in self.graph.live_sessions(unix_now(), window) {
+            if let Some(span) = self.graph.session_spans.get(session_id) {
+                self.live_tails.insert(span.last_node.clone());
+            }
+            self.live_sessions.push(session_id.to_string());
         }
     }
 
-    /// Check if any filter is active that would reduce the visible set
-    fn any_filter_active(&self) -> bool {
-        self.timeline_enabled
-            || self.importance_filter.is_active()
-            || self.project_filter.is_active()
-            || self.semantic_visible_ids.is_some()
-            || self.tool_use_filter.is_active()
-            || self.histogram_session_filter.is_some()
+    fn auto_refresh_interval(&self) -> std::time::Duration {
+        let base = self.settings.auto_refresh_interval_secs.max(1.0);
+        let backoff = 2f32.powi(self.refresh_failures.min(16) as i32);
+        std::time::Duration::from_secs_f32((base * backoff).min(AUTO_REFRESH_MAX_BACKOFF_SECS.max(base)))
     }
 
-    /// Check if a single node passes ALL active filters.
-    /// Used by rebuild_effective_visible_set() to build the unified set.
-    fn is_node_effectively_visible(&self, node: &crate::graph::types::GraphNode) -> bool {
-        // Timeline filter
-        if self.timeline_enabled && !self.graph.timeline.visible_nodes.contains(&node.id) {
-            return false;
-        }
-        // Importance filter
-        if self.importance_filter.is_active() {
-            if let Some(score) = node.importance_score {
-                if score < self.importance_threshold {
-                    return false;
-                }
-            }
+    /// Time left until the next auto-refresh (None when auto-refresh is off)
+    fn auto_refresh_remaining(&self) -> Option<std::time::Duration> {
+        if !self.settings.auto_refresh_enabled {
+            return None;
         }
-        // Project filter
-        if self.project_filter.is_active() && !self.selected_projects.contains(&node.project) {
-            return false;
+        Some(self.auto_refresh_interval().saturating_sub(self.refresh_anchor.elapsed()))
+    }
+
+    /// Kick off an auto-refresh when one is due. Deferred while a load is in
+    /// flight or the user is scrubbing/dragging.
+    fn maybe_auto_refresh(&mut self, ctx: &egui::Context) {
+        if self.loading || self.refresh_rx.is_some() || self.db.is_none() {
+            return;
         }
-        // Session filter (histogram drill-down)
-        if let Some(ref sf) = self.histogram_session_filter {
-            if node.session_id != *sf {
-                return false;
+        match self.auto_refresh_remaining() {
+            None => return,
+            Some(remaining) if !remaining.is_zero() => {
+                // Keep the sidebar countdown ticking
+                ctx.request_repaint_after(remaining.min(std::time::Duration::from_secs(1)));
+                return;
             }
+            Some(_) => {}
         }
-        // Semantic filter (backend-computed visible set)
-        if let Some(ref visible_ids) = self.semantic_visible_ids {
-            if let Ok(msg_id) = node.id.parse::<i64>() {
-                if !visible_ids.contains(&msg_id) {
-                    return false;
-                }
-            }
+        if self.timeline_dragging || ctx.input(|i| i.pointer.any_down()) {
+            ctx.request_repaint_after(std::time::Duration::from_millis(250));
+            return;
         }
-        // Tool use filter
-        if self.tool_use_filter.is_active() && node.has_tool_usage {
+        self.start_background_refresh();
+    }
+
+    /// Check if .beads/ directory has changed since last check
+    /// Returns true if changes detected and we should reload
+    fn check_beads_changed(&mut self) -> bool {
+        // Only check if auto-refresh is enabled
+        if !self.settings.auto_refresh_enabled {
             return false;
         }
-        true
-    }
 
-    /// Rebuild the effective visible set by iterating all nodes once.
-    /// Should be called when effective_visible_dirty is true.
-    fn rebuild_effective_visible_set(&mut self) {
-        self.effective_visible_nodes.clear();
-        for node in &self.graph.data.nodes {
-            if self.is_node_effectively_visible(node) {
-                self.effective_visible_nodes.insert(node.id.clone());
-            }
+        // Check if enough time has passed since last check
+        let now = Instant::now();
+        let interval = std::time::Duration::from_secs_f32(self.settings.auto_refresh_interval_secs);
+        if now.duration_since(self.beads_last_check) < interval {
+            return false;
         }
-        self.effective_visible_count = self.effective_visible_nodes.len();
-        self.effective_visible_dirty = false;
-    }
-
-    /// Check if any proximity query is currently loading
-    fn any_proximity_loading(&self) -> bool {
-        self.proximity_queries.iter().any(|q| q.loading)
-    }
+        self.beads_last_check = now;
 
-    /// Total edge count across all proximity queries
-    fn total_proximity_edge_count(&self) -> usize {
-        self.proximity_queries.iter().map(|q| q.edge_count).sum()
-    }
+        // Re-run the search until a directory turns up (e.g. `bd init` after launch)
+        if self.beads_search.resolved.as_ref().is_none_or(|path| !path.exists()) {
+            self.beads_search = locate_beads_dir(self.settings.beads_path.as_deref());
+        }
+        let Some(beads_path) = self.beads_search.resolved.clone() else {
+            return false;
+        };
 
-    /// Add a new proximity query, dedup, assign color, and trigger fetch
-    fn add_proximity_query(&mut self, text: String) {
-        let text = text.trim().to_string();
-        if text.is_empty() { return; }
-        // Dedup check
-        if self.proximity_queries.iter().any(|q| q.query == text) { return; }
+        // Get the latest modification time from any file in .beads/
+        let current_mtime = match std::fs::read_dir(&beads_path) {
+            Ok(entries) => {
+                entries
+                    .filter_map(|e| e.ok())
+                    .filter_map(|e| e.metadata().ok())
+                    .filter_map(|m| m.modified().ok())
+                    .max()
+            }
+            Err(_) => None,
+        };
 
-        let color = QUERY_COLORS[self.proximity_queries.len() % QUERY_COLORS.len()];
-        let idx = self.proximity_queries.len();
-        self.proximity_queries.push(ProximityQuery {
-            query: text,
-            color,
-            scores: HashMap::new(),
-            edges: Vec::new(),
-            edge_count: 0,
-            active: false,
-            loading: false,
-            rx: None,
-            opacity: self.proximity_edge_opacity,
-            visible: true,
+        // If we can't get mtime, fall back to directory mtime
+        let current_mtime = current_mtime.or_else(|| {
+            std::fs::metadata(&beads_path)
+                .ok()
+                .and_then(|m| m.modified().ok())
         });
-        self.graph.score_proximity_enabled = true;
-        self.trigger_proximity_fetch_for(idx);
+
+        // Compare with previous
+        let changed = match (current_mtime, self.beads_last_mtime) {
+            (Some(current), Some(previous)) => current > previous,
+            (Some(_), None) => true, // First check, consider changed to trigger initial state
+            _ => false,
+        };
+
+        // Update stored mtime
+        self.beads_last_mtime = current_mtime;
+
+        changed
     }
 
-    /// Remove a proximity query by index, rebuild edges
-    fn remove_proximity_query(&mut self, index: usize) {
-        if index >= self.proximity_queries.len() { return; }
-        self.proximity_queries.remove(index);
-        // Fix heat map index
-        if let Some(hmi) = self.proximity_heat_map_index {
-            if hmi == index {
-                self.proximity_heat_map_index = None;
-            } else if hmi > index {
-                self.proximity_heat_map_index = Some(hmi - 1);
+    /// Load mail network data from API
+    fn load_mail_network(&mut self) {
+        self.mail_network_loading = true;
+        self.mail_network_error = None;
+
+        let api = ApiClient::new();
+        match api.fetch_mail_network() {
+            Ok(data) => {
+                // Initialize state with positions in a circle
+                let center = Pos2::new(125.0, 100.0);  // Center of mini-panel
+                let radius = 60.0;
+                self.mail_network_state = Some(MailNetworkState::new(data, center, radius));
+                self.mail_network_loading = false;
             }
-        }
-        // Fix per-query popup index
-        if let Some(EdgePopup::ProximityQuery(qi)) = self.edge_popup_open {
-            if qi == index {
-                self.edge_popup_open = None;
-            } else if qi > index {
-                self.edge_popup_open = Some(EdgePopup::ProximityQuery(qi - 1));
+            Err(e) => {
+                self.mail_network_error = Some(e);
+                self.mail_network_loading = false;
             }
         }
-        self.rebuild_all_proximity_edges();
-        if self.proximity_queries.is_empty() {
-            self.graph.score_proximity_enabled = false;
+    }
+
+    fn update_fps(&mut self) {
+        let now = Instant::now();
+        let frame_ms = now.duration_since(self.last_frame).as_secs_f32() * 1000.0;
+        self.last_frame = now;
+
+        self.frame_times.push(frame_ms);
+        if let Some(avg_ms) = self.frame_times.recent_average(FPS_WINDOW) {
+            self.fps = 1000.0 / avg_ms;
         }
     }
 
-    /// Combine all per-query edge vecs into graph.data.edges
-    fn rebuild_all_proximity_edges(&mut self) {
-        let mut all_edges = Vec::new();
-        for (idx, q) in self.proximity_queries.iter().enumerate() {
-            for edge in &q.edges {
-                let mut e = edge.clone();
-                e.query_index = Some(idx);
-                all_edges.push(e);
+    /// Frame time over the last few seconds as one polyline, with the 60 and
+    /// 30 fps budgets marked and p95 / worst frame beside it
+    fn render_frame_sparkline(&self, ui: &mut egui::Ui) {
+        let times = &self.frame_times;
+        let (Some(p95), Some(worst)) = (times.percentile(0.95), times.worst()) else {
+            return;
+        };
+        let (rect, response) = ui.allocate_exact_size(Vec2::new(ui.available_width().min(220.0), 36.0), egui::Sense::hover());
+        let painter = ui.painter_at(rect);
+        painter.rect_filled(rect, 2.0, self.palette.bg.surface);
+
+        // Scale to the worst frame, but keep both guides on the chart
+        let top_ms = worst.clamp(FRAME_BUDGET_30_MS * 1.2, 250.0);
+        let y_of = |ms: f32| rect.bottom() - (ms / top_ms).min(1.0) * rect.height();
+        let x_step = rect.width() / (profiler::FRAME_HISTORY - 1) as f32;
+        // Right-aligned, so the newest frame is always at the right edge
+        let x_start = rect.right() - (times.len().saturating_sub(1)) as f32 * x_step;
+        for (ms, color) in [(FRAME_BUDGET_60_MS, theme::state::WARNING), (FRAME_BUDGET_30_MS, theme::state::ERROR)] {
+            painter.hline(rect.x_range(), y_of(ms), Stroke::new(1.0, color.gamma_multiply(0.6)));
+        }
+        let points: Vec<Pos2> = times.iter()
+            .enumerate()
+            .map(|(i, ms)| Pos2::new(x_start + i as f32 * x_step, y_of(ms)))
+            .collect();
+        painter.add(egui::Shape::line(points, Stroke::new(1.0, self.palette.text.primary)));
+
+        if let Some(pos) = response.hover_pos() {
+            let i = ((pos.x - x_start) / x_step).round();
+            if let Some(ms) = (i >= 0.0).then(|| times.get(i as usize)).flatten() {
+                painter.vline(x_start + i * x_step, rect.y_range(), Stroke::new(1.0, self.palette.text.muted));
+                response.on_hover_text(format!("{:.1} ms", ms));
             }
         }
-        self.graph.set_proximity_edges(all_edges);
+        ui.label(egui::RichText::new(format!("p95 {:.1} ms · worst {:.1} ms", p95, worst))
+            .small()
+            .color(self.palette.text.muted));
     }
 
-    /// Trigger proximity fetch for a specific query index
-    fn trigger_proximity_fetch_for(&mut self, index: usize) {
-        let query = self.proximity_queries[index].query.trim().to_string();
-        if query.is_empty() { return; }
+    /// `find_node_at_scrubber`, searched again only once the scrubber has moved
+    fn node_at_scrubber(&mut self) -> Option<usize> {
+        let position = self.graph.timeline.effective_position();
+        match self.scrubber_node {
+            Some((cached, idx)) if cached == position => idx,
+            _ => {
+                let idx = self.find_node_at_scrubber();
+                self.scrubber_node = Some((position, idx));
+                idx
+            }
+        }
+    }
 
-        self.proximity_queries[index].loading = true;
+    /// Find the node closest to the current scrubber position
+    fn find_node_at_scrubber(&self) -> Option<usize> {
+        let timeline = &self.graph.timeline;
+        if timeline.timestamps.is_empty() {
+            return None;
+        }
 
-        let delta = self.graph.score_proximity_delta;
-        let max_edges = self.graph.max_proximity_edges;
-        let max_neighbors = self.graph.max_neighbors_per_node;
+        // Binary search the sorted timestamps; the closest is on one side of the split
+        let scrubber_time = timeline.time_at_position(timeline.effective_position());
+        let split = timeline.timestamps.partition_point(|&t| t < scrubber_time);
+        let closest = [split.checked_sub(1), Some(split).filter(|&i| i < timeline.timestamps.len())]
+            .into_iter()
+            .flatten()
+            .min_by(|&a, &b| {
+                let da = (timeline.timestamps[a] - scrubber_time).abs();
+                let db = (timeline.timestamps[b] - scrubber_time).abs();
+                da.total_cmp(&db)
+            })?;
+        timeline.sorted_indices.get(closest).copied()
+    }
 
-        let (tx, rx) = mpsc::channel();
-        std::thread::spawn(move || {
-            let api = ApiClient::new();
-            match api.fetch_proximity_edges(&query, delta, max_edges, max_neighbors) {
-                Ok(response) => {
-                    let edges: Vec<GraphEdge> = response.edges.into_iter().map(|e| {
-                        GraphEdge::similarity(e.source, e.target, e.strength, None)
-                    }).collect();
-                    let _ = tx.send(Ok((edges, response.scores)));
+    /// Collect node IDs into inactive and filtered sets based on active filters.
+    /// First-match-wins order: tool_use → importance → project.
+    fn collect_filter_sets(&self) -> (HashSet<String>, HashSet<String>) {
+        let mut inactive = HashSet::new();
+        let mut filtered = HashSet::new();
+
+        for node in &self.graph.data.nodes {
+            // Folded into a collapsed tool-result run: edges go through the run's head
+            if self.graph.tool_runs.is_folded(&node.id) {
+                inactive.insert(node.id.clone());
+                continue;
+            }
+            // Tool use filter
+            if self.tool_use_filter.is_active() && node.has_tool_usage {
+                match self.tool_use_filter {
+                    FilterMode::Inactive => { inactive.insert(node.id.clone()); }
+                    FilterMode::Filtered => { filtered.insert(node.id.clone()); }
+                    FilterMode::Off => {}
                 }
-                Err(e) => {
-                    let _ = tx.send(Err(e));
+                continue;
+            }
+            // Importance filter
+            if self.importance_filter.is_active() {
+                if let Some(score) = node.importance_score {
+                    if score < self.importance_threshold {
+                        match self.importance_filter {
+                            FilterMode::Inactive => { inactive.insert(node.id.clone()); }
+                            FilterMode::Filtered => { filtered.insert(node.id.clone()); }
+                            FilterMode::Off => {}
+                        }
+                        continue;
+                    }
                 }
             }
-        });
-
-        self.proximity_queries[index].rx = Some(rx);
-    }
-
-    /// Re-fetch all active queries (e.g., after delta/max change)
-    fn refetch_all_proximity_queries(&mut self) {
-        for i in 0..self.proximity_queries.len() {
-            if self.proximity_queries[i].active || !self.proximity_queries[i].query.is_empty() {
-                self.trigger_proximity_fetch_for(i);
+            // Project filter
+            if self.project_hidden(&node.project) {
+                match self.project_filter {
+                    FilterMode::Inactive => { inactive.insert(node.id.clone()); }
+                    FilterMode::Filtered => { filtered.insert(node.id.clone()); }
+                    FilterMode::Off => {}
+                }
             }
         }
+
+        (inactive, filtered)
     }
 
-    /// Clear all proximity queries, edges, and overlay
-    fn clear_proximity(&mut self) {
-        self.proximity_queries.clear();
-        self.proximity_input.clear();
-        self.proximity_edge_count_filtered = 0;
-        self.proximity_heat_map_index = None;
-        self.graph.set_proximity_edges(Vec::new());
-        self.graph.score_proximity_enabled = false;
+    /// Convenience: collect filter sets and recompute bypass edges
+    fn recompute_bypass_edges(&mut self) {
+        let (inactive, filtered) = self.collect_filter_sets();
+        self.bypass_edges = compute_bypass_edges(&self.graph.data.nodes, &self.graph.data.edges, &inactive, &filtered);
     }
 
-    /// Load embedding stats from the API
-    fn load_embedding_stats(&mut self) {
-        let api = ApiClient::new();
-        match api.fetch_embedding_stats() {
-            Ok(stats) => {
-                self.embedding_stats = Some(stats);
-            }
-            Err(e) => {
-                eprintln!("Failed to load embedding stats: {}", e);
+    /// Returns true if a node is hidden by any active filter (tool_use, importance, project).
+    /// Does NOT check timeline or semantic filters (those are handled separately).
+    fn is_node_hidden(&self, node: &crate::graph::types::GraphNode) -> bool {
+        if self.tool_use_filter.is_active() && node.has_tool_usage {
+            return true;
+        }
+        if self.importance_filter.is_active() {
+            if let Some(score) = node.importance_score {
+                if score < self.importance_threshold {
+                    return true;
+                }
             }
         }
+        if self.project_hidden(&node.project) {
+            return true;
+        }
+        false
     }
 
-    /// Trigger embedding generation (runs in background)
-    fn trigger_embedding_generation(&mut self) {
-        self.embedding_gen_loading = true;
+    /// Check if any semantic filters are active (not Off)
+    fn has_active_semantic_filters(&self) -> bool {
+        self.semantic_filter_modes.values()
+            .any(|mode| *mode != SemanticFilterMode::Off)
+    }
 
-        let (tx, rx) = mpsc::channel();
-        std::thread::spawn(move || {
-            let api = ApiClient::new();
-            let result = api.generate_embeddings(1000);
-            let _ = tx.send(result);
-        });
+    /// Build adjacency list from graph edges for BFS neighbor lookup
+    fn build_adjacency_list(&self, include_temporal: bool) -> HashMap<String, Vec<String>> {
+        build_adjacency_list(&self.graph.data.edges, include_temporal)
+    }
 
-        self.embedding_gen_receiver = Some(rx);
+    /// Adjacency over the edges inside the timeline window (all edges when the
+    /// timeline is off), for neighborhood features that follow the scrubber
+    fn build_visible_adjacency_list(&self, include_temporal: bool) -> HashMap<String, Vec<String>> {
+        if self.timeline_enabled {
+            build_adjacency_list(self.graph.visible_edges(), include_temporal)
+        } else {
+            self.build_adjacency_list(include_temporal)
+        }
     }
 
-    /// Trigger embedding generation for visible nodes only (runs in background)
-    fn trigger_embedding_generation_visible(&mut self) {
-        // Collect IDs of currently visible nodes
-        let visible_ids: Vec<String> = self.get_visible_node_ids();
-        if visible_ids.is_empty() {
-            return;
-        }
-
-        self.embedding_gen_loading = true;
+    /// Expand a set of nodes to include neighbors up to given depth using BFS
+    fn expand_to_neighbors(&self, seeds: &HashSet<String>, depth: usize, adj: &HashMap<String, Vec<String>>) -> HashSet<String> {
+        expand_to_neighbors(seeds, depth, adj)
+    }
 
-        let (tx, rx) = mpsc::channel();
-        std::thread::spawn(move || {
-            let api = ApiClient::new();
-            let result = api.generate_embeddings_visible(&visible_ids);
-            let _ = tx.send(result);
-        });
+    /// Filter modes as the backend's mode strings, keyed by filter id
+    fn semantic_filter_mode_strings(&self) -> HashMap<i32, String> {
+        self.semantic_filter_modes.iter()
+            .map(|(&id, mode)| {
+                let mode_str = match mode {
+                    SemanticFilterMode::Off => "off",
+                    SemanticFilterMode::Exclude => "exclude",
+                    SemanticFilterMode::Include => "include",
+                    SemanticFilterMode::IncludePlus1 => "include_plus_1",
+                    SemanticFilterMode::IncludePlus2 => "include_plus_2",
+                };
+                (id, mode_str.to_string())
+            })
+            .collect()
+    }
 
-        self.embedding_gen_receiver = Some(rx);
+    /// Compute the semantic filter visible set on this thread (used on full loads)
+    fn compute_semantic_filter_now(&mut self) {
+        if self.apply_local_semantic_filters() {
+            return;
+        }
+        let api = ApiClient::new();
+        match api.compute_visible_set(&self.semantic_filter_mode_strings(), self.time_range_hours) {
+            Ok(maybe_ids) => {
+                self.semantic_visible_ids = maybe_ids.map(|ids| ids.into_iter().collect());
+                self.effective_visible_dirty = true;
+            }
+            Err(e) => tracing::warn!("Semantic filter compute failed: {}", e),
+        }
     }
 
-    /// Get IDs of all currently visible (non-filtered) nodes
-    fn get_visible_node_ids(&self) -> Vec<String> {
-        self.effective_visible_nodes.iter().cloned().collect()
+    /// Evaluate rule filters against the loaded nodes, recording parse errors
+    fn evaluate_local_rules(&mut self) {
+        self.local_rule_matches.clear();
+        self.rule_errors.clear();
+        for filter in self.semantic_filters.iter().filter(|f| f.is_rule()) {
+            match Rule::parse(&filter.query_text) {
+                Ok(rule) => {
+                    self.local_rule_matches.insert(filter.id, rule.evaluate(&self.graph.data.nodes));
+                }
+                Err(e) => {
+                    self.rule_errors.insert(filter.id, e);
+                }
+            }
+        }
     }
 
-    /// Start rescoring importance for visible nodes (runs in background with progress)
-    fn start_rescore_visible(&mut self) {
-        // Collect unique session IDs from visible nodes
-        let session_ids: Vec<String> = self.get_visible_session_ids();
+    /// Compute the semantic filter visible set without the backend when every
+    /// active filter is a locally evaluated rule. Returns false if it can't.
+    fn apply_local_semantic_filters(&mut self) -> bool {
+        let active: Vec<(i32, SemanticFilterMode)> = self
+            .semantic_filter_modes
+            .iter()
+            .filter(|(_, mode)| **mode != SemanticFilterMode::Off)
+            .map(|(&id, &mode)| (id, mode))
+            .collect();
+        if active.is_empty() || !active.iter().all(|(id, _)| self.local_rule_matches.contains_key(id)) {
+            return false;
+        }
+        let needs_expansion = active
+            .iter()
+            .any(|(_, mode)| matches!(mode, SemanticFilterMode::IncludePlus1 | SemanticFilterMode::IncludePlus2));
+        let adj = if needs_expansion { self.build_adjacency_list(false) } else { HashMap::new() };
+        let all_ids: HashSet<String> = self.graph.data.nodes.iter().map(|n| n.id.clone()).collect();
+        let visible = combine_filter_matches(&active, &self.local_rule_matches, &all_ids, &adj);
+        self.semantic_visible_ids = Some(visible.iter().filter_map(|id| ids::message_id(id)).collect());
+        self.effective_visible_dirty = true;
+        true
+    }
 
-        if session_ids.is_empty() {
+    /// Fire a backend request to compute the semantic filter visible set.
+    /// The result arrives asynchronously via semantic_filter_rx.
+    fn request_semantic_filter_compute(&mut self) {
+        if self.apply_local_semantic_filters() {
             return;
         }
+        let filter_modes = self.semantic_filter_mode_strings();
+        let hours = self.time_range_hours;
+        self.semantic_filter_pending = true;
 
-        self.rescore_loading = true;
-        self.rescore_result = None;
-        self.rescore_progress = None;
-
-        // Run rescore in background thread with streaming progress
         let (tx, rx) = mpsc::channel();
-
         std::thread::spawn(move || {
             let api = ApiClient::new();
-            if let Err(e) = api.rescore_importance_stream(session_ids, tx.clone()) {
-                let _ = tx.send(RescoreEvent::Error(e));
-            }
+            let result = api.compute_visible_set(&filter_modes, hours);
+            let _ = tx.send(result);
         });
-
-        self.rescore_receiver = Some(rx);
+        self.semantic_filter_rx = Some(rx);
     }
 
-    /// Start re-ingestion of Claude sessions in background thread.
-    /// Computes the since window from the most recent node timestamp.
-    fn start_ingest(&mut self) {
-        self.ingest_loading = true;
-        self.ingest_result = None;
+    /// Check if a node is a "same-project future node" (outside timeline but same project as selected/hovered)
+    fn is_same_project_future_node(&self, node: &crate::graph::types::GraphNode) -> bool {
+        // Must be outside timeline window (future)
+        if self.graph.is_node_visible(&node.id) {
+            return false;
+        }
 
-        // Find the most recent node timestamp to determine staleness
-        let now = std::time::SystemTime::now()
-            .duration_since(std::time::UNIX_EPOCH)
-            .unwrap_or_default()
-            .as_secs_f64();
-        let max_ts = self.graph.data.nodes.iter()
-            .filter_map(|n| n.timestamp_secs())
-            .fold(0.0_f64, f64::max);
+        // Check if same project as selected or hovered node
+        let reference_project = self.graph.selected_node.as_ref()
+            .or(self.graph.hovered_node.as_ref())
+            .and_then(|id| self.graph.get_node(id))
+            .map(|n| &n.project);
 
-        let hours_stale = if max_ts > 0.0 {
-            ((now - max_ts) / 3600.0).ceil() as u64
-        } else {
-            24 // fallback if no nodes loaded
-        };
+        if let Some(ref_project) = reference_project {
+            return &node.project == ref_project;
+        }
 
-        // Convert to a --since string, minimum 1h, add 1h padding
-        let since = if hours_stale + 1 >= 48 {
-            format!("{}d", (hours_stale + 1 + 23) / 24) // round up to days
+        false
+    }
+
+    /// Debug: Check if node is after the current playhead position
+    fn is_after_playhead(&self, node: &crate::graph::types::GraphNode) -> bool {
+        if !self.timeline_enabled {
+            return false;
+        }
+        let scrubber_time = self.graph.timeline.time_at_position(self.graph.timeline.effective_position());
+        if let Some(node_time) = node.timestamp_secs() {
+            node_time > scrubber_time
         } else {
-            format!("{}h", (hours_stale + 1).max(1))
-        };
+            false
+        }
+    }
 
-        let (tx, rx) = mpsc::channel();
-        std::thread::spawn(move || {
-            let api = ApiClient::new();
-            let _ = tx.send(api.trigger_ingest(&since));
-        });
+    /// Debug: Check if node is in same session as selected node
+    fn is_same_session_as_selected(&self, node: &crate::graph::types::GraphNode) -> bool {
+        if let Some(ref selected_id) = self.graph.selected_node {
+            if let Some(selected_node) = self.graph.get_node(selected_id) {
+                return node.session_id == selected_node.session_id;
+            }
+        }
+        false
+    }
 
-        self.ingest_receiver = Some(rx);
+    /// Debug: Check if node is in same project as selected node
+    fn is_same_project_as_selected(&self, node: &crate::graph::types::GraphNode) -> bool {
+        if let Some(ref selected_id) = self.graph.selected_node {
+            if let Some(selected_node) = self.graph.get_node(selected_id) {
+                return node.project == selected_node.project;
+            }
+        }
+        false
     }
 
-    /// Get unique session IDs from currently visible nodes
-    fn get_visible_session_ids(&self) -> Vec<String> {
-        let mut session_ids: HashSet<String> = HashSet::new();
+    /// Compute which nodes should participate in physics simulation.
+    /// Uses the effective visible set + adds same-project future nodes.
+    /// Returns None if no filtering is active (simulate all nodes).
+    fn compute_physics_visible_nodes(&self) -> Option<HashSet<String>> {
+        if !self.any_filter_active() && !self.any_proximity_active() {
+            return None;
+        }
+
+        // Clone effective visible set and add same-project future nodes
+        let mut visible = self.effective_visible_nodes.clone();
         for node in &self.graph.data.nodes {
-            if self.effective_visible_nodes.contains(&node.id) {
-                session_ids.insert(node.session_id.clone());
+            if self.is_same_project_future_node(node) {
+                visible.insert(node.id.clone());
             }
         }
-        session_ids.into_iter().collect()
+
+        Some(visible)
     }
 
-    /// Render the floating summary window (Point-in-Time + Session Summary, triggered by double-click)
-    fn render_summary_window(&mut self, ctx: &egui::Context) {
-        if !self.summary_window_open {
+    /// Recompute the per-node recency size factors when the scrubber time,
+    /// time range or decay weight changed since the last call
+    fn refresh_recency_factors(&mut self) {
+        let timeline = &self.graph.timeline;
+        let scrubber_time = timeline.time_at_position(timeline.effective_position());
+        let key = (scrubber_time, timeline.min_time, timeline.max_time, self.w_time);
+        if self.recency_key == Some(key) && self.recency_factors.len() == timeline.node_times.len() {
             return;
         }
+        self.recency_key = Some(key);
 
-        // Calculate default position (top-right, offset from edge)
-        let screen_rect = ctx.screen_rect();
-        let default_pos = egui::pos2(screen_rect.right() - 420.0, 60.0);
-
-        let mut open = self.summary_window_open;
+        let time_range = timeline.max_time - timeline.min_time;
+        let w_time = self.w_time;
+        self.recency_factors.clear();
+        self.recency_factors.extend(timeline.node_times.iter().map(|t| match t {
+            Some(node_time) if time_range > 0.0 => {
+                let distance = (scrubber_time - node_time).abs();
+                let normalized_distance = (distance / time_range).clamp(0.0, 1.0) as f32;
+                (-w_time * normalized_distance).exp()
+            }
+            // No timestamp or no time range = neutral
+            _ => 1.0,
+        }));
+    }
 
-        let window_response = egui::Window::new("Summary")
-            .open(&mut open)
-            .default_pos(default_pos)
-            .default_size([400.0, 500.0])
-            .resizable(true)
-            .collapsible(true)
-            .show(ctx, |ui| {
-                // Resume session button bar
-                if let Some(ref session_id) = self.summary_session_id.clone() {
-                    let project = self.summary_node_id.as_ref()
-                        .and_then(|nid| self.graph.get_node(nid))
-                        .map(|n| n.project.clone())
-                        .unwrap_or_default();
+    /// Compute node sizes for physics simulation
+    /// Returns None if size_physics_weight is 0 (uniform masses)
+    /// Returns Some(HashMap) with node_id -> size when physics uses variable mass
+    fn compute_node_sizes(&self) -> Option<std::collections::HashMap<String, f32>> {
+        // If weight is ~0, return None for uniform masses (optimization)
+        if self.layout.size_physics_weight < 0.001 {
+            return None;
+        }
 
-                    ui.horizontal(|ui| {
-                        ui.label(egui::RichText::new(format!("Session: {}…", &session_id[..8.min(session_id.len())]))
-                            .small()
-                            .color(egui::Color32::GRAY));
+        let mut sizes = std::collections::HashMap::new();
 
-                        ui.with_layout(egui::Layout::right_to_left(egui::Align::Center), |ui| {
-                            let recently_copied = self.resume_copied_at
-                                .map(|t| t.elapsed().as_millis() < 1500)
-                                .unwrap_or(false);
+        for (idx, node) in self.graph.data.nodes.iter().enumerate() {
+            // Same formula as visual sizing, but for ALL nodes
+            // (physics may simulate nodes not currently drawn)
+            let time_factor = self.recency_factors.get(idx).copied().unwrap_or(1.0);
+            let size = scene::size_multiplier(&self.graph, node, self.w_importance, self.w_tokens, time_factor);
+            sizes.insert(node.id.clone(), size);
+        }
 
-                            let btn_text = if recently_copied { "Copied!" } else { "Resume" };
-                            let btn = ui.button(btn_text);
+        Some(sizes)
+    }
 
-                            if recently_copied {
-                                ctx.request_repaint();
-                            }
+    /// Trigger summary fetch for a double-clicked node
+    fn trigger_summary_for_node(&mut self, node_id: String) {
+        if let Some(node) = self.graph.get_node(&node_id) {
+            let session_id = node.session_id.clone();
+            let timestamp = node.timestamp.clone();
 
-                            if btn.clicked() {
-                                // Expand ~/ to full home path
-                                let full_path = if project.starts_with("~/") {
-                                    if let Some(home) = dirs::home_dir() {
-                                        format!("{}{}", home.display(), &project[1..])
-                                    } else {
-                                        project.clone()
-                                    }
-                                } else {
-                                    project.clone()
-                                };
+            // Store state
+            self.summary_node_id = Some(node_id);
+            self.summary_session_id = Some(session_id.clone());
+            self.summary_timestamp = timestamp.clone();
+            self.summary_loading = true; // Enable point-in-time summary via API
+            self.summary_data = None;
+            self.summary_error = None;
 
-                                let cmd = format!("cd {} && claude --resume {}", full_path, session_id);
-                                ui.output_mut(|o| o.copied_text = cmd);
-                                self.resume_copied_at = Some(Instant::now());
-                            }
+            // Reset session summary state
+            self.session_summary_data = None;
+            self.session_summary_loading = true;
 
-                            btn.on_hover_text("Copy zsh command to resume this Claude Code session");
-                        });
-                    });
-                    ui.separator();
-                }
+            // Open floating window
+            if !self.summary_window_dragged {
+                self.summary_window_dragged = false;
+            }
+            self.summary_window_open = true;
 
-                // Point-in-Time Summary section
+            // Create channels for both summaries
+            let (partial_tx, partial_rx) = mpsc::channel();
+            let (session_tx, session_rx) = mpsc::channel();
+            self.summary_receiver = Some(partial_rx);
+            self.session_summary_receiver = Some(session_rx);
+
+            // Fetch point-in-time summary via API (with AI generation)
+            if let Some(ref ts) = timestamp {
+                let api = ApiClient::new();
+                let sid = session_id.clone();
+                let ts_clone = ts.clone();
+                std::thread::spawn(move || {
+                    let result = std::panic::catch_unwind(std::panic::AssertUnwindSafe(|| {
+                        api.fetch_partial_summary(&sid, &ts_clone)
+                    }));
+                    match result {
+                        Ok(r) => { let _ = partial_tx.send(r); }
+                        Err(_) => { let _ = partial_tx.send(Err("Thread panicked".to_string())); }
+                    }
+                });
+            } else {
+                // No timestamp - can't do point-in-time summary
+                self.summary_loading = false;
+            }
+
+            // Fetch session summary via API (with generate_if_missing=true)
+            let api = ApiClient::new();
+            std::thread::spawn(move || {
+                let result = std::panic::catch_unwind(std::panic::AssertUnwindSafe(|| {
+                    api.fetch_session_summary(&session_id, true)
+                }));
+                match result {
+                    Ok(r) => { let _ = session_tx.send(r); }
+                    Err(_) => { let _ = session_tx.send(Err("Thread panicked".to_string())); }
+                }
+            });
+        }
+    }
+
+    /// Trigger neighborhood summary for a Ctrl+Clicked node and its direct neighbors
+    fn trigger_neighborhood_summary(&mut self, node_id: String) {
+        // Build adjacency list and find neighbors at configured depth
+        let adj = self.build_visible_adjacency_list(self.neighborhood_include_temporal);
+        let mut seeds = HashSet::new();
+        seeds.insert(node_id.clone());
+        let neighbor_ids = self.expand_to_neighbors(&seeds, self.neighborhood_depth, &adj);
+
+        let message_ids: Vec<String> = neighbor_ids.into_iter().collect();
+        let count = message_ids.len();
+
+        // Set loading state
+        self.neighborhood_summary_data = None;
+        self.neighborhood_summary_loading = true;
+        self.neighborhood_summary_error = None;
+        self.neighborhood_summary_center_node = Some(node_id);
+        self.neighborhood_summary_count = count;
+
+        // Open neighborhood window
+        if !self.neighborhood_window_dragged {
+            self.neighborhood_window_dragged = false;
+        }
+        self.neighborhood_window_open = true;
+
+        // Create channel and spawn background thread
+        let (tx, rx) = mpsc::channel();
+        self.neighborhood_summary_receiver = Some(rx);
+
+        let api = ApiClient::new();
+        std::thread::spawn(move || {
+            let result = std::panic::catch_unwind(std::panic::AssertUnwindSafe(|| {
+                api.fetch_neighborhood_summary(message_ids)
+            }));
+            match result {
+                Ok(r) => { let _ = tx.send(r); }
+                Err(_) => { let _ = tx.send(Err("Thread panicked".to_string())); }
+            }
+        });
+    }
+
+    /// Load semantic filters from the API
+    fn load_semantic_filters(&mut self) {
+        self.semantic_filter_loading = true;
+
+        let api = ApiClient::new();
+        match api.fetch_semantic_filters() {
+            Ok(mut filters) => {
+                // Deletes still queued stay hidden
+                filters.retain(|f| !self.write_queue.is_pending(&WriteOp::DeleteSemanticFilter { filter_id: f.id }));
+                // Drop saved modes for filters that no longer exist
+                let before = self.semantic_filter_modes.len();
+                self.semantic_filter_modes.retain(|id, _| filters.iter().any(|f| f.id == *id));
+                if self.semantic_filter_modes.len() != before {
+                    self.settings_dirty = true;
+                }
+                self.semantic_filters = filters;
+                self.semantic_filter_loading = false;
+            }
+            Err(e) => {
+                tracing::warn!("Failed to load semantic filters: {}", e);
+                self.semantic_filter_loading = false;
+            }
+        }
+    }
+
+    /// Create a new semantic filter (LLM-scored)
+    fn create_semantic_filter(&mut self) {
+        let name = self.new_filter_input.trim().to_string();
+        if name.is_empty() {
+            return;
+        }
+
+        let api = ApiClient::new();
+        match api.create_semantic_filter(&name, &name, "semantic") {
+            Ok(filter) => {
+                self.semantic_filters.push(filter);
+                self.new_filter_input.clear();
+            }
+            Err(e) => {
+                let op = WriteOp::CreateSemanticFilter { name: name.clone(), query_text: name, filter_type: "semantic".to_string() };
+                if self.queue_failed_write(op, e) {
+                    self.new_filter_input.clear();
+                }
+            }
+        }
+    }
+
+    /// Create a rule-based filter (auto-scored, no LLM)
+    fn create_rule_filter(&mut self, name: &str, query_text: &str) {
+        let api = ApiClient::new();
+        match api.create_semantic_filter(name, query_text, "rule") {
+            Ok(filter) => {
+                self.semantic_filters.push(filter);
+                self.semantic_visible_ids = None;
+                self.schedule_reload();
+            }
+            Err(e) => {
+                let op = WriteOp::CreateSemanticFilter {
+                    name: name.to_string(),
+                    query_text: query_text.to_string(),
+                    filter_type: "rule".to_string(),
+                };
+                self.queue_failed_write(op, e);
+            }
+        }
+    }
+
+    /// Delete a semantic filter
+    fn delete_semantic_filter(&mut self, filter_id: i32) {
+        let api = ApiClient::new();
+        let deleted = match api.delete_semantic_filter(filter_id) {
+            Ok(()) => true,
+            Err(e) => self.queue_failed_write(WriteOp::DeleteSemanticFilter { filter_id }, e),
+        };
+        // A queued delete is shown as done; the filter list hides it until it's sent
+        if deleted {
+            self.semantic_filters.retain(|f| f.id != filter_id);
+            if self.semantic_filter_modes.remove(&filter_id).is_some() {
+                self.settings_dirty = true;
+            }
+        }
+    }
+
+    /// Queue a write the backend couldn't take right now. Returns false (and
+    /// logs) when the backend rejected it, so retrying is pointless.
+    fn queue_failed_write(&mut self, op: WriteOp, error: String) -> bool {
+        match WriteError::from_api(error) {
+            WriteError::Transient(e) => {
+                tracing::warn!("{} failed, queued for retry: {}", op.describe(), e);
+                self.write_queue.push(op, e, unix_now());
+                true
+            }
+            WriteError::Permanent(e) => {
+                tracing::warn!("{} failed: {}", op.describe(), e);
+                false
+            }
+        }
+    }
+
+    /// Badge for queued writes; expands to a list with flush and dismiss
+    fn render_write_queue(&mut self, ui: &mut egui::Ui) {
+        if self.write_queue.is_empty() {
+            return;
+        }
+        let pending = self.write_queue.pending();
+        let failed = self.write_queue.entries.len() - pending;
+        let mut text = String::new();
+        if pending > 0 {
+            text = format!("{} pending write{}", pending, if pending == 1 { "" } else { "s" });
+        }
+        if failed > 0 {
+            if !text.is_empty() {
+                text.push_str(", ");
+            }
+            text.push_str(&format!("{} failed", failed));
+        }
+        let color = if failed > 0 { theme::state::ERROR } else { theme::state::WARNING };
+        let badge = ui.add(egui::Button::new(egui::RichText::new(format!("⟳ {}", text)).size(11.0).color(color)).small());
+        if badge.on_hover_text("Filter changes the backend didn't take. Click to review.").clicked() {
+            self.write_queue_open = !self.write_queue_open;
+        }
+        if !self.write_queue_open {
+            return;
+        }
+
+        let now = unix_now();
+        let mut dismiss = None;
+        for entry in &self.write_queue.entries {
+            ui.horizontal(|ui| {
+                if ui.small_button("✖").on_hover_text("Dismiss without sending").clicked() {
+                    dismiss = Some(entry.id);
+                }
+                ui.label(egui::RichText::new(entry.op.describe()).size(11.0));
+            });
+            let status = if entry.failed {
+                format!("Rejected: {}", entry.last_error)
+            } else {
+                format!(
+                    "{} attempt{}, next in {:.0}s: {}",
+                    entry.attempts,
+                    if entry.attempts == 1 { "" } else { "s" },
+                    (entry.retry_at - now).max(0.0),
+                    entry.last_error
+                )
+            };
+            let color = if entry.failed { theme::state::ERROR } else { self.palette.text.muted };
+            ui.label(egui::RichText::new(status).size(10.0).color(color));
+        }
+        if let Some(id) = dismiss {
+            self.write_queue.dismiss(id);
+        }
+        if pending > 0 && ui.small_button("Retry now").clicked() {
+            self.write_queue.make_due();
+            self.replay_queued_writes();
+        }
+    }
+
+    /// Replay queued writes that are due; refreshes the filter list when any land
+    fn replay_queued_writes(&mut self) {
+        if self.write_queue.pending() == 0 {
+            return;
+        }
+        let api = ApiClient::new();
+        let existing: Vec<(String, String)> = self.semantic_filters.iter()
+            .map(|f| (f.query_text.clone(), f.filter_type.clone()))
+            .collect();
+        let replay = self.write_queue.replay(unix_now(), |op| match op {
+            WriteOp::CreateSemanticFilter { name, query_text, filter_type } => {
+                // A create whose reply was lost may already exist; don't make a second one
+                if existing.iter().any(|(q, t)| q == query_text && t == filter_type) {
+                    return Ok(());
+                }
+                api.create_semantic_filter(name, query_text, filter_type)
+                    .map(|_| ())
+                    .map_err(WriteError::from_api)
+            }
+            WriteOp::DeleteSemanticFilter { filter_id } => {
+                api.delete_semantic_filter(*filter_id).map_err(WriteError::from_api)
+            }
+        });
+        if replay.failed > 0 {
+            self.write_queue_open = true;
+        }
+        if replay.applied > 0 {
+            tracing::info!("Replayed {} queued write(s)", replay.applied);
+            self.load_semantic_filters();
+        }
+    }
+
+    /// Trigger categorization for a semantic filter (runs in background)
+    fn trigger_categorization(&mut self, filter_id: i32) {
+        self.categorizing_filter_id = Some(filter_id);
+        self.categorization_progress = None;
+
+        // Run categorization in background thread
+        let (tx, rx) = mpsc::channel();
+
+        std::thread::spawn(move || {
+            let api = ApiClient::new();
+            let result = api.trigger_categorization(filter_id);
+            let _ = tx.send(result);
+        });
+
+        // Poll progress in a separate thread
+        let (progress_tx, progress_rx) = mpsc::channel();
+        let done_flag = std::sync::Arc::new(std::sync::atomic::AtomicBool::new(false));
+        let done_flag_clone = done_flag.clone();
+
+        std::thread::spawn(move || {
+            let api = ApiClient::new();
+            while !done_flag_clone.load(std::sync::atomic::Ordering::Relaxed) {
+                if let Ok(status) = api.fetch_filter_status(filter_id) {
+                    if progress_tx.send(status).is_err() {
+                        break; // receiver dropped
+                    }
+                }
+                std::thread::sleep(std::time::Duration::from_millis(500));
+            }
+        });
+
+        // Store the done flag so we can stop polling when categorization finishes
+        self.categorization_done_flag = Some(done_flag);
+        self.categorization_receiver = Some(rx);
+        self.categorization_progress_rx = Some(progress_rx);
+    }
+
+    /// Check if any proximity query is active (has results)
+    fn any_proximity_active(&self) -> bool {
+        self.proximity_queries.iter().any(|q| q.active)
+    }
+
+    /// Get a reference to the effective visible set for passing to graph methods.
+    /// Returns Some(&HashSet) when any filter is active, None otherwise.
+    fn effective_visible_set(&self) -> Option<&HashSet<String>> {
+        if self.any_filter_active() {
+            Some(&self.effective_visible_nodes)
+        } else {
+            None
+        }
+    }
+
+    /// Check if any filter is active that would reduce the visible set
+    fn any_filter_active(&self) -> bool {
+        self.timeline_enabled
+            || self.importance_filter.is_active()
+            || self.project_filter.is_active()
+            || self.semantic_visible_ids.is_some()
+            || self.tool_use_filter.is_active()
+            || self.settings.code_only_filter
+            || self.settings.role_filter != RoleFilter::All
+            || !self.graph.tool_runs.is_empty()
+            || self.histogram_session_filter.is_some()
+            || self.tag_filter.is_active()
+    }
+
+    /// Check if a single node passes ALL active filters.
+    /// Used by rebuild_effective_visible_set() to build the unified set.
+    fn is_node_effectively_visible(&self, node: &crate::graph::types::GraphNode) -> bool {
+        // Timeline filter
+        if self.timeline_enabled && !self.graph.timeline.visible_nodes.contains(&node.id) {
+            return false;
+        }
+        self.passes_content_filters(node, self.histogram_session_filter.as_deref())
+    }
+
+    /// Every filter except the timeline window, isolating `session` when given.
+    /// Shared by the main visible set and the per-side sets of compare mode.
+    fn passes_content_filters(&self, node: &crate::graph::types::GraphNode, session: Option<&str>) -> bool {
+        // Importance filter
+        if self.importance_filter.is_active() {
+            if let Some(score) = node.importance_score {
+                if score < self.importance_threshold {
+                    return false;
+                }
+            }
+        }
+        // Role filter
+        if !self.settings.role_filter.admits(&node.role) {
+            return false;
+        }
+        // Folded into a collapsed tool-result run
+        if self.graph.tool_runs.is_folded(&node.id) {
+            return false;
+        }
+        // Project filter (topics span projects)
+        if node.role != Role::Topic && self.project_hidden(&node.project) {
+            return false;
+        }
+        // Tag filter (topics span sessions)
+        if node.role != Role::Topic && !self.tag_filter.admits(&self.session_tags, &node.session_id) {
+            return false;
+        }
+        // Session isolation (histogram drill-down or a compare side)
+        if let Some(sf) = session {
+            if node.session_id != sf {
+                return false;
+            }
+        }
+        // Semantic filter (backend-computed visible set)
+        if let Some(ref visible_ids) = self.semantic_visible_ids {
+            if !passes_semantic_filter(visible_ids, &node.id) {
+                return false;
+            }
+        }
+        // Tool use filter
+        if self.tool_use_filter.is_active() && node.has_tool_usage {
+            return false;
+        }
+        // Code-heavy only
+        if self.settings.code_only_filter
+            && self.graph.content_class(&node.id).is_none_or(|c| c.kind != ContentKind::Code)
+        {
+            return false;
+        }
+        true
+    }
+
+    /// Rebuild the effective visible set by iterating all nodes once.
+    /// Should be called when effective_visible_dirty is true.
+    fn rebuild_effective_visible_set(&mut self) {
+        // While diffing, both windows stand in for the timeline window
+        self.graph_diff = (self.diff_mode && self.timeline_enabled && !self.graph.timeline.is_degenerate()).then(|| {
+            let b = self.graph.get_timeline_window();
+            let a = self.diff_window_a.unwrap_or_else(|| diff::previous_window(b));
+            diff::compute(&self.graph.data, &self.graph.timeline, a, b)
+        });
+        self.effective_visible_nodes.clear();
+        for (idx, node) in self.graph.data.nodes.iter().enumerate() {
+            let visible = match self.graph_diff {
+                Some(ref diff) => diff.class_of(idx).is_some()
+                    && self.passes_content_filters(node, self.histogram_session_filter.as_deref()),
+                None => self.is_node_effectively_visible(node),
+            };
+            if visible {
+                self.effective_visible_nodes.insert(node.id.clone());
+            }
+        }
+        self.effective_visible_count = self.effective_visible_nodes.len();
+        self.effective_visible_generation += 1;
+        self.effective_visible_dirty = false;
+        self.hit_grid_dirty = true;
+        self.declutter = None;
+
+        // Timeline moves land here too, so refresh the project tree's window counts
+        // and the cost totals
+        self.project_window_counts.clear();
+        self.cost_summary.clear();
+        let today_start = cost::today_start_secs();
+        for node in &self.graph.data.nodes {
+            let in_window = !self.timeline_enabled || self.graph.timeline.visible_nodes.contains(&node.id);
+            if in_window {
+                *self.project_window_counts.entry(node.project.clone()).or_default() += 1;
+            }
+            self.cost_summary.add(node, in_window, today_start);
+        }
+
+        if let Some(sessions) = &self.compare_sessions {
+            let sides = [0, 1].map(|side| {
+                self.graph.data.nodes.iter()
+                    .enumerate()
+                    .filter(|(_, node)| self.passes_content_filters(node, Some(&sessions[side])))
+                    .map(|(idx, _)| idx)
+                    .collect()
+            });
+            self.compare_nodes = sides;
+        }
+    }
+
+    /// Check if any proximity query is currently loading
+    fn any_proximity_loading(&self) -> bool {
+        self.proximity_queries.iter().any(|q| q.loading)
+    }
+
+    /// Total edge count across all proximity queries
+    fn total_proximity_edge_count(&self) -> usize {
+        self.proximity_queries.iter().map(|q| q.edge_count).sum()
+    }
+
+    /// Add a new proximity query, dedup, assign color, and trigger fetch
+    fn add_proximity_query(&mut self, text: String) {
+        let text = text.trim().to_string();
+        if text.is_empty() { return; }
+        // Dedup check
+        if self.proximity_queries.iter().any(|q| q.query == text) { return; }
+
+        let idx = self.proximity_queries.len();
+        self.proximity_queries.push(ProximityQuery {
+            query: text,
+            scores: HashMap::new(),
+            edges: Vec::new(),
+            edge_count: 0,
+            active: false,
+            loading: false,
+            rx: None,
+            opacity: self.proximity_edge_opacity,
+            visible: true,
+        });
+        self.graph.score_proximity_enabled = true;
+        self.trigger_proximity_fetch_for(idx);
+    }
+
+    /// Remove a proximity query by index, rebuild edges
+    fn remove_proximity_query(&mut self, index: usize) {
+        if index >= self.proximity_queries.len() { return; }
+        self.proximity_queries.remove(index);
+        // Fix heat map index
+        if let Some(hmi) = self.proximity_heat_map_index {
+            if hmi == index {
+                self.proximity_heat_map_index = None;
+            } else if hmi > index {
+                self.proximity_heat_map_index = Some(hmi - 1);
+            }
+        }
+        // Fix per-query popup index
+        if let Some(EdgePopup::ProximityQuery(qi)) = self.edge_popup_open {
+            if qi == index {
+                self.edge_popup_open = None;
+            } else if qi > index {
+                self.edge_popup_open = Some(EdgePopup::ProximityQuery(qi - 1));
+            }
+        }
+        self.rebuild_all_proximity_edges();
+        if self.proximity_queries.is_empty() {
+            self.graph.score_proximity_enabled = false;
+        }
+    }
+
+    /// Combine all per-query edge vecs into graph.data.edges
+    fn rebuild_all_proximity_edges(&mut self) {
+        let mut all_edges = Vec::new();
+        for (idx, q) in self.proximity_queries.iter().enumerate() {
+            for edge in &q.edges {
+                let mut e = edge.clone();
+                e.query_index = Some(idx);
+                all_edges.push(e);
+            }
+        }
+        // Discovered neighbours are similarity edges too; keep those still attached
+        all_edges.extend(self.discovered.edges.iter()
+            .filter(|e| self.graph.node_index.contains_key(&e.source) && self.graph.node_index.contains_key(&e.target))
+            .cloned());
+        self.graph.set_proximity_edges(all_edges);
+    }
+
+    /// Trigger proximity fetch for a specific query index
+    fn trigger_proximity_fetch_for(&mut self, index: usize) {
+        let query = self.proximity_queries[index].query.trim().to_string();
+        if query.is_empty() { return; }
+
+        self.proximity_queries[index].loading = true;
+
+        let delta = self.graph.score_proximity_delta;
+        let max_edges = self.graph.max_proximity_edges;
+        let max_neighbors = self.graph.max_neighbors_per_node;
+
+        let (tx, rx) = mpsc::channel();
+        std::thread::spawn(move || {
+            let api = ApiClient::new();
+            match api.fetch_proximity_edges(&query, delta, max_edges, max_neighbors) {
+                Ok(response) => {
+                    let edges: Vec<GraphEdge> = response.edges.into_iter().map(|e| {
+                        GraphEdge::similarity(e.source, e.target, e.strength, None)
+                    }).collect();
+                    let _ = tx.send(Ok((edges, response.scores)));
+                }
+                Err(e) => {
+                    let _ = tx.send(Err(e));
+                }
+            }
+        });
+
+        self.proximity_queries[index].rx = Some(rx);
+    }
+
+    /// Re-fetch all active queries (e.g., after delta/max change)
+    fn refetch_all_proximity_queries(&mut self) {
+        for i in 0..self.proximity_queries.len() {
+            if self.proximity_queries[i].active || !self.proximity_queries[i].query.is_empty() {
+                self.trigger_proximity_fetch_for(i);
+            }
+        }
+    }
+
+    /// Clear all proximity queries, edges, and overlay
+    fn clear_proximity(&mut self) {
+        self.proximity_queries.clear();
+        self.proximity_input.clear();
+        self.proximity_edge_count_filtered = 0;
+        self.proximity_heat_map_index = None;
+        // Leaves only the discovered edges
+        self.rebuild_all_proximity_edges();
+        self.graph.score_proximity_enabled = false;
+    }
+
+    /// Load embedding stats from the API
+    fn load_embedding_stats(&mut self) {
+        let api = ApiClient::new();
+        match api.fetch_embedding_stats() {
+            Ok(stats) => {
+                self.embedding_stats = Some(stats);
+            }
+            Err(e) => {
+                tracing::warn!("Failed to load embedding stats: {}", e);
+            }
+        }
+    }
+
+    /// Trigger embedding generation (runs in background)
+    fn trigger_embedding_generation(&mut self) {
+        self.embedding_gen_loading = true;
+
+        let (tx, rx) = mpsc::channel();
+        std::thread::spawn(move || {
+            let api = ApiClient::new();
+            let result = api.generate_embeddings(1000);
+            let _ = tx.send(result);
+        });
+
+        self.embedding_gen_receiver = Some(rx);
+    }
+
+    /// Trigger embedding generation for visible nodes only (runs in background)
+    fn trigger_embedding_generation_visible(&mut self) {
+        // Collect IDs of currently visible nodes
+        let visible_ids: Vec<String> = self.get_visible_node_ids();
+        if visible_ids.is_empty() {
+            return;
+        }
+
+        self.embedding_gen_loading = true;
+
+        let (tx, rx) = mpsc::channel();
+        std::thread::spawn(move || {
+            let api = ApiClient::new();
+            let result = api.generate_embeddings_visible(&visible_ids);
+            let _ = tx.send(result);
+        });
+
+        self.embedding_gen_receiver = Some(rx);
+    }
+
+    /// Get IDs of all currently visible (non-filtered) nodes
+    fn get_visible_node_ids(&self) -> Vec<String> {
+        self.effective_visible_nodes.iter().cloned().collect()
+    }
+
+    /// Start rescoring importance for visible nodes (runs in background with progress)
+    fn start_rescore_visible(&mut self) {
+        // Collect unique session IDs from visible nodes
+        let session_ids: Vec<String> = self.get_visible_session_ids();
+
+        if session_ids.is_empty() {
+            return;
+        }
+
+        self.rescore_loading = true;
+        self.rescore_result = None;
+        self.rescore_progress = None;
+
+        // Run rescore in background thread with streaming progress
+        let (tx, rx) = mpsc::channel();
+
+        std::thread::spawn(move || {
+            let api = ApiClient::new();
+            if let Err(e) = api.rescore_importance_stream(session_ids, tx.clone()) {
+                let _ = tx.send(RescoreEvent::Error(e));
+            }
+        });
+
+        self.rescore_receiver = Some(rx);
+    }
+
+    /// Start re-ingestion of Claude sessions in background thread.
+    /// Computes the since window from the most recent node timestamp.
+    fn start_ingest(&mut self) {
+        self.ingest_loading = true;
+        self.ingest_result = None;
+
+        // Find the most recent node timestamp to determine staleness
+        let now = std::time::SystemTime::now()
+            .duration_since(std::time::UNIX_EPOCH)
+            .unwrap_or_default()
+            .as_secs_f64();
+        let max_ts = self.graph.data.nodes.iter()
+            .filter_map(|n| n.timestamp_secs())
+            .fold(0.0_f64, f64::max);
+
+        let hours_stale = if max_ts > 0.0 {
+            ((now - max_ts) / 3600.0).ceil() as u64
+        } else {
+            24 // fallback if no nodes loaded
+        };
+
+        // Convert to a --since string, minimum 1h, add 1h padding
+        let since = if hours_stale + 1 >= 48 {
+            format!("{}d", (hours_stale + 1 + 23) / 24) // round up to days
+        } else {
+            format!("{}h", (hours_stale + 1).max(1))
+        };
+
+        let (tx, rx) = mpsc::channel();
+        std::thread::spawn(move || {
+            let api = ApiClient::new();
+            let _ = tx.send(api.trigger_ingest(&since));
+        });
+
+        self.ingest_receiver = Some(rx);
+    }
+
+    /// Get unique session IDs from currently visible nodes
+    fn get_visible_session_ids(&self) -> Vec<String> {
+        let mut session_ids: HashSet<String> = HashSet::new();
+        for node in &self.graph.data.nodes {
+            if self.effective_visible_nodes.contains(&node.id) {
+                session_ids.insert(node.session_id.clone());
+            }
+        }
+        session_ids.into_iter().collect()
+    }
+
+    /// Render the floating summary window (Point-in-Time + Session Summary, triggered by double-click)
+    fn render_summary_window(&mut self, ctx: &egui::Context) {
+        if !self.summary_window_open {
+            return;
+        }
+
+        // Calculate default position (top-right, offset from edge)
+        let screen_rect = ctx.screen_rect();
+        let default_pos = egui::pos2(screen_rect.right() - 420.0, 60.0);
+
+        let mut open = self.summary_window_open;
+
+        let window_response = egui::Window::new("Summary")
+            .open(&mut open)
+            .default_pos(default_pos)
+            .default_size([400.0, 500.0])
+            .resizable(true)
+            .collapsible(true)
+            .show(ctx, |ui| {
+                // Resume session button bar
+                if let Some(ref session_id) = self.summary_session_id.clone() {
+                    let project = self.summary_node_id.as_ref()
+                        .and_then(|nid| self.graph.get_node(nid))
+                        .map(|n| n.project.clone())
+                        .unwrap_or_default();
+
+                    ui.horizontal(|ui| {
+                        ui.label(egui::RichText::new("Session:").small().color(egui::Color32::GRAY));
+                        self.session_chip(ui, session_id);
+
+                        ui.with_layout(egui::Layout::right_to_left(egui::Align::Center), |ui| {
+                            let recently_copied = self.resume_copied_at
+                                .map(|t| t.elapsed().as_millis() < 1500)
+                                .unwrap_or(false);
+
+                            let btn_text = if recently_copied { "Copied!" } else { "Resume" };
+                            let btn = ui.button(btn_text);
+
+                            if recently_copied {
+                                ctx.request_repaint();
+                            }
+
+                            if btn.clicked() {
+                                // Expand ~/ to full home path
+                                let full_path = if project.starts_with("~/") {
+                                    if let Some(home) = dirs::home_dir() {
+                                        format!("{}{}", home.display(), &project[1..])
+                                    } else {
+                                        project.clone()
+                                    }
+                                } else {
+                                    project.clone()
+                                };
+
+                                let cmd = format!("cd {} && claude --resume {}", full_path, session_id);
+                                ui.output_mut(|o| o.copied_text = cmd);
+                                self.resume_copied_at = Some(Instant::now());
+                            }
+
+                            btn.on_hover_text("Copy zsh command to resume this Claude Code session");
+                        });
+                    });
+                    if let Some(totals) = self.cost_summary.by_session.get(session_id) {
+                        let ratio = totals.cache_hit_ratio();
+                        let color = if totals.poor_cache_reuse() { theme::state::WARNING } else { self.palette.text.secondary };
+                        ui.label(egui::RichText::new(format!("Cache hits: {}", format_ratio(ratio))).small().color(color))
+                            .on_hover_text("Cache reads / (cache reads + input) for this session within the timeline window");
+                    }
+                    ui.separator();
+                }
+
+                // Token classes for the clicked node
+                if let Some(node) = self.summary_node_id.as_ref().and_then(|id| self.graph.get_node(id)) {
+                    let mut totals = TokenTotals::default();
+                    totals.add_node(node);
+                    if totals.total() > 0 {
+                        self.render_token_classes(ui, &totals);
+                        ui.separator();
+                    }
+                }
+
+                // Point-in-Time Summary section
                 egui::CollapsingHeader::new("Point-in-Time Summary")
                     .default_open(true)
                     .show(ui, |ui| {
@@ -1795,689 +4461,3198 @@ impl DashboardApp {
                                 self.summary_error = None;
                                 self.summary_node_id = None;
                             }
-                        } else if let Some(ref data) = self.summary_data.clone() {
-                            // Message counts
-                            ui.horizontal(|ui| {
-                                ui.label(format!("Messages: {} you / {} Claude",
-                                    data.user_count, data.assistant_count));
-                            });
-                            ui.add_space(5.0);
+                        } else if let Some(ref data) = self.summary_data.clone() {
+                            // Message counts
+                            ui.horizontal(|ui| {
+                                ui.label(format!("Messages: {} you / {} Claude",
+                                    data.user_count, data.assistant_count));
+                            });
+                            ui.add_space(5.0);
+
+                            // Summary
+                            ui.label(egui::RichText::new("Summary").strong());
+                            egui::ScrollArea::vertical()
+                                .max_height(80.0)
+                                .id_salt("window_summary_scroll")
+                                .show(ui, |ui| {
+                                    ui.label(&data.summary);
+                                });
+                            ui.add_space(5.0);
+
+                            // Current Focus
+                            if !data.current_focus.is_empty() {
+                                ui.label(egui::RichText::new("Working On").strong());
+                                ui.label(&data.current_focus);
+                                ui.add_space(5.0);
+                            }
+
+                            // Completed Work
+                            if !data.completed_work.is_empty() {
+                                ui.label(egui::RichText::new("Completed").strong().color(theme::state::SUCCESS));
+                                egui::ScrollArea::vertical()
+                                    .max_height(60.0)
+                                    .id_salt("window_completed_scroll")
+                                    .show(ui, |ui| {
+                                        for line in data.completed_work.split('\n').filter(|l| !l.is_empty()) {
+                                            ui.label(line);
+                                        }
+                                    });
+                                ui.add_space(5.0);
+                            }
+
+                            // Unsuccessful Attempts
+                            if !data.unsuccessful_attempts.is_empty() {
+                                ui.label(egui::RichText::new("Tried but Failed").strong().color(Color32::from_rgb(255, 100, 100)));
+                                egui::ScrollArea::vertical()
+                                    .max_height(60.0)
+                                    .id_salt("window_failed_scroll")
+                                    .show(ui, |ui| {
+                                        for line in data.unsuccessful_attempts.split('\n').filter(|l| !l.is_empty()) {
+                                            ui.label(line);
+                                        }
+                                    });
+                            }
+
+                            // Importance grading for the clicked node
+                            if let Some(ref node_id) = self.summary_node_id.clone() {
+                                if let Some(node) = self.graph.get_node(&node_id) {
+                                    if node.importance_score.is_some() || node.importance_reason.is_some() {
+                                        ui.add_space(5.0);
+                                        ui.label(egui::RichText::new("Importance").strong().color(Color32::from_rgb(255, 193, 7)));
+                                        if let Some(score) = node.importance_score {
+                                            ui.label(format!("Score: {:.0}%", score * 100.0));
+                                        }
+                                        if let Some(ref reason) = node.importance_reason {
+                                            ui.label(reason);
+                                        }
+                                    }
+                                }
+                            }
+                        } else {
+                            ui.colored_label(egui::Color32::GRAY, "(Disabled - requires AI generation)");
+                        }
+                    });
+
+                ui.separator();
+
+                // Session Summary section
+                egui::CollapsingHeader::new("Session Summary")
+                    .default_open(true)
+                    .show(ui, |ui| {
+                        if self.session_summary_loading {
+                            ui.horizontal(|ui| {
+                                ui.spinner();
+                                ui.label("Generating summary...");
+                            });
+                            ui.add_space(8.0);
+                            // Skeleton preview of summary content
+                            theme::skeleton_lines(ui, 4, ui.available_width() * 0.9);
+                        } else if let Some(ref data) = self.session_summary_data.clone() {
+                            // Check for errors first
+                            if let Some(ref err) = data.error {
+                                ui.colored_label(theme::state::ERROR, format!("Error: {}", err));
+                            } else if !data.exists {
+                                ui.label("No summary in database for this session.");
+                            } else {
+                                // Show "just generated" badge if applicable
+                                if data.generated {
+                                    ui.colored_label(theme::state::SUCCESS, "✓ Just generated");
+                                    ui.add_space(5.0);
+                                }
+
+                                // Project and topics
+                                if let Some(ref project) = data.detected_project {
+                                    ui.horizontal(|ui| {
+                                        ui.label(egui::RichText::new("Project:").strong());
+                                        ui.label(project);
+                                    });
+                                }
+
+                                if let Some(ref topics) = data.topics {
+                                    if !topics.is_empty() {
+                                        ui.horizontal_wrapped(|ui| {
+                                            ui.label(egui::RichText::new("Topics:").strong());
+                                            for topic in topics.iter().take(5) {
+                                                ui.label(format!("[{}]", topic));
+                                            }
+                                        });
+                                    }
+                                }
+
+                                ui.add_space(5.0);
+
+                                // Summary paragraph
+                                if let Some(ref summary) = data.summary {
+                                    ui.label(egui::RichText::new("Summary").strong());
+                                    egui::ScrollArea::vertical()
+                                        .max_height(100.0)
+                                        .id_salt("window_session_summary_scroll")
+                                        .show(ui, |ui| {
+                                            ui.label(summary);
+                                        });
+                                    ui.add_space(5.0);
+                                }
+
+                                // Completed work
+                                if let Some(ref completed) = data.completed_work {
+                                    if !completed.is_empty() {
+                                        ui.label(egui::RichText::new("Completed Work").strong().color(theme::state::SUCCESS));
+                                        egui::ScrollArea::vertical()
+                                            .max_height(80.0)
+                                            .id_salt("window_session_completed_scroll")
+                                            .show(ui, |ui| {
+                                                for line in completed.split('\n').filter(|l| !l.is_empty()) {
+                                                    ui.label(line);
+                                                }
+                                            });
+                                        ui.add_space(5.0);
+                                    }
+                                }
+
+                                // User requests
+                                if let Some(ref requests) = data.user_requests {
+                                    if !requests.is_empty() {
+                                        ui.label(egui::RichText::new("User Requests").strong().color(Color32::from_rgb(100, 149, 237)));
+                                        egui::ScrollArea::vertical()
+                                            .max_height(60.0)
+                                            .id_salt("window_session_requests_scroll")
+                                            .show(ui, |ui| {
+                                                for line in requests.split('\n').filter(|l| !l.is_empty()) {
+                                                    ui.label(line);
+                                                }
+                                            });
+                                    }
+                                }
+                            }
+                        } else {
+                            ui.label("Double-click a node to see");
+                            ui.label("the full session summary.");
+                        }
+                    });
+
+                ui.add_space(10.0);
+                ui.separator();
+
+                // Clear button - only clears summary window state
+                if ui.button("Clear & Close").clicked() {
+                    self.summary_data = None;
+                    self.summary_node_id = None;
+                    self.session_summary_data = None;
+                    self.summary_window_open = false;
+                    self.summary_window_dragged = false;
+                }
+            });
+
+        self.summary_window_open = open;
+
+        // Detect if window was dragged from default position
+        if let Some(inner) = window_response {
+            let pos = inner.response.rect.left_top();
+            let dist = (pos - default_pos).length();
+            if dist > 10.0 {
+                self.summary_window_dragged = true;
+            }
+        }
+    }
+
+    /// Render the floating neighborhood window (Neighborhood Summary, triggered by Ctrl+Click)
+    fn render_neighborhood_window(&mut self, ctx: &egui::Context) {
+        if !self.neighborhood_window_open {
+            return;
+        }
+
+        // Calculate default position (offset from summary window position)
+        let screen_rect = ctx.screen_rect();
+        let default_pos = egui::pos2(screen_rect.right() - 420.0, 580.0);
+
+        let mut open = self.neighborhood_window_open;
+
+        let window_response = egui::Window::new("Neighborhood")
+            .open(&mut open)
+            .default_pos(default_pos)
+            .default_size([400.0, 350.0])
+            .resizable(true)
+            .collapsible(true)
+            .show(ctx, |ui| {
+                // Depth slider
+                ui.horizontal(|ui| {
+                    ui.label("Depth:");
+                    ui.add(egui::Slider::new(&mut self.neighborhood_depth, 1..=5).text("edges"));
+                });
+
+                // Temporal edge toggle
+                ui.checkbox(&mut self.neighborhood_include_temporal, "Include temporal edges");
+
+                ui.add_space(5.0);
+
+                if self.neighborhood_summary_loading {
+                    ui.horizontal(|ui| {
+                        ui.spinner();
+                        ui.label(format!("Summarizing {} nodes...", self.neighborhood_summary_count));
+                    });
+                    ui.add_space(8.0);
+                    theme::skeleton_lines(ui, 3, ui.available_width() * 0.9);
+                } else if let Some(ref error) = self.neighborhood_summary_error.clone() {
+                    ui.colored_label(theme::state::ERROR, format!("Error: {}", error));
+                } else if let Some(ref data) = self.neighborhood_summary_data.clone() {
+                    // Node/session counts
+                    ui.horizontal(|ui| {
+                        ui.label(format!("{} nodes across {} sessions",
+                            data.node_count, data.session_count));
+                    });
+                    ui.add_space(5.0);
+
+                    // Summary
+                    ui.label(egui::RichText::new("Summary").strong());
+                    egui::ScrollArea::vertical()
+                        .max_height(100.0)
+                        .id_salt("window_neighborhood_summary_scroll")
+                        .show(ui, |ui| {
+                            ui.label(&data.summary);
+                        });
+                    ui.add_space(5.0);
+
+                    // Themes
+                    if !data.themes.is_empty() {
+                        ui.label(egui::RichText::new("Themes").strong().color(Color32::from_rgb(100, 149, 237)));
+                        ui.label(&data.themes);
+                    }
+                } else {
+                    ui.colored_label(
+                        egui::Color32::GRAY,
+                        "Ctrl+Click a node to summarize\nit and its neighbors.",
+                    );
+                }
+
+                ui.add_space(10.0);
+                ui.separator();
+
+                // Clear button - only clears neighborhood window state
+                if ui.button("Clear & Close").clicked() {
+                    self.neighborhood_summary_data = None;
+                    self.neighborhood_summary_error = None;
+                    self.neighborhood_summary_center_node = None;
+                    self.neighborhood_summary_count = 0;
+                    self.neighborhood_window_open = false;
+                    self.neighborhood_window_dragged = false;
+                }
+            });
+
+        self.neighborhood_window_open = open;
+
+        // Detect if window was dragged from default position
+        if let Some(inner) = window_response {
+            let pos = inner.response.rect.left_top();
+            let dist = (pos - default_pos).length();
+            if dist > 10.0 {
+                self.neighborhood_window_dragged = true;
+            }
+        }
+    }
+
+    /// Render the beads panel (issues/tasks)
+    fn render_beads_panel(&mut self, ui: &mut egui::Ui) {
+        ui.horizontal(|ui| {
+            ui.heading("Beads");
+            ui.with_layout(egui::Layout::right_to_left(egui::Align::Center), |ui| {
+                ui.label(
+                    egui::RichText::new("B to toggle")
+                        .small()
+                        .color(self.palette.text.muted)
+                );
+            });
+        });
+        ui.add_space(8.0);
+        ui.separator();
+        ui.add_space(8.0);
+
+        match self.beads_search.resolved.clone() {
+            Some(path) => {
+                ui.label(
+                    egui::RichText::new(path.display().to_string())
+                        .small()
+                        .color(self.palette.text.muted)
+                ).on_hover_text("Beads directory in use");
+            }
+            None => {
+                self.render_beads_not_found(ui);
+                return;
+            }
+        }
+        ui.add_space(8.0);
+
+        egui::ScrollArea::vertical().show(ui, |ui| {
+            // Placeholder content - beads data integration would go here
+            ui.label(
+                egui::RichText::new("Issue tracking panel")
+                    .color(self.palette.text.secondary)
+            );
+            ui.add_space(16.0);
+
+            // Sample structure showing what the panel would contain
+            self.status_heading(ui, IssueStatus::Open, "Ready");
+            ui.add_space(4.0);
+            ui.label(
+                egui::RichText::new("No ready issues")
+                    .color(self.palette.text.muted)
+                    .italics()
+            );
+
+            ui.add_space(16.0);
+            self.status_heading(ui, IssueStatus::InProgress, "In Progress");
+            ui.add_space(4.0);
+            ui.label(
+                egui::RichText::new("No issues in progress")
+                    .color(self.palette.text.muted)
+                    .italics()
+            );
+
+            ui.add_space(16.0);
+            self.status_heading(ui, IssueStatus::Blocked, "Blocked");
+            ui.add_space(4.0);
+            ui.label(
+                egui::RichText::new("No blocked issues")
+                    .color(self.palette.text.muted)
+                    .italics()
+            );
+
+            ui.add_space(16.0);
+            self.render_closed_beads(ui);
+        });
+    }
+
+    /// Closed issues, most recently updated first. Closed history only grows,
+    /// so just the newest `beads_closed_limit` are laid out.
+    fn render_closed_beads(&mut self, ui: &mut egui::Ui) {
+        let mut closed: Vec<(Option<f64>, &str, &str)> = self.graph.data.beads.iter()
+            .filter(|b| b.status == IssueStatus::Closed)
+            .map(|b| (b.updated_at_secs().or_else(|| b.timestamp_secs()), b.id.as_str(), b.title.as_str()))
+            .collect();
+        closed.sort_by(|a, b| b.0.unwrap_or(f64::MIN).total_cmp(&a.0.unwrap_or(f64::MIN)));
+        let total = closed.len();
+        let rows: Vec<(String, String, bool)> = closed.into_iter()
+            .take(self.beads_closed_limit)
+            .map(|(_, id, title)| (id.to_string(), title.to_string(), self.graph.is_bead_visible(id)))
+            .collect();
+
+        let title = if self.timeline_enabled {
+            let in_window = self.graph.data.beads.iter()
+                .filter(|b| b.status == IssueStatus::Closed && self.graph.is_bead_visible(&b.id))
+                .count();
+            format!("Closed ({} in window, {} total)", in_window, total)
+        } else {
+            format!("Closed ({})", total)
+        };
+        self.status_heading(ui, IssueStatus::Closed, &title);
+        ui.add_space(4.0);
+        if rows.is_empty() {
+            ui.label(
+                egui::RichText::new("No closed issues")
+                    .color(self.palette.text.muted)
+                    .italics()
+            );
+            return;
+        }
+        for (id, title, in_window) in &rows {
+            let color = if self.timeline_enabled && !in_window {
+                self.palette.text.muted
+            } else {
+                self.palette.text.secondary
+            };
+            ui.label(egui::RichText::new(format!("{}  {}", id, title)).small().color(color));
+        }
+        if total > rows.len() {
+            let more = BEADS_CLOSED_PAGE.min(total - rows.len());
+            ui.horizontal(|ui| {
+                if ui.small_button(format!("Show {} more", more)).clicked() {
+                    self.beads_closed_limit += BEADS_CLOSED_PAGE;
+                }
+                ui.label(
+                    egui::RichText::new(format!("{} of {}", rows.len(), total))
+                        .small()
+                        .color(self.palette.text.muted)
+                );
+            });
+        }
+    }
+
+    /// Empty state for the beads panel: what was searched, plus a directory override
+    fn render_beads_not_found(&mut self, ui: &mut egui::Ui) {
+        ui.label(
+            egui::RichText::new("No .beads directory found")
+                .color(self.palette.text.secondary)
+        );
+        ui.add_space(4.0);
+        ui.label(
+            egui::RichText::new("Searched:")
+                .small()
+                .color(self.palette.text.muted)
+        );
+        egui::ScrollArea::vertical().max_height(160.0).show(ui, |ui| {
+            for path in &self.beads_search.searched {
+                ui.label(
+                    egui::RichText::new(path.display().to_string())
+                        .small()
+                        .monospace()
+                        .color(self.palette.text.muted)
+                );
+            }
+        });
+
+        ui.add_space(12.0);
+        ui.label("Beads directory:");
+        let response = ui.add(
+            egui::TextEdit::singleline(&mut self.beads_path_input)
+                .hint_text("~/project or ~/project/.beads")
+                .desired_width(f32::INFINITY)
+        );
+        let submitted = response.lost_focus() && ui.input(|i| i.key_pressed(egui::Key::Enter));
+        if ui.button("Use this folder").clicked() || submitted {
+            let trimmed = self.beads_path_input.trim();
+            self.settings.beads_path = (!trimmed.is_empty()).then(|| expand_home(trimmed));
+            self.beads_search = locate_beads_dir(self.settings.beads_path.as_deref());
+            self.beads_last_mtime = None;
+            self.settings_dirty = true;
+        }
+    }
+
+    /// Vault path input and the latest scan's outcome
+    fn render_obsidian_section(&mut self, ui: &mut egui::Ui) {
+        ui.label("Vault:");
+        let response = ui.add(
+            egui::TextEdit::singleline(&mut self.obsidian_vault_input)
+                .hint_text("~/Documents/Vault")
+                .desired_width(f32::INFINITY)
+        );
+        let submitted = response.lost_focus() && ui.input(|i| i.key_pressed(egui::Key::Enter));
+        ui.horizontal(|ui| {
+            if ui.button("Use this vault").clicked() || submitted {
+                let trimmed = self.obsidian_vault_input.trim();
+                self.settings.obsidian_vault = (!trimmed.is_empty()).then(|| expand_home(trimmed));
+                self.settings_dirty = true;
+                if self.settings.obsidian_vault.is_some() {
+                    self.start_obsidian_scan();
+                }
+            }
+            if self.settings.obsidian_vault.is_some() && ui.button("Disconnect").clicked() {
+                self.settings.obsidian_vault = None;
+                self.obsidian_vault_input.clear();
+                self.obsidian_rx = None;
+                self.obsidian_status = None;
+                self.obsidian_notes = GraphData::default();
+                self.obsidian_note_paths.clear();
+                self.settings_dirty = true;
+                self.merge_derived_nodes();
+            }
+        });
+
+        if self.obsidian_rx.is_some() {
+            ui.horizontal(|ui| {
+                ui.spinner();
+                ui.label(egui::RichText::new("Scanning vault...").small().color(self.palette.text.muted));
+            });
+        } else if let Some(status) = &self.obsidian_status {
+            ui.label(egui::RichText::new(status).small().color(self.palette.text.muted));
+        }
+        ui.label(
+            egui::RichText::new("Notes modified in the loaded range, linked to messages that mention their title or aliases")
+                .small()
+                .color(self.palette.text.muted)
+        );
+    }
+
+    /// Section heading with a lettered status badge (readable without color)
+    fn status_heading(&self, ui: &mut egui::Ui, status: IssueStatus, title: &str) {
+        ui.horizontal(|ui| {
+            let color = status.status_color(&self.palette);
+            ui.label(
+                egui::RichText::new(format!(" {} ", status.badge()))
+                    .small()
+                    .strong()
+                    .color(self.palette.bg.panel)
+                    .background_color(color),
+            )
+            .on_hover_text(status.label());
+            ui.label(egui::RichText::new(title).strong());
+        });
+    }
+
+    /// Render the mail panel (inbox/outbox)
+    fn render_mail_panel(&mut self, ui: &mut egui::Ui) {
+        ui.horizontal(|ui| {
+            ui.heading("Mail");
+            ui.with_layout(egui::Layout::right_to_left(egui::Align::Center), |ui| {
+                ui.label(
+                    egui::RichText::new("M to toggle")
+                        .small()
+                        .color(self.palette.text.muted)
+                );
+            });
+        });
+        ui.add_space(8.0);
+        ui.separator();
+        ui.add_space(8.0);
+
+        // Tab selection for Inbox/Outbox (placeholder - actual tab state would go here)
+        ui.horizontal(|ui| {
+            let _ = ui.selectable_label(true, "Inbox");
+            let _ = ui.selectable_label(false, "Outbox");
+        });
+        ui.add_space(8.0);
+
+        egui::ScrollArea::vertical().show(ui, |ui| {
+            // Placeholder content - mail data integration would go here
+            ui.label(
+                egui::RichText::new("Mail panel")
+                    .color(self.palette.text.secondary)
+            );
+            ui.add_space(16.0);
+
+            ui.label(
+                egui::RichText::new("No messages")
+                    .color(self.palette.text.muted)
+                    .italics()
+            );
+        });
+    }
+
+    /// One scrolling list of messages, bead changes and mail in time order.
+    /// Rows are laid out only while visible; following the playhead scrolls
+    /// the feed whenever the timeline position moves.
+    fn render_activity_panel(&mut self, ui: &mut egui::Ui) {
+        ui.horizontal(|ui| {
+            ui.heading("Activity");
+            ui.with_layout(egui::Layout::right_to_left(egui::Align::Center), |ui| {
+                ui.label(
+                    egui::RichText::new("A to toggle")
+                        .small()
+                        .color(self.palette.text.muted)
+                );
+            });
+        });
+        ui.add_space(8.0);
+        ui.separator();
+        if ui.checkbox(&mut self.settings.activity_follow_playhead, "Follow playhead")
+            .on_hover_text("Scroll the feed to the timeline position as it moves")
+            .changed()
+        {
+            self.activity_scrolled_to = None;
+            self.mark_settings_dirty();
+        }
+        ui.add_space(4.0);
+
+        if self.activity_feed.is_empty() {
+            ui.label(
+                egui::RichText::new("No activity loaded")
+                    .color(self.palette.text.muted)
+                    .italics()
+            );
+            return;
+        }
+
+        let timeline = &self.graph.timeline;
+        let playhead = timeline.time_at_position(timeline.effective_position());
+        let playhead_row = self.activity_feed.partition_point(|e| e.time <= playhead);
+        let row_height = ui.text_style_height(&egui::TextStyle::Body) + 4.0;
+        let mut area = egui::ScrollArea::vertical().auto_shrink([false, false]);
+        if self.settings.activity_follow_playhead && self.activity_scrolled_to != Some(playhead) {
+            // Newest item at or before the playhead sits at the bottom of the view
+            let pitch = row_height + ui.spacing().item_spacing.y;
+            area = area.vertical_scroll_offset((playhead_row as f32 * pitch - ui.available_height()).max(0.0));
+            self.activity_scrolled_to = Some(playhead);
+        }
+        area.show_rows(ui, row_height, self.activity_feed.len(), |ui, rows| {
+            for row in rows {
+                let entry = self.activity_feed[row];
+                self.render_activity_row(ui, entry, row_height, row >= playhead_row);
+            }
+        });
+    }
+
+    /// Feed row: kind icon, time and a one-line summary. Clicking a message
+    /// selects and frames its node; beads and mail open their panel.
+    fn render_activity_row(&mut self, ui: &mut egui::Ui, entry: FeedEntry, height: f32, after_playhead: bool) {
+        let (icon, icon_color, summary, node_id) = match entry.kind {
+            FeedKind::Message => {
+                let Some(node) = self.graph.data.nodes.get(entry.index) else {
+                    return;
+                };
+                let preview = node.content_preview.replace('\n', " ");
+                ("●", node.role.color(&self.palette), format!("{}: {}", node.role.label(), preview), Some(node.id.clone()))
+            }
+            FeedKind::Bead => {
+                let Some(bead) = self.graph.data.beads.get(entry.index) else {
+                    return;
+                };
+                (bead.status.badge(), bead.status.status_color(&self.palette), format!("{} {} · {}", bead.id, bead.title, bead.status.label()), None)
+            }
+            FeedKind::Mail => {
+                let Some(mail) = self.graph.data.mail.get(entry.index) else {
+                    return;
+                };
+                ("✉", self.palette.accent.orange, format!("{} → {}: {}", mail.sender, mail.recipient, mail.subject), None)
+            }
+        };
+
+        let (rect, response) = ui.allocate_exact_size(egui::vec2(ui.available_width(), height), egui::Sense::click());
+        let selected = node_id.is_some() && node_id == self.graph.selected_node;
+        if selected {
+            ui.painter().rect_filled(rect, 2.0, self.palette.highlight.selected.gamma_multiply(0.25));
+        } else if response.hovered() {
+            ui.painter().rect_filled(rect, 2.0, self.palette.bg.surface);
+        }
+        // Items past the playhead are dimmed while the timeline filters
+        let dim = if self.timeline_enabled && after_playhead { 0.4 } else { 1.0 };
+        let painter = ui.painter_at(rect);
+        let font = egui::FontId::proportional(12.0);
+        let y = rect.center().y;
+        self.text_layout.text(&painter, Pos2::new(rect.left() + 2.0, y), egui::Align2::LEFT_CENTER, icon, font.clone(), icon_color.gamma_multiply(dim));
+        let time = chrono::DateTime::from_timestamp(entry.time as i64, 0)
+            .map(|t| t.with_timezone(&chrono::Local).format("%H:%M").to_string())
+            .unwrap_or_default();
+        self.text_layout.text(
+            &painter,
+            Pos2::new(rect.left() + 18.0, y),
+            egui::Align2::LEFT_CENTER,
+            &time,
+            egui::FontId::monospace(11.0),
+            self.palette.text.muted.gamma_multiply(dim),
+        );
+        self.text_layout.text(
+            &painter,
+            Pos2::new(rect.left() + 60.0, y),
+            egui::Align2::LEFT_CENTER,
+            &truncate(&summary, 120),
+            font,
+            self.palette.text.secondary.gamma_multiply(dim),
+        );
+
+        let response = response.on_hover_text(self.graph.timeline.format_time(entry.time));
+        if response.clicked() {
+            match entry.kind {
+                FeedKind::Message => {
+                    if let Some(id) = node_id {
+                        self.fit_request = Some(vec![id.clone()]);
+                        self.commands.push(AppCommand::SelectNode(Some(id)));
+                    }
+                }
+                FeedKind::Bead => {
+                    self.beads_panel_open = true;
+                    self.mark_settings_dirty();
+                }
+                FeedKind::Mail => {
+                    self.mail_panel_open = true;
+                    self.mark_settings_dirty();
+                }
+            }
+        }
+    }
+
+    /// Switch between force-directed and timeline views, swapping per-view
+    /// physics/sizing and the layout algorithm
+    fn switch_view_mode(&mut self, mode: ViewMode) {
+        if mode == self.settings.view_mode {
+            return;
+        }
+        // Capture current values so the outgoing view keeps them
+        self.sync_settings_from_ui();
+        let default_preset = self.settings.switch_view_mode(mode);
+        self.sync_ui_from_settings();
+        self.layout.algorithm = layout_algorithm_for(mode);
+        if let Some(name) = default_preset {
+            self.apply_named_preset(&name);
+        }
+        // Temporal edges follow the incoming view's temporal_attraction_enabled
+        self.temporal_edges_dirty = true;
+        self.mark_settings_dirty();
+    }
+
+    /// Banner explaining that the settings file didn't parse and where it went
+    fn render_settings_recovered(&mut self, ui: &mut egui::Ui) {
+        let Some(ref recovered) = self.settings_recovered else {
+            return;
+        };
+        let mut dismiss = false;
+        egui::Frame::group(ui.style()).stroke(Stroke::new(1.0, theme::state::WARNING)).show(ui, |ui| {
+            ui.label(egui::RichText::new("Settings file was unreadable; defaults loaded").small().strong().color(theme::state::WARNING));
+            ui.label(egui::RichText::new(&recovered.error).small().color(self.palette.text.muted));
+            match recovered.backup {
+                Some(ref backup) => {
+                    ui.label(egui::RichText::new(format!("Saved a copy as {}", backup.display())).small());
+                }
+                None => {
+                    ui.label(egui::RichText::new("The old file couldn't be backed up").small().color(theme::state::ERROR));
+                }
+            }
+            ui.horizontal(|ui| {
+                if let Some(folder) = recovered.backup.as_ref().and_then(|b| b.parent()) {
+                    if ui.small_button("Open folder").clicked() {
+                        open_folder(folder);
+                    }
+                }
+                if ui.small_button("Dismiss").clicked() {
+                    dismiss = true;
+                }
+            });
+        });
+        if dismiss {
+            self.settings_recovered = None;
+        }
+    }
+
+    /// Saved views as buttons; the active one is highlighted until the data
+    /// selection or preset diverges from it, then marked as modified
+    fn render_saved_views(&mut self, ui: &mut egui::Ui) {
+        enum ViewAction {
+            Apply(usize),
+            StartNew,
+            StartRename(usize),
+            CommitName,
+            Update(usize),
+            Delete(usize),
+        }
+        let current = self.current_saved_view(String::new());
+        let name_id = ui.id().with("saved_view_name");
+        let mut action = None;
+
+        ui.horizontal_wrapped(|ui| {
+            ui.label("Views:");
+            for (idx, view) in self.settings.saved_views.iter().enumerate() {
+                let is_active = self.active_saved_view.as_deref() == Some(view.name.as_str());
+                let modified = is_active && !view.matches(&current);
+                let mut text = egui::RichText::new(&view.name);
+                if modified {
+                    text = egui::RichText::new(format!("{} (modified)", view.name)).italics();
+                }
+                let mut hover = format!("Last {}", format_hours_label(view.time_range_hours));
+                match view.projects.len() {
+                    0 => hover.push_str(", all projects"),
+                    1 => hover.push_str(", 1 project"),
+                    n => hover.push_str(&format!(", {} projects", n)),
+                }
+                if let Some(session) = &view.session {
+                    hover.push_str(&format!(", session {}", truncate(session, 12)));
+                }
+                if let Some(preset) = &view.preset {
+                    hover.push_str(&format!(", preset '{}'", preset));
+                }
+                let response = ui.selectable_label(is_active && !modified, text)
+                    .on_hover_text(hover);
+                if response.clicked() {
+                    action = Some(ViewAction::Apply(idx));
+                }
+                response.context_menu(|ui| {
+                    if ui.button("Rename").clicked() {
+                        action = Some(ViewAction::StartRename(idx));
+                        ui.close_menu();
+                    }
+                    if ui.button("Update from current").clicked() {
+                        action = Some(ViewAction::Update(idx));
+                        ui.close_menu();
+                    }
+                    if ui.button("Delete").clicked() {
+                        action = Some(ViewAction::Delete(idx));
+                        ui.close_menu();
+                    }
+                });
+            }
+            if ui.small_button("+")
+                .on_hover_text("Save the time range, projects, session and preset as a view")
+                .clicked()
+            {
+                action = Some(ViewAction::StartNew);
+            }
+        });
+
+        // Inline name editor for a new or renamed view
+        if let Some((target, buffer)) = &mut self.saved_view_editing {
+            let name = buffer.trim().to_string();
+            let conflict = self.settings.saved_views.iter().enumerate()
+                .any(|(i, v)| Some(i) != *target && v.name == name);
+            let valid = !name.is_empty() && !conflict;
+            let mut cancel = false;
+            ui.horizontal(|ui| {
+                let response = ui.add(
+                    egui::TextEdit::singleline(buffer)
+                        .id(name_id)
+                        .hint_text("View name")
+                        .desired_width(140.0)
+                );
+                let enter = response.lost_focus() && ui.input(|i| i.key_pressed(egui::Key::Enter));
+                if ui.add_enabled(valid, egui::Button::new("✔").small()).clicked() || (enter && valid) {
+                    action = Some(ViewAction::CommitName);
+                }
+                if ui.small_button("✖").clicked() || ui.input(|i| i.key_pressed(egui::Key::Escape)) {
+                    cancel = true;
+                }
+            });
+            if conflict {
+                ui.label(
+                    egui::RichText::new(format!("'{}' already exists", name))
+                        .small()
+                        .color(theme::state::WARNING),
+                );
+            }
+            if cancel {
+                self.saved_view_editing = None;
+            }
+        }
+
+        match action {
+            Some(ViewAction::Apply(idx)) => self.apply_saved_view(idx),
+            Some(ViewAction::StartNew) => {
+                let name = format!("View {}", self.settings.saved_views.len() + 1);
+                self.saved_view_editing = Some((None, name));
+                ui.memory_mut(|m| m.request_focus(name_id));
+            }
+            Some(ViewAction::StartRename(idx)) => {
+                self.saved_view_editing = Some((Some(idx), self.settings.saved_views[idx].name.clone()));
+                ui.memory_mut(|m| m.request_focus(name_id));
+            }
+            Some(ViewAction::CommitName) => {
+                if let Some((target, buffer)) = self.saved_view_editing.take() {
+                    let name = buffer.trim().to_string();
+                    match target {
+                        Some(idx) => {
+                            let old = std::mem::replace(&mut self.settings.saved_views[idx].name, name.clone());
+                            if self.active_saved_view.as_deref() == Some(old.as_str()) {
+                                self.active_saved_view = Some(name);
+                            }
+                        }
+                        None => {
+                            self.settings.saved_views.push(self.current_saved_view(name.clone()));
+                            self.active_saved_view = Some(name);
+                        }
+                    }
+                    self.mark_settings_dirty();
+                }
+            }
+            Some(ViewAction::Update(idx)) => {
+                let name = self.settings.saved_views[idx].name.clone();
+                self.settings.saved_views[idx] = self.current_saved_view(name.clone());
+                self.active_saved_view = Some(name);
+                self.mark_settings_dirty();
+            }
+            Some(ViewAction::Delete(idx)) => {
+                let removed = self.settings.saved_views.remove(idx);
+                if self.active_saved_view.as_deref() == Some(removed.name.as_str()) {
+                    self.active_saved_view = None;
+                }
+                self.saved_view_editing = None;
+                self.mark_settings_dirty();
+            }
+            None => {}
+        }
+        ui.separator();
+    }
+
+    fn render_sidebar(&mut self, ui: &mut egui::Ui) {
+        self.render_saved_views(ui);
+
+        // View mode switcher
+        let mut new_view_mode = None;
+        ui.horizontal(|ui| {
+            ui.label("View:");
+            for &mode in ViewMode::all() {
+                let selected = self.settings.view_mode == mode && self.overview.is_none();
+                if ui.selectable_label(selected, mode.label()).clicked() {
+                    new_view_mode = Some(mode);
+                }
+            }
+            if ui.selectable_label(self.overview.is_some(), "Overview")
+                .on_hover_text("One node per session")
+                .clicked()
+            {
+                if self.overview.is_some() {
+                    self.exit_overview();
+                } else {
+                    self.enter_overview();
+                }
+            }
+
+            // Theme selector (applied at the start of the next frame)
+            ui.with_layout(egui::Layout::right_to_left(egui::Align::Center), |ui| {
+                let current = self.settings.theme;
+                egui::ComboBox::from_id_salt("theme")
+                    .selected_text(current.label())
+                    .width(70.0)
+                    .show_ui(ui, |ui| {
+                        for &choice in theme::Theme::all() {
+                            let label = ui.selectable_label(current == choice, choice.label());
+                            let label = if choice == theme::Theme::System {
+                                label.on_hover_text("Follow the operating system's dark/light setting, including changes while running")
+                            } else {
+                                label
+                            };
+                            if label.clicked() {
+                                self.settings.theme = choice;
+                                ui.ctx().set_theme(choice.preference());
+                                self.mark_settings_dirty();
+                            }
+                        }
+                        ui.separator();
+                        let path = theme_file::path()
+                            .map(|p| p.display().to_string())
+                            .unwrap_or_else(|| "theme.toml".to_string());
+                        if ui.button("Reload theme").on_hover_text(format!("Re-read {}", path)).clicked() {
+                            self.reload_theme_file();
+                        }
+                    });
+            });
+        });
+        if let Some(mode) = new_view_mode {
+            self.exit_overview();
+            self.switch_view_mode(mode);
+        }
+
+        // Theme file problems are reported here rather than silently ignored
+        if let Some((message, is_error)) = self.theme_file_status.clone() {
+            if is_error {
+                let color = theme::state::WARNING;
+                if self.theme_file_errors.is_empty() {
+                    ui.label(egui::RichText::new(message).small().color(color));
+                } else {
+                    egui::CollapsingHeader::new(egui::RichText::new(message).small().color(color))
+                        .id_salt("theme_file_errors")
+                        .show(ui, |ui| {
+                            for error in &self.theme_file_errors {
+                                ui.label(egui::RichText::new(error.to_string()).small().color(self.palette.text.muted));
+                            }
+                        });
+                }
+            }
+        }
+
+        if self.settings_recovered.is_some() {
+            self.render_settings_recovered(ui);
+        }
+        if !self.dropped_transcripts.is_empty() || self.drop_status.is_some() {
+            self.render_dropped_transcripts(ui);
+        }
+
+        // Tab bar at top
+        let prev_tab = self.sidebar_tab;
+        ui.horizontal(|ui| {
+            ui.selectable_value(&mut self.sidebar_tab, SidebarTab::Data, "Data");
+            ui.selectable_value(&mut self.sidebar_tab, SidebarTab::Nodes, "Nodes");
+            ui.selectable_value(&mut self.sidebar_tab, SidebarTab::Edges, "Edges");
+            ui.selectable_value(&mut self.sidebar_tab, SidebarTab::Filters, "Filters");
+
+            // Undo depth indicator
+            let undo_depth = self.settings_history.undo_depth();
+            let redo_depth = self.settings_history.redo_depth();
+            if undo_depth > 0 || redo_depth > 0 {
+                ui.with_layout(egui::Layout::right_to_left(egui::Align::Center), |ui| {
+                    ui.label(
+                        egui::RichText::new(format!("↶{}", undo_depth))
+                            .small()
+                            .color(self.palette.text.muted),
+                    )
+                    .on_hover_text(format!(
+                        "{} undo / {} redo steps (Ctrl+Z / Ctrl+Shift+Z)",
+                        undo_depth, redo_depth
+                    ));
+                });
+            }
+        });
+        if self.sidebar_tab != prev_tab {
+            self.mark_settings_dirty();
+        }
+        ui.separator();
+
+        // Tab content
+        match self.sidebar_tab {
+            SidebarTab::Data => self.render_sidebar_data(ui),
+            SidebarTab::Nodes => self.render_sidebar_nodes(ui),
+            SidebarTab::Edges => self.render_sidebar_edges(ui),
+            SidebarTab::Filters => self.render_sidebar_filters(ui),
+        }
+    }
+
+    fn render_sidebar_data(&mut self, ui: &mut egui::Ui) {
+        // Database status
+        ui.horizontal(|ui| {
+            if self.db_connected {
+                ui.colored_label(theme::state::SUCCESS, "● DB Connected");
+            } else {
+                ui.colored_label(theme::state::ERROR, "● DB Disconnected");
+                if ui.button("Retry").clicked() {
+                    self.commands.push(AppCommand::Reload);
+                }
+            }
+        });
+
+        if let Some(ref err) = self.db_error {
+            ui.colored_label(theme::state::ERROR, format!("Error: {}", err));
+        }
+
+        ui.add_space(10.0);
+
+        // Data Selection section
+        egui::CollapsingHeader::new("Data Selection")
+            .default_open(true)
+            .show(ui, |ui| {
+                ui.label(format!("Range: {}", format_hours_label(self.slider_hours)));
+                let range_slider = ui.add(
+                    egui::Slider::new(&mut self.slider_hours, 1.0..=MAX_RANGE_HOURS)
+                        .logarithmic(true)
+                        .clamping(egui::SliderClamping::Always)
+                        .show_value(false),
+                );
+                if range_slider.changed() && (self.slider_hours - self.time_range_hours).abs() > 0.5 {
+                    self.commands.push(AppCommand::LoadGraph { hours: self.slider_hours, session: self.session_filter.clone() });
+                    self.mark_settings_dirty();
+                }
+
+                // Session restriction from --session
+                if let Some(session_id) = self.session_filter.clone() {
+                    ui.horizontal(|ui| {
+                        ui.label(format!("Session: {}", truncate(&session_id, 12)));
+                        if ui.small_button("✖").on_hover_text("Load all sessions").clicked() {
+                            self.commands.push(AppCommand::LoadGraph { hours: self.time_range_hours, session: None });
+                        }
+                    });
+                }
+
+                // Session isolation from a histogram or overview drill-down
+                if let Some(session_id) = self.histogram_session_filter.clone() {
+                    ui.horizontal(|ui| {
+                        ui.label(format!("Showing session: {}", truncate(&session_id, 12)));
+                        if ui.small_button("✖").on_hover_text("Show all sessions").clicked() {
+                            self.histogram_session_filter = None;
+                            self.histogram_last_clicked = None;
+                            self.histogram_drill_level = 0;
+                            self.effective_visible_dirty = true;
+                        }
+                    });
+                }
+
+                // Solo banner
+                if let Some(solo) = &self.project_solo {
+                    let nodes: usize = self.selected_projects.iter()
+                        .filter_map(|p| self.project_node_counts.get(p))
+                        .sum();
+                    let label = format!("Solo: {} ({} nodes)", solo.path, nodes);
+                    ui.horizontal(|ui| {
+                        ui.label(egui::RichText::new(label).color(self.palette.accent.orange));
+                        if ui.small_button("Unsolo").clicked() {
+                            self.unsolo_project();
+                        }
+                    });
+                }
+
+                // Project restriction from "Load only these"
+                if !self.project_pushdown.is_empty() && self.project_solo.is_none() {
+                    ui.horizontal(|ui| {
+                        let label = match self.project_pushdown.as_slice() {
+                            [only] => format!("Project: {}", truncate(only, 24)),
+                            many => format!("Projects: {} loaded", many.len()),
+                        };
+                        ui.label(label).on_hover_text(self.project_pushdown.join("\n"));
+                        if ui.small_button("✖").on_hover_text("Load all projects").clicked() {
+                            self.project_pushdown.clear();
+                            self.schedule_reload();
+                        }
+                    });
+                }
+
+                ui.add_space(5.0);
+                if ui.checkbox(&mut self.settings.topic_nodes_enabled, "Topic nodes")
+                    .on_hover_text("One node per session summary topic, linked to every session that lists it")
+                    .changed()
+                {
+                    self.load_session_topics();
+                    self.merge_derived_nodes();
+                    self.mark_settings_dirty();
+                }
+
+                ui.add_space(5.0);
+                ui.horizontal(|ui| {
+                    if ui.button("⟳ Reload").clicked() {
+                        self.start_background_load();
+                    }
+                    if let Some((loaded, expected)) = self.load_progress {
+                        ui.label(egui::RichText::new(load_progress_text(loaded, expected)).small().color(self.palette.text.muted));
+                        if ui.small_button("Cancel").on_hover_text("Stop loading and keep what's loaded").clicked() {
+                            self.cancel_background_load();
+                        }
+                    } else if self.reload_requested_at.is_some() || self.load_rx.is_some() {
+                        ui.label(egui::RichText::new("pending…").small().color(self.palette.text.muted))
+                            .on_hover_text("Reloading once the controls settle");
+                    }
+                    if ui.button("↺ Reset All").clicked() {
+                        // Reset all UI state to defaults
+                        self.node_size = 15.0;
+                        self.show_arrows = true;
+                        self.graph.physics_enabled = true;
+                        self.timeline_enabled = true;
+                        // Reset sizing to Balanced preset
+                        self.sizing_preset = SizingPreset::Balanced;
+                        let (w_imp, w_tok, w_time) = SizingPreset::Balanced.weights();
+                        self.w_importance = w_imp;
+                        self.w_tokens = w_tok;
+                        self.w_time = w_time;
+                        self.layout.repulsion = 10000.0;
+                        self.layout.attraction = 0.1;
+                        self.layout.centering = 0.0001;
+                        self.layout.size_physics_weight = 0.0;
+                        self.layout.directed_stiffness = 1.0;
+                        self.layout.recency_centering = 0.0;
+                        self.layout.momentum = 0.0;
+                        self.pan_offset = Vec2::ZERO;
+                        self.zoom = 1.0;
+                        self.mark_settings_dirty();
+                        self.schedule_reload();
+                    }
+                });
+                self.render_lost_selection(ui);
 
-                            // Summary
-                            ui.label(egui::RichText::new("Summary").strong());
-                            egui::ScrollArea::vertical()
-                                .max_height(80.0)
-                                .id_salt("window_summary_scroll")
-                                .show(ui, |ui| {
-                                    ui.label(&data.summary);
-                                });
-                            ui.add_space(5.0);
+                // Re-ingest sessions from ~/.claude/
+                if self.ingest_loading {
+                    ui.horizontal(|ui| {
+                        ui.spinner();
+                        ui.label("Ingesting sessions...");
+                    });
+                } else {
+                    if ui.button("↻ Re-ingest Sessions").on_hover_text(
+                        "Import new sessions from ~/.claude/ into the database"
+                    ).clicked() {
+                        self.start_ingest();
+                    }
+                }
+                if let Some(ref result) = self.ingest_result {
+                    ui.label(
+                        egui::RichText::new(format!(
+                            "Imported {} sessions, {} msgs",
+                            result.sessions, result.messages
+                        ))
+                        .size(11.0)
+                        .color(theme::state::SUCCESS),
+                    );
+                }
 
-                            // Current Focus
-                            if !data.current_focus.is_empty() {
-                                ui.label(egui::RichText::new("Working On").strong());
-                                ui.label(&data.current_focus);
-                                ui.add_space(5.0);
-                            }
+                // Last synced timestamp
+                if let Some(last_synced) = self.last_synced {
+                    let elapsed = last_synced.elapsed();
+                    let elapsed_str = if elapsed.as_secs() < 60 {
+                        format!("{}s ago", elapsed.as_secs())
+                    } else if elapsed.as_secs() < 3600 {
+                        format!("{}m ago", elapsed.as_secs() / 60)
+                    } else {
+                        format!("{}h ago", elapsed.as_secs() / 3600)
+                    };
+                    ui.label(format!("Last synced: {}", elapsed_str));
+                }
+                if self.refresh_rx.is_some() {
+                    ui.label(egui::RichText::new("Refreshing…").size(11.0).color(self.palette.text.muted));
+                } else if let Some(remaining) = self.auto_refresh_remaining() {
+                    let text = if self.refresh_failures > 0 {
+                        format!("Refresh failed, retrying in {}s", remaining.as_secs() + 1)
+                    } else {
+                        format!("Next refresh in {}s", remaining.as_secs() + 1)
+                    };
+                    let color = if self.refresh_failures > 0 { theme::state::WARNING } else { self.palette.text.muted };
+                    ui.label(egui::RichText::new(text).size(11.0).color(color));
+                }
+                if !self.new_activity.is_empty() {
+                    let sessions = self.new_activity.iter()
+                        .filter(|a| a.kind == ActivityKind::NewSession)
+                        .count();
+                    let badge = ui.add(egui::Button::new(
+                        egui::RichText::new(format!("● {} new", self.new_activity.len()))
+                            .size(11.0)
+                            .color(theme::state::ACTIVE)
+                    ).small());
+                    if badge.on_hover_text(format!(
+                        "{} new sessions, {} important messages since the last look. Click to select the newest.",
+                        sessions,
+                        self.new_activity.len() - sessions,
+                    )).clicked() {
+                        self.acknowledge_new_activity();
+                    }
+                }
+                self.render_write_queue(ui);
+
+                // Auto-refresh toggle
+                ui.add_space(5.0);
+                let mut auto_refresh = self.settings.auto_refresh_enabled;
+                if ui.checkbox(&mut auto_refresh, "Auto-refresh").changed() {
+                    self.settings.auto_refresh_enabled = auto_refresh;
+                    self.refresh_anchor = Instant::now();
+                    self.refresh_failures = 0;
+                    self.refresh_live_sessions();
+                    self.mark_settings_dirty();
+                }
+                if self.settings.auto_refresh_enabled {
+                    ui.horizontal(|ui| {
+                        ui.label("Interval:");
+                        let mut interval = self.settings.auto_refresh_interval_secs;
+                        if ui.add(egui::DragValue::new(&mut interval)
+                            .range(1.0..=60.0)
+                            .suffix("s")
+                            .speed(0.5)
+                        ).changed() {
+                            self.settings.auto_refresh_interval_secs = interval;
+                            self.mark_settings_dirty();
+                        }
+                    });
+                    ui.horizontal(|ui| {
+                        let mut changed = ui.checkbox(&mut self.settings.live_highlight, "Live sessions")
+                            .on_hover_text("Pulse the newest message of sessions with recent activity")
+                            .changed();
+                        if self.settings.live_highlight {
+                            changed |= ui.add(egui::DragValue::new(&mut self.settings.live_window_minutes)
+                                .range(1.0..=60.0)
+                                .suffix(" min")
+                                .speed(0.2)
+                            ).changed();
+                        }
+                        if changed {
+                            self.refresh_live_sessions();
+                            self.mark_settings_dirty();
+                        }
+                    });
+                    if !self.live_sessions.is_empty() {
+                        let count = self.live_sessions.len();
+                        let label = format!("{} active session{}", count, if count == 1 { "" } else { "s" });
+                        if ui.link(label).on_hover_text("Zoom to their latest messages").clicked() {
+                            self.fit_request = Some(self.live_tails.iter().cloned().collect());
+                        }
+                    }
+                }
+
+                // Desktop notifications for new activity found by refreshes
+                let mut notify = self.settings.notifications_enabled;
+                if ui.checkbox(&mut notify, "Desktop notifications")
+                    .on_hover_text("Notify when a refresh finds a new session or an important message")
+                    .changed()
+                {
+                    self.settings.notifications_enabled = notify;
+                    self.mark_settings_dirty();
+                }
+                if self.settings.notifications_enabled {
+                    ui.horizontal(|ui| {
+                        ui.label("Importance ≥");
+                        let mut threshold = self.settings.notify_importance_threshold;
+                        if ui.add(egui::DragValue::new(&mut threshold).range(0.0..=1.0).speed(0.01)).changed() {
+                            self.settings.notify_importance_threshold = threshold;
+                            // Messages already loaded shouldn't count as new under the new threshold
+                            self.activity_watch.prime(&self.graph.data.nodes, threshold);
+                            self.mark_settings_dirty();
+                        }
+                    });
+                    ui.horizontal(|ui| {
+                        ui.label("At most every");
+                        let mut interval = self.settings.notify_min_interval_secs;
+                        if ui.add(egui::DragValue::new(&mut interval)
+                            .range(5.0..=3600.0)
+                            .suffix("s")
+                            .speed(1.0)
+                        ).changed() {
+                            self.settings.notify_min_interval_secs = interval;
+                            self.mark_settings_dirty();
+                        }
+                    });
+                }
+            });
+
+        // Presets section
+        egui::CollapsingHeader::new("Presets")
+            .default_open(false)
+            .show(ui, |ui| {
+                self.render_presets_section(ui);
+            });
+
+        // Starred messages, reachable even after they leave the time range
+        egui::CollapsingHeader::new(format!("Favorites ({})", self.favorites.len()))
+            .id_salt("favorites")
+            .default_open(false)
+            .show(ui, |ui| {
+                self.render_favorites_section(ui);
+            });
+
+        // Named layouts with positions, for getting a good arrangement back
+        egui::CollapsingHeader::new("Snapshots")
+            .default_open(false)
+            .show(ui, |ui| {
+                self.render_snapshots_section(ui);
+            });
+
+        // Share the exact current view as a pasteable string
+        egui::CollapsingHeader::new("Share View")
+            .default_open(false)
+            .show(ui, |ui| {
+                self.render_view_string_section(ui);
+            });
+
+        // Pick two sessions to show side by side
+        egui::CollapsingHeader::new("Compare Sessions")
+            .default_open(false)
+            .show(ui, |ui| {
+                self.render_compare_picker(ui);
+            });
+
+        // Compare two timeline windows in place
+        egui::CollapsingHeader::new("Diff Windows")
+            .default_open(false)
+            .show(ui, |ui| {
+                self.render_diff_section(ui);
+            });
+
+        // Sessions that went quiet within the last hour
+        egui::CollapsingHeader::new("Recently Ended")
+            .default_open(false)
+            .show(ui, |ui| {
+                self.render_recently_ended(ui);
+            });
+
+        // Activity charts over the loaded data
+        egui::CollapsingHeader::new("Stats")
+            .default_open(false)
+            .show(ui, |ui| {
+                self.render_stats_section(ui);
+            });
+
+        // Dollar estimates from token counts
+        egui::CollapsingHeader::new("Cost")
+            .default_open(false)
+            .show(ui, |ui| {
+                self.render_cost_section(ui);
+            });
+
+        // Recently modified vault notes as graph nodes
+        egui::CollapsingHeader::new("Obsidian")
+            .default_open(false)
+            .show(ui, |ui| {
+                self.render_obsidian_section(ui);
+            });
+
+        ui.add_space(5.0);
+        ui.separator();
+
+        // View / Zoom controls
+        ui.label("View");
+        ui.horizontal(|ui| {
+            if ui.button("Reset View").clicked() {
+                self.pan_offset = Vec2::ZERO;
+                self.zoom = 1.0;
+            }
+            ui.label(format!("Zoom: {:.0}%", self.zoom * 100.0));
+        });
+
+        ui.add_space(5.0);
+
+        // Token Histogram toggle
+        if ui.checkbox(&mut self.histogram_panel_enabled, "Token Histogram Panel")
+            .on_hover_text("Show token usage histogram in a split pane")
+            .changed()
+        { self.mark_settings_dirty(); }
+
+        ui.add_space(5.0);
+        ui.separator();
+
+        // Embedding stats and generation
+        let stats_info = self.embedding_stats.as_ref().map(|s| (s.embedded, s.total, s.unembedded));
+        let gen_loading = self.embedding_gen_loading;
+
+        if let Some((embedded, total, unembedded)) = stats_info {
+            ui.label(format!("{} / {} embedded", embedded, total));
+
+            if unembedded > 0 {
+                let gen_text = if gen_loading { "Generating..." } else { "Generate" };
+                ui.horizontal(|ui| {
+                    if gen_loading {
+                        ui.spinner();
+                    }
+                    if ui.add_enabled(!gen_loading, egui::Button::new(gen_text)).clicked() {
+                        self.trigger_embedding_generation();
+                    }
+                    if ui.add_enabled(!gen_loading, egui::Button::new("Visible only")).clicked() {
+                        self.trigger_embedding_generation_visible();
+                    }
+                    ui.label(format!("{} remaining", unembedded));
+                });
+            }
+        } else {
+            if ui.button("Load stats").clicked() {
+                self.load_embedding_stats();
+            }
+        }
+
+        ui.add_space(5.0);
+        ui.separator();
+
+        // Info section
+        ui.label("Info");
+        let visible_count = self.effective_visible_count;
+        let total_count = self.graph.data.nodes.len();
+        if self.any_filter_active() && visible_count < total_count {
+            ui.label(format!("Nodes: {} / {}", visible_count, total_count));
+        } else {
+            ui.label(format!("Nodes: {}", total_count));
+        }
+        ui.label(format!("Edges: {}", self.graph.data.edges.len()));
+        if let Some(build) = self.graph.temporal_build {
+            ui.label(format!(
+                "Temporal edges: {} built in {}ms",
+                format_count(build.edges as i64),
+                build.elapsed.as_millis()
+            ));
+        }
+        match self.graph.communities {
+            Some(ref communities) => {
+                let largest: Vec<String> = communities.sizes.iter().take(3).map(|s| s.to_string()).collect();
+                ui.label(format!("Communities: {} (largest {})", communities.count(), largest.join(", ")))
+                    .on_hover_text("Connected groups over conversation and similarity edges");
+            }
+            None => {
+                ui.label("Communities: detecting…");
+            }
+        }
+        ui.label(format!("FPS: {:.1}", self.fps));
+        self.render_frame_sparkline(ui);
+        ui.label(format!("Hit-testing: {}", self.hit_test.label()))
+            .on_hover_text(format!("Graphs of {}+ nodes use a spatial grid for hover", spatial::GRID_MIN_NODES));
+        self.render_memory_info(ui);
+        egui::CollapsingHeader::new(egui::RichText::new("Frame time").small())
+            .id_salt("frame_profiler")
+            .show(ui, |ui| {
+                ui.checkbox(&mut self.profiler_overlay, "Show overlay")
+                    .on_hover_text("Milliseconds per frame phase, drawn over the graph");
+                self.render_profiler_table(ui);
+            });
+        let stalls = self.watchdog.events().count();
+        egui::CollapsingHeader::new(egui::RichText::new(format!("UI stalls ({})", stalls)).small())
+            .id_salt("ui_stalls")
+            .show(ui, |ui| {
+                self.render_stalls(ui);
+            });
+        egui::CollapsingHeader::new(egui::RichText::new(format!("Log ({})", log_buffer::len())).small())
+            .id_salt("log_events")
+            .show(ui, |ui| {
+                self.render_log_events(ui);
+            });
+        self.render_debug_bundle_row(ui);
+
+        let user_count = self.graph.data.nodes.iter().filter(|n| n.role == crate::graph::types::Role::User).count();
+        let assistant_count = self.graph.data.nodes.iter().filter(|n| n.role == crate::graph::types::Role::Assistant).count();
+        ui.label(format!("You: {} | Claude: {}", user_count, assistant_count));
+    }
+
+    /// Approximate memory use of the loaded graph and the guardrail controls
+    fn render_memory_info(&mut self, ui: &mut egui::Ui) {
+        let estimate = self.memory_estimate;
+        ui.label(format!("Memory: ~{}", memory::format_bytes(estimate.total())))
+            .on_hover_text(format!(
+                "Message text: {}\nNodes: {}\nEdges: {}\nLayout: {}",
+                memory::format_bytes(estimate.content),
+                memory::format_bytes(estimate.nodes),
+                memory::format_bytes(estimate.edges),
+                memory::format_bytes(estimate.layout),
+            ));
+        if let Some(ref notice) = self.memory_notice {
+            ui.label(egui::RichText::new(notice).small().color(theme::state::WARNING));
+        }
+        if ui.checkbox(&mut self.settings.content_lazy, "Load previews only")
+            .on_hover_text("Skip full message text to save memory (applies on the next load; rule filters then match previews)")
+            .changed()
+        {
+            self.mark_settings_dirty();
+        }
+        ui.horizontal(|ui| {
+            ui.label("Memory budget:");
+            if ui.add(egui::DragValue::new(&mut self.settings.memory_budget_mb)
+                .range(64.0..=65536.0)
+                .suffix(" MB")
+                .speed(16.0)
+            ).changed() {
+                self.mark_settings_dirty();
+            }
+        });
+    }
 
-                            // Completed Work
-                            if !data.completed_work.is_empty() {
-                                ui.label(egui::RichText::new("Completed").strong().color(theme::state::SUCCESS));
-                                egui::ScrollArea::vertical()
-                                    .max_height(60.0)
-                                    .id_salt("window_completed_scroll")
-                                    .show(ui, |ui| {
-                                        for line in data.completed_work.split('\n').filter(|l| !l.is_empty()) {
-                                            ui.label(line);
-                                        }
-                                    });
-                                ui.add_space(5.0);
-                            }
+    /// Rolling average milliseconds per frame phase, slowest highlighted
+    fn render_profiler_table(&self, ui: &mut egui::Ui) {
+        let slowest = self.profiler.slowest();
+        egui::Grid::new(ui.next_auto_id()).num_columns(2).show(ui, |ui| {
+            for phase in Phase::ALL {
+                let text = |s: String| {
+                    let rich = egui::RichText::new(s).small().monospace();
+                    if slowest == Some(phase) { rich.color(theme::state::WARNING) } else { rich }
+                };
+                ui.label(text(phase.label().to_string()));
+                ui.label(text(format!("{:6.2} ms", self.profiler.average_ms(phase))));
+                ui.end_row();
+            }
+            ui.label(egui::RichText::new("Frame").small().monospace().strong());
+            ui.label(egui::RichText::new(format!("{:6.2} ms", self.profiler.frame_average_ms())).small().monospace().strong());
+            ui.end_row();
+            let hit_rate = self.text_layout.hit_rate()
+                .map_or("–".to_string(), |rate| format!("{:.0}% of {}", rate * 100.0, self.text_layout.len()));
+            ui.label(egui::RichText::new("Text cache").small().monospace());
+            ui.label(egui::RichText::new(hit_rate).small().monospace());
+            ui.end_row();
+        });
+    }
 
-                            // Unsuccessful Attempts
-                            if !data.unsuccessful_attempts.is_empty() {
-                                ui.label(egui::RichText::new("Tried but Failed").strong().color(Color32::from_rgb(255, 100, 100)));
-                                egui::ScrollArea::vertical()
-                                    .max_height(60.0)
-                                    .id_salt("window_failed_scroll")
-                                    .show(ui, |ui| {
-                                        for line in data.unsuccessful_attempts.split('\n').filter(|l| !l.is_empty()) {
-                                            ui.label(line);
-                                        }
-                                    });
-                            }
+    /// Frames that blocked the UI, newest first, with a copy button for bug reports
+    fn render_stalls(&mut self, ui: &mut egui::Ui) {
+        let threshold_ms = watchdog::STALL_THRESHOLD.as_millis();
+        let lines: Vec<String> = self.watchdog.events().map(|e| e.describe()).collect();
+        if lines.is_empty() {
+            ui.label(egui::RichText::new(format!("No frame has taken over {}ms", threshold_ms))
+                .small()
+                .color(self.palette.text.muted));
+            return;
+        }
+        for line in &lines {
+            ui.label(egui::RichText::new(line).small().monospace());
+        }
+        ui.horizontal(|ui| {
+            if ui.small_button("Copy").on_hover_text("Copy for a bug report").clicked() {
+                let report = format!("UI stalls (frames over {}ms):\n{}", threshold_ms, lines.join("\n"));
+                ui.output_mut(|o| o.copied_text = report);
+            }
+            if ui.small_button("Clear").clicked() {
+                self.watchdog.clear();
+            }
+        });
+    }
 
-                            // Importance grading for the clicked node
-                            if let Some(ref node_id) = self.summary_node_id.clone() {
-                                if let Some(node) = self.graph.get_node(&node_id) {
-                                    if node.importance_score.is_some() || node.importance_reason.is_some() {
-                                        ui.add_space(5.0);
-                                        ui.label(egui::RichText::new("Importance").strong().color(Color32::from_rgb(255, 193, 7)));
-                                        if let Some(score) = node.importance_score {
-                                            ui.label(format!("Score: {:.0}%", score * 100.0));
-                                        }
-                                        if let Some(ref reason) = node.importance_reason {
-                                            ui.label(reason);
-                                        }
-                                    }
-                                }
-                            }
-                        } else {
-                            ui.colored_label(egui::Color32::GRAY, "(Disabled - requires AI generation)");
-                        }
-                    });
+    /// Export a debug bundle, or say which one is being shown
+    fn render_debug_bundle_row(&mut self, ui: &mut egui::Ui) {
+        let muted = self.palette.text.muted;
+        if let Some(ref source) = self.bundle_source {
+            ui.label(egui::RichText::new(format!("Showing debug bundle {} (database not used, settings not saved)", source))
+                .small()
+                .color(theme::state::WARNING));
+        }
+        ui.horizontal(|ui| {
+            if ui.small_button("Export debug bundle")
+                .on_hover_text("Save the loaded graph, settings, versions and recent stalls and warnings for a bug report. Open it with --load-bundle.")
+                .clicked()
+            {
+                self.bundle_status = Some(match self.export_debug_bundle() {
+                    Ok(path) => (format!("Saved {}", path.display()), false),
+                    Err(e) => (e, true),
+                });
+            }
+            ui.checkbox(&mut self.bundle_anonymize, "Anonymize")
+                .on_hover_text("Leave message text out; nodes keep only its length");
+        });
+        if let Some((message, is_error)) = &self.bundle_status {
+            let color = if *is_error { theme::state::ERROR } else { muted };
+            ui.label(egui::RichText::new(message).small().color(color));
+        }
+    }
 
-                ui.separator();
+    /// Recent warnings and errors, newest first
+    fn render_log_events(&self, ui: &mut egui::Ui) {
+        let events = log_buffer::events();
+        if events.is_empty() {
+            ui.label(egui::RichText::new("No warnings or errors")
+                .small()
+                .color(self.palette.text.muted));
+            return;
+        }
+        egui::ScrollArea::vertical()
+            .id_salt("log_events_scroll")
+            .max_height(200.0)
+            .show(ui, |ui| {
+                for event in &events {
+                    let color = if event.level == tracing::Level::ERROR {
+                        theme::state::ERROR
+                    } else {
+                        theme::state::WARNING
+                    };
+                    ui.label(egui::RichText::new(event.describe()).small().monospace().color(color));
+                }
+            });
+        ui.horizontal(|ui| {
+            if ui.small_button("Copy").on_hover_text("Copy for a bug report").clicked() {
+                let lines: Vec<String> = events.iter().map(|e| e.describe()).collect();
+                ui.output_mut(|o| o.copied_text = lines.join("\n"));
+            }
+            if ui.small_button("Clear").clicked() {
+                log_buffer::clear();
+            }
+        });
+    }
 
-                // Session Summary section
-                egui::CollapsingHeader::new("Session Summary")
-                    .default_open(true)
+    /// Translucent per-phase timing table in the top-right corner of the window
+    fn render_profiler_overlay(&self, ctx: &egui::Context) {
+        if !self.profiler_overlay {
+            return;
+        }
+        egui::Area::new(egui::Id::new("profiler_overlay"))
+            .anchor(egui::Align2::RIGHT_TOP, egui::vec2(-12.0, 12.0))
+            .interactable(false)
+            .show(ctx, |ui| {
+                egui::Frame::popup(ui.style())
+                    .fill(self.palette.bg.panel.gamma_multiply(0.85))
                     .show(ui, |ui| {
-                        if self.session_summary_loading {
-                            ui.horizontal(|ui| {
-                                ui.spinner();
-                                ui.label("Generating summary...");
-                            });
-                            ui.add_space(8.0);
-                            // Skeleton preview of summary content
-                            theme::skeleton_lines(ui, 4, ui.available_width() * 0.9);
-                        } else if let Some(ref data) = self.session_summary_data.clone() {
-                            // Check for errors first
-                            if let Some(ref err) = data.error {
-                                ui.colored_label(theme::state::ERROR, format!("Error: {}", err));
-                            } else if !data.exists {
-                                ui.label("No summary in database for this session.");
-                            } else {
-                                // Show "just generated" badge if applicable
-                                if data.generated {
-                                    ui.colored_label(theme::state::SUCCESS, "✓ Just generated");
-                                    ui.add_space(5.0);
-                                }
+                        self.render_profiler_table(ui);
+                    });
+            });
+    }
 
-                                // Project and topics
-                                if let Some(ref project) = data.detected_project {
-                                    ui.horizontal(|ui| {
-                                        ui.label(egui::RichText::new("Project:").strong());
-                                        ui.label(project);
-                                    });
-                                }
+    /// Estimated spend for the timeline window and today, a per-project table, and the rates used
+    fn render_cost_section(&mut self, ui: &mut egui::Ui) {
+        let rates = self.settings.token_rates;
+        let summary = &self.cost_summary;
+        ui.label(
+            egui::RichText::new("Estimates from recorded token counts, not billing data")
+                .small()
+                .italics()
+                .color(self.palette.text.muted)
+        );
+        let window_label = if self.timeline_enabled { "Timeline window" } else { "Loaded range" };
+        egui::Grid::new("cost_totals").num_columns(2).show(ui, |ui| {
+            for (label, totals) in [(window_label, &summary.window), ("Today", &summary.today)] {
+                ui.label(label);
+                ui.label(format!("~{}", cost::format_usd(totals.cost(&rates))))
+                    .on_hover_text(format!("{} tokens", format_count(totals.total() as i64)));
+                ui.end_row();
+            }
+        });
 
-                                if let Some(ref topics) = data.topics {
-                                    if !topics.is_empty() {
-                                        ui.horizontal_wrapped(|ui| {
-                                            ui.label(egui::RichText::new("Topics:").strong());
-                                            for topic in topics.iter().take(5) {
-                                                ui.label(format!("[{}]", topic));
-                                            }
-                                        });
-                                    }
-                                }
+        let mut projects: Vec<(&String, f64)> = summary.by_project.iter()
+            .map(|(project, totals)| (project, totals.cost(&rates)))
+            .filter(|(_, cost)| *cost > 0.0)
+            .collect();
+        if !projects.is_empty() {
+            projects.sort_by(|a, b| b.1.total_cmp(&a.1).then_with(|| a.0.cmp(b.0)));
+            ui.add_space(4.0);
+            egui::Grid::new("cost_by_project").num_columns(2).striped(true).show(ui, |ui| {
+                for (project, cost) in projects {
+                    let name = if project.is_empty() { "(no project)" } else { project.as_str() };
+                    ui.label(truncate(name, 28)).on_hover_text(name);
+                    ui.label(format!("~{}", cost::format_usd(cost)));
+                    ui.end_row();
+                }
+            });
+        }
 
-                                ui.add_space(5.0);
+        ui.add_space(4.0);
+        egui::CollapsingHeader::new(egui::RichText::new("Rates ($ per 1M tokens)").small())
+            .id_salt("cost_rates")
+            .show(ui, |ui| {
+                let rates = &mut self.settings.token_rates;
+                let mut changed = false;
+                egui::Grid::new("cost_rates_grid").num_columns(2).show(ui, |ui| {
+                    for (label, rate) in [
+                        ("Input", &mut rates.input),
+                        ("Output", &mut rates.output),
+                        ("Cache read", &mut rates.cache_read),
+                        ("Cache write", &mut rates.cache_creation),
+                    ] {
+                        ui.label(label);
+                        changed |= ui.add(egui::DragValue::new(rate).speed(0.05).range(0.0..=1000.0).prefix("$")).changed();
+                        ui.end_row();
+                    }
+                });
+                if reset_defaults_button(ui) {
+                    *rates = TokenRates::default();
+                    changed = true;
+                }
+                if changed {
+                    self.settings_dirty = true;
+                }
+            });
+    }
 
-                                // Summary paragraph
-                                if let Some(ref summary) = data.summary {
-                                    ui.label(egui::RichText::new("Summary").strong());
-                                    egui::ScrollArea::vertical()
-                                        .max_height(100.0)
-                                        .id_salt("window_session_summary_scroll")
-                                        .show(ui, |ui| {
-                                            ui.label(summary);
-                                        });
-                                    ui.add_space(5.0);
-                                }
+    /// Stacked bar of the four token classes with a count per class
+    fn render_token_classes(&self, ui: &mut egui::Ui, totals: &TokenTotals) {
+        let colors = self.palette.histogram;
+        let classes = [
+            ("Input", totals.input, colors.input),
+            ("Output", totals.output, colors.output),
+            ("Cache read", totals.cache_read, colors.cache_read),
+            ("Cache write", totals.cache_creation, colors.cache_create),
+        ];
+        let total = totals.total().max(1) as f32;
+        let (bar, painter) = ui.allocate_painter(egui::vec2(ui.available_width(), 8.0), egui::Sense::hover());
+        painter.rect_filled(bar.rect, 2.0, self.palette.bg.timeline_track);
+        let mut x = bar.rect.left();
+        for (_, count, color) in classes {
+            let w = bar.rect.width() * count as f32 / total;
+            if w > 0.0 {
+                painter.rect_filled(egui::Rect::from_min_size(egui::pos2(x, bar.rect.top()), egui::vec2(w, bar.rect.height())), 0.0, color);
+            }
+            x += w;
+        }
+        egui::Grid::new(ui.id().with("token_classes")).num_columns(4).show(ui, |ui| {
+            for (label, count, color) in classes {
+                ui.label(egui::RichText::new("■").color(color));
+                ui.label(egui::RichText::new(format!("{} {}", label, format_count(count as i64))).small());
+                if label == "Output" {
+                    ui.end_row();
+                }
+            }
+        });
+    }
 
-                                // Completed work
-                                if let Some(ref completed) = data.completed_work {
-                                    if !completed.is_empty() {
-                                        ui.label(egui::RichText::new("Completed Work").strong().color(theme::state::SUCCESS));
-                                        egui::ScrollArea::vertical()
-                                            .max_height(80.0)
-                                            .id_salt("window_session_completed_scroll")
-                                            .show(ui, |ui| {
-                                                for line in completed.split('\n').filter(|l| !l.is_empty()) {
-                                                    ui.label(line);
-                                                }
-                                            });
-                                        ui.add_space(5.0);
-                                    }
-                                }
+    /// Daily volume, top projects, busiest hours and session averages for the loaded data.
+    /// Computed on first show after each load; clicking a day bar sets the timeline window.
+    fn render_stats_section(&mut self, ui: &mut egui::Ui) {
+        if self.graph.data.nodes.is_empty() {
+            ui.label(egui::RichText::new("No messages loaded").small().italics().color(self.palette.text.muted));
+            return;
+        }
+        if self.activity_stats.is_none() {
+            let today = chrono::Local::now().date_naive();
+            self.activity_stats = Some(ActivityStats::compute(&self.graph.data.nodes, &chrono::Local, today));
+        }
+        let Some(stats) = self.activity_stats.clone() else {
+            return;
+        };
+        let muted = self.palette.text.muted;
+        ui.label(
+            egui::RichText::new(format!("From loaded data (last {})", format_hours_label(self.time_range_hours)))
+                .small()
+                .italics()
+                .color(muted)
+        );
+        ui.horizontal(|ui| {
+            if ui
+                .small_button("Export stats JSON")
+                .on_hover_text("Save per-project and per-session totals, bead and mail counts for the current window and filters")
+                .clicked()
+            {
+                self.stats_json_status = Some(match self.export_stats_json() {
+                    Ok(path) => (format!("Saved {}", path.display()), false),
+                    Err(e) => (e, true),
+                });
+            }
+            if let Some((message, is_error)) = &self.stats_json_status {
+                let color = if *is_error { theme::state::ERROR } else { muted };
+                ui.label(egui::RichText::new(message).small().color(color));
+            }
+        });
 
-                                // User requests
-                                if let Some(ref requests) = data.user_requests {
-                                    if !requests.is_empty() {
-                                        ui.label(egui::RichText::new("User Requests").strong().color(Color32::from_rgb(100, 149, 237)));
-                                        egui::ScrollArea::vertical()
-                                            .max_height(60.0)
-                                            .id_salt("window_session_requests_scroll")
-                                            .show(ui, |ui| {
-                                                for line in requests.split('\n').filter(|l| !l.is_empty()) {
-                                                    ui.label(line);
-                                                }
-                                            });
-                                    }
-                                }
-                            }
-                        } else {
-                            ui.label("Double-click a node to see");
-                            ui.label("the full session summary.");
-                        }
-                    });
+        // Messages per day
+        ui.label(egui::RichText::new(format!("Messages per day ({} days)", stats::DAYS)).small().strong());
+        let width = ui.available_width();
+        let (response, painter) = ui.allocate_painter(egui::vec2(width, 56.0), egui::Sense::click());
+        let rect = response.rect;
+        let max_day = stats.daily.iter().map(|d| d.messages).max().unwrap_or(0).max(1);
+        let slot = rect.width() / stats.daily.len().max(1) as f32;
+        let hovered_day = response.hover_pos()
+            .map(|pos| ((pos.x - rect.left()) / slot).floor() as usize)
+            .filter(|&i| i < stats.daily.len());
+        for (i, day) in stats.daily.iter().enumerate() {
+            let height = (rect.height() - 12.0) * day.messages as f32 / max_day as f32;
+            let bar = egui::Rect::from_min_max(
+                egui::pos2(rect.left() + i as f32 * slot + 1.0, rect.bottom() - 12.0 - height),
+                egui::pos2(rect.left() + (i + 1) as f32 * slot - 1.0, rect.bottom() - 12.0),
+            );
+            let color = if hovered_day == Some(i) {
+                self.palette.timeline.bar_highlight
+            } else {
+                self.palette.timeline.bar_selected
+            };
+            painter.rect_filled(bar, 1.0, color);
+        }
+        let axis_font = egui::FontId::proportional(9.0);
+        if let (Some(first), Some(last)) = (stats.daily.first(), stats.daily.last()) {
+            painter.text(rect.left_bottom(), egui::Align2::LEFT_BOTTOM, first.date.format("%b %-d").to_string(), axis_font.clone(), muted);
+            painter.text(rect.right_bottom(), egui::Align2::RIGHT_BOTTOM, last.date.format("%b %-d").to_string(), axis_font.clone(), muted);
+        }
+        if let Some(day) = hovered_day.map(|i| &stats.daily[i]) {
+            let clicked = response.clicked();
+            response.on_hover_text(format!("{}: {} messages\nClick to show this day", day.date.format("%a %b %-d"), day.messages));
+            if clicked {
+                self.commands.push(AppCommand::SetTimelineWindow { start: day.start, end: day.end });
+            }
+        }
 
-                ui.add_space(10.0);
-                ui.separator();
+        // Top projects by tokens
+        if !stats.top_projects.is_empty() {
+            ui.add_space(6.0);
+            ui.label(egui::RichText::new("Top projects by tokens").small().strong());
+            let max_tokens = stats.top_projects[0].1.max(1);
+            for (project, tokens) in &stats.top_projects {
+                let (row, painter) = ui.allocate_painter(egui::vec2(width, 16.0), egui::Sense::hover());
+                let fill = egui::Rect::from_min_size(
+                    row.rect.min,
+                    egui::vec2(row.rect.width() * *tokens as f32 / max_tokens as f32, row.rect.height()),
+                );
+                painter.rect_filled(fill, 2.0, self.graph.project_color(project).gamma_multiply(0.45));
+                let name = if project.is_empty() { "(no project)" } else { project.rsplit('/').next().unwrap_or_default() };
+                let font = egui::FontId::proportional(11.0);
+                painter.text(row.rect.left_center() + egui::vec2(4.0, 0.0), egui::Align2::LEFT_CENTER, truncate(name, 24), font.clone(), self.palette.text.primary);
+                painter.text(row.rect.right_center() - egui::vec2(4.0, 0.0), egui::Align2::RIGHT_CENTER, format_count(*tokens as i64), font, self.palette.text.secondary);
+                row.on_hover_text(project.as_str());
+            }
+        }
 
-                // Clear button - only clears summary window state
-                if ui.button("Clear & Close").clicked() {
-                    self.summary_data = None;
-                    self.summary_node_id = None;
-                    self.session_summary_data = None;
-                    self.summary_window_open = false;
-                    self.summary_window_dragged = false;
+        // Busiest hours (local time)
+        ui.add_space(6.0);
+        ui.label(egui::RichText::new("Messages by hour of day").small().strong());
+        let (strip, painter) = ui.allocate_painter(egui::vec2(width, 30.0), egui::Sense::hover());
+        let max_hour = stats.hourly.iter().copied().max().unwrap_or(0).max(1);
+        let cell = strip.rect.width() / 24.0;
+        for (hour, &count) in stats.hourly.iter().enumerate() {
+            let cell_rect = egui::Rect::from_min_size(
+                egui::pos2(strip.rect.left() + hour as f32 * cell, strip.rect.top()),
+                egui::vec2(cell - 1.0, 16.0),
+            );
+            let t = count as f32 / max_hour as f32;
+            painter.rect_filled(cell_rect, 1.0, crate::graph::types::lerp_color(self.palette.bg.surface, self.palette.accent.orange, t));
+            if hour % 6 == 0 {
+                painter.text(egui::pos2(cell_rect.left(), strip.rect.bottom()), egui::Align2::LEFT_BOTTOM, format!("{:02}", hour), axis_font.clone(), muted);
+            }
+        }
+        if let Some(pos) = strip.hover_pos() {
+            let hour = (((pos.x - strip.rect.left()) / cell).floor() as usize).min(23);
+            strip.on_hover_text(format!("{:02}:00–{:02}:59: {} messages", hour, hour, stats.hourly[hour]));
+        }
+
+        // Session averages
+        ui.add_space(6.0);
+        egui::Grid::new("activity_stats_summary").num_columns(2).show(ui, |ui| {
+            ui.label("Avg session length");
+            ui.label(stats.avg_session_secs.map(compare::format_duration).unwrap_or_else(|| "—".to_string()));
+            ui.end_row();
+            ui.label("Claude : you");
+            ui.label(stats.assistant_per_user.map(|r| format!("{:.1} : 1", r)).unwrap_or_else(|| "—".to_string()));
+            ui.end_row();
+            ui.label("Cache hits (window)").on_hover_text("Cache reads / (cache reads + input) over the timeline window");
+            ui.label(format_ratio(self.cost_summary.window.cache_hit_ratio()));
+            ui.end_row();
+        });
+
+        // Sessions in the window that mostly re-sent their prompts
+        let mut poor: Vec<(&String, f64)> = self.cost_summary.by_session.iter()
+            .filter(|(_, totals)| totals.poor_cache_reuse())
+            .filter_map(|(session_id, totals)| Some((session_id, totals.cache_hit_ratio()?)))
+            .collect();
+        if !poor.is_empty() {
+            poor.sort_by(|a, b| a.1.total_cmp(&b.1).then_with(|| a.0.cmp(b.0)));
+            ui.add_space(6.0);
+            ui.label(egui::RichText::new(format!("Poor cache reuse ({} sessions)", poor.len())).small().strong());
+            egui::Grid::new("poor_cache_sessions").num_columns(2).striped(true).show(ui, |ui| {
+                for (session_id, ratio) in poor.into_iter().take(5) {
+                    self.session_chip(ui, session_id);
+                    ui.label(egui::RichText::new(format_ratio(Some(ratio))).color(theme::state::WARNING));
+                    ui.end_row();
                 }
             });
+        }
+    }
 
-        self.summary_window_open = open;
-
-        // Detect if window was dragged from default position
-        if let Some(inner) = window_response {
-            let pos = inner.response.rect.left_top();
-            let dist = (pos - default_pos).length();
-            if dist > 10.0 {
-                self.summary_window_dragged = true;
+    /// Move the scrubber to the time typed in the "Jump to" box, snapped to
+    /// the nearest message. Times outside the loaded range clamp to its ends.
+    fn jump_to_time(&mut self) {
+        let target = match time_expr::parse(&self.jump_input, &chrono::Local::now()) {
+            Ok(t) => t,
+            Err(e) => {
+                self.jump_hint = Some((e, true));
+                return;
             }
+        };
+        let timeline = &mut self.graph.timeline;
+        if timeline.is_degenerate() {
+            self.jump_hint = Some(("Nothing loaded to jump through".to_string(), true));
+            return;
+        }
+        let clamped = target.clamp(timeline.min_time, timeline.max_time);
+        self.jump_hint = (clamped != target).then(|| (
+            format!("Outside loaded range ({} → {})", timeline.format_time(timeline.min_time), timeline.format_time(timeline.max_time)),
+            false,
+        ));
+        let pos = timeline.snap_to_notch(timeline.position_at_time(clamped).clamp(0.0, 1.0));
+        timeline.position = pos;
+        timeline.start_position = timeline.start_position.min(pos);
+        if !self.timeline_enabled {
+            self.timeline_enabled = true;
+            self.mark_settings_dirty();
+        }
+        self.graph.update_visible_items();
+        self.effective_visible_dirty = true;
+        self.jump_flash = Some((pos, Instant::now()));
+    }
+
+    /// Set the timeline window to [start, end] (epoch seconds), enabling the timeline if needed
+    fn show_timeline_window(&mut self, start: f64, end: f64) {
+        if self.graph.timeline.is_degenerate() {
+            return;
+        }
+        if !self.timeline_enabled {
+            self.timeline_enabled = true;
+            self.mark_settings_dirty();
         }
+        let timeline = &mut self.graph.timeline;
+        timeline.start_position = timeline.position_at_time(start).clamp(0.0, 1.0);
+        timeline.position = timeline.position_at_time(end).clamp(timeline.start_position, 1.0);
+        self.graph.update_visible_items();
+        self.effective_visible_dirty = true;
     }
 
-    /// Render the floating neighborhood window (Neighborhood Summary, triggered by Ctrl+Click)
-    fn render_neighborhood_window(&mut self, ctx: &egui::Context) {
-        if !self.neighborhood_window_open {
+    /// Sessions in the loaded data as (session_id, label), newest first
+    fn compare_session_options(&self) -> Vec<(String, String)> {
+        let mut first_nodes: HashMap<&str, &crate::graph::types::GraphNode> = HashMap::new();
+        for node in &self.graph.data.nodes {
+            first_nodes.entry(node.session_id.as_str()).or_insert(node);
+        }
+        let mut options: Vec<(String, String, f64)> = first_nodes.into_iter()
+            .map(|(session_id, node)| {
+                let (start, count) = self.session_metadata_cache.get(session_id).copied().unwrap_or((f64::MAX, 0));
+                let start = if start == f64::MAX { f64::MIN } else { start };
+                let project = node.project.rsplit('/').next().unwrap_or_default();
+                (session_id.to_string(), format!("{} · {} ({} msgs)", node.session_short, project, count), start)
+            })
+            .collect();
+        options.sort_by(|a, b| b.2.total_cmp(&a.2).then_with(|| a.0.cmp(&b.0)));
+        options.into_iter().map(|(session_id, label, _)| (session_id, label)).collect()
+    }
+
+    /// Two session dropdowns and the button that enters or leaves compare mode
+    fn render_compare_picker(&mut self, ui: &mut egui::Ui) {
+        let options = self.compare_session_options();
+        if options.len() < 2 {
+            ui.label(
+                egui::RichText::new("Load at least two sessions to compare")
+                    .small()
+                    .color(self.palette.text.muted)
+            );
             return;
         }
 
-        // Calculate default position (offset from summary window position)
-        let screen_rect = ctx.screen_rect();
-        let default_pos = egui::pos2(screen_rect.right() - 420.0, 580.0);
-
-        let mut open = self.neighborhood_window_open;
-
-        let window_response = egui::Window::new("Neighborhood")
-            .open(&mut open)
-            .default_pos(default_pos)
-            .default_size([400.0, 350.0])
-            .resizable(true)
-            .collapsible(true)
-            .show(ctx, |ui| {
-                // Depth slider
-                ui.horizontal(|ui| {
-                    ui.label("Depth:");
-                    ui.add(egui::Slider::new(&mut self.neighborhood_depth, 1..=5).text("edges"));
-                });
-
-                // Temporal edge toggle
-                ui.checkbox(&mut self.neighborhood_include_temporal, "Include temporal edges");
+        for (side, name) in ["A", "B"].into_iter().enumerate() {
+            let selected_label = self.compare_choice[side].as_ref()
+                .and_then(|id| options.iter().find(|(session_id, _)| session_id == id))
+                .map(|(_, label)| label.clone())
+                .unwrap_or_else(|| "Choose a session…".to_string());
+            ui.horizontal(|ui| {
+                ui.label(name);
+                egui::ComboBox::from_id_salt(("compare_choice", side))
+                    .selected_text(truncate(&selected_label, 36))
+                    .width(ui.available_width())
+                    .show_ui(ui, |ui| {
+                        for (session_id, label) in &options {
+                            let poor = self.cost_summary.by_session.get(session_id).filter(|t| t.poor_cache_reuse());
+                            let text = match poor {
+                                Some(_) => egui::RichText::new(label).color(theme::state::WARNING),
+                                None => egui::RichText::new(label),
+                            };
+                            let response = ui.selectable_value(&mut self.compare_choice[side], Some(session_id.clone()), text);
+                            if let Some(totals) = poor {
+                                response.on_hover_text(format!("Poor cache reuse: {} cache hits", format_ratio(totals.cache_hit_ratio())));
+                            }
+                        }
+                    });
+            });
+        }
 
-                ui.add_space(5.0);
+        ui.horizontal(|ui| {
+            let pair = match &self.compare_choice {
+                [Some(a), Some(b)] if a != b => Some([a.clone(), b.clone()]),
+                _ => None,
+            };
+            if ui.add_enabled(pair.is_some(), egui::Button::new("Compare")).clicked() {
+                if let Some(pair) = pair {
+                    self.enter_compare(pair);
+                }
+            }
+            if self.compare_sessions.is_some() && ui.button("Exit compare").clicked() {
+                self.exit_compare();
+            }
+        });
 
-                if self.neighborhood_summary_loading {
-                    ui.horizontal(|ui| {
-                        ui.spinner();
-                        ui.label(format!("Summarizing {} nodes...", self.neighborhood_summary_count));
-                    });
-                    ui.add_space(8.0);
-                    theme::skeleton_lines(ui, 3, ui.available_width() * 0.9);
-                } else if let Some(ref error) = self.neighborhood_summary_error.clone() {
-                    ui.colored_label(theme::state::ERROR, format!("Error: {}", error));
-                } else if let Some(ref data) = self.neighborhood_summary_data.clone() {
-                    // Node/session counts
-                    ui.horizontal(|ui| {
-                        ui.label(format!("{} nodes across {} sessions",
-                            data.node_count, data.session_count));
-                    });
-                    ui.add_space(5.0);
+        if let Some(ref picked) = self.compare_pick {
+            ui.label(
+                egui::RichText::new(format!("Picked {} — right-click another node to compare", ids::short_session(picked)))
+                    .small()
+                    .color(self.palette.text.muted)
+            );
+        }
+    }
 
-                    // Summary
-                    ui.label(egui::RichText::new("Summary").strong());
-                    egui::ScrollArea::vertical()
-                        .max_height(100.0)
-                        .id_salt("window_neighborhood_summary_scroll")
-                        .show(ui, |ui| {
-                            ui.label(&data.summary);
-                        });
-                    ui.add_space(5.0);
+    /// Diff toggle and window A controls; window B is the timeline window
+    /// A session's short id that shows the session hover card
+    fn session_chip(&self, ui: &mut egui::Ui, session_id: &str) -> egui::Response {
+        ui.add(egui::Label::new(egui::RichText::new(ids::short_session(session_id)).monospace()).sense(egui::Sense::click()))
+            .on_hover_ui(|ui| self.render_session_card(ui, session_id))
+    }
 
-                    // Themes
-                    if !data.themes.is_empty() {
-                        ui.label(egui::RichText::new("Themes").strong().color(Color32::from_rgb(100, 149, 237)));
-                        ui.label(&data.themes);
-                    }
-                } else {
-                    ui.colored_label(
-                        egui::Color32::GRAY,
-                        "Ctrl+Click a node to summarize\nit and its neighbors.",
-                    );
+    /// Hover card for a session. Everything comes from maps built at load or
+    /// summaries already fetched, so hovering never queries the backend.
+    fn render_session_card(&self, ui: &mut egui::Ui, session_id: &str) {
+        theme::scale_text(ui.style_mut(), self.settings.tooltip_text_scale * self.palette.tooltip_text_scale);
+        ui.set_max_width(280.0);
+        let muted = self.palette.text.muted;
+        let span = self.graph.session_spans.get(session_id);
+        let summary = self.session_summary_cache.get(session_id);
+
+        ui.label(egui::RichText::new(format!("Session {}", truncate(session_id, 13))).strong());
+        let project = summary.and_then(|s| s.detected_project.as_deref())
+            .or(span.map(|s| s.project.as_str()))
+            .filter(|p| !p.is_empty());
+        if let Some(project) = project {
+            ui.label(egui::RichText::new(project).small().color(muted));
+        }
+        let tags = self.session_tags.tags_for(session_id);
+        if !tags.is_empty() {
+            ui.horizontal_wrapped(|ui| {
+                for tag in tags {
+                    ui.label(egui::RichText::new(format!("#{}", tag)).small().color(self.palette.accent.cyan));
                 }
+            });
+        }
+        let Some(span) = span else {
+            ui.label(egui::RichText::new("Not in the loaded data").small().color(muted));
+            return;
+        };
+        ui.label(egui::RichText::new(format!(
+            "{} · {} msgs · {} tokens",
+            compare::format_duration(span.duration_secs()),
+            span.messages,
+            format_count(span.tokens as i64),
+        )).small());
+        if let Some(prompt) = span.first_prompt.as_deref().and_then(|id| self.graph.get_node(id)) {
+            ui.add_space(4.0);
+            ui.label(egui::RichText::new(truncate(&prompt.content_preview, 140)).small().italics());
+        }
+        if let Some(text) = summary.and_then(|s| s.summary.as_deref()).filter(|t| !t.trim().is_empty()) {
+            ui.add_space(4.0);
+            ui.label(egui::RichText::new(truncate(text::first_sentence(text), 200)).small().color(muted));
+        }
+    }
 
-                ui.add_space(10.0);
-                ui.separator();
+    /// Sessions idle for at least `SESSION_IDLE_SECS` whose last message is
+    /// under an hour old; clicking one frames its nodes
+    fn render_recently_ended(&mut self, ui: &mut egui::Ui) {
+        let now = unix_now();
+        let mut ended: Vec<(&String, &crate::graph::types::SessionSpan)> = self.graph.session_spans.iter()
+            .filter(|(_, span)| (SESSION_IDLE_SECS..=RECENTLY_ENDED_SECS).contains(&(now - span.end)))
+            .collect();
+        if ended.is_empty() {
+            ui.label(egui::RichText::new("No sessions ended in the last hour").small().color(self.palette.text.muted));
+            return;
+        }
+        ended.sort_by(|a, b| b.1.end.total_cmp(&a.1.end).then_with(|| a.0.cmp(b.0)));
 
-                // Clear button - only clears neighborhood window state
-                if ui.button("Clear & Close").clicked() {
-                    self.neighborhood_summary_data = None;
-                    self.neighborhood_summary_error = None;
-                    self.neighborhood_summary_center_node = None;
-                    self.neighborhood_summary_count = 0;
-                    self.neighborhood_window_open = false;
-                    self.neighborhood_window_dragged = false;
-                }
+        let muted = self.palette.text.muted;
+        let mut framed = None;
+        for (session_id, span) in ended {
+            let short = ids::short_session(session_id);
+            let project = span.project.rsplit('/').next().unwrap_or_default();
+            ui.horizontal(|ui| {
+                if ui.link(format!("{} · {}", short, project))
+                    .on_hover_ui(|ui| {
+                        self.render_session_card(ui, session_id);
+                        ui.label(egui::RichText::new("Click to frame this session in the graph").small().weak());
+                    })
+                    .clicked()
+                {
+                    framed = Some(session_id.clone());
+                }
+                ui.label(egui::RichText::new(format!(
+                    "{} · {} msgs · ended {} ago",
+                    compare::format_duration(span.duration_secs()),
+                    span.messages,
+                    compare::format_duration(now - span.end),
+                )).small().color(muted));
             });
+        }
+        if let Some(session_id) = framed {
+            let ids: Vec<String> = self.graph.data.nodes.iter()
+                .filter(|n| n.session_id == session_id)
+                .map(|n| n.id.clone())
+                .collect();
+            self.fit_request = Some(ids);
+        }
+    }
 
-        self.neighborhood_window_open = open;
+    fn render_diff_section(&mut self, ui: &mut egui::Ui) {
+        let mut diff_mode = self.diff_mode;
+        if ui.checkbox(&mut diff_mode, "Diff against window A")
+            .on_hover_text("New messages in the timeline window draw normally, ones only in A are ghosted, ones in both are dimmed")
+            .changed()
+        {
+            self.set_diff_mode(diff_mode);
+        }
+        if !self.diff_mode {
+            return;
+        }
 
-        // Detect if window was dragged from default position
-        if let Some(inner) = window_response {
-            let pos = inner.response.rect.left_top();
-            let dist = (pos - default_pos).length();
-            if dist > 10.0 {
-                self.neighborhood_window_dragged = true;
+        let timeline = &self.graph.timeline;
+        let b = self.graph.get_timeline_window();
+        let a = self.diff_window_a.unwrap_or_else(|| diff::previous_window(b));
+        let muted = self.palette.text.muted;
+        egui::Grid::new("diff_windows").num_columns(2).show(ui, |ui| {
+            for (name, (start, end)) in [("A", a), ("B", b)] {
+                ui.label(egui::RichText::new(name).strong());
+                ui.label(egui::RichText::new(format!("{} – {}", timeline.format_time(start), timeline.format_time(end))).small().color(muted));
+                ui.end_row();
             }
+        });
+        ui.horizontal(|ui| {
+            if ui.button("Pin A to current window")
+                .on_hover_text("Keep this window as A, then move the timeline to choose B")
+                .clicked()
+            {
+                self.diff_window_a = Some(b);
+                self.effective_visible_dirty = true;
+            }
+            if self.diff_window_a.is_some() && ui.button("Previous span").on_hover_text("Let A follow the span just before B").clicked() {
+                self.diff_window_a = None;
+                self.effective_visible_dirty = true;
+            }
+        });
+    }
+
+    /// Enter or leave diff mode; diffing needs the timeline window as B
+    fn set_diff_mode(&mut self, enabled: bool) {
+        self.diff_mode = enabled;
+        if enabled && !self.timeline_enabled {
+            self.timeline_enabled = true;
+            self.mark_settings_dirty();
+        }
+        if !enabled {
+            self.diff_window_a = None;
         }
+        self.effective_visible_dirty = true;
     }
 
-    /// Render the beads panel (issues/tasks)
-    fn render_beads_panel(&mut self, ui: &mut egui::Ui) {
+    /// Counts above the graph while diffing: new sessions, new messages, token delta
+    fn render_diff_strip(&mut self, ui: &mut egui::Ui) {
+        let Some(summary) = self.graph_diff.as_ref().map(|d| d.summary) else {
+            return;
+        };
+        let muted = self.palette.text.muted;
         ui.horizontal(|ui| {
-            ui.heading("Beads");
+            ui.add_space(8.0);
+            ui.label(egui::RichText::new("Diff").strong());
+            ui.label(format!("+{} sessions", summary.new_sessions));
+            ui.label(format!("+{} messages", summary.new_messages));
+            let sign = if summary.token_delta >= 0 { "+" } else { "" };
+            ui.label(format!("{}{} tokens", sign, format_count(summary.token_delta)));
+            ui.label(egui::RichText::new(format!("{} only in A", summary.old_messages)).color(muted));
             ui.with_layout(egui::Layout::right_to_left(egui::Align::Center), |ui| {
-                ui.label(
-                    egui::RichText::new("B to toggle")
-                        .small()
-                        .color(theme::text::MUTED)
-                );
+                if ui.small_button("Exit diff").clicked() {
+                    self.set_diff_mode(false);
+                }
             });
         });
-        ui.add_space(8.0);
-        ui.separator();
-        ui.add_space(8.0);
+    }
 
-        egui::ScrollArea::vertical().show(ui, |ui| {
-            // Placeholder content - beads data integration would go here
-            ui.label(
-                egui::RichText::new("Issue tracking panel")
-                    .color(theme::text::SECONDARY)
-            );
-            ui.add_space(16.0);
+    /// Show two sessions side by side in the central panel
+    fn enter_compare(&mut self, sessions: [String; 2]) {
+        self.compare_choice = [Some(sessions[0].clone()), Some(sessions[1].clone())];
+        self.compare_sessions = Some(sessions);
+        self.compare_pick = None;
+        self.compare_pan_y = 0.0;
+        self.compare_zoom = 1.0;
+        self.effective_visible_dirty = true;
+    }
 
-            // Sample structure showing what the panel would contain
-            ui.label(egui::RichText::new("Ready").strong());
-            ui.add_space(4.0);
-            ui.label(
-                egui::RichText::new("No ready issues")
-                    .color(theme::text::MUTED)
-                    .italics()
-            );
+    /// Return to the normal graph; layout and data are left untouched
+    fn exit_compare(&mut self) {
+        self.compare_sessions = None;
+        self.compare_nodes = [Vec::new(), Vec::new()];
+    }
 
-            ui.add_space(16.0);
-            ui.label(egui::RichText::new("In Progress").strong());
-            ui.add_space(4.0);
-            ui.label(
-                egui::RichText::new("No issues in progress")
-                    .color(theme::text::MUTED)
-                    .italics()
-            );
+    /// Session graph derived from the loaded data
+    fn build_overview(&self) -> overview::Overview {
+        let detected: HashMap<String, String> = self.session_summary_cache.iter()
+            .filter_map(|(id, summary)| Some((id.clone(), summary.detected_project.clone()?)))
+            .collect();
+        overview::build(
+            &self.graph.data.nodes,
+            &self.graph.data.edges,
+            &detected,
+            self.settings.overview_overlap_mins.max(0.0) as f64 * 60.0,
+        )
+    }
 
-            ui.add_space(16.0);
-            ui.label(egui::RichText::new("Blocked").strong());
-            ui.add_space(4.0);
-            ui.label(
-                egui::RichText::new("No blocked issues")
-                    .color(theme::text::MUTED)
-                    .italics()
-            );
+    /// Replace the message graph with one node per session
+    fn enter_overview(&mut self) {
+        self.exit_compare();
+        let built = self.build_overview();
+        let mut graph = GraphState::new();
+        // Overlap edges are built here, not from timestamps at load
+        graph.temporal_attraction_enabled = false;
+        graph.load(built.data, egui::Rect::from_center_size(OVERVIEW_CENTER, Vec2::new(600.0, 400.0)));
+        self.style_overview_graph(&mut graph);
+        self.overview = Some(OverviewState {
+            graph,
+            layout: ForceLayout::default(),
+            messages: built.messages,
+            pan: Vec2::ZERO,
+            zoom: 1.0,
         });
     }
 
-    /// Render the mail panel (inbox/outbox)
-    fn render_mail_panel(&mut self, ui: &mut egui::Ui) {
+    fn exit_overview(&mut self) {
+        self.overview = None;
+    }
+
+    /// Re-derive an open overview after the data changed, keeping its layout
+    fn rebuild_overview(&mut self) {
+        if self.overview.is_none() {
+            return;
+        }
+        let built = self.build_overview();
+        let bounds = egui::Rect::from_center_size(OVERVIEW_CENTER, Vec2::new(600.0, 400.0));
+        let Some(mut state) = self.overview.take() else {
+            return;
+        };
+        state.graph.merge(built.data, bounds);
+        state.messages = built.messages;
+        self.style_overview_graph(&mut state.graph);
+        self.overview = Some(state);
+    }
+
+    /// Match the main graph's coloring so sessions keep their colors
+    fn style_overview_graph(&self, graph: &mut GraphState) {
+        graph.color_mode = self.graph.color_mode;
+        graph.shading = self.graph.shading;
+        graph.is_light = self.graph.is_light;
+        graph.colorblind_safe = self.graph.colorblind_safe;
+        graph.hue_offset = self.graph.hue_offset;
+        graph.hue_overrides = self.graph.hue_overrides.clone();
+        graph.session_colors.extend(self.graph.session_colors.iter().map(|(k, v)| (k.clone(), *v)));
+        graph.project_colors.extend(self.graph.project_colors.iter().map(|(k, v)| (k.clone(), *v)));
+        graph.session_slots.extend(self.graph.session_slots.iter().map(|(k, v)| (k.clone(), *v)));
+        graph.project_slots.extend(self.graph.project_slots.iter().map(|(k, v)| (k.clone(), *v)));
+    }
+
+    /// Leave the overview and show only this session's messages
+    fn drill_into_session(&mut self, session_id: String) {
+        self.exit_overview();
+        self.histogram_session_filter = Some(session_id);
+        self.histogram_last_clicked = None;
+        self.histogram_drill_level = 2;
+        self.graph.selected_node = None;
+        self.effective_visible_dirty = true;
+    }
+
+    /// Session graph: drag to pan, scroll to zoom, click a session to drill down
+    fn render_overview(&mut self, ui: &mut egui::Ui) {
+        let mut rebuild = false;
         ui.horizontal(|ui| {
-            ui.heading("Mail");
+            ui.label(egui::RichText::new("Session overview").strong());
+            ui.label(egui::RichText::new("Size by").color(self.palette.text.muted));
+            for &sizing in OverviewSizing::all() {
+                if ui.selectable_label(self.settings.overview_sizing == sizing, sizing.label()).clicked() {
+                    self.settings.overview_sizing = sizing;
+                    self.mark_settings_dirty();
+                }
+            }
+            ui.separator();
+            ui.label(egui::RichText::new("Overlap ≥").color(self.palette.text.muted));
+            let mut mins = self.settings.overview_overlap_mins;
+            if ui.add(egui::DragValue::new(&mut mins).range(0.0..=240.0).suffix(" min").speed(0.5))
+                .on_hover_text("Link sessions whose time spans overlap at least this long")
+                .changed()
+            {
+                self.settings.overview_overlap_mins = mins;
+                self.mark_settings_dirty();
+                rebuild = true;
+            }
             ui.with_layout(egui::Layout::right_to_left(egui::Align::Center), |ui| {
-                ui.label(
-                    egui::RichText::new("M to toggle")
-                        .small()
-                        .color(theme::text::MUTED)
-                );
+                if ui.button("Exit overview").clicked() {
+                    self.exit_overview();
+                }
             });
         });
-        ui.add_space(8.0);
-        ui.separator();
-        ui.add_space(8.0);
+        if rebuild {
+            self.rebuild_overview();
+        }
 
-        // Tab selection for Inbox/Outbox (placeholder - actual tab state would go here)
-        ui.horizontal(|ui| {
-            let _ = ui.selectable_label(true, "Inbox");
-            let _ = ui.selectable_label(false, "Outbox");
-        });
-        ui.add_space(8.0);
+        let sizing = self.settings.overview_sizing;
+        let node_size = self.node_size;
+        let palette = self.palette;
+        let Some(state) = self.overview.as_mut() else {
+            return;
+        };
 
-        egui::ScrollArea::vertical().show(ui, |ui| {
-            // Placeholder content - mail data integration would go here
-            ui.label(
-                egui::RichText::new("Mail panel")
-                    .color(theme::text::SECONDARY)
-            );
-            ui.add_space(16.0);
+        let (response, painter) = ui.allocate_painter(ui.available_size(), egui::Sense::click_and_drag());
+        let rect = response.rect;
+        painter.rect_filled(rect, 0.0, palette.bg.graph);
+
+        if response.dragged_by(egui::PointerButton::Primary) {
+            state.pan += response.drag_delta();
+        }
+        if response.hovered() {
+            let (scroll, zoom_delta) = ui.input(|i| (i.smooth_scroll_delta, i.zoom_delta()));
+            state.pan += scroll;
+            if zoom_delta != 1.0 {
+                state.zoom = (state.zoom * zoom_delta).clamp(0.05, 20.0);
+            }
+        }
+
+        if !state.layout.is_settled(&state.graph, None) {
+            if theme::motion_enabled() {
+                state.layout.step(&mut state.graph, OVERVIEW_CENTER, None, None, &[]);
+                ui.ctx().request_repaint();
+            } else {
+                state.layout.settle(&mut state.graph, OVERVIEW_CENTER, None, None, &[], PRESETTLE_MAX_STEPS);
+            }
+        }
+
+        let zoom = state.zoom;
+        let pan = state.pan;
+        let transform = |pos: Pos2| rect.center() + (pos - OVERVIEW_CENTER) * zoom + pan;
+
+        let max_messages = state.messages.values().copied().max().unwrap_or(1).max(1) as f32;
+        let max_tokens = state.graph.data.nodes.iter().map(|n| n.total_tokens()).max().unwrap_or(1).max(1) as f32;
+        let radius_of = |node: &crate::graph::types::GraphNode| {
+            let share = match sizing {
+                OverviewSizing::Messages => state.messages.get(&node.session_id).copied().unwrap_or(0) as f32 / max_messages,
+                OverviewSizing::Tokens => node.total_tokens() as f32 / max_tokens,
+            };
+            (node_size * 0.5 * (1.0 + 3.0 * share.sqrt()) * zoom).max(2.0)
+        };
+
+        for edge in &state.graph.data.edges {
+            let (Some(from), Some(to)) = (state.graph.get_pos(&edge.source), state.graph.get_pos(&edge.target)) else {
+                continue;
+            };
+            let weight = edge.similarity.unwrap_or(1.0).clamp(0.1, 1.0);
+            let color = state.graph.edge_color(edge).gamma_multiply(0.3 + 0.5 * weight);
+            painter.line_segment([transform(from), transform(to)], Stroke::new(1.0 + 2.0 * weight, color));
+        }
+
+        let hover_pos = response.hover_pos();
+        let mut hovered: Option<(usize, f32)> = None;
+        let show_labels = state.graph.data.nodes.len() <= 80;
+        for (idx, node) in state.graph.data.nodes.iter().enumerate() {
+            let Some(pos) = state.graph.get_pos(&node.id) else {
+                continue;
+            };
+            let screen_pos = transform(pos);
+            let radius = radius_of(node);
+            let fill = state.graph.node_color(node);
+            painter.circle_filled(screen_pos, radius, fill);
+            painter.circle_stroke(screen_pos, radius, Stroke::new(1.0, fill.gamma_multiply(0.7)));
+            if show_labels {
+                self.text_layout.text(
+                    &painter,
+                    screen_pos + Vec2::new(0.0, radius + 2.0),
+                    egui::Align2::CENTER_TOP,
+                    &truncate(&node.content_preview, 32),
+                    egui::FontId::proportional(11.0),
+                    palette.text.muted,
+                );
+            }
+            if let Some(hover) = hover_pos {
+                let dist = hover.distance(screen_pos);
+                if dist <= radius + 4.0 && hovered.is_none_or(|(_, best)| dist < best) {
+                    hovered = Some((idx, dist));
+                }
+            }
+        }
 
-            ui.label(
-                egui::RichText::new("No messages")
-                    .color(theme::text::MUTED)
-                    .italics()
+        let hovered_session = hovered.map(|(idx, _)| state.graph.data.nodes[idx].session_id.clone());
+        if let (Some((idx, _)), Some(hover)) = (hovered, hover_pos) {
+            let node = &state.graph.data.nodes[idx];
+            let messages = state.messages.get(&node.session_id).copied().unwrap_or(0);
+            let text = format!(
+                "{}\n{} · {} messages · {} tokens\nClick to open this session",
+                truncate(&node.content_preview, 60),
+                if node.project.is_empty() { "no project" } else { node.project.as_str() },
+                format_count(messages as i64),
+                format_count(node.total_tokens() as i64),
             );
-        });
-    }
-
-    fn render_sidebar(&mut self, ui: &mut egui::Ui) {
-        // Tab bar at top
-        let prev_tab = self.sidebar_tab;
-        ui.horizontal(|ui| {
-            ui.selectable_value(&mut self.sidebar_tab, SidebarTab::Data, "Data");
-            ui.selectable_value(&mut self.sidebar_tab, SidebarTab::Nodes, "Nodes");
-            ui.selectable_value(&mut self.sidebar_tab, SidebarTab::Edges, "Edges");
-            ui.selectable_value(&mut self.sidebar_tab, SidebarTab::Filters, "Filters");
-        });
-        if self.sidebar_tab != prev_tab {
-            self.mark_settings_dirty();
+            let galley = self.text_layout.layout_no_wrap(&painter, &text, egui::FontId::proportional(12.0), palette.tooltip.text);
+            let text_pos = hover + Vec2::new(12.0, -galley.size().y / 2.0);
+            painter.rect_filled(egui::Rect::from_min_size(text_pos, galley.size()).expand(4.0), 4.0, palette.tooltip.bg);
+            painter.galley(text_pos, galley, palette.tooltip.text);
         }
-        ui.separator();
 
-        // Tab content
-        match self.sidebar_tab {
-            SidebarTab::Data => self.render_sidebar_data(ui),
-            SidebarTab::Nodes => self.render_sidebar_nodes(ui),
-            SidebarTab::Edges => self.render_sidebar_edges(ui),
-            SidebarTab::Filters => self.render_sidebar_filters(ui),
+        if response.clicked() {
+            if let Some(session_id) = hovered_session {
+                self.drill_into_session(session_id);
+            }
         }
     }
 
-    fn render_sidebar_data(&mut self, ui: &mut egui::Ui) {
-        // Database status
-        ui.horizontal(|ui| {
-            if self.db_connected {
-                ui.colored_label(theme::state::SUCCESS, "● DB Connected");
+    /// Render the saved preset list with apply, rename, overwrite, delete, and reorder controls
+    fn render_presets_section(&mut self, ui: &mut egui::Ui) {
+        let rename_id = egui::Id::new("preset_rename_edit");
+        let count = self.settings.presets.len();
+        let mut action: Option<PresetAction> = None;
+
+        // Fade out the highlight on a just-applied preset
+        // Reduced motion holds the highlight steady, then drops it
+        let motion = theme::motion_enabled();
+        let flash = self.preset_flash.and_then(|(idx, at)| {
+            let t = at.elapsed().as_secs_f32();
+            (t < PRESET_FLASH_SECS).then_some((idx, if motion { 1.0 - t / PRESET_FLASH_SECS } else { 1.0 }))
+        });
+        if let Some((_, at)) = self.preset_flash.filter(|_| flash.is_some()) {
+            if motion {
+                ui.ctx().request_repaint();
             } else {
-                ui.colored_label(theme::state::ERROR, "● DB Disconnected");
-                if ui.button("Retry").clicked() {
-                    self.reconnect_db();
-                    if self.db_connected {
-                        self.load_graph();
-                    }
-                }
+                ui.ctx().request_repaint_after(std::time::Duration::from_secs_f32(PRESET_FLASH_SECS).saturating_sub(at.elapsed()));
             }
-        });
-
-        if let Some(ref err) = self.db_error {
-            ui.colored_label(theme::state::ERROR, format!("Error: {}", err));
+        } else {
+            self.preset_flash = None;
         }
 
-        ui.add_space(10.0);
+        for idx in 0..count {
+            let name = self.settings.presets[idx].name.clone();
 
-        // Data Selection section
-        egui::CollapsingHeader::new("Data Selection")
-            .default_open(true)
-            .show(ui, |ui| {
-                ui.label(format!("Range: {}", format_hours_label(self.slider_hours)));
-                ui.add(
-                    egui::Slider::new(&mut self.slider_hours, 1.0..=2160.0)
-                        .logarithmic(true)
-                        .clamping(egui::SliderClamping::Always)
-                        .show_value(false),
-                );
-                let changed = (self.slider_hours - self.time_range_hours).abs() > 0.5;
-                ui.add_enabled_ui(changed, |ui| {
-                    if ui.button("Load").clicked() {
-                        self.time_range_hours = self.slider_hours;
-                        self.load_graph();
-                        self.mark_settings_dirty();
+            // Inline rename editor
+            if let Some((rename_idx, ref mut buffer)) = self.preset_renaming {
+                if rename_idx == idx {
+                    let new_name = buffer.trim().to_string();
+                    let conflict = self.settings.presets.iter().enumerate()
+                        .any(|(i, p)| i != idx && p.name == new_name);
+                    let valid = !new_name.is_empty() && !conflict;
+                    let mut cancel = false;
+                    ui.horizontal(|ui| {
+                        let response = ui.add(
+                            egui::TextEdit::singleline(buffer)
+                                .id(rename_id)
+                                .desired_width(120.0)
+                        );
+                        let enter = response.lost_focus() && ui.input(|i| i.key_pressed(egui::Key::Enter));
+                        if ui.add_enabled(valid, egui::Button::new("✔").small()).clicked() || (enter && valid) {
+                            action = Some(PresetAction::CommitRename(idx));
+                        }
+                        if ui.small_button("✖").clicked() || ui.input(|i| i.key_pressed(egui::Key::Escape)) {
+                            cancel = true;
+                        }
+                    });
+                    if conflict {
+                        ui.label(
+                            egui::RichText::new(format!("'{}' already exists", new_name))
+                                .small()
+                                .color(theme::state::WARNING),
+                        );
                     }
-                });
+                    if cancel {
+                        self.preset_renaming = None;
+                    }
+                    continue;
+                }
+            }
 
-                ui.add_space(5.0);
+            // Delete confirmation
+            if self.preset_delete_confirm == Some(idx) {
                 ui.horizontal(|ui| {
-                    if ui.button("⟳ Reload").clicked() {
-                        self.load_graph();
+                    ui.label(egui::RichText::new(format!("Delete '{}'?", name)).color(theme::state::WARNING));
+                    if ui.small_button("Delete").clicked() {
+                        action = Some(PresetAction::Delete(idx));
                     }
-                    if ui.button("↺ Reset All").clicked() {
-                        // Reset all UI state to defaults
-                        self.node_size = 15.0;
-                        self.show_arrows = true;
-                        self.graph.physics_enabled = true;
-                        self.timeline_enabled = true;
-                        // Reset sizing to Balanced preset
-                        self.sizing_preset = SizingPreset::Balanced;
-                        let (w_imp, w_tok, w_time) = SizingPreset::Balanced.weights();
-                        self.w_importance = w_imp;
-                        self.w_tokens = w_tok;
-                        self.w_time = w_time;
-                        self.layout.repulsion = 10000.0;
-                        self.layout.attraction = 0.1;
-                        self.layout.centering = 0.0001;
-                        self.layout.size_physics_weight = 0.0;
-                        self.layout.directed_stiffness = 1.0;
-                        self.layout.recency_centering = 0.0;
-                        self.layout.momentum = 0.0;
-                        self.pan_offset = Vec2::ZERO;
-                        self.zoom = 1.0;
-                        self.load_graph();
+                    if ui.small_button("Cancel").clicked() {
+                        self.preset_delete_confirm = None;
                     }
                 });
+                continue;
+            }
 
-                // Re-ingest sessions from ~/.claude/
-                if self.ingest_loading {
-                    ui.horizontal(|ui| {
-                        ui.spinner();
-                        ui.label("Ingesting sessions...");
-                    });
-                } else {
-                    if ui.button("↻ Re-ingest Sessions").on_hover_text(
-                        "Import new sessions from ~/.claude/ into the database"
-                    ).clicked() {
-                        self.start_ingest();
-                    }
+            ui.horizontal(|ui| {
+                // Reorder controls
+                if ui.add_enabled(idx > 0, egui::Button::new("\u{25B2}").small())
+                    .on_hover_text("Move up")
+                    .clicked()
+                {
+                    action = Some(PresetAction::Swap(idx, idx - 1));
                 }
-                if let Some(ref result) = self.ingest_result {
-                    ui.label(
-                        egui::RichText::new(format!(
-                            "Imported {} sessions, {} msgs",
-                            result.sessions, result.messages
-                        ))
-                        .size(11.0)
-                        .color(theme::state::SUCCESS),
-                    );
+                if ui.add_enabled(idx + 1 < count, egui::Button::new("\u{25BC}").small())
+                    .on_hover_text("Move down")
+                    .clicked()
+                {
+                    action = Some(PresetAction::Swap(idx, idx + 1));
                 }
 
-                // Last synced timestamp
-                if let Some(last_synced) = self.last_synced {
-                    let elapsed = last_synced.elapsed();
-                    let elapsed_str = if elapsed.as_secs() < 60 {
-                        format!("{}s ago", elapsed.as_secs())
-                    } else if elapsed.as_secs() < 3600 {
-                        format!("{}m ago", elapsed.as_secs() / 60)
-                    } else {
-                        format!("{}h ago", elapsed.as_secs() / 3600)
-                    };
-                    ui.label(format!("Last synced: {}", elapsed_str));
+                // Preset name: click to apply, highlighted when active
+                let is_active = self.selected_preset_index == Some(idx);
+                let mut text = egui::RichText::new(&name);
+                if is_active {
+                    text = text.color(self.palette.highlight.selected).strong();
                 }
-
-                // Auto-refresh toggle
-                ui.add_space(5.0);
-                let mut auto_refresh = self.settings.auto_refresh_enabled;
-                if ui.checkbox(&mut auto_refresh, "Auto-refresh").changed() {
-                    self.settings.auto_refresh_enabled = auto_refresh;
-                    self.mark_settings_dirty();
+                let fill = match flash {
+                    Some((flash_idx, strength)) if flash_idx == idx => {
+                        self.palette.accent.orange.gamma_multiply(0.6 * strength)
+                    }
+                    _ => Color32::TRANSPARENT,
+                };
+                if ui.add(egui::Button::new(text).fill(fill))
+                    .on_hover_text("Apply preset")
+                    .clicked()
+                {
+                    action = Some(PresetAction::Apply(idx));
                 }
-                if self.settings.auto_refresh_enabled {
-                    ui.horizontal(|ui| {
-                        ui.label("Interval:");
-                        let mut interval = self.settings.auto_refresh_interval_secs;
-                        if ui.add(egui::DragValue::new(&mut interval)
-                            .range(1.0..=60.0)
-                            .suffix("s")
-                            .speed(0.5)
-                        ).changed() {
-                            self.settings.auto_refresh_interval_secs = interval;
-                            self.mark_settings_dirty();
-                        }
-                    });
+                let is_startup = self.settings.startup_preset.as_deref() == Some(name.as_str());
+                if is_startup {
+                    ui.label(egui::RichText::new("startup").small().color(self.palette.text.muted))
+                        .on_hover_text("Applied at launch");
                 }
-            });
 
-        // Presets section
-        egui::CollapsingHeader::new("Presets")
-            .default_open(false)
-            .show(ui, |ui| {
-                // Dropdown to select a preset
-                let preset_names: Vec<String> = self.settings.presets.iter().map(|p| p.name.clone()).collect();
-                let selected_label = self.selected_preset_index
-                    .and_then(|i| preset_names.get(i).cloned())
-                    .unwrap_or_else(|| "Select preset...".to_string());
-
-                egui::ComboBox::from_id_salt("preset_selector")
-                    .selected_text(&selected_label)
-                    .show_ui(ui, |ui| {
-                        for (i, name) in preset_names.iter().enumerate() {
-                            if ui.selectable_value(&mut self.selected_preset_index, Some(i), name).changed() {
-                                // Apply the preset immediately on selection
-                                if let Some(preset) = self.settings.presets.get(i).cloned() {
-                                    preset.apply_to(&mut self.settings, &mut self.graph);
-                                    self.sync_ui_from_settings();
-                                    self.mark_settings_dirty();
-                                }
-                            }
+                // Per-preset actions menu
+                ui.menu_button("…", |ui| {
+                    if ui.button("Rename").clicked() {
+                        action = Some(PresetAction::StartRename(idx));
+                        ui.close_menu();
+                    }
+                    if ui.button("Overwrite with current").clicked() {
+                        action = Some(PresetAction::Overwrite(idx));
+                        ui.close_menu();
+                    }
+                    if ui.button("Delete…").clicked() {
+                        action = Some(PresetAction::ConfirmDelete(idx));
+                        ui.close_menu();
+                    }
+                    ui.separator();
+                    let mut is_startup = is_startup;
+                    if ui.checkbox(&mut is_startup, "Apply at startup").changed() {
+                        action = Some(PresetAction::ToggleStartup(idx));
+                    }
+                    for &mode in ViewMode::all() {
+                        let mut is_default = self.settings.view_default_preset(mode) == Some(name.as_str());
+                        if ui.checkbox(&mut is_default, format!("Default for {} view", mode.label())).changed() {
+                            action = Some(PresetAction::ToggleViewDefault(idx, mode));
                         }
-                    });
+                    }
+                })
+                .response
+                .on_hover_text("Preset actions");
+            });
+        }
 
-                ui.add_space(5.0);
+        if count == 0 {
+            ui.label("No saved presets yet");
+        }
 
-                // Save current settings as new preset
-                ui.horizontal(|ui| {
-                    ui.add(egui::TextEdit::singleline(&mut self.preset_name_input)
-                        .hint_text("Preset name")
-                        .desired_width(120.0));
-
-                    if ui.button("Save").clicked() && !self.preset_name_input.trim().is_empty() {
-                        let name = self.preset_name_input.trim().to_string();
-
-                        // Check if preset with this name exists
-                        if let Some(idx) = self.settings.presets.iter().position(|p| p.name == name) {
-                            // Update existing
-                            self.settings.presets[idx] = Preset::from_settings(name, &self.settings, &self.graph);
-                            self.selected_preset_index = Some(idx);
-                        } else {
-                            // Add new
-                            let preset = Preset::from_settings(name, &self.settings, &self.graph);
-                            self.settings.presets.push(preset);
-                            self.selected_preset_index = Some(self.settings.presets.len() - 1);
-                        }
-                        self.preset_name_input.clear();
-                        self.mark_settings_dirty();
-                    }
-                });
+        match action {
+            Some(PresetAction::Apply(idx)) => {
+                self.commands.push(AppCommand::ApplyPreset(self.settings.presets[idx].name.clone()));
+            }
+            Some(PresetAction::StartRename(idx)) => {
+                self.preset_delete_confirm = None;
+                self.preset_renaming = Some((idx, self.settings.presets[idx].name.clone()));
+                ui.memory_mut(|m| m.request_focus(rename_id));
+            }
+            Some(PresetAction::CommitRename(idx)) => {
+                if let Some((_, buffer)) = self.preset_renaming.take() {
+                    let new_name = buffer.trim().to_string();
+                    let old_name = std::mem::replace(&mut self.settings.presets[idx].name, new_name.clone());
+                    self.settings.rename_preset_references(&old_name, &new_name);
+                    self.mark_settings_dirty();
+                }
+            }
+            Some(PresetAction::Overwrite(idx)) => {
+                let name = self.settings.presets[idx].name.clone();
+                let idx = self.save_current_as_preset(name);
+                self.preset_flash = Some((idx, Instant::now()));
+            }
+            Some(PresetAction::ConfirmDelete(idx)) => {
+                self.preset_renaming = None;
+                self.preset_delete_confirm = Some(idx);
+            }
+            Some(PresetAction::Delete(idx)) => self.delete_preset(idx),
+            Some(PresetAction::Swap(a, b)) => self.swap_presets(a, b),
+            Some(PresetAction::ToggleStartup(idx)) => {
+                let name = self.settings.presets[idx].name.clone();
+                self.settings.startup_preset = if self.settings.startup_preset.as_deref() == Some(name.as_str()) {
+                    None
+                } else {
+                    Some(name)
+                };
+                self.mark_settings_dirty();
+            }
+            Some(PresetAction::ToggleViewDefault(idx, mode)) => {
+                let name = self.settings.presets[idx].name.clone();
+                let current = self.settings.view_default_preset(mode) == Some(name.as_str());
+                self.settings.set_view_default_preset(mode, if current { None } else { Some(name) });
+                self.mark_settings_dirty();
+            }
+            None => {}
+        }
 
-                // Delete selected preset
-                if self.selected_preset_index.is_some() {
-                    ui.add_space(5.0);
-                    if ui.button("Delete selected").clicked() {
-                        if let Some(idx) = self.selected_preset_index {
-                            if idx < self.settings.presets.len() {
-                                self.settings.presets.remove(idx);
-                                self.selected_preset_index = None;
-                                self.mark_settings_dirty();
-                            }
-                        }
+        ui.add_space(5.0);
+
+        // Save current settings as a new preset
+        ui.label("Save current as…");
+        let name = self.preset_name_input.trim().to_string();
+        let exists = self.settings.presets.iter().any(|p| p.name == name);
+        ui.horizontal(|ui| {
+            let response = ui.add(egui::TextEdit::singleline(&mut self.preset_name_input)
+                .hint_text("Preset name")
+                .desired_width(120.0));
+
+            let enter = response.lost_focus() && ui.input(|i| i.key_pressed(egui::Key::Enter));
+            let label = if exists { "Overwrite" } else { "Save" };
+            if (ui.add_enabled(!name.is_empty(), egui::Button::new(label)).clicked() || enter)
+                && !name.is_empty()
+            {
+                let idx = self.save_current_as_preset(name.clone());
+                self.preset_flash = Some((idx, Instant::now()));
+                self.preset_name_input.clear();
+            }
+        });
+        ui.checkbox(&mut self.preset_include_analysis, "Include filters & queries")
+            .on_hover_text("Save semantic filter modes and proximity queries with the preset");
+        if exists && !name.is_empty() {
+            ui.label(
+                egui::RichText::new(format!("'{}' exists and will be overwritten", name))
+                    .small()
+                    .color(theme::state::WARNING),
+            );
+        }
+    }
+
+    /// Copy/paste controls for shareable view strings
+    fn render_view_string_section(&mut self, ui: &mut egui::Ui) {
+        if ui.button("📋 Copy view string")
+            .on_hover_text("Copy time range, timeline window, selection, camera, colors, and filters")
+            .clicked()
+        {
+            match self.current_view_string().encode() {
+                Ok(text) => {
+                    ui.output_mut(|o| o.copied_text = text);
+                    self.view_string_status = Some(("Copied to clipboard".to_string(), false));
+                }
+                Err(e) => self.view_string_status = Some((e, true)),
+            }
+        }
+
+        ui.horizontal(|ui| {
+            let response = ui.add(egui::TextEdit::singleline(&mut self.view_string_input)
+                .hint_text("Paste view string")
+                .desired_width(150.0));
+            let enter = response.lost_focus() && ui.input(|i| i.key_pressed(egui::Key::Enter));
+            let has_input = !self.view_string_input.trim().is_empty();
+            if (ui.add_enabled(has_input, egui::Button::new("Apply")).clicked() || enter) && has_input {
+                match ViewString::decode(&self.view_string_input) {
+                    Ok(view) => {
+                        self.apply_view_string(view);
+                        self.view_string_input.clear();
+                        self.view_string_status = Some(("View applied".to_string(), false));
                     }
+                    Err(e) => self.view_string_status = Some((e, true)),
                 }
+            }
+        });
 
-                if self.settings.presets.is_empty() {
-                    ui.add_space(5.0);
-                    ui.label("No saved presets yet");
+        if let Some((ref message, is_error)) = self.view_string_status {
+            let color = if is_error { theme::state::ERROR } else { self.palette.text.muted };
+            ui.label(egui::RichText::new(message).small().color(color));
+        }
+    }
+
+    /// Capture positions, camera and visibility settings under `name`
+    fn take_snapshot(&mut self, name: String) {
+        self.sync_settings_from_ui();
+        let settings = Preset::from_settings(name.clone(), &self.settings, &self.graph);
+        self.snapshots.push(LayoutSnapshot::new(
+            name.clone(),
+            unix_now(),
+            &self.graph.positions,
+            [self.pan_offset.x, self.pan_offset.y],
+            self.zoom,
+            settings,
+        ));
+        self.snapshot_status = Some((format!("Saved '{}' ({} nodes)", name, self.graph.positions.len()), false));
+    }
+
+    /// Restore a snapshot's positions, camera and settings, then pause physics
+    /// so the layout stays put. Warns when many of its nodes are gone.
+    fn restore_snapshot(&mut self, idx: usize) {
+        let Some(snap) = self.snapshots.snapshots.get(idx).cloned() else {
+            return;
+        };
+        let matched = snap.match_fraction(|id| self.graph.node_index.contains_key(id));
+
+        snap.settings.apply_to(&mut self.settings, &mut self.graph);
+        self.sync_ui_from_settings();
+        self.recompute_bypass_edges();
+        self.effective_visible_dirty = true;
+        self.temporal_edges_dirty = true;
+
+        let restored = snap.restore_positions(&mut self.graph.positions);
+        self.graph.velocities.values_mut().for_each(|v| *v = Vec2::ZERO);
+        self.graph.physics_enabled = false;
+        self.pan_offset = Vec2::new(snap.pan[0], snap.pan[1]);
+        self.zoom = snap.zoom.clamp(0.005, 5.0);
+        self.mark_settings_dirty();
+
+        self.snapshot_status = Some(if matched < snapshot::MATCH_WARN_FRACTION {
+            (format!("Restored '{}': only {:.0}% of its nodes are loaded ({} placed)", snap.name, matched * 100.0, restored), true)
+        } else {
+            (format!("Restored '{}'; physics paused", snap.name), false)
+        });
+    }
+
+    fn render_favorites_section(&mut self, ui: &mut egui::Ui) {
+        if self.favorites.is_empty() {
+            ui.label(egui::RichText::new("Right-click a message and choose Favorite to star it")
+                .small()
+                .color(self.palette.text.muted));
+        }
+        let mut go_to = None;
+        let mut unstar = None;
+        let mut save_note = None;
+        for fav in &self.favorites {
+            let node_id = fav.node_id();
+            let timestamp = fav.timestamp.as_deref().and_then(parse_iso_timestamp);
+            ui.horizontal(|ui| {
+                if ui.small_button("★").on_hover_text("Remove favorite").clicked() {
+                    unstar = Some(node_id.clone());
+                }
+                let when = timestamp
+                    .and_then(|t| chrono::DateTime::from_timestamp(t as i64, 0))
+                    .map(|t| t.with_timezone(&chrono::Local).format("%b %d").to_string())
+                    .unwrap_or_default();
+                let label = format!("{} {}", when, truncate(fav.preview.lines().next().unwrap_or_default(), 40));
+                if ui.add(egui::Label::new(egui::RichText::new(label).small()).sense(egui::Sense::click()))
+                    .on_hover_text(format!("{}\n\n{}", truncate(&fav.preview, 200), fav.note.as_deref().unwrap_or("Click to go to it")))
+                    .clicked()
+                {
+                    go_to = Some((node_id.clone(), timestamp));
                 }
             });
+            match self.favorite_note_edit {
+                Some((id, ref mut text)) if id == fav.message_id => {
+                    let response = ui.add(egui::TextEdit::singleline(text).hint_text("Note").desired_width(f32::INFINITY));
+                    if response.lost_focus() {
+                        save_note = Some((id, text.clone()));
+                    }
+                }
+                _ => {
+                    let note = fav.note.as_deref().unwrap_or("Add note");
+                    let color = if fav.note.is_some() { self.palette.text.secondary } else { self.palette.text.muted };
+                    if ui.add(egui::Label::new(egui::RichText::new(note).small().italics().color(color)).sense(egui::Sense::click()))
+                        .on_hover_text("Click to edit")
+                        .clicked()
+                    {
+                        self.favorite_note_edit = Some((fav.message_id, fav.note.clone().unwrap_or_default()));
+                    }
+                }
+            }
+        }
+        if let Some((id, note)) = save_note {
+            self.favorite_note_edit = None;
+            if let Some(ref db) = self.db {
+                match db.set_favorite_note(id, &note) {
+                    Ok(()) => self.load_favorites(),
+                    Err(e) => self.favorites_status = Some((e, true)),
+                }
+            }
+        }
+        if let Some(node_id) = unstar {
+            self.set_favorite(&node_id, false);
+        }
+        if let Some((node_id, timestamp)) = go_to {
+            self.go_to_favorite(node_id, timestamp);
+        }
+        if let Some((ref message, is_error)) = self.favorites_status {
+            let color = if is_error { theme::state::WARNING } else { self.palette.text.muted };
+            ui.label(egui::RichText::new(message).small().color(color));
+        }
+    }
 
-        ui.add_space(5.0);
-        ui.separator();
+    fn render_snapshots_section(&mut self, ui: &mut egui::Ui) {
+        ui.horizontal(|ui| {
+            let response = ui.add(egui::TextEdit::singleline(&mut self.snapshot_name_input)
+                .hint_text("Snapshot name")
+                .desired_width(120.0));
+            let enter = response.lost_focus() && ui.input(|i| i.key_pressed(egui::Key::Enter));
+            if ui.button("📸 Take snapshot")
+                .on_hover_text("Store node positions, camera and visibility settings")
+                .clicked()
+                || enter
+            {
+                let typed = self.snapshot_name_input.trim().to_string();
+                let name = if typed.is_empty() {
+                    chrono::Local::now().format("Snapshot %H:%M:%S").to_string()
+                } else {
+                    typed
+                };
+                self.take_snapshot(name);
+                self.snapshot_name_input.clear();
+            }
+        });
+
+        let mut restore = None;
+        let mut remove = None;
+        for (idx, snap) in self.snapshots.snapshots.iter().enumerate().rev() {
+            ui.horizontal(|ui| {
+                let taken = chrono::DateTime::from_timestamp(snap.taken_at as i64, 0)
+                    .map(|t| t.with_timezone(&chrono::Local).format("%b %d %H:%M").to_string())
+                    .unwrap_or_default();
+                if ui.button(&snap.name)
+                    .on_hover_text(format!("{} · {} nodes · {:.0}% zoom", taken, snap.positions.len(), snap.zoom * 100.0))
+                    .clicked()
+                {
+                    restore = Some(idx);
+                }
+                if ui.small_button("✖").on_hover_text("Delete snapshot").clicked() {
+                    remove = Some(idx);
+                }
+            });
+        }
+        if let Some(idx) = restore {
+            self.restore_snapshot(idx);
+        }
+        if let Some(idx) = remove {
+            self.snapshots.remove(idx);
+        }
 
-        // View / Zoom controls
-        ui.label("View");
         ui.horizontal(|ui| {
-            if ui.button("Reset View").clicked() {
-                self.pan_offset = Vec2::ZERO;
-                self.zoom = 1.0;
+            if ui.add_enabled(!self.snapshots.snapshots.is_empty(), egui::Button::new("Save to file"))
+                .on_hover_text(format!("Keep at most {} snapshots", snapshot::MAX_SNAPSHOTS))
+                .clicked()
+            {
+                self.snapshot_status = Some(match self.snapshots.save() {
+                    Ok(path) => (format!("Saved to {}", path.display()), false),
+                    Err(e) => (e, true),
+                });
+            }
+            if ui.button("Load from file").clicked() {
+                self.snapshot_status = Some(match SnapshotHistory::load() {
+                    Ok(history) => {
+                        let count = history.snapshots.len();
+                        self.snapshots = history;
+                        (format!("Loaded {} snapshot(s)", count), false)
+                    }
+                    Err(e) => (e, true),
+                });
             }
-            ui.label(format!("Zoom: {:.0}%", self.zoom * 100.0));
         });
 
-        ui.add_space(5.0);
+        if let Some((ref message, is_error)) = self.snapshot_status {
+            let color = if is_error { theme::state::WARNING } else { self.palette.text.muted };
+            ui.label(egui::RichText::new(message).small().color(color));
+        }
+    }
 
-        // Token Histogram toggle
-        if ui.checkbox(&mut self.histogram_panel_enabled, "Token Histogram Panel")
-            .on_hover_text("Show token usage histogram in a split pane")
-            .changed()
-        { self.mark_settings_dirty(); }
+    /// (Re)load the user theme file and install its palettes
+    fn reload_theme_file(&mut self) {
+        match theme_file::load_and_install() {
+            Ok(Some(file)) => {
+                let message = if file.errors.is_empty() {
+                    format!("Theme file loaded ({} overrides)", file.override_count())
+                } else {
+                    format!(
+                        "Theme file loaded with {} problem(s); {} overrides applied",
+                        file.errors.len(),
+                        file.override_count()
+                    )
+                };
+                self.theme_file_status = Some((message, !file.errors.is_empty()));
+                self.theme_file_errors = file.errors;
+            }
+            Ok(None) => {
+                self.theme_file_status = None;
+                self.theme_file_errors.clear();
+            }
+            Err(e) => {
+                tracing::warn!("{}", e);
+                self.theme_file_status = Some((e, true));
+                self.theme_file_errors.clear();
+            }
+        }
+    }
 
-        ui.add_space(5.0);
-        ui.separator();
+    /// Capture the current view for sharing
+    fn current_view_string(&mut self) -> ViewString {
+        self.sync_settings_from_ui();
+        let timeline = &self.graph.timeline;
+        let (timeline_start, timeline_end) = if self.timeline_enabled {
+            (
+                Some(timeline.time_at_position(timeline.start_position)),
+                Some(timeline.time_at_position(timeline.position)),
+            )
+        } else {
+            (None, None)
+        };
+        let mut hidden_projects: Vec<String> = self.available_projects.iter()
+            .filter(|p| !self.selected_projects.is_empty() && !self.selected_projects.contains(*p))
+            .cloned()
+            .collect();
+        hidden_projects.sort();
+
+        ViewString {
+            time_range_hours: self.time_range_hours,
+            session: self.session_filter.clone(),
+            timeline_start,
+            timeline_end,
+            selected_node: self.graph.selected_node.clone(),
+            pan: [self.pan_offset.x, self.pan_offset.y],
+            zoom: self.zoom,
+            color_mode: self.settings.color_mode,
+            preset: self.selected_preset_index
+                .and_then(|idx| self.settings.presets.get(idx))
+                .map(|p| p.name.clone()),
+            importance_threshold: self.settings.importance_threshold,
+            importance_filter: self.settings.importance_filter,
+            tool_use_filter: self.settings.tool_use_filter,
+            project_filter: self.settings.project_filter,
+            hidden_projects,
+            semantic_filter_modes: self.semantic_filter_modes.iter()
+                .filter(|(_, mode)| **mode != SemanticFilterMode::Off)
+                .map(|(&id, &mode)| (id, mode))
+                .collect(),
+        }
+    }
 
-        // Embedding stats and generation
-        let stats_info = self.embedding_stats.as_ref().map(|s| (s.embedded, s.total, s.unembedded));
-        let gen_loading = self.embedding_gen_loading;
+    /// Apply a decoded view string: its preset first, then the encoded overrides,
+    /// reloading data when the encoded range or session differs
+    fn apply_view_string(&mut self, view: ViewString) {
+        if let Some(ref name) = view.preset {
+            self.apply_named_preset(name);
+        }
+
+        self.sync_settings_from_ui();
+        self.settings.color_mode = view.color_mode;
+        self.settings.importance_threshold = view.importance_threshold;
+        self.settings.importance_filter = view.importance_filter;
+        self.settings.tool_use_filter = view.tool_use_filter;
+        self.settings.project_filter = view.project_filter;
+        self.settings.timeline_enabled = view.timeline_end.is_some();
+        self.sync_ui_from_settings();
+
+        if view.semantic_filter_modes != self.semantic_filter_modes {
+            self.semantic_filter_modes = view.semantic_filter_modes.clone();
+            self.request_semantic_filter_compute();
+        }
+        self.pan_offset = Vec2::new(view.pan[0], view.pan[1]);
+        self.zoom = view.zoom.clamp(0.005, 5.0);
+
+        // Projects, selection and timeline window refer to the view's data,
+        // so with a different range or session they wait for the reload
+        let pending = PendingViewString {
+            view: LastView {
+                pan: view.pan,
+                zoom: view.zoom,
+                selected_node: view.selected_node,
+                timeline_start: view.timeline_start,
+                timeline_end: view.timeline_end,
+            },
+            hidden_projects: view.hidden_projects,
+        };
+        let range_changed = (view.time_range_hours - self.time_range_hours).abs() > 0.5;
+        if range_changed || view.session != self.session_filter {
+            self.hours_from_cli &= !range_changed;
+            self.time_range_hours = view.time_range_hours.max(1.0);
+            self.slider_hours = self.time_range_hours;
+            self.session_filter = view.session;
+            self.pending_view_string = Some(pending);
+            self.schedule_reload();
+        } else {
+            self.restore_view_string(pending);
+        }
 
-        if let Some((embedded, total, unembedded)) = stats_info {
-            ui.label(format!("{} / {} embedded", embedded, total));
+        self.recompute_bypass_edges();
+        self.effective_visible_dirty = true;
+        self.temporal_edges_dirty = true;
+        self.mark_settings_dirty();
+    }
 
-            if unembedded > 0 {
-                let gen_text = if gen_loading { "Generating..." } else { "Generate" };
-                ui.horizontal(|ui| {
-                    if gen_loading {
-                        ui.spinner();
-                    }
-                    if ui.add_enabled(!gen_loading, egui::Button::new(gen_text)).clicked() {
-                        self.trigger_embedding_generation();
-                    }
-                    if ui.add_enabled(!gen_loading, egui::Button::new("Visible only")).clicked() {
-                        self.trigger_embedding_generation_visible();
-                    }
-                    ui.label(format!("{} remaining", unembedded));
-                });
+    /// Apply a saved preset by index and flash it in the preset list
+    fn apply_preset(&mut self, idx: usize) {
+        let Some(preset) = self.settings.presets.get(idx).cloned() else {
+            return;
+        };
+        // The active view's copy must be current in case the preset targets another view
+        self.sync_settings_from_ui();
+        preset.apply_to(&mut self.settings, &mut self.graph);
+        self.sync_ui_from_settings();
+        if preset.include_analysis {
+            self.restore_preset_analysis(&preset);
+        }
+        self.recompute_bypass_edges();
+        self.effective_visible_dirty = true;
+        self.temporal_edges_dirty = true;
+        self.selected_preset_index = Some(idx);
+        self.preset_flash = Some((idx, Instant::now()));
+        self.mark_settings_dirty();
+    }
+
+    /// Restore a preset's semantic filter modes and proximity queries,
+    /// recomputing the filtered set and re-fetching query edges
+    fn restore_preset_analysis(&mut self, preset: &Preset) {
+        if preset.semantic_filter_modes != self.semantic_filter_modes {
+            self.semantic_filter_modes = preset.semantic_filter_modes.clone();
+            self.request_semantic_filter_compute();
+        }
+
+        let current: Vec<&str> = self.proximity_queries.iter().map(|q| q.query.as_str()).collect();
+        if current != preset.proximity_queries.iter().map(String::as_str).collect::<Vec<_>>() {
+            self.clear_proximity();
+            for query in &preset.proximity_queries {
+                self.add_proximity_query(query.clone());
             }
-        } else {
-            if ui.button("Load stats").clicked() {
-                self.load_embedding_stats();
+        }
+    }
+
+    /// Apply a preset by name. A missing preset keeps the current settings
+    /// and logs a warning once per name.
+    fn apply_named_preset(&mut self, name: &str) {
+        match self.settings.preset_index(name) {
+            Some(idx) => self.apply_preset(idx),
+            None => {
+                if self.missing_preset_warnings.insert(name.to_string()) {
+                    tracing::warn!("Preset '{}' not found, using current settings", name);
+                }
             }
         }
+    }
 
-        ui.add_space(5.0);
-        ui.separator();
+    /// Restore the active view's parameters, then apply the startup preset
+    /// (or the view's default preset when no startup preset is set)
+    fn apply_startup_preset(&mut self) {
+        let view_default = self.settings.apply_active_view_settings();
+        self.sync_ui_from_settings();
+        if let Some(name) = self.settings.startup_preset.clone().or(view_default) {
+            self.apply_named_preset(&name);
+        }
+    }
 
-        // Info section
-        ui.label("Info");
-        let visible_count = self.effective_visible_count;
-        let total_count = self.graph.data.nodes.len();
-        if self.any_filter_active() && visible_count < total_count {
-            ui.label(format!("Nodes: {} / {}", visible_count, total_count));
-        } else {
-            ui.label(format!("Nodes: {}", total_count));
+    /// Save the current settings as a preset, replacing any preset with the same name.
+    /// Returns the preset's index.
+    fn save_current_as_preset(&mut self, name: String) -> usize {
+        self.sync_settings_from_ui();
+        let mut preset = Preset::from_settings(name, &self.settings, &self.graph);
+        if self.preset_include_analysis {
+            let queries = self.proximity_queries.iter().map(|q| q.query.clone()).collect();
+            preset = preset.with_analysis(&self.semantic_filter_modes, queries);
+        }
+        let idx = match self.settings.presets.iter().position(|p| p.name == preset.name) {
+            Some(idx) => {
+                self.settings.presets[idx] = preset;
+                idx
+            }
+            None => {
+                self.settings.presets.push(preset);
+                self.settings.presets.len() - 1
+            }
+        };
+        self.selected_preset_index = Some(idx);
+        self.mark_settings_dirty();
+        idx
+    }
+
+    /// Delete a preset, keeping the active-preset index pointing at the same entry
+    fn delete_preset(&mut self, idx: usize) {
+        if idx >= self.settings.presets.len() {
+            return;
         }
-        ui.label(format!("Edges: {}", self.graph.data.edges.len()));
-        ui.label(format!("FPS: {:.1}", self.fps));
+        let removed = self.settings.presets.remove(idx);
+        self.settings.remove_preset_references(&removed.name);
+        remap_preset_rows(
+            &mut self.selected_preset_index,
+            &mut self.preset_renaming,
+            &mut self.preset_delete_confirm,
+            |row| preset_row_after_delete(row, idx),
+        );
+        self.preset_flash = None;
+        self.mark_settings_dirty();
+    }
 
-        let user_count = self.graph.data.nodes.iter().filter(|n| n.role == crate::graph::types::Role::User).count();
-        let assistant_count = self.graph.data.nodes.iter().filter(|n| n.role == crate::graph::types::Role::Assistant).count();
-        ui.label(format!("You: {} | Claude: {}", user_count, assistant_count));
+    /// Swap two presets in the list (used for reordering)
+    fn swap_presets(&mut self, a: usize, b: usize) {
+        let len = self.settings.presets.len();
+        if a >= len || b >= len {
+            return;
+        }
+        self.settings.presets.swap(a, b);
+        remap_preset_rows(
+            &mut self.selected_preset_index,
+            &mut self.preset_renaming,
+            &mut self.preset_delete_confirm,
+            |row| Some(preset_row_after_swap(row, a, b)),
+        );
+        self.preset_flash = None;
+        self.mark_settings_dirty();
+    }
+
+    /// Restore one section's fields to defaults. Physics and sizing use the
+    /// active view's tuned defaults so each view resets to its own values.
+    fn reset_section(&mut self, section: SettingsSection) {
+        let defaults = Settings::default();
+        let view = ViewSettings::defaults_for(self.settings.view_mode);
+        match section {
+            SettingsSection::Display => {
+                self.node_size = defaults.node_size;
+                self.graph.color_mode = defaults.color_mode;
+                self.settings.hue_assignment = defaults.hue_assignment;
+                self.apply_hue_assignment();
+                self.settings.colorblind_safe = defaults.colorblind_safe;
+                self.settings.high_contrast = defaults.high_contrast;
+                self.settings.reduce_motion = defaults.reduce_motion;
+                self.settings.importance_rings = defaults.importance_rings;
+                self.settings.content_badges = defaults.content_badges;
+                self.settings.smart_declutter = defaults.smart_declutter;
+                self.settings.session_end_markers = defaults.session_end_markers;
+                self.settings.idle_gap_labels = defaults.idle_gap_labels;
+                self.settings.idle_gap_minutes = defaults.idle_gap_minutes;
+                self.settings.filter_pills = defaults.filter_pills;
+                self.settings.ui_scale = defaults.ui_scale;
+                self.settings.sidebar_text_scale = defaults.sidebar_text_scale;
+                self.settings.tooltip_text_scale = defaults.tooltip_text_scale;
+                self.settings.tooltip = defaults.tooltip;
+                self.ui_scale_dirty = true;
+            }
+            SettingsSection::Colors => {
+                self.settings.role_colors = defaults.role_colors;
+            }
+            SettingsSection::NodeSizing => {
+                self.sizing_preset = view.sizing_preset;
+                self.w_importance = view.w_importance;
+                self.w_tokens = view.w_tokens;
+                self.w_time = view.w_time;
+                self.max_node_multiplier = view.max_node_multiplier;
+            }
+            SettingsSection::SizePhysics => {
+                self.layout.size_physics_weight = view.size_physics_weight;
+            }
+            SettingsSection::Physics => {
+                self.graph.physics_enabled = view.physics_enabled;
+                self.layout.repulsion = view.repulsion;
+                self.layout.attraction = view.attraction;
+                self.layout.centering = view.centering;
+                self.layout.momentum = view.momentum;
+            }
+            SettingsSection::LayoutShaping => {
+                self.layout.directed_stiffness = view.directed_stiffness;
+                self.layout.recency_centering = view.recency_centering;
+            }
+            SettingsSection::TemporalClustering => {
+                self.layout.temporal_strength = view.temporal_strength;
+                self.graph.temporal_window_secs = (defaults.temporal_window_mins * 60.0) as f64;
+                self.temporal_edge_opacity = defaults.temporal_edge_opacity;
+                self.graph.max_temporal_edges = defaults.max_temporal_edges;
+                self.graph.max_temporal_neighbors = defaults.max_temporal_neighbors;
+                self.temporal_edges_dirty = true;
+            }
+            SettingsSection::Importance => {
+                self.importance_filter = defaults.importance_filter;
+                self.importance_threshold = defaults.importance_threshold;
+            }
+            SettingsSection::Project => {
+                self.project_filter = defaults.project_filter;
+                self.selected_projects = self.available_projects.iter().cloned().collect();
+            }
+            SettingsSection::ToolUses => {
+                self.tool_use_filter = defaults.tool_use_filter;
+                self.settings.code_only_filter = defaults.code_only_filter;
+                self.settings.role_filter = defaults.role_filter;
+                self.settings.collapse_tool_results = defaults.collapse_tool_results;
+                self.graph.set_collapse_tool_results(defaults.collapse_tool_results);
+            }
+            SettingsSection::SemanticFilters => {
+                if self.has_active_semantic_filters() {
+                    self.semantic_filter_modes.clear();
+                    self.request_semantic_filter_compute();
+                }
+            }
+        }
+        self.recompute_bypass_edges();
+        self.effective_visible_dirty = true;
+        self.mark_settings_dirty();
     }
 
     fn render_sidebar_nodes(&mut self, ui: &mut egui::Ui) {
         // Display section
-        egui::CollapsingHeader::new("Display")
+        let reset = ResettableSection::new("Display")
             .default_open(true)
             .show(ui, |ui| {
                 if ui.add(egui::Slider::new(&mut self.node_size, 5.0..=50.0).text("Node size")).changed() {
@@ -2508,9 +7683,120 @@ impl DashboardApp {
                         self.graph.color_mode = ColorMode::Session;
                         self.mark_settings_dirty();
                     }
+                    if ui.selectable_label(self.graph.color_mode == ColorMode::Community, "Community")
+                        .on_hover_text("Nodes joined by conversation or similarity edges share a color")
+                        .clicked()
+                    {
+                        self.graph.color_mode = ColorMode::Community;
+                        self.mark_settings_dirty();
+                    }
                     ui.separator();
                     if ui.button("🎲").on_hover_text("Randomize hues").clicked() {
                         self.graph.randomize_hue_offset();
+                        self.settings_history_pending = true;
+                    }
+                });
+                let mut sequential = self.settings.hue_assignment == HueAssignment::Sequential;
+                if ui.checkbox(&mut sequential, "Spread hues by load order")
+                    .on_hover_text("Space hues as far apart as possible in the order sessions load. Colors then change when the time range does; off, each session keeps its hue.")
+                    .changed()
+                {
+                    self.settings.hue_assignment = if sequential { HueAssignment::Sequential } else { HueAssignment::Stable };
+                    self.apply_hue_assignment();
+                    self.mark_settings_dirty();
+                }
+                ui.horizontal_wrapped(|ui| {
+                    ui.label("Filter pills:");
+                    for &pill in FilterPill::all() {
+                        let mut shown = self.settings.filter_pills.contains(&pill);
+                        if ui.checkbox(&mut shown, pill.label()).changed() {
+                            if shown {
+                                self.settings.filter_pills.push(pill);
+                            } else {
+                                self.settings.filter_pills.retain(|&p| p != pill);
+                            }
+                            self.mark_settings_dirty();
+                        }
+                    }
+                });
+                ui.add_space(5.0);
+                let mut scale = self.ui_scale_drag.unwrap_or(self.settings.ui_scale);
+                let response = ui.add(egui::Slider::new(&mut scale, 0.75..=2.0).text("UI scale").suffix("×"))
+                    .on_hover_text("Scale the whole interface (Ctrl +/- also works). Graph zoom is unaffected.");
+                if response.dragged() {
+                    // Rescaling mid-drag would move the slider out from under the pointer
+                    self.ui_scale_drag = Some(scale);
+                } else if response.changed() || response.drag_stopped() {
+                    self.ui_scale_drag = None;
+                    self.settings.ui_scale = scale;
+                    self.ui_scale_dirty = true;
+                    self.mark_settings_dirty();
+                }
+                if ui.add(egui::Slider::new(&mut self.settings.sidebar_text_scale, 0.8..=1.6).text("Sidebar text").suffix("×")).changed() {
+                    self.mark_settings_dirty();
+                }
+                if ui.add(egui::Slider::new(&mut self.settings.tooltip_text_scale, 0.8..=1.6).text("Tooltip text").suffix("×")).changed() {
+                    self.mark_settings_dirty();
+                }
+                if ui.button("Tooltip fields…").on_hover_text("Choose and order what node tooltips show").clicked() {
+                    self.tooltip_config_open = !self.tooltip_config_open;
+                }
+
+                if ui.checkbox(&mut self.settings.high_contrast, "High contrast (P)")
+                    .on_hover_text("Presentation mode: thicker edges and borders, pure text colors, larger tooltips")
+                    .changed()
+                {
+                    self.mark_settings_dirty();
+                }
+                if ui.checkbox(&mut self.settings.reduce_motion, "Reduce motion")
+                    .on_hover_text("No shimmer or pulsing; the layout settles once after loading and then holds still")
+                    .changed()
+                {
+                    theme::set_reduce_motion(self.settings.reduce_motion);
+                    self.presettle_pending = self.settings.reduce_motion;
+                    self.mark_settings_dirty();
+                }
+                if ui.checkbox(&mut self.settings.colorblind_safe, "Colorblind safe")
+                    .on_hover_text("Use a categorical palette (Okabe-Ito) for projects and sessions, and swap red/green status colors")
+                    .changed()
+                {
+                    self.mark_settings_dirty();
+                }
+                if ui.checkbox(&mut self.settings.importance_rings, "Importance rings")
+                    .on_hover_text("Arc around scored nodes, longer and more orange for higher importance. Hidden on small nodes; turn off to halve node draw calls.")
+                    .changed()
+                {
+                    self.mark_settings_dirty();
+                }
+                if ui.checkbox(&mut self.settings.content_badges, "Code badges")
+                    .on_hover_text("</> beside code-heavy messages once nodes are large enough to read it")
+                    .changed()
+                {
+                    self.mark_settings_dirty();
+                }
+                if ui.checkbox(&mut self.settings.smart_declutter, "Smart declutter")
+                    .on_hover_text("When zoomed out, draw only the most important nodes in each patch of screen and shade the rest. Zoom in to reveal more.")
+                    .changed()
+                {
+                    self.mark_settings_dirty();
+                }
+                if ui.checkbox(&mut self.settings.session_end_markers, "Session end markers")
+                    .on_hover_text("Square marker on the last message of each session, and a tick on the timeline where it ended")
+                    .changed()
+                {
+                    self.mark_settings_dirty();
+                }
+                ui.horizontal(|ui| {
+                    if ui.checkbox(&mut self.settings.idle_gap_labels, "Idle gaps over")
+                        .on_hover_text("When zoomed in, label conversation edges with the time between their messages. Longer gaps are drawn greyer either way.")
+                        .changed()
+                    {
+                        self.mark_settings_dirty();
+                    }
+                    if ui.add(egui::DragValue::new(&mut self.settings.idle_gap_minutes).range(1.0..=1440.0).speed(1.0).suffix(" min"))
+                        .changed()
+                    {
+                        self.mark_settings_dirty();
                     }
                 });
 
@@ -2518,9 +7804,34 @@ impl DashboardApp {
                 ui.checkbox(&mut self.debug_tooltip, "Debug tooltip")
                     .on_hover_text("Show node classification and rendering debug info in tooltip");
             });
+        if reset {
+            self.reset_section(SettingsSection::Display);
+        }
+
+        // Colors section
+        let reset = ResettableSection::new("Colors")
+            .default_open(false)
+            .show(ui, |ui| {
+                egui::Grid::new("role_colors").num_columns(2).show(ui, |ui| {
+                    for role in Role::all() {
+                        ui.label(role.label());
+                        let mut color = role.color(&self.palette);
+                        if ui.color_edit_button_srgba(&mut color).changed() {
+                            *self.settings.role_colors.get_mut(role) = Some(color);
+                            self.palette.role = theme::current(ui.ctx()).role;
+                            self.settings.role_colors.apply(&mut self.palette.role);
+                            self.mark_settings_dirty();
+                        }
+                        ui.end_row();
+                    }
+                });
+            });
+        if reset {
+            self.reset_section(SettingsSection::Colors);
+        }
 
         // Node Sizing section
-        egui::CollapsingHeader::new("Node Sizing")
+        let reset = ResettableSection::new("Node Sizing")
             .default_open(true)
             .show(ui, |ui| {
                 // Preset dropdown
@@ -2589,9 +7900,12 @@ impl DashboardApp {
                 ui.add_space(5.0);
                 ui.label(egui::RichText::new("Largest node will be this multiple of base size").weak().small());
             });
+        if reset {
+            self.reset_section(SettingsSection::NodeSizing);
+        }
 
         // Size → Physics weight
-        egui::CollapsingHeader::new("Size → Physics")
+        let reset = ResettableSection::new("Size → Physics")
             .default_open(false)
             .show(ui, |ui| {
                 if ui.add(egui::Slider::new(&mut self.layout.size_physics_weight, 0.0..=5.0)
@@ -2601,49 +7915,154 @@ impl DashboardApp {
                 }
                 ui.label(egui::RichText::new("Higher = small nodes become less significant in physics").small().weak());
             });
+        if reset {
+            self.reset_section(SettingsSection::SizePhysics);
+        }
 
         ui.add_space(5.0);
         ui.separator();
 
         // Legend
-        if self.graph.color_mode != ColorMode::Session {
+        if self.graph.color_mode == ColorMode::Community {
+            self.render_community_legend(ui);
+        } else if self.graph.color_mode != ColorMode::Session {
             ui.label(if self.graph.color_mode == ColorMode::Hybrid { "Projects (Hybrid)" } else { "Projects" });
-            // Show top projects by color
-            let mut projects: Vec<_> = self.graph.project_colors.iter().collect();
-            projects.sort_by(|a, b| a.0.cmp(b.0));
-            for (project, &hue) in projects.iter().take(8) {
-                ui.horizontal(|ui| {
-                    let color = crate::graph::types::hsl_to_rgb(hue, 0.7, 0.55);
+            // Show top projects by color; right-click a row to pin its hue
+            let mut projects: Vec<String> = self.graph.project_colors.keys().cloned().collect();
+            projects.sort();
+            let mut picked = None;
+            for project in projects.iter().take(8) {
+                let target = HueTarget::Project(project.clone());
+                let pinned = self.graph.hue_overrides.get(&target).is_some();
+                let row = ui.horizontal(|ui| {
+                    let color = self.graph.project_color(project);
                     ui.colored_label(color, "●");
-                    let label = if project.len() > 15 {
-                        format!("{}…", &project[..14])
-                    } else {
-                        project.to_string()
-                    };
-                    ui.label(label);
-                });
+                    ui.label(truncate(project, 15));
+                    if pinned {
+                        ui.label(egui::RichText::new("📌").small()).on_hover_text("Color set by hand");
+                    }
+                }).response;
+                ui.interact(row.rect, ui.id().with(("legend_project", project)), egui::Sense::click())
+                    .context_menu(|ui| {
+                        if let Some(action) = hue_override_menu(ui, pinned) {
+                            picked = Some((target.clone(), action));
+                        }
+                    });
             }
             if projects.len() > 8 {
                 ui.label(format!("  +{} more", projects.len() - 8));
             }
+            if let Some((target, action)) = picked {
+                self.apply_hue_menu_action(target, action);
+            }
         } else {
             ui.label("Legend");
             ui.horizontal(|ui| {
-                ui.colored_label(Color32::WHITE, "●");
+                ui.colored_label(Role::User.color(&self.palette), "●");
                 ui.label("You");
             });
             ui.horizontal(|ui| {
-                ui.colored_label(Color32::from_rgb(255, 149, 0), "●");
+                ui.colored_label(Role::Assistant.color(&self.palette), "●");
                 ui.label("Claude");
             });
         }
+
+        self.render_edge_legend(ui);
+
+        // Proximity query edge hues (fainter = weaker similarity)
+        if !self.proximity_queries.is_empty() {
+            ui.label("Proximity queries");
+            for (i, q) in self.proximity_queries.iter().enumerate() {
+                ui.horizontal(|ui| {
+                    ui.colored_label(theme::query_color(i, self.graph.colorblind_safe), "┈");
+                    ui.label(truncate(&q.query, 18));
+                });
+            }
+        }
     }
 
-    fn render_sidebar_edges(&mut self, ui: &mut egui::Ui) {
-        // Show arrows toggle
-        if ui.checkbox(&mut self.show_arrows, "Show arrows").changed() {
+    /// Edge kinds present in the loaded data, each with a line sample in its
+    /// drawn color and style and a checkbox that shows or hides the kind
+    fn render_edge_legend(&mut self, ui: &mut egui::Ui) {
+        let mut present: Vec<(EdgeKind, usize, Color32)> = Vec::new();
+        for edge in &self.graph.data.edges {
+            let kind = EdgeKind::of(edge);
+            match present.iter_mut().find(|(k, _, _)| *k == kind) {
+                Some((_, count, _)) => *count += 1,
+                None => present.push((kind, 1, self.graph.edge_color(edge))),
+            }
+        }
+        if present.is_empty() {
+            return;
+        }
+        present.sort_by_key(|(kind, _, _)| EdgeKind::ALL.iter().position(|k| k == kind));
+
+        ui.label("Edges");
+        let mut changed = false;
+        for (kind, count, color) in present {
+            let base_opacity = match kind {
+                EdgeKind::Temporal => self.temporal_edge_opacity,
+                EdgeKind::Similarity => self.proximity_edge_opacity,
+                _ => 0.5,
+            };
+            let style = self.settings.edge_styles.get_mut(kind);
+            ui.horizontal(|ui| {
+                changed |= ui.checkbox(&mut style.visible, "").changed();
+                let (rect, _) = ui.allocate_exact_size(Vec2::new(28.0, 10.0), egui::Sense::hover());
+                // Faint kinds stay legible in the sample
+                let opacity = (base_opacity * style.opacity * self.palette.edge_gamma).clamp(0.35, 1.0);
+                let mut sample = scene::EdgeBatch::new(ui.ctx().pixels_per_point());
+                sample.styled_line(rect.left_center(), rect.right_center(), style.width * self.palette.stroke_scale, color.gamma_multiply(opacity), style.line, 1.0);
+                ui.painter().extend(sample.into_shapes());
+                let label = egui::RichText::new(kind.label());
+                ui.label(if style.visible { label } else { label.color(self.palette.text.disabled) });
+                ui.label(egui::RichText::new(format_count(count as i64)).small().color(self.palette.text.muted));
+            });
+        }
+        if changed {
             self.mark_settings_dirty();
         }
+    }
+
+    /// Largest communities with their sizes; clicking one dims the rest
+    fn render_community_legend(&mut self, ui: &mut egui::Ui) {
+        ui.label("Communities");
+        let Some(ref communities) = self.graph.communities else {
+            ui.horizontal(|ui| {
+                ui.spinner();
+                ui.label(egui::RichText::new("Detecting…").small());
+            });
+            return;
+        };
+        let sizes: Vec<usize> = communities.sizes.iter().copied().take(8).collect();
+        let count = communities.count();
+        for (community, size) in sizes.into_iter().enumerate() {
+            ui.horizontal(|ui| {
+                ui.colored_label(self.graph.community_color(community), "●");
+                let focused = self.community_focus == Some(community);
+                if ui.selectable_label(focused, format!("#{} ({} nodes)", community + 1, size))
+                    .on_hover_text(if focused { "Show all communities" } else { "Dim the other communities" })
+                    .clicked()
+                {
+                    self.community_focus = if focused { None } else { Some(community) };
+                }
+            });
+        }
+        if count > 8 {
+            ui.label(format!("  +{} more", count - 8));
+        }
+    }
+
+    fn render_sidebar_edges(&mut self, ui: &mut egui::Ui) {
+        // Show arrows toggle
+        ui.horizontal(|ui| {
+            if ui.checkbox(&mut self.show_arrows, "Show arrows").changed() {
+                self.mark_settings_dirty();
+            }
+            if ui.button("Edge styles…").on_hover_text("Line pattern, width and opacity for each kind of edge").clicked() {
+                self.edge_styles_open = !self.edge_styles_open;
+            }
+        });
 
         ui.add_space(5.0);
 
@@ -2782,6 +8201,25 @@ impl DashboardApp {
                 });
             });
 
+            // "Find similar" results, shown whether or not queries are enabled
+            if self.similar_rx.is_some() || !self.discovered.is_empty() {
+                ui.horizontal(|ui| {
+                    if self.similar_rx.is_some() {
+                        ui.spinner();
+                        ui.label("Finding similar...");
+                    } else {
+                        ui.label(egui::RichText::new(format!(
+                            "Discovered: {} nodes, {} edges",
+                            self.discovered.nodes.len(),
+                            self.discovered.edges.len()
+                        )).small());
+                        if ui.small_button("Clear discovered").clicked() {
+                            self.clear_discovered();
+                        }
+                    }
+                });
+            }
+
             // Inline controls: search, tags, queries (always visible when enabled)
             if self.graph.score_proximity_enabled {
                 // Search input + Add button
@@ -2878,10 +8316,11 @@ impl DashboardApp {
                                 egui::vec2(8.0, 8.0),
                                 egui::Sense::hover(),
                             );
+                            let color = theme::query_color(i, self.graph.colorblind_safe);
                             let dot_color = if q.visible {
-                                q.color
+                                color
                             } else {
-                                q.color.gamma_multiply(0.3)
+                                color.gamma_multiply(0.3)
                             };
                             ui.painter().circle_filled(rect.center(), 4.0, dot_color);
 
@@ -2948,6 +8387,9 @@ impl DashboardApp {
         if ui.add(egui::Slider::new(&mut self.layout.momentum, 0.0..=0.95).fixed_decimals(2).text("Momentum")).changed() {
             self.mark_settings_dirty();
         }
+        if reset_defaults_button(ui) {
+            self.reset_section(SettingsSection::Physics);
+        }
     }
 
     fn render_layout_shaping_popup(&mut self, ui: &mut egui::Ui) {
@@ -2968,6 +8410,9 @@ impl DashboardApp {
                 self.mark_settings_dirty();
             }
             ui.label(egui::RichText::new("Higher = newer nodes pulled to center").small().weak());
+            if reset_defaults_button(ui) {
+                self.reset_section(SettingsSection::LayoutShaping);
+            }
         } else {
             ui.label(egui::RichText::new("Enable Layout Shaping to configure.").weak());
         }
@@ -3003,18 +8448,10 @@ impl DashboardApp {
             .fixed_decimals(2)).changed() {
             self.mark_settings_dirty();
         }
-
-        // Max temporal edges dropdown
-        let edge_limits = [
-            (10_000, "10k"),
-            (50_000, "50k"),
-            (100_000, "100k"),
-            (250_000, "250k"),
-            (500_000, "500k"),
-            (1_000_000, "1M"),
-        ];
+
+        // Max temporal edges dropdown
         let current_limit = self.graph.max_temporal_edges;
-        let current_label = edge_limits.iter()
+        let current_label = TEMPORAL_EDGE_LIMITS.iter()
             .find(|(v, _)| *v == current_limit)
             .map(|(_, l)| *l)
             .unwrap_or("Custom");
@@ -3024,7 +8461,7 @@ impl DashboardApp {
             egui::ComboBox::from_id_salt("max_temporal_edges")
                 .selected_text(current_label)
                 .show_ui(ui, |ui| {
-                    for (value, label) in edge_limits {
+                    for (value, label) in TEMPORAL_EDGE_LIMITS {
                         if ui.selectable_label(current_limit == value, label).clicked() {
                             self.graph.max_temporal_edges = value;
                             self.temporal_edges_dirty = true;
@@ -3035,9 +8472,37 @@ impl DashboardApp {
                 });
         });
 
+        // Per-node cap, so one dense burst can't take the whole budget
+        let current_neighbors = self.graph.max_temporal_neighbors;
+        let neighbors_label = TEMPORAL_NEIGHBOR_LIMITS.iter()
+            .find(|(v, _)| *v == current_neighbors)
+            .map(|(_, l)| *l)
+            .unwrap_or("Custom");
+        ui.horizontal(|ui| {
+            ui.label("Max per node:");
+            egui::ComboBox::from_id_salt("max_temporal_neighbors")
+                .selected_text(neighbors_label)
+                .show_ui(ui, |ui| {
+                    for (value, label) in TEMPORAL_NEIGHBOR_LIMITS {
+                        if ui.selectable_label(current_neighbors == value, label).clicked() {
+                            self.graph.max_temporal_neighbors = value;
+                            self.temporal_edges_dirty = true;
+                            self.settings.max_temporal_neighbors = value;
+                            self.mark_settings_dirty();
+                        }
+                    }
+                });
+        })
+        .response
+        .on_hover_text("Temporal edges any one message can have, so the edge budget spreads across the timeline");
+
         // Show temporal edge count
         let temporal_count = self.graph.data.edges.iter().filter(|e| e.is_temporal).count();
         ui.label(format!("Temporal edges: {}", temporal_count));
+
+        if reset_defaults_button(ui) {
+            self.reset_section(SettingsSection::TemporalClustering);
+        }
     }
 
     fn render_proximity_popup(&mut self, ui: &mut egui::Ui) {
@@ -3195,6 +8660,7 @@ impl DashboardApp {
 
     /// Render per-query popup (gear icon per proximity query)
     fn render_proximity_query_popup(&mut self, ui: &mut egui::Ui, qi: usize) {
+        let color = theme::query_color(qi, self.graph.colorblind_safe);
         let q = match self.proximity_queries.get_mut(qi) {
             Some(q) => q,
             None => {
@@ -3206,7 +8672,7 @@ impl DashboardApp {
         // Color swatch + query name
         ui.horizontal(|ui| {
             let (rect, _) = ui.allocate_exact_size(egui::vec2(12.0, 12.0), egui::Sense::hover());
-            ui.painter().circle_filled(rect.center(), 6.0, q.color);
+            ui.painter().circle_filled(rect.center(), 6.0, color);
             ui.label(egui::RichText::new(&q.query).strong());
         });
 
@@ -3301,6 +8767,215 @@ impl DashboardApp {
         }
     }
 
+    /// Window for choosing tooltip fields: checkboxes, drag handles to reorder,
+    /// and the preview length
+    fn render_tooltip_config(&mut self, ctx: &egui::Context) {
+        if !self.tooltip_config_open {
+            return;
+        }
+        let mut open = true;
+        let mut changed = false;
+        egui::Window::new("Tooltip fields")
+            .open(&mut open)
+            .default_pos([240.0, 140.0])
+            .auto_sized()
+            .collapsible(false)
+            .show(ctx, |ui| {
+                ui.label(egui::RichText::new("Drag ☰ to reorder").small().color(self.palette.text.muted));
+                let mut moved: Option<(usize, usize)> = None;
+                for i in 0..self.settings.tooltip.entries.len() {
+                    let row = ui.horizontal(|ui| {
+                        ui.dnd_drag_source(ui.id().with(("tooltip_field", i)), i, |ui| {
+                            ui.label("☰");
+                        });
+                        let entry = &mut self.settings.tooltip.entries[i];
+                        changed |= ui.checkbox(&mut entry.enabled, entry.field.label()).changed();
+                    }).response;
+                    if let Some(from) = row.dnd_release_payload::<usize>() {
+                        moved = Some((*from, i));
+                    }
+                    if row.dnd_hover_payload::<usize>().is_some() {
+                        ui.painter().hline(row.rect.x_range(), row.rect.top(), Stroke::new(2.0, self.palette.border.focus));
+                    }
+                }
+                if let Some((from, to)) = moved {
+                    self.settings.tooltip.move_entry(from, to);
+                    changed = true;
+                }
+                ui.separator();
+                let mut chars = self.settings.tooltip.preview_chars;
+                if ui.add(egui::Slider::new(&mut chars, 0..=500).text("Preview chars")).changed() {
+                    self.settings.tooltip.preview_chars = chars;
+                    changed = true;
+                }
+                if ui.button("Reset").clicked() {
+                    self.settings.tooltip = Default::default();
+                    changed = true;
+                }
+            });
+        if changed {
+            self.mark_settings_dirty();
+        }
+        if !open {
+            self.tooltip_config_open = false;
+        }
+    }
+
+    /// Window for per-kind edge styles, each row with a swatch drawn the way
+    /// the graph draws that kind at zoom 1
+    fn render_edge_styles(&mut self, ctx: &egui::Context) {
+        if !self.edge_styles_open {
+            return;
+        }
+        let mut open = true;
+        let mut changed = false;
+        egui::Window::new("Edge styles")
+            .open(&mut open)
+            .default_pos([240.0, 160.0])
+            .auto_sized()
+            .collapsible(false)
+            .show(ctx, |ui| {
+                egui::Grid::new("edge_styles").num_columns(5).spacing([8.0, 6.0]).show(ui, |ui| {
+                    for kind in EdgeKind::ALL {
+                        let style = self.settings.edge_styles.get_mut(kind);
+                        ui.label(kind.label());
+                        egui::ComboBox::from_id_salt(("edge_line", kind.label()))
+                            .selected_text(style.line.label())
+                            .width(70.0)
+                            .show_ui(ui, |ui| {
+                                for line in LineStyle::ALL {
+                                    changed |= ui.selectable_value(&mut style.line, line, line.label()).changed();
+                                }
+                            });
+                        changed |= ui.add(egui::Slider::new(&mut style.width, 0.5..=6.0).text("width")).changed();
+                        changed |= ui.add(egui::Slider::new(&mut style.opacity, 0.0..=2.0).text("opacity")).changed();
+
+                        let style = *style;
+                        let (rect, _) = ui.allocate_exact_size(Vec2::new(60.0, 14.0), egui::Sense::hover());
+                        let color = self.palette.text.secondary.gamma_multiply((0.8 * style.opacity).min(1.0));
+                        let mut swatch = scene::EdgeBatch::new(ctx.pixels_per_point());
+                        swatch.styled_line(rect.left_center(), rect.right_center(), style.width * self.palette.stroke_scale, color, style.line, 1.0);
+                        ui.painter().extend(swatch.into_shapes());
+                        ui.end_row();
+                    }
+                });
+                ui.label(egui::RichText::new("Patterns draw solid when zoomed far out").small().color(self.palette.text.muted));
+
+                ui.separator();
+                ui.label("Arrowheads");
+                let arrows = &mut self.settings.edge_styles.arrows;
+                ui.horizontal(|ui| {
+                    for style in ArrowStyle::ALL {
+                        changed |= ui.selectable_value(&mut arrows.style, style, style.label()).changed();
+                    }
+                });
+                changed |= ui.add(egui::Slider::new(&mut arrows.scale, 2.0..=12.0).text("size × width")).changed();
+                changed |= ui.add(egui::Slider::new(&mut arrows.min_zoom, 0.0..=2.0).text("hide below zoom")).changed();
+                ui.label(egui::RichText::new("Temporal and similarity edges never get arrows").small().color(self.palette.text.muted));
+                if ui.button("Reset").clicked() {
+                    self.settings.edge_styles = Default::default();
+                    changed = true;
+                }
+            });
+        if changed {
+            self.mark_settings_dirty();
+        }
+        if !open {
+            self.edge_styles_open = false;
+        }
+    }
+
+    /// What "Set color…" recolors for a node under the current color mode
+    fn hue_target_for(&self, node: &crate::graph::types::GraphNode) -> Option<HueTarget> {
+        if self.graph.colorblind_safe {
+            return None;
+        }
+        match self.graph.color_mode {
+            ColorMode::Project | ColorMode::Hybrid if !node.project.is_empty() => Some(HueTarget::Project(node.project.clone())),
+            ColorMode::Community => None,
+            _ => Some(HueTarget::Session(node.session_id.clone())),
+        }
+    }
+
+    fn apply_hue_menu_action(&mut self, target: HueTarget, action: HueMenuAction) {
+        match action {
+            HueMenuAction::Pick => self.hue_picker = Some(target),
+            HueMenuAction::Clear => self.set_hue_override(&target, None),
+        }
+    }
+
+    /// Pin (or with None, unpin) a project or session hue, in settings and on the graph
+    fn set_hue_override(&mut self, target: &HueTarget, hue: Option<f32>) {
+        self.settings.hue_overrides.set(target, hue);
+        self.graph.hue_overrides = self.settings.hue_overrides.clone();
+        if let Some(ref mut overview) = self.overview {
+            overview.graph.hue_overrides = self.settings.hue_overrides.clone();
+        }
+        self.mark_settings_dirty();
+    }
+
+    /// Hue strip and slider for the project or session picked from a menu
+    fn render_hue_picker(&mut self, ctx: &egui::Context) {
+        let Some(target) = self.hue_picker.clone() else {
+            return;
+        };
+        let current = match target {
+            HueTarget::Project(ref project) => self.graph.project_hue(project),
+            HueTarget::Session(ref session_id) => self.graph.session_hue(session_id),
+        };
+        let pinned = self.graph.hue_overrides.get(&target).is_some();
+        let mut open = true;
+        let mut hue = current;
+        let mut clear = false;
+        let mut done = false;
+        egui::Window::new("Set color")
+            .open(&mut open)
+            .default_pos([240.0, 140.0])
+            .auto_sized()
+            .collapsible(false)
+            .show(ctx, |ui| {
+                ui.label(truncate(&target.label(), 40));
+                // Click or drag along the strip to pick a hue
+                let (strip, painter) = ui.allocate_painter(egui::vec2(240.0, 18.0), egui::Sense::click_and_drag());
+                let steps = 36;
+                let step_width = strip.rect.width() / steps as f32;
+                for i in 0..steps {
+                    let cell = egui::Rect::from_min_size(
+                        egui::pos2(strip.rect.left() + i as f32 * step_width, strip.rect.top()),
+                        egui::vec2(step_width + 0.5, strip.rect.height()),
+                    );
+                    let cell_hue = (i as f32 + 0.5) * 360.0 / steps as f32;
+                    painter.rect_filled(cell, 0.0, crate::graph::types::hsl_to_rgb(cell_hue, 0.7, 0.55));
+                }
+                let marker_x = strip.rect.left() + strip.rect.width() * hue / 360.0;
+                painter.vline(marker_x, strip.rect.y_range(), Stroke::new(2.0, self.palette.text.primary));
+                if let Some(pos) = strip.interact_pointer_pos() {
+                    hue = ((pos.x - strip.rect.left()) / strip.rect.width()).clamp(0.0, 0.999) * 360.0;
+                }
+                ui.add(egui::Slider::new(&mut hue, 0.0..=360.0).text("Hue").suffix("°"));
+                ui.horizontal(|ui| {
+                    if ui.add_enabled(pinned, egui::Button::new("Clear override"))
+                        .on_hover_text("Go back to the computed color")
+                        .clicked()
+                    {
+                        clear = true;
+                    }
+                    done = ui.button("Done").clicked();
+                });
+            });
+        if clear {
+            self.set_hue_override(&target, None);
+            self.hue_picker = None;
+            return;
+        }
+        if (hue - current).abs() > f32::EPSILON {
+            self.set_hue_override(&target, Some(hue));
+        }
+        if !open || done {
+            self.hue_picker = None;
+        }
+    }
+
     fn render_sidebar_filters(&mut self, ui: &mut egui::Ui) {
         // Timeline controls
         egui::CollapsingHeader::new("Timeline")
@@ -3312,16 +8987,32 @@ impl DashboardApp {
                 }
                 if self.timeline_enabled {
                     if ui.checkbox(&mut self.hover_scrubs_timeline, "Hover scrubs timeline")
-                        .on_hover_text("Same-session hover scrubs instantly; click to jump to cross-session nodes")
+                        .on_hover_text("Hover the scrubber, or hold the modifier over a node, to preview that time; click to keep it")
                         .changed()
                     {
                         self.mark_settings_dirty();
                     }
+                    if self.hover_scrubs_timeline {
+                        ui.horizontal(|ui| {
+                            ui.label("Modifier:");
+                            let current = self.settings.hover_scrub_modifier;
+                            egui::ComboBox::from_id_salt("hover_scrub_modifier")
+                                .selected_text(current.label())
+                                .show_ui(ui, |ui| {
+                                    for &modifier in HoverScrubModifier::all() {
+                                        if ui.selectable_label(current == modifier, modifier.label()).clicked() {
+                                            self.settings.hover_scrub_modifier = modifier;
+                                            self.mark_settings_dirty();
+                                        }
+                                    }
+                                });
+                        });
+                    }
                 }
             });
 
         // Importance filter
-        egui::CollapsingHeader::new("Importance")
+        let reset = ResettableSection::new("Importance")
             .default_open(true)
             .show(ui, |ui| {
                 ui.horizontal(|ui| {
@@ -3398,9 +9089,12 @@ impl DashboardApp {
                     }
                 }
             });
+        if reset {
+            self.reset_section(SettingsSection::Importance);
+        }
 
         // Project filter
-        egui::CollapsingHeader::new("Project")
+        let reset = ResettableSection::new("Project")
             .default_open(true)
             .show(ui, |ui| {
                 ui.horizontal(|ui| {
@@ -3424,30 +9118,75 @@ impl DashboardApp {
                                 if ui.small_button("All").clicked() {
                                     self.selected_projects = self.available_projects.iter().cloned().collect();
                                     self.effective_visible_dirty = true;
+                                    self.settings_dirty = true;
                                 }
-                                if ui.small_button("None").clicked() {
-                                    self.selected_projects.clear();
-                                    self.effective_visible_dirty = true;
+                                let narrowed = !self.selected_projects.is_empty()
+                                    && self.available_projects.iter().any(|p| !self.selected_projects.contains(p));
+                                if ui.add_enabled(narrowed, egui::Button::new("Load only these").small())
+                                    .on_hover_text("Reload, fetching only the selected projects from the database")
+                                    .clicked()
+                                {
+                                    let mut projects: Vec<String> = self.selected_projects.iter().cloned().collect();
+                                    projects.sort();
+                                    self.project_pushdown = projects;
+                                    self.schedule_reload();
+                                }
+                            });
+                            ui.horizontal(|ui| {
+                                ui.add(egui::TextEdit::singleline(&mut self.project_tree_search)
+                                    .hint_text("Filter projects...")
+                                    .desired_width(150.0));
+                                if !self.project_tree_search.is_empty() && ui.small_button("✖").clicked() {
+                                    self.project_tree_search.clear();
                                 }
                             });
+                            let needle = self.project_tree_search.trim().to_lowercase();
                             egui::ScrollArea::vertical()
                                 .max_height(200.0)
                                 .show(ui, |ui| {
-                                    for child in &tree.children {
-                                        self.render_project_tree_node(ui, child);
+                                    for child in tree.children.iter().filter(|c| c.matches(&needle)) {
+                                        self.render_project_tree_node(ui, child, &needle);
+                                    }
+                                });
+                            let mut missing: Vec<String> = self.selected_projects.iter()
+                                .filter(|p| !self.available_projects.contains(p))
+                                .cloned()
+                                .collect();
+                            if !missing.is_empty() {
+                                missing.sort();
+                                ui.horizontal(|ui| {
+                                    ui.label(egui::RichText::new(format!("{} selected, not loaded", missing.len()))
+                                        .small()
+                                        .color(self.palette.text.muted));
+                                    if ui.small_button("Prune missing").clicked() {
+                                        let available: HashSet<&String> = self.available_projects.iter().collect();
+                                        self.selected_projects.retain(|p| available.contains(p));
+                                        self.settings_dirty = true;
                                     }
                                 });
+                                for path in &missing {
+                                    ui.label(egui::RichText::new(path).small().color(self.palette.text.disabled));
+                                }
+                            }
                             let visible = self.graph.data.nodes.iter()
-                                .filter(|n| self.selected_projects.contains(&n.project))
+                                .filter(|n| !self.project_hidden(&n.project))
                                 .count();
                             ui.label(format!("{} / {} nodes", visible, self.graph.data.nodes.len()));
                         });
                     }
                 }
+                self.render_excluded_projects(ui);
             });
+        if reset {
+            self.reset_section(SettingsSection::Project);
+        }
+
+        egui::CollapsingHeader::new("Tags")
+            .default_open(true)
+            .show(ui, |ui| self.render_tag_filter(ui));
 
         // Hide tool uses
-        egui::CollapsingHeader::new("Tool Uses")
+        let reset = ResettableSection::new("Tool Uses")
             .default_open(true)
             .show(ui, |ui| {
                 ui.horizontal(|ui| {
@@ -3469,10 +9208,41 @@ impl DashboardApp {
                     let total = self.graph.data.nodes.len();
                     ui.label(format!("Hiding: {} / {} nodes", tool_count, total));
                 }
+                let code_count = self.graph.content_classes.iter().filter(|c| c.kind == ContentKind::Code).count();
+                if ui.selectable_label(self.settings.code_only_filter, format!("</> Code-heavy only ({})", code_count))
+                    .on_hover_text("Show only messages that are mostly fenced code blocks")
+                    .clicked()
+                {
+                    self.settings.code_only_filter = !self.settings.code_only_filter;
+                    self.effective_visible_dirty = true;
+                    self.mark_settings_dirty();
+                }
+                if ui.checkbox(&mut self.settings.collapse_tool_results, "Collapse tool results")
+                    .on_hover_text("Fold consecutive tool-output messages in a session into one node")
+                    .changed()
+                {
+                    self.graph.set_collapse_tool_results(self.settings.collapse_tool_results);
+                    self.recompute_bypass_edges();
+                    self.effective_visible_dirty = true;
+                    self.mark_settings_dirty();
+                }
+                ui.horizontal(|ui| {
+                    ui.label("Roles:");
+                    for &filter in RoleFilter::all() {
+                        if ui.selectable_label(self.settings.role_filter == filter, filter.label()).clicked()
+                            && self.settings.role_filter != filter
+                        {
+                            self.set_role_filter(filter);
+                        }
+                    }
+                });
             });
+        if reset {
+            self.reset_section(SettingsSection::ToolUses);
+        }
 
         // Semantic Filters section
-        egui::CollapsingHeader::new("Semantic Filters")
+        let reset = ResettableSection::new("Semantic Filters")
             .default_open(false)
             .show(ui, |ui| {
                 // Loading indicator with skeleton
@@ -3529,73 +9299,48 @@ impl DashboardApp {
                                 .copied()
                                 .unwrap_or(SemanticFilterMode::Off);
 
-                            let inactive = theme::filter::INACTIVE;
-                            let active_neutral = theme::filter::ACTIVE;
-                            let active_green = theme::filter::INCLUDE;
-                            let active_blue = theme::filter::INCLUDE_PLUS1;
-                            let active_purple = theme::filter::INCLUDE_PLUS2;
-                            let active_red = theme::filter::EXCLUDE;
-
-                            // Off button (O)
-                            let off_color = if current_mode == SemanticFilterMode::Off { active_neutral } else { inactive };
-                            if ui.add(egui::Button::new("O").fill(off_color).min_size(Vec2::new(20.0, 18.0)))
-                                .on_hover_text("Off - filter not applied")
-                                .clicked()
-                            {
-                                self.semantic_filter_modes.insert(filter.id, SemanticFilterMode::Off);
-                                self.request_semantic_filter_compute();
-                            }
-
-                            // Exclude button (-)
-                            let exclude_color = if current_mode == SemanticFilterMode::Exclude { active_red } else { inactive };
-                            if ui.add(egui::Button::new("-").fill(exclude_color).min_size(Vec2::new(20.0, 18.0)))
-                                .on_hover_text("Exclude - hide matching nodes")
-                                .clicked()
-                            {
-                                self.semantic_filter_modes.insert(filter.id, SemanticFilterMode::Exclude);
-                                self.request_semantic_filter_compute();
-                            }
-
-                            // Include button (+)
-                            let include_color = if current_mode == SemanticFilterMode::Include { active_green } else { inactive };
-                            if ui.add(egui::Button::new("+").fill(include_color).min_size(Vec2::new(20.0, 18.0)))
-                                .on_hover_text("Include - only show matching nodes")
-                                .clicked()
-                            {
-                                                self.semantic_filter_modes.insert(filter.id, SemanticFilterMode::Include);
-                                self.request_semantic_filter_compute();
-                            }
-
-                            // Include +1 button (show matching + direct neighbors)
-                            let plus1_color = if current_mode == SemanticFilterMode::IncludePlus1 { active_blue } else { inactive };
-                            if ui.add(egui::Button::new("+1").fill(plus1_color).min_size(Vec2::new(24.0, 18.0)))
-                                .on_hover_text("Include +1 - show matching nodes + their direct neighbors")
-                                .clicked()
-                            {
-                                self.semantic_filter_modes.insert(filter.id, SemanticFilterMode::IncludePlus1);
-                                self.request_semantic_filter_compute();
-                            }
+                            let mode_color = match current_mode {
+                                SemanticFilterMode::Off => self.palette.filter.inactive,
+                                SemanticFilterMode::Include => self.palette.filter.include,
+                                SemanticFilterMode::IncludePlus1 => self.palette.filter.include_plus1,
+                                SemanticFilterMode::IncludePlus2 => self.palette.filter.include_plus2,
+                                SemanticFilterMode::Exclude => self.palette.filter.exclude,
+                            };
 
-                            // Include +2 button (show matching + neighbors up to depth 2)
-                            let plus2_color = if current_mode == SemanticFilterMode::IncludePlus2 { active_purple } else { inactive };
-                            if ui.add(egui::Button::new("+2").fill(plus2_color).min_size(Vec2::new(24.0, 18.0)))
-                                .on_hover_text("Include +2 - show matching nodes + neighbors up to 2 hops")
-                                .clicked()
-                            {
-                                self.semantic_filter_modes.insert(filter.id, SemanticFilterMode::IncludePlus2);
+                            // Single button cycles Off -> + -> +1 -> +2 -> - (right-click goes back)
+                            let button_text = format!("{} {}/{}", current_mode.symbol(), filter.matches, filter.total_scored);
+                            let response = ui
+                                .add(egui::Button::new(button_text).fill(mode_color).min_size(Vec2::new(64.0, 18.0)))
+                                .on_hover_text(format!(
+                                    "{}\nClick for next mode, right-click for previous",
+                                    current_mode.description()
+                                ));
+                            let new_mode = if response.clicked() {
+                                Some(current_mode.next())
+                            } else if response.secondary_clicked() {
+                                Some(current_mode.prev())
+                            } else {
+                                None
+                            };
+                            if let Some(mode) = new_mode {
+                                self.semantic_filter_modes.insert(filter.id, mode);
                                 self.request_semantic_filter_compute();
+                                self.settings_dirty = true;
                             }
 
                             // Type badge for rule filters
                             if filter.is_rule() {
                                 ui.label(egui::RichText::new("[R]").small().color(egui::Color32::from_rgb(100, 200, 100)));
                             }
+                            if let Some(error) = self.rule_errors.get(&filter.id) {
+                                ui.label(egui::RichText::new("⚠").color(theme::state::WARNING))
+                                    .on_hover_text(format!("Not evaluated locally: {}", error));
+                            }
 
                             // Clickable filter name to toggle detail panel
-                            let label_text = format!("{} ({}/{})", filter.name, filter.matches, filter.total_scored);
                             let label_response = ui.add(
                                 egui::Label::new(
-                                    egui::RichText::new(&label_text).underline()
+                                    egui::RichText::new(&filter.name).underline()
                                 ).sense(egui::Sense::click())
                             );
                             if label_response.clicked() {
@@ -3653,9 +9398,9 @@ impl DashboardApp {
                                     });
                                     ui.horizontal(|ui| {
                                         ui.add_space(8.0);
-                                        ui.label(egui::RichText::new(format!("+ {}", match_count)).color(theme::filter::INCLUDE));
+                                        ui.label(egui::RichText::new(format!("+ {}", match_count)).color(self.palette.filter.include));
                                         ui.label(egui::RichText::new("|").weak());
-                                        ui.label(egui::RichText::new(format!("- {}", exclude_count)).color(theme::filter::EXCLUDE));
+                                        ui.label(egui::RichText::new(format!("- {}", exclude_count)).color(self.palette.filter.exclude));
                                     });
 
                                     ui.add_space(2.0);
@@ -3802,6 +9547,9 @@ impl DashboardApp {
                     ));
                 }
             });
+        if reset {
+            self.reset_section(SettingsSection::SemanticFilters);
+        }
 
         ui.add_space(10.0);
         ui.separator();
@@ -3835,70 +9583,396 @@ impl DashboardApp {
                 }
             });
 
-        ui.add_space(10.0);
-        ui.separator();
+        ui.add_space(10.0);
+        ui.separator();
+
+        // Node at the scrubber, or the selected node
+        ui.horizontal(|ui| {
+            ui.label("Node at");
+            for &source in NodeInfoSource::all() {
+                if ui.selectable_label(self.settings.node_info_source == source, source.label()).clicked()
+                    && self.settings.node_info_source != source
+                {
+                    self.settings.node_info_source = source;
+                    self.mark_settings_dirty();
+                }
+            }
+        });
+        let selected_idx = self.graph.selected_node.as_ref()
+            .and_then(|id| self.graph.node_index.get(id).copied());
+        match self.settings.node_info_source {
+            NodeInfoSource::Scrubber => {
+                let scrubber_idx = self.node_at_scrubber();
+                if let Some(idx) = scrubber_idx {
+                    self.render_node_summary(ui, idx);
+                    // The selected node's session takes precedence over the scrubber's
+                    if let Some(anchor) = selected_idx.or(scrubber_idx) {
+                        self.render_mini_transcript(ui, anchor);
+                    }
+                } else {
+                    ui.label("No nodes loaded");
+                }
+            }
+            NodeInfoSource::Selection => {
+                if let Some(idx) = selected_idx {
+                    self.render_node_summary(ui, idx);
+                    self.render_mini_transcript(ui, idx);
+                } else {
+                    ui.label(egui::RichText::new("No node selected").color(self.palette.text.muted));
+                }
+            }
+        }
+    }
+
+    /// Role, time, session, project and a content preview for one node
+    fn render_node_summary(&mut self, ui: &mut egui::Ui, idx: usize) {
+        let node = self.graph.data.nodes[idx].clone();
+        ui.horizontal(|ui| {
+            let role_color = self.graph.node_color(&node);
+            ui.colored_label(role_color, "●");
+            ui.label(node.role.label());
+        });
+        if let Some(ref ts) = node.timestamp {
+            // Format timestamp using the timeline's format_time function for consistency
+            if let Some(epoch_secs) = node.timestamp_secs() {
+                let formatted = self.graph.timeline.format_time(epoch_secs);
+                ui.label(format!("Time: {}", formatted));
+            } else {
+                // Drawn every frame, so throttled
+                static PARSE_LOG: log_buffer::Throttle = log_buffer::Throttle::new();
+                if PARSE_LOG.ready() {
+                    tracing::warn!("Failed to parse timestamp: {}", ts);
+                }
+                // Fallback: show just the time portion if parsing fails
+                let time_display = if let Some(t_idx) = ts.find('T') {
+                    let time_part = &ts[t_idx + 1..];
+                    let end = time_part.find('+').unwrap_or(time_part.len()).min(8);
+                    time_part.get(..end).unwrap_or(time_part)
+                } else {
+                    ts.as_str()
+                };
+                ui.label(format!("Time: {}", time_display));
+            }
+        }
+        ui.horizontal(|ui| {
+            ui.label("Session:");
+            self.session_chip(ui, &node.session_id);
+        });
+        if !node.project.is_empty() {
+            ui.label(format!("Project: {}", node.project));
+        }
+        self.render_session_tag_editor(ui, &node.session_id);
+
+        // Content preview with word wrap
+        ui.add_space(5.0);
+        let preview = truncate(&node.content_preview, 100);
+        egui::ScrollArea::vertical()
+            .id_salt(("node_summary", idx))
+            .max_height(80.0)
+            .show(ui, |ui| {
+                ui.label(egui::RichText::new(preview).small());
+            });
+        self.render_tool_run_members(ui, idx);
+        self.render_node_forces(ui, idx);
+    }
+
+    /// What the physics sees of one node: its degree per edge kind, its
+    /// strongest temporal and similarity links, and its current speed
+    fn render_node_forces(&mut self, ui: &mut egui::Ui, idx: usize) {
+        egui::CollapsingHeader::new("Forces")
+            .id_salt("node_forces")
+            .default_open(false)
+            .show(ui, |ui| {
+                let degree = self.graph.degree_by_kind(idx);
+                egui::Grid::new("node_degree").num_columns(2).spacing([12.0, 2.0]).show(ui, |ui| {
+                    for (kind, count) in EdgeKind::ALL.into_iter().zip(degree) {
+                        if count > 0 {
+                            ui.label(kind.label());
+                            ui.label(format_count(count as i64));
+                            ui.end_row();
+                        }
+                    }
+                    ui.label(egui::RichText::new("Total").strong());
+                    ui.label(format_count(degree.iter().sum::<usize>() as i64));
+                    ui.end_row();
+                });
+
+                let links = self.graph.strongest_links(idx, NODE_FORCE_LINKS);
+                if !links.is_empty() {
+                    ui.add_space(4.0);
+                    ui.label(egui::RichText::new("Strongest links").small().color(self.palette.text.muted));
+                    let mut select = None;
+                    for (neighbour, kind, strength) in links {
+                        let node = &self.graph.data.nodes[neighbour];
+                        ui.horizontal(|ui| {
+                            ui.label(egui::RichText::new(format!("{:.2}", strength)).monospace());
+                            ui.label(egui::RichText::new(kind.label()).small().color(self.palette.text.secondary));
+                            if ui.link(truncate(&node.content_preview, 40)).clicked() {
+                                select = Some(node.id.clone());
+                            }
+                        });
+                    }
+                    if let Some(id) = select {
+                        self.commands.push(AppCommand::SelectNode(Some(id)));
+                    }
+                }
+
+                ui.add_space(4.0);
+                let id = self.graph.data.nodes[idx].id.clone();
+                let speed = self.graph.velocities.get(&id).map_or(0.0, |v| v.length());
+                ui.horizontal(|ui| {
+                    ui.label(format!("Speed: {:.2}", speed));
+                    if ui.add_enabled(speed > 0.0, egui::Button::new("Release energy").small())
+                        .on_hover_text("Zero this node's velocity")
+                        .clicked()
+                    {
+                        if let Some(v) = self.graph.velocities.get_mut(&id) {
+                            *v = Vec2::ZERO;
+                        }
+                    }
+                });
+            });
+    }
+
+    /// Tag chips for a session with remove buttons, and an input that adds
+    /// a tag on Enter, suggesting tags other sessions already use
+    fn render_session_tag_editor(&mut self, ui: &mut egui::Ui, session_id: &str) {
+        let mut removed = None;
+        ui.horizontal_wrapped(|ui| {
+            ui.label("Tags:");
+            for tag in self.session_tags.tags_for(session_id) {
+                if ui.add(egui::Button::new(egui::RichText::new(format!("{} ✖", tag)).small()).rounding(8.0))
+                    .on_hover_text("Remove tag")
+                    .clicked()
+                {
+                    removed = Some(tag.clone());
+                }
+            }
+        });
+        if let Some(tag) = removed {
+            self.set_session_tag(session_id, &tag, false);
+        }
+
+        let mut added = None;
+        let input = ui.add(egui::TextEdit::singleline(&mut self.tag_input)
+            .hint_text("Add tag…")
+            .desired_width(150.0));
+        if input.lost_focus() && ui.input(|i| i.key_pressed(egui::Key::Enter)) {
+            added = tags::normalize(&self.tag_input);
+        }
+        if input.has_focus() || !self.tag_input.is_empty() {
+            let suggestions = self.session_tags.suggestions(&self.tag_input, session_id);
+            if !suggestions.is_empty() {
+                ui.horizontal_wrapped(|ui| {
+                    for tag in suggestions {
+                        if ui.small_button(tag).clicked() {
+                            added = Some(tag.to_string());
+                        }
+                    }
+                });
+            }
+        }
+        if let Some(tag) = added {
+            self.set_session_tag(session_id, &tag, true);
+            self.tag_input.clear();
+        }
+        if let Some(ref err) = self.tags_error {
+            ui.label(egui::RichText::new(err).small().color(theme::state::ERROR));
+        }
+    }
+
+    /// Tags in use as toggles; selecting any shows only sessions carrying one
+    fn render_tag_filter(&mut self, ui: &mut egui::Ui) {
+        let counts: Vec<(String, usize)> = self.session_tags.counts().into_iter()
+            .map(|(tag, n)| (tag.to_string(), n))
+            .collect();
+        if counts.is_empty() {
+            ui.label(egui::RichText::new("Tag sessions from a selected node's details").small().color(self.palette.text.muted));
+            return;
+        }
+        let mut changed = false;
+        ui.horizontal_wrapped(|ui| {
+            for (tag, sessions) in &counts {
+                let on = self.tag_filter.tags.contains(tag);
+                if ui.selectable_label(on, format!("{} ({})", tag, sessions)).clicked() {
+                    self.tag_filter.toggle(tag);
+                    changed = true;
+                }
+            }
+        });
+        if self.tag_filter.is_active() {
+            let visible = self.graph.data.nodes.iter()
+                .filter(|n| self.tag_filter.admits(&self.session_tags, &n.session_id))
+                .count();
+            ui.horizontal(|ui| {
+                ui.label(format!("{} / {} nodes", visible, self.graph.data.nodes.len()));
+                if ui.small_button("Clear").clicked() {
+                    self.tag_filter = TagFilter::default();
+                    changed = true;
+                }
+            });
+        }
+        if changed {
+            self.effective_visible_dirty = true;
+        }
+    }
+
+    /// The tool results folded into node `idx`, oldest first
+    fn render_tool_run_members(&self, ui: &mut egui::Ui, idx: usize) {
+        let Some(members) = self.graph.tool_runs.members.get(&idx) else {
+            return;
+        };
+        ui.add_space(5.0);
+        ui.label(
+            egui::RichText::new(format!(
+                "{} tool results, {} tokens",
+                members.len() + 1,
+                format_count(self.graph.display_tokens(&self.graph.data.nodes[idx]) as i64)
+            ))
+            .small()
+            .strong(),
+        );
+        egui::ScrollArea::vertical()
+            .id_salt(("tool_run", idx))
+            .max_height(120.0)
+            .show(ui, |ui| {
+                for &member in members {
+                    let node = &self.graph.data.nodes[member];
+                    let when = self.graph.timeline.node_times.get(member).copied().flatten()
+                        .map(|t| self.graph.timeline.format_time(t))
+                        .unwrap_or_default();
+                    ui.label(egui::RichText::new(format!("{} · {}", when, truncate(&node.content_preview, 60))).small())
+                        .on_hover_text(format!("{} tokens", format_count(node.total_tokens() as i64)));
+                }
+            });
+    }
+
+    /// Two messages either side of `anchor` in its session, clickable to select
+    fn render_mini_transcript(&mut self, ui: &mut egui::Ui, anchor: usize) {
+        let (before, after) = self.graph.session_neighbors(anchor, 2, 2);
+        if before.is_empty() && after.is_empty() {
+            return;
+        }
+        let rows: Vec<usize> = before.iter().copied().chain([anchor]).chain(after.iter().copied()).collect();
+
+        ui.add_space(5.0);
+        ui.label(egui::RichText::new("Transcript").small().strong());
+        let mut clicked = None;
+        for idx in rows {
+            let node = &self.graph.data.nodes[idx];
+            let current = idx == anchor;
+            let when = node.timestamp_secs().map(|t| self.graph.timeline.format_time(t)).unwrap_or_default();
+            ui.horizontal_wrapped(|ui| {
+                ui.colored_label(node.role.color(&self.palette), "●");
+                let preview = egui::RichText::new(truncate(&node.content_preview, 60)).small();
+                let preview = if current { preview.strong() } else { preview };
+                if ui.selectable_label(current, preview).clicked() {
+                    clicked = Some(node.id.clone());
+                }
+                ui.label(egui::RichText::new(when).small().color(self.palette.text.muted));
+            });
+        }
+        if clicked.is_some() {
+            self.commands.push(AppCommand::SelectNode(clicked));
+        }
+    }
+
+    /// Whether the project filter hides this project (an empty selection means all projects)
+    fn project_hidden(&self, project: &str) -> bool {
+        self.project_filter.is_active()
+            && !self.selected_projects.is_empty()
+            && !self.selected_projects.contains(project)
+    }
 
-        // Node at Scrubber Position
-        ui.label("Node at Scrubber");
-        if let Some(closest_node) = self.find_node_at_scrubber() {
-            ui.horizontal(|ui| {
-                let role_color = self.graph.node_color(&closest_node);
-                ui.colored_label(role_color, "●");
-                ui.label(closest_node.role.label());
-            });
-            if let Some(ref ts) = closest_node.timestamp {
-                // Format timestamp using the timeline's format_time function for consistency
-                if let Some(epoch_secs) = closest_node.timestamp_secs() {
-                    let formatted = self.graph.timeline.format_time(epoch_secs);
-                    ui.label(format!("Time: {}", formatted));
-                } else {
-                    // Debug: parsing failed, show what we got
-                    eprintln!("Failed to parse timestamp: {}", ts);
-                    // Fallback: show just the time portion if parsing fails
-                    let time_display = if let Some(t_idx) = ts.find('T') {
-                        let time_part = &ts[t_idx + 1..];
-                        if let Some(plus_idx) = time_part.find('+') {
-                            &time_part[..plus_idx.min(8)]
-                        } else {
-                            &time_part[..8.min(time_part.len())]
-                        }
-                    } else {
-                        ts.as_str()
-                    };
-                    ui.label(format!("Time: {}", time_display));
-                }
-            }
-            ui.label(format!("Session: {}", closest_node.session_short));
-            if !closest_node.project.is_empty() {
-                ui.label(format!("Project: {}", closest_node.project));
-            }
+    /// Turn an implicit "all projects" (empty) selection into an explicit one before narrowing it
+    fn materialize_project_selection(&mut self) {
+        if self.selected_projects.is_empty() {
+            self.selected_projects = self.available_projects.iter().cloned().collect();
+        }
+    }
 
-            // Content preview with word wrap
-            ui.add_space(5.0);
-            let preview = if closest_node.content_preview.chars().count() > 100 {
-                let truncated: String = closest_node.content_preview.chars().take(100).collect();
-                format!("{}...", truncated)
-            } else {
-                closest_node.content_preview.clone()
-            };
-            egui::ScrollArea::vertical()
-                .max_height(80.0)
-                .show(ui, |ui| {
-                    ui.label(egui::RichText::new(preview).small());
+    /// Show only this node's projects (reloading just those), or restore the
+    /// previous selection when it is already soloed
+    fn toggle_project_solo(&mut self, node: &ProjectTreeNode) {
+        if self.project_solo.as_ref().is_some_and(|s| s.path == node.full_path) {
+            self.unsolo_project();
+            return;
+        }
+        let leaves = node.leaf_paths();
+        let solo = match self.project_solo.take() {
+            // Switching solo keeps the original state to restore
+            Some(previous) => ProjectSolo { path: node.full_path.clone(), ..previous },
+            None => ProjectSolo {
+                path: node.full_path.clone(),
+                previous_selection: self.selected_projects.clone(),
+                previous_pushdown: self.project_pushdown.clone(),
+            },
+        };
+        self.project_solo = Some(solo);
+        self.project_pushdown = leaves.clone();
+        self.selected_projects = leaves.into_iter().collect();
+        // A full load resets the selection, so hand it over as pending
+        self.pending_project_selection = Some(self.selected_projects.clone());
+        self.schedule_reload();
+        self.effective_visible_dirty = true;
+        self.settings_dirty = true;
+    }
+
+    /// Leave solo mode, restoring the previous selection and loaded projects
+    fn unsolo_project(&mut self) {
+        let Some(solo) = self.project_solo.take() else {
+            return;
+        };
+        self.project_pushdown = solo.previous_pushdown;
+        self.selected_projects = solo.previous_selection;
+        self.pending_project_selection = Some(self.selected_projects.clone());
+        self.schedule_reload();
+        self.effective_visible_dirty = true;
+        self.settings_dirty = true;
+    }
+
+    /// Tree row label with node counts; greyed out when nothing falls in the timeline window.
+    /// Double-clicking the name solos the node.
+    fn project_tree_label(&self, ui: &mut egui::Ui, node: &ProjectTreeNode, needle: &str) -> egui::Response {
+        let in_window = node.count_under(&self.project_window_counts);
+        let loaded = node.count_under(&self.project_node_counts);
+        let color = if in_window == 0 { self.palette.text.disabled } else { self.palette.text.primary };
+        let font = egui::TextStyle::Body.resolve(ui.style());
+        let plain = egui::TextFormat { font_id: font.clone(), color, ..Default::default() };
+        let mut job = egui::text::LayoutJob::default();
+        match project_tree::match_range(&node.name, needle) {
+            Some(range) => {
+                job.append(&node.name[..range.start], 0.0, plain.clone());
+                job.append(&node.name[range.clone()], 0.0, egui::TextFormat {
+                    background: self.palette.accent.orange_subtle,
+                    ..plain.clone()
                 });
-        } else {
-            ui.label("No nodes loaded");
+                job.append(&node.name[range.end..], 0.0, plain);
+            }
+            None => job.append(&node.name, 0.0, plain),
         }
+        let response = ui.add(egui::Label::new(job).sense(egui::Sense::click()))
+            .on_hover_text("Double-click to solo");
+        ui.label(egui::RichText::new(in_window.to_string()).small().color(self.palette.text.muted))
+            .on_hover_text(format!("{} in the timeline window, {} loaded", in_window, loaded));
+        response
     }
 
     /// Recursively render one node in the project tree with tri-state checkboxes.
-    fn render_project_tree_node(&mut self, ui: &mut egui::Ui, node: &ProjectTreeNode) {
-        let state = node.check_state(&self.selected_projects);
+    /// While searching, only matching branches are shown and they render expanded
+    /// without touching the remembered expansion state.
+    fn render_project_tree_node(&mut self, ui: &mut egui::Ui, node: &ProjectTreeNode, needle: &str) {
+        let state = if self.selected_projects.is_empty() {
+            CheckState::Checked
+        } else {
+            node.check_state(&self.selected_projects)
+        };
         let has_children = !node.children.is_empty();
+        let mut exclude = None;
 
         if has_children {
             // Interior node: collapsible with tri-state checkbox
-            let is_expanded = self.project_tree_expanded.contains(&node.full_path);
+            let is_expanded = !needle.is_empty() || self.project_tree_expanded.contains(&node.full_path);
             ui.horizontal(|ui| {
                 // Expand/collapse arrow
                 let arrow = if is_expanded { "\u{25BC}" } else { "\u{25B6}" };
@@ -3908,6 +9982,7 @@ impl DashboardApp {
                     } else {
                         self.project_tree_expanded.insert(node.full_path.clone());
                     }
+                    self.settings_dirty = true;
                 }
                 // Tri-state checkbox
                 if let Some(select_all) = project_tree::tri_state_checkbox(ui, state) {
@@ -3915,16 +9990,27 @@ impl DashboardApp {
                     if select_all {
                         for p in leaves { self.selected_projects.insert(p); }
                     } else {
+                        self.materialize_project_selection();
                         for p in &leaves { self.selected_projects.remove(p); }
                     }
                     self.effective_visible_dirty = true;
+                    self.settings_dirty = true;
                 }
-                ui.label(&node.name);
+                let label = self.project_tree_label(ui, node, needle);
+                if label.double_clicked() {
+                    self.toggle_project_solo(node);
+                }
+                label.context_menu(|ui| {
+                    if ui.button("Exclude everything under here").clicked() {
+                        exclude = Some(format!("{}/", node.full_path));
+                        ui.close_menu();
+                    }
+                });
             });
             if is_expanded {
                 ui.indent(&node.full_path, |ui| {
-                    for child in &node.children {
-                        self.render_project_tree_node(ui, child);
+                    for child in node.children.iter().filter(|c| c.matches(needle)) {
+                        self.render_project_tree_node(ui, child, needle);
                     }
                 });
             }
@@ -3934,15 +10020,93 @@ impl DashboardApp {
                 // Small indent to align with children of interior nodes
                 ui.add_space(ui.spacing().indent);
                 let mut selected = state == CheckState::Checked;
-                if ui.checkbox(&mut selected, &node.name).changed() {
+                let changed = ui.checkbox(&mut selected, "").changed();
+                let (swatch, _) = ui.allocate_exact_size(Vec2::splat(10.0), egui::Sense::hover());
+                ui.painter().circle_filled(swatch.center(), 4.5, self.graph.project_color(&node.full_path));
+                let label = self.project_tree_label(ui, node, needle);
+                if label.double_clicked() {
+                    self.toggle_project_solo(node);
+                }
+                label.context_menu(|ui| {
+                    if ui.button("Exclude project").clicked() {
+                        exclude = Some(node.full_path.clone());
+                        ui.close_menu();
+                    }
+                });
+                if changed {
                     if selected {
                         self.selected_projects.insert(node.full_path.clone());
                     } else {
+                        self.materialize_project_selection();
                         self.selected_projects.remove(&node.full_path);
                     }
                     self.effective_visible_dirty = true;
+                    self.settings_dirty = true;
+                }
+            });
+        }
+        if let Some(pattern) = exclude {
+            self.set_project_excluded(pattern, true);
+        }
+    }
+
+    /// Add or remove an exclusion pattern and reload so the change takes effect
+    fn set_project_excluded(&mut self, pattern: String, excluded: bool) {
+        let pattern = pattern.trim().to_string();
+        if pattern.is_empty() {
+            return;
+        }
+        let patterns = &mut self.settings.excluded_projects;
+        if excluded {
+            if patterns.contains(&pattern) {
+                return;
+            }
+            patterns.push(pattern);
+        } else {
+            patterns.retain(|p| *p != pattern);
+        }
+        self.graph.excluded_projects = self.settings.excluded_projects.clone();
+        self.mark_settings_dirty();
+        self.schedule_reload();
+    }
+
+    /// Collapsed list of exclusion patterns with unexclude buttons and an
+    /// input for adding one
+    fn render_excluded_projects(&mut self, ui: &mut egui::Ui) {
+        let count = self.settings.excluded_projects.len();
+        let mut unexclude = None;
+        let mut add = None;
+        egui::CollapsingHeader::new(format!("Excluded ({})", count))
+            .id_salt("excluded_projects")
+            .default_open(false)
+            .show(ui, |ui| {
+                ui.label(egui::RichText::new("Never loaded. Paths are exact; * and ** glob, a trailing / takes the subtree.")
+                    .small()
+                    .color(self.palette.text.muted));
+                for pattern in &self.settings.excluded_projects {
+                    ui.horizontal(|ui| {
+                        if ui.small_button("✖").on_hover_text("Unexclude and reload").clicked() {
+                            unexclude = Some(pattern.clone());
+                        }
+                        ui.label(egui::RichText::new(pattern).small().monospace());
+                    });
                 }
+                ui.horizontal(|ui| {
+                    let response = ui.add(egui::TextEdit::singleline(&mut self.exclude_pattern_input)
+                        .hint_text("~/scratch/ or ~/**/secret")
+                        .desired_width(150.0));
+                    let enter = response.lost_focus() && ui.input(|i| i.key_pressed(egui::Key::Enter));
+                    let has_input = !self.exclude_pattern_input.trim().is_empty();
+                    if (ui.add_enabled(has_input, egui::Button::new("Exclude")).clicked() || enter) && has_input {
+                        add = Some(std::mem::take(&mut self.exclude_pattern_input));
+                    }
+                });
             });
+        if let Some(pattern) = unexclude {
+            self.set_project_excluded(pattern, false);
+        }
+        if let Some(pattern) = add {
+            self.set_project_excluded(pattern, true);
         }
     }
 
@@ -3961,7 +10125,7 @@ impl DashboardApp {
                     ui.label(
                         egui::RichText::new("Welcome to Claude Activity Dashboard")
                             .size(22.0)
-                            .color(theme::text::PRIMARY)
+                            .color(self.palette.text.primary)
                             .strong(),
                     );
                     ui.add_space(12.0);
@@ -3971,22 +10135,22 @@ impl DashboardApp {
                         ui.label(
                             egui::RichText::new(format!("Database error: {}", err))
                                 .size(14.0)
-                                .color(theme::accent::RED),
+                                .color(self.palette.accent.red),
                         );
                         ui.add_space(8.0);
                         if ui.button("Retry connection").clicked() {
-                            self.reconnect_db();
-                            if self.db_connected {
-                                self.load_graph();
-                            }
+                            self.commands.push(AppCommand::Reload);
                         }
+                    } else if let Some(latest) = self.latest_message_time {
+                        self.render_empty_range(ui, latest);
+                        return;
                     } else {
                         ui.label(
                             egui::RichText::new(
                                 "No conversation data found.\nImport your Claude Code history to get started.",
                             )
                             .size(14.0)
-                            .color(theme::text::SECONDARY),
+                            .color(self.palette.text.secondary),
                         );
                     }
 
@@ -3994,14 +10158,14 @@ impl DashboardApp {
 
                     // Instructions
                     egui::Frame::none()
-                        .fill(theme::bg::SURFACE)
+                        .fill(self.palette.bg.surface)
                         .rounding(6.0)
                         .inner_margin(egui::Margin::same(16.0))
                         .show(ui, |ui| {
                             ui.label(
                                 egui::RichText::new("Getting started")
                                     .size(14.0)
-                                    .color(theme::text::PRIMARY)
+                                    .color(self.palette.text.primary)
                                     .strong(),
                             );
                             ui.add_space(8.0);
@@ -4013,7 +10177,7 @@ impl DashboardApp {
                                      dashboard-native ingest --since 7d",
                                 )
                                 .size(13.0)
-                                .color(theme::text::SECONDARY)
+                                .color(self.palette.text.secondary)
                                 .family(egui::FontFamily::Monospace),
                             );
                         });
@@ -4021,38 +10185,264 @@ impl DashboardApp {
                     ui.add_space(16.0);
 
                     // Database path info
-                    let db_path = std::env::var("DB_PATH").unwrap_or_else(|_| {
-                        dirs::config_dir()
-                            .unwrap_or_else(|| std::path::PathBuf::from("."))
-                            .join("dashboard-native")
-                            .join("dashboard.db")
-                            .to_string_lossy()
-                            .to_string()
-                    });
+                    let db_path = crate::db::db_path(self.db_path_override.as_deref());
                     ui.label(
                         egui::RichText::new(format!("Database: {}", db_path))
                             .size(11.0)
-                            .color(theme::text::MUTED),
+                            .color(self.palette.text.muted),
                     );
 
                     ui.add_space(16.0);
 
                     if ui.button("Refresh").clicked() {
-                        if self.db_connected {
-                            self.load_graph();
-                        } else {
-                            self.reconnect_db();
-                            if self.db_connected {
-                                self.load_graph();
-                            }
-                        }
+                        self.commands.push(AppCommand::Reload);
                     }
                 });
             },
         );
     }
 
+    /// Empty-state card for a load that matched nothing while the database
+    /// has messages: when the newest one is, and ways to widen the load
+    fn render_empty_range(&mut self, ui: &mut egui::Ui, latest: f64) {
+        egui::Frame::none()
+            .fill(self.palette.bg.surface)
+            .rounding(6.0)
+            .inner_margin(egui::Margin::same(16.0))
+            .show(ui, |ui| {
+                ui.label(
+                    egui::RichText::new(format!("No messages in the past {}", format_hours_label(self.time_range_hours)))
+                        .size(16.0)
+                        .color(self.palette.text.primary)
+                        .strong(),
+                );
+                ui.add_space(6.0);
+                let restricted = self.session_filter.is_some() || !self.project_pushdown.is_empty();
+                ui.label(
+                    egui::RichText::new(if restricted {
+                        "Nothing matched the time range and the session or project restriction."
+                    } else {
+                        "Nothing matched the time range."
+                    })
+                    .size(13.0)
+                    .color(self.palette.text.secondary),
+                );
+                ui.label(
+                    egui::RichText::new(format!("Most recent message: {}", self.graph.timeline.format_time(latest)))
+                        .size(13.0)
+                        .color(self.palette.text.muted),
+                );
+                ui.add_space(12.0);
+
+                ui.horizontal(|ui| {
+                    if self.time_range_hours < 24.0 && ui.button("Expand to past 24h").clicked() {
+                        self.commands.push(AppCommand::LoadGraph { hours: 24.0, session: self.session_filter.clone() });
+                        self.mark_settings_dirty();
+                    }
+                    let reaches_latest = unix_now() - latest <= self.time_range_hours as f64 * 3600.0;
+                    if !reaches_latest
+                        && ui.button("Load back to it")
+                            .on_hover_text("Widen the range to include the most recent message")
+                            .clicked()
+                    {
+                        self.widen_range_to(latest);
+                    }
+                    if restricted
+                        && ui.button("Clear filters")
+                            .on_hover_text("Load every session and project")
+                            .clicked()
+                    {
+                        self.project_solo = None;
+                        self.project_pushdown.clear();
+                        self.commands.push(AppCommand::LoadGraph { hours: self.time_range_hours, session: None });
+                    }
+                    if ui.button("Open settings").on_hover_text("Show data selection in the sidebar").clicked() {
+                        self.sidebar_tab = SidebarTab::Data;
+                        self.mark_settings_dirty();
+                    }
+                });
+            });
+    }
+
+    /// Show only one role, or everything for `RoleFilter::All`
+    fn set_role_filter(&mut self, filter: RoleFilter) {
+        self.settings.role_filter = filter;
+        self.effective_visible_dirty = true;
+        self.mark_settings_dirty();
+    }
+
+    /// Pills enabled in settings, in settings order, with their current counts
+    fn filter_pill_specs(&self) -> Vec<PillSpec> {
+        if self.settings.filter_pills.is_empty() {
+            return Vec::new();
+        }
+        let (mut users, mut claude, mut tools, mut important) = (0i64, 0i64, 0i64, 0i64);
+        for node in &self.graph.data.nodes {
+            users += RoleFilter::Users.admits(&node.role) as i64;
+            claude += RoleFilter::Claude.admits(&node.role) as i64;
+            tools += node.has_tool_usage as i64;
+            important += node.importance_score.is_some_and(|s| s >= self.importance_threshold) as i64;
+        }
+        let role = self.settings.role_filter;
+        let mut pills = Vec::new();
+        for &pill in &self.settings.filter_pills {
+            match pill {
+                FilterPill::UsersOnly => pills.push(PillSpec {
+                    action: PillAction::Role(RoleFilter::Users),
+                    label: format!("Users only {}", format_count(users)),
+                    hover: "Show only user messages".to_string(),
+                    on: role == RoleFilter::Users,
+                }),
+                FilterPill::ClaudeOnly => pills.push(PillSpec {
+                    action: PillAction::Role(RoleFilter::Claude),
+                    label: format!("Claude only {}", format_count(claude)),
+                    hover: "Show only assistant and subagent messages".to_string(),
+                    on: role == RoleFilter::Claude,
+                }),
+                FilterPill::ToolCalls => pills.push(PillSpec {
+                    action: PillAction::ToolCalls,
+                    label: format!("No tool calls {}", format_count(tools)),
+                    hover: "Hide messages that use tools".to_string(),
+                    on: self.tool_use_filter.is_active(),
+                }),
+                FilterPill::HighImportance => pills.push(PillSpec {
+                    action: PillAction::HighImportance,
+                    label: format!("Importance ≥ {:.2} {}", self.importance_threshold, format_count(important)),
+                    hover: "Hide messages below the importance threshold".to_string(),
+                    on: self.importance_filter.is_active(),
+                }),
+                FilterPill::SemanticFilters => {
+                    for filter in self.semantic_filters.iter().filter(|f| f.is_active) {
+                        let mode = self.semantic_filter_modes.get(&filter.id).copied().unwrap_or(SemanticFilterMode::Off);
+                        pills.push(PillSpec {
+                            action: PillAction::Semantic(filter.id),
+                            label: format!("{} {}", filter.name, format_count(filter.matches)),
+                            hover: mode.description().to_string(),
+                            on: mode != SemanticFilterMode::Off,
+                        });
+                    }
+                }
+            }
+        }
+        pills
+    }
+
+    /// Flip the sidebar filter behind a pill
+    fn toggle_filter_pill(&mut self, action: PillAction) {
+        match action {
+            PillAction::Role(filter) => {
+                let next = if self.settings.role_filter == filter { RoleFilter::All } else { filter };
+                self.set_role_filter(next);
+            }
+            PillAction::ToolCalls | PillAction::HighImportance => {
+                let mode = if action == PillAction::ToolCalls { &mut self.tool_use_filter } else { &mut self.importance_filter };
+                *mode = if mode.is_active() { FilterMode::Off } else { FilterMode::Filtered };
+                self.recompute_bypass_edges();
+                self.effective_visible_dirty = true;
+                self.mark_settings_dirty();
+            }
+            PillAction::Semantic(id) => self.commands.push(AppCommand::ToggleFilter(id)),
+        }
+    }
+
+    /// Toggle pills across the top of the graph. Pills that don't fit the
+    /// width move into a trailing "…" menu.
+    fn render_filter_pills(&mut self, ui: &mut egui::Ui, rect: egui::Rect) {
+        const OVERFLOW_WIDTH: f32 = 28.0;
+        let pills = self.filter_pill_specs();
+        if pills.is_empty() {
+            return;
+        }
+        let bar = egui::Rect::from_min_size(
+            rect.min + egui::vec2(8.0, 8.0),
+            egui::vec2((rect.width() - 16.0).max(0.0), 22.0),
+        );
+        let mut bar_ui = ui.new_child(
+            egui::UiBuilder::new().max_rect(bar).layout(egui::Layout::left_to_right(egui::Align::Center)),
+        );
+        let font = egui::TextStyle::Button.resolve(bar_ui.style());
+        let extra = bar_ui.spacing().button_padding.x * 2.0 + bar_ui.spacing().item_spacing.x;
+        let widths: Vec<f32> = pills.iter()
+            .map(|p| bar_ui.fonts(|f| f.layout_no_wrap(p.label.clone(), font.clone(), egui::Color32::WHITE).size().x) + extra)
+            .collect();
+        let fits = if widths.iter().sum::<f32>() <= bar.width() {
+            pills.len()
+        } else {
+            let mut used = OVERFLOW_WIDTH;
+            widths.iter().take_while(|&&w| { used += w; used <= bar.width() }).count()
+        };
+
+        let mut toggled = None;
+        for pill in &pills[..fits] {
+            let fill = if pill.on {
+                bar_ui.visuals().selection.bg_fill
+            } else {
+                self.palette.bg.panel.gamma_multiply(0.85)
+            };
+            if bar_ui.add(egui::Button::new(&pill.label).fill(fill).rounding(10.0))
+                .on_hover_text(&pill.hover)
+                .clicked()
+            {
+                toggled = Some(pill.action);
+            }
+        }
+        if fits < pills.len() {
+            bar_ui.menu_button("…", |ui| {
+                for pill in &pills[fits..] {
+                    if ui.selectable_label(pill.on, &pill.label).on_hover_text(&pill.hover).clicked() {
+                        toggled = Some(pill.action);
+                        ui.close_menu();
+                    }
+                }
+            });
+        }
+        if let Some(action) = toggled {
+            self.toggle_filter_pill(action);
+        }
+    }
+
+    /// Fingerprint of everything the edge pass draws from: camera, node
+    /// positions, the edge list and its visibility, and edge styling. None
+    /// while live tails march, since those edges change every frame.
+    fn edge_scene_key(&self, center: Pos2, zoom: f32, pixels_per_point: f32, declutter: Option<DeclutterLevel>, any_filter: bool) -> Option<u64> {
+        use std::collections::hash_map::DefaultHasher;
+        use std::hash::{Hash, Hasher};
+
+        if !self.live_tails.is_empty() && theme::motion_enabled() {
+            return None;
+        }
+        let mut hasher = DefaultHasher::new();
+        let scalars = [
+            center.x, center.y, zoom, pixels_per_point, self.pan_offset.x, self.pan_offset.y, self.node_size,
+            self.temporal_edge_opacity, self.proximity_edge_opacity, self.graph.hue_offset, self.settings.idle_gap_minutes,
+        ];
+        for value in scalars {
+            value.to_bits().hash(&mut hasher);
+        }
+        for pos in self.graph.positions.values() {
+            (pos.x.to_bits(), pos.y.to_bits()).hash(&mut hasher);
+        }
+        (self.graph.edges_generation, self.graph.community_generation, self.effective_visible_generation).hash(&mut hasher);
+        (self.timeline_enabled, &self.graph.timeline.visible_edges).hash(&mut hasher);
+        (any_filter, self.show_arrows, self.settings.idle_gap_labels, self.graph.colorblind_safe, self.graph.max_neighbors_per_node).hash(&mut hasher);
+        for query in &self.proximity_queries {
+            (query.visible, query.opacity.to_bits()).hash(&mut hasher);
+        }
+        for edge in &self.bypass_edges {
+            (&edge.source, &edge.target).hash(&mut hasher);
+        }
+        format!(
+            "{:?}{:?}{:?}{:?}{:?}",
+            self.graph.color_mode, self.graph.hue_overrides, self.settings.edge_styles, declutter, self.palette
+        )
+        .hash(&mut hasher);
+        Some(hasher.finish())
+    }
+
     fn render_graph(&mut self, ui: &mut egui::Ui) {
+        if self.graph_diff.is_some() {
+            self.render_diff_strip(ui);
+        }
         let (response, painter) = ui.allocate_painter(ui.available_size(), egui::Sense::click_and_drag());
         let rect = response.rect;
         let center = rect.center();
@@ -4062,8 +10452,16 @@ impl DashboardApp {
         let zoom_delta = ui.input(|i| i.zoom_delta());
         let hover_pos = response.hover_pos();
 
+        // Cmd+drag selects a time window instead of panning
+        if response.drag_started_by(egui::PointerButton::Primary) && ui.input(|i| i.modifiers.command) {
+            if let Some(p) = response.interact_pointer_pos() {
+                let timeline = &self.graph.timeline;
+                self.time_drag = Some(TimeDrag { origin: p, current: p, restore: (timeline.start_position, timeline.position) });
+            }
+        }
+
         // Handle click-drag pan (for mouse users)
-        if response.dragged_by(egui::PointerButton::Primary) {
+        if response.dragged_by(egui::PointerButton::Primary) && self.time_drag.is_none() {
             self.pan_offset += response.drag_delta();
         }
 
@@ -4087,17 +10485,32 @@ impl DashboardApp {
             }
         }
 
+        self.apply_fit_request(rect, ui.ctx().zoom_factor());
+        let cancel_time_drag = ui.input(|i| shortcuts::pressed(i, Action::Cancel));
+        self.update_time_drag(&response, center, self.zoom / ui.ctx().zoom_factor(), cancel_time_drag);
+
         // Run physics simulation (uses graph-space center, unaffected by viewport pan)
         // Only simulate visible nodes (respects timeline + importance filters)
         // Wire proximity stiffness into layout before step
+        let physics_started = Instant::now();
         self.layout.similarity_stiffness = self.proximity_stiffness;
         let physics_visible = self.compute_physics_visible_nodes();
+        // Recency factors are shared by physics sizing and the draw pass below
+        self.refresh_recency_factors();
         let node_sizes = self.compute_node_sizes();
-        self.layout.step(&mut self.graph, center, physics_visible.as_ref(), node_sizes.as_ref());
+        if std::mem::take(&mut self.presettle_pending) && !theme::motion_enabled() {
+            self.graph.physics_enabled = true;
+            self.layout.settle(&mut self.graph, center, physics_visible.as_ref(), node_sizes.as_ref(), &self.bypass_edges, PRESETTLE_MAX_STEPS);
+            self.graph.physics_enabled = false;
+        }
+        self.layout.step(&mut self.graph, center, physics_visible.as_ref(), node_sizes.as_ref(), &self.bypass_edges);
+        self.profiler.record(Phase::Physics, physics_started);
 
-        // Cache values for transform closure to avoid borrowing self
+        // Cache values for transform closure to avoid borrowing self.
+        // Graph-world sizes are divided by the UI scale so they aren't scaled twice
+        // (once by the zoom, again by pixels-per-point).
         let pan_offset = self.pan_offset;
-        let zoom = self.zoom;
+        let zoom = self.zoom / ui.ctx().zoom_factor();
 
         // Transform helper: graph space -> screen space
         // Pan is in screen space (applied after zoom) for 1:1 movement at any zoom level
@@ -4115,187 +10528,295 @@ impl DashboardApp {
         let evn = &self.effective_visible_nodes;
         let any_filter = self.any_filter_active();
 
-        // Draw edges first (behind nodes)
-        // Per-node neighbor cap for similarity edges (client-side filtering)
-        let max_neighbors = self.graph.max_neighbors_per_node;
-        let mut sim_degree: HashMap<&str, usize> = HashMap::new();
-
-        for edge in &self.graph.data.edges {
-            // Check if edge is dimmed (timeline-hidden) vs fully hidden (other filters)
-            let is_timeline_dimmed = self.timeline_enabled && !self.graph.is_edge_visible(edge);
+        // Smart declutter: when zoomed out, keep only the most important nodes of
+        // each screen-sized cell. Recomputed per zoom step, or every frame while
+        // the layout moves; hover follows whatever is drawn.
+        let declutter_level = if self.settings.smart_declutter { spatial::declutter_level(zoom) } else { None };
+        match declutter_level {
+            Some(level) => {
+                let stale = self.graph.physics_enabled || self.declutter.as_ref().is_none_or(|(current, _)| *current != level);
+                if stale {
+                    let candidates = self.graph.data.nodes.iter()
+                        .enumerate()
+                        .filter(|(_, node)| !any_filter || evn.contains(&node.id))
+                        .filter_map(|(i, node)| Some((i, self.graph.get_pos(&node.id)?)));
+                    let grid = SpatialGrid::build(candidates, level.cell_size);
+                    let nodes = &self.graph.data.nodes;
+                    self.declutter = Some((level, grid.top_per_cell(level.k, |i| nodes[i].importance_score.unwrap_or(0.0))));
+                    self.hit_grid_dirty = true;
+                }
+            }
+            None => {
+                if self.declutter.take().is_some() {
+                    self.hit_grid_dirty = true;
+                }
+            }
+        }
+        let declutter = self.declutter.as_ref().map(|(_, d)| d);
+        let drawn = |id: &str| declutter.is_none_or(|d| self.graph.node_index.get(id).is_some_and(|&i| d.is_kept(i)));
 
-            // Skip edges where either endpoint is not effectively visible
-            if any_filter {
-                if !evn.contains(&edge.source) || !evn.contains(&edge.target) {
+        // Density underlay for the nodes declutter left out
+        if let Some(declutter) = declutter {
+            let clip = painter.clip_rect();
+            for &(cell, hidden) in &declutter.hidden {
+                let rect = egui::Rect::from_min_max(transform(cell.min), transform(cell.max));
+                if !rect.intersects(clip) {
                     continue;
                 }
+                let alpha = (0.04 + 0.03 * (hidden as f32).ln()).min(0.25);
+                painter.rect_filled(rect, 0.0, self.palette.text.muted.gamma_multiply(alpha));
             }
+        }
 
-            // Per-node neighbor cap: skip similarity edges once a node hits the limit
-            if edge.is_similarity && max_neighbors > 0 {
-                let src_deg = sim_degree.get(edge.source.as_str()).copied().unwrap_or(0);
-                let tgt_deg = sim_degree.get(edge.target.as_str()).copied().unwrap_or(0);
-                if src_deg >= max_neighbors || tgt_deg >= max_neighbors {
+        // Draw edges first (behind nodes)
+        let edges_started = Instant::now();
+        // Live session tails pulse and their incoming edge marches; held still with reduced motion
+        let live_time = if theme::motion_enabled() { ui.input(|i| i.time) as f32 } else { 0.0 };
+        if !self.live_tails.is_empty() && theme::motion_enabled() {
+            ui.ctx().request_repaint_after(LIVE_REPAINT);
+        }
+        // A settled layout under a still camera reuses last frame's edge meshes
+        let pixels_per_point = ui.ctx().pixels_per_point();
+        let edge_key = self.edge_scene_key(center, zoom, pixels_per_point, declutter_level, any_filter);
+        if edge_key.is_none() || self.edge_frame.as_ref().is_none_or(|frame| frame.key != edge_key) {
+            // Per-node neighbor cap for similarity edges (client-side filtering)
+            let max_neighbors = self.graph.max_neighbors_per_node;
+            let mut sim_degree: HashMap<&str, usize> = HashMap::new();
+            // Every edge, dash and arrowhead goes into a few meshes submitted together
+            let mut edge_batch = scene::EdgeBatch::new(pixels_per_point);
+            // Screen segments of drawn gap edges, for hover
+            let mut gap_segments: Vec<(usize, Pos2, Pos2)> = Vec::new();
+            let idle_threshold = self.settings.idle_gap_minutes as f64 * 60.0;
+            let label_idle_gaps = self.settings.idle_gap_labels && zoom >= IDLE_GAP_LABEL_MIN_ZOOM;
+            let arrows = self.settings.edge_styles.arrows;
+            let draw_arrows = self.show_arrows && zoom >= arrows.min_zoom;
+            let mut idle_labels: Vec<(Pos2, f64)> = Vec::new();
+
+            for (edge_index, edge) in self.graph.data.edges.iter().enumerate() {
+                // Check if edge is dimmed (timeline-hidden) vs fully hidden (other filters)
+                let is_timeline_dimmed = self.timeline_enabled && !self.graph.is_edge_index_visible(edge_index);
+
+                // Skip edges where either endpoint is not effectively visible
+                if any_filter {
+                    if !evn.contains(&edge.source) || !evn.contains(&edge.target) {
+                        continue;
+                    }
+                }
+                if !drawn(&edge.source) || !drawn(&edge.target) {
                     continue;
                 }
-                *sim_degree.entry(edge.source.as_str()).or_insert(0) += 1;
-                *sim_degree.entry(edge.target.as_str()).or_insert(0) += 1;
-            }
 
-            let source_pos = match self.graph.get_pos(&edge.source) {
-                Some(p) => transform(p),
-                None => continue,
-            };
-            let target_pos = match self.graph.get_pos(&edge.target) {
-                Some(p) => transform(p),
-                None => continue,
-            };
+                // Per-node neighbor cap: skip similarity edges once a node hits the limit
+                if edge.is_similarity && max_neighbors > 0 {
+                    let src_deg = sim_degree.get(edge.source.as_str()).copied().unwrap_or(0);
+                    let tgt_deg = sim_degree.get(edge.target.as_str()).copied().unwrap_or(0);
+                    if src_deg >= max_neighbors || tgt_deg >= max_neighbors {
+                        continue;
+                    }
+                    *sim_degree.entry(edge.source.as_str()).or_insert(0) += 1;
+                    *sim_degree.entry(edge.target.as_str()).or_insert(0) += 1;
+                }
+
+                let source_pos = match self.graph.get_pos(&edge.source) {
+                    Some(p) => transform(p),
+                    None => continue,
+                };
+                let target_pos = match self.graph.get_pos(&edge.target) {
+                    Some(p) => transform(p),
+                    None => continue,
+                };
+
+                // Edge kinds switched off in the legend
+                let style = self.settings.edge_styles.get(EdgeKind::of(edge));
+                if !style.visible {
+                    continue;
+                }
 
-            let base_opacity = if edge.is_temporal {
-                self.temporal_edge_opacity
-            } else if edge.is_similarity {
-                // Per-query opacity and visibility
-                if let Some(qi) = edge.query_index {
-                    if let Some(pq) = self.proximity_queries.get(qi) {
-                        if !pq.visible { continue; }
-                        pq.opacity
+                let base_opacity = if edge.is_temporal {
+                    self.temporal_edge_opacity
+                } else if edge.is_similarity {
+                    // Per-query opacity and visibility
+                    if let Some(qi) = edge.query_index {
+                        if let Some(pq) = self.proximity_queries.get(qi) {
+                            if !pq.visible { continue; }
+                            pq.opacity
+                        } else {
+                            self.proximity_edge_opacity
+                        }
                     } else {
                         self.proximity_edge_opacity
                     }
                 } else {
-                    self.proximity_edge_opacity
+                    0.5
+                };
+
+                // Similarity edges fade with weaker scores
+                let base_opacity = scene::edge_opacity(edge, base_opacity) * style.opacity;
+
+                // Use greyscale and reduced opacity for timeline-dimmed edges
+                let mut base_color = self.graph.edge_color(edge);
+                if let Some(idle) = edge.idle_secs.filter(|&s| s >= idle_threshold) {
+                    base_color = lerp_color(base_color, Color32::GRAY, idle_gap_cooling(idle, idle_threshold));
+                    if label_idle_gaps && !is_timeline_dimmed {
+                        idle_labels.push((source_pos.lerp(target_pos, 0.5), idle));
+                    }
+                }
+                let mut color = base_color.gamma_multiply((base_opacity * self.palette.edge_gamma).min(1.0));
+                if is_timeline_dimmed {
+                    color = scene::dimmed(color);
+                }
+                let width = style.width * zoom * self.palette.stroke_scale;
+                // Gap edges are always dashed so a broken session chain never reads as a real link
+                let line = if edge.gap.is_some() {
+                    gap_segments.push((edge_index, source_pos, target_pos));
+                    LineStyle::Dashed
+                } else {
+                    style.line
+                };
+                let is_live_tail = !edge.is_temporal && !edge.is_similarity && !edge.is_topic && !edge.is_obsidian
+                    && self.live_tails.contains(&edge.target);
+                if is_live_tail {
+                    let (dash, gap) = ((8.0 * zoom).max(2.0), (5.0 * zoom).max(2.0));
+                    edge_batch.marching_line(source_pos, target_pos, width, color, dash, gap, live_time * LIVE_MARCH_SPEED * zoom);
+                } else {
+                    edge_batch.styled_line(source_pos, target_pos, width, color, line, zoom);
                 }
-            } else {
-                0.5
-            };
 
-            // Use greyscale and reduced opacity for timeline-dimmed edges
-            // For similarity edges, use query-specific color if available
-            let base_color = if edge.is_similarity {
-                edge.query_index
-                    .and_then(|qi| self.proximity_queries.get(qi))
-                    .map(|pq| pq.color)
-                    .unwrap_or_else(|| self.graph.edge_color(edge))
-            } else {
-                self.graph.edge_color(edge)
-            };
-            let mut color = base_color.gamma_multiply(base_opacity);
-            if is_timeline_dimmed {
-                color = crate::graph::types::to_greyscale(color).gamma_multiply(0.4);
-            }
-            let stroke = Stroke::new(1.5 * self.zoom, color);
-
-            if edge.is_similarity {
-                // Draw dotted line for similarity/proximity edges
-                let diff = target_pos - source_pos;
-                let length = diff.length();
-                let dir = diff / length;
-                let dot_len = 4.0 * self.zoom;
-                let gap_len = 4.0 * self.zoom;
-                let step = dot_len + gap_len;
-                let mut d = 0.0;
-                while d < length {
-                    let seg_end = (d + dot_len).min(length);
-                    let p0 = source_pos + dir * d;
-                    let p1 = source_pos + dir * seg_end;
-                    painter.line_segment([p0, p1], stroke);
-                    d += step;
+                // Arrowheads on directed edges, sized from the stroke
+                if draw_arrows && EdgeKind::of(edge).is_directed() {
+                    let head = scene::arrow_head(source_pos, target_pos, self.node_size * zoom + 2.0, width * arrows.scale, arrows.style);
+                    match (head, arrows.style) {
+                        (Some(points), ArrowStyle::Tip) => edge_batch.triangle(points, color),
+                        (Some([apex, a, b]), ArrowStyle::Chevron) => {
+                            edge_batch.line(a, apex, width, color);
+                            edge_batch.line(b, apex, width, color);
+                        }
+                        (None, _) => {}
+                    }
                 }
-            } else {
-                painter.line_segment([source_pos, target_pos], stroke);
             }
 
-            // Draw arrow if enabled
-            if self.show_arrows {
-                let dir = (target_pos - source_pos).normalized();
-                let arrow_size = 8.0 * self.zoom;
-                let arrow_pos = target_pos - dir * (self.node_size * self.zoom + 2.0);
-
-                let perp = Vec2::new(-dir.y, dir.x);
-                let p1 = arrow_pos;
-                let p2 = arrow_pos - dir * arrow_size + perp * arrow_size * 0.5;
-                let p3 = arrow_pos - dir * arrow_size - perp * arrow_size * 0.5;
-
-                painter.add(egui::Shape::convex_polygon(
-                    vec![p1, p2, p3],
-                    color,
-                    Stroke::NONE,
-                ));
+            // Update filtered edge count for UI display
+            if max_neighbors > 0 {
+                let total: usize = sim_degree.values().sum();
+                self.proximity_edge_count_filtered = total / 2; // each edge counted twice
             }
-        }
-
-        // Update filtered edge count for UI display
-        if max_neighbors > 0 {
-            let total: usize = sim_degree.values().sum();
-            self.proximity_edge_count_filtered = total / 2; // each edge counted twice
-        }
 
-        // Draw bypass edges (bridging over hidden nodes in Inactive mode)
-        if !self.bypass_edges.is_empty() {
-            for edge in &self.bypass_edges.clone() {
-                // Skip bypass edges where either endpoint is not effectively visible
-                if any_filter {
-                    if !evn.contains(&edge.source) || !evn.contains(&edge.target) {
+            // Draw bypass edges (bridging over hidden nodes in Inactive mode)
+            if !self.bypass_edges.is_empty() {
+                for edge in &self.bypass_edges {
+                    // Skip bypass edges where either endpoint is not effectively visible
+                    if any_filter {
+                        if !evn.contains(&edge.source) || !evn.contains(&edge.target) {
+                            continue;
+                        }
+                    }
+                    if !drawn(&edge.source) || !drawn(&edge.target) {
                         continue;
                     }
+                    let source_pos = match self.graph.get_pos(&edge.source) {
+                        Some(p) => transform(p),
+                        None => continue,
+                    };
+                    let target_pos = match self.graph.get_pos(&edge.target) {
+                        Some(p) => transform(p),
+                        None => continue,
+                    };
+                    // Dashed and faded so bridges read differently from real session edges
+                    let color = self.graph.edge_color(edge).gamma_multiply(0.5);
+                    edge_batch.dashed_line(
+                        source_pos,
+                        target_pos,
+                        1.5 * zoom * self.palette.stroke_scale,
+                        color,
+                        (6.0 * zoom).max(2.0),
+                        (4.0 * zoom).max(2.0),
+                    );
                 }
-                let source_pos = match self.graph.get_pos(&edge.source) {
-                    Some(p) => transform(p),
-                    None => continue,
-                };
-                let target_pos = match self.graph.get_pos(&edge.target) {
-                    Some(p) => transform(p),
-                    None => continue,
-                };
-                let color = self.graph.edge_color(edge).gamma_multiply(0.5);
-                let stroke = Stroke::new(1.5 * self.zoom, color);
-                painter.line_segment([source_pos, target_pos], stroke);
             }
+            self.edge_frame = Some(EdgeFrame { key: edge_key, shapes: edge_batch.into_shapes().collect(), gap_segments, idle_labels });
+        }
+        let edge_frame = self.edge_frame.as_ref().expect("edge frame was just ensured");
+        painter.extend(edge_frame.shapes.iter().cloned());
+        for &(mid, idle) in &edge_frame.idle_labels {
+            self.text_layout.text(
+                &painter,
+                mid,
+                egui::Align2::CENTER_CENTER,
+                &format_idle_gap(idle),
+                egui::FontId::proportional(10.0),
+                self.palette.text.muted,
+            );
         }
 
+        self.profiler.record(Phase::Edges, edges_started);
+
         // Detect hover - select closest node to cursor
         // Note: Timeline-dimmed nodes are hoverable (they're greyed out, not hidden)
+        let hover_started = Instant::now();
         let mut new_hovered = None;
         if let Some(hover_pos) = response.hover_pos() {
-            let mut closest: Option<(String, f32)> = None; // (node_id, distance)
+            // Skip nodes not in effective visible set, or left out by declutter
+            let candidates = self.graph.data.nodes.iter()
+                .enumerate()
+                .filter(|(_, node)| !any_filter || evn.contains(&node.id))
+                .filter(|(i, _)| declutter.is_none_or(|d| d.is_kept(*i)))
+                .filter_map(|(i, node)| Some((i, self.graph.get_pos(&node.id)?)));
+
+            // The screen transform is a uniform scale, so the nearest node in
+            // graph space is also the nearest on screen
+            let closest = if self.graph.data.nodes.len() >= spatial::GRID_MIN_NODES {
+                // Rebuilt while the layout moves; a settled layout keeps its grid
+                if self.hit_grid.is_none() || self.hit_grid_dirty || self.graph.physics_enabled {
+                    let cell_size = (self.node_size * self.max_node_multiplier).max(1.0);
+                    self.hit_grid = Some(SpatialGrid::build(candidates, cell_size));
+                    self.hit_grid_dirty = false;
+                }
+                let grid = self.hit_grid.as_ref().expect("hit grid was just built");
+                self.hit_test = HitTest::Grid { nodes: grid.len(), cells: grid.cell_count() };
+                grid.nearest(center + (hover_pos - center - pan_offset) / zoom)
+            } else {
+                self.hit_grid = None;
+                self.hit_test = HitTest::Linear;
+                spatial::nearest_linear(candidates.map(|(i, pos)| (i, transform(pos))), hover_pos)
+            };
 
-            for node in &self.graph.data.nodes {
-                // Skip nodes not in effective visible set
-                if any_filter && !evn.contains(&node.id) {
-                    continue;
-                }
-                if let Some(pos) = self.graph.get_pos(&node.id) {
-                    let screen_pos = transform(pos);
-                    let distance = screen_pos.distance(hover_pos);
+            // Only within reach of the node as drawn last frame; nodes not yet drawn always count
+            let pixels_per_point = ui.ctx().pixels_per_point();
+            new_hovered = closest
+                .filter(|&i| {
+                    let radius = self.node_radii.get(i).copied().unwrap_or(0.0);
+                    radius <= 0.0 || self.graph.get_pos(&self.graph.data.nodes[i].id)
+                        .is_some_and(|pos| transform(pos).distance(hover_pos) <= scene::hit_radius(radius, pixels_per_point))
+                })
+                .map(|i| self.graph.data.nodes[i].id.clone());
+        }
 
-                    if closest.is_none() || distance < closest.as_ref().unwrap().1 {
-                        closest = Some((node.id.clone(), distance));
-                    }
-                }
+        // A gap edge under the pointer beats empty space, but not a node
+        self.hovered_gap = None;
+        if let Some(hover_pos) = response.hover_pos() {
+            if new_hovered.is_none() {
+                self.hovered_gap = self.edge_frame.iter()
+                    .flat_map(|frame| &frame.gap_segments)
+                    .map(|&(i, a, b)| (i, segment_distance(hover_pos, a, b)))
+                    .filter(|&(_, d)| d <= GAP_HOVER_PX)
+                    .min_by(|a, b| a.1.total_cmp(&b.1))
+                    .map(|(i, _)| i);
             }
-
-            new_hovered = closest.map(|(id, _)| id);
         }
 
-        // Hover-to-scrub: move timeline playhead to hovered node's timestamp
-        // Same-session nodes switch instantly; cross-session requires click
-        let prev_hovered = self.graph.hovered_node.clone();
         self.graph.hovered_node = new_hovered;
 
-        // DISABLED FOR DEBUGGING
-        if false && self.hover_scrubs_timeline && self.timeline_enabled {
-            if let Some(ref hovered_id) = self.graph.hovered_node {
-                // Only instant-scrub for same-session (visible) nodes
-                if self.graph.is_node_visible(hovered_id) && self.graph.hovered_node != prev_hovered {
-                    let node_time = self.graph.get_node(hovered_id).and_then(|n| n.timestamp_secs());
-                    if let Some(t) = node_time {
-                        let new_pos = self.graph.timeline.position_at_time(t);
-                        self.graph.timeline.position = new_pos.max(self.graph.timeline.start_position);
-                        self.graph.update_visible_nodes();
-                        self.effective_visible_dirty = true;
-                    }
-                }
-                // Cross-session (dimmed) nodes: do nothing on hover, handled by click
+        // Hover-to-scrub: while the modifier is held, preview the timeline at the
+        // hovered node's timestamp. The committed position only changes on click.
+        let scrub_held = hover_scrub_modifier_held(self.settings.hover_scrub_modifier, ui.input(|i| i.modifiers));
+        if self.hover_scrubs_timeline && self.timeline_enabled && scrub_held && !self.graph.timeline.playing {
+            let node_time = self.graph.hovered_node.as_ref()
+                .and_then(|id| self.graph.get_node(id))
+                .and_then(|n| n.timestamp_secs());
+            if let Some(t) = node_time {
+                let preview = self.graph.timeline.position_at_time(t);
+                self.hover_scrub_target = Some(preview.max(self.graph.timeline.start_position));
             }
         }
 
@@ -4303,7 +10824,7 @@ impl DashboardApp {
         let modifiers = ui.input(|i| i.modifiers);
         if modifiers.command {
             if let Some(ref hovered_id) = self.graph.hovered_node {
-                let adj = self.build_adjacency_list(self.neighborhood_include_temporal);
+                let adj = self.build_visible_adjacency_list(self.neighborhood_include_temporal);
                 let mut seeds = HashSet::new();
                 seeds.insert(hovered_id.clone());
                 self.cmd_hover_neighbors = self.expand_to_neighbors(&seeds, self.neighborhood_depth, &adj);
@@ -4317,51 +10838,42 @@ impl DashboardApp {
             ui.ctx().request_repaint();
         }
 
+        self.profiler.record(Phase::Hover, hover_started);
+
         // Two-pass node rendering:
+        let nodes_started = Instant::now();
+        let pixels_per_point = ui.ctx().pixels_per_point();
+        self.node_radii.clear();
+        self.node_radii.resize(self.graph.data.nodes.len(), 0.0);
         // Pass 1: Compute all size multipliers and find max
         // Tuple: (index, multiplier, is_timeline_dimmed, is_same_project_future)
         let mut node_multipliers: Vec<(usize, f32, bool, bool)> = Vec::new();
         let mut max_multiplier: f32 = 0.001; // Avoid division by zero
 
         for (idx, node) in self.graph.data.nodes.iter().enumerate() {
-            // Check if node is timeline-dimmed (visible but greyed out)
-            let is_timeline_dimmed = self.timeline_enabled && !self.graph.is_node_visible(&node.id);
-            let is_same_project_future = self.is_same_project_future_node(node);
+            // Check if node is timeline-dimmed (visible but greyed out). While
+            // diffing, only nodes new in window B are drawn at full strength.
+            let (is_timeline_dimmed, is_same_project_future) = match self.graph_diff {
+                Some(ref diff) => (diff.class_of(idx) != Some(DiffClass::Added), false),
+                None => (
+                    self.timeline_enabled && !self.graph.is_node_visible(&node.id),
+                    self.is_same_project_future_node(node),
+                ),
+            };
 
             // Skip nodes not in effective visible set
             if any_filter && !evn.contains(&node.id) {
                 continue;
             }
+            if declutter.is_some_and(|d| !d.is_kept(idx)) {
+                continue;
+            }
 
             if self.graph.get_pos(&node.id).is_some() {
                 // Unified node sizing formula:
                 // size = base * exp(w_imp * importance) * exp(w_tok * tokens_norm) * exp(-w_time * time_dist)
-
-                // 1. Importance factor (0-1, default 0.5)
-                let importance = node.importance_score.unwrap_or(0.5);
-                let imp_factor = (self.w_importance * importance).exp();
-
-                // 2. Token factor (log-normalized 0-1)
-                let tokens_norm = self.graph.normalize_tokens(node);
-                let tok_factor = (self.w_tokens * tokens_norm).exp();
-
-                // 3. Time/recency factor (distance from scrubber, 0-1)
-                let time_factor = if self.graph.timeline.max_time > self.graph.timeline.min_time {
-                    if let Some(node_time) = node.timestamp_secs() {
-                        let time_range = self.graph.timeline.max_time - self.graph.timeline.min_time;
-                        let scrubber_time = self.graph.timeline.time_at_position(self.graph.timeline.position);
-                        let distance = (scrubber_time - node_time).abs();
-                        let normalized_distance = (distance / time_range).clamp(0.0, 1.0) as f32;
-                        (-self.w_time * normalized_distance).exp()
-                    } else {
-                        1.0 // No timestamp = neutral
-                    }
-                } else {
-                    1.0 // No time range = neutral
-                };
-
-                // Combine factors multiplicatively
-                let raw_multiplier = imp_factor * tok_factor * time_factor;
+                let time_factor = self.recency_factors.get(idx).copied().unwrap_or(1.0);
+                let raw_multiplier = scene::size_multiplier(&self.graph, node, self.w_importance, self.w_tokens, time_factor);
 
                 // Same-project future nodes should be treated as active (not dimmed)
                 let is_dimmed_for_rendering = is_timeline_dimmed && !is_same_project_future;
@@ -4375,9 +10887,6 @@ impl DashboardApp {
             }
         }
 
-        // Compute normalization scale: largest visible node gets max_node_multiplier
-        let scale = self.max_node_multiplier / max_multiplier;
-
         // Pass 2: Draw nodes with normalized sizes
         // Draw dimmed nodes first (behind active nodes)
         for &(idx, _raw_multiplier, is_dimmed, is_same_project_future) in &node_multipliers {
@@ -4393,11 +10902,17 @@ impl DashboardApp {
                 let screen_pos = transform(pos);
 
                 // Dimmed nodes use a fixed smaller size
-                let size = self.node_size * self.zoom * 0.5;
+                let size = scene::clamp_node_radius(self.node_size * zoom * 0.5, pixels_per_point);
+                self.node_radii[idx] = size;
+
+                // Diff: nodes only in window A are an outline of their color
+                if self.graph_diff.as_ref().and_then(|d| d.class_of(idx)) == Some(DiffClass::Removed) {
+                    painter.circle_stroke(screen_pos, size, Stroke::new(1.0, scene::ghosted(self.graph.node_color(node))));
+                    continue;
+                }
 
                 // Use greyscale color with reduced opacity
-                let base_color = self.graph.node_color(node);
-                let color = crate::graph::types::to_greyscale(base_color).gamma_multiply(0.4);
+                let color = scene::dimmed(self.graph.node_color(node));
 
                 // Draw node
                 painter.circle_filled(screen_pos, size, color);
@@ -4416,7 +10931,7 @@ impl DashboardApp {
         // Pass 2b: Draw active (non-dimmed) nodes on top
         // Get current scrubber time for "future node" desaturation
         let scrubber_time = if self.timeline_enabled {
-            Some(self.graph.timeline.time_at_position(self.graph.timeline.position))
+            Some(self.graph.timeline.time_at_position(self.graph.timeline.effective_position()))
         } else {
             None
         };
@@ -4431,17 +10946,19 @@ impl DashboardApp {
                 let is_hovered = self.graph.hovered_node.as_ref() == Some(&node.id);
                 let is_selected = self.graph.selected_node.as_ref() == Some(&node.id);
 
-                // Apply normalization and clamp
-                let size_multiplier = (raw_multiplier * scale).clamp(0.05, self.max_node_multiplier);
-                let base_size = self.node_size * self.zoom * size_multiplier;
+                // Normalize so the largest visible node gets max_node_multiplier
+                let size_multiplier = scene::normalize_multiplier(raw_multiplier, max_multiplier, self.max_node_multiplier);
+                let base_size = scene::clamp_node_radius(self.node_size * zoom * size_multiplier, pixels_per_point);
                 let size = if is_hovered || is_selected {
                     base_size * 1.3
                 } else {
                     base_size
                 };
+                self.node_radii[idx] = base_size;
 
                 // Use project or session color based on mode
                 let base_color = self.graph.node_color(node);
+                let base_color = if self.community_dimmed(node) { scene::dimmed(base_color) } else { base_color };
 
                 // Color logic:
                 // - Same-project future nodes: greyscale
@@ -4453,7 +10970,7 @@ impl DashboardApp {
                     let is_future = scrubber_time
                         .and_then(|st| node.timestamp_secs().map(|nt| nt > st))
                         .unwrap_or(false);
-                    if is_future && !is_hovered && !is_selected {
+                    if is_future && !is_hovered && !is_selected && self.palette.desaturate_future {
                         crate::graph::types::desaturate(base_color, 0.7)
                     } else {
                         base_color
@@ -4511,11 +11028,11 @@ impl DashboardApp {
                     let border_color = if is_summary_node {
                         theme::state::ACTIVE // Cyan for summary node
                     } else if is_selected {
-                        theme::state::SELECTED
+                        self.palette.highlight.selected
                     } else if is_cmd_neighbor {
                         theme::state::ACTIVE // Cyan for cmd-hover neighbor
                     } else if is_hovered {
-                        theme::state::HOVER
+                        self.palette.highlight.hover
                     } else {
                         color.gamma_multiply(0.7)
                     };
@@ -4528,30 +11045,118 @@ impl DashboardApp {
                     } else {
                         theme::stroke_width::NORMAL
                     };
-                    painter.circle_stroke(screen_pos, size, Stroke::new(border_width, border_color));
+                    painter.circle_stroke(screen_pos, size, Stroke::new(border_width * self.palette.stroke_scale, border_color));
+
+                    // Importance arc: sweep proportional to the score (skipped on small nodes)
+                    if self.settings.importance_rings {
+                        let arc = scene::importance_arc(
+                            node,
+                            screen_pos,
+                            size,
+                            1.5 * self.palette.stroke_scale,
+                            self.palette.text.muted,
+                            self.palette.accent.orange,
+                        );
+                        if let Some(arc) = arc {
+                            painter.add(arc);
+                        }
+                    }
+                }
+
+                // Session terminus: small square off the last message of each session
+                let is_session_end = self.settings.session_end_markers
+                    && self.graph.session_spans.get(&node.session_id).is_some_and(|s| s.last_node == node.id);
+                if is_session_end {
+                    let side = (size * 0.5).max(4.0);
+                    let marker = egui::Rect::from_center_size(screen_pos + Vec2::new(size * 0.9, -size * 0.9), Vec2::splat(side));
+                    painter.rect_filled(marker, 1.0, color);
+                    painter.rect_stroke(marker, 1.0, Stroke::new(self.palette.stroke_scale, self.palette.text.primary));
+                }
+
+                // Live session tail: a slowly pulsing halo
+                if self.live_tails.contains(&node.id) {
+                    let pulse = (live_time * 2.0).sin() * 0.5 + 0.5;
+                    painter.circle_stroke(
+                        screen_pos,
+                        size + 3.0 + pulse * 2.0,
+                        Stroke::new(2.0 * self.palette.stroke_scale, color.gamma_multiply(0.3 + pulse * 0.4)),
+                    );
+                }
+
+                // Favorite: a star off the lower right
+                if self.favorite_ids.contains(&node.id) {
+                    self.text_layout.text(
+                        &painter,
+                        screen_pos + Vec2::new(size * 0.8, size * 0.8),
+                        egui::Align2::LEFT_TOP,
+                        "★",
+                        egui::FontId::proportional((size * 0.8).clamp(9.0, 16.0)),
+                        self.palette.accent.orange,
+                    );
+                }
+
+                // Collapsed tool results: run length under the node
+                let run_len = self.graph.tool_runs.run_len(idx);
+                if run_len > 1 {
+                    self.text_layout.text(
+                        &painter,
+                        screen_pos + Vec2::new(0.0, size + 2.0),
+                        egui::Align2::CENTER_TOP,
+                        &format!("{} tool results", run_len),
+                        egui::FontId::proportional((size * 0.5).clamp(8.0, 12.0)),
+                        self.palette.text.muted,
+                    );
+                }
+
+                // Code badge: </> off the upper left of code-heavy messages
+                let is_code = self.settings.content_badges
+                    && size >= CONTENT_BADGE_MIN_RADIUS
+                    && self.graph.content_classes.get(idx).is_some_and(|c| c.kind == ContentKind::Code);
+                if is_code {
+                    self.text_layout.text(
+                        &painter,
+                        screen_pos + Vec2::new(-size * 0.9, -size * 0.9),
+                        egui::Align2::RIGHT_BOTTOM,
+                        "</>",
+                        egui::FontId::monospace((size * 0.6).clamp(8.0, 14.0)),
+                        self.palette.text.primary,
+                    );
                 }
             }
         }
 
+        self.profiler.record(Phase::Nodes, nodes_started);
+
+        // Right-click menu for the node under the pointer (session comparison)
+        if response.secondary_clicked() {
+            self.context_menu_node = self.graph.hovered_node.clone();
+        }
+        response.context_menu(|ui| self.render_node_context_menu(ui));
+
+        // Clicking a gap edge loads the messages it stands in for
+        if let Some(gap_index) = self.hovered_gap {
+            let missing = self.graph.data.edges.get(gap_index).and_then(|e| e.gap).unwrap_or(0);
+            let text_scale = self.settings.tooltip_text_scale * self.palette.tooltip_text_scale;
+            response.clone().on_hover_ui_at_pointer(|ui| {
+                theme::scale_text(ui.style_mut(), text_scale);
+                ui.label(format!("{} message{} outside the loaded window", missing, if missing == 1 { "" } else { "s" }));
+                ui.label(egui::RichText::new("Click to widen the time range to include them").small().weak());
+            });
+            if response.clicked() {
+                self.widen_range_for_gap(gap_index);
+            }
+        }
+
         // Handle click selection with double-click and Ctrl+Click detection
         // Use the already-computed closest node from hover detection
-        if response.clicked() {
+        if response.clicked() && self.hovered_gap.is_none() {
             let clicked_node = self.graph.hovered_node.clone();
             let modifiers = ui.input(|i| i.modifiers);
 
             if let Some(ref node_id) = clicked_node {
-                // Cross-session click: if clicking on a dimmed (non-visible) node, jump timeline to it
-                // DISABLED FOR DEBUGGING
-                if false && self.hover_scrubs_timeline && self.timeline_enabled {
-                    if !self.graph.is_node_visible(node_id) {
-                        let node_time = self.graph.get_node(node_id).and_then(|n| n.timestamp_secs());
-                        if let Some(t) = node_time {
-                            let new_pos = self.graph.timeline.position_at_time(t);
-                            self.graph.timeline.position = new_pos.max(self.graph.timeline.start_position);
-                            self.graph.update_visible_nodes();
-                            self.effective_visible_dirty = true;
-                        }
-                    }
+                // Clicking while hover-scrubbing commits the previewed position
+                if let Some(preview) = self.graph.timeline.preview_position {
+                    self.graph.timeline.position = preview;
                 }
 
                 // Ctrl+Click (Cmd+Click on macOS) → neighborhood summary
@@ -4575,7 +11180,7 @@ impl DashboardApp {
                 self.last_click_node = None;
             }
 
-            self.graph.selected_node = clicked_node;
+            self.commands.push(AppCommand::SelectNode(clicked_node));
         }
 
         // Draw tooltip for hovered node
@@ -4583,7 +11188,7 @@ impl DashboardApp {
             if let Some(node) = self.graph.get_node(hovered_id) {
                 if let Some(pos) = self.graph.get_pos(hovered_id) {
                     let screen_pos = transform(pos);
-                    let tooltip_pos = screen_pos + Vec2::new(self.node_size * self.zoom + 10.0, 0.0);
+                    let tooltip_pos = screen_pos + Vec2::new(self.node_size * zoom + 10.0, 0.0);
 
                     let mut lines: Vec<String> = Vec::new();
 
@@ -4655,57 +11260,23 @@ impl DashboardApp {
                         }
                         lines.push(format!("Display: {}", display_props.join(", ")));
                     } else {
-                        // Normal tooltip: content preview + metadata
-                        // Content preview — word-wrap to ~50 chars, max 4 lines
-                        let preview = &node.content_preview;
-                        let max_line_len = 50;
-                        let max_preview_lines = 4;
-                        let mut char_iter = preview.chars().peekable();
-                        let mut preview_lines = 0;
-                        while char_iter.peek().is_some() && preview_lines < max_preview_lines {
-                            let chunk: String = char_iter.by_ref().take(max_line_len).collect();
-                            lines.push(chunk.trim_end().to_string());
-                            preview_lines += 1;
-                        }
-                        if char_iter.peek().is_some() {
-                            if let Some(last) = lines.last_mut() {
-                                last.push_str("...");
-                            }
-                        }
-
-                        lines.push(String::new());
-
-                        // Project
-                        lines.push(format!("Project: {}", node.project));
-
-                        // Timestamp — relative "3 hours ago", "Yesterday at 2:30 PM", etc.
-                        if let Some(secs) = node.timestamp_secs() {
-                            lines.push(format!("Time: {}", self.graph.timeline.format_time(secs)));
-                        }
-
-                        // Tokens — compact "1.2k in / 3.4k out"
-                        let in_tok = node.input_tokens.unwrap_or(0);
-                        let out_tok = node.output_tokens.unwrap_or(0);
-                        if in_tok > 0 || out_tok > 0 {
-                            let fmt_tok = |t: i32| -> String {
-                                if t >= 1000 { format!("{:.1}k", t as f64 / 1000.0) }
-                                else { format!("{}", t) }
-                            };
-                            lines.push(format!("Tokens: {} in / {} out", fmt_tok(in_tok), fmt_tok(out_tok)));
-                        }
-
-                        // Tools used
-                        if node.has_tool_usage {
-                            lines.push("Tools used".to_string());
-                        }
+                        // Normal tooltip: the configured fields, in order
+                        let filters: Vec<String> = self.semantic_filters.iter()
+                            .filter(|f| self.local_rule_matches.get(&f.id).is_some_and(|m| m.contains(&node.id)))
+                            .map(|f| f.name.clone())
+                            .collect();
+                        let timeline = &self.graph.timeline;
+                        lines = self.settings.tooltip.lines(node, self.graph.content_class(&node.id), |secs| timeline.format_time(secs), &filters);
                     }
 
                     let tooltip_text = lines.join("\n");
 
-                    let galley = painter.layout_no_wrap(
-                        tooltip_text,
-                        egui::FontId::new(13.0, egui::FontFamily::Proportional),
-                        Color32::WHITE,
+                    let tooltip_colors = self.palette.tooltip;
+                    let galley = self.text_layout.layout_no_wrap(
+                        &painter,
+                        &tooltip_text,
+                        egui::FontId::new(13.0 * self.settings.tooltip_text_scale * self.palette.tooltip_text_scale, egui::FontFamily::Proportional),
+                        tooltip_colors.text,
                     );
 
                     let tooltip_rect = egui::Rect::from_min_size(
@@ -4713,27 +11284,35 @@ impl DashboardApp {
                         galley.size() + Vec2::splat(16.0),
                     );
 
-                    painter.rect_filled(
-                        tooltip_rect,
-                        4.0,
-                        Color32::from_rgba_unmultiplied(20, 20, 30, 230),
-                    );
-                    painter.galley(tooltip_pos + Vec2::splat(8.0), galley, Color32::WHITE);
+                    painter.rect_filled(tooltip_rect, 4.0, tooltip_colors.bg);
+                    painter.rect_stroke(tooltip_rect, 4.0, Stroke::new(1.0, tooltip_colors.border));
+                    painter.galley(tooltip_pos + Vec2::splat(8.0), galley, tooltip_colors.text);
                 }
             }
         }
 
-        // Loading indicator with skeleton animation
-        if self.loading {
-            // Animated loading pulse
-            let time = ui.ctx().input(|i| i.time);
-            let pulse = ((time * 2.0).sin() * 0.5 + 0.5) as f32;
-            let text_color = Color32::from_rgba_unmultiplied(
-                240,
-                240,
-                245,
-                (150.0 + pulse * 105.0) as u8
+        if let Some(drag) = self.time_drag {
+            self.render_time_drag(&painter, rect, &drag, zoom);
+        }
+
+        // A chunked load with some of the graph already in: a progress line
+        // over the graph instead of the placeholder
+        if let Some((loaded, expected)) = self.load_progress.filter(|_| !self.graph.data.nodes.is_empty()) {
+            painter.text(
+                Pos2::new(rect.center().x, rect.top() + 16.0),
+                egui::Align2::CENTER_CENTER,
+                format!("Loading {}", load_progress_text(loaded, expected)),
+                egui::FontId::proportional(14.0),
+                self.palette.text.muted,
             );
+            ui.ctx().request_repaint_after(REDUCED_MOTION_REPAINT);
+        } else if self.loading {
+            // Loading indicator with skeleton animation
+            // Animated loading pulse (held at mid-brightness with reduced motion)
+            let motion = theme::motion_enabled();
+            let time = if motion { ui.ctx().input(|i| i.time) } else { 0.0 };
+            let pulse = ((time * 2.0).sin() * 0.5 + 0.5) as f32;
+            let text_color = self.palette.text.primary.gamma_multiply(0.6 + pulse * 0.4);
 
             painter.text(
                 center,
@@ -4757,7 +11336,12 @@ impl DashboardApp {
                 painter.circle_filled(*pos, size, Color32::from_rgba_unmultiplied(80, 85, 100, alpha));
             }
 
-            ui.ctx().request_repaint(); // Keep animating
+            if motion {
+                ui.ctx().request_repaint(); // Keep animating
+            } else {
+                // Still poll for the load finishing
+                ui.ctx().request_repaint_after(REDUCED_MOTION_REPAINT);
+            }
         }
     }
 
@@ -4800,6 +11384,291 @@ impl DashboardApp {
         self.render_token_histogram(&mut histogram_ui);
     }
 
+    /// Right-click menu on a graph node: pick its session for side-by-side comparison
+    fn render_node_context_menu(&mut self, ui: &mut egui::Ui) {
+        let session_id = self.context_menu_node.as_ref()
+            .and_then(|id| self.graph.get_node(id))
+            .map(|node| node.session_id.clone());
+        let Some(session_id) = session_id else {
+            ui.label(egui::RichText::new("Right-click a node to compare its session").small().color(self.palette.text.muted));
+            return;
+        };
+
+        if ui.add_enabled(self.similar_rx.is_none() && self.db.is_some(), egui::Button::new("Find similar")).clicked() {
+            if let Some(id) = self.context_menu_node.clone() {
+                self.find_similar(&id);
+            }
+            ui.close_menu();
+        }
+        // Only stored messages can be starred (not dropped transcripts or topics)
+        let stored = self.context_menu_node.clone().filter(|id| ids::message_id(id).is_some() && self.db.is_some());
+        if let Some(id) = stored {
+            let starred = self.favorite_ids.contains(&id);
+            if ui.button(if starred { "☆ Remove favorite" } else { "★ Favorite" }).clicked() {
+                self.set_favorite(&id, !starred);
+                ui.close_menu();
+            }
+        }
+        let diff_pair = self.graph.selected_node.clone()
+            .zip(self.context_menu_node.clone())
+            .filter(|(selected, clicked)| selected != clicked);
+        if let Some((selected, clicked)) = diff_pair {
+            let running = matches!(self.content_diff, Some(ContentDiffState::Running(_)));
+            if ui.add_enabled(!running, egui::Button::new("Diff contents with selected"))
+                .on_hover_text("Line diff from the selected message to this one, e.g. for a retried prompt")
+                .clicked()
+            {
+                self.start_content_diff([selected, clicked]);
+                ui.close_menu();
+            }
+        }
+        let target = self.context_menu_node.as_ref()
+            .and_then(|id| self.graph.get_node(id))
+            .and_then(|node| self.hue_target_for(node));
+        if let Some(target) = target {
+            let pinned = self.graph.hue_overrides.get(&target).is_some();
+            if let Some(action) = hue_override_menu(ui, pinned) {
+                self.apply_hue_menu_action(target, action);
+            }
+        }
+        ui.separator();
+
+        match self.compare_pick.clone() {
+            Some(picked) if picked != session_id => {
+                if ui.button("Compare with picked session").clicked() {
+                    self.enter_compare([picked, session_id]);
+                    ui.close_menu();
+                } else if ui.button("Pick this session instead").clicked() {
+                    self.compare_pick = Some(session_id);
+                    ui.close_menu();
+                }
+            }
+            Some(_) => {
+                ui.label(egui::RichText::new("Right-click a node from another session").small().color(self.palette.text.muted));
+                if ui.button("Cancel pick").clicked() {
+                    self.compare_pick = None;
+                    ui.close_menu();
+                }
+            }
+            None => {
+                if ui.button("Pick session to compare").clicked() {
+                    self.compare_pick = Some(session_id);
+                    ui.close_menu();
+                }
+            }
+        }
+    }
+
+    /// Render two sessions side by side below a stats strip comparing them
+    fn render_compare_view(&mut self, ui: &mut egui::Ui) {
+        let Some(sessions) = self.compare_sessions.clone() else {
+            return;
+        };
+        if self.effective_visible_dirty {
+            self.rebuild_effective_visible_set();
+        }
+        let stats = [0, 1].map(|side| {
+            SessionStats::from_nodes(self.compare_nodes[side].iter().map(|&idx| &self.graph.data.nodes[idx]))
+        });
+
+        self.render_compare_stats(ui, &sessions, &stats);
+        ui.separator();
+
+        let available = ui.available_rect_before_wrap();
+        let divider_width = 4.0;
+        let side_width = (available.width() - divider_width) / 2.0;
+        for (side, session_id) in sessions.iter().enumerate() {
+            let side_rect = egui::Rect::from_min_size(
+                egui::pos2(available.min.x + side as f32 * (side_width + divider_width), available.min.y),
+                egui::vec2(side_width, available.height()),
+            );
+            let mut side_ui = ui.new_child(egui::UiBuilder::new().max_rect(side_rect));
+            self.render_compare_side(&mut side_ui, side, session_id, &stats[side]);
+        }
+
+        let divider_rect = egui::Rect::from_min_size(
+            egui::pos2(available.min.x + side_width, available.min.y),
+            egui::vec2(divider_width, available.height()),
+        );
+        ui.painter().rect_filled(divider_rect, 0.0, self.palette.border.subtle);
+    }
+
+    /// Message, token, duration and tool-use counts for both compared sessions
+    fn render_compare_stats(&mut self, ui: &mut egui::Ui, sessions: &[String; 2], stats: &[SessionStats; 2]) {
+        ui.horizontal(|ui| {
+            ui.label(egui::RichText::new("Session comparison").strong());
+            ui.with_layout(egui::Layout::right_to_left(egui::Align::Center), |ui| {
+                if ui.button("Exit compare").clicked() {
+                    self.exit_compare();
+                }
+            });
+        });
+
+        let duration = |s: &SessionStats| s.duration_secs().map(compare::format_duration).unwrap_or_else(|| "—".to_string());
+        let rows = [
+            ("Messages", stats[0].messages.to_string(), stats[1].messages.to_string()),
+            ("Tokens", format_count(stats[0].tokens as i64), format_count(stats[1].tokens as i64)),
+            ("Duration", duration(&stats[0]), duration(&stats[1])),
+            ("Tool uses", stats[0].tool_uses.to_string(), stats[1].tool_uses.to_string()),
+        ];
+        egui::Grid::new("compare_stats").num_columns(3).striped(true).show(ui, |ui| {
+            ui.label("");
+            for (side, session_id) in sessions.iter().enumerate() {
+                ui.label(egui::RichText::new(self.compare_side_title(side, session_id)).strong().color(self.compare_side_color(side, session_id)));
+            }
+            ui.end_row();
+            for (label, a, b) in rows {
+                ui.label(egui::RichText::new(label).color(self.palette.text.muted));
+                ui.label(a);
+                ui.label(b);
+                ui.end_row();
+            }
+        });
+    }
+
+    /// "A 1a2b3c4d" style heading for one side of the compare view
+    fn compare_side_title(&self, side: usize, session_id: &str) -> String {
+        format!("{} {}", ["A", "B"][side], ids::short_session(session_id))
+    }
+
+    /// Node color of a compared session, taken from its first visible node
+    fn compare_side_color(&self, side: usize, session_id: &str) -> Color32 {
+        let project = self.compare_nodes[side].first()
+            .map(|&idx| self.graph.data.nodes[idx].project.as_str())
+            .unwrap_or_default();
+        self.graph.session_color(session_id, project)
+    }
+
+    /// One half of the compare view: the session's nodes placed on its own time axis,
+    /// with vertical positions from the graph layout under the shared vertical pan/zoom
+    fn render_compare_side(&mut self, ui: &mut egui::Ui, side: usize, session_id: &str, stats: &SessionStats) {
+        let indices = self.compare_nodes[side].clone();
+        let project = indices.first()
+            .map(|&idx| self.graph.data.nodes[idx].project.clone())
+            .unwrap_or_default();
+
+        ui.horizontal(|ui| {
+            ui.label(egui::RichText::new(self.compare_side_title(side, session_id)).strong().color(self.compare_side_color(side, session_id)));
+            ui.label(egui::RichText::new(&project).small().color(self.palette.text.muted));
+        });
+        if let Some(summary) = self.session_summary_cache.get(session_id).and_then(|data| data.summary.clone()) {
+            egui::ScrollArea::vertical()
+                .max_height(60.0)
+                .id_salt(("compare_summary_scroll", side))
+                .show(ui, |ui| {
+                    ui.label(egui::RichText::new(summary).small());
+                });
+        }
+
+        let (response, painter) = ui.allocate_painter(ui.available_size(), egui::Sense::click_and_drag());
+        let rect = response.rect;
+        painter.rect_filled(rect, 0.0, self.palette.bg.graph);
+
+        // Dragging or scrolling either side moves both vertically
+        if response.dragged_by(egui::PointerButton::Primary) {
+            self.compare_pan_y += response.drag_delta().y;
+        }
+        if response.hovered() {
+            let (scroll_y, zoom_delta) = ui.input(|i| (i.smooth_scroll_delta.y, i.zoom_delta()));
+            self.compare_pan_y += scroll_y;
+            if zoom_delta != 1.0 {
+                self.compare_zoom = (self.compare_zoom * zoom_delta).clamp(0.05, 20.0);
+            }
+        }
+
+        if indices.is_empty() {
+            painter.text(
+                rect.center(),
+                egui::Align2::CENTER_CENTER,
+                "No visible messages in this session",
+                egui::FontId::proportional(13.0),
+                self.palette.text.muted,
+            );
+            return;
+        }
+
+        // Time axis along the bottom, relative to the session's first message
+        let margin = 16.0;
+        let axis_left = rect.left() + margin;
+        let axis_width = (rect.width() - margin * 2.0).max(1.0);
+        let axis_y = rect.bottom() - 18.0;
+        painter.line_segment(
+            [egui::pos2(axis_left, axis_y), egui::pos2(axis_left + axis_width, axis_y)],
+            Stroke::new(1.0, self.palette.border.default),
+        );
+        let axis_font = egui::FontId::proportional(10.0);
+        painter.text(egui::pos2(axis_left, axis_y + 3.0), egui::Align2::LEFT_TOP, "0s", axis_font.clone(), self.palette.text.muted);
+        if let Some(span) = stats.duration_secs() {
+            painter.text(
+                egui::pos2(axis_left + axis_width, axis_y + 3.0),
+                egui::Align2::RIGHT_TOP,
+                compare::format_duration(span),
+                axis_font,
+                self.palette.text.muted,
+            );
+        }
+
+        // Center each side's layout vertically so the shared pan lines them up
+        let layout_ys: Vec<f32> = indices.iter()
+            .filter_map(|&idx| self.graph.get_pos(&self.graph.data.nodes[idx].id).map(|p| p.y))
+            .collect();
+        let mean_y = if layout_ys.is_empty() { 0.0 } else { layout_ys.iter().sum::<f32>() / layout_ys.len() as f32 };
+        let center_y = (rect.top() + axis_y) / 2.0;
+        let radius = (self.node_size * 0.6).max(3.0);
+        let hover_pos = response.hover_pos();
+        let mut hovered: Option<(usize, f32)> = None;
+
+        let clip = painter.with_clip_rect(egui::Rect::from_min_max(rect.min, egui::pos2(rect.right(), axis_y)));
+        for &idx in &indices {
+            let node = &self.graph.data.nodes[idx];
+            let Some(pos) = self.graph.get_pos(&node.id) else {
+                continue;
+            };
+            let screen_pos = egui::pos2(
+                axis_left + stats.time_fraction(node.timestamp_secs()) * axis_width,
+                center_y + (pos.y - mean_y) * self.compare_zoom + self.compare_pan_y,
+            );
+            clip.circle_filled(screen_pos, radius, self.graph.node_color(node));
+            if node.role == Role::Assistant {
+                clip.circle_filled(screen_pos, radius * 0.4, Color32::BLACK);
+            }
+            if self.graph.selected_node.as_deref() == Some(node.id.as_str()) {
+                clip.circle_stroke(screen_pos, radius, Stroke::new(theme::stroke_width::SELECTED, self.palette.highlight.selected));
+            }
+            if let Some(hover) = hover_pos {
+                let dist = hover.distance(screen_pos);
+                if dist <= radius + 4.0 && hovered.is_none_or(|(_, best)| dist < best) {
+                    hovered = Some((idx, dist));
+                }
+            }
+        }
+
+        let hovered_id = hovered.map(|(idx, _)| self.graph.data.nodes[idx].id.clone());
+        if let (Some(id), Some(hover)) = (&hovered_id, hover_pos) {
+            if let Some(node) = self.graph.get_node(id) {
+                let galley = self.text_layout.layout_no_wrap(
+                    &painter,
+                    &truncate(&node.content_preview, 60),
+                    egui::FontId::proportional(12.0),
+                    self.palette.tooltip.text,
+                );
+                let text_pos = hover + egui::vec2(12.0, -galley.size().y / 2.0);
+                painter.rect_filled(egui::Rect::from_min_size(text_pos, galley.size()).expand(4.0), 4.0, self.palette.tooltip.bg);
+                painter.galley(text_pos, galley, self.palette.tooltip.text);
+            }
+        }
+
+        // Click selects, double-click opens the node's summary like the main graph
+        if response.double_clicked() {
+            if let Some(id) = hovered_id.clone() {
+                self.trigger_summary_for_node(id);
+            }
+        }
+        if response.clicked() {
+            self.commands.push(AppCommand::SelectNode(hovered_id));
+        }
+    }
+
     /// Render the draggable divider between graph and histogram
     fn render_divider(&mut self, ui: &mut egui::Ui, rect: egui::Rect) {
         let response = ui.allocate_rect(rect, egui::Sense::click_and_drag());
@@ -4818,9 +11687,9 @@ impl DashboardApp {
 
         // Visual feedback
         let color = if response.hovered() || self.histogram_dragging_divider {
-            theme::border::FOCUS
+            self.palette.border.focus
         } else {
-            theme::border::SUBTLE
+            self.palette.border.subtle
         };
 
         ui.painter().rect_filled(rect, 0.0, color);
@@ -4833,24 +11702,7 @@ impl DashboardApp {
 
     /// Get the color for a session in the histogram, matching graph node colors
     fn histogram_session_color(&self, session_id: &str, project: &str) -> egui::Color32 {
-        use crate::graph::types::{ColorMode, hsl_to_rgb};
-        match self.graph.color_mode {
-            ColorMode::Project if !project.is_empty() => {
-                let hue = self.graph.project_colors.get(project).copied().unwrap_or(0.0);
-                hsl_to_rgb(self.graph.apply_hue_offset(hue), 0.7, 0.55)
-            }
-            ColorMode::Hybrid if !project.is_empty() => {
-                let hue = self.graph.project_colors.get(project).copied().unwrap_or(0.0);
-                let t = self.graph.session_position_in_project(session_id, project);
-                let sat = 0.5 + t * 0.4;
-                let light = 0.65 - t * 0.2;
-                hsl_to_rgb(self.graph.apply_hue_offset(hue), sat, light)
-            }
-            _ => {
-                let hue = self.graph.session_colors.get(session_id).copied().unwrap_or(0.0);
-                hsl_to_rgb(self.graph.apply_hue_offset(hue), 0.7, 0.5)
-            }
-        }
+        self.graph.session_color(session_id, project)
     }
 
     /// Render the token usage histogram
@@ -5032,6 +11884,7 @@ impl DashboardApp {
                                             self.selected_projects.insert(session.project.clone());
                                         }
                                     }
+                                    self.settings_dirty = true;
                                     // Reset drill tracking to this segment
                                     self.histogram_session_filter = None;
                                     self.histogram_last_clicked = Some(this_seg);
@@ -5070,7 +11923,7 @@ impl DashboardApp {
 
             let tick_top = rect.min.y + available_height;
             let tick_bottom = tick_top + 4.0;
-            let label_color = theme::text::SECONDARY;
+            let label_color = self.palette.text.secondary;
 
             // Tick mark
             painter.line_segment(
@@ -5080,7 +11933,8 @@ impl DashboardApp {
 
             // Label
             let label = format_timestamp(&bin.timestamp_start);
-            painter.text(
+            self.text_layout.text(
+                &painter,
                 egui::pos2(bar_x + 2.0, tick_bottom + 1.0),
                 egui::Align2::LEFT_TOP,
                 &label,
@@ -5105,6 +11959,7 @@ impl DashboardApp {
             );
 
             egui::show_tooltip_at_pointer(ui.ctx(), egui::LayerId::new(egui::Order::Tooltip, ui.id().with("hist_layer")), ui.id().with("hist_tooltip"), |ui| {
+                theme::scale_text(ui.style_mut(), self.settings.tooltip_text_scale * self.palette.tooltip_text_scale);
                 ui.label(format!("{} - {}",
                     format_timestamp(&bin.timestamp_start),
                     format_timestamp(&bin.timestamp_end)
@@ -5113,7 +11968,7 @@ impl DashboardApp {
                 for session in &bin.sessions {
                     let color = self.histogram_session_color(&session.session_id, &session.project);
                     let label = if session.project.is_empty() {
-                        format!("{}: {} tokens", &session.session_id[..8.min(session.session_id.len())], session.total_tokens)
+                        format!("{}: {} tokens", ids::short_session(&session.session_id), session.total_tokens)
                     } else {
                         format!("{}{}: {} tokens",
                             session.project,
@@ -5179,7 +12034,7 @@ impl DashboardApp {
         // When timeline scrubber is active, synchronize histogram to the visible window
         let (start_time, bin_duration_secs, bin_count) = if self.timeline_enabled {
             let scrubber_start_epoch = self.graph.timeline.time_at_position(self.graph.timeline.start_position);
-            let scrubber_end_epoch = self.graph.timeline.time_at_position(self.graph.timeline.position);
+            let scrubber_end_epoch = self.graph.timeline.time_at_position(self.graph.timeline.effective_position());
             let visible_range_secs = (scrubber_end_epoch - scrubber_start_epoch).max(1.0);
 
             let start_dt = DateTime::<Utc>::from_timestamp(scrubber_start_epoch as i64, 0)
@@ -5248,7 +12103,7 @@ impl DashboardApp {
             let mut sessions: Vec<SessionTokens> = session_map
                 .into_iter()
                 .map(|(session_id, (project, total))| {
-                    let is_filtered = (project_filter_active && !selected_projects.contains(&project))
+                    let is_filtered = (project_filter_active && !selected_projects.is_empty() && !selected_projects.contains(&project))
                         || session_filter.as_ref().is_some_and(|sf| sf != &session_id);
                     SessionTokens {
                         session_id,
@@ -5271,20 +12126,357 @@ impl DashboardApp {
                         a_ts.partial_cmp(&b_ts).unwrap_or(std::cmp::Ordering::Equal)
                     });
                 }
-                HistogramStackOrder::MostMessages => {
-                    sessions.sort_by(|a, b| {
-                        let a_count = session_cache.get(&a.session_id).map(|c| c.1).unwrap_or(0);
-                        let b_count = session_cache.get(&b.session_id).map(|c| c.1).unwrap_or(0);
-                        b_count.cmp(&a_count)
-                    });
+                HistogramStackOrder::MostMessages => {
+                    sessions.sort_by(|a, b| {
+                        let a_count = session_cache.get(&a.session_id).map(|c| c.1).unwrap_or(0);
+                        let b_count = session_cache.get(&b.session_id).map(|c| c.1).unwrap_or(0);
+                        b_count.cmp(&a_count)
+                    });
+                }
+            }
+
+            bins[i].total_tokens = sessions.iter().map(|s| s.total_tokens).sum();
+            bins[i].sessions = sessions;
+        }
+
+        bins
+    }
+
+    /// Render the collapsible token usage histogram (bins from `HistogramState`)
+    /// Write the token histogram as CSV into the downloads (or home) directory
+    fn export_token_csv(&self) -> Result<std::path::PathBuf, String> {
+        let dir = dirs::download_dir()
+            .or_else(dirs::home_dir)
+            .ok_or_else(|| "Could not determine a directory to save to".to_string())?;
+        let name = format!("token-usage-{}.csv", chrono::Local::now().format("%Y%m%d-%H%M%S"));
+        let path = dir.join(name);
+        std::fs::write(&path, self.token_histogram.to_csv()).map_err(|e| format!("Failed to write CSV: {}", e))?;
+        tracing::info!("Exported token usage to {:?}", path);
+        Ok(path)
+    }
+
+    /// Filters currently narrowing the visible set, as recorded in a stats export
+    fn active_export_filters(&self) -> ActiveFilters {
+        let mut projects: Vec<String> = if self.project_filter.is_active() {
+            self.selected_projects.iter().cloned().collect()
+        } else {
+            Vec::new()
+        };
+        projects.sort();
+        ActiveFilters {
+            timeline_window: self.timeline_enabled,
+            min_importance: self.importance_filter.is_active().then_some(self.importance_threshold),
+            projects,
+            semantic: self.semantic_filters.iter()
+                .filter(|f| self.semantic_filter_modes.get(&f.id).is_some_and(|m| *m != SemanticFilterMode::Off))
+                .map(|f| f.name.clone())
+                .collect(),
+            hide_tool_use: self.tool_use_filter.is_active(),
+            session: self.histogram_session_filter.clone(),
+            tags: self.tag_filter.tags.iter().cloned().collect(),
+        }
+    }
+
+    /// Write graph statistics for the current window and filters as JSON into
+    /// the downloads (or home) directory
+    fn export_stats_json(&self) -> Result<std::path::PathBuf, String> {
+        let dir = dirs::download_dir()
+            .or_else(dirs::home_dir)
+            .ok_or_else(|| "Could not determine a directory to save to".to_string())?;
+        let visible = self.effective_visible_set();
+        let window = self.timeline_enabled.then(|| self.graph.get_timeline_window());
+        let export = StatsExport::build(
+            &self.graph.data,
+            |node| visible.is_none_or(|set| set.contains(&node.id)),
+            window,
+            self.active_export_filters(),
+            self.time_range_hours,
+            unix_now(),
+        )
+        .with_session_tags(&self.session_tags);
+        let name = format!("graph-stats-{}.json", chrono::Local::now().format("%Y%m%d-%H%M%S"));
+        let path = dir.join(name);
+        std::fs::write(&path, export.to_json()?).map_err(|e| format!("Failed to write JSON: {}", e))?;
+        tracing::info!("Exported graph stats to {:?}", path);
+        Ok(path)
+    }
+
+    fn render_token_usage_panel(&mut self, ui: &mut egui::Ui) {
+        ui.horizontal(|ui| {
+            let open = self.settings.token_usage_panel_open;
+            let arrow = if open { "⏷" } else { "⏵" };
+            if ui.selectable_label(false, format!("{} Token usage", arrow)).clicked() {
+                self.settings.token_usage_panel_open = !open;
+                self.mark_settings_dirty();
+            }
+            if !open {
+                return;
+            }
+
+            let hist = &mut self.token_histogram;
+            let mut stack_order = hist.stack_order;
+            egui::ComboBox::from_id_salt("token_stack_order")
+                .selected_text(format!("Stack: {}", stack_order.label()))
+                .show_ui(ui, |ui| {
+                    for &order in StackOrder::all() {
+                        ui.selectable_value(&mut stack_order, order, order.label());
+                    }
+                });
+            hist.set_stack_order(stack_order);
+
+            let mut display_mode = hist.display_mode;
+            egui::ComboBox::from_id_salt("token_display_mode")
+                .selected_text(format!("Show: {}", display_mode.label()))
+                .show_ui(ui, |ui| {
+                    for &mode in TokenDisplayMode::all() {
+                        ui.selectable_value(&mut display_mode, mode, mode.label());
+                    }
+                });
+            hist.set_display_mode(display_mode);
+
+            let mut bin_count = hist.bin_count;
+            ui.add(egui::Slider::new(&mut bin_count, 5..=200).text("bins"));
+            hist.set_bin_count(bin_count);
+
+            if ui
+                .selectable_label(self.settings.token_histogram_log_scale, "Log")
+                .on_hover_text("Log-scale bar heights so one huge bin doesn't flatten the rest")
+                .clicked()
+            {
+                self.settings.token_histogram_log_scale = !self.settings.token_histogram_log_scale;
+                self.mark_settings_dirty();
+            }
+
+            ui.with_layout(egui::Layout::right_to_left(egui::Align::Center), |ui| {
+                if ui
+                    .small_button("Export CSV")
+                    .on_hover_text("Save the binned counts (current stack order and filters) as CSV")
+                    .clicked()
+                {
+                    self.token_csv_status = Some(match self.export_token_csv() {
+                        Ok(path) => (format!("Saved {}", path.display()), false),
+                        Err(e) => (e, true),
+                    });
+                }
+                if let Some((message, is_error)) = &self.token_csv_status {
+                    let color = if *is_error { theme::state::ERROR } else { self.palette.text.muted };
+                    ui.label(egui::RichText::new(message).small().color(color));
+                }
+                ui.label(
+                    egui::RichText::new(format!("{} tokens", self.token_histogram.total_tokens))
+                        .small()
+                        .color(self.palette.text.muted),
+                );
+            });
+        });
+        if !self.settings.token_usage_panel_open {
+            return;
+        }
+
+        // Breadcrumbs for active segment filters
+        let active_segments = self.token_histogram.filter.active_segments();
+        if !active_segments.is_empty() {
+            ui.horizontal_wrapped(|ui| {
+                ui.label(egui::RichText::new("Filtered to:").small().color(self.palette.text.muted));
+                for (order, label) in active_segments {
+                    let chip = format!("{}: {}  ✕", order.label(), label);
+                    if ui.small_button(chip).on_hover_text("Remove this filter").clicked() {
+                        self.token_histogram.toggle_segment_filter(order, &label);
+                    }
+                }
+            });
+        }
+
+        // Rebuild when flagged, or when the data, time domain, or node colors change
+        let timeline = &self.graph.timeline;
+        let (min_time, max_time) = (timeline.min_time, timeline.max_time);
+        let key = (
+            self.graph.data.nodes.len(),
+            min_time.to_bits(),
+            max_time.to_bits(),
+            self.graph.color_mode,
+            self.graph.colorblind_safe,
+        );
+        if self.token_histogram.is_dirty() || self.token_histogram_key != Some(key) {
+            self.token_histogram.rebuild(&self.graph.data, min_time, max_time, &self.graph, &self.palette);
+            self.token_histogram_key = Some(key);
+        }
+
+        let (response, painter) = ui.allocate_painter(Vec2::new(ui.available_width(), 60.0), egui::Sense::click_and_drag());
+        let rect = response.rect;
+        painter.rect_filled(rect, 4.0, self.palette.bg.timeline_track);
+
+        let hist = &self.token_histogram;
+        if hist.bins.is_empty() || hist.max_value <= 0 || max_time <= min_time {
+            painter.text(
+                rect.center(),
+                egui::Align2::CENTER_CENTER,
+                "No token data",
+                egui::FontId::proportional(11.0),
+                self.palette.text.muted,
+            );
+            return;
+        }
+
+        // Same time -> x mapping as the scrubber track
+        let x_at = |t: f64| rect.left() + ((t - min_time) / (max_time - min_time)) as f32 * rect.width();
+        let time_at = |x: f32| min_time + ((x - rect.left()) / rect.width()) as f64 * (max_time - min_time);
+
+        // Brush: drag across bars to pick a window, Escape cancels
+        if response.drag_started() {
+            self.token_brush = response.interact_pointer_pos().map(|p| (time_at(p.x), time_at(p.x)));
+        }
+        if let (Some(brush), Some(p)) = (self.token_brush.as_mut(), response.interact_pointer_pos()) {
+            if response.dragged() {
+                brush.1 = time_at(p.x);
+            }
+        }
+        if self.token_brush.is_some() && ui.input(|i| shortcuts::pressed(i, Action::Cancel)) {
+            self.token_brush = None;
+        }
+        let brushed = self.token_brush.and_then(|(a, b)| hist.brush_range(a, b));
+        let commit_brush = response.drag_stopped() && self.token_brush.take().is_some();
+        let window_start = timeline.time_at_position(timeline.start_position);
+        let window_end = timeline.time_at_position(timeline.effective_position());
+        let track_height = rect.height() - 6.0;
+        let log_scale = self.settings.token_histogram_log_scale;
+        let hover_pos = response.hover_pos();
+
+        if log_scale {
+            for tick in hist.log_ticks() {
+                let y = rect.bottom() - 3.0 - hist.bar_fraction(tick, true) * track_height;
+                painter.line_segment(
+                    [Pos2::new(rect.left(), y), Pos2::new(rect.right(), y)],
+                    Stroke::new(0.5, self.palette.border.subtle),
+                );
+                let label = if tick >= 1_000_000 { format!("{}M", tick / 1_000_000) } else { format!("{}k", tick / 1_000) };
+                self.text_layout.text(
+                    &painter,
+                    Pos2::new(rect.left() + 2.0, y),
+                    egui::Align2::LEFT_BOTTOM,
+                    &label,
+                    egui::FontId::proportional(9.0),
+                    self.palette.text.muted,
+                );
+            }
+        }
+        let mut hovered = None;
+        let mut hovered_segment = None;
+
+        for (i, bin) in hist.bins.iter().enumerate() {
+            let x_start = x_at(bin.start_time);
+            let x_end = x_at(bin.end_time);
+            if hover_pos.is_some_and(|p| p.x >= x_start && p.x < x_end) {
+                hovered = Some(i);
+            }
+            if bin.total <= 0 {
+                continue;
+            }
+            let bar_width = (x_end - x_start - 1.0).max(1.0);
+            let in_window = bin.start_time >= window_start && bin.end_time <= window_end;
+            // Total height may be log-scaled; segments always split it linearly
+            let height = hist.bar_fraction(bin.total, log_scale) * track_height;
+            let mut y = rect.bottom() - 3.0;
+
+            // Segment under the cursor, by its Y extent within the stack
+            if hovered == Some(i) {
+                let mut segment_bottom = y;
+                for (s, segment) in bin.segments.iter().enumerate() {
+                    let segment_top = segment_bottom - segment.count as f32 / bin.total as f32 * height;
+                    if hover_pos.is_some_and(|p| p.y >= segment_top && p.y <= segment_bottom) {
+                        hovered_segment = Some(s);
+                    }
+                    segment_bottom = segment_top;
+                }
+            }
+
+            if in_window {
+                for segment in &bin.segments {
+                    let segment_height = segment.count as f32 / bin.total as f32 * height;
+                    let segment_rect = egui::Rect::from_min_max(
+                        Pos2::new(x_start, y - segment_height),
+                        Pos2::new(x_start + bar_width, y),
+                    );
+                    painter.rect_filled(segment_rect, 0.0, segment.color);
+                    y -= segment_height;
                 }
+                // Baseline marks bars inside the selected window
+                painter.line_segment(
+                    [Pos2::new(x_start, rect.bottom() - 2.0), Pos2::new(x_start + bar_width, rect.bottom() - 2.0)],
+                    Stroke::new(2.0, self.palette.timeline.bar_selected),
+                );
+            } else {
+                let bar_rect = egui::Rect::from_min_max(
+                    Pos2::new(x_start, y - height),
+                    Pos2::new(x_start + bar_width, y),
+                );
+                painter.rect_filled(bar_rect, 0.0, self.palette.timeline.bar_inactive);
             }
+        }
 
-            bins[i].total_tokens = sessions.iter().map(|s| s.total_tokens).sum();
-            bins[i].sessions = sessions;
+        if let Some((start, end)) = brushed {
+            let brush_rect = egui::Rect::from_min_max(
+                Pos2::new(x_at(start), rect.top()),
+                Pos2::new(x_at(end), rect.bottom()),
+            );
+            painter.rect_filled(brush_rect, 2.0, self.palette.accent.orange_subtle);
+            painter.rect_stroke(brush_rect, 2.0, Stroke::new(1.0, self.palette.timeline.bar_selected));
         }
 
-        bins
+        if let (true, Some((start, end))) = (commit_brush, brushed) {
+            // Same effect as dragging both scrubber handles
+            self.commands.push(AppCommand::SetTimelineWindow { start, end });
+            return;
+        }
+
+        if let Some(i) = hovered.filter(|_| self.token_brush.is_none()) {
+            let bin = &hist.bins[i];
+            let segment = hovered_segment.map(|s| &bin.segments[s]);
+            if let (true, Some(segment)) = (response.clicked(), segment) {
+                let (order, label) = (hist.stack_order, segment.label.clone());
+                self.token_histogram.toggle_segment_filter(order, &label);
+                return;
+            }
+            let segment_line = segment.map(|segment| {
+                let kind = match (hist.stack_order, hist.filter.single_token_type()) {
+                    (StackOrder::ByTokenType, _) => format!("{} tokens", segment.label),
+                    (_, Some(token_type)) => format!("{} tokens", token_type),
+                    _ => "tokens".to_string(),
+                };
+                let share = segment.count as f64 / bin.total.max(1) as f64 * 100.0;
+                let text = format!("{} — {} {} ({:.0}% of bin)", segment.label, format_count(segment.count), kind, share);
+                (text, segment.color)
+            });
+            let value = match hist.display_mode {
+                TokenDisplayMode::Absolute => format!("{} tokens", bin.total),
+                TokenDisplayMode::Percentage => {
+                    format!("{:.1}% of tokens", bin.total as f64 / hist.total_tokens.max(1) as f64 * 100.0)
+                }
+                TokenDisplayMode::Rate => {
+                    format!("{:.0} tokens/min", bin.total as f64 / (bin.duration() / 60.0).max(f64::EPSILON))
+                }
+            };
+            let range = format!("{} – {}", timeline.format_time(bin.start_time), timeline.format_time(bin.end_time));
+            let segments: Vec<(String, i64, Color32)> = bin
+                .segments
+                .iter()
+                .map(|s| (s.label.clone(), s.count, s.color))
+                .collect();
+            let text_scale = self.settings.tooltip_text_scale * self.palette.tooltip_text_scale;
+            response.on_hover_ui_at_pointer(|ui| {
+                theme::scale_text(ui.style_mut(), text_scale);
+                ui.label(range);
+                ui.label(egui::RichText::new(value).strong());
+                if let Some((text, color)) = segment_line {
+                    ui.label(egui::RichText::new(text).color(color).strong());
+                    ui.label(egui::RichText::new("Click to filter to this segment").small().weak());
+                    ui.separator();
+                }
+                for (label, count, color) in segments {
+                    ui.colored_label(color, format!("{}: {}", label, format_count(count)));
+                }
+            });
+        }
     }
 
     fn render_timeline(&mut self, ui: &mut egui::Ui) {
@@ -5292,6 +12484,19 @@ impl DashboardApp {
             ui.label("No timestamped nodes");
             return;
         }
+        if self.graph.timeline.is_degenerate() {
+            // Everything shares one timestamp: a disabled track with a single centered notch
+            let (rect, _) = ui.allocate_exact_size(Vec2::new(ui.available_width(), 40.0), egui::Sense::hover());
+            let painter = ui.painter();
+            painter.rect_filled(rect, 4.0, self.palette.bg.timeline_track.gamma_multiply(0.5));
+            painter.line_segment(
+                [Pos2::new(rect.center().x, rect.top() + 5.0), Pos2::new(rect.center().x, rect.bottom() - 5.0)],
+                Stroke::new(2.0, self.palette.timeline.notch),
+            );
+            let when = self.graph.timeline.format_time(self.graph.timeline.min_time);
+            ui.label(egui::RichText::new(format!("All nodes share one timestamp ({})", when)).color(self.palette.text.muted));
+            return;
+        }
 
         // Cache values we need before any closures
         let is_playing = self.graph.timeline.playing;
@@ -5308,7 +12513,20 @@ impl DashboardApp {
         let min_time = self.graph.timeline.min_time;
         let max_time = self.graph.timeline.max_time;
         let histogram_mode = self.timeline_histogram_mode;
+        let session_ends: Vec<f64> = if self.settings.session_end_markers {
+            self.graph.session_spans.values().map(|s| s.end).collect()
+        } else {
+            Vec::new()
+        };
         let bin_duration = bin_duration_for_hours(self.time_range_hours);
+        // One binning pass feeds both the histogram and the zoom minimap
+        let bin_counts = if histogram_mode || self.timeline_zoom.is_some() {
+            timeline_bin_counts(&timestamps, min_time, max_time, bin_duration)
+        } else {
+            Vec::new()
+        };
+        let (view_start, view_end) = self.timeline_zoom.unwrap_or((0.0, 1.0));
+        let view_span = (view_end - view_start).max(f32::EPSILON);
 
         // Helper to calculate position from time
         let position_at_time = |t: f64| -> f32 {
@@ -5368,6 +12586,31 @@ impl DashboardApp {
             if ui.button(view_label).on_hover_text(view_tooltip).clicked() {
                 self.timeline_histogram_mode = !self.timeline_histogram_mode;
             }
+
+            // Zoom the track into the selected range
+            let selection = end_pos - start_pos;
+            if ui.add_enabled(selection < view_span * 0.9, egui::Button::new("🔍"))
+                .on_hover_text("Zoom the track to the selected range")
+                .clicked()
+            {
+                let pad = selection * 0.05;
+                let zoom = ((start_pos - pad).max(0.0), (end_pos + pad).min(1.0));
+                self.timeline_zoom = Some(zoom);
+            }
+            if self.timeline_zoom.is_some() && ui.button("🔍 ✖").on_hover_text("Show the full range").clicked() {
+                self.timeline_zoom = None;
+            }
+
+            ui.separator();
+            let jump = ui.add(egui::TextEdit::singleline(&mut self.jump_input).hint_text("Jump to…").desired_width(110.0))
+                .on_hover_text("Move the scrubber: \"yesterday 3pm\", \"2025-06-14 09:30\", \"-3h\", \"-2d\"");
+            if jump.lost_focus() && ui.input(|i| i.key_pressed(egui::Key::Enter)) {
+                self.jump_to_time();
+            }
+            if let Some((ref message, is_error)) = self.jump_hint {
+                let color = if is_error { theme::state::ERROR } else { self.palette.text.muted };
+                ui.label(egui::RichText::new(message).small().color(color));
+            }
         });
 
         ui.add_space(4.0);
@@ -5388,35 +12631,29 @@ impl DashboardApp {
             egui::Sense::click_and_drag()
         );
         let rect = response.rect;
+        let painter = painter.with_clip_rect(rect);
+
+        // Track x for a scrubber position and back, through the zoomed window
+        let x_at = |pos: f32| rect.left() + (pos - view_start) / view_span * rect.width();
+        let pos_at = |x: f32| view_start + ((x - rect.left()) / rect.width()).clamp(0.0, 1.0) * view_span;
 
         // Draw track background
         painter.rect_filled(
             rect,
             4.0,
-            theme::bg::TIMELINE_TRACK
+            self.palette.bg.timeline_track
         );
 
         // Draw either notches or histogram based on mode
         if histogram_mode {
-            // Histogram mode: bin timestamps and draw bars
-            let time_span = max_time - min_time;
-            if time_span > 0.0 && bin_duration > 0.0 {
-                let num_bins = ((time_span / bin_duration).ceil() as usize).max(1);
-                let mut bin_counts: Vec<usize> = vec![0; num_bins];
-
-                // Count messages per bin
-                for &t in &timestamps {
-                    let bin_idx = ((t - min_time) / bin_duration) as usize;
-                    let bin_idx = bin_idx.min(num_bins - 1);
-                    bin_counts[bin_idx] += 1;
-                }
-
+            // Histogram mode: draw the binned counts as bars
+            if !bin_counts.is_empty() {
                 // Find max count for normalization
                 let max_count = bin_counts.iter().copied().max().unwrap_or(1).max(1);
 
                 // Draw histogram bars
-                let bar_color = theme::timeline::BAR_INACTIVE;
-                let bar_highlight = theme::timeline::BAR_HIGHLIGHT;
+                let bar_color = self.palette.timeline.bar_inactive;
+                let bar_highlight = self.palette.timeline.bar_highlight;
                 let track_height = rect.height() - 10.0; // Leave padding
 
                 for (i, &count) in bin_counts.iter().enumerate() {
@@ -5427,8 +12664,8 @@ impl DashboardApp {
                     let bin_start_time = min_time + (i as f64) * bin_duration;
                     let bin_end_time = (bin_start_time + bin_duration).min(max_time);
 
-                    let x_start = rect.left() + position_at_time(bin_start_time) * rect.width();
-                    let x_end = rect.left() + position_at_time(bin_end_time) * rect.width();
+                    let x_start = x_at(position_at_time(bin_start_time));
+                    let x_end = x_at(position_at_time(bin_end_time));
                     let bar_width = (x_end - x_start - 2.0).max(2.0); // Min 2px, 1px gap
 
                     let height_ratio = (count as f32) / (max_count as f32);
@@ -5449,10 +12686,13 @@ impl DashboardApp {
             }
         } else {
             // Notch mode: draw individual lines for each timestamp
-            let notch_color = theme::timeline::NOTCH;
+            let notch_color = self.palette.timeline.notch;
             for &t in &timestamps {
                 let pos = position_at_time(t);
-                let x = rect.left() + pos * rect.width();
+                if pos < view_start || pos > view_end {
+                    continue;
+                }
+                let x = x_at(pos);
                 painter.line_segment(
                     [Pos2::new(x, rect.top() + 5.0), Pos2::new(x, rect.bottom() - 5.0)],
                     Stroke::new(1.0, notch_color)
@@ -5460,9 +12700,41 @@ impl DashboardApp {
             }
         }
 
+        // Fading marker where "Jump to" landed
+        let motion = theme::motion_enabled();
+        let flash = self.jump_flash.and_then(|(pos, at)| {
+            let t = at.elapsed().as_secs_f32();
+            (t < PRESET_FLASH_SECS).then_some((pos, if motion { 1.0 - t / PRESET_FLASH_SECS } else { 1.0 }))
+        });
+        match flash {
+            Some((pos, strength)) => {
+                let x = x_at(pos);
+                painter.line_segment(
+                    [Pos2::new(x, rect.top()), Pos2::new(x, rect.bottom())],
+                    Stroke::new(3.0, self.palette.accent.orange.gamma_multiply(strength)),
+                );
+                ui.ctx().request_repaint();
+            }
+            None => self.jump_flash = None,
+        }
+
+        // Faint ticks where sessions ended, along the top edge
+        let end_tick_color = self.palette.text.muted.gamma_multiply(0.6);
+        for &end in &session_ends {
+            let pos = position_at_time(end);
+            if pos < view_start || pos > view_end {
+                continue;
+            }
+            let x = x_at(pos);
+            painter.line_segment(
+                [Pos2::new(x, rect.top() + 1.0), Pos2::new(x, rect.top() + 8.0)],
+                Stroke::new(1.0, end_tick_color)
+            );
+        }
+
         // Draw selected range
-        let start_x = rect.left() + start_pos * rect.width();
-        let end_x = rect.left() + end_pos * rect.width();
+        let start_x = x_at(start_pos);
+        let end_x = x_at(end_pos);
         let range_rect = egui::Rect::from_min_max(
             Pos2::new(start_x, rect.top() + 2.0),
             Pos2::new(end_x, rect.bottom() - 2.0)
@@ -5470,7 +12742,7 @@ impl DashboardApp {
         painter.rect_filled(
             range_rect,
             2.0,
-            theme::accent::orange_subtle()
+            self.palette.accent.orange_subtle
         );
 
         // Draw start handle
@@ -5479,19 +12751,35 @@ impl DashboardApp {
             Pos2::new(start_x, rect.center().y),
             Vec2::new(handle_width, rect.height() - 4.0)
         );
-        painter.rect_filled(start_handle_rect, 2.0, theme::timeline::HANDLE_START);
+        painter.rect_filled(start_handle_rect, 2.0, self.palette.timeline.handle_start);
 
         // Draw end/position handle (main scrubber)
         let end_handle_rect = egui::Rect::from_center_size(
             Pos2::new(end_x, rect.center().y),
             Vec2::new(handle_width, rect.height() - 4.0)
         );
-        painter.rect_filled(end_handle_rect, 2.0, theme::timeline::HANDLE_END);
+        painter.rect_filled(end_handle_rect, 2.0, self.palette.timeline.handle_end);
+
+        // Hovering the track (without pressing) previews that position
+        if let Some(preview) = self.graph.timeline.preview_position {
+            let x = x_at(preview);
+            painter.line_segment(
+                [Pos2::new(x, rect.top() + 2.0), Pos2::new(x, rect.bottom() - 2.0)],
+                Stroke::new(2.0, self.palette.timeline.handle_end.gamma_multiply(0.6)),
+            );
+        }
+        if self.hover_scrubs_timeline && !is_playing && !response.is_pointer_button_down_on() {
+            if let Some(pos) = response.hover_pos() {
+                let new_pos = pos_at(pos.x);
+                let snapped = self.graph.timeline.snap_to_notch(new_pos);
+                self.hover_scrub_target = Some(snapped.max(self.graph.timeline.start_position + 0.01));
+            }
+        }
 
         // Handle interaction
         if response.dragged() {
             if let Some(pos) = response.interact_pointer_pos() {
-                let new_pos = ((pos.x - rect.left()) / rect.width()).clamp(0.0, 1.0);
+                let new_pos = pos_at(pos.x);
 
                 // Determine which handle to move based on which is closer
                 let dist_to_start = (pos.x - start_x).abs();
@@ -5518,39 +12806,188 @@ impl DashboardApp {
         // Handle click to jump
         if response.clicked() {
             if let Some(pos) = response.interact_pointer_pos() {
-                let new_pos = ((pos.x - rect.left()) / rect.width()).clamp(0.0, 1.0);
+                let new_pos = pos_at(pos.x);
                 let snapped = self.graph.timeline.snap_to_notch(new_pos);
                 self.graph.timeline.position = snapped.max(self.graph.timeline.start_position + 0.01);
                 self.graph.update_visible_nodes();
                 self.effective_visible_dirty = true;
             }
         }
+
+        if self.timeline_zoom.is_some() {
+            ui.add_space(2.0);
+            self.render_timeline_minimap(ui, &bin_counts, min_time, max_time, bin_duration);
+        }
+    }
+
+    /// 12px strip under a zoomed scrubber: message density over the full range
+    /// and a bracket around the zoomed window. Dragging the bracket pans the
+    /// zoom; clicking recenters it.
+    fn render_timeline_minimap(&mut self, ui: &mut egui::Ui, bin_counts: &[usize], min_time: f64, max_time: f64, bin_duration: f64) {
+        let Some((zoom_start, zoom_end)) = self.timeline_zoom else {
+            return;
+        };
+        let (response, painter) = ui.allocate_painter(Vec2::new(ui.available_width(), 12.0), egui::Sense::click_and_drag());
+        let rect = response.rect;
+        let track = self.palette.bg.timeline_track;
+        painter.rect_filled(rect, 2.0, track);
+
+        let span = max_time - min_time;
+        let max_count = bin_counts.iter().copied().max().unwrap_or(0).max(1);
+        for (i, &count) in bin_counts.iter().enumerate() {
+            if count == 0 || span <= 0.0 {
+                continue;
+            }
+            let bin_start = (i as f64 * bin_duration / span) as f32;
+            let bin_end = (((i + 1) as f64 * bin_duration).min(span) / span) as f32;
+            let cell = egui::Rect::from_x_y_ranges(
+                rect.left() + bin_start * rect.width()..=rect.left() + bin_end * rect.width(),
+                rect.y_range(),
+            );
+            // Square root so sparse stretches still register
+            let heat = (count as f32 / max_count as f32).sqrt();
+            painter.rect_filled(cell, 0.0, crate::graph::types::lerp_color(track, self.palette.text.primary, heat * 0.8));
+        }
+
+        let bracket = egui::Rect::from_x_y_ranges(
+            rect.left() + zoom_start * rect.width()..=rect.left() + zoom_end * rect.width(),
+            rect.y_range(),
+        );
+        painter.rect_stroke(bracket, 2.0, Stroke::new(1.5, self.palette.timeline.handle_end));
+
+        let pointer_pos = |x: f32| ((x - rect.left()) / rect.width()).clamp(0.0, 1.0);
+        let center = (zoom_start + zoom_end) / 2.0;
+        if response.drag_started() {
+            // Grabbing the bracket keeps the offset; grabbing outside centers it on the pointer
+            self.timeline_minimap_grab = response.interact_pointer_pos()
+                .filter(|p| bracket.x_range().contains(p.x))
+                .map(|p| pointer_pos(p.x) - center);
+        }
+        if response.dragged() || response.clicked() {
+            if let Some(pos) = response.interact_pointer_pos() {
+                let grab = if response.dragged() { self.timeline_minimap_grab.unwrap_or(0.0) } else { 0.0 };
+                self.timeline_zoom = Some(pan_timeline_zoom((zoom_start, zoom_end), pointer_pos(pos.x) - grab));
+            }
+        }
+        if response.drag_stopped() {
+            self.timeline_minimap_grab = None;
+        }
+        response.on_hover_text("Full range. Drag the bracket to pan the zoomed track; click to jump.");
+    }
+}
+
+/// Current wall-clock time in unix seconds
+fn unix_now() -> f64 {
+    SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .unwrap_or_default()
+        .as_secs_f64()
+}
+
+/// Show a folder in the platform file manager
+fn open_folder(path: &std::path::Path) {
+    let program = if cfg!(target_os = "macos") {
+        "open"
+    } else if cfg!(target_os = "windows") {
+        "explorer"
+    } else {
+        "xdg-open"
+    };
+    if let Err(e) = std::process::Command::new(program).arg(path).spawn() {
+        tracing::warn!("Failed to open {:?}: {}", path, e);
+    }
+}
+
+/// Search for the beads directory from the working directory, honouring the settings override
+fn locate_beads_dir(override_path: Option<&str>) -> BeadsSearch {
+    let start = std::env::current_dir().unwrap_or_else(|_| std::path::PathBuf::from("."));
+    beads_dir::find_beads_dir(&start, override_path.map(std::path::Path::new))
+}
+
+impl DashboardApp {
+    /// Apply the commands queued while drawing this frame
+    fn handle_commands(&mut self) {
+        for command in self.commands.drain() {
+            command::dispatch(self, command);
+        }
+    }
+}
+
+impl CommandTarget for DashboardApp {
+    fn load_range(&mut self, hours: f32, session: Option<String>) {
+        let hours = hours.clamp(1.0, MAX_RANGE_HOURS);
+        if hours != self.time_range_hours {
+            self.hours_from_cli = false;
+        }
+        self.time_range_hours = hours;
+        self.slider_hours = hours;
+        self.session_filter = session;
+        self.schedule_reload();
+    }
+
+    fn reload(&mut self) {
+        if !self.db_connected {
+            self.reconnect_db();
+        }
+        if self.db_connected {
+            self.start_background_load();
+        }
+    }
+
+    fn set_timeline_window(&mut self, start: f64, end: f64) {
+        self.show_timeline_window(start, end);
+    }
+
+    fn select_node(&mut self, id: Option<String>) {
+        self.graph.selected_node = id;
+    }
+
+    fn apply_preset_by_name(&mut self, name: &str) {
+        self.apply_named_preset(name);
+    }
+
+    fn toggle_semantic_filter(&mut self, id: i32) {
+        let mode = self.semantic_filter_modes.get(&id).copied().unwrap_or(SemanticFilterMode::Off);
+        let next = if mode == SemanticFilterMode::Off { SemanticFilterMode::Include } else { SemanticFilterMode::Off };
+        self.semantic_filter_modes.insert(id, next);
+        self.request_semantic_filter_compute();
+        self.settings_dirty = true;
     }
 }
 
 impl eframe::App for DashboardApp {
     fn update(&mut self, ctx: &egui::Context, _frame: &mut eframe::Frame) {
+        self.profiler.begin_frame();
+        self.text_layout.begin_frame(ctx.pixels_per_point(), ctx.theme());
+        self.watchdog.frame_started();
         self.update_fps();
         self.maybe_save_settings();
 
-        // Handle keyboard shortcuts for panel toggles
-        // Only trigger when no text input is focused
-        ctx.input(|i| {
-            if !i.focused {
-                if i.key_pressed(egui::Key::B) {
-                    self.beads_panel_open = !self.beads_panel_open;
-                    self.mark_settings_dirty();
-                }
-                if i.key_pressed(egui::Key::M) {
-                    self.mail_panel_open = !self.mail_panel_open;
-                    self.mark_settings_dirty();
-                }
+        // Keyboard shortcuts from the registry. Only trigger when no text input is
+        // focused, so text edits keep their own keys (and undo).
+        if ctx.memory(|m| m.focused().is_none()) {
+            for action in ctx.input(shortcuts::triggered) {
+                self.handle_shortcut(action);
             }
-        });
+        }
 
-        // Check for .beads/ changes and auto-refresh if needed
-        if self.check_beads_changed() && !self.loading {
-            self.load_graph();
+        // Debounced reloads from control changes
+        self.poll_scheduled_reload(ctx);
+        self.poll_background_load();
+        self.poll_load_stats();
+        self.update_communities();
+        self.poll_find_similar();
+        self.poll_content_diff();
+
+        // Auto-refresh: merge finished refreshes, start one when due
+        self.poll_background_refresh();
+        self.poll_obsidian_scan();
+        self.maybe_auto_refresh(ctx);
+        self.handle_dropped_files(ctx);
+
+        // Check for .beads/ changes and refresh if needed
+        if self.check_beads_changed() && !self.loading && self.refresh_rx.is_none() {
+            self.start_background_refresh();
         }
 
         // Poll for semantic filter backend result
@@ -5563,7 +13000,7 @@ impl eframe::App for DashboardApp {
                     self.effective_visible_dirty = true;
                 }
                 Ok(Err(e)) => {
-                    eprintln!("Semantic filter compute failed: {}", e);
+                    tracing::warn!("Semantic filter compute failed: {}", e);
                     self.semantic_filter_pending = false;
                     self.semantic_filter_rx = None;
                 }
@@ -5580,6 +13017,7 @@ impl eframe::App for DashboardApp {
         // Rebuild effective visible set when any filter changed
         if self.effective_visible_dirty {
             self.rebuild_effective_visible_set();
+            self.recompute_bypass_edges();
             self.temporal_edges_dirty = true; // visible set changed → edges need rebuild
         }
 
@@ -5588,17 +13026,19 @@ impl eframe::App for DashboardApp {
         // edges will rebuild when playback stops or is paused.
         if self.temporal_edges_dirty && !self.graph.timeline.playing {
             if self.graph.temporal_attraction_enabled {
-                let vis = if self.any_filter_active() {
-                    Some(self.effective_visible_nodes.clone())
-                } else {
-                    None
-                };
-                self.graph.build_temporal_edges_filtered(vis.as_ref());
+                // Isolated sessions and soloed projects are already in the visible set
+                let vis = self.any_filter_active().then_some(&self.effective_visible_nodes);
+                // Scrubber releases often leave the visible set as it was
+                let settled = self.layout.is_settled(&self.graph, vis);
+                self.graph.rebuild_temporal_edges_if_changed(vis, settled);
             } else {
                 // Temporal disabled — just remove temporal edges
                 self.graph.data.edges.retain(|e| !e.is_temporal);
+                self.graph.temporal_build = None;
+                self.graph.edges_changed();
             }
             self.temporal_edges_dirty = false;
+            self.memory_estimate = MemoryEstimate::of_graph(&self.graph);
         }
 
         // Check for point-in-time summary result from background thread
@@ -5636,8 +13076,17 @@ impl eframe::App for DashboardApp {
                 Ok(Ok(data)) => {
                     // Cache for tooltip display (if we have a valid summary)
                     if data.exists {
-                        if let Some(ref session_id) = self.summary_session_id {
+                        if let Some(session_id) = self.summary_session_id.clone() {
                             self.session_summary_cache.insert(session_id.clone(), data.clone());
+                            // A freshly generated summary may bring new topics
+                            if self.settings.topic_nodes_enabled {
+                                if let Some(topics) = data.topics.clone().filter(|t| !t.is_empty()) {
+                                    if self.session_topics.get(&session_id) != Some(&topics) {
+                                        self.session_topics.insert(session_id, topics);
+                                        self.merge_derived_nodes();
+                                    }
+                                }
+                            }
                         }
                     }
                     self.session_summary_data = Some(data);
@@ -5715,7 +13164,7 @@ impl eframe::App for DashboardApp {
                     }
                 }
                 Ok(Err(e)) => {
-                    eprintln!("Categorization failed: {}", e);
+                    tracing::warn!("Categorization failed: {}", e);
                     if let Some(ref flag) = self.categorization_done_flag {
                         flag.store(true, std::sync::atomic::Ordering::Relaxed);
                     }
@@ -5759,11 +13208,11 @@ impl eframe::App for DashboardApp {
                         self.rescore_progress = None;
                         self.rescore_receiver = None;
                         // Reload graph to get updated importance scores
-                        self.load_graph();
+                        self.schedule_reload();
                         break;
                     }
                     Ok(RescoreEvent::Error(e)) => {
-                        eprintln!("Rescore failed: {}", e);
+                        tracing::warn!("Rescore failed: {}", e);
                         self.rescore_loading = false;
                         self.rescore_progress = None;
                         self.rescore_receiver = None;
@@ -5792,10 +13241,10 @@ impl eframe::App for DashboardApp {
                     self.ingest_loading = false;
                     self.ingest_receiver = None;
                     // Reload graph to show newly ingested data
-                    self.load_graph();
+                    self.schedule_reload();
                 }
                 Ok(Err(e)) => {
-                    eprintln!("Ingest failed: {}", e);
+                    tracing::warn!("Ingest failed: {}", e);
                     self.ingest_loading = false;
                     self.ingest_receiver = None;
                 }
@@ -5818,7 +13267,7 @@ impl eframe::App for DashboardApp {
             match rx.try_recv() {
                 Ok(Ok((edges, scores))) => {
                     let count = edges.len();
-                    eprintln!("Query '{}': loaded {} edges, {} scored nodes",
+                    tracing::info!("Query '{}': loaded {} edges, {} scored nodes",
                         self.proximity_queries[i].query, count, scores.len());
                     self.proximity_queries[i].edges = edges;
                     self.proximity_queries[i].scores = scores;
@@ -5828,7 +13277,7 @@ impl eframe::App for DashboardApp {
                     any_proximity_completed = true;
                 }
                 Ok(Err(e)) => {
-                    eprintln!("Proximity fetch failed for '{}': {}", self.proximity_queries[i].query, e);
+                    tracing::warn!("Proximity fetch failed for '{}': {}", self.proximity_queries[i].query, e);
                     self.proximity_queries[i].loading = false;
                 }
                 Err(std::sync::mpsc::TryRecvError::Empty) => {
@@ -5849,14 +13298,14 @@ impl eframe::App for DashboardApp {
         if let Some(ref rx) = self.embedding_gen_receiver {
             match rx.try_recv() {
                 Ok(Ok(result)) => {
-                    eprintln!("Generated {} embeddings", result.generated);
+                    tracing::info!("Generated {} embeddings", result.generated);
                     self.embedding_gen_loading = false;
                     self.embedding_gen_receiver = None;
                     // Refresh stats
                     self.load_embedding_stats();
                 }
                 Ok(Err(e)) => {
-                    eprintln!("Embedding generation failed: {}", e);
+                    tracing::warn!("Embedding generation failed: {}", e);
                     self.embedding_gen_loading = false;
                     self.embedding_gen_receiver = None;
                 }
@@ -5897,36 +13346,71 @@ impl eframe::App for DashboardApp {
             ctx.request_repaint();
         }
 
-        // Dark theme
-        ctx.set_visuals(egui::Visuals::dark());
+        // Theme (applies immediately when changed in the sidebar)
+        ctx.set_theme(self.settings.theme.preference());
+        theme::set_colorblind_safe(self.settings.colorblind_safe);
+        theme::set_high_contrast(self.settings.high_contrast);
+        theme::set_reduce_motion(self.settings.reduce_motion);
+        self.palette = theme::current(ctx);
+        let override_text = self.settings.high_contrast.then_some(self.palette.text.primary);
+        if ctx.style().visuals.override_text_color != override_text {
+            ctx.style_mut(|style| style.visuals.override_text_color = override_text);
+        }
+        self.settings.role_colors.apply(&mut self.palette.role);
+        self.graph.shading = self.palette.shading;
+        self.graph.is_light = ctx.theme() == egui::Theme::Light;
+        self.graph.colorblind_safe = self.settings.colorblind_safe;
+
+        // UI scale: apply slider changes, and adopt keyboard zoom (Ctrl +/-) into settings
+        if self.ui_scale_dirty {
+            ctx.set_zoom_factor(self.settings.ui_scale);
+            self.ui_scale_dirty = false;
+        } else if (ctx.zoom_factor() - self.settings.ui_scale).abs() > 0.001 {
+            self.settings.ui_scale = ctx.zoom_factor();
+            self.mark_settings_dirty();
+        }
 
         // Floating summary/neighborhood windows (rendered before panels so they float on top)
         self.render_summary_window(ctx);
         self.render_neighborhood_window(ctx);
+        self.render_shortcut_help(ctx);
+        self.render_onboarding(ctx);
+        self.render_note_window(ctx);
+        self.render_content_diff(ctx);
         self.render_edge_popups(ctx);
+        self.render_tooltip_config(ctx);
+        self.render_edge_styles(ctx);
+        self.render_hue_picker(ctx);
 
         // Sidebar
-        egui::SidePanel::left("sidebar")
+        let panels_started = Instant::now();
+        let sidebar = egui::SidePanel::left("sidebar")
             .min_width(220.0)
+            .default_width(self.sidebar_width)
             .show(ctx, |ui| {
+                theme::scale_text(ui.style_mut(), self.settings.sidebar_text_scale);
                 egui::ScrollArea::vertical().show(ui, |ui| {
                     self.render_sidebar(ui);
                 });
             });
+        if update_panel_size(&mut self.sidebar_width, sidebar.response.rect.width()) {
+            self.mark_settings_dirty();
+        }
+        self.profiler.record(Phase::Panels, panels_started);
 
         // Top panel for hovered node session ID and project
         if let Some(ref hovered_id) = self.graph.hovered_node {
             if let Some(node) = self.graph.data.nodes.iter().find(|n| &n.id == hovered_id) {
                 egui::TopBottomPanel::top("session_id_display")
                     .frame(egui::Frame::none()
-                        .fill(theme::bg::PANEL)
+                        .fill(self.palette.bg.panel)
                         .inner_margin(egui::Margin::symmetric(12.0, 8.0)))
                     .show(ctx, |ui| {
                         ui.vertical_centered(|ui| {
                             ui.label(
                                 egui::RichText::new(format!("Session: {} | Project: {}", node.session_id, node.project))
                                     .size(14.0)
-                                    .color(theme::text::SECONDARY)
+                                    .color(self.palette.text.secondary)
                             );
                         });
                     });
@@ -5935,67 +13419,369 @@ impl eframe::App for DashboardApp {
 
         // Bottom timeline panel (only when enabled)
         if self.timeline_enabled {
-            egui::TopBottomPanel::bottom("timeline")
+            let timeline_started = Instant::now();
+            let timeline = egui::TopBottomPanel::bottom("timeline")
                 .min_height(80.0)
+                .default_height(self.timeline_panel_height)
+                .resizable(true)
                 .frame(egui::Frame::none()
-                    .fill(theme::bg::PANEL)
+                    .fill(self.palette.bg.panel)
                     .inner_margin(egui::Margin::symmetric(12.0, 8.0)))
                 .show(ctx, |ui| {
                     self.render_timeline(ui);
                 });
+            if update_panel_size(&mut self.timeline_panel_height, timeline.response.rect.height()) {
+                self.mark_settings_dirty();
+            }
+
+            // Token usage bars, stacked above the timeline and sharing its time axis
+            egui::TopBottomPanel::bottom("token_usage")
+                .frame(egui::Frame::none()
+                    .fill(self.palette.bg.panel)
+                    .inner_margin(egui::Margin::symmetric(12.0, 6.0)))
+                .show(ctx, |ui| {
+                    self.render_token_usage_panel(ui);
+                });
+            self.profiler.record(Phase::Timeline, timeline_started);
         }
 
         // Beads panel (right side, toggled with B)
+        let panels_started = Instant::now();
         if self.beads_panel_open {
-            egui::SidePanel::right("beads_panel")
+            let panel = egui::SidePanel::right("beads_panel")
                 .min_width(280.0)
                 .max_width(400.0)
+                .default_width(self.beads_panel_width)
                 .frame(egui::Frame::none()
-                    .fill(theme::bg::PANEL)
+                    .fill(self.palette.bg.panel)
                     .inner_margin(egui::Margin::same(12.0)))
                 .show(ctx, |ui| {
                     self.render_beads_panel(ui);
                 });
+            if update_panel_size(&mut self.beads_panel_width, panel.response.rect.width()) {
+                self.mark_settings_dirty();
+            }
         }
 
         // Mail panel (right side, toggled with M)
         if self.mail_panel_open {
-            egui::SidePanel::right("mail_panel")
+            let panel = egui::SidePanel::right("mail_panel")
                 .min_width(280.0)
                 .max_width(400.0)
+                .default_width(self.mail_panel_width)
                 .frame(egui::Frame::none()
-                    .fill(theme::bg::PANEL)
+                    .fill(self.palette.bg.panel)
                     .inner_margin(egui::Margin::same(12.0)))
                 .show(ctx, |ui| {
                     self.render_mail_panel(ui);
                 });
+            if update_panel_size(&mut self.mail_panel_width, panel.response.rect.width()) {
+                self.mark_settings_dirty();
+            }
+        }
+
+        // Activity feed (right side, toggled with A)
+        if self.settings.activity_panel_open {
+            let panel = egui::SidePanel::right("activity_panel")
+                .min_width(280.0)
+                .max_width(480.0)
+                .default_width(self.settings.activity_panel_width)
+                .frame(egui::Frame::none()
+                    .fill(self.palette.bg.panel)
+                    .inner_margin(egui::Margin::same(12.0)))
+                .show(ctx, |ui| {
+                    self.render_activity_panel(ui);
+                });
+            if update_panel_size(&mut self.settings.activity_panel_width, panel.response.rect.width()) {
+                self.mark_settings_dirty();
+            }
         }
+        self.profiler.record(Phase::Panels, panels_started);
 
         // Main graph area
         egui::CentralPanel::default()
-            .frame(egui::Frame::none().fill(theme::bg::GRAPH))
+            .frame(egui::Frame::none().fill(self.palette.bg.graph))
             .show(ctx, |ui| {
                 if !self.db_connected || (!self.loading && self.graph.data.nodes.is_empty()) {
                     self.render_empty_state(ui);
+                } else if self.compare_sessions.is_some() {
+                    self.render_compare_view(ui);
+                } else if self.overview.is_some() {
+                    self.render_overview(ui);
                 } else if self.histogram_panel_enabled {
                     self.render_split_view(ui);
                 } else {
+                    let rect = ui.max_rect();
                     self.render_graph(ui);
+                    self.render_filter_pills(ui, rect);
                 }
             });
+
+        self.handle_commands();
+
+        // Apply (or end) the hover-scrub preview requested this frame
+        let scrub_target = self.hover_scrub_target.take();
+        if self.graph.set_timeline_preview(scrub_target) {
+            self.effective_visible_dirty = true;
+            ctx.request_repaint();
+        }
+
+        // Record settings changes committed this frame
+        self.commit_settings_history(ctx);
+
+        self.render_profiler_overlay(ctx);
+        self.profiler.end_frame();
+        self.watchdog.frame_finished(&self.profiler);
+    }
+
+    /// Called by eframe on its persistence interval and before shutdown
+    fn save(&mut self, _storage: &mut dyn eframe::Storage) {
+        self.flush_settings();
     }
 
     fn on_exit(&mut self, _gl: Option<&eframe::glow::Context>) {
-        // Force save settings on exit
-        if self.settings_dirty {
-            self.sync_settings_from_ui();
-            self.settings.save();
+        // Window close / quit: flush anything the last periodic save missed
+        self.flush_settings();
+    }
+}
+
+/// Layout algorithm used for each view mode
+fn layout_algorithm_for(mode: ViewMode) -> LayoutAlgorithm {
+    match mode {
+        ViewMode::ForceDirected => LayoutAlgorithm::ForceDirected,
+        ViewMode::Timeline => LayoutAlgorithm::Timeline,
+    }
+}
+
+/// Record a panel's current size, returning true if it changed
+fn update_panel_size(stored: &mut f32, actual: f32) -> bool {
+    if (actual - *stored).abs() > 0.5 {
+        *stored = actual;
+        true
+    } else {
+        false
+    }
+}
+
+/// Keep the preset rows the list points at (active, being renamed, awaiting
+/// delete confirmation) on the same presets after the list changes. `remap`
+/// gives a row's new index, or None when its preset is gone.
+fn remap_preset_rows(
+    selected: &mut Option<usize>,
+    renaming: &mut Option<(usize, String)>,
+    delete_confirm: &mut Option<usize>,
+    remap: impl Fn(usize) -> Option<usize>,
+) {
+    *selected = selected.and_then(&remap);
+    *renaming = renaming.take().and_then(|(row, buffer)| Some((remap(row)?, buffer)));
+    *delete_confirm = delete_confirm.and_then(&remap);
+}
+
+/// Index of preset row `row` once row `deleted` is removed; None for the deleted one
+fn preset_row_after_delete(row: usize, deleted: usize) -> Option<usize> {
+    match row.cmp(&deleted) {
+        std::cmp::Ordering::Less => Some(row),
+        std::cmp::Ordering::Equal => None,
+        std::cmp::Ordering::Greater => Some(row - 1),
+    }
+}
+
+/// Index of preset row `row` once rows `a` and `b` swap
+fn preset_row_after_swap(row: usize, a: usize, b: usize) -> usize {
+    if row == a {
+        b
+    } else if row == b {
+        a
+    } else {
+        row
+    }
+}
+
+/// Collapsible section with a small reset button in its header.
+/// Mirrors `CollapsingHeader`; `show` returns true when reset was clicked.
+struct ResettableSection {
+    title: &'static str,
+    default_open: bool,
+}
+
+impl ResettableSection {
+    fn new(title: &'static str) -> Self {
+        Self { title, default_open: false }
+    }
+
+    fn default_open(mut self, open: bool) -> Self {
+        self.default_open = open;
+        self
+    }
+
+    fn show(self, ui: &mut egui::Ui, add_body: impl FnOnce(&mut egui::Ui)) -> bool {
+        let id = ui.make_persistent_id(self.title);
+        let mut title_clicked = false;
+        let mut reset_clicked = false;
+        let mut header = egui::collapsing_header::CollapsingState::load_with_default_open(ui.ctx(), id, self.default_open)
+            .show_header(ui, |ui| {
+                title_clicked = ui.add(egui::Label::new(self.title).selectable(false).sense(egui::Sense::click())).clicked();
+                ui.with_layout(egui::Layout::right_to_left(egui::Align::Center), |ui| {
+                    reset_clicked = ui.add(egui::Button::new(egui::RichText::new("↺").small()).frame(false))
+                        .on_hover_text(format!("Reset {} to defaults", self.title))
+                        .clicked();
+                });
+            });
+        if title_clicked {
+            header.toggle();
         }
+        header.body(add_body);
+        reset_clicked
+    }
+}
+
+/// Choice from a "Set color…" menu
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum HueMenuAction {
+    Pick,
+    Clear,
+}
+
+/// "Set color…" (and "Clear color override" when pinned) menu entries
+fn hue_override_menu(ui: &mut egui::Ui, pinned: bool) -> Option<HueMenuAction> {
+    let mut action = None;
+    if ui.button("Set color…").clicked() {
+        action = Some(HueMenuAction::Pick);
+        ui.close_menu();
+    }
+    if pinned && ui.button("Clear color override").clicked() {
+        action = Some(HueMenuAction::Clear);
+        ui.close_menu();
+    }
+    action
+}
+
+/// Small "reset to defaults" button for settings popups
+fn reset_defaults_button(ui: &mut egui::Ui) -> bool {
+    ui.add_space(5.0);
+    ui.small_button("↺ Reset to defaults").clicked()
+}
+
+/// Distance from `p` to the segment `a`–`b`
+fn segment_distance(p: Pos2, a: Pos2, b: Pos2) -> f32 {
+    let ab = b - a;
+    let len_sq = ab.length_sq();
+    if len_sq <= f32::EPSILON {
+        return p.distance(a);
+    }
+    let t = ((p - a).dot(ab) / len_sq).clamp(0.0, 1.0);
+    p.distance(a + ab * t)
+}
+
+/// Whether the configured hover-scrub modifier is held
+fn hover_scrub_modifier_held(modifier: HoverScrubModifier, modifiers: egui::Modifiers) -> bool {
+    match modifier {
+        HoverScrubModifier::Alt => modifiers.alt,
+        HoverScrubModifier::Shift => modifiers.shift,
     }
 }
 
+/// Snapshot the tunable settings (plus hue offset and color maps) for undo history.
+/// The sidebar tab is normalized so switching tabs doesn't count as a change.
+fn settings_snapshot(settings: &Settings, graph: &GraphState) -> Preset {
+    let mut snapshot = Preset::from_settings(String::new(), settings, graph);
+    snapshot.sidebar_tab = SidebarTab::default();
+    snapshot
+}
+
 /// Build adjacency list from graph edges, optionally excluding temporal edges.
-fn build_adjacency_list(edges: &[crate::graph::types::GraphEdge], include_temporal: bool) -> HashMap<String, Vec<String>> {
+/// Compute bypass edges that bridge over inactive nodes.
+/// Walks session chains (non-temporal, non-similarity edges), connecting each
+/// visible node to its nearest visible successor across inactive nodes;
+/// the walk stops at filtered nodes.
+fn compute_bypass_edges(
+    nodes: &[crate::graph::types::GraphNode],
+    edges: &[GraphEdge],
+    inactive: &HashSet<String>,
+    filtered: &HashSet<String>,
+) -> Vec<GraphEdge> {
+    if inactive.is_empty() {
+        return Vec::new();
+    }
+
+    // Build per-node successor map from session edges (non-temporal only)
+    let mut next: HashMap<&str, &str> = HashMap::new();
+    for edge in edges {
+        if !edge.is_temporal && !edge.is_similarity && !edge.is_bypass {
+            next.insert(&edge.source, &edge.target);
+        }
+    }
+
+    let mut bypass = Vec::new();
+    let mut seen_pairs: HashSet<(String, String)> = HashSet::new();
+
+    for node in nodes {
+        if inactive.contains(&node.id) || filtered.contains(&node.id) {
+            continue; // Skip hidden nodes as sources
+        }
+
+        // Walk the chain from this node
+        let mut cursor = node.id.as_str();
+        while let Some(&successor) = next.get(cursor) {
+            if filtered.contains(successor) {
+                break; // Stop at truly removed nodes
+            }
+            if !inactive.contains(successor) {
+                // Successor is visible — if we skipped any nodes, create bypass edge
+                if cursor != node.id.as_str() {
+                    let pair = (node.id.clone(), successor.to_string());
+                    if seen_pairs.insert(pair) {
+                        bypass.push(GraphEdge::bypass(node.id.clone(), successor.to_string(), node.session_id.clone()));
+                    }
+                }
+                break;
+            }
+            cursor = successor;
+        }
+    }
+
+    bypass
+}
+
+/// Whether a node survives the backend's semantic filter set. Only stored
+/// messages are filtered; topics, notes and dropped transcripts always pass.
+fn passes_semantic_filter(visible_ids: &HashSet<i64>, node_id: &str) -> bool {
+    ids::message_id(node_id).is_none_or(|id| visible_ids.contains(&id))
+}
+
+/// Combine per-filter match sets the way the backend does: includes (expanded
+/// by +1/+2 hops) are unioned, or everything if there are none, then excludes
+/// are subtracted.
+fn combine_filter_matches(
+    modes: &[(i32, SemanticFilterMode)],
+    matches: &HashMap<i32, HashSet<String>>,
+    all_ids: &HashSet<String>,
+    adj: &HashMap<String, Vec<String>>,
+) -> HashSet<String> {
+    let empty = HashSet::new();
+    let matching = |id: &i32| matches.get(id).unwrap_or(&empty);
+    let mut include_union = HashSet::new();
+    let mut has_includes = false;
+    for (id, mode) in modes {
+        let depth = match mode {
+            SemanticFilterMode::Include => 0,
+            SemanticFilterMode::IncludePlus1 => 1,
+            SemanticFilterMode::IncludePlus2 => 2,
+            _ => continue,
+        };
+        has_includes = true;
+        include_union.extend(expand_to_neighbors(matching(id), depth, adj).into_iter().filter(|n| all_ids.contains(n)));
+    }
+    let mut visible = if has_includes { include_union } else { all_ids.clone() };
+    for (id, mode) in modes {
+        if *mode == SemanticFilterMode::Exclude {
+            visible.retain(|n| !matching(id).contains(n));
+        }
+    }
+    visible
+}
+
+fn build_adjacency_list<'a>(edges: impl IntoIterator<Item = &'a GraphEdge>, include_temporal: bool) -> HashMap<String, Vec<String>> {
     let mut adj: HashMap<String, Vec<String>> = HashMap::new();
     for edge in edges {
         if !include_temporal && edge.is_temporal {
@@ -6029,34 +13815,18 @@ fn expand_to_neighbors(seeds: &HashSet<String>, depth: usize, adj: &HashMap<Stri
     visited
 }
 
-fn truncate(s: &str, max_chars: usize) -> String {
-    if s.chars().count() > max_chars {
-        let truncated: String = s.chars().take(max_chars).collect();
-        format!("{}...", truncated)
-    } else {
-        s.to_string()
-    }
-}
-
 /// Truncate to a limited number of lines, each with a max character count
 fn truncate_lines(s: &str, max_lines: usize, max_chars_per_line: usize) -> String {
     let lines: Vec<&str> = s.lines().collect();
     let truncated_lines: Vec<String> = lines
         .iter()
         .take(max_lines)
-        .map(|line| {
-            if line.chars().count() > max_chars_per_line {
-                let truncated: String = line.chars().take(max_chars_per_line).collect();
-                format!("{}...", truncated)
-            } else {
-                line.to_string()
-            }
-        })
+        .map(|line| truncate(line, max_chars_per_line))
         .collect();
 
     let result = truncated_lines.join("\n");
     if lines.len() > max_lines {
-        format!("{}...", result)
+        format!("{}{}", result, text::ELLIPSIS)
     } else {
         result
     }
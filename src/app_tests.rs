@@ -2,18 +2,7 @@ use super::*;
 use crate::graph::types::GraphEdge;
 
 fn session_edge(source: &str, target: &str) -> GraphEdge {
-    GraphEdge {
-        source: source.into(),
-        target: target.into(),
-        session_id: "s1".into(),
-        timestamp: None,
-        is_obsidian: false,
-        is_topic: false,
-        is_similarity: false,
-        is_temporal: false,
-        similarity: None,
-        query_index: None,
-    }
+    GraphEdge::conversation(source.into(), target.into(), "s1".into(), None)
 }
 
 #[test]
@@ -102,3 +91,275 @@ fn expand_depth_with_temporal_toggle() {
     assert!(result.contains("B"));
     assert!(!result.contains("C"));
 }
+
+fn chain_node(id: &str) -> crate::graph::types::GraphNode {
+    crate::graph::types::GraphNode {
+        project: "proj".into(),
+        ..crate::graph::types::GraphNode::stub(id, "s1")
+    }
+}
+
+#[test]
+fn bypass_bridges_hidden_run() {
+    // A - B - C - D - E with B, C, D inactive
+    let nodes: Vec<_> = ["A", "B", "C", "D", "E"].iter().map(|id| chain_node(id)).collect();
+    let edges = vec![session_edge("A", "B"), session_edge("B", "C"), session_edge("C", "D"), session_edge("D", "E")];
+    let inactive: HashSet<String> = ["B".into(), "C".into(), "D".into()].into();
+
+    let bypass = compute_bypass_edges(&nodes, &edges, &inactive, &HashSet::new());
+    assert_eq!(bypass.len(), 1);
+    assert_eq!((bypass[0].source.as_str(), bypass[0].target.as_str()), ("A", "E"));
+    assert!(bypass[0].is_bypass);
+
+    // A filtered node in the run breaks the bridge
+    let filtered: HashSet<String> = ["C".into()].into();
+    assert!(compute_bypass_edges(&nodes, &edges, &inactive, &filtered).is_empty());
+
+    // Clearing the filter leaves no bypass edges
+    assert!(compute_bypass_edges(&nodes, &edges, &HashSet::new(), &HashSet::new()).is_empty());
+}
+
+#[test]
+fn combine_matches_unions_includes_and_subtracts_excludes() {
+    // 1 - 2 - 3 - 4
+    let adj = build_adjacency_list(&[session_edge("1", "2"), session_edge("2", "3"), session_edge("3", "4")], true);
+    let all: HashSet<String> = ["1", "2", "3", "4"].iter().map(|s| s.to_string()).collect();
+    let set = |ids: &[&str]| ids.iter().map(|s| s.to_string()).collect::<HashSet<String>>();
+    let matches = HashMap::from([(1, set(&["1"])), (2, set(&["4"])), (3, set(&["2"]))]);
+
+    let visible = combine_filter_matches(
+        &[(1, SemanticFilterMode::IncludePlus1), (2, SemanticFilterMode::Include), (3, SemanticFilterMode::Exclude)],
+        &matches,
+        &all,
+        &adj,
+    );
+    assert_eq!(visible, set(&["1", "4"]));
+
+    // Excludes alone start from everything
+    let visible = combine_filter_matches(&[(3, SemanticFilterMode::Exclude)], &matches, &all, &adj);
+    assert_eq!(visible, set(&["1", "3", "4"]));
+}
+
+#[test]
+fn semantic_filter_matches_mixed_ids() {
+    // Local matches come back as node ids; the visible set is row ids
+    let all: HashSet<String> = ["msg:1", "2", "topic:x"].iter().map(|s| s.to_string()).collect();
+    let matches = HashMap::from([(1, all.iter().filter(|id| id.as_str() != "2").cloned().collect::<HashSet<String>>())]);
+    let visible = combine_filter_matches(&[(1, SemanticFilterMode::Include)], &matches, &all, &HashMap::new());
+    let visible_ids: HashSet<i64> = visible.iter().filter_map(|id| ids::message_id(id)).collect();
+    assert_eq!(visible_ids, HashSet::from([1]));
+
+    assert!(passes_semantic_filter(&visible_ids, "msg:1"));
+    assert!(passes_semantic_filter(&visible_ids, "1"));
+    assert!(!passes_semantic_filter(&visible_ids, "msg:2"));
+    assert!(!passes_semantic_filter(&visible_ids, "2"));
+    // Synthetic nodes aren't stored messages, so the filter leaves them alone
+    assert!(passes_semantic_filter(&visible_ids, "topic:x"));
+    assert!(passes_semantic_filter(&visible_ids, &ids::note("a.md")));
+}
+
+#[test]
+fn timeline_bins_and_zoom_panning() {
+    // Three hours of data in hourly bins; the final timestamp lands in the last bin
+    let bins = timeline_bin_counts(&[0.0, 10.0, 3600.0, 7199.0, 10800.0], 0.0, 10800.0, 3600.0);
+    assert_eq!(bins, vec![2, 2, 1]);
+    assert!(timeline_bin_counts(&[5.0], 5.0, 5.0, 60.0).is_empty());
+
+    // Panning keeps the width and stays inside the track
+    assert_eq!(pan_timeline_zoom((0.2, 0.4), 0.5), (0.4, 0.6));
+    assert_eq!(pan_timeline_zoom((0.2, 0.4), 0.0), (0.0, 0.2));
+    let (start, end) = pan_timeline_zoom((0.2, 0.4), 1.0);
+    assert!((start - 0.8).abs() < 1e-6 && (end - 1.0).abs() < 1e-6);
+}
+
+#[test]
+fn segment_distance_clamps_to_endpoints() {
+    let (a, b) = (Pos2::new(0.0, 0.0), Pos2::new(10.0, 0.0));
+    assert_eq!(segment_distance(Pos2::new(5.0, 3.0), a, b), 3.0);
+    assert_eq!(segment_distance(Pos2::new(-4.0, 3.0), a, b), 5.0);
+    assert_eq!(segment_distance(Pos2::new(13.0, 4.0), a, b), 5.0);
+    assert_eq!(segment_distance(Pos2::new(3.0, 4.0), a, a), 5.0);
+}
+
+#[test]
+fn load_progress_text_groups_thousands() {
+    assert_eq!(load_progress_text(42_000, 180_000), "42,000 / ~180,000 messages");
+    assert_eq!(load_progress_text(0, 950), "0 / ~950 messages");
+}
+
+#[test]
+fn tag_filter_composes_with_timeline_window() {
+    let timed = |id: &str, session: &str, timestamp: &str| crate::graph::types::GraphNode {
+        session_id: session.into(),
+        timestamp: Some(timestamp.into()),
+        ..chain_node(id)
+    };
+    let nodes = vec![
+        timed("a0", "s1", "2025-06-01T00:00:00Z"),
+        timed("b1", "s2", "2025-06-01T01:00:00Z"),
+        timed("a2", "s1", "2025-06-01T02:00:00Z"),
+        timed("b3", "s2", "2025-06-01T03:00:00Z"),
+    ];
+    let mut graph = GraphState::new();
+    graph.temporal_attraction_enabled = false;
+    graph.load(GraphData::new(nodes, vec![]), egui::Rect::from_min_size(Pos2::ZERO, Vec2::splat(100.0)));
+    // Window over the middle two hours
+    graph.timeline.start_position = 0.3;
+    graph.timeline.position = 0.7;
+    graph.update_visible_items();
+
+    let tags = SessionTags::from_rows([("s2".to_string(), "billing-bug".to_string())]);
+    let mut filter = TagFilter::default();
+    let visible = |filter: &TagFilter| -> Vec<String> {
+        graph.data.nodes.iter()
+            .filter(|n| graph.timeline.visible_nodes.contains(&n.id) && filter.admits(&tags, &n.session_id))
+            .map(|n| n.id.clone())
+            .collect()
+    };
+    assert_eq!(visible(&filter), vec!["b1", "a2"]);
+    filter.toggle("billing-bug");
+    assert_eq!(visible(&filter), vec!["b1"]);
+}
+
+#[test]
+fn deleting_a_preset_while_renaming_keeps_the_rename_on_its_row() {
+    let (mut selected, mut renaming, mut confirm) = (Some(3), Some((2, "Draft name".to_string())), Some(0));
+    remap_preset_rows(&mut selected, &mut renaming, &mut confirm, |row| preset_row_after_delete(row, 0));
+    assert_eq!(selected, Some(2));
+    assert_eq!(renaming, Some((1, "Draft name".to_string())));
+    assert_eq!(confirm, None);
+
+    // Deleting the row being renamed ends the rename
+    remap_preset_rows(&mut selected, &mut renaming, &mut confirm, |row| preset_row_after_delete(row, 1));
+    assert_eq!((selected, renaming), (Some(1), None));
+}
+
+#[test]
+fn swapping_presets_moves_every_row_pointer() {
+    let (mut selected, mut renaming, mut confirm) = (Some(0), Some((1, "x".to_string())), Some(2));
+    remap_preset_rows(&mut selected, &mut renaming, &mut confirm, |row| Some(preset_row_after_swap(row, 1, 2)));
+    assert_eq!((selected, renaming, confirm), (Some(0), Some((2, "x".to_string())), Some(1)));
+}
+
+/// `nodes` messages over a week in sessions of 20, each session a
+/// conversation chain, plus random links inside sessions up to `edges`
+fn synthetic_graph(nodes: usize, edges: usize) -> GraphData {
+    use rand::rngs::StdRng;
+    use rand::{Rng, SeedableRng};
+
+    const PER_SESSION: usize = 20;
+    let mut rng = StdRng::seed_from_u64(11);
+    let start = 1_750_000_000;
+    let step = 7 * 24 * 3600 / nodes.max(1) as i64;
+    let session = |i: usize| format!("session-{}", i / PER_SESSION);
+    let id = |i: usize| ids::message(i as i64 + 1);
+    let node_list = (0..nodes)
+        .map(|i| crate::graph::types::GraphNode {
+            role: if i % 2 == 0 { Role::User } else { Role::Assistant },
+            content_preview: format!("message {}", i),
+            project: format!("~/proj{}", i / PER_SESSION % 12),
+            timestamp: chrono::DateTime::from_timestamp(start + i as i64 * step, 0).map(|t| t.to_rfc3339()),
+            importance_score: Some(rng.gen_range(0.0..1.0)),
+            output_tokens: Some(rng.gen_range(0..2000)),
+            input_tokens: Some(rng.gen_range(0..500)),
+            has_tool_usage: i % 3 == 0,
+            ..crate::graph::types::GraphNode::stub(&id(i), &session(i))
+        })
+        .collect();
+    let mut edge_list: Vec<GraphEdge> = (1..nodes)
+        .filter(|i| i % PER_SESSION != 0)
+        .map(|i| GraphEdge::conversation(id(i - 1), id(i), session(i), None))
+        .collect();
+    while edge_list.len() < edges {
+        let a = rng.gen_range(0..nodes);
+        let b = (a / PER_SESSION * PER_SESSION + rng.gen_range(0..PER_SESSION)).min(nodes - 1);
+        if a != b {
+            edge_list.push(GraphEdge::conversation(id(a), id(b), session(a), None));
+        }
+    }
+    GraphData::new(node_list, edge_list)
+}
+
+/// Frame times for `render_graph` and tessellation on a settled synthetic
+/// graph. BENCH_NODES and BENCH_EDGES size it (10k nodes, 50k edges by
+/// default); BENCH_NO_TEMPORAL leaves temporal edges off so the node pass
+/// dominates. Run with `cargo test --release bench_graph_frames -- --ignored --nocapture`.
+#[test]
+#[ignore]
+fn bench_graph_frames() {
+    use std::time::{Duration, Instant};
+
+    let env = |key: &str, default: usize| std::env::var(key).ok().and_then(|v| v.parse().ok()).unwrap_or(default);
+    let (nodes, edges) = (env("BENCH_NODES", 10_000), env("BENCH_EDGES", 50_000));
+    let ctx = egui::Context::default();
+    let mut app = DashboardApp::with_context(&ctx, CliArgs::default(), None);
+    if std::env::var("BENCH_NO_TEMPORAL").is_ok() {
+        app.graph.temporal_attraction_enabled = false;
+    }
+    app.apply_graph_data(synthetic_graph(nodes, edges), false);
+
+    let screen = egui::Rect::from_min_size(Pos2::ZERO, Vec2::new(1600.0, 1000.0));
+    let (mut ui_times, mut tessellate_times) = (Vec::new(), Vec::new());
+    for frame in 0..160 {
+        // Freeze the layout so every measured frame draws the same scene
+        if frame == 10 {
+            app.graph.physics_enabled = false;
+        }
+        let input = egui::RawInput { screen_rect: Some(screen), time: Some(frame as f64 / 60.0), ..Default::default() };
+        let started = Instant::now();
+        let output = ctx.run(input, |ctx| {
+            egui::CentralPanel::default().show(ctx, |ui| app.render_graph(ui));
+        });
+        let ui_time = started.elapsed();
+        let started = Instant::now();
+        std::hint::black_box(ctx.tessellate(output.shapes, output.pixels_per_point));
+        if frame >= 60 {
+            ui_times.push(ui_time);
+            tessellate_times.push(started.elapsed());
+        }
+    }
+
+    let median = |mut times: Vec<Duration>| {
+        times.sort();
+        times[times.len() / 2].as_secs_f64() * 1000.0
+    };
+    let totals = ui_times.iter().zip(&tessellate_times).map(|(a, b)| *a + *b).collect();
+    println!(
+        "{} nodes, {} edges: render_graph {:.1} ms, tessellate {:.1} ms, total {:.1} ms (medians)",
+        nodes,
+        app.graph.data.edges.len(),
+        median(ui_times),
+        median(tessellate_times),
+        median(totals),
+    );
+}
+
+/// Draws one headless frame and returns the key the edge pass ended up with
+fn edge_frame_key(ctx: &egui::Context, app: &mut DashboardApp) -> Option<u64> {
+    let screen = egui::Rect::from_min_size(Pos2::ZERO, Vec2::new(800.0, 600.0));
+    let input = egui::RawInput { screen_rect: Some(screen), ..Default::default() };
+    let _ = ctx.run(input, |ctx| {
+        egui::CentralPanel::default().show(ctx, |ui| app.render_graph(ui));
+    });
+    app.edge_frame.as_ref().and_then(|frame| frame.key)
+}
+
+#[test]
+fn edge_geometry_is_reused_until_the_scene_changes() {
+    let ctx = egui::Context::default();
+    let mut app = DashboardApp::with_context(&ctx, CliArgs::default(), None);
+    app.apply_graph_data(synthetic_graph(60, 90), false);
+    app.graph.physics_enabled = false;
+
+    let settled = edge_frame_key(&ctx, &mut app);
+    assert!(settled.is_some());
+    assert_eq!(edge_frame_key(&ctx, &mut app), settled);
+
+    app.pan_offset += Vec2::new(12.0, 0.0);
+    let panned = edge_frame_key(&ctx, &mut app);
+    assert_ne!(panned, settled);
+
+    let id = app.graph.data.nodes[0].id.clone();
+    app.graph.positions.insert(id, Pos2::new(-500.0, -500.0));
+    assert_ne!(edge_frame_key(&ctx, &mut app), panned);
+}
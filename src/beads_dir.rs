@@ -0,0 +1,134 @@
+//! Locating the `.beads/` issue directory.
+//!
+//! The app is often launched from a launcher with cwd `/`, so the search walks
+//! upward from the start directory and honours an explicit override first.
+
+use std::path::{Path, PathBuf};
+
+/// Name of the beads directory inside a project
+pub const BEADS_DIR_NAME: &str = ".beads";
+
+/// File inside `.beads/` pointing at the real beads directory (e.g. from a worktree)
+const REDIRECT_FILE: &str = "redirect";
+
+/// Redirects followed before giving up (guards against cycles)
+const MAX_REDIRECTS: usize = 8;
+
+/// Outcome of a search: the directory found, plus every location that was checked
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct BeadsSearch {
+    pub resolved: Option<PathBuf>,
+    pub searched: Vec<PathBuf>,
+}
+
+/// Find the beads directory. `override_path` may name either a `.beads`
+/// directory or a project directory containing one; it wins when valid,
+/// otherwise the walk from `start` to the filesystem root is used.
+pub fn find_beads_dir(start: &Path, override_path: Option<&Path>) -> BeadsSearch {
+    let mut search = BeadsSearch::default();
+
+    if let Some(path) = override_path {
+        let candidate = if path.file_name().is_some_and(|name| name == BEADS_DIR_NAME) {
+            path.to_path_buf()
+        } else {
+            path.join(BEADS_DIR_NAME)
+        };
+        search.searched.push(candidate.clone());
+        if let Some(found) = follow_redirects(&candidate) {
+            search.resolved = Some(found);
+            return search;
+        }
+    }
+
+    for dir in start.ancestors() {
+        let candidate = dir.join(BEADS_DIR_NAME);
+        search.searched.push(candidate.clone());
+        if let Some(found) = follow_redirects(&candidate) {
+            search.resolved = Some(found);
+            break;
+        }
+    }
+    search
+}
+
+/// Resolve a candidate `.beads` directory through its redirect file, if any.
+/// Relative redirect targets are resolved against the project directory
+/// (the parent of the `.beads` directory holding the redirect).
+fn follow_redirects(candidate: &Path) -> Option<PathBuf> {
+    let mut current = candidate.to_path_buf();
+    for _ in 0..=MAX_REDIRECTS {
+        if !current.is_dir() {
+            return None;
+        }
+        let target = match std::fs::read_to_string(current.join(REDIRECT_FILE)) {
+            Ok(contents) => contents.trim().to_string(),
+            Err(_) => return Some(current),
+        };
+        if target.is_empty() {
+            return Some(current);
+        }
+        let base = current.parent().unwrap_or(&current).to_path_buf();
+        current = base.join(target);
+    }
+    tracing::warn!("Too many beads redirects starting at {}", candidate.display());
+    None
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Fresh, empty scratch directory for one test
+    fn scratch(name: &str) -> PathBuf {
+        let dir = std::env::temp_dir().join(format!("beads-dir-{}-{}", name, std::process::id()));
+        let _ = std::fs::remove_dir_all(&dir);
+        std::fs::create_dir_all(&dir).unwrap();
+        dir
+    }
+
+    #[test]
+    fn test_walks_upward_to_nearest_beads_dir() {
+        let root = scratch("upward");
+        let nested = root.join("project/src/deep");
+        std::fs::create_dir_all(&nested).unwrap();
+        std::fs::create_dir_all(root.join("project/.beads")).unwrap();
+
+        let search = find_beads_dir(&nested, None);
+        assert_eq!(search.resolved, Some(root.join("project/.beads")));
+        assert_eq!(search.searched.first(), Some(&nested.join(BEADS_DIR_NAME)));
+        assert_eq!(search.searched.len(), 3);
+
+        let _ = std::fs::remove_dir_all(&root);
+    }
+
+    #[test]
+    fn test_follows_redirect_file() {
+        let root = scratch("redirect");
+        std::fs::create_dir_all(root.join("main/.beads")).unwrap();
+        std::fs::create_dir_all(root.join("worktree/.beads")).unwrap();
+        std::fs::write(root.join("worktree/.beads/redirect"), "../main/.beads\n").unwrap();
+
+        let search = find_beads_dir(&root.join("worktree"), None);
+        assert_eq!(search.resolved, Some(root.join("worktree/../main/.beads")));
+
+        let _ = std::fs::remove_dir_all(&root);
+    }
+
+    #[test]
+    fn test_override_takes_precedence() {
+        let root = scratch("override");
+        std::fs::create_dir_all(root.join("here/.beads")).unwrap();
+        std::fs::create_dir_all(root.join("elsewhere/.beads")).unwrap();
+
+        let search = find_beads_dir(&root.join("here"), Some(&root.join("elsewhere")));
+        assert_eq!(search.resolved, Some(root.join("elsewhere/.beads")));
+        assert_eq!(search.searched.len(), 1);
+
+        // An override that doesn't exist falls back to the upward walk
+        let search = find_beads_dir(&root.join("here"), Some(&root.join("missing/.beads")));
+        assert_eq!(search.resolved, Some(root.join("here/.beads")));
+        assert_eq!(search.searched[0], root.join("missing/.beads"));
+
+        let _ = std::fs::remove_dir_all(&root);
+    }
+}
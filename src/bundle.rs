@@ -0,0 +1,420 @@
+//! Debug bundles for bug reports: the loaded graph, settings, versions and
+//! recent stalls and log events, written as a directory of JSON files.
+//! `--load-bundle` reads one back in place of the database so a layout or
+//! timeline problem can be reproduced on another machine.
+//!
+//! The bundle structs are their own schema rather than the graph types, so
+//! internal refactors don't break bundles already attached to issues. Add
+//! fields with `#[serde(default)]`; bump `BUNDLE_FORMAT` only for changes old
+//! readers can't ignore.
+
+use crate::graph::ids;
+use crate::graph::types::{GraphData, GraphEdge, GraphNode, Role};
+use crate::graph::GraphState;
+use crate::settings::Settings;
+use egui::Pos2;
+use serde::de::DeserializeOwned;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::path::Path;
+
+/// Bundle layout version; readers reject bundles newer than this
+pub const BUNDLE_FORMAT: u32 = 1;
+
+const MANIFEST_FILE: &str = "manifest.json";
+const GRAPH_FILE: &str = "graph.json";
+const SETTINGS_FILE: &str = "settings.json";
+const EVENTS_FILE: &str = "events.json";
+
+/// Where and what produced the bundle
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Manifest {
+    pub format: u32,
+    pub app_version: String,
+    pub os: String,
+    pub arch: String,
+    /// RFC 3339, local time
+    pub created_at: String,
+    /// Message text left out; nodes carry only its length
+    pub anonymized: bool,
+    #[serde(default)]
+    pub active_preset: Option<String>,
+}
+
+impl Manifest {
+    pub fn new(anonymized: bool, active_preset: Option<String>) -> Self {
+        Self {
+            format: BUNDLE_FORMAT,
+            app_version: env!("CARGO_PKG_VERSION").to_string(),
+            os: std::env::consts::OS.to_string(),
+            arch: std::env::consts::ARCH.to_string(),
+            created_at: chrono::Local::now().to_rfc3339(),
+            anonymized,
+            active_preset,
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct BundleNode {
+    pub id: String,
+    pub role: Role,
+    pub session_id: String,
+    #[serde(default)]
+    pub session_short: String,
+    #[serde(default)]
+    pub project: String,
+    #[serde(default)]
+    pub timestamp: Option<String>,
+    /// Preview text; empty when anonymized
+    #[serde(default)]
+    pub preview: String,
+    #[serde(default)]
+    pub full_content: Option<String>,
+    /// Length of the message text in characters, kept when anonymized
+    #[serde(default)]
+    pub content_chars: usize,
+    #[serde(default)]
+    pub importance_score: Option<f32>,
+    #[serde(default)]
+    pub output_tokens: Option<i32>,
+    #[serde(default)]
+    pub input_tokens: Option<i32>,
+    #[serde(default)]
+    pub cache_read_tokens: Option<i32>,
+    #[serde(default)]
+    pub cache_creation_tokens: Option<i32>,
+    #[serde(default)]
+    pub has_tool_usage: bool,
+    /// Layout position when the bundle was written
+    #[serde(default)]
+    pub pos: Option<[f32; 2]>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum BundleEdgeKind {
+    Conversation,
+    Similarity,
+    Topic,
+    Obsidian,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct BundleEdge {
+    pub source: String,
+    pub target: String,
+    pub kind: BundleEdgeKind,
+    #[serde(default)]
+    pub session_id: String,
+    #[serde(default)]
+    pub timestamp: Option<String>,
+    /// Similarity score
+    #[serde(default)]
+    pub strength: Option<f32>,
+    #[serde(default)]
+    pub query_index: Option<usize>,
+    /// Messages left out between the two ends of a gap edge
+    #[serde(default)]
+    pub gap: Option<u32>,
+}
+
+/// Loaded nodes and edges. Temporal edges are left out: they are rebuilt from
+/// the settings on load.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct BundleGraph {
+    pub nodes: Vec<BundleNode>,
+    pub edges: Vec<BundleEdge>,
+}
+
+impl BundleGraph {
+    pub fn from_state(graph: &GraphState, anonymize: bool) -> Self {
+        let nodes = graph.data.nodes.iter()
+            .map(|node| {
+                let text = node.full_content.as_deref().unwrap_or(&node.content_preview);
+                BundleNode {
+                    id: node.id.clone(),
+                    role: node.role.clone(),
+                    session_id: node.session_id.clone(),
+                    session_short: node.session_short.clone(),
+                    project: node.project.clone(),
+                    timestamp: node.timestamp.clone(),
+                    preview: if anonymize { String::new() } else { node.content_preview.clone() },
+                    full_content: if anonymize { None } else { node.full_content.clone() },
+                    content_chars: text.chars().count(),
+                    importance_score: node.importance_score,
+                    output_tokens: node.output_tokens,
+                    input_tokens: node.input_tokens,
+                    cache_read_tokens: node.cache_read_tokens,
+                    cache_creation_tokens: node.cache_creation_tokens,
+                    has_tool_usage: node.has_tool_usage,
+                    pos: graph.get_pos(&node.id).map(|p| [p.x, p.y]),
+                }
+            })
+            .collect();
+        let edges = graph.data.edges.iter()
+            .filter(|edge| !edge.is_temporal && !edge.is_bypass)
+            .map(|edge| BundleEdge {
+                source: edge.source.clone(),
+                target: edge.target.clone(),
+                kind: if edge.is_similarity {
+                    BundleEdgeKind::Similarity
+                } else if edge.is_topic {
+                    BundleEdgeKind::Topic
+                } else if edge.is_obsidian {
+                    BundleEdgeKind::Obsidian
+                } else {
+                    BundleEdgeKind::Conversation
+                },
+                session_id: edge.session_id.clone(),
+                timestamp: edge.timestamp.clone(),
+                strength: edge.is_similarity.then_some(edge.similarity).flatten(),
+                query_index: edge.query_index,
+                gap: edge.gap,
+            })
+            .collect();
+        Self { nodes, edges }
+    }
+
+    /// Graph data to install, and the saved layout positions by node id
+    pub fn into_graph_data(self) -> (GraphData, HashMap<String, Pos2>) {
+        let mut positions = HashMap::new();
+        let nodes = self.nodes.into_iter()
+            .map(|node| {
+                if let Some([x, y]) = node.pos {
+                    positions.insert(ids::normalize(node.id.clone()), Pos2::new(x, y));
+                }
+                let content_preview = if node.preview.is_empty() && node.content_chars > 0 {
+                    format!("[{} chars]", node.content_chars)
+                } else {
+                    node.preview
+                };
+                GraphNode {
+                    id: node.id,
+                    role: node.role,
+                    content_preview,
+                    full_content: node.full_content,
+                    session_id: node.session_id,
+                    session_short: node.session_short,
+                    project: node.project,
+                    timestamp: node.timestamp,
+                    importance_score: node.importance_score,
+                    importance_reason: None,
+                    output_tokens: node.output_tokens,
+                    input_tokens: node.input_tokens,
+                    cache_read_tokens: node.cache_read_tokens,
+                    cache_creation_tokens: node.cache_creation_tokens,
+                    has_tool_usage: node.has_tool_usage,
+                }
+            })
+            .collect();
+        let edges = self.edges.into_iter()
+            .map(|edge| match (edge.kind, edge.gap) {
+                (BundleEdgeKind::Similarity, _) => {
+                    GraphEdge::similarity(edge.source, edge.target, edge.strength.unwrap_or(1.0), edge.query_index)
+                }
+                (BundleEdgeKind::Topic, _) => GraphEdge::topic(edge.source, edge.target, edge.session_id),
+                (BundleEdgeKind::Obsidian, _) => GraphEdge::obsidian(edge.source, edge.target),
+                (BundleEdgeKind::Conversation, Some(missing)) => {
+                    GraphEdge::gap(edge.source, edge.target, edge.session_id, missing)
+                }
+                (BundleEdgeKind::Conversation, None) => {
+                    GraphEdge::conversation(edge.source, edge.target, edge.session_id, edge.timestamp)
+                }
+            })
+            .collect();
+        // Bundles written before ids were namespaced hold bare message ids
+        let mut data = GraphData::new(nodes, edges);
+        ids::normalize_graph(&mut data);
+        (data, positions)
+    }
+}
+
+/// Recent UI stalls and warnings, as shown in the sidebar
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct BundleEvents {
+    #[serde(default)]
+    pub stalls: Vec<String>,
+    #[serde(default)]
+    pub log: Vec<String>,
+}
+
+#[derive(Debug, Clone)]
+pub struct DebugBundle {
+    pub manifest: Manifest,
+    pub graph: BundleGraph,
+    pub settings: Settings,
+    pub events: BundleEvents,
+}
+
+impl DebugBundle {
+    /// Write into `dir`, creating it if needed
+    pub fn write(&self, dir: &Path) -> Result<(), String> {
+        std::fs::create_dir_all(dir).map_err(|e| format!("Failed to create {}: {}", dir.display(), e))?;
+        write_json(&dir.join(MANIFEST_FILE), &self.manifest)?;
+        write_json(&dir.join(GRAPH_FILE), &self.graph)?;
+        write_json(&dir.join(SETTINGS_FILE), &self.settings)?;
+        write_json(&dir.join(EVENTS_FILE), &self.events)
+    }
+
+    pub fn read(dir: &Path) -> Result<Self, String> {
+        let manifest: Manifest = read_json(&dir.join(MANIFEST_FILE))?;
+        if manifest.format > BUNDLE_FORMAT {
+            return Err(format!(
+                "{} is bundle format {}, newer than this build reads ({})",
+                dir.display(),
+                manifest.format,
+                BUNDLE_FORMAT
+            ));
+        }
+        Ok(Self {
+            manifest,
+            graph: read_json(&dir.join(GRAPH_FILE))?,
+            settings: read_json(&dir.join(SETTINGS_FILE))?,
+            // Events are for reading, not for reproducing; a bundle without them still loads
+            events: read_json(&dir.join(EVENTS_FILE)).unwrap_or_default(),
+        })
+    }
+}
+
+fn write_json<T: Serialize>(path: &Path, value: &T) -> Result<(), String> {
+    let json = serde_json::to_string_pretty(value).map_err(|e| format!("Failed to serialize {}: {}", path.display(), e))?;
+    std::fs::write(path, json).map_err(|e| format!("Failed to write {}: {}", path.display(), e))
+}
+
+fn read_json<T: DeserializeOwned>(path: &Path) -> Result<T, String> {
+    let json = std::fs::read_to_string(path).map_err(|e| format!("Failed to read {}: {}", path.display(), e))?;
+    serde_json::from_str(&json).map_err(|e| format!("Failed to parse {}: {}", path.display(), e))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn node(id: &str, role: Role, content: &str) -> GraphNode {
+        GraphNode {
+            role,
+            content_preview: content.to_string(),
+            full_content: Some(content.to_string()),
+            project: "proj".to_string(),
+            timestamp: Some("2025-06-15T12:00:00+00:00".to_string()),
+            importance_score: Some(0.7),
+            output_tokens: Some(120),
+            input_tokens: Some(40),
+            cache_creation_tokens: Some(3),
+            has_tool_usage: true,
+            ..GraphNode::stub(id, "s1")
+        }
+    }
+
+    fn bounds() -> egui::Rect {
+        egui::Rect::from_center_size(Pos2::new(400.0, 300.0), egui::Vec2::new(600.0, 400.0))
+    }
+
+    fn graph() -> GraphState {
+        let nodes = vec![node("msg:1", Role::User, "Fix the parser"), node("msg:2", Role::Assistant, "Done, tests pass")];
+        let edges = vec![
+            GraphEdge::conversation("msg:1".into(), "msg:2".into(), "s1".into(), Some("2025-06-15T12:00:00+00:00".into())),
+            GraphEdge::similarity("msg:1".into(), "msg:2".into(), 0.8, Some(1)),
+            GraphEdge::gap("msg:2".into(), "msg:1".into(), "s1".into(), 4),
+            GraphEdge::temporal("msg:1".into(), "msg:2".into(), 0.5),
+        ];
+        let mut graph = GraphState::new();
+        graph.temporal_attraction_enabled = false;
+        graph.load(GraphData::new(nodes, edges), bounds());
+        graph
+    }
+
+    #[test]
+    fn test_graph_round_trip() {
+        let graph = graph();
+        let bundle = BundleGraph::from_state(&graph, false);
+        assert_eq!(bundle.edges.len(), 3, "temporal edges are rebuilt, not stored");
+
+        let json = serde_json::to_string(&bundle).unwrap();
+        let parsed: BundleGraph = serde_json::from_str(&json).unwrap();
+        assert_eq!(parsed, bundle);
+
+        let (data, positions) = parsed.into_graph_data();
+        assert_eq!(data.nodes.len(), 2);
+        assert_eq!(data.nodes[1].content_preview, "Done, tests pass");
+        assert_eq!(data.nodes[0].total_tokens(), 160);
+        assert_eq!(positions.get("msg:1").copied(), graph.get_pos("msg:1"));
+        assert_eq!(data.edges[1].similarity, Some(0.8));
+        assert_eq!(data.edges[2].gap, Some(4));
+        // Going round again changes nothing
+        let mut reloaded = GraphState::new();
+        reloaded.temporal_attraction_enabled = false;
+        reloaded.load(data, bounds());
+        reloaded.positions = positions;
+        assert_eq!(BundleGraph::from_state(&reloaded, false), bundle);
+    }
+
+    #[test]
+    fn test_anonymize_keeps_lengths_only() {
+        let bundle = BundleGraph::from_state(&graph(), true);
+        assert!(bundle.nodes.iter().all(|n| n.preview.is_empty() && n.full_content.is_none()));
+        assert_eq!(bundle.nodes[0].content_chars, "Fix the parser".len());
+        assert_eq!(bundle.nodes[0].output_tokens, Some(120));
+
+        let (data, _) = bundle.into_graph_data();
+        assert_eq!(data.nodes[0].content_preview, "[14 chars]");
+        assert!(!serde_json::to_string(&BundleGraph::from_state(&graph(), true)).unwrap().contains("parser"));
+    }
+
+    #[test]
+    fn test_schema_is_stable() {
+        // A bundle written by format 1; later builds must keep reading it
+        let json = r#"{
+            "nodes": [{"id": "7", "role": "assistant", "session_id": "s", "content_chars": 12}],
+            "edges": [{"source": "7", "target": "8", "kind": "conversation"}]
+        }"#;
+        let graph: BundleGraph = serde_json::from_str(json).unwrap();
+        assert_eq!(graph.nodes[0].role, Role::Assistant);
+        assert_eq!(graph.nodes[0].pos, None);
+        assert_eq!(graph.edges[0].kind, BundleEdgeKind::Conversation);
+
+        let value = serde_json::to_value(&graph.edges[0]).unwrap();
+        assert_eq!(value["kind"], "conversation");
+
+        // Its bare message ids load namespaced
+        let (data, _) = graph.into_graph_data();
+        assert_eq!(data.nodes[0].id, "msg:7");
+        assert_eq!((data.edges[0].source.as_str(), data.edges[0].target.as_str()), ("msg:7", "msg:8"));
+    }
+
+    #[test]
+    fn test_directory_round_trip() {
+        let dir = std::env::temp_dir().join(format!("dashboard-bundle-test-{}", std::process::id()));
+        let settings = Settings { node_size: 17.0, ..Default::default() };
+        let bundle = DebugBundle {
+            manifest: Manifest::new(true, Some("Review".to_string())),
+            graph: BundleGraph::from_state(&graph(), true),
+            settings,
+            events: BundleEvents { stalls: vec!["stall".to_string()], log: vec!["warn".to_string()] },
+        };
+        bundle.write(&dir).unwrap();
+        let read = DebugBundle::read(&dir).unwrap();
+        std::fs::remove_dir_all(&dir).ok();
+
+        assert_eq!(read.manifest, bundle.manifest);
+        assert_eq!(read.graph, bundle.graph);
+        assert_eq!(read.events, bundle.events);
+        assert_eq!(
+            serde_json::to_value(&read.settings).unwrap(),
+            serde_json::to_value(&bundle.settings).unwrap()
+        );
+    }
+
+    #[test]
+    fn test_rejects_newer_format() {
+        let dir = std::env::temp_dir().join(format!("dashboard-bundle-future-{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let mut manifest = Manifest::new(false, None);
+        manifest.format = BUNDLE_FORMAT + 1;
+        write_json(&dir.join(MANIFEST_FILE), &manifest).unwrap();
+        let result = DebugBundle::read(&dir);
+        std::fs::remove_dir_all(&dir).ok();
+        assert!(result.unwrap_err().contains("newer"));
+    }
+}
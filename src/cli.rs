@@ -0,0 +1,190 @@
+//! Command-line options for launching the dashboard pre-configured.
+
+use crate::app::MAX_RANGE_HOURS;
+
+/// Help text printed for `--help` and after argument errors
+pub const USAGE: &str = "\
+Usage: dashboard-native [OPTIONS]
+
+Options:
+  --db-path <PATH>     Existing SQLite database to open (overrides DB_PATH)
+  --hours <HOURS>      Time range to load, 1 to 2160 hours (overrides saved setting)
+  --session <ID>       Only load messages from this session
+  --load-bundle <DIR>  Show a debug bundle instead of the database
+  -h, --help           Print this help and exit
+
+Headless export (no window; exits non-zero on failure):
+  --headless-export <PATH>   Lay out the graph and write it as a PNG
+  --size <WxH>               Image size in pixels (default 1600x1000)
+  --iterations <N>           Layout iterations to run (default 500)";
+
+/// Launch options parsed from the command line
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct CliArgs {
+    pub db_path: Option<String>,
+    pub hours: Option<f32>,
+    pub session: Option<String>,
+    pub headless_export: Option<String>,
+    pub export_size: Option<(u32, u32)>,
+    pub layout_iterations: Option<u32>,
+    pub load_bundle: Option<String>,
+    pub help: bool,
+}
+
+impl CliArgs {
+    /// Parse arguments (excluding the program name).
+    /// Accepts both `--flag value` and `--flag=value`.
+    pub fn parse<I: IntoIterator<Item = String>>(args: I) -> Result<Self, String> {
+        let mut parsed = Self::default();
+        let mut args = args.into_iter();
+
+        while let Some(arg) = args.next() {
+            let (flag, inline_value) = match arg.split_once('=') {
+                Some((flag, value)) if flag.starts_with("--") => (flag.to_string(), Some(value.to_string())),
+                _ => (arg.clone(), None),
+            };
+
+            if flag == "-h" || flag == "--help" {
+                parsed.help = true;
+                continue;
+            }
+
+            let mut value = |name: &str| -> Result<String, String> {
+                let value = match inline_value.clone() {
+                    Some(v) => v,
+                    None => args.next().ok_or_else(|| format!("{} requires a value", name))?,
+                };
+                if value.trim().is_empty() {
+                    return Err(format!("{} requires a non-empty value", name));
+                }
+                Ok(value)
+            };
+
+            match flag.as_str() {
+                "--db-path" => parsed.db_path = Some(expand_home(&value("--db-path")?)),
+                "--hours" => {
+                    let raw = value("--hours")?;
+                    let hours: f32 = raw
+                        .parse()
+                        .map_err(|_| format!("invalid --hours value '{}': expected a number", raw))?;
+                    // The load query works in whole hours and the slider stops at MAX_RANGE_HOURS
+                    if !(1.0..=MAX_RANGE_HOURS).contains(&hours) {
+                        return Err(format!("invalid --hours value '{}': must be between 1 and {}", raw, MAX_RANGE_HOURS));
+                    }
+                    parsed.hours = Some(hours);
+                }
+                "--session" => parsed.session = Some(value("--session")?),
+                "--load-bundle" => parsed.load_bundle = Some(expand_home(&value("--load-bundle")?)),
+                "--headless-export" => parsed.headless_export = Some(expand_home(&value("--headless-export")?)),
+                "--size" => {
+                    let raw = value("--size")?;
+                    parsed.export_size = Some(parse_size(&raw)
+                        .ok_or_else(|| format!("invalid --size value '{}': expected WIDTHxHEIGHT, e.g. 1600x1000", raw))?);
+                }
+                "--iterations" => {
+                    let raw = value("--iterations")?;
+                    let iterations = raw
+                        .parse()
+                        .map_err(|_| format!("invalid --iterations value '{}': expected a whole number", raw))?;
+                    parsed.layout_iterations = Some(iterations);
+                }
+                _ => return Err(format!("unrecognized argument '{}'", arg)),
+            }
+        }
+
+        Ok(parsed)
+    }
+
+    /// Check that an explicit `--db-path` names an existing file. Opening a
+    /// mistyped path would otherwise create an empty database there.
+    pub fn check_db_path(&self) -> Result<(), String> {
+        match self.db_path {
+            Some(ref path) if !std::path::Path::new(path).is_file() => Err(format!("database not found: {}", path)),
+            _ => Ok(()),
+        }
+    }
+}
+
+/// Parse `WIDTHxHEIGHT`, each side between 16 and 16384 pixels
+fn parse_size(raw: &str) -> Option<(u32, u32)> {
+    let (w, h) = raw.to_ascii_lowercase().split_once('x').map(|(w, h)| (w.trim().parse().ok(), h.trim().parse().ok()))?;
+    let valid = |side: u32| (16..=16384).contains(&side);
+    match (w, h) {
+        (Some(w), Some(h)) if valid(w) && valid(h) => Some((w, h)),
+        _ => None,
+    }
+}
+
+/// Expand a leading `~/` to the home directory (for `--db-path=~/...`, which the shell won't expand)
+pub fn expand_home(path: &str) -> String {
+    match (path.strip_prefix("~/"), dirs::home_dir()) {
+        (Some(rest), Some(home)) => home.join(rest).to_string_lossy().to_string(),
+        _ => path.to_string(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn parse(args: &[&str]) -> Result<CliArgs, String> {
+        CliArgs::parse(args.iter().map(|s| s.to_string()))
+    }
+
+    #[test]
+    fn test_parse_all_flags() {
+        let args = parse(&["--db-path", "/tmp/march.db", "--hours=72", "--session", "8f2c"]).unwrap();
+        assert_eq!(args.db_path.as_deref(), Some("/tmp/march.db"));
+        assert_eq!(args.hours, Some(72.0));
+        assert_eq!(args.session.as_deref(), Some("8f2c"));
+        assert!(!args.help);
+    }
+
+    #[test]
+    fn test_parse_load_bundle() {
+        let args = parse(&["--load-bundle=/tmp/report-bundle"]).unwrap();
+        assert_eq!(args.load_bundle.as_deref(), Some("/tmp/report-bundle"));
+        assert!(parse(&["--load-bundle"]).is_err());
+    }
+
+    #[test]
+    fn test_parse_help() {
+        assert!(parse(&["--help"]).unwrap().help);
+        assert!(parse(&["-h"]).unwrap().help);
+    }
+
+    #[test]
+    fn test_parse_headless_export() {
+        let args = parse(&["--headless-export", "/tmp/daily.png", "--size=1920x1080", "--iterations", "200"]).unwrap();
+        assert_eq!(args.headless_export.as_deref(), Some("/tmp/daily.png"));
+        assert_eq!(args.export_size, Some((1920, 1080)));
+        assert_eq!(args.layout_iterations, Some(200));
+
+        assert!(parse(&["--size", "1920"]).is_err());
+        assert!(parse(&["--size", "0x0"]).is_err());
+        assert!(parse(&["--iterations", "-1"]).is_err());
+    }
+
+    #[test]
+    fn test_db_path_must_exist() {
+        let path = std::env::temp_dir().join(format!("dashboard-cli-{}.db", std::process::id()));
+        let args = parse(&["--db-path", path.to_str().unwrap()]).unwrap();
+        assert_eq!(args.check_db_path(), Err(format!("database not found: {}", path.display())));
+        std::fs::write(&path, b"").unwrap();
+        assert_eq!(args.check_db_path(), Ok(()));
+        std::fs::remove_file(&path).unwrap();
+        assert_eq!(parse(&[]).unwrap().check_db_path(), Ok(()));
+    }
+
+    #[test]
+    fn test_parse_errors() {
+        assert!(parse(&["--hours", "abc"]).is_err());
+        assert!(parse(&["--hours", "-5"]).is_err());
+        assert!(parse(&["--hours", "0.5"]).is_err());
+        assert!(parse(&["--hours", "2161"]).is_err());
+        assert_eq!(parse(&["--hours", "2160"]).unwrap().hours, Some(2160.0));
+        assert!(parse(&["--session"]).is_err());
+        assert!(parse(&["--db-path="]).is_err());
+        assert!(parse(&["--verbose"]).is_err());
+    }
+}
@@ -0,0 +1,195 @@
+//! Commands emitted by UI code and applied once per frame.
+//!
+//! Rendering code pushes `AppCommand`s onto a `CommandQueue` instead of
+//! starting loads or mutating graph state mid-frame. After the panels are
+//! drawn the app drains the queue and hands each command to `dispatch`,
+//! which works against the `CommandTarget` trait so command sequences can
+//! be exercised without egui.
+
+/// A state change requested by the UI
+#[derive(Debug, Clone, PartialEq)]
+pub enum AppCommand {
+    /// Reload with a new range and session restriction, debounced
+    LoadGraph { hours: f32, session: Option<String> },
+    /// Reload the current range now, reconnecting first if needed
+    Reload,
+    /// Set the timeline window (epoch seconds), enabling the timeline
+    SetTimelineWindow { start: f64, end: f64 },
+    /// Select a node, or clear the selection
+    SelectNode(Option<String>),
+    /// Apply a saved preset by name
+    ApplyPreset(String),
+    /// Switch a semantic filter between off and include
+    ToggleFilter(i32),
+}
+
+impl AppCommand {
+    /// Whether a later command of the same kind makes this one redundant
+    fn superseded_by(&self, later: &AppCommand) -> bool {
+        matches!(
+            (self, later),
+            (AppCommand::LoadGraph { .. }, AppCommand::LoadGraph { .. })
+                | (AppCommand::Reload, AppCommand::Reload)
+                | (AppCommand::SetTimelineWindow { .. }, AppCommand::SetTimelineWindow { .. })
+                | (AppCommand::SelectNode(_), AppCommand::SelectNode(_))
+        )
+    }
+}
+
+/// Commands queued during a frame
+#[derive(Debug, Default)]
+pub struct CommandQueue {
+    pending: Vec<AppCommand>,
+}
+
+impl CommandQueue {
+    pub fn push(&mut self, command: AppCommand) {
+        self.pending.push(command);
+    }
+
+    /// Take the queued commands in order. Loads, timeline windows and
+    /// selections only keep their last occurrence; presets and toggles all run.
+    pub fn drain(&mut self) -> Vec<AppCommand> {
+        let pending = std::mem::take(&mut self.pending);
+        pending.iter().enumerate()
+            .filter(|(i, command)| !pending[i + 1..].iter().any(|later| command.superseded_by(later)))
+            .map(|(_, command)| command.clone())
+            .collect()
+    }
+}
+
+/// The state a command mutates. Implemented by the app; tests use a recorder.
+pub trait CommandTarget {
+    fn load_range(&mut self, hours: f32, session: Option<String>);
+    fn reload(&mut self);
+    fn set_timeline_window(&mut self, start: f64, end: f64);
+    fn select_node(&mut self, id: Option<String>);
+    fn apply_preset_by_name(&mut self, name: &str);
+    fn toggle_semantic_filter(&mut self, id: i32);
+}
+
+/// Apply one command to its target
+pub fn dispatch(target: &mut impl CommandTarget, command: AppCommand) {
+    tracing::debug!("command: {:?}", command);
+    match command {
+        AppCommand::LoadGraph { hours, session } => target.load_range(hours, session),
+        AppCommand::Reload => target.reload(),
+        AppCommand::SetTimelineWindow { start, end } => target.set_timeline_window(start, end),
+        AppCommand::SelectNode(id) => target.select_node(id),
+        AppCommand::ApplyPreset(name) => target.apply_preset_by_name(&name),
+        AppCommand::ToggleFilter(id) => target.toggle_semantic_filter(id),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Minimal stand-in for the app's load, timeline, selection and preset state
+    #[derive(Default)]
+    struct Recorder {
+        hours: f32,
+        session: Option<String>,
+        loads: usize,
+        reloads: usize,
+        window: Option<(f64, f64)>,
+        selected: Option<String>,
+        presets: Vec<String>,
+        filters_on: Vec<i32>,
+    }
+
+    impl CommandTarget for Recorder {
+        fn load_range(&mut self, hours: f32, session: Option<String>) {
+            self.hours = hours;
+            self.session = session;
+            self.loads += 1;
+        }
+        fn reload(&mut self) {
+            self.reloads += 1;
+        }
+        fn set_timeline_window(&mut self, start: f64, end: f64) {
+            self.window = Some((start, end));
+        }
+        fn select_node(&mut self, id: Option<String>) {
+            self.selected = id;
+        }
+        fn apply_preset_by_name(&mut self, name: &str) {
+            self.presets.push(name.to_string());
+        }
+        fn toggle_semantic_filter(&mut self, id: i32) {
+            if let Some(pos) = self.filters_on.iter().position(|&f| f == id) {
+                self.filters_on.remove(pos);
+            } else {
+                self.filters_on.push(id);
+            }
+        }
+    }
+
+    fn run(commands: Vec<AppCommand>) -> Recorder {
+        let mut queue = CommandQueue::default();
+        for command in commands {
+            queue.push(command);
+        }
+        let mut recorder = Recorder::default();
+        for command in queue.drain() {
+            dispatch(&mut recorder, command);
+        }
+        assert!(queue.drain().is_empty());
+        recorder
+    }
+
+    #[test]
+    fn test_slider_drag_loads_once_with_final_range() {
+        let recorder = run(vec![
+            AppCommand::LoadGraph { hours: 24.0, session: None },
+            AppCommand::LoadGraph { hours: 48.0, session: None },
+            AppCommand::LoadGraph { hours: 72.0, session: Some("s1".into()) },
+        ]);
+        assert_eq!(recorder.loads, 1);
+        assert_eq!(recorder.hours, 72.0);
+        assert_eq!(recorder.session.as_deref(), Some("s1"));
+    }
+
+    #[test]
+    fn test_last_timeline_window_and_selection_win() {
+        let recorder = run(vec![
+            AppCommand::SelectNode(Some("a".into())),
+            AppCommand::SetTimelineWindow { start: 10.0, end: 20.0 },
+            AppCommand::SelectNode(None),
+            AppCommand::SetTimelineWindow { start: 15.0, end: 30.0 },
+            AppCommand::SelectNode(Some("b".into())),
+        ]);
+        assert_eq!(recorder.window, Some((15.0, 30.0)));
+        assert_eq!(recorder.selected.as_deref(), Some("b"));
+    }
+
+    #[test]
+    fn test_presets_and_toggles_all_apply_in_order() {
+        let recorder = run(vec![
+            AppCommand::ApplyPreset("Focus".into()),
+            AppCommand::ToggleFilter(3),
+            AppCommand::ApplyPreset("Overview".into()),
+            AppCommand::ToggleFilter(3),
+            AppCommand::ToggleFilter(7),
+            AppCommand::Reload,
+        ]);
+        assert_eq!(recorder.presets, vec!["Focus", "Overview"]);
+        assert_eq!(recorder.filters_on, vec![7]);
+        assert_eq!(recorder.reloads, 1);
+    }
+
+    #[test]
+    fn test_drain_keeps_relative_order() {
+        let mut queue = CommandQueue::default();
+        queue.push(AppCommand::SelectNode(Some("a".into())));
+        queue.push(AppCommand::ApplyPreset("P".into()));
+        queue.push(AppCommand::Reload);
+        queue.push(AppCommand::SelectNode(Some("b".into())));
+        assert_eq!(queue.drain(), vec![
+            AppCommand::ApplyPreset("P".into()),
+            AppCommand::Reload,
+            AppCommand::SelectNode(Some("b".into())),
+        ]);
+        assert!(queue.drain().is_empty());
+    }
+}
@@ -0,0 +1,101 @@
+//! Side-by-side comparison of two sessions (e.g. a task and its retry).
+
+use crate::cost::TokenTotals;
+use crate::graph::types::GraphNode;
+
+/// Per-session numbers shown in the comparison strip
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct SessionStats {
+    pub messages: usize,
+    pub tokens: u64,
+    pub tool_uses: usize,
+    pub start: Option<f64>,
+    pub end: Option<f64>,
+}
+
+impl SessionStats {
+    pub fn from_nodes<'a>(nodes: impl IntoIterator<Item = &'a GraphNode>) -> Self {
+        let mut stats = Self::default();
+        let mut tokens = TokenTotals::default();
+        for node in nodes {
+            stats.messages += 1;
+            tokens.add_node(node);
+            if node.has_tool_usage {
+                stats.tool_uses += 1;
+            }
+            if let Some(t) = node.timestamp_secs() {
+                stats.start = Some(stats.start.map_or(t, |s| s.min(t)));
+                stats.end = Some(stats.end.map_or(t, |e| e.max(t)));
+            }
+        }
+        stats.tokens = tokens.total();
+        stats
+    }
+
+    /// Seconds from first to last message
+    pub fn duration_secs(&self) -> Option<f64> {
+        Some(self.end? - self.start?)
+    }
+
+    /// Horizontal position (0-1) of a timestamp on this session's own time axis.
+    /// Single-instant sessions and untimed nodes sit in the middle.
+    pub fn time_fraction(&self, t: Option<f64>) -> f32 {
+        match (t, self.start, self.duration_secs()) {
+            (Some(t), Some(start), Some(span)) if span > 0.0 => ((t - start) / span).clamp(0.0, 1.0) as f32,
+            _ => 0.5,
+        }
+    }
+}
+
+/// Compact duration like "1h 05m", "42m 10s" or "35s"
+pub fn format_duration(secs: f64) -> String {
+    let secs = secs.max(0.0).round() as u64;
+    let (h, m, s) = (secs / 3600, secs % 3600 / 60, secs % 60);
+    if h > 0 {
+        format!("{}h {:02}m", h, m)
+    } else if m > 0 {
+        format!("{}m {:02}s", m, s)
+    } else {
+        format!("{}s", s)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::graph::types::Role;
+
+    fn node(timestamp: &str, output_tokens: i32, has_tool_usage: bool) -> GraphNode {
+        GraphNode {
+            role: Role::Assistant,
+            project: "p".to_string(),
+            timestamp: Some(timestamp.to_string()),
+            output_tokens: Some(output_tokens),
+            has_tool_usage,
+            ..GraphNode::stub(timestamp, "s")
+        }
+    }
+
+    #[test]
+    fn test_session_stats_and_axis() {
+        let nodes = [
+            node("2025-06-01T10:00:00Z", 100, false),
+            node("2025-06-01T10:30:00Z", 50, true),
+            node("2025-06-01T11:00:00Z", 25, true),
+        ];
+        let stats = SessionStats::from_nodes(&nodes);
+        assert_eq!(stats.messages, 3);
+        assert_eq!(stats.tokens, 175);
+        assert_eq!(stats.tool_uses, 2);
+        assert_eq!(stats.duration_secs(), Some(3600.0));
+        assert_eq!(format_duration(3600.0), "1h 00m");
+
+        assert_eq!(stats.time_fraction(nodes[1].timestamp_secs()), 0.5);
+        assert_eq!(stats.time_fraction(nodes[2].timestamp_secs()), 1.0);
+        assert_eq!(stats.time_fraction(None), 0.5);
+
+        // A single message has no span; it sits mid-axis
+        let single = SessionStats::from_nodes(&nodes[..1]);
+        assert_eq!(single.time_fraction(nodes[0].timestamp_secs()), 0.5);
+    }
+}
@@ -0,0 +1,183 @@
+//! Cheap classification of message text as prose, code-heavy or tool output,
+//! so code-dominated messages can be badged and filtered. Pure string work:
+//! no parsing beyond fence lines, safe to run over every node at load.
+
+/// Share of the text inside fenced blocks at which a message is code-heavy
+pub const CODE_FENCE_RATIO: f32 = 0.5;
+/// Share of lines that must look like tool output (`12→` file listings,
+/// `$ ` prompts) for unfenced text to count as tool output
+const TOOL_LINE_RATIO: f32 = 0.6;
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum ContentKind {
+    #[default]
+    Prose,
+    Code,
+    ToolOutput,
+}
+
+impl ContentKind {
+    pub fn label(&self) -> &'static str {
+        match self {
+            ContentKind::Prose => "Prose",
+            ContentKind::Code => "Code-heavy",
+            ContentKind::ToolOutput => "Tool output",
+        }
+    }
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct ContentClass {
+    pub kind: ContentKind,
+    /// Language covering the most fenced text, from the fence info strings
+    pub language: Option<String>,
+}
+
+impl ContentClass {
+    /// "Code-heavy (rust)", or just the kind
+    pub fn describe(&self) -> String {
+        match &self.language {
+            Some(language) => format!("{} ({})", self.kind.label(), language),
+            None => self.kind.label().to_string(),
+        }
+    }
+}
+
+/// Classify `text`. An unterminated fence runs to the end, since previews
+/// are often cut mid-block.
+pub fn classify(text: &str) -> ContentClass {
+    let text = text.trim();
+    if text.starts_with("[Used tools:") {
+        return ContentClass { kind: ContentKind::ToolOutput, language: None };
+    }
+
+    let mut total = 0usize;
+    let mut fenced = 0usize;
+    let mut lines = 0usize;
+    let mut tool_lines = 0usize;
+    // (language, fenced chars) in first-seen order so ties go to the earlier fence
+    let mut languages: Vec<(String, usize)> = Vec::new();
+    let mut open: Option<Option<String>> = None;
+    for line in text.lines() {
+        let chars = line.chars().count();
+        total += chars;
+        let trimmed = line.trim_start();
+        if let Some(info) = trimmed.strip_prefix("```").or_else(|| trimmed.strip_prefix("~~~")) {
+            open = match open {
+                Some(_) => None,
+                None => Some(language_of(info)),
+            };
+            fenced += chars;
+            continue;
+        }
+        match open {
+            Some(ref language) => {
+                fenced += chars;
+                if let Some(language) = language {
+                    match languages.iter_mut().find(|(l, _)| l == language) {
+                        Some((_, n)) => *n += chars,
+                        None => languages.push((language.clone(), chars)),
+                    }
+                }
+            }
+            None if !trimmed.is_empty() => {
+                lines += 1;
+                if looks_like_tool_line(trimmed) {
+                    tool_lines += 1;
+                }
+            }
+            None => {}
+        }
+    }
+
+    if total > 0 && fenced as f32 / total as f32 >= CODE_FENCE_RATIO {
+        let mut best: Option<&(String, usize)> = None;
+        for entry in &languages {
+            if best.is_none_or(|b| entry.1 > b.1) {
+                best = Some(entry);
+            }
+        }
+        return ContentClass { kind: ContentKind::Code, language: best.map(|(l, _)| l.clone()) };
+    }
+    if lines >= 2 && tool_lines as f32 / lines as f32 >= TOOL_LINE_RATIO {
+        return ContentClass { kind: ContentKind::ToolOutput, language: None };
+    }
+    ContentClass::default()
+}
+
+/// Canonical language name from a fence info string ("rs" -> "rust")
+fn language_of(info: &str) -> Option<String> {
+    let word = info.trim().split(|c: char| c.is_whitespace() || c == ',' || c == '{').next()?;
+    let word = word.to_ascii_lowercase();
+    let name = match word.as_str() {
+        "" => return None,
+        "rs" => "rust",
+        "py" | "python3" => "python",
+        "js" | "jsx" | "mjs" => "javascript",
+        "ts" | "tsx" => "typescript",
+        "sh" | "bash" | "zsh" | "shell" | "console" => "shell",
+        "yml" => "yaml",
+        "md" => "markdown",
+        other => other,
+    };
+    Some(name.to_string())
+}
+
+/// A `Read`-style numbered line (`  12→...`) or a shell prompt
+fn looks_like_tool_line(line: &str) -> bool {
+    if line.starts_with("$ ") {
+        return true;
+    }
+    let digits = line.chars().take_while(char::is_ascii_digit).count();
+    digits > 0 && line[digits..].starts_with('→')
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_plain_prose() {
+        let class = classify("I looked at the parser and the bug is in how it handles escapes. I'll fix it next.");
+        assert_eq!(class, ContentClass::default());
+        assert_eq!(classify(""), ContentClass::default());
+    }
+
+    #[test]
+    fn test_mostly_fenced_is_code() {
+        let text = "Here's the fix:\n```rust\nfn parse(input: &str) -> Result<Ast, Error> {\n    let tokens = lex(input)?;\n    build(tokens)\n}\n```";
+        let class = classify(text);
+        assert_eq!(class.kind, ContentKind::Code);
+        assert_eq!(class.language.as_deref(), Some("rust"));
+        assert_eq!(class.describe(), "Code-heavy (rust)");
+    }
+
+    #[test]
+    fn test_short_snippet_in_long_prose_stays_prose() {
+        let text = format!("{}\n```sh\nmake test\n```\n{}", "Some explanation of the change. ".repeat(10), "More discussion. ".repeat(10));
+        assert_eq!(classify(&text).kind, ContentKind::Prose);
+    }
+
+    #[test]
+    fn test_dominant_language_and_aliases() {
+        let text = "```py\nimport os\n```\n```ts\nconst a: number = 1;\nconst b: number = 2;\nexport { a, b };\n```";
+        assert_eq!(classify(text).language.as_deref(), Some("typescript"));
+        // No info string: code, language unknown
+        assert_eq!(classify("```\nSELECT * FROM messages;\n```"), ContentClass { kind: ContentKind::Code, language: None });
+    }
+
+    #[test]
+    fn test_unterminated_fence_runs_to_the_end() {
+        let preview = "Update:\n```python\ndef handler(event):\n    return process(event[\"body\"])...";
+        let class = classify(preview);
+        assert_eq!((class.kind, class.language.as_deref()), (ContentKind::Code, Some("python")));
+    }
+
+    #[test]
+    fn test_tool_output() {
+        assert_eq!(classify("[Used tools: Bash, Read]").kind, ContentKind::ToolOutput);
+        let listing = "     1→use std::io;\n     2→\n     3→fn main() {\n     4→}";
+        assert_eq!(classify(listing).kind, ContentKind::ToolOutput);
+        assert_eq!(classify("$ cargo test\nrunning 3 tests\n$ cargo build").kind, ContentKind::ToolOutput);
+    }
+}
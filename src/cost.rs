@@ -0,0 +1,203 @@
+//! Dollar estimates from per-message token counts.
+
+use crate::graph::types::GraphNode;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+
+/// Sessions whose cache hit ratio is below this are flagged as poor reuse
+pub const POOR_CACHE_RATIO: f64 = 0.5;
+/// Input-side tokens a session needs before its ratio is judged
+pub const CACHE_RATIO_MIN_TOKENS: u64 = 10_000;
+
+/// Prices in USD per million tokens, by token class
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct TokenRates {
+    pub input: f64,
+    pub output: f64,
+    pub cache_read: f64,
+    pub cache_creation: f64,
+}
+
+impl Default for TokenRates {
+    fn default() -> Self {
+        Self {
+            input: 3.0,
+            output: 15.0,
+            cache_read: 0.30,
+            cache_creation: 3.75,
+        }
+    }
+}
+
+/// Summed token counts by class
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct TokenTotals {
+    pub input: u64,
+    pub output: u64,
+    pub cache_read: u64,
+    pub cache_creation: u64,
+}
+
+impl TokenTotals {
+    /// Add one message's tokens (missing or negative counts are zero)
+    pub fn add_node(&mut self, node: &GraphNode) {
+        let count = |tokens: Option<i32>| tokens.unwrap_or(0).max(0) as u64;
+        self.input += count(node.input_tokens);
+        self.output += count(node.output_tokens);
+        self.cache_read += count(node.cache_read_tokens);
+        self.cache_creation += count(node.cache_creation_tokens);
+    }
+
+    pub fn total(&self) -> u64 {
+        self.input + self.output + self.cache_read + self.cache_creation
+    }
+
+    /// Share of prompt tokens served from cache: cache_read / (cache_read + input).
+    /// None when nothing was read either way.
+    pub fn cache_hit_ratio(&self) -> Option<f64> {
+        let prompt = self.cache_read + self.input;
+        (prompt > 0).then(|| self.cache_read as f64 / prompt as f64)
+    }
+
+    /// Enough prompt tokens to judge, and too few of them from cache
+    pub fn poor_cache_reuse(&self) -> bool {
+        self.cache_read + self.input >= CACHE_RATIO_MIN_TOKENS
+            && self.cache_hit_ratio().is_some_and(|r| r < POOR_CACHE_RATIO)
+    }
+
+    /// Estimated cost in USD
+    pub fn cost(&self, rates: &TokenRates) -> f64 {
+        (self.input as f64 * rates.input
+            + self.output as f64 * rates.output
+            + self.cache_read as f64 * rates.cache_read
+            + self.cache_creation as f64 * rates.cache_creation)
+            / 1_000_000.0
+    }
+}
+
+/// Token totals for the timeline window, for today, and per project and
+/// session within the window
+#[derive(Debug, Clone, Default)]
+pub struct CostSummary {
+    pub window: TokenTotals,
+    pub today: TokenTotals,
+    pub by_project: HashMap<String, TokenTotals>,
+    pub by_session: HashMap<String, TokenTotals>,
+}
+
+impl CostSummary {
+    pub fn clear(&mut self) {
+        self.window = TokenTotals::default();
+        self.today = TokenTotals::default();
+        self.by_project.clear();
+        self.by_session.clear();
+    }
+
+    /// Count a node, given whether it falls in the timeline window.
+    /// `today_start` is local midnight in epoch seconds.
+    pub fn add(&mut self, node: &GraphNode, in_window: bool, today_start: f64) {
+        if in_window {
+            self.window.add_node(node);
+            self.by_project.entry(node.project.clone()).or_default().add_node(node);
+            self.by_session.entry(node.session_id.clone()).or_default().add_node(node);
+        }
+        if node.timestamp_secs().is_some_and(|t| t >= today_start) {
+            self.today.add_node(node);
+        }
+    }
+}
+
+/// Local midnight today, as epoch seconds
+pub fn today_start_secs() -> f64 {
+    let now = chrono::Local::now();
+    now.date_naive()
+        .and_hms_opt(0, 0, 0)
+        .and_then(|midnight| midnight.and_local_timezone(chrono::Local).earliest())
+        .map_or(now.timestamp() as f64, |midnight| midnight.timestamp() as f64)
+}
+
+/// Format a dollar estimate with precision suited to its size
+pub fn format_usd(amount: f64) -> String {
+    if amount >= 100.0 {
+        format!("${:.0}", amount)
+    } else if amount >= 0.01 || amount == 0.0 {
+        format!("${:.2}", amount)
+    } else {
+        "<$0.01".to_string()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::graph::types::Role;
+
+    fn node(project: &str, timestamp: &str, input: i32, output: i32, cache_read: i32, cache_creation: i32) -> GraphNode {
+        GraphNode {
+            role: Role::Assistant,
+            project: project.to_string(),
+            timestamp: Some(timestamp.to_string()),
+            output_tokens: Some(output),
+            input_tokens: Some(input),
+            cache_read_tokens: Some(cache_read),
+            cache_creation_tokens: Some(cache_creation),
+            ..GraphNode::stub(&format!("{}-{}", project, timestamp), "s")
+        }
+    }
+
+    #[test]
+    fn test_cost_arithmetic() {
+        let mut totals = TokenTotals::default();
+        totals.add_node(&node("a", "2025-06-01T10:00:00Z", 1_000_000, 200_000, 2_000_000, 400_000));
+        totals.add_node(&node("a", "2025-06-01T10:01:00Z", -5, 0, 0, 0));
+        assert_eq!(totals.input, 1_000_000);
+        assert_eq!(totals.total(), 3_600_000);
+
+        // 1M * $3 + 0.2M * $15 + 2M * $0.30 + 0.4M * $3.75
+        let cost = totals.cost(&TokenRates::default());
+        assert!((cost - (3.0 + 3.0 + 0.6 + 1.5)).abs() < 1e-9, "cost = {}", cost);
+        assert_eq!(TokenTotals::default().cost(&TokenRates::default()), 0.0);
+    }
+
+    #[test]
+    fn test_summary_window_and_today() {
+        let early = node("alpha", "2025-06-01T08:00:00Z", 100, 10, 0, 0);
+        let late = node("beta", "2025-06-01T20:00:00Z", 200, 20, 0, 0);
+        let today_start = early.timestamp_secs().unwrap() + 3600.0;
+
+        let mut summary = CostSummary::default();
+        summary.add(&early, true, today_start);
+        summary.add(&late, false, today_start);
+
+        // Only the windowed node counts toward the window and project table
+        assert_eq!(summary.window.input, 100);
+        assert_eq!(summary.by_project.len(), 1);
+        assert_eq!(summary.by_project["alpha"].output, 10);
+        // "Today" ignores the window
+        assert_eq!(summary.today.input, 200);
+
+        summary.clear();
+        assert_eq!(summary.window, TokenTotals::default());
+        assert!(summary.by_project.is_empty());
+        assert!(summary.by_session.is_empty());
+    }
+
+    #[test]
+    fn test_cache_hit_ratio() {
+        assert_eq!(TokenTotals::default().cache_hit_ratio(), None);
+
+        // Output and cache writes don't count toward the ratio
+        let mut good = CostSummary::default();
+        good.add(&node("a", "2025-06-01T10:00:00Z", 20_000, 5_000, 60_000, 9_000), true, 0.0);
+        let ratio = good.by_session["s"].cache_hit_ratio().unwrap();
+        assert!((ratio - 0.75).abs() < 1e-9, "ratio = {}", ratio);
+        assert!(!good.by_session["s"].poor_cache_reuse());
+
+        let poor = TokenTotals { input: 30_000, cache_read: 10_000, ..Default::default() };
+        assert!(poor.poor_cache_reuse());
+        // Too small to judge
+        let tiny = TokenTotals { input: 300, cache_read: 100, ..Default::default() };
+        assert!(!tiny.poor_cache_reuse());
+    }
+}
@@ -4,17 +4,23 @@
 
 use sqlx::sqlite::SqlitePoolOptions;
 use sqlx::{FromRow, SqlitePool};
+use std::collections::HashMap;
 use std::sync::Arc;
 use tokio::runtime::Runtime;
 
+use crate::exclude;
+use crate::graph::ids;
 use crate::graph::types::{GraphData, GraphEdge, GraphNode, Role, SessionSummaryData};
 
 /// Embedded schema — run on every connect (all statements are IF NOT EXISTS).
 const SCHEMA_SQL: &str = include_str!("../schema.sqlite.sql");
 
-/// Resolve the database file path (env override or default config dir).
+/// Resolve the database file path (explicit path, env override, or default config dir).
 /// Uses ~/.config/dashboard-native/ to match the Python ingest script.
-fn db_path() -> String {
+pub fn db_path(path_override: Option<&str>) -> String {
+    if let Some(path) = path_override {
+        return path.to_string();
+    }
     std::env::var("DB_PATH").unwrap_or_else(|_| {
         let home = dirs::home_dir().unwrap_or_else(|| std::path::PathBuf::from("."));
         let config_dir = home.join(".config").join("dashboard-native");
@@ -61,6 +67,25 @@ struct SummaryRow {
     generated_at: Option<String>,
 }
 
+/// A starred message with enough of it to list and find it again
+#[derive(Debug, Clone, FromRow, PartialEq)]
+pub struct Favorite {
+    pub message_id: i32,
+    pub note: Option<String>,
+    pub created_at: Option<String>,
+    pub session_id: String,
+    pub role: String,
+    pub preview: String,
+    pub timestamp: Option<String>,
+}
+
+impl Favorite {
+    /// Graph node id of the message
+    pub fn node_id(&self) -> String {
+        ids::message(self.message_id as i64)
+    }
+}
+
 /// Importance scoring statistics
 #[derive(Debug, Clone)]
 pub struct ImportanceStats {
@@ -70,18 +95,53 @@ pub struct ImportanceStats {
     pub sessions_with_unscored: i64,
 }
 
-/// Database client with connection pool
+/// Size of a prospective load, from a cheap aggregate query
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct LoadPreview {
+    pub messages: usize,
+    /// Total bytes of message text
+    pub content_bytes: usize,
+}
+
+/// Rows fetched per step of a progressive load
+pub const LOAD_CHUNK_ROWS: usize = 5_000;
+
+/// One step of a progressive load: the messages it fetched and their edges
+#[derive(Debug, Clone, Default)]
+pub struct LoadChunk {
+    pub data: GraphData,
+    /// Messages fetched so far
+    pub loaded: usize,
+    /// Count taken before the first chunk; messages ingested since aren't in it
+    pub expected: usize,
+    /// The last chunk; nothing further will arrive
+    pub done: bool,
+}
+
+impl LoadChunk {
+    /// Fold the chunk that came after this one into it
+    pub fn append(&mut self, later: LoadChunk) {
+        self.data.nodes.extend(later.data.nodes);
+        self.data.edges.extend(later.data.edges);
+        self.loaded = later.loaded;
+        self.expected = later.expected;
+        self.done = later.done;
+    }
+}
+
+/// Database client with connection pool (cheap to clone for background threads)
+#[derive(Clone)]
 pub struct DbClient {
     pool: SqlitePool,
     runtime: Arc<Runtime>,
 }
 
 impl DbClient {
-    /// Create a new database client
-    pub fn new() -> Result<Self, String> {
+    /// Create a new database client, optionally at an explicit database path
+    pub fn new(path_override: Option<&str>) -> Result<Self, String> {
         let runtime = Runtime::new().map_err(|e| format!("Failed to create runtime: {}", e))?;
 
-        let path = db_path();
+        let path = db_path(path_override);
         let url = format!("sqlite://{}?mode=rwc", path);
 
         let pool = runtime.block_on(async {
@@ -144,11 +204,156 @@ impl DbClient {
         })
     }
 
-    /// Fetch graph data (nodes and edges)
-    pub fn fetch_graph(&self, hours: f32, session_id: Option<&str>) -> Result<GraphData, String> {
+    /// Count the messages (and their text bytes) `fetch_graph` would load, without loading them
+    pub fn preview_load(
+        &self,
+        hours: f32,
+        session_id: Option<&str>,
+        projects: &[String],
+        excluded: &[String],
+    ) -> Result<LoadPreview, String> {
         self.runtime.block_on(async {
-            let rows: Vec<MessageRow> = if let Some(sid) = session_id {
-                sqlx::query_as(
+            let scope = self.cwd_scope(projects, excluded).await?;
+            let sql = format!(
+                r#"
+                SELECT COUNT(*), COALESCE(SUM(LENGTH(m.content)), 0)
+                FROM messages m
+                JOIN sessions s ON m.session_id = s.session_id
+                WHERE {} AND {}
+                "#,
+                range_clause(session_id),
+                scope.clause(2),
+            );
+            let query = sqlx::query_as::<_, (i64, i64)>(&sql);
+            let mut query = match session_id {
+                Some(sid) => query.bind(sid),
+                None => query.bind(hours as f64),
+            };
+            for cwd in scope.params() {
+                query = query.bind(cwd);
+            }
+            let (messages, content_bytes) = query
+                .fetch_one(&self.pool)
+                .await
+                .map_err(|e| format!("Query failed: {}", e))?;
+            Ok(LoadPreview {
+                messages: messages.max(0) as usize,
+                content_bytes: content_bytes.max(0) as usize,
+            })
+        })
+    }
+
+    /// Session directories the project filters leave in a load
+    async fn cwd_scope(&self, projects: &[String], excluded: &[String]) -> Result<CwdScope, String> {
+        if projects.is_empty() && excluded.is_empty() {
+            return Ok(CwdScope::All);
+        }
+        let cwds: Vec<String> = sqlx::query_scalar("SELECT DISTINCT cwd FROM sessions")
+            .fetch_all(&self.pool)
+            .await
+            .map_err(|e| format!("Query failed: {}", e))?;
+        Ok(CwdScope::resolve(&cwds, projects, excluded))
+    }
+
+    /// Fetch graph data (nodes and edges).
+    /// A non-empty `projects` list restricts the result to those projects and
+    /// projects matching an `excluded` pattern are left out, both in the query.
+    /// With `content_lazy`, nodes keep only their preview (`full_content` is None).
+    pub fn fetch_graph(
+        &self,
+        hours: f32,
+        session_id: Option<&str>,
+        projects: &[String],
+        excluded: &[String],
+        content_lazy: bool,
+    ) -> Result<GraphData, String> {
+        self.runtime.block_on(async {
+            let scope = self.cwd_scope(projects, excluded).await?;
+            let sql = format!(
+                r#"
+                SELECT
+                    m.id,
+                    m.session_id,
+                    m.role,
+                    m.content,
+                    m.timestamp,
+                    m.sequence_num,
+                    m.importance_score,
+                    m.importance_reason,
+                    m.token_count,
+                    m.input_tokens,
+                    m.cache_read_tokens,
+                    m.cache_creation_tokens,
+                    s.cwd
+                FROM messages m
+                JOIN sessions s ON m.session_id = s.session_id
+                WHERE {} AND {}
+                ORDER BY m.session_id, m.sequence_num
+                "#,
+                range_clause(session_id),
+                scope.clause(2),
+            );
+            let query = sqlx::query_as::<_, MessageRow>(&sql);
+            let mut query = match session_id {
+                Some(sid) => query.bind(sid),
+                None => query.bind(hours as f64),
+            };
+            for cwd in scope.params() {
+                query = query.bind(cwd);
+            }
+            let rows = query
+                .fetch_all(&self.pool)
+                .await
+                .map_err(|e| format!("Query failed: {}", e))?;
+
+            // Convert rows to nodes, keeping each one's place in its session
+            let mut nodes = Vec::new();
+            let mut sequence = Vec::new();
+            for row in rows {
+                sequence.push(row.sequence_num);
+                nodes.push(message_node(row, content_lazy));
+            }
+            let edges = session_chain_edges(&nodes, &sequence);
+
+            self.mark_tool_usage(&mut nodes).await;
+
+            Ok(GraphData::new(nodes, edges))
+        })
+    }
+
+    /// `fetch_graph` in chunks of `chunk_rows`, newest messages first, so recent
+    /// activity can be shown while older history is still loading. Each chunk
+    /// holds only its own messages and the edges reaching them, including the
+    /// links back to the part of a session an earlier chunk delivered.
+    /// `on_chunk` returns false to stop; the chunks given so far always add up
+    /// to a complete graph of the rows they cover.
+    #[allow(clippy::too_many_arguments)]
+    pub fn fetch_graph_progressive(
+        &self,
+        hours: f32,
+        session_id: Option<&str>,
+        projects: &[String],
+        excluded: &[String],
+        content_lazy: bool,
+        chunk_rows: usize,
+        mut on_chunk: impl FnMut(LoadChunk) -> bool,
+    ) -> Result<(), String> {
+        let expected = self.preview_load(hours, session_id, projects, excluded)?.messages;
+        let scope = self.runtime.block_on(self.cwd_scope(projects, excluded))?;
+        let chunk_rows = chunk_rows.max(1);
+        let mut heads: HashMap<String, SessionHead> = HashMap::new();
+        // (timestamp, id) of the last row fetched; the next page starts below it
+        let mut cursor: Option<(Option<String>, i32)> = None;
+        let mut loaded = 0;
+        loop {
+            let rows: Vec<MessageRow> = self.runtime.block_on(async {
+                // Null timestamps sort last, so they follow every dated row
+                let after = match cursor {
+                    None => "1",
+                    Some((Some(_), _)) => "((m.timestamp, m.id) < (?2, ?3) OR m.timestamp IS NULL)",
+                    Some((None, _)) => "(m.timestamp IS NULL AND m.id < ?3)",
+                };
+                let sql = format!(
                     r#"
                     SELECT
                         m.id,
@@ -166,16 +371,70 @@ impl DbClient {
                         s.cwd
                     FROM messages m
                     JOIN sessions s ON m.session_id = s.session_id
-                    WHERE m.session_id = ?1
-                    ORDER BY m.session_id, m.sequence_num
+                    WHERE {} AND {} AND {}
+                    ORDER BY m.timestamp DESC, m.id DESC
+                    LIMIT ?4
                     "#,
-                )
-                .bind(sid)
-                .fetch_all(&self.pool)
-                .await
-                .map_err(|e| format!("Query failed: {}", e))?
-            } else {
-                sqlx::query_as(
+                    range_clause(session_id),
+                    after,
+                    scope.clause(5),
+                );
+                let query = sqlx::query_as::<_, MessageRow>(&sql);
+                let query = match session_id {
+                    Some(sid) => query.bind(sid),
+                    None => query.bind(hours as f64),
+                };
+                let (after_timestamp, after_id) = cursor.clone().unwrap_or_default();
+                let mut query = query
+                    .bind(after_timestamp)
+                    .bind(after_id)
+                    .bind(chunk_rows as i64);
+                for cwd in scope.params() {
+                    query = query.bind(cwd);
+                }
+                query
+                    .fetch_all(&self.pool)
+                    .await
+                    .map_err(|e| format!("Query failed: {}", e))
+            })?;
+            loaded += rows.len();
+            let done = rows.len() < chunk_rows;
+            if let Some(last) = rows.last() {
+                cursor = Some((last.timestamp.clone(), last.id));
+            }
+
+            let mut nodes = Vec::new();
+            let mut sequence = Vec::new();
+            for row in rows {
+                sequence.push(row.sequence_num);
+                nodes.push(message_node(row, content_lazy));
+            }
+            self.runtime.block_on(self.mark_tool_usage(&mut nodes));
+
+            let chunk = LoadChunk {
+                data: chunk_graph(nodes.into_iter().zip(sequence).collect(), &mut heads),
+                loaded,
+                expected: expected.max(loaded),
+                done,
+            };
+            if !on_chunk(chunk) || done {
+                return Ok(());
+            }
+        }
+    }
+
+    /// Fetch specific messages by id, wherever they fall in time. Ids that
+    /// don't parse or don't exist are skipped.
+    pub fn fetch_messages(&self, ids: &[String], content_lazy: bool) -> Result<Vec<GraphNode>, String> {
+        let ids: Vec<i64> = ids.iter().filter_map(|id| ids::message_id(id)).collect();
+        if ids.is_empty() {
+            return Ok(Vec::new());
+        }
+        self.runtime.block_on(async {
+            let mut nodes = Vec::new();
+            for batch in ids.chunks(900) {
+                let placeholders: Vec<String> = (1..=batch.len()).map(|i| format!("?{}", i)).collect();
+                let sql = format!(
                     r#"
                     SELECT
                         m.id,
@@ -193,130 +452,66 @@ impl DbClient {
                         s.cwd
                     FROM messages m
                     JOIN sessions s ON m.session_id = s.session_id
-                    WHERE m.timestamp >= datetime('now', '-' || CAST(?1 AS INTEGER) || ' hours')
+                    WHERE m.id IN ({})
                     ORDER BY m.session_id, m.sequence_num
                     "#,
-                )
-                .bind(hours as f64)
-                .fetch_all(&self.pool)
-                .await
-                .map_err(|e| format!("Query failed: {}", e))?
-            };
-
-            // Convert rows to nodes and edges
-            let mut nodes = Vec::new();
-            let mut edges = Vec::new();
-            let mut prev_msg: std::collections::HashMap<String, String> = std::collections::HashMap::new();
+                    placeholders.join(", ")
+                );
+                let mut query = sqlx::query_as::<_, MessageRow>(&sql);
+                for id in batch {
+                    query = query.bind(id);
+                }
+                let rows = query
+                    .fetch_all(&self.pool)
+                    .await
+                    .map_err(|e| format!("Query failed: {}", e))?;
+                nodes.extend(rows.into_iter().map(|row| message_node(row, content_lazy)));
+            }
+            self.mark_tool_usage(&mut nodes).await;
+            Ok(nodes)
+        })
+    }
 
-            for row in rows {
-                let msg_id = row.id.to_string();
-                let session_id = row.session_id.clone();
-                let content = row.content.unwrap_or_default();
-
-                let role = match row.role.as_str() {
-                    "user" => Role::User,
-                    "assistant" => Role::Assistant,
-                    "polecat" | "witness" | "mayor" | "crew" | "refinery" => Role::Agent,
-                    _ => Role::User,
-                };
+    /// Set `has_tool_usage` on nodes whose message has tool usages (batched for SQLite limit)
+    async fn mark_tool_usage(&self, nodes: &mut [GraphNode]) {
+        let message_ids: Vec<i64> = nodes.iter()
+            .filter_map(|n| ids::message_id(&n.id))
+            .collect();
+        if message_ids.is_empty() {
+            return;
+        }
 
-                let content_preview = if content.chars().count() > 100 {
-                    format!("{}...", content.chars().take(100).collect::<String>())
-                } else {
-                    content.clone()
-                };
+        let mut tool_msg_ids: std::collections::HashSet<i64> = std::collections::HashSet::new();
+        for batch in message_ids.chunks(900) {
+            let placeholders: Vec<String> = (1..=batch.len())
+                .map(|i| format!("?{}", i))
+                .collect();
+            let in_clause = placeholders.join(", ");
+            let sql = format!(
+                "SELECT DISTINCT message_id FROM tool_usages WHERE message_id IN ({})",
+                in_clause
+            );
 
-                let cwd = row.cwd.unwrap_or_default();
-                let project = if let Some(home) = dirs::home_dir() {
-                    let home_str = format!("{}/", home.display());
-                    cwd.replace(&home_str, "~/")
-                } else {
-                    cwd.clone()
-                };
+            let mut query = sqlx::query_scalar::<_, i64>(&sql);
+            for id in batch {
+                query = query.bind(id);
+            }
 
-                let ts = row.timestamp;
-
-                nodes.push(GraphNode {
-                    id: msg_id.clone(),
-                    role,
-                    content_preview,
-                    full_content: Some(content),
-                    session_id: session_id.clone(),
-                    session_short: session_id[..8.min(session_id.len())].to_string(),
-                    project,
-                    timestamp: ts.clone(),
-                    importance_score: row.importance_score.map(|v| v as f32),
-                    importance_reason: row.importance_reason,
-                    output_tokens: row.token_count,
-                    input_tokens: row.input_tokens,
-                    cache_read_tokens: row.cache_read_tokens,
-                    cache_creation_tokens: row.cache_creation_tokens,
-                    has_tool_usage: false, // Populated below
-                });
-
-                // Create edge from previous message in same session
-                if let Some(prev_id) = prev_msg.get(&session_id) {
-                    edges.push(GraphEdge {
-                        source: prev_id.clone(),
-                        target: msg_id.clone(),
-                        session_id: session_id.clone(),
-                        timestamp: ts.clone(),
-                        is_obsidian: false,
-                        is_topic: false,
-                        is_similarity: false,
-                        is_temporal: false,
-                        similarity: None,
-                        query_index: None,
-                    });
-                }
+            let batch_results: Vec<i64> = query
+                .fetch_all(&self.pool)
+                .await
+                .unwrap_or_default();
 
-                prev_msg.insert(session_id, msg_id);
-            }
+            tool_msg_ids.extend(batch_results);
+        }
 
-            // Identify messages with tool usages (batched for SQLite limit)
-            if !nodes.is_empty() {
-                let message_ids: Vec<i32> = nodes.iter()
-                    .filter_map(|n| n.id.parse::<i32>().ok())
-                    .collect();
-
-                if !message_ids.is_empty() {
-                    let mut tool_msg_ids: std::collections::HashSet<i32> = std::collections::HashSet::new();
-
-                    for batch in message_ids.chunks(900) {
-                        let placeholders: Vec<String> = (1..=batch.len())
-                            .map(|i| format!("?{}", i))
-                            .collect();
-                        let in_clause = placeholders.join(", ");
-                        let sql = format!(
-                            "SELECT DISTINCT message_id FROM tool_usages WHERE message_id IN ({})",
-                            in_clause
-                        );
-
-                        let mut query = sqlx::query_scalar::<_, i32>(&sql);
-                        for id in batch {
-                            query = query.bind(id);
-                        }
-
-                        let batch_results: Vec<i32> = query
-                            .fetch_all(&self.pool)
-                            .await
-                            .unwrap_or_default();
-
-                        tool_msg_ids.extend(batch_results);
-                    }
-
-                    for node in &mut nodes {
-                        if let Ok(msg_id) = node.id.parse::<i32>() {
-                            if tool_msg_ids.contains(&msg_id) {
-                                node.has_tool_usage = true;
-                            }
-                        }
-                    }
+        for node in nodes {
+            if let Some(msg_id) = ids::message_id(&node.id) {
+                if tool_msg_ids.contains(&msg_id) {
+                    node.has_tool_usage = true;
                 }
             }
-
-            Ok(GraphData { nodes, edges, beads: Vec::new(), mail: Vec::new() })
-        })
+        }
     }
 
     /// Fetch session summary from database (no generation)
@@ -373,6 +568,158 @@ impl DbClient {
         })
     }
 
+    /// Topics listed by every stored session summary: session_id -> topics
+    pub fn fetch_session_topics(&self) -> Result<HashMap<String, Vec<String>>, String> {
+        self.runtime.block_on(async {
+            let rows: Vec<(String, String)> = sqlx::query_as(
+                "SELECT session_id, topics FROM session_summaries WHERE topics IS NOT NULL",
+            )
+            .fetch_all(&self.pool)
+            .await
+            .map_err(|e| format!("Query failed: {}", e))?;
+
+            Ok(rows.into_iter()
+                .filter_map(|(session_id, topics)| {
+                    let topics: Vec<String> = serde_json::from_str(&topics).ok()?;
+                    (!topics.is_empty()).then_some((session_id, topics))
+                })
+                .collect())
+        })
+    }
+
+    /// Earliest timestamp among the messages between two messages of a session
+    /// (exclusive), i.e. how far back a load must reach to close a gap edge
+    pub fn fetch_gap_start(&self, source_id: &str, target_id: &str) -> Result<Option<String>, String> {
+        let (Some(source), Some(target)) = (ids::message_id(source_id), ids::message_id(target_id)) else {
+            return Ok(None);
+        };
+        self.runtime.block_on(async {
+            sqlx::query_scalar(
+                r#"
+                SELECT MIN(m.timestamp)
+                FROM messages m
+                JOIN messages a ON a.id = ?1
+                JOIN messages b ON b.id = ?2
+                WHERE m.session_id = a.session_id
+                  AND m.sequence_num > a.sequence_num
+                  AND m.sequence_num < b.sequence_num
+                "#,
+            )
+            .bind(source)
+            .bind(target)
+            .fetch_one(&self.pool)
+            .await
+            .map_err(|e| format!("Query failed: {}", e))
+        })
+    }
+
+    /// Number of messages in the database
+    pub fn message_count(&self) -> Result<i64, String> {
+        self.runtime.block_on(async {
+            sqlx::query_scalar("SELECT COUNT(*) FROM messages")
+                .fetch_one(&self.pool)
+                .await
+                .map_err(|e| format!("Query failed: {}", e))
+        })
+    }
+
+    /// Timestamp of the newest message in the database, None when it's empty
+    pub fn latest_message_time(&self) -> Result<Option<String>, String> {
+        self.runtime.block_on(async {
+            sqlx::query_scalar("SELECT MAX(timestamp) FROM messages")
+                .fetch_one(&self.pool)
+                .await
+                .map_err(|e| format!("Query failed: {}", e))
+        })
+    }
+
+    /// Every favorite, newest first
+    pub fn fetch_favorites(&self) -> Result<Vec<Favorite>, String> {
+        self.runtime.block_on(async {
+            sqlx::query_as::<_, Favorite>(
+                r#"
+                SELECT
+                    f.message_id,
+                    f.note,
+                    f.created_at,
+                    m.session_id,
+                    m.role,
+                    substr(m.content, 1, 200) AS preview,
+                    m.timestamp
+                FROM favorites f
+                JOIN messages m ON m.id = f.message_id
+                ORDER BY f.created_at DESC, f.message_id DESC
+                "#,
+            )
+            .fetch_all(&self.pool)
+            .await
+            .map_err(|e| format!("Query failed: {}", e))
+        })
+    }
+
+    /// Star or unstar a message. Starring an existing favorite keeps its note.
+    pub fn set_favorite(&self, node_id: &str, favorite: bool) -> Result<(), String> {
+        let id = ids::message_id(node_id).ok_or_else(|| format!("{} is not a stored message", node_id))?;
+        let sql = if favorite {
+            "INSERT OR IGNORE INTO favorites (message_id) VALUES (?1)"
+        } else {
+            "DELETE FROM favorites WHERE message_id = ?1"
+        };
+        self.runtime.block_on(async {
+            sqlx::query(sql)
+                .bind(id)
+                .execute(&self.pool)
+                .await
+                .map(|_| ())
+                .map_err(|e| format!("Failed to update favorites: {}", e))
+        })
+    }
+
+    /// Replace a favorite's note; blank clears it
+    pub fn set_favorite_note(&self, message_id: i32, note: &str) -> Result<(), String> {
+        let note = Some(note.trim()).filter(|n| !n.is_empty());
+        self.runtime.block_on(async {
+            sqlx::query("UPDATE favorites SET note = ?2 WHERE message_id = ?1")
+                .bind(message_id)
+                .bind(note)
+                .execute(&self.pool)
+                .await
+                .map(|_| ())
+                .map_err(|e| format!("Failed to save note: {}", e))
+        })
+    }
+
+    /// Every `(session_id, tag)` pair
+    pub fn fetch_session_tags(&self) -> Result<Vec<(String, String)>, String> {
+        self.runtime.block_on(async {
+            sqlx::query_as("SELECT session_id, tag FROM session_tags ORDER BY session_id, tag")
+                .fetch_all(&self.pool)
+                .await
+                .map_err(|e| format!("Query failed: {}", e))
+        })
+    }
+
+    /// Tag a session; tagging it twice is a no-op
+    pub fn add_session_tag(&self, session_id: &str, tag: &str) -> Result<(), String> {
+        self.update_session_tag("INSERT OR IGNORE INTO session_tags (session_id, tag) VALUES (?1, ?2)", session_id, tag)
+    }
+
+    pub fn remove_session_tag(&self, session_id: &str, tag: &str) -> Result<(), String> {
+        self.update_session_tag("DELETE FROM session_tags WHERE session_id = ?1 AND tag = ?2", session_id, tag)
+    }
+
+    fn update_session_tag(&self, sql: &str, session_id: &str, tag: &str) -> Result<(), String> {
+        self.runtime.block_on(async {
+            sqlx::query(sql)
+                .bind(session_id)
+                .bind(tag)
+                .execute(&self.pool)
+                .await
+                .map(|_| ())
+                .map_err(|e| format!("Failed to update tags: {}", e))
+        })
+    }
+
     /// Fetch importance scoring statistics
     pub fn fetch_importance_stats(&self) -> Result<ImportanceStats, String> {
         self.runtime.block_on(async {
@@ -401,8 +748,384 @@ impl DbClient {
 
 }
 
+/// Link each message to the previous loaded one in its session. `sequence`
+/// holds each node's sequence number; a jump between neighbours means the
+/// messages in between fell outside the load, so that link is a gap edge.
+fn session_chain_edges(nodes: &[GraphNode], sequence: &[i32]) -> Vec<GraphEdge> {
+    let mut edges = Vec::new();
+    let mut prev: HashMap<&str, usize> = HashMap::new();
+    for (i, node) in nodes.iter().enumerate() {
+        if let Some(&p) = prev.get(node.session_id.as_str()) {
+            edges.push(chain_edge(nodes[p].id.clone(), sequence[p], node.id.clone(), sequence[i], node.session_id.clone(), node.timestamp.clone()));
+        }
+        prev.insert(node.session_id.as_str(), i);
+    }
+    edges
+}
+
+/// Link from one loaded message to the next loaded one in its session: a gap
+/// edge when messages between them weren't loaded. `timestamp` is the target's.
+fn chain_edge(source: String, source_seq: i32, target: String, target_seq: i32, session_id: String, timestamp: Option<String>) -> GraphEdge {
+    let missing = target_seq.saturating_sub(source_seq).saturating_sub(1);
+    if missing > 0 {
+        GraphEdge::gap(source, target, session_id, missing as u32)
+    } else {
+        GraphEdge::conversation(source, target, session_id, timestamp)
+    }
+}
+
+/// Oldest message a progressive load has delivered in a session; the next
+/// chunk's newest message in that session links to it
+#[derive(Debug, Clone)]
+struct SessionHead {
+    id: String,
+    sequence: i32,
+    timestamp: Option<String>,
+}
+
+/// One progressive-load chunk of `(node, sequence_num)` pairs, laid out in
+/// session and sequence order as `fetch_graph` returns them. Besides the
+/// chains inside the chunk, each session's newest message here links to its
+/// head in `heads`, which then moves to the session's oldest message here.
+fn chunk_graph(mut fetched: Vec<(GraphNode, i32)>, heads: &mut HashMap<String, SessionHead>) -> GraphData {
+    fetched.sort_by(|(na, sa), (nb, sb)| na.session_id.cmp(&nb.session_id).then(sa.cmp(sb)));
+    let (nodes, sequence): (Vec<GraphNode>, Vec<i32>) = fetched.into_iter().unzip();
+    let mut edges = session_chain_edges(&nodes, &sequence);
+    let mut oldest = 0;
+    for (i, node) in nodes.iter().enumerate() {
+        if nodes[oldest].session_id != node.session_id {
+            oldest = i;
+        }
+        if nodes.get(i + 1).is_some_and(|next| next.session_id == node.session_id) {
+            continue;
+        }
+        // Last of the session here. A head older than it means timestamps and
+        // sequence disagree; leave that pair unlinked rather than point backwards.
+        if let Some(head) = heads.get(&node.session_id).filter(|h| h.sequence > sequence[i]) {
+            edges.push(chain_edge(node.id.clone(), sequence[i], head.id.clone(), head.sequence, node.session_id.clone(), head.timestamp.clone()));
+        }
+        heads.insert(node.session_id.clone(), SessionHead {
+            id: nodes[oldest].id.clone(),
+            sequence: sequence[oldest],
+            timestamp: nodes[oldest].timestamp.clone(),
+        });
+    }
+    GraphData::new(nodes, edges)
+}
+
 impl Default for DbClient {
     fn default() -> Self {
-        Self::new().expect("Failed to create database client")
+        Self::new(None).expect("Failed to create database client")
+    }
+}
+
+/// Load condition binding `?1`: the session id when loading one session,
+/// otherwise the range in hours
+fn range_clause(session_id: Option<&str>) -> &'static str {
+    if session_id.is_some() {
+        "m.session_id = ?1"
+    } else {
+        "m.timestamp >= datetime('now', '-' || CAST(?1 AS INTEGER) || ' hours')"
+    }
+}
+
+/// Normalized `~/...` project string for a session's working directory
+fn project_of(cwd: &str) -> String {
+    match dirs::home_dir() {
+        Some(home) => cwd.replace(&format!("{}/", home.display()), "~/"),
+        None => cwd.to_string(),
+    }
+}
+
+/// Sessions a load may include, as a condition on `s.cwd`. Project filters
+/// match the normalized project string, which SQL can't compute, so they are
+/// resolved against the distinct session directories first.
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum CwdScope {
+    All,
+    Only(Vec<String>),
+    Except(Vec<String>),
+}
+
+impl CwdScope {
+    /// Resolve a project selection (empty for all) and exclusion patterns
+    /// against the session directories in the database
+    fn resolve(cwds: &[String], projects: &[String], excluded: &[String]) -> Self {
+        if !projects.is_empty() {
+            return CwdScope::Only(cwds.iter()
+                .filter(|cwd| {
+                    let project = project_of(cwd);
+                    projects.contains(&project) && !exclude::is_excluded(&project, excluded)
+                })
+                .cloned()
+                .collect());
+        }
+        let skipped: Vec<String> = cwds.iter()
+            .filter(|cwd| exclude::is_excluded(&project_of(cwd), excluded))
+            .cloned()
+            .collect();
+        if skipped.is_empty() {
+            CwdScope::All
+        } else {
+            CwdScope::Except(skipped)
+        }
+    }
+
+    /// SQL condition with numbered placeholders from `?{first}`
+    fn clause(&self, first: usize) -> String {
+        match self {
+            CwdScope::All => "1".to_string(),
+            CwdScope::Only(cwds) | CwdScope::Except(cwds) => {
+                let placeholders: Vec<String> = (first..first + cwds.len()).map(|i| format!("?{}", i)).collect();
+                let operator = if matches!(self, CwdScope::Only(_)) { "IN" } else { "NOT IN" };
+                format!("s.cwd {} ({})", operator, placeholders.join(", "))
+            }
+        }
+    }
+
+    /// Values for the placeholders in `clause`, in order
+    fn params(&self) -> &[String] {
+        match self {
+            CwdScope::All => &[],
+            CwdScope::Only(cwds) | CwdScope::Except(cwds) => cwds,
+        }
+    }
+}
+
+/// Convert a message row into a graph node; `has_tool_usage` is filled in separately
+fn message_node(row: MessageRow, content_lazy: bool) -> GraphNode {
+    let content = row.content.unwrap_or_default();
+
+    let role = match row.role.as_str() {
+        "user" => Role::User,
+        "assistant" => Role::Assistant,
+        "polecat" | "witness" | "mayor" | "crew" | "refinery" => Role::Agent,
+        _ => Role::User,
+    };
+
+    let content_preview = if content.chars().count() > 100 {
+        format!("{}...", content.chars().take(100).collect::<String>())
+    } else {
+        content.clone()
+    };
+
+    let project = project_of(&row.cwd.unwrap_or_default());
+
+    GraphNode {
+        id: ids::message(row.id as i64),
+        role,
+        content_preview,
+        full_content: if content_lazy { None } else { Some(content) },
+        session_short: row.session_id[..8.min(row.session_id.len())].to_string(),
+        session_id: row.session_id,
+        project,
+        timestamp: row.timestamp,
+        importance_score: row.importance_score.map(|v| v as f32),
+        importance_reason: row.importance_reason,
+        output_tokens: row.token_count,
+        input_tokens: row.input_tokens,
+        cache_read_tokens: row.cache_read_tokens,
+        cache_creation_tokens: row.cache_creation_tokens,
+        has_tool_usage: false,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn node(id: &str, session: &str) -> GraphNode {
+        GraphNode {
+            project: "~/proj".to_string(),
+            ..GraphNode::stub(id, session)
+        }
+    }
+
+    fn links(edges: &[GraphEdge]) -> Vec<(&str, &str, Option<u32>)> {
+        edges.iter().map(|e| (e.source.as_str(), e.target.as_str(), e.gap)).collect()
+    }
+
+    #[test]
+    fn test_contiguous_session_has_no_gaps() {
+        let nodes = vec![node("1", "s1"), node("2", "s1"), node("3", "s1"), node("9", "s2")];
+        let edges = session_chain_edges(&nodes, &[0, 1, 2, 0]);
+        assert_eq!(links(&edges), vec![("1", "2", None), ("2", "3", None)]);
+    }
+
+    #[test]
+    fn test_session_straddling_the_window_on_both_ends() {
+        // s1 runs 0..=9; the load caught 2-3 and 6-7, so its first two, middle
+        // two and last two messages are outside. Only the middle gap joins
+        // loaded fragments; the missing ends have nothing to link to.
+        let nodes = vec![
+            node("a2", "s1"), node("a3", "s1"),
+            node("a6", "s1"), node("a7", "s1"),
+            node("b0", "s2"), node("b1", "s2"),
+        ];
+        let edges = session_chain_edges(&nodes, &[2, 3, 6, 7, 0, 1]);
+        assert_eq!(links(&edges), vec![
+            ("a2", "a3", None),
+            ("a3", "a6", Some(2)),
+            ("a6", "a7", None),
+            ("b0", "b1", None),
+        ]);
+        assert_eq!(edges[1].session_id, "s1");
+    }
+
+    #[test]
+    fn test_session_tag_crud() {
+        let path = std::env::temp_dir().join(format!("dashboard-tags-{}.db", std::process::id()));
+        let db = DbClient::new(Some(path.to_str().unwrap())).unwrap();
+        db.add_session_tag("s1", "demo-prep").unwrap();
+        db.add_session_tag("s1", "billing-bug").unwrap();
+        db.add_session_tag("s1", "demo-prep").unwrap();
+        db.add_session_tag("s2", "billing-bug").unwrap();
+        assert_eq!(db.fetch_session_tags().unwrap(), vec![
+            ("s1".to_string(), "billing-bug".to_string()),
+            ("s1".to_string(), "demo-prep".to_string()),
+            ("s2".to_string(), "billing-bug".to_string()),
+        ]);
+
+        db.remove_session_tag("s1", "billing-bug").unwrap();
+        db.remove_session_tag("s3", "missing").unwrap();
+        assert_eq!(db.fetch_session_tags().unwrap(), vec![
+            ("s1".to_string(), "demo-prep".to_string()),
+            ("s2".to_string(), "billing-bug".to_string()),
+        ]);
+        drop(db);
+        for suffix in ["", "-wal", "-shm"] {
+            let _ = std::fs::remove_file(format!("{}{}", path.display(), suffix));
+        }
+    }
+
+    #[test]
+    fn test_messages_fetch_by_either_id_form() {
+        let path = std::env::temp_dir().join(format!("dashboard-ids-{}.db", std::process::id()));
+        let db = DbClient::new(Some(path.to_str().unwrap())).unwrap();
+        db.runtime.block_on(async {
+            for sql in [
+                "INSERT INTO sessions (session_id, cwd) VALUES ('s1', '/tmp/proj')",
+                "INSERT INTO messages (id, session_id, role, content, sequence_num) VALUES (7, 's1', 'user', 'hi', 0)",
+                "INSERT INTO messages (id, session_id, role, content, sequence_num) VALUES (8, 's1', 'assistant', 'ok', 1)",
+                "INSERT INTO tool_usages (message_id, tool_name) VALUES (8, 'Read')",
+            ] {
+                sqlx::query(sql).execute(&db.pool).await.unwrap();
+            }
+        });
+
+        // Prefixed and bare ids both resolve; synthetic ids are skipped
+        let ids = ["msg:7".to_string(), "8".to_string(), "topic:x".to_string()];
+        let nodes = db.fetch_messages(&ids, false).unwrap();
+        let found: Vec<(&str, bool)> = nodes.iter().map(|n| (n.id.as_str(), n.has_tool_usage)).collect();
+        assert_eq!(found, vec![("msg:7", false), ("msg:8", true)]);
+
+        db.set_favorite("msg:8", true).unwrap();
+        assert_eq!(db.fetch_favorites().unwrap()[0].node_id(), "msg:8");
+        assert!(db.set_favorite("topic:x", true).is_err());
+        drop(db);
+        for suffix in ["", "-wal", "-shm"] {
+            let _ = std::fs::remove_file(format!("{}{}", path.display(), suffix));
+        }
+    }
+
+    #[test]
+    fn test_project_filters_apply_in_the_query() {
+        let path = std::env::temp_dir().join(format!("dashboard-exclude-{}.db", std::process::id()));
+        let db = DbClient::new(Some(path.to_str().unwrap())).unwrap();
+        db.runtime.block_on(async {
+            for sql in [
+                "INSERT INTO sessions (session_id, cwd) VALUES ('s1', '/tmp/keep')",
+                "INSERT INTO sessions (session_id, cwd) VALUES ('s2', '/tmp/secret/api')",
+                "INSERT INTO messages (id, session_id, role, content, sequence_num) VALUES (1, 's1', 'user', 'hi', 0)",
+                "INSERT INTO messages (id, session_id, role, content, sequence_num) VALUES (2, 's2', 'user', 'hidden', 0)",
+                "INSERT INTO messages (id, session_id, role, content, sequence_num) VALUES (3, 's2', 'assistant', 'hidden', 1)",
+            ] {
+                sqlx::query(sql).execute(&db.pool).await.unwrap();
+            }
+        });
+
+        let excluded = vec!["/tmp/secret/".to_string()];
+        let scope = db.runtime.block_on(db.cwd_scope(&[], &excluded)).unwrap();
+        assert_eq!(scope, CwdScope::Except(vec!["/tmp/secret/api".to_string()]));
+        assert_eq!(db.preview_load(24.0, None, &[], &excluded).unwrap().messages, 1);
+        assert_eq!(db.preview_load(24.0, None, &[], &[]).unwrap().messages, 3);
+        let data = db.fetch_graph(24.0, None, &[], &excluded, false).unwrap();
+        let ids: Vec<&str> = data.nodes.iter().map(|n| n.id.as_str()).collect();
+        assert_eq!(ids, vec!["msg:1"]);
+
+        // A selection narrows the query to its sessions; exclusions still win
+        let selected = vec!["/tmp/secret/api".to_string()];
+        let scope = db.runtime.block_on(db.cwd_scope(&selected, &[])).unwrap();
+        assert_eq!(scope, CwdScope::Only(selected.clone()));
+        assert_eq!(db.preview_load(24.0, None, &selected, &[]).unwrap().messages, 2);
+        let data = db.fetch_graph(24.0, None, &selected, &[], false).unwrap();
+        let ids: Vec<&str> = data.nodes.iter().map(|n| n.id.as_str()).collect();
+        assert_eq!(ids, vec!["msg:2", "msg:3"]);
+        assert!(db.fetch_graph(24.0, None, &selected, &excluded, false).unwrap().nodes.is_empty());
+        drop(db);
+        for suffix in ["", "-wal", "-shm"] {
+            let _ = std::fs::remove_file(format!("{}{}", path.display(), suffix));
+        }
+    }
+
+    #[test]
+    fn test_chunks_link_back_to_earlier_chunks() {
+        // Newest first, as a progressive load fetches them: s1's tail arrives
+        // before its head and the sessions interleave
+        let mut heads = HashMap::new();
+        let first = chunk_graph(vec![(node("a3", "s1"), 3), (node("b1", "s2"), 1), (node("a2", "s1"), 2)], &mut heads);
+        let ids: Vec<&str> = first.nodes.iter().map(|n| n.id.as_str()).collect();
+        assert_eq!(ids, vec!["a2", "a3", "b1"]);
+        assert_eq!(links(&first.edges), vec![("a2", "a3", None)]);
+
+        // Only the new messages, with the edges joining them to the first chunk
+        let second = chunk_graph(vec![(node("b0", "s2"), 0), (node("a0", "s1"), 0), (node("c0", "s3"), 0)], &mut heads);
+        let ids: Vec<&str> = second.nodes.iter().map(|n| n.id.as_str()).collect();
+        assert_eq!(ids, vec!["a0", "b0", "c0"]);
+        assert_eq!(links(&second.edges), vec![("a0", "a2", Some(1)), ("b0", "b1", None)]);
+        assert_eq!(heads["s1"].id, "a0");
+    }
+
+    #[test]
+    fn test_progressive_load_pages_by_timestamp() {
+        let path = std::env::temp_dir().join(format!("dashboard-chunks-{}.db", std::process::id()));
+        let db = DbClient::new(Some(path.to_str().unwrap())).unwrap();
+        db.runtime.block_on(async {
+            sqlx::query("INSERT INTO sessions (session_id, cwd) VALUES ('s1', '/tmp/proj')").execute(&db.pool).await.unwrap();
+            // Messages 1-3 share a timestamp, so paging has to break ties by id
+            for (id, minutes) in [(1, 30), (2, 30), (3, 30), (4, 20), (5, 10)] {
+                sqlx::query(&format!(
+                    "INSERT INTO messages (id, session_id, role, content, sequence_num, timestamp) \
+                     VALUES ({}, 's1', 'user', 'm', {}, datetime('now', '-{} minutes'))",
+                    id, id, minutes,
+                ))
+                .execute(&db.pool)
+                .await
+                .unwrap();
+            }
+        });
+
+        let mut chunks = Vec::new();
+        db.fetch_graph_progressive(24.0, None, &[], &[], false, 2, |chunk| {
+            chunks.push(chunk);
+            true
+        })
+        .unwrap();
+        let pages: Vec<Vec<&str>> = chunks.iter().map(|c| c.data.nodes.iter().map(|n| n.id.as_str()).collect()).collect();
+        assert_eq!(pages, vec![vec!["msg:4", "msg:5"], vec!["msg:2", "msg:3"], vec!["msg:1"]]);
+        assert!(chunks.iter().map(|c| c.done).eq([false, false, true]));
+        assert_eq!((chunks[2].loaded, chunks[2].expected), (5, 5));
+
+        // Together the chunks make the same graph as a single fetch
+        let mut edges: Vec<(&str, &str, Option<u32>)> = chunks.iter().flat_map(|c| links(&c.data.edges)).collect();
+        let full = db.fetch_graph(24.0, None, &[], &[], false).unwrap();
+        let mut expected = links(&full.edges);
+        expected.sort();
+        edges.sort();
+        assert_eq!(edges, expected);
+        drop(db);
+        for suffix in ["", "-wal", "-shm"] {
+            let _ = std::fs::remove_file(format!("{}{}", path.display(), suffix));
+        }
     }
 }
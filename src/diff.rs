@@ -0,0 +1,165 @@
+//! Diff of two timeline windows ("what changed since yesterday?"). Window B
+//! is the current timeline window; window A defaults to the same span just
+//! before it. Each node is classed by which windows it falls in.
+
+use crate::cost::TokenTotals;
+use crate::graph::types::{GraphData, TimelineState};
+use std::collections::HashSet;
+
+/// Where a node falls across the two windows
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DiffClass {
+    /// Only in B: drawn normally
+    Added,
+    /// Only in A: drawn ghosted
+    Removed,
+    /// In both (overlapping windows): drawn dimmed
+    Kept,
+}
+
+/// Counts for the summary strip
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct DiffSummary {
+    /// Sessions with messages in B but none in A
+    pub new_sessions: usize,
+    /// Messages only in B
+    pub new_messages: usize,
+    /// Messages only in A
+    pub old_messages: usize,
+    /// Tokens in B minus tokens in A
+    pub token_delta: i64,
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct GraphDiff {
+    /// Indexed like `GraphData::nodes`; None for nodes in neither window
+    pub class: Vec<Option<DiffClass>>,
+    pub summary: DiffSummary,
+}
+
+impl GraphDiff {
+    pub fn class_of(&self, index: usize) -> Option<DiffClass> {
+        self.class.get(index).copied().flatten()
+    }
+}
+
+/// The span of `b` immediately before it
+pub fn previous_window(b: (f64, f64)) -> (f64, f64) {
+    let span = (b.1 - b.0).max(0.0);
+    (b.0 - span, b.0)
+}
+
+/// Class every node against windows `a` and `b` (epoch seconds, inclusive)
+pub fn compute(data: &GraphData, timeline: &TimelineState, a: (f64, f64), b: (f64, f64)) -> GraphDiff {
+    let mut in_a = vec![false; data.nodes.len()];
+    let mut in_b = vec![false; data.nodes.len()];
+    for (window, flags) in [(a, &mut in_a), (b, &mut in_b)] {
+        for &idx in &timeline.sorted_indices[timeline.window_range(window.0, window.1)] {
+            if let Some(flag) = flags.get_mut(idx) {
+                *flag = true;
+            }
+        }
+    }
+
+    let mut summary = DiffSummary::default();
+    let mut tokens_a = TokenTotals::default();
+    let mut tokens_b = TokenTotals::default();
+    let mut sessions_a: HashSet<&str> = HashSet::new();
+    let mut sessions_b: HashSet<&str> = HashSet::new();
+    let class: Vec<Option<DiffClass>> = data.nodes.iter()
+        .enumerate()
+        .map(|(idx, node)| {
+            if in_a[idx] {
+                tokens_a.add_node(node);
+                sessions_a.insert(node.session_id.as_str());
+            }
+            if in_b[idx] {
+                tokens_b.add_node(node);
+                sessions_b.insert(node.session_id.as_str());
+            }
+            match (in_a[idx], in_b[idx]) {
+                (false, true) => {
+                    summary.new_messages += 1;
+                    Some(DiffClass::Added)
+                }
+                (true, false) => {
+                    summary.old_messages += 1;
+                    Some(DiffClass::Removed)
+                }
+                (true, true) => Some(DiffClass::Kept),
+                (false, false) => None,
+            }
+        })
+        .collect();
+    summary.new_sessions = sessions_b.difference(&sessions_a).count();
+    summary.token_delta = tokens_b.total() as i64 - tokens_a.total() as i64;
+
+    GraphDiff { class, summary }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::graph::types::{GraphNode, Role};
+
+    fn node(id: &str, session: &str, time: f64, tokens: i32) -> GraphNode {
+        GraphNode {
+            role: Role::Assistant,
+            project: "~/proj".to_string(),
+            timestamp: chrono::DateTime::from_timestamp(time as i64, 0).map(|t| t.to_rfc3339()),
+            output_tokens: Some(tokens),
+            ..GraphNode::stub(id, session)
+        }
+    }
+
+    fn timeline(data: &GraphData) -> TimelineState {
+        let mut timed: Vec<(usize, f64)> = data.nodes.iter()
+            .enumerate()
+            .filter_map(|(i, n)| Some((i, n.timestamp_secs()?)))
+            .collect();
+        timed.sort_by(|a, b| a.1.total_cmp(&b.1));
+        TimelineState {
+            sorted_indices: timed.iter().map(|(i, _)| *i).collect(),
+            timestamps: timed.iter().map(|(_, t)| *t).collect(),
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn test_previous_window() {
+        assert_eq!(previous_window((100.0, 160.0)), (40.0, 100.0));
+        assert_eq!(previous_window((100.0, 90.0)), (100.0, 100.0));
+    }
+
+    #[test]
+    fn test_classes_and_summary() {
+        let day = 86_400.0;
+        let data = GraphData::new(vec![
+            node("old", "s1", day * 10.5, 100),
+            node("edge", "s1", day * 11.0, 10),
+            node("new", "s1", day * 11.5, 300),
+            node("fresh", "s2", day * 11.6, 50),
+            node("ancient", "s0", day * 2.0, 1000),
+        ], Vec::new());
+        let timeline = timeline(&data);
+
+        let b = (day * 11.0, day * 12.0);
+        let diff = compute(&data, &timeline, previous_window(b), b);
+        let classes: Vec<Option<DiffClass>> = (0..5).map(|i| diff.class_of(i)).collect();
+        assert_eq!(classes, vec![
+            Some(DiffClass::Removed),
+            // On the shared boundary, so in both
+            Some(DiffClass::Kept),
+            Some(DiffClass::Added),
+            Some(DiffClass::Added),
+            None,
+        ]);
+        assert_eq!(diff.summary, DiffSummary {
+            new_sessions: 1,
+            new_messages: 2,
+            old_messages: 1,
+            token_delta: (10 + 300 + 50) - (100 + 10),
+        });
+        assert_eq!(diff.class_of(99), None);
+    }
+}
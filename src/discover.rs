@@ -0,0 +1,150 @@
+//! Nodes pulled in by "Find similar": a message's nearest neighbours across
+//! the whole database, linked to it by similarity edges. They survive reloads
+//! until cleared, even when they fall outside the loaded time range.
+
+use crate::graph::types::{GraphData, GraphEdge, GraphNode};
+use std::collections::{HashMap, HashSet};
+
+/// Neighbours kept per "Find similar"
+pub const TOP_K: usize = 12;
+
+/// Result of one "Find similar" run, sent back from the worker thread
+#[derive(Debug, Clone)]
+pub struct Found {
+    pub source: String,
+    /// (id, score), best first
+    pub neighbours: Vec<(String, f32)>,
+    /// Neighbours that weren't loaded, fetched by id
+    pub fetched: Vec<GraphNode>,
+}
+
+/// Everything "Find similar" has added so far
+#[derive(Debug, Clone, Default)]
+pub struct Discovered {
+    /// Fetched nodes, re-attached when a reload doesn't include them
+    pub nodes: Vec<GraphNode>,
+    /// Similarity edges from each searched node to its neighbours
+    pub edges: Vec<GraphEdge>,
+}
+
+impl Discovered {
+    pub fn is_empty(&self) -> bool {
+        self.nodes.is_empty() && self.edges.is_empty()
+    }
+
+    /// Record a run, replacing any earlier edges from the same source
+    pub fn add(&mut self, found: Found) {
+        let known: HashSet<String> = self.nodes.iter().map(|n| n.id.clone()).collect();
+        self.nodes.extend(found.fetched.into_iter().filter(|n| !known.contains(&n.id)));
+        self.edges.retain(|e| e.source != found.source);
+        for (id, score) in found.neighbours {
+            self.edges.push(GraphEdge::similarity(found.source.clone(), id, score, None));
+        }
+    }
+
+    fn contains_edge(&self, edge: &GraphEdge) -> bool {
+        edge.is_similarity
+            && edge.query_index.is_none()
+            && self.edges.iter().any(|e| e.source == edge.source && e.target == edge.target)
+    }
+
+    /// Take discovered nodes and edges back out of `data`
+    pub fn detach(&self, data: &mut GraphData) {
+        let ids: HashSet<&str> = self.nodes.iter().map(|n| n.id.as_str()).collect();
+        data.nodes.retain(|n| !ids.contains(n.id.as_str()));
+        data.edges.retain(|e| !self.contains_edge(e) && !ids.contains(e.source.as_str()) && !ids.contains(e.target.as_str()));
+    }
+}
+
+/// The `k` best-scoring ids other than `source`, best first (ties by id)
+pub fn top_k(scores: &HashMap<String, f32>, source: &str, k: usize) -> Vec<(String, f32)> {
+    let mut ranked: Vec<(String, f32)> = scores.iter()
+        .filter(|(id, score)| id.as_str() != source && score.is_finite())
+        .map(|(id, score)| (id.clone(), *score))
+        .collect();
+    ranked.sort_by(|a, b| b.1.total_cmp(&a.1).then_with(|| a.0.cmp(&b.0)));
+    ranked.truncate(k);
+    ranked
+}
+
+/// Add discovered nodes that `data` lacks, then the discovered edges whose
+/// endpoints are both present (replacing copies already in `data`)
+pub fn attach(data: &mut GraphData, discovered: &Discovered) {
+    if discovered.is_empty() {
+        return;
+    }
+    let mut present: HashSet<String> = data.nodes.iter().map(|n| n.id.clone()).collect();
+    for node in &discovered.nodes {
+        if present.insert(node.id.clone()) {
+            data.nodes.push(node.clone());
+        }
+    }
+    data.edges.retain(|e| !discovered.contains_edge(e));
+    data.edges.extend(discovered.edges.iter()
+        .filter(|e| present.contains(&e.source) && present.contains(&e.target))
+        .cloned());
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn node(id: &str) -> GraphNode {
+        GraphNode {
+            project: "~/proj".to_string(),
+            ..GraphNode::stub(id, &format!("s{}", id))
+        }
+    }
+
+    #[test]
+    fn test_top_k_excludes_source() {
+        let scores = HashMap::from([
+            ("1".to_string(), 0.99),
+            ("2".to_string(), 0.5),
+            ("3".to_string(), 0.8),
+            ("4".to_string(), 0.8),
+            ("5".to_string(), f32::NAN),
+        ]);
+        let top = top_k(&scores, "1", 3);
+        assert_eq!(top, vec![("3".to_string(), 0.8), ("4".to_string(), 0.8), ("2".to_string(), 0.5)]);
+    }
+
+    #[test]
+    fn test_attach_survives_reload_and_detaches() {
+        let mut discovered = Discovered::default();
+        discovered.add(Found {
+            source: "1".to_string(),
+            neighbours: vec![("2".to_string(), 0.9), ("9".to_string(), 0.7)],
+            fetched: vec![node("9")],
+        });
+
+        // A reload without node 9: it comes back, with both edges
+        let mut data = GraphData::new(vec![node("1"), node("2")], Vec::new());
+        attach(&mut data, &discovered);
+        attach(&mut data, &discovered);
+        let ids: Vec<&str> = data.nodes.iter().map(|n| n.id.as_str()).collect();
+        assert_eq!(ids, vec!["1", "2", "9"]);
+        assert_eq!(data.edges.len(), 2);
+        assert!(data.edges.iter().all(|e| e.is_similarity && e.source == "1"));
+
+        // The source dropped out of the load: its edges go too
+        let mut data = GraphData::new(vec![node("2")], Vec::new());
+        attach(&mut data, &discovered);
+        assert_eq!(data.nodes.len(), 2);
+        assert!(data.edges.is_empty());
+
+        // Searching again from the same node replaces its edges
+        discovered.add(Found { source: "1".to_string(), neighbours: vec![("2".to_string(), 0.6)], fetched: vec![node("9")] });
+        assert_eq!(discovered.nodes.len(), 1);
+        assert_eq!(discovered.edges.len(), 1);
+
+        let mut data = GraphData::new(vec![node("1"), node("2")], vec![GraphEdge::similarity("2".into(), "1".into(), 0.4, Some(0))]);
+        attach(&mut data, &discovered);
+        discovered.detach(&mut data);
+        let ids: Vec<&str> = data.nodes.iter().map(|n| n.id.as_str()).collect();
+        assert_eq!(ids, vec!["1", "2"]);
+        // Query edges stay
+        assert_eq!(data.edges.len(), 1);
+        assert_eq!(data.edges[0].query_index, Some(0));
+    }
+}
@@ -0,0 +1,127 @@
+//! Project exclusion patterns. Excluded projects never reach the graph: the
+//! database load skips them and `GraphState::load` drops any that slip through.
+//!
+//! Patterns match the normalized `~/...` project string. A pattern without
+//! wildcards is an exact path; a trailing `/` also takes everything under it.
+//! `*` and `?` stay within one path segment, `**` spans any number of them.
+
+/// True when `project` matches any of `patterns`
+pub fn is_excluded(project: &str, patterns: &[String]) -> bool {
+    patterns.iter().any(|p| matches(p, project))
+}
+
+/// Match one pattern against a project path
+pub fn matches(pattern: &str, project: &str) -> bool {
+    let pattern = pattern.trim();
+    if pattern.is_empty() {
+        return false;
+    }
+    let project = project.trim_end_matches('/');
+    if let Some(dir) = pattern.strip_suffix('/') {
+        let dir = dir.trim_end_matches('/');
+        return glob(dir, project) || glob(&format!("{}/**", dir), project);
+    }
+    glob(pattern, project)
+}
+
+fn glob(pattern: &str, path: &str) -> bool {
+    let pattern: Vec<&str> = pattern.split('/').collect();
+    let path: Vec<&str> = path.split('/').collect();
+    match_segments(&pattern, &path)
+}
+
+fn match_segments(pattern: &[&str], path: &[&str]) -> bool {
+    match pattern.split_first() {
+        None => path.is_empty(),
+        Some((&"**", rest)) => (0..=path.len()).any(|skip| match_segments(rest, &path[skip..])),
+        Some((segment, rest)) => match path.split_first() {
+            Some((name, path_rest)) => match_segment(segment, name) && match_segments(rest, path_rest),
+            None => false,
+        },
+    }
+}
+
+/// `*` and `?` within a single segment
+fn match_segment(pattern: &str, name: &str) -> bool {
+    let pattern: Vec<char> = pattern.chars().collect();
+    let name: Vec<char> = name.chars().collect();
+    let (mut p, mut n) = (0, 0);
+    // Last `*` seen and the name position it was tried at, for backtracking
+    let mut star: Option<(usize, usize)> = None;
+    while n < name.len() {
+        match pattern.get(p) {
+            Some('*') => {
+                star = Some((p, n));
+                p += 1;
+            }
+            Some(&c) if c == '?' || c == name[n] => {
+                p += 1;
+                n += 1;
+            }
+            _ => match star {
+                Some((star_p, star_n)) => {
+                    p = star_p + 1;
+                    n = star_n + 1;
+                    star = Some((star_p, star_n + 1));
+                }
+                None => return false,
+            },
+        }
+    }
+    pattern[p..].iter().all(|&c| c == '*')
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_exact_paths() {
+        assert!(matches("~/scratch", "~/scratch"));
+        assert!(!matches("~/scratch", "~/scratch/tmp"));
+        assert!(!matches("~/scratch", "~/scratchpad"));
+        assert!(!matches("", "~/scratch"));
+        assert!(matches("  ~/scratch ", "~/scratch"));
+    }
+
+    #[test]
+    fn test_trailing_slash_takes_the_subtree() {
+        assert!(matches("~/clients/", "~/clients"));
+        assert!(matches("~/clients/", "~/clients/acme"));
+        assert!(matches("~/clients/", "~/clients/acme/api"));
+        assert!(!matches("~/clients/", "~/clientside"));
+        assert!(matches("~/work/*/", "~/work/secret/repo"));
+    }
+
+    #[test]
+    fn test_single_segment_wildcards() {
+        assert!(matches("~/tmp/*", "~/tmp/a"));
+        assert!(!matches("~/tmp/*", "~/tmp/a/b"));
+        assert!(!matches("~/tmp/*", "~/tmp"));
+        assert!(matches("~/proj-?", "~/proj-1"));
+        assert!(!matches("~/proj-?", "~/proj-12"));
+        assert!(matches("~/*-scratch", "~/foo-scratch"));
+        assert!(matches("~/a*b*c", "~/aXbYbZc"));
+    }
+
+    #[test]
+    fn test_double_star_spans_segments() {
+        assert!(matches("~/**/secret", "~/secret"));
+        assert!(matches("~/**/secret", "~/a/b/secret"));
+        assert!(!matches("~/**/secret", "~/a/secret/x"));
+        assert!(matches("**/node_modules/**", "~/app/node_modules/pkg"));
+        assert!(matches("**/node_modules/**", "~/app/node_modules"));
+        assert!(matches("~/Documents/**", "~/Documents"));
+        assert!(matches("~/Documents/**", "~/Documents/GitHub/x"));
+        assert!(!matches("~/Documents/**", "~/Downloads"));
+    }
+
+    #[test]
+    fn test_is_excluded_checks_every_pattern() {
+        let patterns = vec!["~/scratch".to_string(), "~/clients/".to_string()];
+        assert!(is_excluded("~/clients/acme", &patterns));
+        assert!(is_excluded("~/scratch", &patterns));
+        assert!(!is_excluded("~/Documents/app", &patterns));
+        assert!(!is_excluded("~/scratch", &[]));
+    }
+}
@@ -0,0 +1,293 @@
+//! Headless PNG export (`--headless-export`), for cron jobs and CI.
+//!
+//! Loads data and runs the layout without a window, then rasterizes a
+//! `graph::scene::Scene` with a small anti-aliased software renderer.
+
+use crate::cli::CliArgs;
+use crate::db::{self, DbClient};
+use crate::graph::scene::{self, Scene, SceneStyle};
+use crate::graph::types::ColorMode;
+use crate::graph::{community, ForceLayout, GraphState};
+use crate::settings::Settings;
+use crate::theme::{self, Theme};
+use crate::theme_file;
+use eframe::egui::{self, Color32, Pos2, Vec2};
+use image::RgbaImage;
+
+/// Output size when `--size` isn't given
+pub const DEFAULT_SIZE: (u32, u32) = (1600, 1000);
+
+/// Layout iterations when `--iterations` isn't given
+pub const DEFAULT_ITERATIONS: u32 = 500;
+
+/// Blank border around the graph, as a fraction of the shorter image side
+const MARGIN_FRACTION: f32 = 0.04;
+
+/// Thinnest edge drawn, in pixels (zoomed-out graphs would otherwise vanish)
+const MIN_EDGE_WIDTH: f32 = 0.75;
+
+/// Load the graph, lay it out and write a PNG to `path`.
+/// Returns the number of nodes drawn.
+pub fn run_headless(args: &CliArgs, path: &str) -> Result<usize, String> {
+    let settings = Settings::load();
+    let hours = args.hours.unwrap_or(settings.time_range_hours);
+
+    // DbClient creates missing databases; an export from nothing is an error
+    let db_file = db::db_path(args.db_path.as_deref());
+    if !std::path::Path::new(&db_file).exists() {
+        return Err(format!("Database not found: {}", db_file));
+    }
+    let db = DbClient::new(args.db_path.as_deref())?;
+    let data = db.fetch_graph(hours, args.session.as_deref(), &[], &settings.excluded_projects, false)?;
+    if data.nodes.is_empty() {
+        return Err(format!("No messages in the last {} hours", hours));
+    }
+
+    // Colors: same palette resolution as the app (System falls back to dark)
+    if let Err(e) = theme_file::load_and_install() {
+        tracing::warn!("{}", e);
+    }
+    theme::set_colorblind_safe(settings.colorblind_safe);
+    theme::set_high_contrast(settings.high_contrast);
+    let egui_theme = match settings.theme {
+        Theme::Light => egui::Theme::Light,
+        Theme::Dark | Theme::System => egui::Theme::Dark,
+    };
+    let palette = theme::palette(egui_theme);
+
+    let mut graph = GraphState::new();
+    graph.color_mode = settings.color_mode;
+    graph.shading = palette.shading;
+    graph.is_light = egui_theme == egui::Theme::Light;
+    graph.colorblind_safe = settings.colorblind_safe;
+    graph.temporal_attraction_enabled = settings.temporal_attraction_enabled;
+    graph.temporal_window_secs = (settings.temporal_window_mins * 60.0) as f64;
+    graph.max_temporal_edges = settings.max_temporal_edges;
+    graph.max_temporal_neighbors = settings.max_temporal_neighbors;
+    graph.physics_enabled = true;
+    let bounds = egui::Rect::from_center_size(Pos2::new(400.0, 300.0), Vec2::new(600.0, 400.0));
+    graph.load(data, bounds);
+    if graph.color_mode == ColorMode::Community {
+        graph.communities = Some(community::detect_graph(&graph.data.nodes, &graph.data.edges));
+    }
+
+    let layout = ForceLayout {
+        repulsion: settings.repulsion,
+        attraction: settings.attraction,
+        centering: settings.centering,
+        momentum: settings.momentum,
+        temporal_strength: settings.temporal_strength,
+        directed_stiffness: settings.directed_stiffness,
+        recency_centering: settings.recency_centering,
+        similarity_strength: settings.proximity_strength,
+        similarity_stiffness: settings.proximity_stiffness,
+        ..Default::default()
+    };
+    let iterations = args.layout_iterations.unwrap_or(DEFAULT_ITERATIONS);
+    for _ in 0..iterations {
+        layout.step(&mut graph, bounds.center(), None, None, &[]);
+        if layout.is_settled(&graph, None) {
+            break;
+        }
+    }
+
+    let style = SceneStyle {
+        node_size: settings.node_size,
+        w_importance: settings.w_importance,
+        w_tokens: settings.w_tokens,
+        max_node_multiplier: settings.max_node_multiplier,
+        temporal_edge_opacity: settings.temporal_edge_opacity,
+        proximity_edge_opacity: settings.proximity_edge_opacity,
+        edge_gamma: palette.edge_gamma,
+        stroke_scale: palette.stroke_scale,
+        edge_styles: settings.edge_styles,
+    };
+    let scene = scene::build_scene(&graph, &style, &[]);
+    let (width, height) = args.export_size.unwrap_or(DEFAULT_SIZE);
+    rasterize(&scene, width, height, palette.bg.graph)
+        .save(path)
+        .map_err(|e| format!("Failed to write {}: {}", path, e))?;
+    Ok(scene.nodes.len())
+}
+
+/// Draw a scene into an image, scaled to fit with a small margin
+pub fn rasterize(scene: &Scene, width: u32, height: u32, background: Color32) -> RgbaImage {
+    let mut canvas = Canvas::new(width, height, background);
+    let Some(bounds) = scene.bounds() else {
+        return canvas.image;
+    };
+
+    let margin = MARGIN_FRACTION * width.min(height) as f32;
+    let available = Vec2::new(width as f32 - 2.0 * margin, height as f32 - 2.0 * margin).max(Vec2::splat(1.0));
+    let scale = (available.x / bounds.width().max(1.0)).min(available.y / bounds.height().max(1.0));
+    let image_center = Vec2::new(width as f32, height as f32) / 2.0;
+    let to_px = |p: Pos2| Pos2::ZERO + (p - bounds.center()) * scale + image_center;
+
+    for edge in &scene.edges {
+        let width = (edge.width * scale).max(MIN_EDGE_WIDTH);
+        let (from, to) = (to_px(edge.from), to_px(edge.to));
+        match edge.line.pattern() {
+            Some((dash, gap)) => canvas.dashed_line(from, to, (dash * scale).max(2.0), (gap * scale).max(2.0), width, edge.color),
+            None => canvas.line(from, to, width, edge.color),
+        }
+    }
+
+    for node in &scene.nodes {
+        let center = to_px(node.pos);
+        let radius = (node.radius * scale).max(1.0);
+        canvas.fill_circle(center, radius, node.fill);
+        if let Some((inner_radius, color)) = node.inner {
+            canvas.fill_circle(center, inner_radius * scale, color);
+        }
+        let border = (theme::stroke_width::NORMAL * scale).clamp(0.5, radius);
+        canvas.stroke_circle(center, radius, border, node.border);
+    }
+
+    canvas.image
+}
+
+/// RGBA image with anti-aliased, premultiplied-alpha drawing primitives
+struct Canvas {
+    image: RgbaImage,
+}
+
+impl Canvas {
+    fn new(width: u32, height: u32, background: Color32) -> Self {
+        let [r, g, b, _] = background.to_array();
+        Self { image: RgbaImage::from_pixel(width, height, image::Rgba([r, g, b, 255])) }
+    }
+
+    /// Composite a premultiplied color over one pixel with partial coverage
+    fn blend(&mut self, x: i64, y: i64, color: Color32, coverage: f32) {
+        if x < 0 || y < 0 || x >= self.image.width() as i64 || y >= self.image.height() as i64 {
+            return;
+        }
+        let src = color.to_array();
+        let keep = 1.0 - src[3] as f32 / 255.0 * coverage;
+        let pixel = self.image.get_pixel_mut(x as u32, y as u32);
+        for (dst, src) in pixel.0.iter_mut().zip(src) {
+            *dst = (src as f32 * coverage + *dst as f32 * keep).round().clamp(0.0, 255.0) as u8;
+        }
+    }
+
+    fn fill_circle(&mut self, center: Pos2, radius: f32, color: Color32) {
+        self.circle_coverage(center, radius + 0.5, color, |distance| radius + 0.5 - distance);
+    }
+
+    fn stroke_circle(&mut self, center: Pos2, radius: f32, width: f32, color: Color32) {
+        let half = width / 2.0;
+        self.circle_coverage(center, radius + half + 0.5, color, |distance| half + 0.5 - (distance - radius).abs());
+    }
+
+    /// Visit pixels within `reach` of `center`, blending with `coverage(distance)`
+    fn circle_coverage(&mut self, center: Pos2, reach: f32, color: Color32, coverage: impl Fn(f32) -> f32) {
+        for y in (center.y - reach).floor() as i64..=(center.y + reach).ceil() as i64 {
+            for x in (center.x - reach).floor() as i64..=(center.x + reach).ceil() as i64 {
+                let distance = Pos2::new(x as f32 + 0.5, y as f32 + 0.5).distance(center);
+                let cov = coverage(distance).clamp(0.0, 1.0);
+                if cov > 0.0 {
+                    self.blend(x, y, color, cov);
+                }
+            }
+        }
+    }
+
+    /// Thick anti-aliased segment with round caps. Walks the major axis so the
+    /// cost is proportional to length × width rather than the bounding box.
+    fn line(&mut self, a: Pos2, b: Pos2, width: f32, color: Color32) {
+        let half = width / 2.0;
+        let delta = b - a;
+        if delta.length() < 1e-3 {
+            self.fill_circle(a, half, color);
+            return;
+        }
+
+        // Work in (major, minor) coordinates with the major axis increasing
+        let steep = delta.y.abs() > delta.x.abs();
+        let swap = |p: Pos2| if steep { Pos2::new(p.y, p.x) } else { p };
+        let (mut pa, mut pb) = (swap(a), swap(b));
+        if pa.x > pb.x {
+            std::mem::swap(&mut pa, &mut pb);
+        }
+        let slope = (pb.y - pa.y) / (pb.x - pa.x);
+        let reach = half + 1.0;
+        let span = reach * (1.0 + slope * slope).sqrt();
+
+        for major in (pa.x - reach).floor() as i64..=(pb.x + reach).ceil() as i64 {
+            let mx = major as f32 + 0.5;
+            let center_minor = pa.y + slope * (mx - pa.x).clamp(0.0, pb.x - pa.x);
+            for minor in (center_minor - span).floor() as i64..=(center_minor + span).ceil() as i64 {
+                let p = Pos2::new(mx, minor as f32 + 0.5);
+                let cov = (half + 0.5 - distance_to_segment(p, pa, pb)).clamp(0.0, 1.0);
+                if cov > 0.0 {
+                    let (x, y) = if steep { (minor, major) } else { (major, minor) };
+                    self.blend(x, y, color, cov);
+                }
+            }
+        }
+    }
+
+    fn dashed_line(&mut self, a: Pos2, b: Pos2, dash: f32, gap: f32, width: f32, color: Color32) {
+        let length = a.distance(b);
+        if length < 1e-3 {
+            return;
+        }
+        let dir = (b - a) / length;
+        let mut d = 0.0;
+        while d < length {
+            let end = (d + dash).min(length);
+            self.line(a + dir * d, a + dir * end, width, color);
+            d += dash + gap;
+        }
+    }
+}
+
+fn distance_to_segment(p: Pos2, a: Pos2, b: Pos2) -> f32 {
+    let ab = b - a;
+    let t = ((p - a).dot(ab) / ab.length_sq()).clamp(0.0, 1.0);
+    p.distance(a + ab * t)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::graph::scene::{LineStyle, SceneEdge, SceneNode};
+
+    #[test]
+    fn test_rasterize_draws_nodes_and_edges() {
+        let red = Color32::from_rgb(220, 40, 40);
+        let green = Color32::from_rgb(40, 200, 60);
+        let node = |x: f32| SceneNode {
+            pos: Pos2::new(x, 0.0),
+            radius: 10.0,
+            fill: red,
+            border: red,
+            inner: None,
+        };
+        let scene = Scene {
+            edges: vec![SceneEdge {
+                from: Pos2::new(-100.0, 0.0),
+                to: Pos2::new(100.0, 0.0),
+                color: green,
+                width: 2.0,
+                line: LineStyle::Solid,
+            }],
+            nodes: vec![node(-100.0), node(100.0)],
+        };
+
+        let image = rasterize(&scene, 200, 100, Color32::BLACK);
+        assert_eq!((image.width(), image.height()), (200, 100));
+        // Node centers land at the fitted ends, the edge runs between them
+        let left_node = image.get_pixel(18, 50).0;
+        assert_eq!(&left_node[..3], &[220, 40, 40]);
+        let midpoint = image.get_pixel(100, 50).0;
+        assert!(midpoint[1] > 150 && midpoint[0] < 100, "edge missing: {:?}", midpoint);
+        assert_eq!(image.get_pixel(100, 5).0, [0, 0, 0, 255]);
+    }
+
+    #[test]
+    fn test_rasterize_empty_scene_is_background() {
+        let image = rasterize(&Scene::default(), 8, 8, Color32::from_rgb(10, 20, 30));
+        assert!(image.pixels().all(|p| p.0 == [10, 20, 30, 255]));
+    }
+}
@@ -0,0 +1,131 @@
+//! Structural communities: connected components over conversation and
+//! similarity edges. Temporal edges link everything active at the same time,
+//! so they're left out; topic, vault and bypass links are derived and skipped too.
+
+use super::types::{GraphEdge, GraphNode};
+use std::collections::HashMap;
+
+/// Graphs at least this large detect communities on a worker thread
+pub const WORKER_MIN_NODES: usize = 20_000;
+
+/// Golden-ratio hue step, so neighbouring community numbers get distant hues
+const GOLDEN_HUE_STEP: f32 = 360.0 * 0.618_034;
+
+/// Community per node, numbered by size (0 is the largest)
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct Communities {
+    /// Indexed like `GraphData::nodes`
+    pub of_node: Vec<usize>,
+    /// Member count per community, descending
+    pub sizes: Vec<usize>,
+}
+
+impl Communities {
+    pub fn count(&self) -> usize {
+        self.sizes.len()
+    }
+}
+
+/// Endpoints of the edges that define communities
+pub fn structural_links(edges: &[GraphEdge]) -> Vec<(String, String)> {
+    edges.iter()
+        .filter(|e| is_structural(e))
+        .map(|e| (e.source.clone(), e.target.clone()))
+        .collect()
+}
+
+fn is_structural(edge: &GraphEdge) -> bool {
+    !edge.is_temporal && !edge.is_topic && !edge.is_obsidian && !edge.is_bypass
+}
+
+/// Union-find over `links`; node ids index into `node_ids`' order
+pub fn detect<'a>(node_ids: impl IntoIterator<Item = &'a str>, links: &[(String, String)]) -> Communities {
+    let index: HashMap<&str, usize> = node_ids.into_iter().enumerate().map(|(i, id)| (id, i)).collect();
+    let mut parent: Vec<usize> = (0..index.len()).collect();
+
+    fn find(parent: &mut [usize], mut i: usize) -> usize {
+        while parent[i] != i {
+            parent[i] = parent[parent[i]];
+            i = parent[i];
+        }
+        i
+    }
+
+    for (source, target) in links {
+        let (Some(&a), Some(&b)) = (index.get(source.as_str()), index.get(target.as_str())) else {
+            continue;
+        };
+        let (ra, rb) = (find(&mut parent, a), find(&mut parent, b));
+        if ra != rb {
+            // Lower root wins so numbering doesn't depend on edge order
+            parent[ra.max(rb)] = ra.min(rb);
+        }
+    }
+
+    let roots: Vec<usize> = (0..parent.len()).map(|i| find(&mut parent, i)).collect();
+    let mut root_sizes: HashMap<usize, usize> = HashMap::new();
+    for &root in &roots {
+        *root_sizes.entry(root).or_default() += 1;
+    }
+    // Largest first; equal sizes in order of their first node
+    let mut ordered: Vec<(usize, usize)> = root_sizes.into_iter().collect();
+    ordered.sort_by(|a, b| b.1.cmp(&a.1).then(a.0.cmp(&b.0)));
+    let number: HashMap<usize, usize> = ordered.iter().enumerate().map(|(n, &(root, _))| (root, n)).collect();
+
+    Communities {
+        of_node: roots.iter().map(|root| number[root]).collect(),
+        sizes: ordered.into_iter().map(|(_, size)| size).collect(),
+    }
+}
+
+/// Communities of the loaded nodes
+pub fn detect_graph(nodes: &[GraphNode], edges: &[GraphEdge]) -> Communities {
+    detect(nodes.iter().map(|n| n.id.as_str()), &structural_links(edges))
+}
+
+/// Hue for a community number
+pub fn hue(community: usize) -> f32 {
+    (community as f32 * GOLDEN_HUE_STEP) % 360.0
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn links(pairs: &[(&str, &str)]) -> Vec<(String, String)> {
+        pairs.iter().map(|(a, b)| (a.to_string(), b.to_string())).collect()
+    }
+
+    #[test]
+    fn test_components_numbered_by_size() {
+        let ids = ["a", "b", "c", "d", "e", "f"];
+        let communities = detect(ids, &links(&[("e", "f"), ("b", "c"), ("c", "d"), ("x", "a")]));
+        assert_eq!(communities.sizes, vec![3, 2, 1]);
+        // b-c-d is largest, then e-f; a is alone (the link to x is ignored)
+        assert_eq!(communities.of_node, vec![2, 0, 0, 0, 1, 1]);
+        assert_eq!(communities.count(), 3);
+    }
+
+    #[test]
+    fn test_temporal_and_derived_edges_excluded() {
+        let edges = vec![
+            GraphEdge::conversation("a".into(), "b".into(), "s".into(), None),
+            GraphEdge::similarity("c".into(), "d".into(), 0.9, None),
+            GraphEdge::temporal("b".into(), "c".into(), 1.0),
+            GraphEdge::topic("topic:x".into(), "a".into(), "s".into()),
+        ];
+        assert_eq!(structural_links(&edges), links(&[("a", "b"), ("c", "d")]));
+        let communities = detect(["a", "b", "c", "d", "topic:x"], &structural_links(&edges));
+        assert_eq!(communities.sizes, vec![2, 2, 1]);
+        assert_ne!(communities.of_node[1], communities.of_node[2]);
+    }
+
+    #[test]
+    fn test_hues_spread() {
+        assert_eq!(hue(0), 0.0);
+        let h1 = hue(1);
+        let h2 = hue(2);
+        assert!((h1 - h2).abs() > 60.0);
+        assert!(h1 < 360.0 && h2 < 360.0);
+    }
+}
@@ -0,0 +1,165 @@
+//! Namespaced node ids.
+//!
+//! A message is `msg:<row id>`. Everything else carries its own prefix
+//! (`topic:<key>`, `note:<path hash>`, `bead:<issue id>`, `session:<id>`,
+//! `dropped:<file>#<n>`) so it can't collide with a message and comes out
+//! the same on every reload, which keeps positions and favorites attached.
+//! Older backends send bare numeric message ids; `message_id` accepts those
+//! and `normalize_graph` rewrites them.
+
+use super::types::GraphData;
+
+pub const MESSAGE: &str = "msg:";
+pub const TOPIC: &str = "topic:";
+pub const NOTE: &str = "note:";
+pub const BEAD: &str = "bead:";
+pub const SESSION: &str = "session:";
+pub const DROPPED: &str = "dropped:";
+
+/// Node id of a stored message
+pub fn message(row_id: i64) -> String {
+    format!("{}{}", MESSAGE, row_id)
+}
+
+/// Node id of a topic, by its normalized key
+pub fn topic(key: &str) -> String {
+    format!("{}{}", TOPIC, key)
+}
+
+/// Node id of a vault note: a hash of its vault-relative path, so odd
+/// characters in file names never leak into ids
+pub fn note(path: &str) -> String {
+    format!("{}{:016x}", NOTE, fnv1a(path.as_bytes()))
+}
+
+/// Id of a bead marker, by its issue id
+pub fn bead(issue_id: &str) -> String {
+    format!("{}{}", BEAD, issue_id)
+}
+
+/// Node id of a session in the session overview
+pub fn session(session_id: &str) -> String {
+    format!("{}{}", SESSION, session_id)
+}
+
+/// Session id of a transcript dropped onto the window, by file name
+pub fn dropped_session(name: &str) -> String {
+    format!("{}{}", DROPPED, name)
+}
+
+/// Node id of the `seq`th message of a dropped transcript's session
+pub fn dropped_message(session_id: &str, seq: usize) -> String {
+    let session = session_id.strip_prefix(DROPPED).unwrap_or(session_id);
+    format!("{}{}#{}", DROPPED, session, seq)
+}
+
+/// First 8 characters of a session id for display, after any namespace
+/// prefix (a dropped transcript's would otherwise all read "dropped:")
+pub fn short_session(session_id: &str) -> &str {
+    let id = session_id.strip_prefix(DROPPED).unwrap_or(session_id);
+    match id.char_indices().nth(8) {
+        Some((end, _)) => &id[..end],
+        None => id,
+    }
+}
+
+/// Database row id behind a message node id, bare or prefixed.
+/// None for synthetic nodes.
+pub fn message_id(id: &str) -> Option<i64> {
+    let digits = id.strip_prefix(MESSAGE).unwrap_or(id);
+    if digits.is_empty() || !digits.bytes().all(|b| b.is_ascii_digit()) {
+        return None;
+    }
+    digits.parse().ok()
+}
+
+/// Prefix a bare numeric id from an older backend; anything else is kept
+pub fn normalize(id: String) -> String {
+    if !id.is_empty() && id.bytes().all(|b| b.is_ascii_digit()) {
+        format!("{}{}", MESSAGE, id)
+    } else {
+        id
+    }
+}
+
+/// Rewrite bare numeric ids in nodes and edges to `msg:` ids
+pub fn normalize_graph(data: &mut GraphData) {
+    for node in &mut data.nodes {
+        node.id = normalize(std::mem::take(&mut node.id));
+    }
+    for edge in &mut data.edges {
+        edge.source = normalize(std::mem::take(&mut edge.source));
+        edge.target = normalize(std::mem::take(&mut edge.target));
+    }
+}
+
+/// 64-bit FNV-1a, fixed across builds unlike `DefaultHasher`
+fn fnv1a(bytes: &[u8]) -> u64 {
+    bytes.iter().fold(0xcbf2_9ce4_8422_2325, |hash, &b| (hash ^ b as u64).wrapping_mul(0x0100_0000_01b3))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::graph::types::{GraphEdge, GraphNode};
+
+    fn node(id: &str) -> GraphNode {
+        GraphNode::stub(id, "s")
+    }
+
+    #[test]
+    fn test_message_id_accepts_both_forms() {
+        assert_eq!(message(123), "msg:123");
+        assert_eq!(message_id("msg:123"), Some(123));
+        assert_eq!(message_id("123"), Some(123));
+        assert_eq!(message_id("topic:123"), None);
+        assert_eq!(message_id(&topic("sqlite")), None);
+        assert_eq!(message_id("msg:"), None);
+        assert_eq!(message_id("-4"), None);
+    }
+
+    #[test]
+    fn test_note_ids_are_stable_and_distinct() {
+        assert_eq!(note("daily/2025-06-15.md"), note("daily/2025-06-15.md"));
+        assert_ne!(note("a.md"), note("b.md"));
+        assert!(note("a.md").starts_with(NOTE));
+        assert_eq!(message_id(&note("12.md")), None);
+    }
+
+    #[test]
+    fn test_synthetic_ids_are_namespaced() {
+        assert_eq!(bead("dn-0559"), "bead:dn-0559");
+        assert_eq!(session("4f2a"), "session:4f2a");
+        assert_eq!(dropped_message(&dropped_session("s.jsonl"), 3), "dropped:s.jsonl#3");
+        assert_eq!(dropped_message("s.jsonl-2", 0), "dropped:s.jsonl-2#0");
+        for id in [bead("12"), session("12"), dropped_message("12", 0)] {
+            assert_eq!(message_id(&id), None, "{}", id);
+            assert_eq!(normalize(id.clone()), id);
+        }
+    }
+
+    #[test]
+    fn test_short_session_skips_the_namespace() {
+        assert_eq!(short_session("8f2c1a9e-77b0-4c2d"), "8f2c1a9e");
+        assert_eq!(short_session("4f2a"), "4f2a");
+        assert_eq!(short_session(&dropped_session("notes.jsonl")), "notes.js");
+        assert_eq!(short_session(&dropped_session("ünïcödé-log.jsonl")), "ünïcödé-");
+    }
+
+    #[test]
+    fn test_normalize_mixed_graph() {
+        let mut data = GraphData::new(
+            vec![node("7"), node("msg:8"), node("topic:sqlite"), node(&note("a.md"))],
+            vec![
+                GraphEdge::temporal("7".into(), "msg:8".into(), 1.0),
+                GraphEdge::topic("topic:sqlite".into(), "7".into(), "s".into()),
+            ],
+        );
+        normalize_graph(&mut data);
+        let ids: Vec<&str> = data.nodes.iter().map(|n| n.id.as_str()).collect();
+        assert_eq!(&ids[..3], ["msg:7", "msg:8", "topic:sqlite"]);
+        assert_eq!(ids[3], note("a.md"));
+        assert_eq!((data.edges[0].source.as_str(), data.edges[0].target.as_str()), ("msg:7", "msg:8"));
+        assert_eq!((data.edges[1].source.as_str(), data.edges[1].target.as_str()), ("topic:sqlite", "msg:7"));
+    }
+}
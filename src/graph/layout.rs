@@ -5,11 +5,16 @@
 //! - Attraction along edges (Hooke's law)
 //! - Centering force toward graph center
 //! - Damping to settle the simulation
+//!
+//! In timeline mode, each node's x is pinned to its timestamp and forces only
+//! act vertically.
 
+use crate::log_buffer;
 use super::quadtree::Quadtree;
 use super::types::GraphState;
 use egui::{Pos2, Vec2};
 use rand::seq::SliceRandom;
+use rand::Rng;
 use std::collections::{HashMap, HashSet};
 
 /// Maximum temporal edges to process per physics frame (stochastic sampling)
@@ -18,8 +23,31 @@ const TEMPORAL_EDGES_PER_FRAME: usize = 2000;
 /// Maximum similarity edges to process per physics frame (stochastic sampling)
 const SIMILARITY_EDGES_PER_FRAME: usize = 2000;
 
+/// Attraction multiplier for bypass edges, relative to structural edges
+const BYPASS_EDGE_STRENGTH: f32 = 0.3;
+
+/// Maximum nudge applied to a node that sits exactly on top of another
+const COINCIDENT_JITTER: f32 = 1.0;
+
+/// Half-size of the square around the center where a non-finite node is re-placed
+const NAN_RECOVERY_SPREAD: f32 = 100.0;
+
+/// Which layout `ForceLayout::step` produces
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum LayoutAlgorithm {
+    /// Free 2D force-directed layout
+    #[default]
+    ForceDirected,
+    /// X pinned to timestamp along a horizontal axis; forces only move nodes vertically
+    Timeline,
+}
+
 /// Force-directed layout parameters
 pub struct ForceLayout {
+    /// Layout algorithm to run
+    pub algorithm: LayoutAlgorithm,
+    /// Width of the time axis in timeline mode (graph-space units)
+    pub timeline_width: f32,
     /// Repulsion strength between nodes
     pub repulsion: f32,
     /// Attraction strength along edges
@@ -53,6 +81,8 @@ pub struct ForceLayout {
 impl Default for ForceLayout {
     fn default() -> Self {
         Self {
+            algorithm: LayoutAlgorithm::ForceDirected,
+            timeline_width: 2000.0,
             repulsion: 10000.0,
             attraction: 0.1,
             centering: 0.0001,
@@ -75,12 +105,14 @@ impl ForceLayout {
     /// Run one iteration of the force simulation
     /// If `visible_nodes` is Some, only simulate those nodes (filtered view)
     /// `node_sizes` maps node IDs to their visual sizes (for mass-based physics)
+    /// `bypass_edges` bridge over inactive nodes and pull with reduced strength
     pub fn step(
         &self,
         state: &mut GraphState,
         center: Pos2,
         visible_nodes: Option<&HashSet<String>>,
         node_sizes: Option<&HashMap<String, f32>>,
+        bypass_edges: &[super::types::GraphEdge],
     ) {
         if !state.physics_enabled || state.data.nodes.is_empty() {
             return;
@@ -133,6 +165,19 @@ impl ForceLayout {
             })
             .collect();
 
+        // Coincident nodes get no repulsion from each other and no edge direction,
+        // so nudge exact duplicates apart before computing forces
+        let mut rng = rand::thread_rng();
+        let mut occupied: HashSet<(u32, u32)> = HashSet::with_capacity(node_ids.len());
+        for id in &node_ids {
+            if let Some(pos) = state.positions.get_mut(id) {
+                if !occupied.insert((pos.x.to_bits(), pos.y.to_bits())) {
+                    let jitter = Vec2::new(rng.gen_range(-1.0..=1.0), rng.gen_range(-1.0..=1.0));
+                    *pos += jitter * COINCIDENT_JITTER;
+                }
+            }
+        }
+
         // Repulsion using Barnes-Hut quadtree - O(n log n) instead of O(n²)
         // Only include visible nodes in the tree, with computed masses
         let positions_with_mass: Vec<(Pos2, f32)> = node_ids
@@ -178,6 +223,9 @@ impl ForceLayout {
         for edge in &regular_edges {
             self.apply_edge_force(edge, state, &local_index, &mut forces, 1.0, &node_masses);
         }
+        for edge in bypass_edges.iter().filter(is_edge_visible) {
+            self.apply_edge_force(edge, state, &local_index, &mut forces, BYPASS_EDGE_STRENGTH, &node_masses);
+        }
 
         // Stochastic sampling: process a random subset of temporal edges
         // Scale force by sampling ratio to maintain correct average force
@@ -186,7 +234,6 @@ impl ForceLayout {
             let sample_size = temporal_count.min(TEMPORAL_EDGES_PER_FRAME);
             let scale = temporal_count as f32 / sample_size as f32;
 
-            let mut rng = rand::thread_rng();
             let sampled: Vec<_> = temporal_edges
                 .choose_multiple(&mut rng, sample_size)
                 .collect();
@@ -202,7 +249,6 @@ impl ForceLayout {
             let sample_size = similarity_count.min(SIMILARITY_EDGES_PER_FRAME);
             let scale = similarity_count as f32 / sample_size as f32;
 
-            let mut rng = rand::thread_rng();
             let sampled: Vec<_> = similarity_edges
                 .choose_multiple(&mut rng, sample_size)
                 .collect();
@@ -249,6 +295,23 @@ impl ForceLayout {
             }
         }
 
+        // Timeline mode: x comes from the timestamp (nodes without one sit mid-axis)
+        let timeline_x: Option<HashMap<&String, f32>> = if self.algorithm == LayoutAlgorithm::Timeline {
+            let min_t = state.timeline.min_time;
+            let range = state.timeline.max_time - min_t;
+            Some(node_ids.iter().map(|id| {
+                let t = state.node_index.get(id)
+                    .and_then(|&idx| state.data.nodes.get(idx))
+                    .and_then(|n| n.timestamp_secs())
+                    .filter(|_| range > 0.0)
+                    .map(|ts| ((ts - min_t) / range) as f32)
+                    .unwrap_or(0.5);
+                (id, self.timeline_x(center.x, t))
+            }).collect())
+        } else {
+            None
+        };
+
         // Apply forces and update positions (only for visible nodes)
         // F = ma, so a = F/m - lighter nodes accelerate more from the same force
         for (i, id) in node_ids.iter().enumerate() {
@@ -260,18 +323,58 @@ impl ForceLayout {
                 *vel *= self.damping;
 
                 // Clamp velocity
-                if vel.length() > self.max_velocity {
-                    *vel = vel.normalized() * self.max_velocity;
+                let speed = vel.length();
+                if speed > self.max_velocity {
+                    *vel *= self.max_velocity / speed;
+                }
+
+                // Timeline mode: horizontal motion is replaced by the pinned x
+                let pinned_x = timeline_x.as_ref().and_then(|m| m.get(id).copied());
+                if pinned_x.is_some() {
+                    vel.x = 0.0;
                 }
 
                 // Update position
                 if let Some(pos) = state.positions.get_mut(id) {
                     *pos += *vel;
+                    if let Some(x) = pinned_x {
+                        pos.x = x;
+                    }
+                    // Recovery: a non-finite position would spread through every edge it touches
+                    if !pos.is_finite() || !vel.is_finite() {
+                        static NAN_LOG: log_buffer::Throttle = log_buffer::Throttle::new();
+                        if NAN_LOG.ready() {
+                            tracing::warn!("Layout: node {} had a non-finite position, re-placing it", id);
+                        }
+                        let spread = NAN_RECOVERY_SPREAD;
+                        *pos = center + Vec2::new(rng.gen_range(-spread..=spread), rng.gen_range(-spread..=spread));
+                        *vel = Vec2::ZERO;
+                    }
                 }
             }
         }
     }
 
+    /// Run up to `max_steps` steps at once, stopping early once settled (for
+    /// laying a graph out without animating it). Returns the steps taken.
+    pub fn settle(
+        &self,
+        state: &mut GraphState,
+        center: Pos2,
+        visible_nodes: Option<&HashSet<String>>,
+        node_sizes: Option<&HashMap<String, f32>>,
+        bypass_edges: &[super::types::GraphEdge],
+        max_steps: usize,
+    ) -> usize {
+        for steps in 0..max_steps {
+            if steps > 0 && self.is_settled(state, visible_nodes) {
+                return steps;
+            }
+            self.step(state, center, visible_nodes, node_sizes, bypass_edges);
+        }
+        max_steps
+    }
+
     /// Check if the simulation has settled
     /// If `visible_nodes` is Some, only check velocity of visible nodes
     pub fn is_settled(&self, state: &GraphState, visible_nodes: Option<&HashSet<String>>) -> bool {
@@ -284,6 +387,19 @@ impl ForceLayout {
         avg_velocity < 0.5
     }
 
+    /// Graph-space x of a time fraction (0 = oldest, 1 = newest) in timeline mode
+    pub fn timeline_x(&self, center_x: f32, fraction: f32) -> f32 {
+        center_x + (fraction - 0.5) * self.timeline_width
+    }
+
+    /// Inverse of `timeline_x`: the time fraction at graph-space `x`, clamped to the axis
+    pub fn timeline_fraction_at(&self, center_x: f32, x: f32) -> f32 {
+        if self.timeline_width <= 0.0 {
+            return 0.5;
+        }
+        ((x - center_x) / self.timeline_width + 0.5).clamp(0.0, 1.0)
+    }
+
     /// Apply attraction force for a single edge
     /// Uses `node_index` to map node IDs to force array indices
     /// Edge force is scaled by geometric mean of node masses (small-small edges are weak)
@@ -343,8 +459,81 @@ impl ForceLayout {
 
         let force_magnitude = self.attraction * displacement * edge_multiplier * mass_factor * scale;
 
-        let force = delta.normalized() * force_magnitude;
+        // Safe: distance is clamped non-zero, unlike delta.normalized()
+        let force = (delta / distance) * force_magnitude;
         forces[source_idx] += force;
         forces[target_idx] -= force;
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::graph::types::{GraphData, GraphEdge, GraphNode};
+
+    fn node(id: &str) -> GraphNode {
+        GraphNode {
+            project: "proj".to_string(),
+            timestamp: Some("2025-06-15T12:00:00Z".to_string()),
+            ..GraphNode::stub(id, "s1")
+        }
+    }
+
+    #[test]
+    fn test_coincident_nodes_stay_finite() {
+        let mut state = GraphState::new();
+        let edge = GraphEdge::conversation("a".into(), "b".into(), "s1".into(), None);
+        let bounds = egui::Rect::from_center_size(Pos2::new(400.0, 300.0), Vec2::new(600.0, 400.0));
+        state.load(GraphData::new(vec![node("a"), node("b")], vec![edge]), bounds);
+        let same = Pos2::new(123.0, 45.0);
+        for id in ["a", "b"] {
+            state.positions.insert(id.to_string(), same);
+            state.velocities.insert(id.to_string(), Vec2::ZERO);
+        }
+
+        let layout = ForceLayout::default();
+        for _ in 0..100 {
+            layout.step(&mut state, Pos2::new(400.0, 300.0), None, None, &[]);
+        }
+        let a = state.positions["a"];
+        let b = state.positions["b"];
+        assert!(a.is_finite() && b.is_finite(), "{:?} {:?}", a, b);
+        assert_ne!(a, b);
+    }
+
+    #[test]
+    fn test_settle_stops_once_settled() {
+        let mut state = GraphState::new();
+        let bounds = egui::Rect::from_center_size(Pos2::new(400.0, 300.0), Vec2::new(600.0, 400.0));
+        let edges = vec![
+            GraphEdge::conversation("a".into(), "b".into(), "s1".into(), None),
+            GraphEdge::conversation("b".into(), "c".into(), "s1".into(), None),
+        ];
+        state.load(GraphData::new(vec![node("a"), node("b"), node("c")], edges), bounds);
+
+        let layout = ForceLayout::default();
+        let steps = layout.settle(&mut state, Pos2::new(400.0, 300.0), None, None, &[], 5000);
+        assert!(steps < 5000, "never settled");
+        assert!(layout.is_settled(&state, None));
+
+        // Disabled physics doesn't move anything
+        state.physics_enabled = false;
+        let before = state.positions.clone();
+        layout.settle(&mut state, Pos2::new(400.0, 300.0), None, None, &[], 10);
+        assert_eq!(state.positions, before);
+    }
+
+    #[test]
+    fn test_timeline_x_round_trips() {
+        let layout = ForceLayout { timeline_width: 1000.0, ..Default::default() };
+        assert_eq!(layout.timeline_x(400.0, 0.0), -100.0);
+        assert_eq!(layout.timeline_x(400.0, 1.0), 900.0);
+        for fraction in [0.0, 0.25, 0.5, 1.0] {
+            let x = layout.timeline_x(400.0, fraction);
+            assert!((layout.timeline_fraction_at(400.0, x) - fraction).abs() < 1e-6);
+        }
+        // Off the axis clamps to its ends
+        assert_eq!(layout.timeline_fraction_at(400.0, -5000.0), 0.0);
+        assert_eq!(layout.timeline_fraction_at(400.0, 5000.0), 1.0);
+    }
+}
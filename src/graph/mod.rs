@@ -1,9 +1,13 @@
 //! Graph data structures and layout algorithms.
 
+pub mod community;
+pub mod ids;
 pub mod layout;
 pub mod quadtree;
+pub mod scene;
+pub mod spatial;
 pub mod types;
 
-pub use layout::ForceLayout;
+pub use layout::{ForceLayout, LayoutAlgorithm};
 pub use quadtree::Quadtree;
 pub use types::{GraphData, GraphEdge, GraphNode, GraphState, hsl_to_rgb};
@@ -3,7 +3,7 @@
 //! Instead of calculating repulsion between all pairs of nodes O(n²),
 //! we group distant nodes and treat them as a single center of mass.
 
-use egui::{Pos2, Vec2};
+use egui::{Pos2, Rect, Vec2};
 
 /// A node in the quadtree - either a leaf with one body, or an internal node with children
 #[derive(Debug)]
@@ -52,6 +52,18 @@ impl Bounds {
         pos.x >= self.min.x && pos.x <= self.max.x && pos.y >= self.min.y && pos.y <= self.max.y
     }
 
+    /// True when every point of this cell lies in `rect`
+    #[cfg_attr(not(test), allow(dead_code))]
+    fn inside(&self, rect: Rect) -> bool {
+        rect.min.x <= self.min.x && self.max.x <= rect.max.x && rect.min.y <= self.min.y && self.max.y <= rect.max.y
+    }
+
+    /// True when no point of this cell lies in `rect`
+    #[cfg_attr(not(test), allow(dead_code))]
+    fn disjoint(&self, rect: Rect) -> bool {
+        self.max.x < rect.min.x || self.min.x > rect.max.x || self.max.y < rect.min.y || self.min.y > rect.max.y
+    }
+
     /// Get the quadrant for a position (0=NW, 1=NE, 2=SW, 3=SE)
     pub fn quadrant(&self, pos: Pos2) -> usize {
         let center = self.center();
@@ -309,6 +321,56 @@ impl Quadtree {
     }
 }
 
+// Density queries for declutter and LOD; only the tests call them until a renderer does
+#[cfg_attr(not(test), allow(dead_code))]
+impl Quadtree {
+    /// Number of bodies inside `rect` (edges inclusive)
+    pub fn count_in_rect(&self, rect: Rect) -> u32 {
+        self.query_rect(rect, &mut 0).0
+    }
+
+    /// Summed mass of the bodies inside `rect` (edges inclusive)
+    pub fn total_mass_in_rect(&self, rect: Rect) -> f32 {
+        self.query_rect(rect, &mut 0).1
+    }
+
+    /// (count, mass) inside `rect`. Cells entirely inside the rect answer from
+    /// their totals and cells entirely outside are skipped, so only cells the
+    /// rect's edges cross are descended into. `visited` counts cells looked at.
+    fn query_rect(&self, rect: Rect, visited: &mut usize) -> (u32, f32) {
+        Self::query_rect_recursive(&self.root, self.bounds, rect, visited)
+    }
+
+    fn query_rect_recursive(node: &QuadNode, bounds: Bounds, rect: Rect, visited: &mut usize) -> (u32, f32) {
+        *visited += 1;
+        match node {
+            QuadNode::Empty => (0, 0.0),
+            QuadNode::Leaf { pos, mass } => {
+                if rect.contains(*pos) {
+                    (1, *mass)
+                } else {
+                    (0, 0.0)
+                }
+            }
+            QuadNode::Internal { total_mass, count, children, .. } => {
+                if bounds.disjoint(rect) {
+                    return (0, 0.0);
+                }
+                if bounds.inside(rect) {
+                    return (*count, *total_mass);
+                }
+                let mut sum = (0, 0.0);
+                for (i, child) in children.iter().enumerate() {
+                    let (count, mass) = Self::query_rect_recursive(child, bounds.child_bounds(i), rect, visited);
+                    sum.0 += count;
+                    sum.1 += mass;
+                }
+                sum
+            }
+        }
+    }
+}
+
 impl Default for QuadNode {
     fn default() -> Self {
         QuadNode::Empty
@@ -318,6 +380,8 @@ impl Default for QuadNode {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use rand::rngs::StdRng;
+    use rand::{Rng, SeedableRng};
 
     #[test]
     fn test_quadtree_build() {
@@ -336,6 +400,113 @@ mod tests {
         }
     }
 
+    fn brute_force(points: &[(Pos2, f32)], rect: Rect) -> (u32, f32) {
+        points.iter()
+            .filter(|(p, _)| rect.contains(*p))
+            .fold((0, 0.0), |(n, m), (_, mass)| (n + 1, m + mass))
+    }
+
+    fn random_points(rng: &mut StdRng, count: usize, spread: f32) -> Vec<(Pos2, f32)> {
+        (0..count)
+            .map(|_| (Pos2::new(rng.gen_range(-spread..spread), rng.gen_range(-spread..spread)), rng.gen_range(0.5..3.0)))
+            .collect()
+    }
+
+    #[test]
+    fn test_rect_queries_match_brute_force() {
+        let mut rng = StdRng::seed_from_u64(7);
+        for round in 0..30 {
+            let spread = rng.gen_range(10.0..3000.0_f32);
+            let n = rng.gen_range(1..500);
+            let points = random_points(&mut rng, n, spread);
+            let tree = Quadtree::build(&points, 1.0);
+            for _ in 0..40 {
+                let reach = spread * 1.5;
+                let corner = Pos2::new(rng.gen_range(-reach..reach), rng.gen_range(-reach..reach));
+                let rect = Rect::from_two_pos(corner, corner + Vec2::new(rng.gen_range(-reach..reach), rng.gen_range(-reach..reach)));
+                let (count, mass) = brute_force(&points, rect);
+                assert_eq!(tree.count_in_rect(rect), count, "round {} rect {:?}", round, rect);
+                assert!((tree.total_mass_in_rect(rect) - mass).abs() < 1e-2 * mass.max(1.0), "round {} rect {:?}", round, rect);
+            }
+        }
+    }
+
+    #[test]
+    fn test_rects_on_cell_boundaries() {
+        // Bounds come out as (-100, -100)..(300, 300): quadrants split at 100, theirs at 0 and 200
+        let points: Vec<(Pos2, f32)> = [(0.0, 0.0), (100.0, 0.0), (100.0, 100.0), (200.0, 200.0), (150.0, 50.0)]
+            .iter()
+            .map(|&(x, y)| (Pos2::new(x, y), 2.0))
+            .collect();
+        let tree = Quadtree::build(&points, 1.0);
+        let whole = Rect::from_min_max(tree.bounds.min, tree.bounds.max);
+        assert_eq!((tree.count_in_rect(whole), tree.total_mass_in_rect(whole)), (5, 10.0));
+        for quadrant in 0..4 {
+            let cell = tree.bounds.child_bounds(quadrant);
+            let rect = Rect::from_min_max(cell.min, cell.max);
+            assert_eq!(tree.count_in_rect(rect), brute_force(&points, rect).0, "quadrant {}", quadrant);
+        }
+        // Points exactly on a rect edge count
+        let edge = Rect::from_min_max(Pos2::new(100.0, 0.0), Pos2::new(200.0, 100.0));
+        assert_eq!(tree.count_in_rect(edge), 3);
+    }
+
+    #[test]
+    fn test_empty_tree_and_missed_rects() {
+        let tree = Quadtree::build(&[], 1.0);
+        let rect = Rect::from_min_max(Pos2::new(-10.0, -10.0), Pos2::new(10.0, 10.0));
+        assert_eq!((tree.count_in_rect(rect), tree.total_mass_in_rect(rect)), (0, 0.0));
+
+        let tree = Quadtree::build(&[(Pos2::ZERO, 1.0), (Pos2::new(50.0, 50.0), 1.0)], 1.0);
+        let far = Rect::from_min_max(Pos2::new(1000.0, 1000.0), Pos2::new(2000.0, 2000.0));
+        assert_eq!(tree.count_in_rect(far), 0);
+    }
+
+    #[test]
+    fn test_rect_queries_scale_sublinearly() {
+        // Brute force looks at every body; the walk only at cells along the
+        // rect's edges, which grow with the square root of the body count
+        let mut rng = StdRng::seed_from_u64(3);
+        let rect = Rect::from_min_max(Pos2::new(-300.0, -300.0), Pos2::new(300.0, 300.0));
+        let mut visits = Vec::new();
+        for count in [2_000, 32_000] {
+            let points = random_points(&mut rng, count, 1000.0);
+            let tree = Quadtree::build(&points, 1.0);
+            let mut visited = 0;
+            let (found, _) = tree.query_rect(rect, &mut visited);
+            assert_eq!(found, brute_force(&points, rect).0);
+            assert!(visited < count / 2, "{} cells visited for {} bodies", visited, count);
+            visits.push(visited);
+        }
+        assert!(visits[1] < visits[0] * 8, "visits grew from {} to {}", visits[0], visits[1]);
+    }
+
+    #[test]
+    #[ignore = "timing benchmark: cargo test --release -- --ignored --nocapture"]
+    fn bench_rect_queries_against_brute_force() {
+        let mut rng = StdRng::seed_from_u64(11);
+        for count in [1_000, 10_000, 100_000] {
+            let points = random_points(&mut rng, count, 5000.0);
+            let tree = Quadtree::build(&points, 1.0);
+            let rects: Vec<Rect> = (0..200)
+                .map(|_| Rect::from_center_size(
+                    Pos2::new(rng.gen_range(-5000.0..5000.0), rng.gen_range(-5000.0..5000.0)),
+                    Vec2::splat(rng.gen_range(100.0..4000.0)),
+                ))
+                .collect();
+
+            let start = std::time::Instant::now();
+            let tree_total: u32 = rects.iter().map(|&r| tree.count_in_rect(r)).sum();
+            let tree_time = start.elapsed();
+            let start = std::time::Instant::now();
+            let brute_total: u32 = rects.iter().map(|&r| brute_force(&points, r).0).sum();
+            let brute_time = start.elapsed();
+
+            assert_eq!(tree_total, brute_total);
+            println!("{:>7} bodies: quadtree {:?}, brute force {:?}", count, tree_time, brute_time);
+        }
+    }
+
     #[test]
     fn test_force_calculation() {
         let positions = vec![
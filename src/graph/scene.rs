@@ -0,0 +1,676 @@
+//! Painter-agnostic description of a graph frame.
+//!
+//! The live view and the headless PNG export share the sizing and coloring
+//! rules here; each backend only decides how to put circles and lines on screen.
+
+use super::types::{to_greyscale, GraphEdge, GraphNode, GraphState, Role};
+use egui::{Color32, Mesh, Pos2, Rect, Shape, Vec2};
+use serde::{Deserialize, Serialize};
+
+/// Smallest normalized size multiplier a visible node is drawn at
+const MIN_SIZE_MULTIPLIER: f32 = 0.05;
+
+/// Vertices per edge mesh before a new one is started
+const MAX_MESH_VERTICES: usize = 60_000;
+
+/// Sizing weight for a topic node's session count (fixed, unlike the token weight)
+const TOPIC_SESSION_WEIGHT: f32 = 2.0;
+
+/// Nodes drawn smaller than this (screen points) skip their importance arc
+pub const IMPORTANCE_ARC_MIN_RADIUS: f32 = 4.0;
+
+/// Below this zoom patterned edges draw solid: their dashes would be sub-pixel
+/// and cost a mesh segment each
+pub const DASH_MIN_ZOOM: f32 = 0.35;
+
+/// Opacity factor for gap edges, which stand in for messages outside the window
+pub const GAP_EDGE_OPACITY: f32 = 0.5;
+
+/// Smallest on-screen node radius, in physical pixels, whatever the sizing math says
+const MIN_NODE_RADIUS_PX: f32 = 2.0;
+
+/// How far (screen points) past its drawn edge a node still takes the pointer
+const HIT_GRACE: f32 = 4.0;
+
+/// Edge categories that each get their own line style
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum EdgeKind {
+    Conversation,
+    Temporal,
+    Similarity,
+    Topic,
+    Obsidian,
+}
+
+impl EdgeKind {
+    pub const ALL: [EdgeKind; 5] = [
+        EdgeKind::Conversation,
+        EdgeKind::Temporal,
+        EdgeKind::Similarity,
+        EdgeKind::Topic,
+        EdgeKind::Obsidian,
+    ];
+
+    pub fn of(edge: &GraphEdge) -> Self {
+        if edge.is_temporal {
+            EdgeKind::Temporal
+        } else if edge.is_similarity {
+            EdgeKind::Similarity
+        } else if edge.is_topic {
+            EdgeKind::Topic
+        } else if edge.is_obsidian {
+            EdgeKind::Obsidian
+        } else {
+            EdgeKind::Conversation
+        }
+    }
+
+    /// Whether the edge reads from source to target; temporal and
+    /// similarity links are symmetric and never get arrowheads
+    pub fn is_directed(&self) -> bool {
+        !matches!(self, EdgeKind::Temporal | EdgeKind::Similarity)
+    }
+
+    pub fn label(&self) -> &'static str {
+        match self {
+            EdgeKind::Conversation => "Conversation",
+            EdgeKind::Temporal => "Temporal",
+            EdgeKind::Similarity => "Similarity",
+            EdgeKind::Topic => "Topic",
+            EdgeKind::Obsidian => "Obsidian",
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+pub enum LineStyle {
+    #[default]
+    Solid,
+    Dashed,
+    Dotted,
+}
+
+impl LineStyle {
+    pub const ALL: [LineStyle; 3] = [LineStyle::Solid, LineStyle::Dashed, LineStyle::Dotted];
+
+    pub fn label(&self) -> &'static str {
+        match self {
+            LineStyle::Solid => "Solid",
+            LineStyle::Dashed => "Dashed",
+            LineStyle::Dotted => "Dotted",
+        }
+    }
+
+    /// (dash, gap) in graph units at zoom 1; None for a solid line
+    pub fn pattern(&self) -> Option<(f32, f32)> {
+        match self {
+            LineStyle::Solid => None,
+            LineStyle::Dashed => Some((10.0, 6.0)),
+            LineStyle::Dotted => Some((4.0, 4.0)),
+        }
+    }
+}
+
+/// Where an edge's direction marker goes
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+pub enum ArrowStyle {
+    /// Filled head just short of the target node
+    #[default]
+    Tip,
+    /// Open chevron at the edge midpoint, easier to read in dense graphs
+    Chevron,
+}
+
+impl ArrowStyle {
+    pub const ALL: [ArrowStyle; 2] = [ArrowStyle::Tip, ArrowStyle::Chevron];
+
+    pub fn label(&self) -> &'static str {
+        match self {
+            ArrowStyle::Tip => "Tip",
+            ArrowStyle::Chevron => "Chevron",
+        }
+    }
+}
+
+/// When and how arrowheads are drawn on directed edges
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct ArrowSettings {
+    pub style: ArrowStyle,
+    /// Arrowheads are skipped below this zoom
+    pub min_zoom: f32,
+    /// Arrow length as a multiple of the drawn edge width
+    pub scale: f32,
+}
+
+impl Default for ArrowSettings {
+    fn default() -> Self {
+        // 5 × the default 1.5 width is close to the old fixed 8px head
+        Self { style: ArrowStyle::Tip, min_zoom: 0.3, scale: 5.0 }
+    }
+}
+
+/// Points of an arrowhead for the edge `from → to`: `[apex, wing, wing]`.
+/// Tips sit `tip_inset` short of `to`; chevrons are centred on the midpoint.
+/// None for a zero-length edge.
+pub fn arrow_head(from: Pos2, to: Pos2, tip_inset: f32, size: f32, style: ArrowStyle) -> Option<[Pos2; 3]> {
+    let delta = to - from;
+    if delta.length() <= f32::EPSILON {
+        return None;
+    }
+    let dir = delta.normalized();
+    let apex = match style {
+        ArrowStyle::Tip => to - dir * tip_inset,
+        ArrowStyle::Chevron => from.lerp(to, 0.5) + dir * size * 0.5,
+    };
+    let perp = Vec2::new(-dir.y, dir.x);
+    let back = apex - dir * size;
+    Some([apex, back + perp * size * 0.5, back - perp * size * 0.5])
+}
+
+/// How one kind of edge is stroked
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct EdgeStyle {
+    pub line: LineStyle,
+    /// Width at zoom 1, before the palette's stroke scale
+    pub width: f32,
+    /// Multiplier on the edge's usual opacity
+    pub opacity: f32,
+    /// Drawn at all (toggled from the legend)
+    #[serde(default = "default_edge_visible")]
+    pub visible: bool,
+}
+
+fn default_edge_visible() -> bool {
+    true
+}
+
+impl Default for EdgeStyle {
+    fn default() -> Self {
+        Self { line: LineStyle::Solid, width: 1.5, opacity: 1.0, visible: true }
+    }
+}
+
+/// Per-kind edge styles; the defaults reproduce the original look
+/// (similarity dotted, everything else solid)
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct EdgeStyles {
+    pub conversation: EdgeStyle,
+    pub temporal: EdgeStyle,
+    pub similarity: EdgeStyle,
+    pub topic: EdgeStyle,
+    pub obsidian: EdgeStyle,
+    pub arrows: ArrowSettings,
+}
+
+impl Default for EdgeStyles {
+    fn default() -> Self {
+        Self {
+            conversation: EdgeStyle::default(),
+            temporal: EdgeStyle::default(),
+            similarity: EdgeStyle { line: LineStyle::Dotted, ..EdgeStyle::default() },
+            topic: EdgeStyle::default(),
+            obsidian: EdgeStyle::default(),
+            arrows: ArrowSettings::default(),
+        }
+    }
+}
+
+impl EdgeStyles {
+    pub fn get(&self, kind: EdgeKind) -> EdgeStyle {
+        match kind {
+            EdgeKind::Conversation => self.conversation,
+            EdgeKind::Temporal => self.temporal,
+            EdgeKind::Similarity => self.similarity,
+            EdgeKind::Topic => self.topic,
+            EdgeKind::Obsidian => self.obsidian,
+        }
+    }
+
+    pub fn get_mut(&mut self, kind: EdgeKind) -> &mut EdgeStyle {
+        match kind {
+            EdgeKind::Conversation => &mut self.conversation,
+            EdgeKind::Temporal => &mut self.temporal,
+            EdgeKind::Similarity => &mut self.similarity,
+            EdgeKind::Topic => &mut self.topic,
+            EdgeKind::Obsidian => &mut self.obsidian,
+        }
+    }
+}
+
+/// Sizing and opacity knobs (mirrors the sidebar settings)
+#[derive(Debug, Clone, Copy)]
+pub struct SceneStyle {
+    pub node_size: f32,
+    pub w_importance: f32,
+    pub w_tokens: f32,
+    pub max_node_multiplier: f32,
+    pub temporal_edge_opacity: f32,
+    pub proximity_edge_opacity: f32,
+    pub edge_gamma: f32,
+    pub stroke_scale: f32,
+    pub edge_styles: EdgeStyles,
+}
+
+/// A node to draw, in graph space
+#[derive(Debug, Clone, PartialEq)]
+pub struct SceneNode {
+    pub pos: Pos2,
+    pub radius: f32,
+    pub fill: Color32,
+    pub border: Color32,
+    /// Inner dot marking assistant responses
+    pub inner: Option<(f32, Color32)>,
+}
+
+/// An edge to draw, in graph space
+#[derive(Debug, Clone, PartialEq)]
+pub struct SceneEdge {
+    pub from: Pos2,
+    pub to: Pos2,
+    pub color: Color32,
+    pub width: f32,
+    pub line: LineStyle,
+}
+
+/// Everything needed to draw one frame: edges first, then nodes
+#[derive(Debug, Clone, Default)]
+pub struct Scene {
+    pub edges: Vec<SceneEdge>,
+    pub nodes: Vec<SceneNode>,
+}
+
+impl Scene {
+    /// Bounding box of all nodes (including their radii)
+    pub fn bounds(&self) -> Option<Rect> {
+        self.nodes
+            .iter()
+            .map(|n| Rect::from_center_size(n.pos, Vec2::splat(n.radius * 2.0)))
+            .reduce(|a, b| a.union(b))
+    }
+}
+
+/// Unnormalized size multiplier:
+/// exp(w_imp * importance) * exp(w_tok * tokens_norm) * recency
+pub fn size_multiplier(state: &GraphState, node: &GraphNode, w_importance: f32, w_tokens: f32, recency: f32) -> f32 {
+    // Importance factor (0-1, default 0.5)
+    let importance = node.importance_score.unwrap_or(0.5);
+    let imp_factor = (w_importance * importance).exp();
+
+    // Token factor (log-normalized 0-1). Topics carry no tokens; they grow
+    // with the number of sessions that list them instead.
+    let tok_factor = if node.role == Role::Topic {
+        (TOPIC_SESSION_WEIGHT * state.normalize_topic_sessions(node)).exp()
+    } else {
+        (w_tokens * state.normalize_tokens(node)).exp()
+    };
+
+    imp_factor * tok_factor * recency
+}
+
+/// Scale a raw multiplier so the largest visible node gets `max_node_multiplier`
+pub fn normalize_multiplier(raw: f32, max_raw: f32, max_node_multiplier: f32) -> f32 {
+    (raw * max_node_multiplier / max_raw.max(0.001)).clamp(MIN_SIZE_MULTIPLIER, max_node_multiplier)
+}
+
+/// A node's on-screen radius (points), kept at least `MIN_NODE_RADIUS_PX`
+/// physical pixels on any display
+pub fn clamp_node_radius(radius: f32, pixels_per_point: f32) -> f32 {
+    radius.max(MIN_NODE_RADIUS_PX / pixels_per_point.max(0.1))
+}
+
+/// Pointer distance (points) within which a node drawn at `radius` is hit
+pub fn hit_radius(radius: f32, pixels_per_point: f32) -> f32 {
+    clamp_node_radius(radius, pixels_per_point) + HIT_GRACE
+}
+
+/// Similarity edges fade with weaker scores and gap edges are faded; other
+/// edges keep their base opacity
+pub fn edge_opacity(edge: &GraphEdge, base_opacity: f32) -> f32 {
+    if edge.is_similarity {
+        base_opacity * (0.35 + 0.65 * edge.similarity.unwrap_or(1.0).clamp(0.0, 1.0))
+    } else if edge.gap.is_some() {
+        base_opacity * GAP_EDGE_OPACITY
+    } else {
+        base_opacity
+    }
+}
+
+/// Build a static frame of the whole graph (no hover, selection or timeline state).
+/// `recency` holds per-node recency factors by index; missing entries count as 1.0.
+pub fn build_scene(state: &GraphState, style: &SceneStyle, recency: &[f32]) -> Scene {
+    let mut scene = Scene::default();
+
+    for edge in &state.data.edges {
+        let (Some(from), Some(to)) = (state.get_pos(&edge.source), state.get_pos(&edge.target)) else {
+            continue;
+        };
+        let base_opacity = if edge.is_temporal {
+            style.temporal_edge_opacity
+        } else if edge.is_similarity {
+            style.proximity_edge_opacity
+        } else {
+            0.5
+        };
+        let edge_style = style.edge_styles.get(EdgeKind::of(edge));
+        if !edge_style.visible {
+            continue;
+        }
+        let opacity = (edge_opacity(edge, base_opacity) * edge_style.opacity * style.edge_gamma).min(1.0);
+        scene.edges.push(SceneEdge {
+            from,
+            to,
+            color: state.edge_color(edge).gamma_multiply(opacity),
+            width: edge_style.width * style.stroke_scale,
+            line: if edge.gap.is_some() { LineStyle::Dashed } else { edge_style.line },
+        });
+    }
+
+    let multipliers: Vec<(usize, f32)> = state.data.nodes.iter()
+        .enumerate()
+        .filter(|(_, node)| state.get_pos(&node.id).is_some())
+        .map(|(idx, node)| {
+            let recency = recency.get(idx).copied().unwrap_or(1.0);
+            (idx, size_multiplier(state, node, style.w_importance, style.w_tokens, recency))
+        })
+        .collect();
+    let max_raw = multipliers.iter().map(|&(_, m)| m).fold(0.001, f32::max);
+
+    for (idx, raw) in multipliers {
+        let node = &state.data.nodes[idx];
+        let Some(pos) = state.get_pos(&node.id) else {
+            continue;
+        };
+        let radius = style.node_size * normalize_multiplier(raw, max_raw, style.max_node_multiplier);
+        let fill = state.node_color(node);
+        scene.nodes.push(SceneNode {
+            pos,
+            radius,
+            fill,
+            border: fill.gamma_multiply(0.7),
+            inner: (node.role == Role::Assistant).then_some((radius * 0.4, Color32::BLACK)),
+        });
+    }
+
+    scene
+}
+
+/// Greyed-out variant of a node color, used for timeline-dimmed nodes and edges
+pub fn dimmed(color: Color32) -> Color32 {
+    to_greyscale(color).gamma_multiply(0.4)
+}
+
+/// Faint outline color for nodes that only exist in a diff's earlier window
+pub fn ghosted(color: Color32) -> Color32 {
+    color.gamma_multiply(0.3)
+}
+
+/// Points of an arc clockwise from twelve o'clock, sweeping `fraction` of a
+/// full turn (clamped to 0..1). Segment count follows the arc length so small
+/// rings stay cheap.
+pub fn arc_points(center: Pos2, radius: f32, fraction: f32) -> Vec<Pos2> {
+    let fraction = fraction.clamp(0.0, 1.0);
+    if fraction <= 0.0 || radius <= 0.0 {
+        return Vec::new();
+    }
+    let sweep = fraction * std::f32::consts::TAU;
+    let segments = ((sweep * radius / 3.0).ceil() as usize).clamp(4, 64);
+    (0..=segments)
+        .map(|i| {
+            let angle = -std::f32::consts::FRAC_PI_2 + sweep * i as f32 / segments as f32;
+            center + Vec2::angled(angle) * radius
+        })
+        .collect()
+}
+
+/// Outer importance arc for a node of screen radius `radius`, or None when the
+/// node has no score or is too small to show one
+pub fn importance_arc(node: &GraphNode, center: Pos2, radius: f32, width: f32, low: Color32, high: Color32) -> Option<Shape> {
+    let score = node.importance_score.filter(|s| *s > 0.0)?;
+    if radius < IMPORTANCE_ARC_MIN_RADIUS {
+        return None;
+    }
+    let color = super::types::lerp_color(low, high, score.clamp(0.0, 1.0));
+    let points = arc_points(center, radius + width * 1.5, score);
+    Some(Shape::line(points, egui::Stroke::new(width, color)))
+}
+
+/// Edge geometry collected into a few meshes, so a frame submits a handful of
+/// shapes instead of one per segment. Lines get the same one-pixel feathered
+/// edge the tessellator gives `line_segment`, and triangles keep submit order.
+pub struct EdgeBatch {
+    meshes: Vec<Mesh>,
+    /// Anti-aliasing fringe width in points
+    feather: f32,
+}
+
+impl EdgeBatch {
+    pub fn new(pixels_per_point: f32) -> Self {
+        Self { meshes: vec![Mesh::default()], feather: 1.0 / pixels_per_point.max(0.1) }
+    }
+
+    /// Mesh with room for `vertices` more vertices
+    fn mesh_for(&mut self, vertices: usize) -> &mut Mesh {
+        if self.meshes.last().is_none_or(|m| m.vertices.len() + vertices > MAX_MESH_VERTICES) {
+            self.meshes.push(Mesh::default());
+        }
+        self.meshes.last_mut().expect("a mesh was just ensured")
+    }
+
+    /// A solid line `width` points wide
+    pub fn line(&mut self, from: Pos2, to: Pos2, width: f32, color: Color32) {
+        let delta = to - from;
+        let length = delta.length();
+        if length <= f32::EPSILON || color == Color32::TRANSPARENT {
+            return;
+        }
+        // Lines thinner than the fringe fade out instead of getting thinner
+        let (inner, color) = if width <= self.feather {
+            (0.0, color.gamma_multiply(width / self.feather))
+        } else {
+            ((width - self.feather) * 0.5, color)
+        };
+        let outer = inner + self.feather;
+        let normal = (delta / length).rot90();
+
+        let mesh = self.mesh_for(8);
+        let base = mesh.vertices.len() as u32;
+        for end in [from, to] {
+            mesh.colored_vertex(end + normal * outer, Color32::TRANSPARENT);
+            mesh.colored_vertex(end + normal * inner, color);
+            mesh.colored_vertex(end - normal * inner, color);
+            mesh.colored_vertex(end - normal * outer, Color32::TRANSPARENT);
+        }
+        for i in 0..3 {
+            let (a, b) = (base + i, base + i + 1);
+            mesh.add_triangle(a, b, a + 4);
+            mesh.add_triangle(b, b + 4, a + 4);
+        }
+    }
+
+    /// Dashes of `dash` points separated by `gap`, starting with a dash at `from`
+    pub fn dashed_line(&mut self, from: Pos2, to: Pos2, width: f32, color: Color32, dash: f32, gap: f32) {
+        self.marching_line(from, to, width, color, dash, gap, 0.0);
+    }
+
+    /// A dashed line with the pattern shifted `offset` pixels toward `to`;
+    /// advancing the offset over time makes the dashes march
+    #[allow(clippy::too_many_arguments)]
+    pub fn marching_line(&mut self, from: Pos2, to: Pos2, width: f32, color: Color32, dash: f32, gap: f32, offset: f32) {
+        let delta = to - from;
+        let length = delta.length();
+        if length <= f32::EPSILON || dash <= 0.0 {
+            return;
+        }
+        let dir = delta / length;
+        let step = dash + gap.max(0.0);
+        let mut d = offset.rem_euclid(step) - step;
+        while d < length {
+            let start = d.max(0.0);
+            let end = (d + dash).min(length);
+            if end > start {
+                self.line(from + dir * start, from + dir * end, width, color);
+            }
+            d += step;
+        }
+    }
+
+    /// A line in `style`. Patterns scale with `zoom` and fall back to solid
+    /// below `DASH_MIN_ZOOM`.
+    pub fn styled_line(&mut self, from: Pos2, to: Pos2, width: f32, color: Color32, style: LineStyle, zoom: f32) {
+        match style.pattern().filter(|_| zoom >= DASH_MIN_ZOOM) {
+            Some((dash, gap)) => self.dashed_line(from, to, width, color, dash * zoom, gap * zoom),
+            None => self.line(from, to, width, color),
+        }
+    }
+
+    /// A filled triangle (arrowheads)
+    pub fn triangle(&mut self, points: [Pos2; 3], color: Color32) {
+        let mesh = self.mesh_for(3);
+        let base = mesh.vertices.len() as u32;
+        for p in points {
+            mesh.colored_vertex(p, color);
+        }
+        mesh.add_triangle(base, base + 1, base + 2);
+    }
+
+    /// The non-empty meshes as shapes, in submit order
+    pub fn into_shapes(self) -> impl Iterator<Item = Shape> {
+        self.meshes.into_iter().filter(|m| !m.is_empty()).map(Shape::mesh)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_normalize_multiplier_bounds() {
+        assert_eq!(normalize_multiplier(4.0, 4.0, 3.0), 3.0);
+        assert_eq!(normalize_multiplier(2.0, 4.0, 3.0), 1.5);
+        assert_eq!(normalize_multiplier(0.0, 4.0, 3.0), MIN_SIZE_MULTIPLIER);
+        // A degenerate maximum doesn't divide by zero
+        assert!(normalize_multiplier(1.0, 0.0, 3.0).is_finite());
+    }
+
+    #[test]
+    fn test_node_radius_clamps_to_physical_pixels() {
+        // Sub-pixel radii grow to two physical pixels
+        assert_eq!(clamp_node_radius(0.01, 1.0), 2.0);
+        assert_eq!(clamp_node_radius(0.01, 2.0), 1.0);
+        assert_eq!(clamp_node_radius(0.01, 1.5), 2.0 / 1.5);
+        assert_eq!(clamp_node_radius(0.01, 4.0), 0.5);
+        // Larger radii are untouched
+        assert_eq!(clamp_node_radius(6.0, 2.0), 6.0);
+        // A bogus scale factor doesn't blow up
+        assert!(clamp_node_radius(0.0, 0.0).is_finite());
+
+        // Hits reach a fixed margin past the drawn edge
+        assert_eq!(hit_radius(0.01, 2.0), 5.0);
+        assert_eq!(hit_radius(6.0, 1.0), 10.0);
+        assert!(hit_radius(0.01, 1.0) > clamp_node_radius(0.01, 1.0));
+    }
+
+    #[test]
+    fn test_edge_batch_geometry() {
+        let mut batch = EdgeBatch::new(1.0);
+        let red = Color32::from_rgb(255, 0, 0);
+        batch.line(Pos2::new(0.0, 0.0), Pos2::new(10.0, 0.0), 3.0, red);
+        // Zero-length lines and transparent colors add nothing
+        batch.line(Pos2::new(5.0, 5.0), Pos2::new(5.0, 5.0), 3.0, red);
+        batch.line(Pos2::new(0.0, 0.0), Pos2::new(10.0, 0.0), 3.0, Color32::TRANSPARENT);
+        batch.triangle([Pos2::ZERO, Pos2::new(1.0, 0.0), Pos2::new(0.0, 1.0)], red);
+
+        let meshes: Vec<Mesh> = batch.meshes.clone();
+        assert_eq!(meshes.len(), 1);
+        assert_eq!(meshes[0].vertices.len(), 8 + 3);
+        assert_eq!(meshes[0].indices.len(), 18 + 3);
+        // Solid core of width - feather, transparent fringe one pixel beyond.
+        // `rot90` turns +x into -y, so the outer edge on that side comes first.
+        let ys: Vec<f32> = meshes[0].vertices[..4].iter().map(|v| v.pos.y).collect();
+        assert_eq!(ys, vec![-2.0, -1.0, 1.0, 2.0]);
+        assert_eq!(meshes[0].vertices[0].color, Color32::TRANSPARENT);
+        assert_eq!(meshes[0].vertices[1].color, red);
+        assert!(meshes[0].is_valid());
+    }
+
+    #[test]
+    fn test_edge_batch_dashes_and_splits() {
+        let mut batch = EdgeBatch::new(2.0);
+        // 4-point dashes with 4-point gaps over 20 points: dashes at 0, 8 and 16
+        batch.dashed_line(Pos2::ZERO, Pos2::new(20.0, 0.0), 1.0, Color32::WHITE, 4.0, 4.0);
+        assert_eq!(batch.meshes[0].vertices.len(), 3 * 8);
+
+        for i in 0..MAX_MESH_VERTICES / 8 {
+            let y = i as f32;
+            batch.line(Pos2::new(0.0, y), Pos2::new(10.0, y), 1.0, Color32::WHITE);
+        }
+        let shapes: Vec<Shape> = batch.into_shapes().collect();
+        assert_eq!(shapes.len(), 2);
+    }
+
+    #[test]
+    fn test_styled_line_solid_when_zoomed_out() {
+        let (from, to) = (Pos2::ZERO, Pos2::new(20.0, 0.0));
+        let mut batch = EdgeBatch::new(1.0);
+        // Dotted at zoom 1: the same three dashes as above
+        batch.styled_line(from, to, 1.0, Color32::WHITE, LineStyle::Dotted, 1.0);
+        assert_eq!(batch.meshes[0].vertices.len(), 3 * 8);
+
+        let mut batch = EdgeBatch::new(1.0);
+        batch.styled_line(from, to, 1.0, Color32::WHITE, LineStyle::Dashed, DASH_MIN_ZOOM * 0.5);
+        assert_eq!(batch.meshes[0].vertices.len(), 8);
+    }
+
+    #[test]
+    fn test_default_edge_styles() {
+        let styles = EdgeStyles::default();
+        let similarity = GraphEdge::similarity("a".into(), "b".into(), 0.9, None);
+        let temporal = GraphEdge::temporal("a".into(), "b".into(), 1.0);
+        assert_eq!(styles.get(EdgeKind::of(&similarity)).line, LineStyle::Dotted);
+        assert_eq!(styles.get(EdgeKind::of(&temporal)), EdgeStyle::default());
+
+        // Older settings without some kinds keep the defaults for those
+        let partial: EdgeStyles = serde_json::from_str(r#"{"topic":{"line":"Dashed","width":3.0,"opacity":0.5}}"#).unwrap();
+        assert_eq!(partial.topic.line, LineStyle::Dashed);
+        assert!(partial.topic.visible);
+        assert_eq!(partial.similarity, styles.similarity);
+    }
+
+    #[test]
+    fn test_arrow_head_placement() {
+        let (from, to) = (Pos2::ZERO, Pos2::new(100.0, 0.0));
+        let [apex, a, b] = arrow_head(from, to, 10.0, 8.0, ArrowStyle::Tip).unwrap();
+        assert_eq!(apex, Pos2::new(90.0, 0.0));
+        assert_eq!(a, Pos2::new(82.0, 4.0));
+        assert_eq!(b, Pos2::new(82.0, -4.0));
+
+        // Chevrons straddle the midpoint whatever the inset
+        let [apex, a, _] = arrow_head(from, to, 10.0, 8.0, ArrowStyle::Chevron).unwrap();
+        assert_eq!(apex, Pos2::new(54.0, 0.0));
+        assert_eq!(a.x, 46.0);
+
+        assert!(arrow_head(to, to, 10.0, 8.0, ArrowStyle::Tip).is_none());
+        assert!(EdgeKind::Conversation.is_directed());
+        assert!(!EdgeKind::Temporal.is_directed() && !EdgeKind::Similarity.is_directed());
+    }
+
+    #[test]
+    fn test_arc_points_sweep() {
+        let center = Pos2::new(10.0, 10.0);
+        assert!(arc_points(center, 5.0, 0.0).is_empty());
+
+        // A quarter turn starts at twelve o'clock and ends at three
+        let quarter = arc_points(center, 5.0, 0.25);
+        assert!(quarter.len() >= 5);
+        let (first, last) = (quarter[0], *quarter.last().unwrap());
+        assert!(first.distance(Pos2::new(10.0, 5.0)) < 1e-4);
+        assert!(last.distance(Pos2::new(15.0, 10.0)) < 1e-4);
+        assert!(quarter.iter().all(|p| (p.distance(center) - 5.0).abs() < 1e-4));
+
+        // Scores above 1 are a full circle
+        let full = arc_points(center, 5.0, 1.5);
+        assert!(full[0].distance(*full.last().unwrap()) < 1e-4);
+    }
+}
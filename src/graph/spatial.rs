@@ -0,0 +1,315 @@
+//! Uniform-grid spatial hash for hover and click hit-testing.
+//!
+//! Points are bucketed into square world-space cells. A nearest query scans
+//! rings of cells outward from the query's cell and stops once no unscanned
+//! cell can hold anything closer, so a settled layout answers hovers without
+//! touching every node.
+//!
+//! The same grid drives smart declutter: at low zoom only the top-ranked
+//! points of each screen-sized cell are drawn.
+
+use egui::{Pos2, Rect, Vec2};
+use std::collections::{HashMap, HashSet};
+
+/// Below this many nodes a linear scan is cheaper than keeping a grid
+pub const GRID_MIN_NODES: usize = 5_000;
+
+/// Zoom at and above which declutter is off and every node is drawn
+pub const DECLUTTER_FULL_ZOOM: f32 = 1.0;
+/// Declutter cell size in screen points at the lowest zoom; halves toward full zoom
+const DECLUTTER_CELL_PX: f32 = 64.0;
+/// Points kept per cell at full zoom (one at the lowest)
+const DECLUTTER_MAX_K: usize = 8;
+/// Zoom levels per doubling; the kept set only changes when crossing one
+const DECLUTTER_STEPS_PER_DOUBLING: f32 = 4.0;
+/// Doublings below full zoom where declutter is at its sparsest
+const DECLUTTER_DOUBLINGS: f32 = 4.0;
+
+/// Which structure answered the last hover query
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum HitTest {
+    Linear,
+    Grid { nodes: usize, cells: usize },
+}
+
+impl HitTest {
+    pub fn label(&self) -> String {
+        match self {
+            HitTest::Linear => "linear scan".to_string(),
+            HitTest::Grid { nodes, cells } => format!("spatial grid ({} nodes in {} cells)", nodes, cells),
+        }
+    }
+}
+
+type Cell = (i32, i32);
+
+/// Declutter settings for one zoom step
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct DeclutterLevel {
+    pub step: i32,
+    /// Cell size in graph units
+    pub cell_size: f32,
+    /// Points kept per cell
+    pub k: usize,
+}
+
+/// Declutter level for a graph-to-screen `zoom`, or None when everything is drawn.
+/// Zoom is snapped to steps so panning and small zooms don't reshuffle the kept set.
+pub fn declutter_level(zoom: f32) -> Option<DeclutterLevel> {
+    if zoom.is_nan() || zoom <= 0.0 || zoom >= DECLUTTER_FULL_ZOOM {
+        return None;
+    }
+    let step = ((zoom / DECLUTTER_FULL_ZOOM).log2() * DECLUTTER_STEPS_PER_DOUBLING).floor() as i32;
+    let doublings = step as f32 / DECLUTTER_STEPS_PER_DOUBLING;
+    let snapped = DECLUTTER_FULL_ZOOM * 2f32.powf(doublings);
+    // 0 at the sparsest level, approaching 1 toward full zoom
+    let t = (1.0 + doublings / DECLUTTER_DOUBLINGS).clamp(0.0, 1.0);
+    let cell_px = DECLUTTER_CELL_PX * (1.0 - 0.5 * t);
+    Some(DeclutterLevel {
+        step,
+        cell_size: cell_px / snapped,
+        k: 1 + (t * (DECLUTTER_MAX_K - 1) as f32).round() as usize,
+    })
+}
+
+/// Points a declutter pass keeps, and what each crowded cell left out
+#[derive(Debug, Clone, Default)]
+pub struct Declutter {
+    kept: HashSet<usize>,
+    /// Graph-space bounds of each cell that hid points, with the hidden count
+    pub hidden: Vec<(Rect, usize)>,
+}
+
+impl Declutter {
+    pub fn is_kept(&self, index: usize) -> bool {
+        self.kept.contains(&index)
+    }
+}
+
+#[derive(Debug, Default)]
+pub struct SpatialGrid {
+    cell_size: f32,
+    cells: HashMap<Cell, Vec<(usize, Pos2)>>,
+    min: Cell,
+    max: Cell,
+    len: usize,
+}
+
+impl SpatialGrid {
+    /// Bucket `(index, position)` pairs into cells `cell_size` wide
+    pub fn build(points: impl IntoIterator<Item = (usize, Pos2)>, cell_size: f32) -> Self {
+        let mut grid = Self {
+            cell_size: cell_size.max(f32::EPSILON),
+            cells: HashMap::new(),
+            min: (i32::MAX, i32::MAX),
+            max: (i32::MIN, i32::MIN),
+            len: 0,
+        };
+        for (index, pos) in points {
+            if !pos.x.is_finite() || !pos.y.is_finite() {
+                continue;
+            }
+            let cell = grid.cell_of(pos);
+            grid.min = (grid.min.0.min(cell.0), grid.min.1.min(cell.1));
+            grid.max = (grid.max.0.max(cell.0), grid.max.1.max(cell.1));
+            grid.cells.entry(cell).or_default().push((index, pos));
+            grid.len += 1;
+        }
+        grid
+    }
+
+    /// Points bucketed, not counting the non-finite ones skipped
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    /// Occupied cells
+    pub fn cell_count(&self) -> usize {
+        self.cells.len()
+    }
+
+    fn cell_of(&self, pos: Pos2) -> Cell {
+        (
+            (pos.x / self.cell_size).floor() as i32,
+            (pos.y / self.cell_size).floor() as i32,
+        )
+    }
+
+    /// Index of the point closest to `pos`; equal distances go to the lower index
+    pub fn nearest(&self, pos: Pos2) -> Option<usize> {
+        if self.is_empty() {
+            return None;
+        }
+        let (cx, cy) = self.cell_of(pos);
+        // Rings needed to reach every occupied cell from the query's cell
+        let reach = [cx - self.min.0, self.max.0 - cx, cy - self.min.1, self.max.1 - cy]
+            .into_iter()
+            .map(|d| d.unsigned_abs())
+            .max()
+            .unwrap_or(0);
+        // Far from the points the rings are mostly empty; visiting every
+        // occupied cell directly is cheaper
+        let budget = 4 * self.cells.len() as u64 + 9;
+
+        let mut best: Option<(f32, usize)> = None;
+        let mut visited: u64 = 0;
+        for ring in 0..=reach {
+            let side = 2 * ring as u64 + 1;
+            visited += if ring == 0 { 1 } else { 4 * (side - 1) };
+            if visited > budget {
+                return self.scan_all(pos);
+            }
+            let ring = ring as i32;
+            for x in cx - ring..=cx + ring {
+                for y in cy - ring..=cy + ring {
+                    if (x - cx).abs() != ring && (y - cy).abs() != ring {
+                        continue;
+                    }
+                    if let Some(points) = self.cells.get(&(x, y)) {
+                        consider(&mut best, pos, points);
+                    }
+                }
+            }
+            // Anything in a farther ring is at least `ring` whole cells away
+            if best.is_some_and(|(d, _)| d <= ring as f32 * self.cell_size) {
+                break;
+            }
+        }
+        best.map(|(_, index)| index)
+    }
+
+    /// Keep the `k` highest-ranked points of each cell (ties to the lower index)
+    pub fn top_per_cell(&self, k: usize, rank: impl Fn(usize) -> f32) -> Declutter {
+        let mut declutter = Declutter::default();
+        for (&(x, y), points) in &self.cells {
+            if points.len() <= k {
+                declutter.kept.extend(points.iter().map(|&(index, _)| index));
+                continue;
+            }
+            let mut ranked: Vec<(usize, f32)> = points.iter().map(|&(index, _)| (index, rank(index))).collect();
+            ranked.sort_by(|a, b| b.1.total_cmp(&a.1).then(a.0.cmp(&b.0)));
+            declutter.kept.extend(ranked.iter().take(k).map(|&(index, _)| index));
+            let min = Pos2::new(x as f32 * self.cell_size, y as f32 * self.cell_size);
+            declutter.hidden.push((Rect::from_min_size(min, Vec2::splat(self.cell_size)), points.len() - k));
+        }
+        declutter
+    }
+
+    fn scan_all(&self, pos: Pos2) -> Option<usize> {
+        let mut best = None;
+        for points in self.cells.values() {
+            consider(&mut best, pos, points);
+        }
+        best.map(|(_, index)| index)
+    }
+}
+
+fn consider(best: &mut Option<(f32, usize)>, pos: Pos2, points: &[(usize, Pos2)]) {
+    for &(index, p) in points {
+        let d = p.distance(pos);
+        let better = match *best {
+            None => true,
+            Some((best_d, best_i)) => d < best_d || (d == best_d && index < best_i),
+        };
+        if better {
+            *best = Some((d, index));
+        }
+    }
+}
+
+/// Brute-force nearest point, first in order on ties
+pub fn nearest_linear(points: impl IntoIterator<Item = (usize, Pos2)>, pos: Pos2) -> Option<usize> {
+    let mut best: Option<(f32, usize)> = None;
+    for (index, p) in points {
+        let d = p.distance(pos);
+        if best.is_none_or(|(best_d, _)| d < best_d) {
+            best = Some((d, index));
+        }
+    }
+    best.map(|(_, index)| index)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rand::rngs::StdRng;
+    use rand::{Rng, SeedableRng};
+
+    #[test]
+    fn test_grid_matches_linear_scan() {
+        let mut rng = StdRng::seed_from_u64(17);
+        for round in 0..50 {
+            let count = rng.gen_range(1..400);
+            let spread = rng.gen_range(10.0..5000.0_f32);
+            let points: Vec<(usize, Pos2)> = (0..count)
+                .map(|i| (i, Pos2::new(rng.gen_range(-spread..spread), rng.gen_range(-spread..spread))))
+                .collect();
+            let grid = SpatialGrid::build(points.iter().copied(), rng.gen_range(1.0..60.0));
+            assert_eq!(grid.len(), count);
+
+            for _ in 0..40 {
+                // Include queries well outside the points
+                let reach = spread * 3.0;
+                let query = Pos2::new(rng.gen_range(-reach..reach), rng.gen_range(-reach..reach));
+                assert_eq!(
+                    grid.nearest(query),
+                    nearest_linear(points.iter().copied(), query),
+                    "round {} query {:?}",
+                    round,
+                    query
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn test_top_per_cell_keeps_highest_ranked() {
+        let points = [
+            (0, Pos2::new(1.0, 1.0)),
+            (1, Pos2::new(2.0, 2.0)),
+            (2, Pos2::new(3.0, 3.0)),
+            (3, Pos2::new(15.0, 1.0)),
+        ];
+        let rank = [0.2, 0.9, 0.9, 0.0];
+        let grid = SpatialGrid::build(points, 10.0);
+        let declutter = grid.top_per_cell(2, |i| rank[i]);
+        assert!(declutter.is_kept(1) && declutter.is_kept(2) && declutter.is_kept(3));
+        assert!(!declutter.is_kept(0));
+        assert_eq!(declutter.kept.len(), 3);
+        assert_eq!(declutter.hidden, vec![(Rect::from_min_size(Pos2::ZERO, Vec2::splat(10.0)), 1)]);
+    }
+
+    #[test]
+    fn test_declutter_reveals_more_when_zooming_in() {
+        assert_eq!(declutter_level(DECLUTTER_FULL_ZOOM), None);
+        assert_eq!(declutter_level(0.0), None);
+        // Within one step the level doesn't change
+        assert_eq!(declutter_level(0.50), declutter_level(0.55));
+
+        let levels: Vec<DeclutterLevel> = [0.01, 0.05, 0.1, 0.25, 0.5, 0.9]
+            .into_iter()
+            .filter_map(declutter_level)
+            .collect();
+        assert_eq!(levels.len(), 6);
+        assert_eq!(levels[0].k, 1);
+        for pair in levels.windows(2) {
+            assert!(pair[1].k >= pair[0].k);
+            assert!(pair[1].cell_size < pair[0].cell_size);
+        }
+    }
+
+    #[test]
+    fn test_empty_and_duplicate_points() {
+        let grid = SpatialGrid::build(std::iter::empty(), 10.0);
+        assert_eq!(grid.nearest(Pos2::ZERO), None);
+
+        let same = Pos2::new(5.0, 5.0);
+        let grid = SpatialGrid::build([(3, same), (1, same), (2, Pos2::new(f32::NAN, 0.0))], 10.0);
+        assert_eq!(grid.len(), 2);
+        assert_eq!(grid.nearest(Pos2::new(100.0, -40.0)), Some(1));
+    }
+}
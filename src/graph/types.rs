@@ -1,11 +1,15 @@
 //! Graph data types matching the API response.
 
+use crate::content_kind::{self, ContentClass, ContentKind};
+use crate::log_buffer;
+use super::ids;
+use super::scene::EdgeKind;
 use egui::Pos2;
-use serde::Deserialize;
+use serde::{Deserialize, Serialize};
 use std::collections::{HashMap, HashSet};
 
 /// Mode for semantic filter application
-#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
 pub enum SemanticFilterMode {
     #[default]
     Off,          // Don't apply this filter
@@ -15,6 +19,51 @@ pub enum SemanticFilterMode {
     IncludePlus2, // Show matching nodes + neighbors up to depth 2
 }
 
+impl SemanticFilterMode {
+    /// Click-cycle order: Off -> Include -> +1 -> +2 -> Exclude -> Off
+    pub fn next(self) -> Self {
+        match self {
+            Self::Off => Self::Include,
+            Self::Include => Self::IncludePlus1,
+            Self::IncludePlus1 => Self::IncludePlus2,
+            Self::IncludePlus2 => Self::Exclude,
+            Self::Exclude => Self::Off,
+        }
+    }
+
+    /// Reverse of `next`
+    pub fn prev(self) -> Self {
+        match self {
+            Self::Off => Self::Exclude,
+            Self::Include => Self::Off,
+            Self::IncludePlus1 => Self::Include,
+            Self::IncludePlus2 => Self::IncludePlus1,
+            Self::Exclude => Self::IncludePlus2,
+        }
+    }
+
+    /// Short symbol shown on the filter button
+    pub fn symbol(&self) -> &'static str {
+        match self {
+            Self::Off => "O",
+            Self::Include => "+",
+            Self::IncludePlus1 => "+1",
+            Self::IncludePlus2 => "+2",
+            Self::Exclude => "-",
+        }
+    }
+
+    pub fn description(&self) -> &'static str {
+        match self {
+            Self::Off => "Off - filter not applied",
+            Self::Include => "Include - only show matching nodes",
+            Self::IncludePlus1 => "Include +1 - show matching nodes + their direct neighbors",
+            Self::IncludePlus2 => "Include +2 - show matching nodes + neighbors up to 2 hops",
+            Self::Exclude => "Exclude - hide matching nodes",
+        }
+    }
+}
+
 /// 3-way filter mode: Off / Inactive (dim, bypass edges) / Filtered (fully removed)
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Default, serde::Serialize, serde::Deserialize)]
 pub enum FilterMode {
@@ -31,6 +80,160 @@ impl FilterMode {
     }
 }
 
+/// What a manual hue override applies to
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum HueTarget {
+    Project(String),
+    Session(String),
+}
+
+impl HueTarget {
+    pub fn label(&self) -> String {
+        match self {
+            HueTarget::Project(project) => project.clone(),
+            HueTarget::Session(session_id) => format!("Session {}", ids::short_session(session_id)),
+        }
+    }
+}
+
+/// Hues (degrees) pinned by hand. They take precedence over computed project
+/// and session hues and ignore the global hue offset.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct HueOverrides {
+    pub projects: HashMap<String, f32>,
+    pub sessions: HashMap<String, f32>,
+}
+
+impl HueOverrides {
+    pub fn get(&self, target: &HueTarget) -> Option<f32> {
+        match target {
+            HueTarget::Project(project) => self.projects.get(project).copied(),
+            HueTarget::Session(session_id) => self.sessions.get(session_id).copied(),
+        }
+    }
+
+    /// Pin `target` to `hue`, or clear its override with None
+    pub fn set(&mut self, target: &HueTarget, hue: Option<f32>) {
+        let (map, key) = match target {
+            HueTarget::Project(project) => (&mut self.projects, project),
+            HueTarget::Session(session_id) => (&mut self.sessions, session_id),
+        };
+        match hue {
+            Some(hue) => {
+                map.insert(key.clone(), hue.rem_euclid(360.0));
+            }
+            None => {
+                map.remove(key);
+            }
+        }
+    }
+}
+
+/// Runs of consecutive tool-output messages folded into the first message
+/// of each run. A view over the loaded data; the nodes themselves are untouched.
+#[derive(Debug, Clone, Default)]
+pub struct ToolRuns {
+    /// Head node index -> the folded node indices after it, in session order
+    pub members: HashMap<usize, Vec<usize>>,
+    /// Folded node id -> head node index
+    head_of: HashMap<String, usize>,
+}
+
+impl ToolRuns {
+    /// Fold every run of two or more consecutive tool-output messages in each
+    /// session's order
+    pub fn find(nodes: &[GraphNode], classes: &[ContentClass], session_index: &HashMap<String, Vec<usize>>) -> Self {
+        let is_tool_result = |idx: usize| {
+            nodes[idx].role == Role::Assistant
+                && classes.get(idx).is_some_and(|c| c.kind == ContentKind::ToolOutput)
+        };
+        let mut runs = ToolRuns::default();
+        for order in session_index.values() {
+            for run in order.chunk_by(|&a, &b| is_tool_result(a) == is_tool_result(b)) {
+                let [head, rest @ ..] = run else {
+                    continue;
+                };
+                if rest.is_empty() || !is_tool_result(*head) {
+                    continue;
+                }
+                for &idx in rest {
+                    runs.head_of.insert(nodes[idx].id.clone(), *head);
+                }
+                runs.members.insert(*head, rest.to_vec());
+            }
+        }
+        runs
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.members.is_empty()
+    }
+
+    /// Whether the node is hidden inside another node's run
+    pub fn is_folded(&self, id: &str) -> bool {
+        self.head_of.contains_key(id)
+    }
+
+    /// Tool results a node stands for, itself included (1 for ordinary nodes)
+    pub fn run_len(&self, idx: usize) -> usize {
+        self.members.get(&idx).map_or(1, |m| m.len() + 1)
+    }
+}
+
+/// When a session ran, from its timestamped messages
+#[derive(Debug, Clone, PartialEq)]
+pub struct SessionSpan {
+    pub project: String,
+    pub start: f64,
+    pub end: f64,
+    pub messages: usize,
+    /// Tokens of every class over the session's messages
+    pub tokens: u64,
+    /// Latest message: where the session's terminus marker goes
+    pub last_node: String,
+    /// Earliest user message, for previews of what the session was about
+    pub first_prompt: Option<String>,
+    first_prompt_at: f64,
+}
+
+impl SessionSpan {
+    fn new(node: &GraphNode, ts: f64) -> Self {
+        let mut span = Self {
+            project: node.project.clone(),
+            start: ts,
+            end: ts,
+            messages: 0,
+            tokens: 0,
+            last_node: node.id.clone(),
+            first_prompt: None,
+            first_prompt_at: f64::MAX,
+        };
+        span.record(node, ts);
+        span
+    }
+
+    fn record(&mut self, node: &GraphNode, ts: f64) {
+        self.messages += 1;
+        let mut tokens = crate::cost::TokenTotals::default();
+        tokens.add_node(node);
+        self.tokens += tokens.total();
+        self.start = self.start.min(ts);
+        if ts >= self.end {
+            self.end = ts;
+            self.last_node = node.id.clone();
+        }
+        if node.role == Role::User && ts < self.first_prompt_at {
+            self.first_prompt_at = ts;
+            self.first_prompt = Some(node.id.clone());
+        }
+    }
+
+    pub fn duration_secs(&self) -> f64 {
+        self.end - self.start
+    }
+}
+
 /// Color mode for graph visualization
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Default, serde::Serialize, serde::Deserialize)]
 pub enum ColorMode {
@@ -38,10 +241,23 @@ pub enum ColorMode {
     Project,  // All sessions in same project share same hue
     Session,  // Each session gets its own hue
     Hybrid,   // Project hue + session S/L variation (temporally similar = similar shade)
+    Community, // Connected components of conversation + similarity edges
+}
+
+/// How session and project hues are picked
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+pub enum HueAssignment {
+    /// From the session id and sorted project names, so a session keeps its
+    /// hue when the loaded range changes
+    #[default]
+    Stable,
+    /// Golden-angle steps in the order sessions are loaded: hues spread as
+    /// far apart as possible, but reshuffle between loads
+    Sequential,
 }
 
 /// Role of a message in the conversation
-#[derive(Debug, Clone, PartialEq, Eq, Hash, Deserialize)]
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
 #[serde(rename_all = "lowercase")]
 pub enum Role {
     User,
@@ -52,13 +268,19 @@ pub enum Role {
 }
 
 impl Role {
-    pub fn color(&self) -> egui::Color32 {
+    /// All roles for UI iteration
+    pub fn all() -> &'static [Role] {
+        &[Role::User, Role::Assistant, Role::Agent, Role::Obsidian, Role::Topic]
+    }
+
+    /// Node color for this role in the given palette
+    pub fn color(&self, palette: &crate::theme::Palette) -> egui::Color32 {
         match self {
-            Role::User => egui::Color32::WHITE,
-            Role::Assistant => egui::Color32::from_rgb(255, 149, 0), // Orange
-            Role::Agent => egui::Color32::from_rgb(0, 191, 255),     // Deep sky blue
-            Role::Obsidian => egui::Color32::from_rgb(155, 89, 182), // Purple
-            Role::Topic => egui::Color32::from_rgb(34, 197, 94),     // Green
+            Role::User => palette.role.user,
+            Role::Assistant => palette.role.assistant,
+            Role::Agent => palette.role.agent,
+            Role::Obsidian => palette.role.obsidian,
+            Role::Topic => palette.role.topic,
         }
     }
 
@@ -108,54 +330,30 @@ impl GraphNode {
 
     /// Parse timestamp string to epoch seconds
     pub fn timestamp_secs(&self) -> Option<f64> {
-        self.timestamp.as_ref().and_then(|ts| {
-            // Parse ISO 8601 format: "2025-12-31T01:30:07.726213+00:00"
-            // Simple parsing - extract the key parts
-            let ts = ts.replace('T', " ").replace('Z', "+00:00");
-
-            // Try to parse with chrono-like manual parsing
-            if let Some(plus_idx) = ts.rfind('+') {
-                let datetime_part = &ts[..plus_idx];
-                // Parse: "2025-12-31 01:30:07.726213"
-                let parts: Vec<&str> = datetime_part.split(' ').collect();
-                if parts.len() >= 2 {
-                    let date_parts: Vec<&str> = parts[0].split('-').collect();
-                    let time_full = parts[1];
-                    let time_parts: Vec<&str> = time_full.split(':').collect();
-
-                    if date_parts.len() >= 3 && time_parts.len() >= 3 {
-                        let year: i32 = date_parts[0].parse().ok()?;
-                        let month: u32 = date_parts[1].parse().ok()?;
-                        let day: u32 = date_parts[2].parse().ok()?;
-                        let hour: u32 = time_parts[0].parse().ok()?;
-                        let min: u32 = time_parts[1].parse().ok()?;
-                        let sec_str = time_parts[2].split('.').next()?;
-                        let sec: u32 = sec_str.parse().ok()?;
-
-                        // Simple epoch calculation (approximate, ignores leap seconds)
-                        let days_since_epoch = days_from_civil(year, month, day);
-                        let secs = days_since_epoch as f64 * 86400.0
-                            + hour as f64 * 3600.0
-                            + min as f64 * 60.0
-                            + sec as f64;
-                        return Some(secs);
-                    }
-                }
-            }
-            None
-        })
+        self.timestamp.as_ref().and_then(|ts| parse_iso_timestamp(ts))
     }
-}
 
-/// Calculate days since Unix epoch (simple implementation)
-fn days_from_civil(year: i32, month: u32, day: u32) -> i64 {
-    let y = if month <= 2 { year - 1 } else { year } as i64;
-    let era = if y >= 0 { y } else { y - 399 } / 400;
-    let yoe = (y - era * 400) as u32;
-    let m = month;
-    let doy = (153 * (if m > 2 { m - 3 } else { m + 9 }) + 2) / 5 + day - 1;
-    let doe = yoe * 365 + yoe / 4 - yoe / 100 + doy;
-    era * 146097 + doe as i64 - 719468
+    /// Empty user message in `session`, for tests to fill in with struct update syntax
+    #[cfg(test)]
+    pub fn stub(id: &str, session: &str) -> Self {
+        GraphNode {
+            id: id.to_string(),
+            role: Role::User,
+            content_preview: String::new(),
+            full_content: None,
+            session_id: session.to_string(),
+            session_short: session.to_string(),
+            project: String::new(),
+            timestamp: None,
+            importance_score: None,
+            importance_reason: None,
+            output_tokens: None,
+            input_tokens: None,
+            cache_read_tokens: None,
+            cache_creation_tokens: None,
+            has_tool_usage: false,
+        }
+    }
 }
 
 /// An edge connecting two nodes
@@ -178,40 +376,82 @@ pub struct GraphEdge {
     /// Which proximity query produced this edge (for multi-query coloring)
     #[serde(default)]
     pub query_index: Option<usize>,
+    /// Synthetic edge bridging over nodes hidden by an Inactive filter
+    #[serde(default)]
+    pub is_bypass: bool,
+    /// Session link across messages outside the loaded window: how many
+    #[serde(default)]
+    pub gap: Option<u32>,
+    /// Seconds between the two messages of a conversation edge, set at load
+    #[serde(default)]
+    pub idle_secs: Option<f64>,
 }
 
 impl GraphEdge {
-    /// Create a temporal edge between two nodes
-    pub fn temporal(source: String, target: String, strength: f32) -> Self {
+    /// Plain edge with every flag off; the named constructors start from this
+    /// so a new field only has to be added here
+    fn base(source: String, target: String, session_id: String) -> Self {
         Self {
             source,
             target,
-            session_id: String::new(),
+            session_id,
             timestamp: None,
             is_obsidian: false,
             is_topic: false,
             is_similarity: false,
+            is_temporal: false,
+            similarity: None,
+            query_index: None,
+            is_bypass: false,
+            gap: None,
+            idle_secs: None,
+        }
+    }
+
+    /// Create a conversation edge from one message to the next in a session
+    pub fn conversation(source: String, target: String, session_id: String, timestamp: Option<String>) -> Self {
+        Self { timestamp, ..Self::base(source, target, session_id) }
+    }
+
+    /// Create a temporal edge between two nodes
+    pub fn temporal(source: String, target: String, strength: f32) -> Self {
+        Self {
             is_temporal: true,
             similarity: Some(strength),
-            query_index: None,
+            ..Self::base(source, target, String::new())
         }
     }
 
     /// Create a similarity edge between two nodes
     pub fn similarity(source: String, target: String, strength: f32, query_index: Option<usize>) -> Self {
         Self {
-            source,
-            target,
-            session_id: String::new(),
-            timestamp: None,
-            is_obsidian: false,
-            is_topic: false,
             is_similarity: true,
-            is_temporal: false,
             similarity: Some(strength),
             query_index,
+            ..Self::base(source, target, String::new())
         }
     }
+
+    /// Create a bypass edge bridging over hidden nodes in a session chain
+    pub fn bypass(source: String, target: String, session_id: String) -> Self {
+        Self { is_bypass: true, ..Self::base(source, target, session_id) }
+    }
+
+    /// Create an edge joining two fragments of a session, with `missing`
+    /// messages between them that the load left out
+    pub fn gap(source: String, target: String, session_id: String, missing: u32) -> Self {
+        Self { gap: Some(missing), ..Self::base(source, target, session_id) }
+    }
+
+    /// Create an edge from a topic node to a message of a session that lists it
+    pub fn topic(source: String, target: String, session_id: String) -> Self {
+        Self { is_topic: true, ..Self::base(source, target, session_id) }
+    }
+
+    /// Create an edge from an Obsidian note to a message it's linked with
+    pub fn obsidian(source: String, target: String) -> Self {
+        Self { is_obsidian: true, ..Self::base(source, target, String::new()) }
+    }
 }
 
 /// Issue status for Kanban columns
@@ -237,6 +477,30 @@ impl IssueStatus {
             IssueStatus::Hooked => "Hooked",
         }
     }
+
+    /// Status color in the given palette
+    pub fn status_color(&self, palette: &crate::theme::Palette) -> egui::Color32 {
+        match self {
+            IssueStatus::Open => palette.status.open,
+            IssueStatus::InProgress => palette.status.in_progress,
+            IssueStatus::Blocked => palette.status.blocked,
+            IssueStatus::Closed => palette.status.closed,
+            IssueStatus::Deferred => palette.status.deferred,
+            IssueStatus::Hooked => palette.status.hooked,
+        }
+    }
+
+    /// Single-letter badge so status never relies on color alone
+    pub fn badge(&self) -> &'static str {
+        match self {
+            IssueStatus::Open => "O",
+            IssueStatus::InProgress => "P",
+            IssueStatus::Blocked => "B",
+            IssueStatus::Closed => "C",
+            IssueStatus::Deferred => "D",
+            IssueStatus::Hooked => "H",
+        }
+    }
 }
 
 /// A bead (issue) item for display in panels
@@ -292,52 +556,39 @@ impl MailItem {
     }
 }
 
-/// Parse an ISO 8601 timestamp to epoch seconds
-fn parse_iso_timestamp(ts: &str) -> Option<f64> {
-    // Parse ISO 8601 format: "2025-12-31T01:30:07.726213+00:00" or "2025-12-31"
-    let ts = ts.replace('T', " ").replace('Z', "+00:00");
-
-    // Handle date-only format "2025-12-31"
-    if !ts.contains(' ') {
-        let date_parts: Vec<&str> = ts.split('-').collect();
-        if date_parts.len() >= 3 {
-            let year: i32 = date_parts[0].parse().ok()?;
-            let month: u32 = date_parts[1].parse().ok()?;
-            let day: u32 = date_parts[2].split('+').next()?.parse().ok()?;
-            let days_since_epoch = days_from_civil(year, month, day);
-            return Some(days_since_epoch as f64 * 86400.0);
-        }
-        return None;
-    }
-
-    // Try to parse datetime with timezone
-    if let Some(plus_idx) = ts.rfind('+') {
-        let datetime_part = &ts[..plus_idx];
-        let parts: Vec<&str> = datetime_part.split(' ').collect();
-        if parts.len() >= 2 {
-            let date_parts: Vec<&str> = parts[0].split('-').collect();
-            let time_full = parts[1];
-            let time_parts: Vec<&str> = time_full.split(':').collect();
-
-            if date_parts.len() >= 3 && time_parts.len() >= 3 {
-                let year: i32 = date_parts[0].parse().ok()?;
-                let month: u32 = date_parts[1].parse().ok()?;
-                let day: u32 = date_parts[2].parse().ok()?;
-                let hour: u32 = time_parts[0].parse().ok()?;
-                let min: u32 = time_parts[1].parse().ok()?;
-                let sec_str = time_parts[2].split('.').next()?;
-                let sec: u32 = sec_str.parse().ok()?;
-
-                let days_since_epoch = days_from_civil(year, month, day);
-                let secs = days_since_epoch as f64 * 86400.0
-                    + hour as f64 * 3600.0
-                    + min as f64 * 60.0
-                    + sec as f64;
-                return Some(secs);
-            }
+/// Parse an ISO 8601 timestamp to UTC epoch seconds.
+///
+/// Accepts `2025-12-31T01:30:07.726213+00:00`, negative offsets (`-05:00`),
+/// a trailing `Z`, a space instead of `T`, and date-only `2025-12-31`.
+/// Timestamps without an offset are taken as UTC; fractional seconds are kept.
+pub fn parse_iso_timestamp(ts: &str) -> Option<f64> {
+    use chrono::{DateTime, NaiveDate, NaiveDateTime};
+
+    let ts = ts.trim();
+    if let Ok(dt) = DateTime::parse_from_rfc3339(ts) {
+        return Some(epoch_secs(dt.timestamp(), dt.timestamp_subsec_nanos()));
+    }
+    // Offset forms RFC 3339 rejects: space separator, `+0530`, `+05`, no seconds
+    for fmt in ["%Y-%m-%dT%H:%M:%S%.f%#z", "%Y-%m-%d %H:%M:%S%.f%#z", "%Y-%m-%dT%H:%M%#z", "%Y-%m-%d %H:%M%#z"] {
+        if let Ok(dt) = DateTime::parse_from_str(ts, fmt) {
+            return Some(epoch_secs(dt.timestamp(), dt.timestamp_subsec_nanos()));
+        }
+    }
+    // No offset: taken as UTC
+    for fmt in ["%Y-%m-%dT%H:%M:%S%.f", "%Y-%m-%d %H:%M:%S%.f", "%Y-%m-%dT%H:%M", "%Y-%m-%d %H:%M"] {
+        if let Ok(dt) = NaiveDateTime::parse_from_str(ts, fmt) {
+            let utc = dt.and_utc();
+            return Some(epoch_secs(utc.timestamp(), utc.timestamp_subsec_nanos()));
         }
     }
-    None
+    // Date only, tolerating a zone suffix ("2025-12-31Z", "2025-12-31+00:00")
+    let date = ts.split(['+', 'Z']).next()?;
+    let midnight = NaiveDate::parse_from_str(date, "%Y-%m-%d").ok()?.and_hms_opt(0, 0, 0)?.and_utc();
+    Some(midnight.timestamp() as f64)
+}
+
+fn epoch_secs(secs: i64, nanos: u32) -> f64 {
+    secs as f64 + nanos as f64 / 1e9
 }
 
 /// Complete graph data from the API
@@ -351,6 +602,27 @@ pub struct GraphData {
     pub mail: Vec<MailItem>,
 }
 
+impl GraphData {
+    /// Nodes and edges with no beads or mail
+    pub fn new(nodes: Vec<GraphNode>, edges: Vec<GraphEdge>) -> Self {
+        Self { nodes, edges, ..Default::default() }
+    }
+
+    /// Drop messages from projects matching `patterns`, with their edges
+    pub fn drop_excluded_projects(&mut self, patterns: &[String]) {
+        if patterns.is_empty() {
+            return;
+        }
+        let before = self.nodes.len();
+        self.nodes.retain(|n| !crate::exclude::is_excluded(&n.project, patterns));
+        if self.nodes.len() == before {
+            return;
+        }
+        let kept: HashSet<&str> = self.nodes.iter().map(|n| n.id.as_str()).collect();
+        self.edges.retain(|e| kept.contains(e.source.as_str()) && kept.contains(e.target.as_str()));
+    }
+}
+
 /// Partial summary data from the API (generated by Gemini)
 #[derive(Debug, Clone, Default, Deserialize)]
 pub struct PartialSummaryData {
@@ -435,6 +707,22 @@ impl SemanticFilter {
     }
 }
 
+/// Kind of item in the activity feed
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum FeedKind {
+    Message,
+    Bead,
+    Mail,
+}
+
+/// One activity feed row: an index into nodes, beads or mail, and its time
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct FeedEntry {
+    pub kind: FeedKind,
+    pub index: usize,
+    pub time: f64,
+}
+
 /// Timeline state for scrubbing through time
 /// This provides unified timeline filtering across all panels:
 /// - Graph nodes (visible_nodes)
@@ -446,6 +734,8 @@ pub struct TimelineState {
     pub sorted_indices: Vec<usize>,
     /// Timestamps in seconds for each sorted node
     pub timestamps: Vec<f64>,
+    /// Parsed timestamp for each node, indexed like `GraphData::nodes`
+    pub node_times: Vec<Option<f64>>,
     /// Min timestamp in the data (considering all items: nodes, beads, mail)
     pub min_time: f64,
     /// Max timestamp in the data (considering all items: nodes, beads, mail)
@@ -460,13 +750,17 @@ pub struct TimelineState {
     pub speed: f32,
     /// Set of visible node IDs based on current time window
     pub visible_nodes: HashSet<String>,
+    /// Per edge (indexed like `GraphData::edges`): both endpoints are in the window
+    pub visible_edges: Vec<bool>,
+    /// Temporary hover-scrub position; overrides `position` until cleared
+    pub preview_position: Option<f32>,
 
     // --- Unified Timeline: Bead filtering ---
     /// Sorted bead indices by timestamp
     pub sorted_bead_indices: Vec<usize>,
     /// Timestamps in seconds for each sorted bead
     pub bead_timestamps: Vec<f64>,
-    /// Set of visible bead IDs based on current time window
+    /// Visible bead marker ids (`bead:<issue id>`) in the current time window
     pub visible_beads: HashSet<String>,
 
     // --- Unified Timeline: Mail filtering ---
@@ -483,6 +777,7 @@ impl Default for TimelineState {
         Self {
             sorted_indices: Vec::new(),
             timestamps: Vec::new(),
+            node_times: Vec::new(),
             min_time: 0.0,
             max_time: 0.0,
             position: 1.0,
@@ -490,6 +785,8 @@ impl Default for TimelineState {
             playing: false,
             speed: 1.0,
             visible_nodes: HashSet::new(),
+            visible_edges: Vec::new(),
+            preview_position: None,
             // Bead filtering
             sorted_bead_indices: Vec::new(),
             bead_timestamps: Vec::new(),
@@ -503,11 +800,56 @@ impl Default for TimelineState {
 }
 
 impl TimelineState {
+    /// Position used for visibility and recency: the hover preview if any, else the committed position
+    pub fn effective_position(&self) -> f32 {
+        self.preview_position.unwrap_or(self.position)
+    }
+
     /// Get time at a given position (0.0 - 1.0)
     pub fn time_at_position(&self, pos: f32) -> f64 {
+        if self.is_degenerate() {
+            return self.min_time;
+        }
+        if !pos.is_finite() {
+            return self.max_time;
+        }
         self.min_time + (self.max_time - self.min_time) * pos as f64
     }
 
+    /// True when the data spans no time (no timestamps, or all equal);
+    /// the whole timeline is then treated as visible
+    pub fn is_degenerate(&self) -> bool {
+        self.max_time.partial_cmp(&self.min_time) != Some(std::cmp::Ordering::Greater)
+    }
+
+    /// Range of `sorted_indices` whose timestamps fall in [start, end], found
+    /// by binary search over the sorted timestamps
+    pub fn window_range(&self, start: f64, end: f64) -> std::ops::Range<usize> {
+        let lo = self.timestamps.partition_point(|&t| t < start);
+        let hi = self.timestamps.partition_point(|&t| t <= end);
+        lo..hi.max(lo)
+    }
+
+    /// Messages, beads and mail merged oldest first from the per-kind sorted
+    /// arrays. Items sharing a timestamp come messages first, then beads, then mail.
+    pub fn feed(&self) -> impl Iterator<Item = FeedEntry> + '_ {
+        let lanes = [
+            (FeedKind::Message, &self.sorted_indices, &self.timestamps),
+            (FeedKind::Bead, &self.sorted_bead_indices, &self.bead_timestamps),
+            (FeedKind::Mail, &self.sorted_mail_indices, &self.mail_timestamps),
+        ];
+        let mut cursors = [0usize; 3];
+        std::iter::from_fn(move || {
+            let lane = (0..lanes.len())
+                .filter(|&lane| cursors[lane] < lanes[lane].2.len())
+                .min_by(|&a, &b| lanes[a].2[cursors[a]].total_cmp(&lanes[b].2[cursors[b]]))?;
+            let (kind, indices, times) = lanes[lane];
+            let at = cursors[lane];
+            cursors[lane] += 1;
+            Some(FeedEntry { kind, index: indices[at], time: times[at] })
+        })
+    }
+
     /// Get position for a given time
     pub fn position_at_time(&self, time: f64) -> f32 {
         if self.max_time <= self.min_time {
@@ -516,18 +858,18 @@ impl TimelineState {
         ((time - self.min_time) / (self.max_time - self.min_time)) as f32
     }
 
-    /// Format a time as a human-readable string
+    /// Format a time as a human-readable string in local time
     pub fn format_time(&self, time: f64) -> String {
-        // Get current time for relative formatting
-        let now = std::time::SystemTime::now()
-            .duration_since(std::time::UNIX_EPOCH)
-            .unwrap()
-            .as_secs() as i64;
+        use chrono::{DateTime, Datelike, Local};
 
-        let timestamp = time as i64;
-        let diff_secs = now - timestamp;
+        let Some(utc) = DateTime::from_timestamp(time.floor() as i64, 0) else {
+            return String::new();
+        };
+        let local = utc.with_timezone(&Local);
+        let now = Local::now();
 
         // Relative time for recent events
+        let diff_secs = (now - local).num_seconds();
         if diff_secs < 60 {
             return "Just now".to_string();
         } else if diff_secs < 3600 {
@@ -538,44 +880,19 @@ impl TimelineState {
             return format!("{} hour{} ago", hours, if hours == 1 { "" } else { "s" });
         }
 
-        // Calculate date components
-        let days = timestamp / 86400;
-        let hours = (timestamp % 86400) / 3600;
-        let mins = (timestamp % 3600) / 60;
-        let (year, month, day) = civil_from_days(days);
-
-        // Convert to 12-hour format
-        let (hour_12, am_pm) = if hours == 0 {
-            (12, "AM")
-        } else if hours < 12 {
-            (hours, "AM")
-        } else if hours == 12 {
-            (12, "PM")
-        } else {
-            (hours - 12, "PM")
-        };
-
-        // Format based on how old it is
-        let now_days = now / 86400;
-        let day_diff = now_days - days;
-
+        // Format based on how many calendar days ago it was
+        let clock = local.format("%-I:%M %p");
+        let day_diff = (now.date_naive() - local.date_naive()).num_days();
         if day_diff == 0 {
-            format!("Today at {}:{:02} {}", hour_12, mins, am_pm)
+            format!("Today at {}", clock)
         } else if day_diff == 1 {
-            format!("Yesterday at {}:{:02} {}", hour_12, mins, am_pm)
+            format!("Yesterday at {}", clock)
         } else if day_diff < 7 {
-            let weekday = ["Sun", "Mon", "Tue", "Wed", "Thu", "Fri", "Sat"];
-            let day_of_week = ((days + 4) % 7) as usize; // Jan 1, 1970 was Thursday
-            format!("{} at {}:{:02} {}", weekday[day_of_week], hour_12, mins, am_pm)
+            format!("{} at {}", local.format("%a"), clock)
+        } else if local.year() == now.year() {
+            format!("{} at {}", local.format("%b %-d"), clock)
         } else {
-            let month_name = ["Jan", "Feb", "Mar", "Apr", "May", "Jun",
-                            "Jul", "Aug", "Sep", "Oct", "Nov", "Dec"];
-            let current_year = civil_from_days(now_days).0;
-            if year == current_year {
-                format!("{} {} at {}:{:02} {}", month_name[(month - 1) as usize], day, hour_12, mins, am_pm)
-            } else {
-                format!("{} {}, {} at {}:{:02} {}", month_name[(month - 1) as usize], day, year, hour_12, mins, am_pm)
-            }
+            format!("{} at {}", local.format("%b %-d, %Y"), clock)
         }
     }
 
@@ -608,20 +925,11 @@ impl TimelineState {
     }
 }
 
-/// Convert days since epoch to civil date
-fn civil_from_days(days: i64) -> (i32, u32, u32) {
-    let z = days + 719468;
-    let era = if z >= 0 { z } else { z - 146096 } / 146097;
-    let doe = (z - era * 146097) as u32;
-    let yoe = (doe - doe / 1460 + doe / 36524 - doe / 146096) / 365;
-    let y = yoe as i64 + era * 400;
-    let doy = doe - (365 * yoe + yoe / 4 - yoe / 100);
-    let mp = (5 * doy + 2) / 153;
-    let d = doy - (153 * mp + 2) / 5 + 1;
-    let m = if mp < 10 { mp + 3 } else { mp - 9 };
-    let year = if m <= 2 { y + 1 } else { y } as i32;
-    (year, m, d)
-}
+/// Default per-node temporal edge cap: loose enough that small graphs never hit it
+pub const DEFAULT_MAX_TEMPORAL_NEIGHBORS: usize = 50;
+
+/// Node lightness multiplier on light backgrounds, so pale shades stay visible on white
+const LIGHT_BACKGROUND_LIGHTNESS: f32 = 0.8;
 
 /// Runtime graph state with positions
 pub struct GraphState {
@@ -637,17 +945,45 @@ pub struct GraphState {
     pub session_colors: HashMap<String, f32>,
     /// Project colors (project_name -> hue)
     pub project_colors: HashMap<String, f32>,
+    /// Manually pinned hues; kept across loads
+    pub hue_overrides: HueOverrides,
+    /// Categorical palette slots in first-seen order (colorblind-safe mode)
+    pub session_slots: HashMap<String, usize>,
+    pub project_slots: HashMap<String, usize>,
     /// Tracks how many children we've seen for each parent path
     pub child_counts: HashMap<String, usize>,
     /// Maps "parent:child" to the child's sibling index
     pub child_indices: HashMap<String, usize>,
     /// Global hue offset for randomizing colors while preserving relationships
     pub hue_offset: f32,
+    pub hue_assignment: HueAssignment,
+    /// Fold consecutive tool-output messages into one node per run
+    pub collapse_tool_results: bool,
+    /// The folded runs while `collapse_tool_results` is on
+    pub tool_runs: ToolRuns,
     /// Color mode for graph visualization
     pub color_mode: ColorMode,
+    /// Saturation/lightness for project and session node colors (from the active theme)
+    pub shading: crate::theme::NodeShading,
+    /// Use categorical colorblind-safe colors instead of hues
+    pub colorblind_safe: bool,
+    /// Drawing on a light background: node shades are darkened
+    pub is_light: bool,
     /// Sessions within each project, sorted by timestamp: project -> [(session_id, timestamp)]
     /// Used for hybrid coloring to give temporally close sessions similar shades
     pub project_sessions: HashMap<String, Vec<(String, f64)>>,
+    /// First and last message time per session (message nodes with timestamps)
+    pub session_spans: HashMap<String, SessionSpan>,
+    /// Message indices per session, oldest first, with untimed messages
+    /// last in input order. Built once per load.
+    pub session_index: HashMap<String, Vec<usize>>,
+    /// Prose / code / tool-output class of each node, indexed like `data.nodes`
+    pub content_classes: Vec<ContentClass>,
+    /// Each node's place in its session's `session_index` (None for non-messages)
+    session_rank: Vec<Option<usize>>,
+    /// Indices into `data.edges` touching each node, indexed like `data.nodes`.
+    /// Rebuilt by `edges_changed` whenever the edge list changes.
+    incident_edges: Vec<Vec<usize>>,
     /// Is physics simulation running?
     pub physics_enabled: bool,
     /// Currently hovered node
@@ -662,8 +998,15 @@ pub struct GraphState {
     pub temporal_window_secs: f64,
     /// Maximum temporal edges to build
     pub max_temporal_edges: usize,
+    /// Per-node temporal edge cap (0 = unlimited), so one dense burst can't
+    /// use up the whole `max_temporal_edges` budget
+    pub max_temporal_neighbors: usize,
+    /// Project patterns never loaded (see `exclude`)
+    pub excluded_projects: Vec<String>,
     /// Maximum total tokens across all nodes (for normalization)
     pub max_tokens: i32,
+    /// Distinct sessions linked to each topic node (topics are sized by this, not tokens)
+    pub topic_sessions: HashMap<String, usize>,
     /// Whether score-proximity edges are enabled
     pub score_proximity_enabled: bool,
     /// Maximum score difference to create a proximity edge
@@ -672,6 +1015,24 @@ pub struct GraphState {
     pub max_proximity_edges: usize,
     /// Per-node edge cap for proximity edges (0 = unlimited)
     pub max_neighbors_per_node: usize,
+    /// Structural communities of the current nodes and edges (None until detected)
+    pub communities: Option<super::community::Communities>,
+    /// Bumped whenever `communities` goes stale, so late worker results can be dropped
+    pub community_generation: u64,
+    /// Bumped whenever the edge list is replaced or edited, so cached edge
+    /// geometry knows to rebuild
+    pub edges_generation: u64,
+    /// The last temporal edge build (None once its edges are gone)
+    pub temporal_build: Option<TemporalBuild>,
+}
+
+/// One temporal edge build: what it covered and what it cost
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct TemporalBuild {
+    /// Hash of the participating nodes and the edge settings (see `temporal_scope`)
+    pub scope: u64,
+    pub edges: usize,
+    pub elapsed: std::time::Duration,
 }
 
 impl GraphState {
@@ -683,11 +1044,24 @@ impl GraphState {
             node_index: HashMap::new(),
             session_colors: HashMap::new(),
             project_colors: HashMap::new(),
+            hue_overrides: HueOverrides::default(),
+            session_slots: HashMap::new(),
+            project_slots: HashMap::new(),
             child_counts: HashMap::new(),
             child_indices: HashMap::new(),
             hue_offset: 0.0,
+            hue_assignment: HueAssignment::default(),
+            collapse_tool_results: false,
+            tool_runs: ToolRuns::default(),
             color_mode: ColorMode::Project, // Default to project coloring
+            shading: crate::theme::DARK.shading,
+            colorblind_safe: false,
+            is_light: false,
             project_sessions: HashMap::new(),
+            session_spans: HashMap::new(),
+            content_classes: Vec::new(),
+            session_index: HashMap::new(),
+            session_rank: Vec::new(),
             physics_enabled: true,
             hovered_node: None,
             selected_node: None,
@@ -695,18 +1069,47 @@ impl GraphState {
             temporal_attraction_enabled: true,
             temporal_window_secs: 300.0, // 5 minutes default
             max_temporal_edges: 100_000,
+            max_temporal_neighbors: DEFAULT_MAX_TEMPORAL_NEIGHBORS,
+            excluded_projects: Vec::new(),
             max_tokens: 1,
+            topic_sessions: HashMap::new(),
             score_proximity_enabled: false,
             score_proximity_delta: 0.1,
             max_proximity_edges: 100_000,
             max_neighbors_per_node: 0,
+            communities: None,
+            community_generation: 0,
+            edges_generation: 0,
+            temporal_build: None,
+            incident_edges: Vec::new(),
+        }
+    }
+
+    /// Forget detected communities; the nodes or the edges defining them changed
+    pub fn invalidate_communities(&mut self) {
+        self.communities = None;
+        self.community_generation += 1;
+    }
+
+    /// Community number of a node, once detected
+    pub fn node_community(&self, id: &str) -> Option<usize> {
+        let index = *self.node_index.get(id)?;
+        self.communities.as_ref()?.of_node.get(index).copied()
+    }
+
+    /// Color for a community number
+    pub fn community_color(&self, community: usize) -> egui::Color32 {
+        if self.colorblind_safe {
+            return crate::theme::categorical(community);
         }
+        let hue = super::community::hue(community);
+        hsl_to_rgb(self.apply_hue_offset(hue), self.shading.project_saturation, self.shading.project_lightness)
     }
 
     /// Normalize token count to 0-1 range using log scale
     /// Formula: log(tokens + 1) / log(max_tokens + 1)
     pub fn normalize_tokens(&self, node: &GraphNode) -> f32 {
-        let tokens = node.total_tokens() as f32;
+        let tokens = self.display_tokens(node) as f32;
         let max = self.max_tokens as f32;
         if max <= 1.0 {
             return 0.5; // Default when no token data
@@ -714,15 +1117,54 @@ impl GraphState {
         (tokens + 1.0).ln() / (max + 1.0).ln()
     }
 
+    /// A node's tokens, summed over its run when tool results are collapsed
+    pub fn display_tokens(&self, node: &GraphNode) -> i32 {
+        if self.tool_runs.is_empty() {
+            return node.total_tokens();
+        }
+        let Some(members) = self.node_index.get(&node.id).and_then(|i| self.tool_runs.members.get(i)) else {
+            return node.total_tokens();
+        };
+        node.total_tokens() + members.iter().map(|&i| self.data.nodes[i].total_tokens()).sum::<i32>()
+    }
+
+    /// Turn tool-result folding on or off, rebuilding the runs
+    pub fn set_collapse_tool_results(&mut self, collapse: bool) {
+        self.collapse_tool_results = collapse;
+        self.build_tool_runs();
+    }
+
+    /// Recompute folded runs and the token maximum they size against
+    fn build_tool_runs(&mut self) {
+        self.tool_runs = if self.collapse_tool_results {
+            ToolRuns::find(&self.data.nodes, &self.content_classes, &self.session_index)
+        } else {
+            ToolRuns::default()
+        };
+        self.max_tokens = self.data.nodes.iter()
+            .map(|n| self.display_tokens(n))
+            .max()
+            .unwrap_or(1)
+            .max(1);
+    }
+
+    /// Normalize a topic node's session count to 0-1 using log scale, like tokens
+    pub fn normalize_topic_sessions(&self, node: &GraphNode) -> f32 {
+        let max = self.topic_sessions.values().copied().max().unwrap_or(0) as f32;
+        if max <= 1.0 {
+            return 0.5;
+        }
+        let sessions = self.topic_sessions.get(&node.id).copied().unwrap_or(0) as f32;
+        (sessions + 1.0).ln() / (max + 1.0).ln()
+    }
+
     /// Compute hue for a project based on its position in the directory tree.
     /// Tree distance maps to hue distance:
     /// - Siblings at each level get golden-ratio-spaced hues
     /// - Children inherit parent's base hue + smaller offset
     /// - Deeper nesting = tighter clustering (diminishing hue range)
     fn compute_project_hue(&mut self, project: &str) -> f32 {
-        // Normalize consistently - just strip ~/
-        let path = project.trim_start_matches("~/");
-        let parts: Vec<&str> = path.split('/').filter(|s| !s.is_empty()).collect();
+        let parts = project_path_parts(project);
 
         if parts.is_empty() {
             return 0.0;
@@ -763,20 +1205,66 @@ impl GraphState {
         hue % 360.0
     }
 
-    /// Load new graph data, initializing positions randomly
-    pub fn load(&mut self, data: GraphData, bounds: egui::Rect) {
+    /// Session and project hues for everything in `session_slots` and
+    /// `project_slots`, by `hue_assignment`; run again when it changes.
+    /// Projects under the same parent directory get similar hues either way.
+    pub fn assign_hues(&mut self) {
+        self.session_colors.clear();
+        self.project_colors.clear();
+        self.child_counts.clear();
+        self.child_indices.clear();
+
+        let mut sessions: Vec<String> = self.session_slots.keys().cloned().collect();
+        let mut projects: Vec<String> = self.project_slots.keys().cloned().collect();
+        match self.hue_assignment {
+            HueAssignment::Sequential => {
+                sessions.sort_by_key(|s| self.session_slots[s]);
+                projects.sort_by_key(|p| self.project_slots[p]);
+                for (i, session) in sessions.into_iter().enumerate() {
+                    self.session_colors.insert(session, (i as f32 * 137.5) % 360.0);
+                }
+            }
+            HueAssignment::Stable => {
+                sessions.sort();
+                // Siblings then take their indices in name order
+                projects.sort_by(|a, b| project_path_parts(a).cmp(&project_path_parts(b)));
+                let mut taken = Vec::with_capacity(sessions.len());
+                for session in sessions {
+                    let hue = stable_session_hue(&session, &taken);
+                    taken.push(hue);
+                    self.session_colors.insert(session, hue);
+                }
+            }
+        }
+        for project in projects {
+            let hue = self.compute_project_hue(&project);
+            self.project_colors.insert(project, hue);
+        }
+    }
+
+    /// Load new graph data, initializing positions randomly. The selected and
+    /// hovered nodes stay set when their ids are still in the data.
+    pub fn load(&mut self, mut data: GraphData, bounds: egui::Rect) {
         use rand::Rng;
         let mut rng = rand::thread_rng();
 
+        // The database already skips excluded projects; this catches other sources
+        data.drop_excluded_projects(&self.excluded_projects);
+
         // Clear old state
         self.positions.clear();
         self.velocities.clear();
+        self.invalidate_communities();
+        self.temporal_build = None;
         self.node_index.clear();
         self.session_colors.clear();
         self.project_colors.clear();
+        self.session_slots.clear();
+        self.project_slots.clear();
         self.child_counts.clear();
         self.child_indices.clear();
         self.project_sessions.clear();
+        self.session_spans.clear();
 
         // Build node index and initialize positions
         for (i, node) in data.nodes.iter().enumerate() {
@@ -788,37 +1276,47 @@ impl GraphState {
             self.positions.insert(node.id.clone(), Pos2::new(x, y));
             self.velocities.insert(node.id.clone(), egui::Vec2::ZERO);
 
-            // Assign session color if not already assigned
-            if !self.session_colors.contains_key(&node.session_id) {
-                let hue = (self.session_colors.len() as f32 * 137.5) % 360.0;
-                self.session_colors.insert(node.session_id.clone(), hue);
+            // First-seen order; hues are assigned from these once all nodes are in
+            if !self.session_slots.contains_key(&node.session_id) {
+                self.session_slots.insert(node.session_id.clone(), self.session_slots.len());
             }
-
-            // Assign project color using tree-based hue assignment
-            // Projects under the same parent directory get similar hues
-            if !node.project.is_empty() && !self.project_colors.contains_key(&node.project) {
-                let hue = self.compute_project_hue(&node.project);
-                self.project_colors.insert(node.project.clone(), hue);
+            if !node.project.is_empty() && !self.project_slots.contains_key(&node.project) {
+                self.project_slots.insert(node.project.clone(), self.project_slots.len());
             }
         }
+        self.assign_hues();
 
         // Build project_sessions mapping for hybrid coloring
-        // Track earliest timestamp per session within each project
+        // Track earliest timestamp per session within each project, and each
+        // session's messages, span and last node in the same pass
         let mut session_timestamps: HashMap<String, (String, f64)> = HashMap::new(); // session_id -> (project, min_ts)
-        for node in data.nodes.iter() {
-            if node.project.is_empty() {
-                continue;
+        let mut session_messages: HashMap<String, Vec<(usize, Option<f64>)>> = HashMap::new();
+        for (i, node) in data.nodes.iter().enumerate() {
+            let is_message = matches!(node.role, Role::User | Role::Assistant | Role::Agent);
+            let ts = node.timestamp_secs();
+            if is_message {
+                session_messages.entry(node.session_id.clone()).or_default().push((i, ts));
             }
-            if let Some(ts) = node.timestamp_secs() {
-                session_timestamps
+            let Some(ts) = ts else {
+                continue;
+            };
+            if is_message {
+                self.session_spans
                     .entry(node.session_id.clone())
-                    .and_modify(|(_, existing_ts)| {
-                        if ts < *existing_ts {
-                            *existing_ts = ts;
-                        }
-                    })
-                    .or_insert((node.project.clone(), ts));
+                    .and_modify(|span| span.record(node, ts))
+                    .or_insert_with(|| SessionSpan::new(node, ts));
+            }
+            if node.project.is_empty() {
+                continue;
             }
+            session_timestamps
+                .entry(node.session_id.clone())
+                .and_modify(|(_, existing_ts)| {
+                    if ts < *existing_ts {
+                        *existing_ts = ts;
+                    }
+                })
+                .or_insert((node.project.clone(), ts));
         }
         // Group sessions by project
         for (session_id, (project, ts)) in session_timestamps {
@@ -831,6 +1329,7 @@ impl GraphState {
         for sessions in self.project_sessions.values_mut() {
             sessions.sort_by(|a, b| a.1.partial_cmp(&b.1).unwrap_or(std::cmp::Ordering::Equal));
         }
+        self.build_session_index(session_messages, data.nodes.len());
 
         // Compute max tokens for normalization
         self.max_tokens = data.nodes.iter()
@@ -839,11 +1338,31 @@ impl GraphState {
             .unwrap_or(1)
             .max(1); // Ensure non-zero for division
 
+        let mut topic_sessions: HashMap<&str, HashSet<&str>> = HashMap::new();
+        for edge in data.edges.iter().filter(|e| e.is_topic) {
+            topic_sessions.entry(edge.source.as_str()).or_default().insert(edge.session_id.as_str());
+        }
+        self.topic_sessions = topic_sessions.into_iter()
+            .map(|(topic, sessions)| (topic.to_string(), sessions.len()))
+            .collect();
+
+        // Vault notes and topics aren't messages; they stay prose
+        self.content_classes = data.nodes.iter()
+            .map(|n| match n.role {
+                Role::Obsidian | Role::Topic => ContentClass::default(),
+                _ => content_kind::classify(n.full_content.as_deref().unwrap_or(&n.content_preview)),
+            })
+            .collect();
         self.data = data;
+        self.refresh_edge_index();
         self.physics_enabled = true;
+        self.selected_node = self.selected_node.take().filter(|id| self.node_index.contains_key(id));
+        self.hovered_node = self.hovered_node.take().filter(|id| self.node_index.contains_key(id));
 
         // Build timeline data
         self.build_timeline();
+        self.build_tool_runs();
+        self.annotate_idle_gaps();
 
         // Build temporal edges (pre-computed at load time)
         if self.temporal_attraction_enabled {
@@ -851,26 +1370,126 @@ impl GraphState {
         }
     }
 
+    /// Replace graph data while keeping the layout and colors of nodes that
+    /// survive the reload. New nodes start beside a node from their session.
+    pub fn merge(&mut self, mut data: GraphData, bounds: egui::Rect) {
+        use rand::Rng;
+        let mut rng = rand::thread_rng();
+
+        data.drop_excluded_projects(&self.excluded_projects);
+        self.temporal_build = None;
+
+        let old_positions = std::mem::take(&mut self.positions);
+        let old_velocities = std::mem::take(&mut self.velocities);
+        let old_session_colors = std::mem::take(&mut self.session_colors);
+        let old_project_colors = std::mem::take(&mut self.project_colors);
+        let old_session_slots = std::mem::take(&mut self.session_slots);
+        let old_project_slots = std::mem::take(&mut self.project_slots);
+
+        self.load(data, bounds);
+
+        // Existing session colors win so a refresh never recolors the graph
+        self.session_colors.extend(old_session_colors);
+        self.project_colors.extend(old_project_colors);
+        self.session_slots = append_slots(old_session_slots, &self.session_slots);
+        self.project_slots = append_slots(old_project_slots, &self.project_slots);
+
+        let mut session_anchors: HashMap<&str, Pos2> = HashMap::new();
+        for node in &self.data.nodes {
+            if let Some(pos) = old_positions.get(&node.id) {
+                session_anchors.entry(node.session_id.as_str()).or_insert(*pos);
+            }
+        }
+        for node in &self.data.nodes {
+            if let Some(pos) = old_positions.get(&node.id) {
+                self.positions.insert(node.id.clone(), *pos);
+                let vel = old_velocities.get(&node.id).copied().unwrap_or(egui::Vec2::ZERO);
+                self.velocities.insert(node.id.clone(), vel);
+            } else if let Some(anchor) = session_anchors.get(node.session_id.as_str()) {
+                let jitter = egui::vec2(rng.gen_range(-20.0..20.0), rng.gen_range(-20.0..20.0));
+                self.positions.insert(node.id.clone(), *anchor + jitter);
+            }
+        }
+    }
+
+    /// Store the wall-clock gap on each conversation edge so drawing never parses timestamps
+    fn annotate_idle_gaps(&mut self) {
+        let times = &self.timeline.node_times;
+        let time_of = |id: &str| self.node_index.get(id).and_then(|&i| times.get(i).copied().flatten());
+        for edge in &mut self.data.edges {
+            if edge.is_temporal || edge.is_similarity || edge.is_topic || edge.is_obsidian {
+                continue;
+            }
+            edge.idle_secs = match (time_of(&edge.source), time_of(&edge.target)) {
+                (Some(a), Some(b)) => Some((b - a).abs()),
+                _ => None,
+            };
+        }
+    }
+
     /// Build pre-computed temporal edges between nodes close in time.
     /// Wrapper that builds edges for all nodes (no filtering).
     pub fn build_temporal_edges(&mut self) {
         self.build_temporal_edges_filtered(None);
     }
 
+    /// Order-independent hash of the nodes a build over `visible` would link and
+    /// the settings shaping it; equal scopes produce the same temporal edges
+    pub fn temporal_scope(&self, visible: Option<&HashSet<String>>) -> u64 {
+        use std::collections::hash_map::DefaultHasher;
+        use std::hash::{Hash, Hasher};
+        let mut hasher = DefaultHasher::new();
+        match visible {
+            Some(vis) => {
+                // Summing per-id hashes makes the result independent of set order
+                let ids = vis.iter().fold(0u64, |sum, id| {
+                    let mut h = DefaultHasher::new();
+                    id.hash(&mut h);
+                    sum.wrapping_add(h.finish())
+                });
+                (vis.len(), ids).hash(&mut hasher);
+            }
+            None => usize::MAX.hash(&mut hasher),
+        }
+        self.data.nodes.len().hash(&mut hasher);
+        self.temporal_window_secs.to_bits().hash(&mut hasher);
+        (self.max_temporal_edges, self.max_temporal_neighbors).hash(&mut hasher);
+        hasher.finish()
+    }
+
+    /// Rebuild temporal edges unless the layout has settled and the last build
+    /// already covered the same scope. Returns whether a rebuild ran.
+    pub fn rebuild_temporal_edges_if_changed(&mut self, visible: Option<&HashSet<String>>, settled: bool) -> bool {
+        if settled && self.temporal_build.is_some_and(|b| b.scope == self.temporal_scope(visible)) {
+            return false;
+        }
+        self.build_temporal_edges_filtered(visible);
+        true
+    }
+
     /// Build pre-computed temporal edges, optionally restricted to a visible set.
     /// Uses sliding window algorithm: O(n) instead of O(n²).
-    /// Caps at max_temporal_edges to prevent memory issues.
+    /// Caps at max_temporal_edges to prevent memory issues, and each node at
+    /// max_temporal_neighbors so the budget spreads across the timeline.
     /// When `visible` is Some, only nodes in the set participate in edge creation.
     pub fn build_temporal_edges_filtered(&mut self, visible: Option<&HashSet<String>>) {
+        let started = std::time::Instant::now();
+        let scope = self.temporal_scope(visible);
         // Remove any existing temporal edges first
         self.data.edges.retain(|e| !e.is_temporal);
 
         if self.timeline.sorted_indices.is_empty() {
+            self.temporal_build = Some(TemporalBuild { scope, edges: 0, elapsed: started.elapsed() });
+            self.edges_changed();
             return;
         }
 
         let window = self.temporal_window_secs;
         let max_edges = self.max_temporal_edges;
+        let max_neighbors = match self.max_temporal_neighbors {
+            0 => usize::MAX,
+            n => n,
+        };
 
         // Build filtered sorted list: (original_sorted_pos, node_index, timestamp)
         // Only include nodes that are in the visible set (if provided)
@@ -887,6 +1506,8 @@ impl GraphState {
 
         let node_count = filtered.len();
         let mut temporal_edges = Vec::new();
+        // Temporal edges so far per position in `filtered`
+        let mut degree = vec![0usize; node_count];
 
         // Sliding window over sorted timestamps
         for i in 0..node_count {
@@ -897,9 +1518,14 @@ impl GraphState {
                 let dt = ts_j - ts_i;
 
                 // Since sorted, if we exceed window we're done with this node
-                if dt > window {
+                if dt > window || degree[i] >= max_neighbors {
                     break;
                 }
+                if degree[j] >= max_neighbors {
+                    continue;
+                }
+                degree[i] += 1;
+                degree[j] += 1;
 
                 // Strength decays linearly from 1.0 to 0.0 over the window
                 let strength = 1.0 - (dt / window) as f32;
@@ -911,27 +1537,26 @@ impl GraphState {
 
                 // Hard cap to prevent runaway memory issues
                 if temporal_edges.len() >= max_edges {
-                    eprintln!(
-                        "Hit temporal edge limit of {} (window: {}s)",
-                        max_edges,
-                        window
-                    );
+                    // Rebuilt on every slider drag; one warning per second is plenty
+                    static LIMIT_LOG: log_buffer::Throttle = log_buffer::Throttle::new();
+                    if LIMIT_LOG.ready() {
+                        tracing::warn!(
+                            "Hit temporal edge limit of {} (window: {}s)",
+                            max_edges,
+                            window
+                        );
+                    }
+                    self.temporal_build = Some(TemporalBuild { scope, edges: temporal_edges.len(), elapsed: started.elapsed() });
                     self.data.edges.extend(temporal_edges);
+                    self.edges_changed();
                     return;
                 }
             }
         }
 
-        eprintln!(
-            "Built {} temporal edges (window: {}s, nodes: {}/{}, limit: {})",
-            temporal_edges.len(),
-            window,
-            node_count,
-            self.timeline.sorted_indices.len(),
-            max_edges
-        );
-
+        self.temporal_build = Some(TemporalBuild { scope, edges: temporal_edges.len(), elapsed: started.elapsed() });
         self.data.edges.extend(temporal_edges);
+        self.edges_changed();
     }
 
     /// Rebuild temporal edges with a new window size
@@ -950,6 +1575,8 @@ impl GraphState {
         } else {
             // Remove temporal edges
             self.data.edges.retain(|e| !e.is_temporal);
+            self.temporal_build = None;
+            self.edges_changed();
         }
     }
 
@@ -966,18 +1593,86 @@ impl GraphState {
     pub fn set_proximity_edges(&mut self, edges: Vec<GraphEdge>) {
         self.data.edges.retain(|e| !e.is_similarity);
         self.data.edges.extend(edges);
-    }
+        self.edges_changed();
+        self.invalidate_communities();
+    }
+
+    /// Per-session message order, from the time-sorted node indices
+    /// Order each session's messages by time. The sort is stable, so equal
+    /// and missing timestamps keep input order; untimed messages go last.
+    fn build_session_index(&mut self, messages: HashMap<String, Vec<(usize, Option<f64>)>>, node_count: usize) {
+        self.session_index.clear();
+        self.session_rank = vec![None; node_count];
+        for (session_id, mut entries) in messages {
+            entries.sort_by(|a, b| match (a.1, b.1) {
+                (Some(x), Some(y)) => x.partial_cmp(&y).unwrap_or(std::cmp::Ordering::Equal),
+                (Some(_), None) => std::cmp::Ordering::Less,
+                (None, Some(_)) => std::cmp::Ordering::Greater,
+                (None, None) => std::cmp::Ordering::Equal,
+            });
+            for (rank, &(idx, _)) in entries.iter().enumerate() {
+                self.session_rank[idx] = Some(rank);
+            }
+            self.session_index.insert(session_id, entries.into_iter().map(|(idx, _)| idx).collect());
+        }
+    }
+
+    /// Sessions whose latest message is within `window_secs` before `now`, most recent first
+    pub fn live_sessions(&self, now: f64, window_secs: f64) -> Vec<&str> {
+        let mut live: Vec<(&str, f64)> = self.session_spans.iter()
+            .filter(|(_, span)| span.end >= now - window_secs)
+            .map(|(id, span)| (id.as_str(), span.end))
+            .collect();
+        live.sort_by(|a, b| b.1.total_cmp(&a.1).then_with(|| a.0.cmp(b.0)));
+        live.into_iter().map(|(id, _)| id).collect()
+    }
+
+    /// Message indices of a session, oldest first (empty for unknown sessions)
+    pub fn session_nodes(&self, session_id: &str) -> &[usize] {
+        self.session_index.get(session_id).map_or(&[], Vec::as_slice)
+    }
+
+    /// The message after `node_id` in its session
+    pub fn next_in_session(&self, node_id: &str) -> Option<&GraphNode> {
+        self.session_step(node_id, true)
+    }
+
+    /// The message before `node_id` in its session
+    pub fn prev_in_session(&self, node_id: &str) -> Option<&GraphNode> {
+        self.session_step(node_id, false)
+    }
+
+    fn session_step(&self, node_id: &str, forward: bool) -> Option<&GraphNode> {
+        let idx = *self.node_index.get(node_id)?;
+        let rank = self.session_rank.get(idx).copied().flatten()?;
+        let target = if forward { rank + 1 } else { rank.checked_sub(1)? };
+        let order = self.session_nodes(&self.data.nodes[idx].session_id);
+        order.get(target).map(|&i| &self.data.nodes[i])
+    }
+
+    /// Up to `before` messages preceding node `idx` in its session and up to
+    /// `after` following it, both oldest first
+    pub fn session_neighbors(&self, idx: usize, before: usize, after: usize) -> (&[usize], &[usize]) {
+        let Some(rank) = self.session_rank.get(idx).copied().flatten() else {
+            return (&[], &[]);
+        };
+        let order = self.session_nodes(&self.data.nodes[idx].session_id);
+        let end = (rank + 1 + after).min(order.len());
+        (&order[rank.saturating_sub(before)..rank], &order[rank + 1..end])
+    }
 
     /// Build timeline sorted indices and timestamps for all item types.
     /// This creates a unified timeline that spans nodes, beads, and mail.
     fn build_timeline(&mut self) {
         // --- Build node timeline ---
+        // Parse every node timestamp once; per-frame code reads node_times
+        self.timeline.node_times = self.data.nodes.iter().map(|n| n.timestamp_secs()).collect();
         let mut timed_nodes: Vec<(usize, f64)> = self
-            .data
-            .nodes
+            .timeline
+            .node_times
             .iter()
             .enumerate()
-            .filter_map(|(i, node)| node.timestamp_secs().map(|t| (i, t)))
+            .filter_map(|(i, t)| t.map(|t| (i, t)))
             .collect();
         timed_nodes.sort_by(|a, b| a.1.partial_cmp(&b.1).unwrap_or(std::cmp::Ordering::Equal));
         self.timeline.sorted_indices = timed_nodes.iter().map(|(i, _)| *i).collect();
@@ -1044,6 +1739,7 @@ impl GraphState {
         // Initialize with all items visible
         self.timeline.position = 1.0;
         self.timeline.start_position = 0.0;
+        self.timeline.preview_position = None;
         self.update_visible_items();
     }
 
@@ -1051,26 +1747,32 @@ impl GraphState {
     /// This is the unified method that updates nodes, beads, and mail visibility.
     pub fn update_visible_items(&mut self) {
         let start_time = self.timeline.time_at_position(self.timeline.start_position);
-        let end_time = self.timeline.time_at_position(self.timeline.position);
+        let end_time = self.timeline.time_at_position(self.timeline.effective_position());
+        let everything = self.timeline.is_degenerate();
+        let in_window = |t: f64| everything || (t >= start_time && t <= end_time);
 
         // --- Update visible nodes ---
         self.timeline.visible_nodes.clear();
-        for (i, &idx) in self.timeline.sorted_indices.iter().enumerate() {
-            let t = self.timeline.timestamps[i];
-            if t >= start_time && t <= end_time {
-                if let Some(node) = self.data.nodes.get(idx) {
-                    self.timeline.visible_nodes.insert(node.id.clone());
-                }
+        let range = if everything {
+            0..self.timeline.sorted_indices.len()
+        } else {
+            self.timeline.window_range(start_time, end_time)
+        };
+        for &idx in &self.timeline.sorted_indices[range] {
+            if let Some(node) = self.data.nodes.get(idx) {
+                self.timeline.visible_nodes.insert(node.id.clone());
             }
         }
 
+        self.refresh_visible_edges();
+
         // --- Update visible beads ---
         self.timeline.visible_beads.clear();
         for (i, &idx) in self.timeline.sorted_bead_indices.iter().enumerate() {
             let t = self.timeline.bead_timestamps[i];
-            if t >= start_time && t <= end_time {
+            if in_window(t) {
                 if let Some(bead) = self.data.beads.get(idx) {
-                    self.timeline.visible_beads.insert(bead.id.clone());
+                    self.timeline.visible_beads.insert(ids::bead(&bead.id));
                 }
             }
         }
@@ -1079,7 +1781,7 @@ impl GraphState {
         self.timeline.visible_mail.clear();
         for (i, &idx) in self.timeline.sorted_mail_indices.iter().enumerate() {
             let t = self.timeline.mail_timestamps[i];
-            if t >= start_time && t <= end_time {
+            if in_window(t) {
                 if let Some(mail) = self.data.mail.get(idx) {
                     self.timeline.visible_mail.insert(mail.id.clone());
                 }
@@ -1087,6 +1789,17 @@ impl GraphState {
         }
     }
 
+    /// Set or clear the temporary hover-scrub position, refreshing visibility
+    /// when it changes. Returns true if anything changed.
+    pub fn set_timeline_preview(&mut self, preview: Option<f32>) -> bool {
+        if self.timeline.preview_position == preview {
+            return false;
+        }
+        self.timeline.preview_position = preview;
+        self.update_visible_items();
+        true
+    }
+
     /// Update which nodes are visible based on timeline position.
     /// This is a convenience wrapper that calls the unified update method.
     pub fn update_visible_nodes(&mut self) {
@@ -1098,9 +1811,9 @@ impl GraphState {
         self.timeline.visible_nodes.contains(id)
     }
 
-    /// Check if a bead is visible in the current timeline window
-    pub fn is_bead_visible(&self, id: &str) -> bool {
-        self.timeline.visible_beads.contains(id)
+    /// Check if a bead is visible in the current timeline window, by issue id
+    pub fn is_bead_visible(&self, issue_id: &str) -> bool {
+        self.timeline.visible_beads.contains(&ids::bead(issue_id))
     }
 
     /// Check if a mail item is visible in the current timeline window
@@ -1114,11 +1827,90 @@ impl GraphState {
             && self.timeline.visible_nodes.contains(&edge.target)
     }
 
+    /// Recompute `visible_edges` from the visible nodes. Runs whenever the
+    /// window or the edge list changes, so per-frame code can index it instead
+    /// of looking both endpoints up for every edge.
+    pub fn refresh_visible_edges(&mut self) {
+        let visible = &self.timeline.visible_nodes;
+        self.timeline.visible_edges.clear();
+        self.timeline.visible_edges.extend(
+            self.data.edges.iter().map(|e| visible.contains(&e.source) && visible.contains(&e.target))
+        );
+    }
+
+    /// Call after adding or removing edges: rebuilds the per-node edge lists
+    /// and the visible-edge mask
+    pub fn edges_changed(&mut self) {
+        self.refresh_edge_index();
+        self.refresh_visible_edges();
+    }
+
+    fn refresh_edge_index(&mut self) {
+        self.edges_generation += 1;
+        self.incident_edges.clear();
+        self.incident_edges.resize_with(self.data.nodes.len(), Vec::new);
+        for (i, edge) in self.data.edges.iter().enumerate() {
+            if let Some(&n) = self.node_index.get(&edge.source) {
+                self.incident_edges[n].push(i);
+            }
+            if edge.target != edge.source {
+                if let Some(&n) = self.node_index.get(&edge.target) {
+                    self.incident_edges[n].push(i);
+                }
+            }
+        }
+    }
+
+    /// Edges touching the node at `idx`, from the cached per-node lists
+    pub fn node_edges(&self, idx: usize) -> impl Iterator<Item = &GraphEdge> {
+        self.incident_edges.get(idx).into_iter().flatten().filter_map(|&i| self.data.edges.get(i))
+    }
+
+    /// Edges touching a node counted by kind, in `EdgeKind::ALL` order
+    pub fn degree_by_kind(&self, idx: usize) -> [usize; EdgeKind::ALL.len()] {
+        let mut degree = [0; EdgeKind::ALL.len()];
+        for edge in self.node_edges(idx) {
+            degree[EdgeKind::of(edge) as usize] += 1;
+        }
+        degree
+    }
+
+    /// A node's temporal and similarity links as (neighbour index, kind,
+    /// strength), strongest first, at most `limit`
+    pub fn strongest_links(&self, idx: usize, limit: usize) -> Vec<(usize, EdgeKind, f32)> {
+        let Some(id) = self.data.nodes.get(idx).map(|n| n.id.as_str()) else {
+            return Vec::new();
+        };
+        let mut links: Vec<(usize, EdgeKind, f32)> = self.node_edges(idx)
+            .filter(|e| e.is_temporal || e.is_similarity)
+            .filter_map(|e| {
+                let other = if e.source == id { &e.target } else { &e.source };
+                let neighbour = *self.node_index.get(other)?;
+                Some((neighbour, EdgeKind::of(e), e.similarity.unwrap_or(0.0)))
+            })
+            .collect();
+        links.sort_by(|a, b| b.2.total_cmp(&a.2).then(a.0.cmp(&b.0)));
+        links.truncate(limit);
+        links
+    }
+
+    /// Whether the edge at `index` is inside the timeline window
+    pub fn is_edge_index_visible(&self, index: usize) -> bool {
+        self.timeline.visible_edges.get(index).copied().unwrap_or(false)
+    }
+
+    /// Edges with both endpoints inside the timeline window
+    pub fn visible_edges(&self) -> impl Iterator<Item = &GraphEdge> {
+        self.data.edges.iter()
+            .zip(&self.timeline.visible_edges)
+            .filter_map(|(edge, &visible)| visible.then_some(edge))
+    }
+
     /// Get the current timeline window as (start_time, end_time) in epoch seconds.
     /// Useful for panels to filter their data.
     pub fn get_timeline_window(&self) -> (f64, f64) {
         let start_time = self.timeline.time_at_position(self.timeline.start_position);
-        let end_time = self.timeline.time_at_position(self.timeline.position);
+        let end_time = self.timeline.time_at_position(self.timeline.effective_position());
         (start_time, end_time)
     }
 
@@ -1144,9 +1936,26 @@ impl GraphState {
         self.node_index.get(id).map(|&i| &self.data.nodes[i])
     }
 
+    /// Content class of a node by id
+    pub fn content_class(&self, id: &str) -> Option<&ContentClass> {
+        self.node_index.get(id).and_then(|&i| self.content_classes.get(i))
+    }
+
     /// Apply global hue offset, wrapping around 360°
     pub fn apply_hue_offset(&self, hue: f32) -> f32 {
-        (hue + self.hue_offset) % 360.0
+        (hue + self.hue_offset).rem_euclid(360.0)
+    }
+
+    /// Display hue for a project: its override, else the computed hue shifted by the offset
+    pub fn project_hue(&self, project: &str) -> f32 {
+        self.hue_overrides.projects.get(project).copied()
+            .unwrap_or_else(|| self.apply_hue_offset(self.project_colors.get(project).copied().unwrap_or(0.0)))
+    }
+
+    /// Display hue for a session: its override, else the sequential hue shifted by the offset
+    pub fn session_hue(&self, session_id: &str) -> f32 {
+        self.hue_overrides.sessions.get(session_id).copied()
+            .unwrap_or_else(|| self.apply_hue_offset(self.session_colors.get(session_id).copied().unwrap_or(0.0)))
     }
 
     /// Randomize the global hue offset (preserves relative color relationships)
@@ -1169,57 +1978,125 @@ impl GraphState {
         0.5 // Default to middle if not found
     }
 
-    /// Get the color for a node based on current color mode
-    pub fn node_color(&self, node: &GraphNode) -> egui::Color32 {
+    /// Colorblind-safe categorical color for a session/project pair
+    fn categorical_color(&self, session_id: &str, project: &str) -> egui::Color32 {
+        let session_color = || crate::theme::categorical(self.session_slots.get(session_id).copied().unwrap_or(0));
+        if project.is_empty() {
+            return session_color();
+        }
+        let project_color = crate::theme::categorical(self.project_slots.get(project).copied().unwrap_or(0));
         match self.color_mode {
-            ColorMode::Project if !node.project.is_empty() => {
-                let hue = self.project_colors.get(&node.project).copied().unwrap_or(0.0);
-                hsl_to_rgb(self.apply_hue_offset(hue), 0.7, 0.55)
+            ColorMode::Project => project_color,
+            ColorMode::Hybrid => {
+                // Older sessions fade toward grey, newer ones keep the full color
+                let t = self.session_position_in_project(session_id, project);
+                lerp_color(to_greyscale(project_color), project_color, 0.45 + t * 0.55)
             }
-            ColorMode::Hybrid if !node.project.is_empty() => {
+            ColorMode::Session | ColorMode::Community => session_color(),
+        }
+    }
+
+    /// Node color for a session/project pair under the current color mode
+    pub fn session_color(&self, session_id: &str, project: &str) -> egui::Color32 {
+        if self.colorblind_safe {
+            return self.categorical_color(session_id, project);
+        }
+        match self.color_mode {
+            ColorMode::Project if !project.is_empty() => {
+                let hue = self.project_hue(project);
+                hsl_to_rgb(hue, self.shading.project_saturation, self.node_lightness(self.shading.project_lightness))
+            }
+            ColorMode::Hybrid if !project.is_empty() => {
                 // Project hue + session position determines S/L
-                let hue = self.project_colors.get(&node.project).copied().unwrap_or(0.0);
-                let t = self.session_position_in_project(&node.session_id, &node.project);
+                let hue = self.project_hue(project);
+                let t = self.session_position_in_project(session_id, project);
                 // Older sessions: lighter, less saturated (faded)
                 // Newer sessions: darker, more saturated (prominent)
                 let sat = 0.5 + t * 0.4;    // 0.5 -> 0.9
                 let light = 0.65 - t * 0.2; // 0.65 -> 0.45
-                hsl_to_rgb(self.apply_hue_offset(hue), sat, light)
+                hsl_to_rgb(hue, sat, self.node_lightness(light))
             }
             _ => {
                 // Session mode (or fallback for empty project)
-                let hue = self.session_colors.get(&node.session_id).copied().unwrap_or(0.0);
-                hsl_to_rgb(self.apply_hue_offset(hue), 0.7, 0.5)
+                let hue = self.session_hue(session_id);
+                hsl_to_rgb(hue, self.shading.session_saturation, self.node_lightness(self.shading.session_lightness))
             }
         }
     }
 
+    /// Legend color for a project
+    pub fn project_color(&self, project: &str) -> egui::Color32 {
+        if self.colorblind_safe {
+            return crate::theme::categorical(self.project_slots.get(project).copied().unwrap_or(0));
+        }
+        let hue = self.project_hue(project);
+        hsl_to_rgb(hue, self.shading.project_saturation, self.shading.project_lightness)
+    }
+
+    /// Get the color for a node based on current color mode
+    pub fn node_color(&self, node: &GraphNode) -> egui::Color32 {
+        if self.color_mode == ColorMode::Community {
+            // Session colors stand in until detection finishes
+            if let Some(community) = self.node_community(&node.id) {
+                return self.community_color(community);
+            }
+        }
+        self.session_color(&node.session_id, &node.project)
+    }
+
     /// Get a lighter version of node color (for fills)
     pub fn node_color_light(&self, node: &GraphNode) -> egui::Color32 {
+        if self.colorblind_safe {
+            return lerp_color(self.categorical_color(&node.session_id, &node.project), egui::Color32::WHITE, 0.35);
+        }
         match self.color_mode {
             ColorMode::Project if !node.project.is_empty() => {
-                let hue = self.project_colors.get(&node.project).copied().unwrap_or(0.0);
-                hsl_to_rgb(self.apply_hue_offset(hue), 0.6, 0.75)
+                let hue = self.project_hue(&node.project);
+                hsl_to_rgb(hue, 0.6, self.node_lightness(0.75))
             }
             ColorMode::Hybrid if !node.project.is_empty() => {
-                let hue = self.project_colors.get(&node.project).copied().unwrap_or(0.0);
+                let hue = self.project_hue(&node.project);
                 let t = self.session_position_in_project(&node.session_id, &node.project);
                 // Lighter variant: shift both S and L up slightly
                 let sat = 0.4 + t * 0.3;    // 0.4 -> 0.7
                 let light = 0.8 - t * 0.15; // 0.8 -> 0.65
-                hsl_to_rgb(self.apply_hue_offset(hue), sat, light)
+                hsl_to_rgb(hue, sat, self.node_lightness(light))
             }
             _ => {
-                let hue = self.session_colors.get(&node.session_id).copied().unwrap_or(0.0);
-                hsl_to_rgb(self.apply_hue_offset(hue), 0.6, 0.7)
+                let hue = self.session_hue(&node.session_id);
+                hsl_to_rgb(hue, 0.6, self.node_lightness(0.7))
             }
         }
     }
 
+    /// HSL lightness for a node shade on the current background
+    fn node_lightness(&self, light: f32) -> f32 {
+        if self.is_light {
+            light * LIGHT_BACKGROUND_LIGHTNESS
+        } else {
+            light
+        }
+    }
+
     /// Get the session color (hue) for an edge
     pub fn edge_color(&self, edge: &GraphEdge) -> egui::Color32 {
+        if self.colorblind_safe {
+            let [_, sky_blue, bluish_green, _, _, _, reddish_purple, _] = crate::theme::OKABE_ITO;
+            return if edge.is_similarity {
+                edge.query_index.map_or(sky_blue, |qi| crate::theme::query_color(qi, true))
+            } else if edge.is_topic {
+                bluish_green
+            } else if edge.is_obsidian {
+                reddish_purple
+            } else {
+                let project = self.get_node(&edge.source).map(|n| n.project.as_str()).unwrap_or("");
+                lerp_color(self.categorical_color(&edge.session_id, project), egui::Color32::BLACK, 0.25)
+            };
+        }
         if edge.is_similarity {
-            egui::Color32::from_rgb(6, 182, 212) // Cyan
+            // One hue per originating proximity query; cyan when unattributed
+            edge.query_index
+                .map_or(egui::Color32::from_rgb(6, 182, 212), |qi| crate::theme::query_color(qi, false))
         } else if edge.is_topic {
             egui::Color32::from_rgb(34, 197, 94) // Green
         } else if edge.is_obsidian {
@@ -1230,31 +2107,40 @@ impl GraphState {
                     // Find source node's project for edge color
                     if let Some(node) = self.get_node(&edge.source) {
                         if !node.project.is_empty() {
-                            let hue = self.project_colors.get(&node.project).copied().unwrap_or(0.0);
-                            return hsl_to_rgb(self.apply_hue_offset(hue), 0.5, 0.4);
+                            let hue = self.project_hue(&node.project);
+                            return hsl_to_rgb(hue, 0.5, 0.4);
                         }
                     }
                     // Fallback to session color
-                    let hue = self.session_colors.get(&edge.session_id).copied().unwrap_or(0.0);
-                    hsl_to_rgb(self.apply_hue_offset(hue), 0.5, 0.4)
+                    let hue = self.session_hue(&edge.session_id);
+                    hsl_to_rgb(hue, 0.5, 0.4)
                 }
                 ColorMode::Hybrid => {
                     // Use source node's hybrid coloring
                     if let Some(node) = self.get_node(&edge.source) {
                         if !node.project.is_empty() {
-                            let hue = self.project_colors.get(&node.project).copied().unwrap_or(0.0);
+                            let hue = self.project_hue(&node.project);
                             let t = self.session_position_in_project(&node.session_id, &node.project);
                             let sat = 0.4 + t * 0.3;
                             let light = 0.5 - t * 0.15;
-                            return hsl_to_rgb(self.apply_hue_offset(hue), sat, light);
+                            return hsl_to_rgb(hue, sat, light);
                         }
                     }
-                    let hue = self.session_colors.get(&edge.session_id).copied().unwrap_or(0.0);
-                    hsl_to_rgb(self.apply_hue_offset(hue), 0.5, 0.4)
+                    let hue = self.session_hue(&edge.session_id);
+                    hsl_to_rgb(hue, 0.5, 0.4)
                 }
                 ColorMode::Session => {
-                    let hue = self.session_colors.get(&edge.session_id).copied().unwrap_or(0.0);
-                    hsl_to_rgb(self.apply_hue_offset(hue), 0.7, 0.5)
+                    let hue = self.session_hue(&edge.session_id);
+                    hsl_to_rgb(hue, 0.7, 0.5)
+                }
+                ColorMode::Community => {
+                    // Both endpoints share a community for every structural edge
+                    if let Some(community) = self.node_community(&edge.source) {
+                        let hue = super::community::hue(community);
+                        return hsl_to_rgb(self.apply_hue_offset(hue), 0.5, 0.4);
+                    }
+                    let hue = self.session_hue(&edge.session_id);
+                    hsl_to_rgb(hue, 0.5, 0.4)
                 }
             }
         }
@@ -1267,8 +2153,27 @@ impl Default for GraphState {
     }
 }
 
+/// Keep existing slot assignments and number newly seen keys after them
+fn append_slots(mut old: HashMap<String, usize>, fresh: &HashMap<String, usize>) -> HashMap<String, usize> {
+    let mut new_keys: Vec<(&String, usize)> = fresh
+        .iter()
+        .filter(|(k, _)| !old.contains_key(*k))
+        .map(|(k, &slot)| (k, slot))
+        .collect();
+    new_keys.sort_by_key(|(_, slot)| *slot);
+    for (key, _) in new_keys {
+        let next = old.len();
+        old.insert(key.clone(), next);
+    }
+    old
+}
+
 /// Convert HSL to RGB color
 pub fn hsl_to_rgb(h: f32, s: f32, l: f32) -> egui::Color32 {
+    // Wrap the hue into [0, 360) and clamp saturation/lightness to [0, 1]
+    let h = if h.is_finite() { h.rem_euclid(360.0) } else { 0.0 };
+    let s = s.clamp(0.0, 1.0);
+    let l = l.clamp(0.0, 1.0);
     let c = (1.0 - (2.0 * l - 1.0).abs()) * s;
     let x = c * (1.0 - ((h / 60.0) % 2.0 - 1.0).abs());
     let m = l - c / 2.0;
@@ -1287,11 +2192,8 @@ pub fn hsl_to_rgb(h: f32, s: f32, l: f32) -> egui::Color32 {
         (c, 0.0, x)
     };
 
-    egui::Color32::from_rgb(
-        ((r + m) * 255.0) as u8,
-        ((g + m) * 255.0) as u8,
-        ((b + m) * 255.0) as u8,
-    )
+    let channel = |v: f32| ((v + m) * 255.0).round().clamp(0.0, 255.0) as u8;
+    egui::Color32::from_rgb(channel(r), channel(g), channel(b))
 }
 
 /// Convert a color to greyscale (using luminosity method)
@@ -1327,6 +2229,66 @@ pub fn lerp_color(a: egui::Color32, b: egui::Color32, t: f32) -> egui::Color32 {
     )
 }
 
+/// Directory components of a project path, ignoring a leading `~/`
+fn project_path_parts(project: &str) -> Vec<&str> {
+    project.trim_start_matches("~/").split('/').filter(|s| !s.is_empty()).collect()
+}
+
+/// Smallest hue gap, in degrees, a stable session hue is nudged to keep from
+/// the sessions before it
+const SESSION_HUE_MIN_GAP: f32 = 6.0;
+/// Rehashes tried before a crowded session keeps its first hue
+const SESSION_HUE_NUDGES: u32 = 8;
+
+/// Hue for a session from its id alone, rehashed while it lands within
+/// `SESSION_HUE_MIN_GAP` of a hue in `taken`
+pub fn stable_session_hue(session_id: &str, taken: &[f32]) -> f32 {
+    (0..SESSION_HUE_NUDGES)
+        .map(|salt| hashed_hue(session_id, salt))
+        .find(|&hue| taken.iter().all(|&t| hue_distance(hue, t) >= SESSION_HUE_MIN_GAP))
+        .unwrap_or_else(|| hashed_hue(session_id, 0))
+}
+
+/// FNV-1a of the id and salt mapped onto 0..360 in tenths of a degree. Fixed
+/// rather than `DefaultHasher`, whose output may change between Rust releases.
+fn hashed_hue(id: &str, salt: u32) -> f32 {
+    let mut hash: u64 = 0xcbf2_9ce4_8422_2325;
+    for byte in id.bytes().chain(salt.to_le_bytes()) {
+        hash ^= byte as u64;
+        hash = hash.wrapping_mul(0x0000_0100_0000_01b3);
+    }
+    (hash % 3600) as f32 / 10.0
+}
+
+/// Distance between two hues around the color wheel
+fn hue_distance(a: f32, b: f32) -> f32 {
+    let d = (a - b).rem_euclid(360.0);
+    d.min(360.0 - d)
+}
+
+/// Idle gap label for a conversation edge: "+23m", "+2h 05m", "+3d"
+pub fn format_idle_gap(secs: f64) -> String {
+    let minutes = (secs.max(0.0) / 60.0).round() as u64;
+    let (d, h, m) = (minutes / 1440, minutes % 1440 / 60, minutes % 60);
+    if d > 0 {
+        format!("+{}d", d)
+    } else if h > 0 {
+        format!("+{}h {:02}m", h, m)
+    } else {
+        format!("+{}m", m)
+    }
+}
+
+/// How far toward grey to shift a conversation edge idle for `secs`: none under
+/// `threshold`, then growing with each doubling until 16× the threshold
+pub fn idle_gap_cooling(secs: f64, threshold: f64) -> f32 {
+    if threshold <= 0.0 || secs < threshold {
+        return 0.0;
+    }
+    let doublings = (secs / threshold).log2() as f32;
+    0.25 + 0.35 * (doublings / 4.0).min(1.0)
+}
+
 // ============================================================================
 // Histogram Data Structures
 // ============================================================================
@@ -1351,6 +2313,11 @@ impl TokenDisplayMode {
             TokenDisplayMode::Rate => "Rate",
         }
     }
+
+    /// All modes for UI iteration
+    pub fn all() -> &'static [TokenDisplayMode] {
+        &[TokenDisplayMode::Absolute, TokenDisplayMode::Percentage, TokenDisplayMode::Rate]
+    }
 }
 
 /// How to order/stack bars in the histogram
@@ -1376,8 +2343,19 @@ impl StackOrder {
             StackOrder::BySession => "Session",
         }
     }
+
+    /// All orders for UI iteration
+    pub fn all() -> &'static [StackOrder] {
+        &[StackOrder::ByTokenType, StackOrder::ByRole, StackOrder::ByProject, StackOrder::BySession]
+    }
 }
 
+/// Segment label used for nodes without a project
+pub const NO_PROJECT_LABEL: &str = "(no project)";
+
+/// Segment labels for token types, in stacking order
+pub const TOKEN_TYPE_LABELS: [&str; 4] = ["input", "output", "cache_read", "cache_creation"];
+
 /// Filter criteria for histogram data
 #[derive(Debug, Clone, Default)]
 pub struct HistogramFilter {
@@ -1410,6 +2388,88 @@ impl HistogramFilter {
             include_cache_creation: true,
         }
     }
+
+    fn token_type_flags(&self) -> [bool; 4] {
+        [self.include_input, self.include_output, self.include_cache_read, self.include_cache_creation]
+    }
+
+    fn set_token_type_flags(&mut self, flags: [bool; 4]) {
+        [self.include_input, self.include_output, self.include_cache_read, self.include_cache_creation] = flags;
+    }
+
+    /// Toggle the filter for a segment label under the given stack order.
+    /// Projects, sessions and roles toggle membership of their include list;
+    /// the first token-type click narrows to that type, and clearing the last
+    /// included type restores all of them. Returns false for unknown labels.
+    pub fn toggle_segment(&mut self, order: StackOrder, label: &str) -> bool {
+        fn toggle<T: PartialEq>(list: &mut Vec<T>, item: T) {
+            match list.iter().position(|x| *x == item) {
+                Some(i) => {
+                    list.remove(i);
+                }
+                None => list.push(item),
+            }
+        }
+        match order {
+            StackOrder::ByProject => {
+                let project = if label == NO_PROJECT_LABEL { "" } else { label };
+                toggle(&mut self.projects, project.to_string());
+            }
+            StackOrder::BySession => toggle(&mut self.sessions, label.to_string()),
+            StackOrder::ByRole => match Role::all().iter().find(|r| r.label() == label) {
+                Some(role) => toggle(&mut self.roles, role.clone()),
+                None => return false,
+            },
+            StackOrder::ByTokenType => {
+                let Some(i) = TOKEN_TYPE_LABELS.iter().position(|t| *t == label) else {
+                    return false;
+                };
+                let mut flags = self.token_type_flags();
+                if flags.iter().all(|f| *f) {
+                    flags = [false; 4];
+                    flags[i] = true;
+                } else {
+                    flags[i] = !flags[i];
+                    if !flags.iter().any(|f| *f) {
+                        flags = [true; 4];
+                    }
+                }
+                self.set_token_type_flags(flags);
+            }
+        }
+        true
+    }
+
+    /// Active segment filters as (stack order, segment label) pairs, for breadcrumbs
+    pub fn active_segments(&self) -> Vec<(StackOrder, String)> {
+        let mut active: Vec<(StackOrder, String)> = self
+            .projects
+            .iter()
+            .map(|p| (StackOrder::ByProject, if p.is_empty() { NO_PROJECT_LABEL } else { p.as_str() }.to_string()))
+            .collect();
+        active.extend(self.sessions.iter().map(|s| (StackOrder::BySession, s.clone())));
+        active.extend(self.roles.iter().map(|r| (StackOrder::ByRole, r.label().to_string())));
+        let flags = self.token_type_flags();
+        if !flags.iter().all(|f| *f) {
+            active.extend(
+                TOKEN_TYPE_LABELS
+                    .iter()
+                    .zip(flags)
+                    .filter(|(_, included)| *included)
+                    .map(|(label, _)| (StackOrder::ByTokenType, label.to_string())),
+            );
+        }
+        active
+    }
+
+    /// Name of the single included token type, if only one is included
+    pub fn single_token_type(&self) -> Option<&'static str> {
+        let flags = self.token_type_flags();
+        match flags.iter().filter(|f| **f).count() {
+            1 => TOKEN_TYPE_LABELS.iter().zip(flags).find(|(_, f)| *f).map(|(label, _)| *label),
+            _ => None,
+        }
+    }
 }
 
 /// A segment within a histogram bin (for stacked bars)
@@ -1539,6 +2599,193 @@ impl HistogramState {
             self.dirty = true;
         }
     }
+
+    /// Toggle a segment filter and mark dirty
+    pub fn toggle_segment_filter(&mut self, order: StackOrder, label: &str) {
+        if self.filter.toggle_segment(order, label) {
+            self.dirty = true;
+        }
+    }
+
+    /// Bar height for a value as a fraction of the chart height.
+    /// Log scale uses `ln(1 + value) / ln(1 + max_value)`.
+    pub fn bar_fraction(&self, value: i64, log_scale: bool) -> f32 {
+        if self.max_value <= 0 || value <= 0 {
+            return 0.0;
+        }
+        let fraction = if log_scale {
+            (value as f64).ln_1p() / (self.max_value as f64).ln_1p()
+        } else {
+            value as f64 / self.max_value as f64
+        };
+        fraction.clamp(0.0, 1.0) as f32
+    }
+
+    /// Powers of ten from 1k up to `max_value`, for log-scale gridlines
+    pub fn log_ticks(&self) -> Vec<i64> {
+        std::iter::successors(Some(1_000i64), |t| t.checked_mul(10))
+            .take_while(|t| *t <= self.max_value)
+            .collect()
+    }
+
+    /// Value shown for a token count under the current display mode
+    pub fn displayed_value(&self, count: i64, bin: &TokenBin) -> f64 {
+        match self.display_mode {
+            TokenDisplayMode::Absolute => count as f64,
+            TokenDisplayMode::Percentage => count as f64 / self.total_tokens.max(1) as f64 * 100.0,
+            TokenDisplayMode::Rate => count as f64 / (bin.duration() / 60.0).max(f64::EPSILON),
+        }
+    }
+
+    /// Export the current bins as CSV: one row per (bin, segment) followed by a
+    /// `total` row per bin. Empty bins get just their zero `total` row, so every
+    /// bin in the range appears. Times are ISO 8601 UTC; `displayed_value`
+    /// follows the display mode while `token_count` is always the raw count.
+    pub fn to_csv(&self) -> String {
+        fn iso(secs: f64) -> String {
+            chrono::DateTime::from_timestamp(secs.floor() as i64, 0)
+                .map(|t| t.format("%Y-%m-%dT%H:%M:%SZ").to_string())
+                .unwrap_or_default()
+        }
+        fn field(value: &str) -> String {
+            if value.contains([',', '"', '\n', '\r']) {
+                format!("\"{}\"", value.replace('"', "\"\""))
+            } else {
+                value.to_string()
+            }
+        }
+
+        let mut csv = String::from("bin_start,bin_end,segment_label,token_count,displayed_value\n");
+        for bin in &self.bins {
+            let (start, end) = (iso(bin.start_time), iso(bin.end_time));
+            let rows = bin.segments.iter().map(|s| (s.label.as_str(), s.count));
+            for (label, count) in rows.chain(std::iter::once(("total", bin.total))) {
+                csv.push_str(&format!(
+                    "{},{},{},{},{:.2}\n",
+                    start,
+                    end,
+                    field(label),
+                    count,
+                    self.displayed_value(count, bin)
+                ));
+            }
+        }
+        csv
+    }
+
+    /// Snap a brushed time span to the edges of the bins it touches.
+    /// The endpoints may be in either order; times outside the bins are clamped.
+    pub fn brush_range(&self, a: f64, b: f64) -> Option<(f64, f64)> {
+        let first = self.bins.first()?;
+        let last = self.bins.last()?;
+        let bin_at = |t: f64| {
+            let t = t.clamp(first.start_time, last.end_time);
+            self.bins
+                .iter()
+                .position(|bin| t < bin.end_time)
+                .unwrap_or(self.bins.len() - 1)
+        };
+        let (lo, hi) = (bin_at(a.min(b)), bin_at(a.max(b)));
+        Some((self.bins[lo].start_time, self.bins[hi].end_time))
+    }
+
+    /// Rebuild bins from graph data over `[min_time, max_time]`.
+    /// Bins are half-open `[start, end)`, except the last which also takes `max_time`.
+    /// Nodes without timestamps (or outside the range) are skipped. Colors come
+    /// from `palette` (token types, roles) and `graph` (project/session).
+    pub fn rebuild(
+        &mut self,
+        data: &GraphData,
+        min_time: f64,
+        max_time: f64,
+        graph: &GraphState,
+        palette: &crate::theme::Palette,
+    ) {
+        self.bins.clear();
+        self.max_value = 0;
+        self.total_tokens = 0;
+        self.dirty = false;
+        if max_time <= min_time || self.bin_count == 0 {
+            return;
+        }
+
+        let bin_count = self.bin_count;
+        let width = (max_time - min_time) / bin_count as f64;
+        // Per bin: segment key -> (count, color); BTreeMap keeps stacking order stable
+        let mut sums: Vec<std::collections::BTreeMap<(usize, String), (i64, egui::Color32)>> =
+            vec![Default::default(); bin_count];
+
+        let filter = &self.filter;
+        for node in &data.nodes {
+            if !filter.projects.is_empty() && !filter.projects.contains(&node.project) {
+                continue;
+            }
+            if !filter.sessions.is_empty() && !filter.sessions.contains(&node.session_id) {
+                continue;
+            }
+            if !filter.roles.is_empty() && !filter.roles.contains(&node.role) {
+                continue;
+            }
+            let Some(t) = node.timestamp_secs() else {
+                continue;
+            };
+            if t < min_time || t > max_time {
+                continue;
+            }
+            let idx = (((t - min_time) / width) as usize).min(bin_count - 1);
+
+            let token_types = [
+                (filter.include_input, node.input_tokens, TOKEN_TYPE_LABELS[0], palette.histogram.input),
+                (filter.include_output, node.output_tokens, TOKEN_TYPE_LABELS[1], palette.histogram.output),
+                (filter.include_cache_read, node.cache_read_tokens, TOKEN_TYPE_LABELS[2], palette.histogram.cache_read),
+                (filter.include_cache_creation, node.cache_creation_tokens, TOKEN_TYPE_LABELS[3], palette.histogram.cache_create),
+            ];
+            let bin = &mut sums[idx];
+
+            if self.stack_order == StackOrder::ByTokenType {
+                for (order, (included, tokens, label, color)) in token_types.into_iter().enumerate() {
+                    let count = tokens.unwrap_or(0) as i64;
+                    if included && count > 0 {
+                        bin.entry((order, label.to_string())).or_insert((0, color)).0 += count;
+                    }
+                }
+                continue;
+            }
+
+            let count: i64 = token_types
+                .iter()
+                .filter(|(included, ..)| *included)
+                .map(|(_, tokens, ..)| tokens.unwrap_or(0) as i64)
+                .sum();
+            if count <= 0 {
+                continue;
+            }
+            let (key, color) = match self.stack_order {
+                StackOrder::ByRole => {
+                    let order = Role::all().iter().position(|r| *r == node.role).unwrap_or(0);
+                    ((order, node.role.label().to_string()), node.role.color(palette))
+                }
+                StackOrder::ByProject => {
+                    let label = if node.project.is_empty() { NO_PROJECT_LABEL } else { node.project.as_str() };
+                    ((0, label.to_string()), graph.node_color(node))
+                }
+                _ => ((0, node.session_id.clone()), graph.node_color(node)),
+            };
+            bin.entry(key).or_insert((0, color)).0 += count;
+        }
+
+        for (i, segments) in sums.into_iter().enumerate() {
+            let start = min_time + i as f64 * width;
+            let end = if i + 1 == bin_count { max_time } else { start + width };
+            let mut bin = TokenBin::new(start, end);
+            for ((_, label), (count, color)) in segments {
+                bin.add_segment(label, count, color);
+            }
+            self.max_value = self.max_value.max(bin.total);
+            self.total_tokens += bin.total;
+            self.bins.push(bin);
+        }
+    }
 }
 
 #[cfg(test)]
@@ -1569,6 +2816,211 @@ mod tests {
         assert!(ts_invalid.is_none());
     }
 
+    /// Hand-rolled parser used before the switch to chrono, kept as a cross-check.
+    ///
+    /// Accepts `2025-12-31T01:30:07.726213+00:00`, negative offsets (`-05:00`),
+    /// a trailing `Z`, a space instead of `T`, and date-only `2025-12-31`.
+    /// Timestamps without an offset are taken as UTC; fractional seconds are kept.
+    fn legacy_parse_iso_timestamp(ts: &str) -> Option<f64> {
+        let ts = ts.trim();
+        let (date, time) = match ts.find(['T', ' ']) {
+            Some(i) => (&ts[..i], Some(&ts[i + 1..])),
+            None => (ts, None),
+        };
+
+        let mut date_parts = date.splitn(3, '-');
+        let year: i32 = date_parts.next()?.parse().ok()?;
+        let month: u32 = date_parts.next()?.parse().ok()?;
+        // Tolerate a zone suffix on date-only values ("2025-12-31Z", "2025-12-31+00:00")
+        let day: u32 = date_parts.next()?.split(['+', 'Z']).next()?.parse().ok()?;
+        if !(1..=12).contains(&month) || !(1..=31).contains(&day) {
+            return None;
+        }
+        let midnight = legacy_days_from_civil(year, month, day) as f64 * 86400.0;
+        let Some(time) = time else {
+            return Some(midnight);
+        };
+
+        let (clock, offset_secs) = if let Some(clock) = time.strip_suffix(['Z', 'z']) {
+            (clock, 0.0)
+        } else if let Some(i) = time.rfind(['+', '-']) {
+            (&time[..i], parse_utc_offset(&time[i..])?)
+        } else {
+            (time, 0.0)
+        };
+
+        let mut clock_parts = clock.splitn(3, ':');
+        let hour: u32 = clock_parts.next()?.parse().ok()?;
+        let min: u32 = clock_parts.next()?.parse().ok()?;
+        let sec: f64 = match clock_parts.next() {
+            Some(s) => s.parse().ok()?,
+            None => 0.0,
+        };
+        if hour > 23 || min > 59 || !(0.0..61.0).contains(&sec) {
+            return None;
+        }
+
+        Some(midnight + hour as f64 * 3600.0 + min as f64 * 60.0 + sec - offset_secs)
+    }
+
+    /// Parse a `+HH:MM`, `-HHMM` or `+HH` UTC offset into seconds east of UTC
+    fn parse_utc_offset(offset: &str) -> Option<f64> {
+        let (sign, rest) = match offset.split_at_checked(1)? {
+            ("+", rest) => (1.0, rest),
+            ("-", rest) => (-1.0, rest),
+            _ => return None,
+        };
+        let digits: String = rest.chars().filter(|c| *c != ':').collect();
+        if !digits.chars().all(|c| c.is_ascii_digit()) {
+            return None;
+        }
+        let (hours, mins) = match digits.len() {
+            2 => (digits.parse::<u32>().ok()?, 0),
+            4 => (digits[..2].parse::<u32>().ok()?, digits[2..].parse::<u32>().ok()?),
+            _ => return None,
+        };
+        if hours > 23 || mins > 59 {
+            return None;
+        }
+        Some(sign * (hours as f64 * 3600.0 + mins as f64 * 60.0))
+    }
+
+    /// Calculate days since Unix epoch (simple implementation)
+    fn legacy_days_from_civil(year: i32, month: u32, day: u32) -> i64 {
+        let y = if month <= 2 { year - 1 } else { year } as i64;
+        let era = if y >= 0 { y } else { y - 399 } / 400;
+        let yoe = (y - era * 400) as u32;
+        let m = month;
+        let doy = (153 * (if m > 2 { m - 3 } else { m + 9 }) + 2) / 5 + day - 1;
+        let doe = yoe * 365 + yoe / 4 - yoe / 100 + doy;
+        era * 146097 + doe as i64 - 719468
+    }
+
+    /// Convert days since epoch to civil date
+    fn legacy_civil_from_days(days: i64) -> (i32, u32, u32) {
+        let z = days + 719468;
+        let era = if z >= 0 { z } else { z - 146096 } / 146097;
+        let doe = (z - era * 146097) as u32;
+        let yoe = (doe - doe / 1460 + doe / 36524 - doe / 146096) / 365;
+        let y = yoe as i64 + era * 400;
+        let doy = doe - (365 * yoe + yoe / 4 - yoe / 100);
+        let mp = (5 * doy + 2) / 153;
+        let d = doy - (153 * mp + 2) / 5 + 1;
+        let m = if mp < 10 { mp + 3 } else { mp - 9 };
+        let year = if m <= 2 { y + 1 } else { y } as i32;
+        (year, m, d)
+    }
+
+    #[test]
+    fn test_chrono_parser_matches_legacy_parser() {
+        for ts in [
+            "2025-12-31T01:30:07.726213+00:00",
+            "2025-06-15T12:00:00Z",
+            "2025-06-15 12:00:00",
+            "2025-06-15T07:00:00-05:00",
+            "2025-06-16T01:00:00+13:00",
+            "2025-06-15T17:30:00+0530",
+            "2024-02-29T23:59:59.5-08:00",
+            "1999-12-31",
+            "not-a-date",
+            "2025-13-01T00:00:00Z",
+        ] {
+            assert_eq!(parse_iso_timestamp(ts), legacy_parse_iso_timestamp(ts), "{}", ts);
+        }
+        for days in [-1, 0, 11_016, 19_000, 20_254] {
+            let (y, m, d) = legacy_civil_from_days(days);
+            assert_eq!(legacy_days_from_civil(y, m, d), days);
+            let date = chrono::DateTime::from_timestamp(days * 86400, 0).unwrap().date_naive();
+            assert_eq!(date, chrono::NaiveDate::from_ymd_opt(y, m, d).unwrap());
+        }
+    }
+
+    #[test]
+    fn test_parse_iso_timestamp_epoch_values() {
+        for (ts, expected) in [
+            ("2025-12-31T01:30:07.726213+00:00", Some(1_767_144_607.726_213)),
+            ("2024-02-29T23:59:59.5-08:00", Some(1_709_279_999.5)),
+            ("1999-12-31", Some(946_598_400.0)),
+            ("not-a-date", None),
+            ("2025-13-01T00:00:00Z", None),
+        ] {
+            let parsed = parse_iso_timestamp(ts);
+            match (parsed, expected) {
+                (Some(secs), Some(expected)) => assert!((secs - expected).abs() < 1e-6, "{}: {}", ts, secs),
+                _ => assert_eq!(parsed, expected, "{}", ts),
+            }
+        }
+    }
+
+    #[test]
+    fn test_parse_iso_timestamp_offsets() {
+        // 2025-06-15T12:00:00Z
+        let noon_utc = 1_749_988_800.0;
+        assert_eq!(parse_iso_timestamp("2025-06-15T12:00:00Z"), Some(noon_utc));
+        assert_eq!(parse_iso_timestamp("2025-06-15T12:00:00+00:00"), Some(noon_utc));
+        assert_eq!(parse_iso_timestamp("2025-06-15 12:00:00"), Some(noon_utc));
+        assert_eq!(parse_iso_timestamp("2025-06-15T07:00:00-05:00"), Some(noon_utc));
+        assert_eq!(parse_iso_timestamp("2025-06-16T01:00:00+13:00"), Some(noon_utc));
+        assert_eq!(parse_iso_timestamp("2025-06-15T17:30:00+0530"), Some(noon_utc));
+        assert_eq!(parse_iso_timestamp("2025-06-15T12:00Z"), Some(noon_utc));
+        assert_eq!(parse_iso_timestamp("2025-06-15"), Some(noon_utc - 12.0 * 3600.0));
+
+        // Fractional seconds are kept rather than truncated
+        let frac = parse_iso_timestamp("2025-06-15T12:00:00.25-00:00").unwrap();
+        assert!((frac - (noon_utc + 0.25)).abs() < 1e-6);
+
+        assert_eq!(parse_iso_timestamp("2025-06-15T12:00:00+5"), None);
+        assert_eq!(parse_iso_timestamp("2025-13-01T00:00:00Z"), None);
+        assert_eq!(parse_iso_timestamp("2025-06-15T25:00:00Z"), None);
+    }
+
+    #[test]
+    fn test_edge_constructors_set_only_their_flags() {
+        let conv = GraphEdge::conversation("a".into(), "b".into(), "s".into(), Some("t".into()));
+        assert_eq!((conv.session_id.as_str(), conv.timestamp.as_deref()), ("s", Some("t")));
+        assert!(!conv.is_temporal && !conv.is_similarity && !conv.is_bypass && !conv.is_obsidian && !conv.is_topic);
+
+        let temporal = GraphEdge::temporal("a".into(), "b".into(), 0.5);
+        assert!(temporal.is_temporal && !temporal.is_similarity && temporal.similarity == Some(0.5));
+
+        let similar = GraphEdge::similarity("a".into(), "b".into(), 0.8, Some(2));
+        assert!(similar.is_similarity && !similar.is_temporal && similar.query_index == Some(2));
+
+        let bypass = GraphEdge::bypass("a".into(), "b".into(), "s".into());
+        assert!(bypass.is_bypass && bypass.similarity.is_none());
+
+        let gap = GraphEdge::gap("a".into(), "b".into(), "s".into(), 3);
+        assert!(gap.gap == Some(3) && !gap.is_bypass && gap.timestamp.is_none());
+        assert!(conv.gap.is_none());
+
+        let data = GraphData::new(vec![], vec![conv]);
+        assert_eq!(data.edges.len(), 1);
+        assert!(data.beads.is_empty() && data.mail.is_empty());
+    }
+
+    #[test]
+    fn test_hsl_to_rgb_table() {
+        let rgb = |c: egui::Color32| (c.r(), c.g(), c.b());
+        let cases = [
+            (0.0, 1.0, 0.5, (255, 0, 0)),
+            (60.0, 1.0, 0.5, (255, 255, 0)),
+            (120.0, 1.0, 0.5, (0, 255, 0)),
+            (180.0, 1.0, 0.5, (0, 255, 255)),
+            (240.0, 1.0, 0.5, (0, 0, 255)),
+            (300.0, 1.0, 0.5, (255, 0, 255)),
+            (360.0, 1.0, 0.5, (255, 0, 0)),
+            (720.5, 1.0, 0.5, (255, 2, 0)),
+            (-120.0, 1.0, 0.5, (0, 0, 255)),
+            (200.0, 0.0, 0.5, (128, 128, 128)),
+            (200.0, 0.7, 0.0, (0, 0, 0)),
+            (200.0, 0.7, 1.0, (255, 255, 255)),
+            (30.0, 1.5, -0.2, (0, 0, 0)),
+        ];
+        for (h, s, l, expected) in cases {
+            assert_eq!(rgb(hsl_to_rgb(h, s, l)), expected, "hsl({}, {}, {})", h, s, l);
+        }
+    }
+
     #[test]
     fn test_timeline_state_default() {
         let ts = TimelineState::default();
@@ -1638,6 +3090,28 @@ mod tests {
         assert!(mail.timestamp_secs().is_some());
     }
 
+    #[test]
+    fn test_feed_merges_kinds_by_time() {
+        let timeline = TimelineState {
+            sorted_indices: vec![2, 0],
+            timestamps: vec![10.0, 30.0],
+            sorted_bead_indices: vec![1],
+            bead_timestamps: vec![20.0],
+            sorted_mail_indices: vec![0, 1],
+            mail_timestamps: vec![5.0, 30.0],
+            ..Default::default()
+        };
+        let feed: Vec<(FeedKind, usize)> = timeline.feed().map(|e| (e.kind, e.index)).collect();
+        assert_eq!(feed, vec![
+            (FeedKind::Mail, 0),
+            (FeedKind::Message, 2),
+            (FeedKind::Bead, 1),
+            (FeedKind::Message, 0),
+            (FeedKind::Mail, 1),
+        ]);
+        assert_eq!(TimelineState::default().feed().count(), 0);
+    }
+
     /// Helper: create a GraphNode with a given id and timestamp
     fn make_node(id: &str, timestamp: &str) -> GraphNode {
         GraphNode {
@@ -1662,12 +3136,7 @@ mod tests {
     /// Helper: create a GraphState loaded with nodes and timeline built
     fn make_graph_with_nodes(nodes: Vec<GraphNode>) -> GraphState {
         let mut graph = GraphState::new();
-        let data = GraphData {
-            nodes,
-            edges: vec![],
-            beads: vec![],
-            mail: vec![],
-        };
+        let data = GraphData::new(nodes, vec![]);
         let bounds = egui::Rect::from_center_size(
             egui::Pos2::new(400.0, 300.0),
             egui::Vec2::new(600.0, 400.0),
@@ -1678,6 +3147,95 @@ mod tests {
         graph
     }
 
+    fn hues_for(nodes: Vec<GraphNode>, mode: HueAssignment) -> (HashMap<String, f32>, HashMap<String, f32>) {
+        let mut graph = GraphState::new();
+        graph.hue_assignment = mode;
+        graph.temporal_attraction_enabled = false;
+        let bounds = egui::Rect::from_center_size(egui::Pos2::new(400.0, 300.0), egui::Vec2::new(600.0, 400.0));
+        graph.load(GraphData::new(nodes, vec![]), bounds);
+        (graph.session_colors, graph.project_colors)
+    }
+
+    fn session_node(id: &str, session: &str, project: &str) -> GraphNode {
+        GraphNode {
+            session_id: session.to_string(),
+            project: project.to_string(),
+            ..make_node(id, "2025-06-15T12:00:00+00:00")
+        }
+    }
+
+    #[test]
+    fn test_stable_hues_ignore_load_order() {
+        let nodes = vec![
+            session_node("1", "a1f0", "~/code/zeta"),
+            session_node("2", "77c2", "~/code/alpha"),
+            session_node("3", "e913", "~/work/api"),
+            session_node("4", "0b5d", "~/code/alpha"),
+            session_node("5", "c4aa", "~/code/mid"),
+        ];
+        let mut reversed = nodes.clone();
+        reversed.reverse();
+        let mut rotated = nodes.clone();
+        rotated.rotate_left(2);
+
+        let expected = hues_for(nodes.clone(), HueAssignment::Stable);
+        assert_eq!(hues_for(reversed.clone(), HueAssignment::Stable), expected);
+        assert_eq!(hues_for(rotated, HueAssignment::Stable), expected);
+        // Siblings are indexed by name: alpha < mid < zeta under ~/code
+        let (_, projects) = &expected;
+        assert_eq!(projects["~/code/alpha"], hues_for(vec![session_node("9", "x", "~/code/alpha"), session_node("8", "y", "~/code/zeta")], HueAssignment::Stable).1["~/code/alpha"]);
+
+        // Sequential follows encounter order instead
+        assert_ne!(
+            hues_for(nodes, HueAssignment::Sequential).0,
+            hues_for(reversed, HueAssignment::Sequential).0
+        );
+    }
+
+    #[test]
+    fn test_stable_session_hue_nudges_away_from_taken() {
+        let alone = stable_session_hue("a1f0", &[]);
+        assert_eq!(alone, hashed_hue("a1f0", 0));
+        assert_eq!(stable_session_hue("a1f0", &[alone + 180.0]), alone);
+        let nudged = stable_session_hue("a1f0", &[alone]);
+        assert!(hue_distance(nudged, alone) >= SESSION_HUE_MIN_GAP);
+        assert_eq!(hue_distance(350.0, 4.0), 14.0);
+    }
+
+    #[test]
+    fn test_idle_gaps_annotated_at_load() {
+        let nodes = vec![
+            make_node("A", "2025-06-15T12:00:00+00:00"),
+            make_node("B", "2025-06-15T12:23:00+00:00"),
+            make_node("C", "2025-06-15T12:24:00+00:00"),
+        ];
+        let mut graph = GraphState::new();
+        let edges = vec![
+            GraphEdge::conversation("A".into(), "B".into(), "s1".into(), None),
+            GraphEdge::conversation("B".into(), "C".into(), "s1".into(), None),
+            GraphEdge::topic("A".into(), "C".into(), "s1".into()),
+        ];
+        let bounds = egui::Rect::from_center_size(egui::Pos2::new(400.0, 300.0), egui::Vec2::new(600.0, 400.0));
+        graph.temporal_attraction_enabled = false;
+        graph.load(GraphData::new(nodes, edges), bounds);
+
+        let idle: Vec<Option<f64>> = graph.data.edges.iter().map(|e| e.idle_secs).collect();
+        assert_eq!(idle, vec![Some(1380.0), Some(60.0), None]);
+        assert_eq!(format_idle_gap(1380.0), "+23m");
+    }
+
+    #[test]
+    fn test_format_idle_gap_and_cooling() {
+        assert_eq!(format_idle_gap(29.0), "+0m");
+        assert_eq!(format_idle_gap(7500.0), "+2h 05m");
+        assert_eq!(format_idle_gap(3.0 * 86400.0), "+3d");
+
+        assert_eq!(idle_gap_cooling(200.0, 300.0), 0.0);
+        assert_eq!(idle_gap_cooling(300.0, 300.0), 0.25);
+        assert!(idle_gap_cooling(1200.0, 300.0) > idle_gap_cooling(600.0, 300.0));
+        assert_eq!(idle_gap_cooling(300.0 * 64.0, 300.0), 0.6);
+    }
+
     #[test]
     fn test_build_temporal_edges_unfiltered() {
         let nodes = vec![
@@ -1698,6 +3256,36 @@ mod tests {
         assert_eq!(temporal[0].target, "B");
     }
 
+    #[test]
+    fn test_mixed_utc_offsets_order_and_link_correctly() {
+        // Same instants as 12:00Z, 12:01Z and 12:10Z written in three zones
+        let nodes = vec![
+            make_node("C", "2025-06-15T12:10:00+00:00"),
+            make_node("A", "2025-06-16T01:00:00+13:00"),
+            make_node("B", "2025-06-15T04:01:00-08:00"),
+        ];
+        let a = nodes[1].timestamp_secs().unwrap();
+        let b = nodes[2].timestamp_secs().unwrap();
+        let c = nodes[0].timestamp_secs().unwrap();
+        assert_eq!(b - a, 60.0);
+        assert_eq!(c - a, 600.0);
+
+        let mut graph = make_graph_with_nodes(nodes);
+        let order: Vec<&str> = graph.timeline.sorted_indices.iter()
+            .map(|&i| graph.data.nodes[i].id.as_str())
+            .collect();
+        assert_eq!(order, vec!["A", "B", "C"]);
+
+        graph.temporal_window_secs = 120.0;
+        graph.max_temporal_edges = 1000;
+        graph.build_temporal_edges_filtered(None);
+        let temporal: Vec<(&str, &str)> = graph.data.edges.iter()
+            .filter(|e| e.is_temporal)
+            .map(|e| (e.source.as_str(), e.target.as_str()))
+            .collect();
+        assert_eq!(temporal, vec![("A", "B")]);
+    }
+
     #[test]
     fn test_build_temporal_edges_filtered_excludes_invisible() {
         let nodes = vec![
@@ -1720,6 +3308,37 @@ mod tests {
         assert_eq!(temporal[0].target, "C");
     }
 
+    #[test]
+    fn test_temporal_rebuild_skipped_for_same_visible_set() {
+        let nodes = vec![
+            make_node("A", "2025-06-15T12:00:00+00:00"),
+            make_node("B", "2025-06-15T12:01:00+00:00"),
+            make_node("C", "2025-06-15T12:01:30+00:00"),
+        ];
+        let mut graph = make_graph_with_nodes(nodes);
+        graph.temporal_window_secs = 120.0;
+        let visible: HashSet<String> = ["A", "B"].iter().map(|s| s.to_string()).collect();
+
+        assert!(graph.rebuild_temporal_edges_if_changed(Some(&visible), true));
+        assert_eq!(graph.temporal_build.map(|b| b.edges), Some(1));
+        // Same set built in a different order: nothing to do once settled
+        let reordered: HashSet<String> = ["B", "A"].iter().map(|s| s.to_string()).collect();
+        assert!(!graph.rebuild_temporal_edges_if_changed(Some(&reordered), true));
+        // Still running, so it rebuilds
+        assert!(graph.rebuild_temporal_edges_if_changed(Some(&visible), false));
+
+        let wider: HashSet<String> = ["A", "B", "C"].iter().map(|s| s.to_string()).collect();
+        assert!(graph.rebuild_temporal_edges_if_changed(Some(&wider), true));
+        assert_eq!(graph.temporal_build.map(|b| b.edges), Some(3));
+        assert!(graph.rebuild_temporal_edges_if_changed(None, true));
+        assert!(!graph.rebuild_temporal_edges_if_changed(None, true));
+
+        // A settings change alters the scope
+        graph.temporal_window_secs = 30.0;
+        assert!(graph.rebuild_temporal_edges_if_changed(None, true));
+        assert_eq!(graph.temporal_build.map(|b| b.edges), Some(1));
+    }
+
     #[test]
     fn test_build_temporal_edges_filtered_empty_set() {
         let nodes = vec![
@@ -1757,6 +3376,82 @@ mod tests {
         assert_eq!(temporal_count, 5);
     }
 
+    #[test]
+    fn test_per_node_cap_spreads_temporal_budget() {
+        // A burst of 100 messages within 100 seconds, then 20 sparse ones 200s apart
+        let burst = (0..100).map(|i| make_node(&format!("B{}", i), &format!("2025-06-15T12:{:02}:{:02}+00:00", i / 60, i % 60)));
+        let sparse = (0..20).map(|i| {
+            let t = chrono::DateTime::parse_from_rfc3339("2025-06-15T13:00:00+00:00").unwrap() + chrono::Duration::seconds(i * 200);
+            make_node(&format!("S{}", i), &t.to_rfc3339())
+        });
+        let mut graph = make_graph_with_nodes(burst.chain(sparse).collect());
+        graph.temporal_window_secs = 300.0;
+        graph.max_temporal_edges = 1000;
+        // The helper loads with temporal edges off; the rebuilds below need them on
+        graph.temporal_attraction_enabled = true;
+        let sparse_edges = |graph: &GraphState| graph.data.edges.iter()
+            .filter(|e| e.is_temporal && e.source.starts_with('S'))
+            .count();
+        let max_degree = |graph: &GraphState| {
+            let mut degree: HashMap<&str, usize> = HashMap::new();
+            for e in graph.data.edges.iter().filter(|e| e.is_temporal) {
+                *degree.entry(&e.source).or_default() += 1;
+                *degree.entry(&e.target).or_default() += 1;
+            }
+            degree.into_values().max().unwrap_or(0)
+        };
+
+        // Uncapped: the burst alone has 4950 pairs, so the sparse tail gets nothing
+        graph.max_temporal_neighbors = 0;
+        graph.build_temporal_edges_filtered(None);
+        assert_eq!(graph.data.edges.iter().filter(|e| e.is_temporal).count(), 1000);
+        assert_eq!(sparse_edges(&graph), 0);
+        assert!(max_degree(&graph) > 50);
+
+        // Capped: the burst stops early and every sparse neighbour pair is linked
+        graph.max_temporal_neighbors = 4;
+        graph.build_temporal_edges_filtered(None);
+        assert!(graph.data.edges.iter().filter(|e| e.is_temporal).count() < 1000);
+        assert_eq!(sparse_edges(&graph), 19);
+        assert_eq!(max_degree(&graph), 4);
+    }
+
+    #[test]
+    fn test_load_drops_excluded_projects() {
+        let mut secret = make_node("B", "2025-06-15T12:01:00+00:00");
+        secret.project = "~/clients/acme".to_string();
+        let nodes = vec![make_node("A", "2025-06-15T12:00:00+00:00"), secret, make_node("C", "2025-06-15T12:02:00+00:00")];
+        let edges = vec![
+            GraphEdge::conversation("A".to_string(), "B".to_string(), "s1".to_string(), None),
+            GraphEdge::conversation("A".to_string(), "C".to_string(), "s1".to_string(), None),
+        ];
+        let mut graph = GraphState::new();
+        graph.excluded_projects = vec!["~/clients/".to_string()];
+        graph.load(GraphData::new(nodes, edges), egui::Rect::from_min_size(Pos2::ZERO, egui::Vec2::splat(100.0)));
+
+        assert_eq!(graph.data.nodes.iter().map(|n| n.id.as_str()).collect::<Vec<_>>(), vec!["A", "C"]);
+        assert!(graph.data.edges.iter().filter(|e| !e.is_temporal).all(|e| e.target == "C"));
+        assert!(!graph.positions.contains_key("B"));
+    }
+
+    #[test]
+    fn test_load_keeps_selection_of_surviving_nodes() {
+        let mut graph = make_graph_with_nodes(vec![
+            make_node("A", "2025-06-15T12:00:00+00:00"),
+            make_node("B", "2025-06-15T12:01:00+00:00"),
+        ]);
+        graph.selected_node = Some("A".to_string());
+        graph.hovered_node = Some("B".to_string());
+        let bounds = egui::Rect::from_min_size(Pos2::ZERO, egui::Vec2::splat(100.0));
+
+        graph.load(GraphData::new(vec![make_node("A", "2025-06-15T12:00:00+00:00")], vec![]), bounds);
+        assert_eq!(graph.selected_node.as_deref(), Some("A"));
+        assert_eq!(graph.hovered_node, None);
+
+        graph.load(GraphData::new(vec![make_node("C", "2025-06-15T12:02:00+00:00")], vec![]), bounds);
+        assert_eq!(graph.selected_node, None);
+    }
+
     #[test]
     fn test_build_temporal_edges_filtered_cleans_old_edges() {
         let nodes = vec![
@@ -1801,4 +3496,511 @@ mod tests {
         // A-C is 5 min apart, within 10 min window → 1 edge
         assert_eq!(temporal.len(), 1);
     }
+
+    #[test]
+    fn test_degenerate_timelines() {
+        // Zero nodes: conversions stay total and nothing is visible
+        let mut empty = make_graph_with_nodes(vec![]);
+        assert!(empty.timeline.is_degenerate());
+        assert!(empty.timeline.time_at_position(0.5).is_finite());
+        empty.update_visible_items();
+        assert!(empty.timeline.visible_nodes.is_empty());
+
+        // One node: visible wherever the handles sit
+        let mut single = make_graph_with_nodes(vec![make_node("A", "2025-06-15T12:00:00Z")]);
+        assert!(single.timeline.is_degenerate());
+        let t = single.timeline.min_time;
+        for (start, end) in [(0.0, 1.0), (0.2, 0.21), (0.99, 1.0), (0.0, 0.01)] {
+            single.timeline.start_position = start;
+            single.timeline.position = end;
+            assert_eq!(single.timeline.time_at_position(end), t);
+            single.update_visible_items();
+            assert!(single.is_node_visible("A"), "window {}..{}", start, end);
+        }
+
+        // Two nodes one second apart: a normal, non-degenerate range
+        let mut pair = make_graph_with_nodes(vec![
+            make_node("A", "2025-06-15T12:00:00Z"),
+            make_node("B", "2025-06-15T12:00:01Z"),
+        ]);
+        assert!(!pair.timeline.is_degenerate());
+        assert_eq!(pair.timeline.position_at_time(pair.timeline.max_time), 1.0);
+        pair.timeline.start_position = 0.0;
+        pair.timeline.position = 0.5;
+        pair.update_visible_items();
+        assert!(pair.is_node_visible("A"));
+        assert!(!pair.is_node_visible("B"));
+    }
+
+    #[test]
+    fn test_bead_markers_are_namespaced() {
+        let mut graph = make_graph_with_nodes(vec![make_node("12", "2025-06-15T12:00:00Z")]);
+        graph.data.beads.push(BeadItem {
+            id: "12".to_string(),
+            title: "Same id as a message".to_string(),
+            status: IssueStatus::Open,
+            labels: vec![],
+            priority: 2,
+            created_at: Some("2025-06-15T12:00:00+00:00".to_string()),
+            updated_at: None,
+            issue_type: None,
+            description: None,
+            assignee: None,
+        });
+        graph.build_timeline();
+        graph.update_visible_items();
+        assert!(graph.is_bead_visible("12"));
+        assert!(graph.timeline.visible_beads.contains("bead:12"));
+        assert!(!graph.timeline.visible_beads.contains("12"));
+    }
+
+    #[test]
+    fn test_visible_edges_match_predicate() {
+        use rand::rngs::StdRng;
+        use rand::{Rng, SeedableRng};
+
+        let mut rng = StdRng::seed_from_u64(7);
+        let nodes: Vec<GraphNode> = (0..60)
+            .map(|i| make_node(&format!("n{}", i), &format!("2025-06-15T{:02}:{:02}:00Z", i / 6, (i % 6) * 10)))
+            .collect();
+        let mut graph = make_graph_with_nodes(nodes);
+        let edges: Vec<GraphEdge> = (0..200)
+            .map(|_| {
+                let (a, b) = (rng.gen_range(0..60), rng.gen_range(0..60));
+                GraphEdge::similarity(format!("n{}", a), format!("n{}", b), 0.5, None)
+            })
+            .collect();
+        graph.set_proximity_edges(edges);
+
+        for _ in 0..30 {
+            let start: f32 = rng.gen_range(0.0..1.0);
+            graph.timeline.start_position = start;
+            graph.timeline.position = rng.gen_range(start..=1.0);
+            graph.update_visible_items();
+
+            assert_eq!(graph.timeline.visible_edges.len(), graph.data.edges.len());
+            for (i, edge) in graph.data.edges.iter().enumerate() {
+                assert_eq!(graph.is_edge_index_visible(i), graph.is_edge_visible(edge));
+            }
+            let expected = graph.data.edges.iter().filter(|e| graph.is_edge_visible(e)).count();
+            assert_eq!(graph.visible_edges().count(), expected);
+        }
+    }
+
+    #[test]
+    fn test_degree_by_kind_follows_edge_changes() {
+        let nodes = vec![
+            make_node("a", "2025-06-15T12:00:00Z"),
+            make_node("b", "2025-06-15T12:01:00Z"),
+            make_node("c", "2025-06-15T12:02:00Z"),
+        ];
+        let mut graph = GraphState::new();
+        graph.temporal_attraction_enabled = false;
+        let conversation = GraphEdge::conversation("a".into(), "b".into(), "s".into(), None);
+        graph.load(GraphData::new(nodes, vec![conversation]), egui::Rect::from_min_size(Pos2::ZERO, egui::Vec2::splat(100.0)));
+        assert_eq!(graph.degree_by_kind(0), [1, 0, 0, 0, 0]);
+        assert_eq!(graph.degree_by_kind(2), [0; 5]);
+
+        graph.set_proximity_edges(vec![
+            GraphEdge::similarity("a".into(), "b".into(), 0.4, None),
+            GraphEdge::similarity("c".into(), "a".into(), 0.9, None),
+        ]);
+        assert_eq!(graph.degree_by_kind(0), [1, 0, 2, 0, 0]);
+        assert_eq!(graph.strongest_links(0, 5), vec![(2, EdgeKind::Similarity, 0.9), (1, EdgeKind::Similarity, 0.4)]);
+        assert_eq!(graph.strongest_links(0, 1).len(), 1);
+
+        graph.set_temporal_window(600.0, None);
+        graph.set_temporal_attraction_enabled(true, None);
+        assert_eq!(graph.degree_by_kind(0)[EdgeKind::Temporal as usize], 2);
+        graph.set_temporal_attraction_enabled(false, None);
+        assert_eq!(graph.degree_by_kind(0), [1, 0, 2, 0, 0]);
+        graph.set_proximity_edges(Vec::new());
+        assert_eq!(graph.degree_by_kind(0), [1, 0, 0, 0, 0]);
+    }
+
+    #[test]
+    fn test_timeline_preview_leaves_committed_position() {
+        let nodes = vec![
+            make_node("A", "2025-06-15T12:00:00+00:00"),
+            make_node("B", "2025-06-15T12:10:00+00:00"),
+        ];
+        let mut graph = make_graph_with_nodes(nodes);
+        assert_eq!(graph.timeline.visible_nodes.len(), 2);
+
+        assert!(graph.set_timeline_preview(Some(0.5)));
+        assert!(!graph.set_timeline_preview(Some(0.5)));
+        assert_eq!(graph.timeline.position, 1.0);
+        assert!(graph.is_node_visible("A"));
+        assert!(!graph.is_node_visible("B"));
+
+        assert!(graph.set_timeline_preview(None));
+        assert!(graph.is_node_visible("B"));
+    }
+
+    /// Helper: a node with token counts for histogram tests
+    fn token_node(id: &str, secs: i64, role: Role, session: &str, project: &str, input: i32, output: i32) -> GraphNode {
+        let timestamp = chrono::DateTime::from_timestamp(secs, 0).unwrap().to_rfc3339();
+        GraphNode {
+            role,
+            session_id: session.to_string(),
+            project: project.to_string(),
+            input_tokens: Some(input),
+            output_tokens: Some(output),
+            ..make_node(id, &timestamp)
+        }
+    }
+
+    fn histogram_data(nodes: Vec<GraphNode>) -> GraphData {
+        GraphData::new(nodes, vec![])
+    }
+
+    #[test]
+    fn test_histogram_bins_are_half_open() {
+        let base = 1_750_000_000;
+        let data = histogram_data(vec![
+            token_node("a", base, Role::User, "s1", "p", 1, 0),       // first bin start
+            token_node("b", base + 9, Role::User, "s1", "p", 2, 0),   // still first bin
+            token_node("c", base + 10, Role::User, "s1", "p", 4, 0),  // boundary -> second bin
+            token_node("d", base + 40, Role::User, "s1", "p", 8, 0),  // max_time -> last bin
+            token_node("e", base + 41, Role::User, "s1", "p", 16, 0), // out of range
+        ]);
+        let mut hist = HistogramState { bin_count: 4, ..Default::default() };
+        let graph = GraphState::new();
+        hist.rebuild(&data, base as f64, (base + 40) as f64, &graph, &crate::theme::DARK);
+
+        let totals: Vec<i64> = hist.bins.iter().map(|b| b.total).collect();
+        assert_eq!(totals, vec![3, 4, 0, 8]);
+        assert_eq!(hist.total_tokens, 15);
+        assert_eq!(hist.max_value, 8);
+        assert_eq!(hist.bins[1].start_time, (base + 10) as f64);
+        assert!(!hist.is_dirty());
+    }
+
+    #[test]
+    fn test_histogram_stack_orders() {
+        let base = 1_750_000_000;
+        let mut untimed = token_node("x", base, Role::User, "s1", "alpha", 100, 100);
+        untimed.timestamp = None;
+        let data = histogram_data(vec![
+            token_node("a", base, Role::User, "s1", "alpha", 10, 0),
+            token_node("b", base + 1, Role::Assistant, "s1", "alpha", 5, 20),
+            token_node("c", base + 2, Role::Assistant, "s2", "beta", 0, 7),
+            untimed,
+        ]);
+        let graph = GraphState::new();
+        let segments = |order: StackOrder| {
+            let mut hist = HistogramState { bin_count: 1, stack_order: order, ..Default::default() };
+            hist.rebuild(&data, base as f64, (base + 10) as f64, &graph, &crate::theme::DARK);
+            hist.bins[0].segments.iter().map(|s| (s.label.clone(), s.count)).collect::<Vec<_>>()
+        };
+
+        assert_eq!(segments(StackOrder::ByTokenType), vec![("input".to_string(), 15), ("output".to_string(), 27)]);
+        assert_eq!(segments(StackOrder::ByRole), vec![("You".to_string(), 10), ("Claude".to_string(), 32)]);
+        assert_eq!(segments(StackOrder::ByProject), vec![("alpha".to_string(), 35), ("beta".to_string(), 7)]);
+        assert_eq!(segments(StackOrder::BySession), vec![("s1".to_string(), 35), ("s2".to_string(), 7)]);
+
+        // Filters: drop output tokens and restrict to one session
+        let mut hist = HistogramState { bin_count: 1, ..Default::default() };
+        hist.filter.include_output = false;
+        hist.filter.sessions = vec!["s1".to_string()];
+        hist.rebuild(&data, base as f64, (base + 10) as f64, &graph, &crate::theme::DARK);
+        assert_eq!(hist.total_tokens, 15);
+    }
+
+    #[test]
+    fn test_histogram_brush_snaps_to_bin_edges() {
+        let mut hist = HistogramState { bin_count: 4, ..Default::default() };
+        let data = histogram_data(vec![token_node("a", 1_000_000_000, Role::User, "s1", "p", 1, 0)]);
+        hist.rebuild(&data, 0.0, 40.0, &GraphState::new(), &crate::theme::DARK);
+
+        assert_eq!(hist.brush_range(12.0, 27.0), Some((10.0, 30.0)));
+        // Dragging right-to-left gives the same window
+        assert_eq!(hist.brush_range(27.0, 12.0), Some((10.0, 30.0)));
+        // A bin edge belongs to the later bin; out-of-range drags clamp
+        assert_eq!(hist.brush_range(10.0, 10.0), Some((10.0, 20.0)));
+        assert_eq!(hist.brush_range(-5.0, 99.0), Some((0.0, 40.0)));
+        assert_eq!(HistogramState::default().brush_range(0.0, 1.0), None);
+    }
+
+    #[test]
+    fn test_histogram_segment_filters_toggle() {
+        let mut filter = HistogramFilter::all();
+        assert!(filter.toggle_segment(StackOrder::ByProject, NO_PROJECT_LABEL));
+        assert!(filter.toggle_segment(StackOrder::ByRole, Role::Assistant.label()));
+        assert!(!filter.toggle_segment(StackOrder::ByRole, "Nobody"));
+        assert_eq!(filter.projects, vec![String::new()]);
+        assert_eq!(filter.roles, vec![Role::Assistant]);
+
+        // First token-type click narrows to it; un-clicking the last one restores all
+        filter.toggle_segment(StackOrder::ByTokenType, "output");
+        assert_eq!(filter.single_token_type(), Some("output"));
+        assert_eq!(
+            filter.active_segments(),
+            vec![
+                (StackOrder::ByProject, NO_PROJECT_LABEL.to_string()),
+                (StackOrder::ByRole, "Claude".to_string()),
+                (StackOrder::ByTokenType, "output".to_string()),
+            ]
+        );
+        filter.toggle_segment(StackOrder::ByTokenType, "output");
+        assert!(filter.include_input && filter.include_output && filter.include_cache_read && filter.include_cache_creation);
+
+        filter.toggle_segment(StackOrder::ByProject, NO_PROJECT_LABEL);
+        filter.toggle_segment(StackOrder::ByRole, "Claude");
+        assert!(filter.active_segments().is_empty());
+    }
+
+    #[test]
+    fn test_histogram_csv_export() {
+        let mut hist = HistogramState {
+            bin_count: 2,
+            stack_order: StackOrder::ByProject,
+            ..Default::default()
+        };
+        let data = histogram_data(vec![token_node("a", 5, Role::User, "s1", "acme, inc", 1, 0)]);
+        hist.rebuild(&data, 0.0, 20.0, &GraphState::new(), &crate::theme::DARK);
+
+        let csv = hist.to_csv();
+        let lines: Vec<&str> = csv.lines().collect();
+        assert_eq!(lines[0], "bin_start,bin_end,segment_label,token_count,displayed_value");
+        assert_eq!(lines[1], "1970-01-01T00:00:00Z,1970-01-01T00:00:10Z,\"acme, inc\",1,1.00");
+        assert_eq!(lines[2], "1970-01-01T00:00:00Z,1970-01-01T00:00:10Z,total,1,1.00");
+        // Empty bins still get an explicit zero total
+        assert_eq!(lines[3], "1970-01-01T00:00:10Z,1970-01-01T00:00:20Z,total,0,0.00");
+        assert_eq!(lines.len(), 4);
+    }
+
+    #[test]
+    fn test_histogram_log_scale() {
+        let hist = HistogramState { max_value: 2_000_000, ..Default::default() };
+        assert_eq!(hist.bar_fraction(2_000_000, true), 1.0);
+        assert_eq!(hist.bar_fraction(0, true), 0.0);
+        // A 1k bin is invisible linearly but about half height on a log axis
+        assert!(hist.bar_fraction(1_000, false) < 0.001);
+        assert!((hist.bar_fraction(1_000, true) - 0.476).abs() < 0.01);
+        assert_eq!(hist.log_ticks(), vec![1_000, 10_000, 100_000, 1_000_000]);
+    }
+
+    #[test]
+    fn test_semantic_filter_mode_cycle() {
+        let mut mode = SemanticFilterMode::Off;
+        let mut seen = Vec::new();
+        for _ in 0..5 {
+            mode = mode.next();
+            seen.push(mode);
+            assert_eq!(mode.prev().next(), mode);
+        }
+        assert_eq!(
+            seen,
+            vec![
+                SemanticFilterMode::Include,
+                SemanticFilterMode::IncludePlus1,
+                SemanticFilterMode::IncludePlus2,
+                SemanticFilterMode::Exclude,
+                SemanticFilterMode::Off,
+            ]
+        );
+    }
+
+    #[test]
+    fn test_similarity_edges_colored_by_query() {
+        let graph = GraphState::new();
+        let edge = |qi| GraphEdge::similarity("a".into(), "b".into(), 0.8, qi);
+        assert_eq!(graph.edge_color(&edge(None)), egui::Color32::from_rgb(6, 182, 212));
+        assert_ne!(graph.edge_color(&edge(Some(0))), graph.edge_color(&edge(Some(1))));
+        assert_eq!(graph.edge_color(&edge(Some(2))), crate::theme::QUERY_COLORS[2]);
+    }
+
+    #[test]
+    fn test_session_spans_track_last_node() {
+        let mut late = make_node("late", "2025-06-15T12:30:00+00:00");
+        late.role = Role::Assistant;
+        late.output_tokens = Some(500);
+        late.cache_read_tokens = Some(2000);
+        let mut topic = make_node("topic:x", "2025-06-15T13:00:00+00:00");
+        topic.role = Role::Topic;
+        let mut other = make_node("other", "2025-06-15T09:00:00+00:00");
+        other.session_id = "s2".to_string();
+        other.project = String::new();
+        let graph = make_graph_with_nodes(vec![
+            make_node("first", "2025-06-15T12:00:00+00:00"),
+            late,
+            topic,
+            make_node("mid", "2025-06-15T12:10:00+00:00"),
+            other,
+        ]);
+        let span = &graph.session_spans["s1"];
+        assert_eq!(span.messages, 3);
+        assert_eq!(span.last_node, "late");
+        assert_eq!(span.duration_secs(), 1800.0);
+        assert_eq!(span.tokens, 2500);
+        assert_eq!(span.first_prompt.as_deref(), Some("first"));
+        // Sessions without a project still get a span
+        assert_eq!(graph.session_spans["s2"].last_node, "other");
+        assert!(!graph.project_sessions.values().flatten().any(|(id, _)| id == "s2"));
+    }
+
+    #[test]
+    fn test_session_neighbors_in_time_order() {
+        let mut other = make_node("other", "2025-06-15T12:05:00+00:00");
+        other.session_id = "s2".to_string();
+        let mut topic = make_node("topic:x", "2025-06-15T12:06:00+00:00");
+        topic.role = Role::Topic;
+        let graph = make_graph_with_nodes(vec![
+            make_node("c", "2025-06-15T12:20:00+00:00"),
+            make_node("a", "2025-06-15T12:00:00+00:00"),
+            other,
+            topic,
+            make_node("d", "2025-06-15T12:30:00+00:00"),
+            make_node("b", "2025-06-15T12:10:00+00:00"),
+            make_node("untimed", ""),
+        ]);
+        let ids = |idx: &[usize]| idx.iter().map(|&i| graph.data.nodes[i].id.as_str()).collect::<Vec<_>>();
+        let at = |id: &str| graph.node_index[id];
+
+        let (before, after) = graph.session_neighbors(at("b"), 2, 2);
+        assert_eq!((ids(before), ids(after)), (vec!["a"], vec!["c", "d"]));
+        let (before, after) = graph.session_neighbors(at("d"), 2, 2);
+        assert_eq!((ids(before), ids(after)), (vec!["b", "c"], vec!["untimed"]));
+        // Other sessions and topics have no neighbours
+        assert_eq!(graph.session_neighbors(at("other"), 2, 2), (&[][..], &[][..]));
+        assert_eq!(graph.session_neighbors(at("topic:x"), 2, 2), (&[][..], &[][..]));
+    }
+
+    #[test]
+    fn test_session_index_orders_out_of_order_rows() {
+        let mut other = make_node("other", "2025-06-15T12:05:00+00:00");
+        other.session_id = "s2".to_string();
+        let graph = make_graph_with_nodes(vec![
+            make_node("late", "2025-06-15T12:30:00+00:00"),
+            make_node("untimed1", ""),
+            make_node("early", "2025-06-15T12:00:00+00:00"),
+            other,
+            make_node("untimed2", ""),
+            make_node("mid", "2025-06-15T12:10:00+00:00"),
+        ]);
+        let ids: Vec<&str> = graph.session_nodes("s1").iter().map(|&i| graph.data.nodes[i].id.as_str()).collect();
+        assert_eq!(ids, vec!["early", "mid", "late", "untimed1", "untimed2"]);
+        assert_eq!(graph.session_nodes("s2").len(), 1);
+        assert!(graph.session_nodes("missing").is_empty());
+
+        assert_eq!(graph.next_in_session("early").map(|n| n.id.as_str()), Some("mid"));
+        assert_eq!(graph.next_in_session("late").map(|n| n.id.as_str()), Some("untimed1"));
+        assert_eq!(graph.prev_in_session("untimed2").map(|n| n.id.as_str()), Some("untimed1"));
+        assert!(graph.prev_in_session("early").is_none());
+        assert!(graph.next_in_session("untimed2").is_none());
+        assert!(graph.next_in_session("other").is_none());
+        assert!(graph.next_in_session("nope").is_none());
+    }
+
+    fn tool_result(id: &str, timestamp: &str, tokens: i32) -> GraphNode {
+        let mut node = make_node(id, timestamp);
+        node.role = Role::Assistant;
+        node.content_preview = "[Used tools: Bash]".to_string();
+        node.output_tokens = Some(tokens);
+        node
+    }
+
+    #[test]
+    fn test_tool_runs_fold_at_session_start_and_end() {
+        let mut graph = make_graph_with_nodes(vec![
+            tool_result("t1", "2025-06-15T12:00:00+00:00", 10),
+            tool_result("t2", "2025-06-15T12:01:00+00:00", 20),
+            make_node("prompt", "2025-06-15T12:02:00+00:00"),
+            tool_result("lone", "2025-06-15T12:03:00+00:00", 5),
+            make_node("reply", "2025-06-15T12:04:00+00:00"),
+            tool_result("t5", "2025-06-15T12:07:00+00:00", 1),
+            tool_result("t3", "2025-06-15T12:05:00+00:00", 1),
+            tool_result("t4", "2025-06-15T12:06:00+00:00", 1),
+        ]);
+        assert!(graph.tool_runs.is_empty());
+
+        graph.set_collapse_tool_results(true);
+        let index = graph.node_index.clone();
+        let at = |id: &str| index[id];
+        // Run at session start
+        assert_eq!(graph.tool_runs.run_len(at("t1")), 2);
+        assert!(graph.tool_runs.is_folded("t2"));
+        assert_eq!(graph.display_tokens(&graph.data.nodes[at("t1")]), 30);
+        // Run at session end, folded in time order rather than input order
+        assert_eq!(graph.tool_runs.run_len(at("t3")), 3);
+        assert!(graph.tool_runs.is_folded("t4") && graph.tool_runs.is_folded("t5"));
+        // A single tool result stays as it is
+        assert_eq!(graph.tool_runs.run_len(at("lone")), 1);
+        assert!(!graph.tool_runs.is_folded("lone") && !graph.tool_runs.is_folded("prompt"));
+        assert_eq!(graph.max_tokens, 30);
+
+        // The data is untouched and turning it off restores everything
+        assert_eq!(graph.data.nodes.len(), 8);
+        graph.set_collapse_tool_results(false);
+        assert!(graph.tool_runs.is_empty());
+        assert_eq!(graph.display_tokens(&graph.data.nodes[at("t1")]), 10);
+        assert_eq!(graph.max_tokens, 20);
+    }
+
+    #[test]
+    fn test_live_sessions_within_window() {
+        let mut quiet = make_node("quiet", "2025-06-15T11:00:00+00:00");
+        quiet.session_id = "s2".to_string();
+        let mut recent = make_node("recent", "2025-06-15T12:08:00+00:00");
+        recent.session_id = "s3".to_string();
+        let graph = make_graph_with_nodes(vec![
+            make_node("a", "2025-06-15T12:00:00+00:00"),
+            make_node("b", "2025-06-15T12:06:00+00:00"),
+            quiet,
+            recent,
+        ]);
+        let now = parse_iso_timestamp("2025-06-15T12:10:00+00:00").unwrap();
+        assert_eq!(graph.live_sessions(now, 300.0), vec!["s3", "s1"]);
+        assert_eq!(graph.live_sessions(now, 60.0), Vec::<&str>::new());
+    }
+
+    #[test]
+    fn test_session_index_all_untimed_keeps_input_order() {
+        let graph = make_graph_with_nodes(vec![
+            make_node("x", ""),
+            make_node("y", ""),
+            make_node("z", ""),
+        ]);
+        let ids: Vec<&str> = graph.session_nodes("s1").iter().map(|&i| graph.data.nodes[i].id.as_str()).collect();
+        assert_eq!(ids, vec!["x", "y", "z"]);
+        assert_eq!(graph.prev_in_session("z").map(|n| n.id.as_str()), Some("y"));
+    }
+
+    #[test]
+    fn test_hue_overrides_win_and_survive_reload() {
+        let mut graph = make_graph_with_nodes(vec![make_node("1", "2025-01-01T00:00:00Z")]);
+        graph.hue_offset = 90.0;
+        let computed = graph.project_hue("proj");
+        let target = HueTarget::Project("proj".to_string());
+
+        graph.hue_overrides.set(&target, Some(-20.0));
+        assert_eq!(graph.hue_overrides.get(&target), Some(340.0));
+        // The pin ignores the hue offset
+        assert_eq!(graph.project_hue("proj"), 340.0);
+        assert_eq!(graph.project_color("proj"), hsl_to_rgb(340.0, graph.shading.project_saturation, graph.shading.project_lightness));
+
+        let data = graph.data.clone();
+        graph.load(data, egui::Rect::from_min_size(Pos2::ZERO, egui::vec2(100.0, 100.0)));
+        assert_eq!(graph.project_hue("proj"), 340.0);
+
+        graph.hue_overrides.set(&target, None);
+        assert_eq!(graph.project_hue("proj"), computed);
+        assert_eq!(graph.session_hue("s1"), graph.apply_hue_offset(graph.session_colors["s1"]));
+    }
+
+    #[test]
+    fn test_light_background_darkens_node_shades() {
+        let mut graph = make_graph_with_nodes(vec![make_node("1", "2025-01-01T00:00:00Z")]);
+        let node = graph.data.nodes[0].clone();
+        let luma = |c: egui::Color32| c.r() as u32 + c.g() as u32 + c.b() as u32;
+        for mode in [ColorMode::Project, ColorMode::Hybrid, ColorMode::Session] {
+            graph.color_mode = mode;
+            graph.is_light = false;
+            let (dark_bg, dark_bg_fill) = (graph.node_color(&node), graph.node_color_light(&node));
+            graph.is_light = true;
+            assert!(luma(graph.node_color(&node)) < luma(dark_bg), "{:?}", mode);
+            assert!(luma(graph.node_color_light(&node)) < luma(dark_bg_fill), "{:?}", mode);
+        }
+    }
 }
@@ -0,0 +1,121 @@
+//! Bounded undo/redo history of settings snapshots.
+//!
+//! The top of the undo stack is always the current committed state, so
+//! undoing pops it onto the redo stack and returns the state beneath it.
+
+/// Undo/redo stacks of snapshots, deduplicated against the current state.
+#[derive(Debug, Clone)]
+pub struct History<T> {
+    undo: Vec<T>,
+    redo: Vec<T>,
+    capacity: usize,
+}
+
+impl<T: Clone + PartialEq> History<T> {
+    /// Create a history whose current state is `initial`.
+    /// `capacity` bounds the number of undo steps kept.
+    pub fn new(initial: T, capacity: usize) -> Self {
+        Self {
+            undo: vec![initial],
+            redo: Vec::new(),
+            capacity: capacity.max(1),
+        }
+    }
+
+    /// Record a committed state. Returns false (and records nothing) if it
+    /// is identical to the current state. Recording clears the redo stack.
+    pub fn record(&mut self, snapshot: T) -> bool {
+        if self.undo.last() == Some(&snapshot) {
+            return false;
+        }
+        self.undo.push(snapshot);
+        self.redo.clear();
+        // Keep `capacity` undo steps on top of the current state
+        let max_len = self.capacity + 1;
+        if self.undo.len() > max_len {
+            let excess = self.undo.len() - max_len;
+            self.undo.drain(..excess);
+        }
+        true
+    }
+
+    /// Step back one state, returning the state to restore.
+    pub fn undo(&mut self) -> Option<&T> {
+        if self.undo.len() < 2 {
+            return None;
+        }
+        let current = self.undo.pop()?;
+        self.redo.push(current);
+        self.undo.last()
+    }
+
+    /// Step forward one state, returning the state to restore.
+    pub fn redo(&mut self) -> Option<&T> {
+        let next = self.redo.pop()?;
+        self.undo.push(next);
+        self.undo.last()
+    }
+
+    /// Number of available undo steps
+    pub fn undo_depth(&self) -> usize {
+        self.undo.len().saturating_sub(1)
+    }
+
+    /// Number of available redo steps
+    pub fn redo_depth(&self) -> usize {
+        self.redo.len()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_record_skips_unchanged() {
+        let mut history = History::new(1, 10);
+        assert!(!history.record(1));
+        assert!(history.record(2));
+        assert!(!history.record(2));
+        assert_eq!(history.undo_depth(), 1);
+    }
+
+    #[test]
+    fn test_undo_redo_roundtrip() {
+        let mut history = History::new(1, 10);
+        history.record(2);
+        history.record(3);
+
+        assert_eq!(history.undo(), Some(&2));
+        assert_eq!(history.undo(), Some(&1));
+        assert_eq!(history.undo(), None);
+        assert_eq!(history.redo_depth(), 2);
+
+        assert_eq!(history.redo(), Some(&2));
+        assert_eq!(history.redo(), Some(&3));
+        assert_eq!(history.redo(), None);
+    }
+
+    #[test]
+    fn test_record_clears_redo() {
+        let mut history = History::new(1, 10);
+        history.record(2);
+        history.undo();
+        history.record(5);
+        assert_eq!(history.redo_depth(), 0);
+        assert_eq!(history.undo(), Some(&1));
+    }
+
+    #[test]
+    fn test_capacity_drops_oldest() {
+        let mut history = History::new(0, 3);
+        for i in 1..=10 {
+            history.record(i);
+        }
+        assert_eq!(history.undo_depth(), 3);
+        assert_eq!(history.undo(), Some(&9));
+        assert_eq!(history.undo(), Some(&8));
+        assert_eq!(history.undo(), Some(&7));
+        assert_eq!(history.undo(), None);
+    }
+}
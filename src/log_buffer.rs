@@ -0,0 +1,163 @@
+//! In-app log: the most recent warnings and errors, kept for the sidebar's
+//! log viewer so problems are visible without a terminal (e.g. when launched
+//! from Finder). `BufferLayer` tees tracing events into it alongside the
+//! terminal output. `Throttle` keeps per-interaction messages (scrubbing,
+//! per-frame paths) to one per call site per second.
+
+use std::collections::VecDeque;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Mutex, OnceLock};
+use std::time::{Duration, Instant};
+use tracing::field::{Field, Visit};
+use tracing::{Event, Level, Subscriber};
+use tracing_subscriber::layer::{Context, Layer};
+
+/// Events kept, oldest dropped first
+pub const MAX_LOG_EVENTS: usize = 200;
+/// Shortest gap between two messages from one throttled call site
+pub const THROTTLE_INTERVAL: Duration = Duration::from_secs(1);
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct LogEvent {
+    pub at: chrono::DateTime<chrono::Local>,
+    pub level: Level,
+    pub target: String,
+    pub message: String,
+}
+
+impl LogEvent {
+    /// "14:02:31 WARN  dashboard_native::app: message"
+    pub fn describe(&self) -> String {
+        format!("{} {:<5} {}: {}", self.at.format("%H:%M:%S"), self.level.as_str(), self.target, self.message)
+    }
+}
+
+static EVENTS: Mutex<VecDeque<LogEvent>> = Mutex::new(VecDeque::new());
+
+fn record(event: LogEvent) {
+    if let Ok(mut events) = EVENTS.lock() {
+        if events.len() >= MAX_LOG_EVENTS {
+            events.pop_front();
+        }
+        events.push_back(event);
+    }
+}
+
+/// Buffered events, newest first
+pub fn events() -> Vec<LogEvent> {
+    EVENTS.lock().map(|events| events.iter().rev().cloned().collect()).unwrap_or_default()
+}
+
+pub fn len() -> usize {
+    EVENTS.lock().map(|events| events.len()).unwrap_or(0)
+}
+
+pub fn clear() {
+    if let Ok(mut events) = EVENTS.lock() {
+        events.clear();
+    }
+}
+
+/// Tracing layer that copies warnings and errors into the buffer
+pub struct BufferLayer;
+
+impl<S: Subscriber> Layer<S> for BufferLayer {
+    fn on_event(&self, event: &Event<'_>, _ctx: Context<'_, S>) {
+        let metadata = event.metadata();
+        // Levels order by verbosity: ERROR < WARN < INFO
+        if *metadata.level() > Level::WARN {
+            return;
+        }
+        let mut visitor = MessageVisitor::default();
+        event.record(&mut visitor);
+        record(LogEvent {
+            at: chrono::Local::now(),
+            level: *metadata.level(),
+            target: metadata.target().to_string(),
+            message: visitor.message,
+        });
+    }
+}
+
+/// The event's message, followed by any other fields as `name=value`
+#[derive(Default)]
+struct MessageVisitor {
+    message: String,
+}
+
+impl Visit for MessageVisitor {
+    fn record_debug(&mut self, field: &Field, value: &dyn std::fmt::Debug) {
+        if field.name() == "message" {
+            self.message.insert_str(0, &format!("{:?}", value));
+        } else {
+            self.message.push_str(&format!(" {}={:?}", field.name(), value));
+        }
+    }
+}
+
+/// Rate limit for one call site: declare it `static` beside the log call and
+/// only log when `ready()` says so.
+pub struct Throttle {
+    /// Milliseconds since `epoch()` plus one at the last pass; 0 before the first
+    last: AtomicU64,
+}
+
+impl Throttle {
+    pub const fn new() -> Self {
+        Self { last: AtomicU64::new(0) }
+    }
+
+    /// True at most once per `THROTTLE_INTERVAL`
+    pub fn ready(&self) -> bool {
+        self.ready_at(epoch().elapsed().as_millis() as u64 + 1)
+    }
+
+    fn ready_at(&self, now: u64) -> bool {
+        let last = self.last.load(Ordering::Relaxed);
+        if last != 0 && now.saturating_sub(last) < THROTTLE_INTERVAL.as_millis() as u64 {
+            return false;
+        }
+        // Another thread passing first wins the slot
+        self.last.compare_exchange(last, now, Ordering::Relaxed, Ordering::Relaxed).is_ok()
+    }
+}
+
+fn epoch() -> Instant {
+    static EPOCH: OnceLock<Instant> = OnceLock::new();
+    *EPOCH.get_or_init(Instant::now)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tracing_subscriber::layer::SubscriberExt;
+
+    #[test]
+    fn test_throttle_passes_once_per_interval() {
+        let throttle = Throttle::new();
+        let step = THROTTLE_INTERVAL.as_millis() as u64;
+        assert!(throttle.ready_at(1));
+        assert!(!throttle.ready_at(2));
+        assert!(!throttle.ready_at(step));
+        assert!(throttle.ready_at(step + 1));
+        assert!(!throttle.ready_at(step + 2));
+    }
+
+    #[test]
+    fn test_buffer_keeps_newest_warnings() {
+        clear();
+        let subscriber = tracing_subscriber::registry().with(BufferLayer);
+        tracing::subscriber::with_default(subscriber, || {
+            tracing::info!("not kept");
+            for i in 0..MAX_LOG_EVENTS + 5 {
+                tracing::warn!(attempt = i, "retrying");
+            }
+            tracing::error!("gave up");
+        });
+        let events = events();
+        assert_eq!(events.len(), MAX_LOG_EVENTS);
+        assert_eq!((events[0].level, events[0].message.as_str()), (Level::ERROR, "gave up"));
+        assert_eq!(events[1].message, format!("retrying attempt={}", MAX_LOG_EVENTS + 4));
+        assert!(events.iter().all(|e| e.level <= Level::WARN));
+    }
+}
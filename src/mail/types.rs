@@ -75,6 +75,12 @@ impl MailNetworkState {
         }
     }
 
+    /// Whether the nodes have nearly stopped moving
+    pub fn is_settled(&self) -> bool {
+        let total: f32 = self.velocities.values().map(|v| v.length()).sum();
+        (total / self.velocities.len().max(1) as f32) < 0.5
+    }
+
     /// Apply one step of force-directed layout.
     pub fn step(&mut self, center: Pos2, bounds: egui::Rect, dt: f32) {
         let repulsion = 5000.0;
@@ -3,6 +3,11 @@
 use egui::{Color32, Painter, Pos2, Rect, Response, Sense, Stroke, Ui, Vec2};
 
 use super::types::MailNetworkState;
+use crate::graph::hsl_to_rgb;
+use crate::theme;
+
+/// Most layout steps run in one frame when reduced motion settles the layout
+const SETTLE_STEPS: usize = 500;
 
 /// Color palette for agent types
 fn agent_color(agent_id: &str) -> Color32 {
@@ -24,32 +29,6 @@ fn agent_color(agent_id: &str) -> Color32 {
     }
 }
 
-fn hsl_to_rgb(h: f32, s: f32, l: f32) -> Color32 {
-    let c = (1.0 - (2.0 * l - 1.0).abs()) * s;
-    let x = c * (1.0 - ((h / 60.0) % 2.0 - 1.0).abs());
-    let m = l - c / 2.0;
-
-    let (r, g, b) = if h < 60.0 {
-        (c, x, 0.0)
-    } else if h < 120.0 {
-        (x, c, 0.0)
-    } else if h < 180.0 {
-        (0.0, c, x)
-    } else if h < 240.0 {
-        (0.0, x, c)
-    } else if h < 300.0 {
-        (x, 0.0, c)
-    } else {
-        (c, 0.0, x)
-    };
-
-    Color32::from_rgb(
-        ((r + m) * 255.0) as u8,
-        ((g + m) * 255.0) as u8,
-        ((b + m) * 255.0) as u8,
-    )
-}
-
 /// Render the mail network graph widget.
 pub fn render_mail_network(
     ui: &mut Ui,
@@ -59,10 +38,11 @@ pub fn render_mail_network(
     let (response, painter) = ui.allocate_painter(size, Sense::click_and_drag());
     let rect = response.rect;
     let center = rect.center();
+    let palette = theme::current(ui.ctx());
 
     // Background
-    painter.rect_filled(rect, 4.0, Color32::from_rgb(25, 28, 35));
-    painter.rect_stroke(rect, 4.0, Stroke::new(1.0, Color32::from_rgb(60, 65, 75)));
+    painter.rect_filled(rect, 4.0, palette.bg.surface);
+    painter.rect_stroke(rect, 4.0, Stroke::new(1.0, palette.border.default));
 
     // Handle empty state
     if state.data.nodes.is_empty() {
@@ -71,13 +51,22 @@ pub fn render_mail_network(
             egui::Align2::CENTER_CENTER,
             "No mail data",
             egui::FontId::proportional(12.0),
-            Color32::GRAY,
+            palette.text.muted,
         );
         return response;
     }
 
-    // Run physics simulation
-    state.step(center, rect, 0.016);
+    // Run physics simulation; reduced motion settles it in one go instead of animating
+    if theme::motion_enabled() || state.dragged_node.is_some() {
+        state.step(center, rect, 0.016);
+    } else {
+        for _ in 0..SETTLE_STEPS {
+            state.step(center, rect, 0.016);
+            if state.is_settled() {
+                break;
+            }
+        }
+    }
 
     // Handle dragging
     if let Some(pointer_pos) = response.interact_pointer_pos() {
@@ -150,7 +139,7 @@ pub fn render_mail_network(
                 100
             };
 
-            let color = Color32::from_rgba_unmultiplied(150, 150, 150, alpha);
+            let color = palette.text.muted.gamma_multiply((alpha as f32 / 255.0 * palette.edge_gamma).min(1.0));
             painter.line_segment([*src, *tgt], Stroke::new(thickness, color));
 
             // Draw arrow head
@@ -192,9 +181,9 @@ pub fn render_mail_network(
 
             // Draw border
             let border_color = if is_hovered {
-                Color32::WHITE
+                palette.highlight.hover
             } else {
-                Color32::from_rgba_unmultiplied(255, 255, 255, 60)
+                palette.highlight.hover.gamma_multiply(0.25)
             };
             painter.circle_stroke(*pos, radius, Stroke::new(1.0, border_color));
 
@@ -203,9 +192,9 @@ pub fn render_mail_network(
                 let label_pos = Pos2::new(pos.x, pos.y - radius - 8.0);
                 let font = egui::FontId::proportional(if is_hovered { 11.0 } else { 9.0 });
                 let text_color = if is_hovered {
-                    Color32::WHITE
+                    palette.text.primary
                 } else {
-                    Color32::from_rgb(180, 180, 180)
+                    palette.text.secondary
                 };
 
                 painter.text(
@@ -227,8 +216,8 @@ pub fn render_mail_network(
                 let tooltip_rect = Rect::from_min_size(tooltip_pos, Vec2::new(150.0, 50.0));
 
                 // Draw tooltip background
-                painter.rect_filled(tooltip_rect, 4.0, Color32::from_rgb(40, 45, 55));
-                painter.rect_stroke(tooltip_rect, 4.0, Stroke::new(1.0, Color32::from_rgb(80, 85, 95)));
+                painter.rect_filled(tooltip_rect, 4.0, palette.tooltip.bg);
+                painter.rect_stroke(tooltip_rect, 4.0, Stroke::new(1.0, palette.tooltip.border));
 
                 // Draw tooltip text
                 painter.text(
@@ -236,21 +225,21 @@ pub fn render_mail_network(
                     egui::Align2::LEFT_TOP,
                     &node.full_label,
                     egui::FontId::proportional(10.0),
-                    Color32::WHITE,
+                    palette.tooltip.text,
                 );
                 painter.text(
                     Pos2::new(tooltip_rect.left() + 5.0, tooltip_rect.top() + 18.0),
                     egui::Align2::LEFT_TOP,
                     format!("Messages: {}", node.message_count),
                     egui::FontId::proportional(9.0),
-                    Color32::LIGHT_GRAY,
+                    palette.tooltip.text.gamma_multiply(0.8),
                 );
                 painter.text(
                     Pos2::new(tooltip_rect.left() + 5.0, tooltip_rect.top() + 30.0),
                     egui::Align2::LEFT_TOP,
                     format!("Sent: {} | Recv: {}", node.sent_count, node.received_count),
                     egui::FontId::proportional(9.0),
-                    Color32::LIGHT_GRAY,
+                    palette.tooltip.text.gamma_multiply(0.8),
                 );
             }
         }
@@ -266,11 +255,13 @@ pub fn render_mail_network(
         egui::Align2::RIGHT_BOTTOM,
         stats_text,
         egui::FontId::proportional(9.0),
-        Color32::from_rgb(100, 100, 100),
+        palette.text.muted,
     );
 
-    // Request repaint for animation
-    ui.ctx().request_repaint();
+    // Keep the layout animating; with reduced motion only while a node is dragged
+    if theme::motion_enabled() || state.dragged_node.is_some() {
+        ui.ctx().request_repaint();
+    }
 
     response
 }
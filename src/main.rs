@@ -4,19 +4,102 @@
 
 mod api;
 mod app;
+mod beads_dir;
+mod bundle;
+mod cli;
+mod command;
+mod compare;
+mod content_kind;
+mod cost;
 mod db;
+mod diff;
+mod discover;
+mod exclude;
+mod export;
 mod graph;
+mod history;
+mod log_buffer;
 mod mail;
+mod memory;
+mod notify;
+mod obsidian;
+mod overview;
+mod profiler;
 mod project_tree;
+mod rules;
 mod settings;
+mod setup;
+mod shortcuts;
+mod snapshot;
+mod stats;
+mod stats_export;
+mod tags;
+mod text;
+mod text_layout;
+mod text_diff;
+mod tooltip;
+mod topics;
+mod transcript;
 mod theme;
+mod theme_file;
+mod time_expr;
+mod view_string;
+mod watchdog;
+mod write_queue;
 
 use eframe::egui;
-use tracing_subscriber;
+use tracing_subscriber::filter::LevelFilter;
+use tracing_subscriber::prelude::*;
 
 fn main() -> eframe::Result<()> {
-    // Initialize logging
-    tracing_subscriber::fmt::init();
+    // Parse command-line options before starting the UI
+    let args = match cli::CliArgs::parse(std::env::args().skip(1)) {
+        Ok(args) => args,
+        Err(e) => {
+            eprintln!("error: {}\n\n{}", e, cli::USAGE);
+            std::process::exit(2);
+        }
+    };
+    if args.help {
+        println!("{}", cli::USAGE);
+        return Ok(());
+    }
+    if let Err(e) = args.check_db_path() {
+        eprintln!("error: {}", e);
+        std::process::exit(1);
+    }
+
+    // Headless export: no window, exit status reports success
+    if let Some(ref path) = args.headless_export {
+        match export::run_headless(&args, path) {
+            Ok(nodes) => {
+                eprintln!("Wrote {} nodes to {}", nodes, path);
+                return Ok(());
+            }
+            Err(e) => {
+                eprintln!("error: {}", e);
+                std::process::exit(1);
+            }
+        }
+    }
+
+    // A debug bundle stands in for the database; a bad path is a startup error
+    let bundle = match args.load_bundle {
+        Some(ref path) => match bundle::DebugBundle::read(std::path::Path::new(path)) {
+            Ok(bundle) => Some(bundle),
+            Err(e) => {
+                eprintln!("error: {}", e);
+                std::process::exit(1);
+            }
+        },
+        None => None,
+    };
+
+    // Initialize logging: the terminal, plus warnings and errors for the in-app log
+    tracing_subscriber::registry()
+        .with(tracing_subscriber::fmt::layer().with_filter(LevelFilter::INFO))
+        .with(log_buffer::BufferLayer)
+        .init();
 
     let options = eframe::NativeOptions {
         viewport: egui::ViewportBuilder::default()
@@ -29,6 +112,6 @@ fn main() -> eframe::Result<()> {
     eframe::run_native(
         "Claude Activity Dashboard",
         options,
-        Box::new(|cc| Ok(Box::new(app::DashboardApp::new(cc)))),
+        Box::new(|cc| Ok(Box::new(app::DashboardApp::new(cc, args, bundle)))),
     )
 }
@@ -0,0 +1,214 @@
+//! Approximate memory accounting for the loaded graph, and projections for a
+//! load before it happens so large ranges can fall back to leaner settings.
+
+use crate::graph::types::{GraphEdge, GraphNode, GraphState};
+use std::mem::size_of;
+
+/// Per-entry overhead of a hashbrown map beyond the key and value (control byte,
+/// plus slack for the 7/8 load factor)
+const MAP_ENTRY_OVERHEAD: usize = 2;
+
+/// Average heap bytes of a node's short strings (id, session ids, project,
+/// timestamp) when projecting a load that hasn't happened yet
+const PROJECTED_NODE_STRINGS: usize = 120;
+
+/// Average heap bytes of an edge's strings (two ids, session id, timestamp)
+const PROJECTED_EDGE_STRINGS: usize = 80;
+
+/// Preview text kept per node even in content-lazy mode (up to 100 chars plus "...")
+const PROJECTED_PREVIEW: usize = 110;
+
+/// Approximate bytes held by a loaded graph, by category
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct MemoryEstimate {
+    /// Full message text (`full_content`)
+    pub content: usize,
+    /// Node structs and their short strings, including previews
+    pub nodes: usize,
+    /// Edge structs and their strings
+    pub edges: usize,
+    /// Position and velocity maps
+    pub layout: usize,
+}
+
+impl MemoryEstimate {
+    pub fn total(&self) -> usize {
+        self.content + self.nodes + self.edges + self.layout
+    }
+
+    pub fn of_graph(graph: &GraphState) -> Self {
+        let mut estimate = Self::of_data(&graph.data.nodes, &graph.data.edges);
+        estimate.layout = map_bytes::<egui::Pos2>(graph.positions.keys())
+            + map_bytes::<egui::Vec2>(graph.velocities.keys());
+        estimate
+    }
+
+    /// Content, node and edge bytes (no layout maps)
+    pub fn of_data(nodes: &[GraphNode], edges: &[GraphEdge]) -> Self {
+        let mut estimate = Self::default();
+        for node in nodes {
+            estimate.content += node.full_content.as_ref().map_or(0, String::len);
+            estimate.nodes += size_of::<GraphNode>()
+                + node.id.len()
+                + node.content_preview.len()
+                + node.session_id.len()
+                + node.session_short.len()
+                + node.project.len()
+                + node.timestamp.as_ref().map_or(0, String::len)
+                + node.importance_reason.as_ref().map_or(0, String::len);
+        }
+        for edge in edges {
+            estimate.edges += size_of::<GraphEdge>()
+                + edge.source.len()
+                + edge.target.len()
+                + edge.session_id.len()
+                + edge.timestamp.as_ref().map_or(0, String::len);
+        }
+        estimate
+    }
+
+    /// Expected footprint of loading `messages` messages carrying `content_bytes`
+    /// of text, with up to `temporal_edges` temporal edges
+    pub fn projected(messages: usize, content_bytes: usize, content_lazy: bool, temporal_edges: usize) -> Self {
+        let id_len = 8;
+        Self {
+            content: if content_lazy { 0 } else { content_bytes },
+            nodes: messages * (size_of::<GraphNode>() + PROJECTED_NODE_STRINGS + PROJECTED_PREVIEW),
+            // One conversation edge per message, plus the temporal edges
+            edges: (messages + temporal_edges) * (size_of::<GraphEdge>() + PROJECTED_EDGE_STRINGS),
+            layout: messages
+                * (2 * (size_of::<String>() + id_len + MAP_ENTRY_OVERHEAD) + size_of::<egui::Pos2>() + size_of::<egui::Vec2>()),
+        }
+    }
+}
+
+fn map_bytes<'a, V>(keys: impl Iterator<Item = &'a String>) -> usize {
+    keys.map(|k| size_of::<String>() + size_of::<V>() + MAP_ENTRY_OVERHEAD + k.len())
+        .sum()
+}
+
+/// Leaner settings that bring a projected load under `budget` bytes, trying
+/// content-lazy mode first and then the largest temporal edge cap from `edge_caps`
+/// (ascending) that fits. None if the load already fits.
+pub fn fit_to_budget(
+    messages: usize,
+    content_bytes: usize,
+    content_lazy: bool,
+    temporal_edges: usize,
+    edge_caps: &[usize],
+    budget: usize,
+) -> Option<(bool, usize)> {
+    if MemoryEstimate::projected(messages, content_bytes, content_lazy, temporal_edges).total() <= budget {
+        return None;
+    }
+    if MemoryEstimate::projected(messages, content_bytes, true, temporal_edges).total() <= budget {
+        return Some((true, temporal_edges));
+    }
+    let cap = edge_caps.iter()
+        .rev()
+        .copied()
+        .filter(|&cap| cap < temporal_edges)
+        .find(|&cap| MemoryEstimate::projected(messages, content_bytes, true, cap).total() <= budget)
+        .or_else(|| edge_caps.first().copied().filter(|&cap| cap < temporal_edges))
+        .unwrap_or(temporal_edges);
+    Some((true, cap))
+}
+
+/// "512 KB", "1.4 MB", "2.1 GB"
+pub fn format_bytes(bytes: usize) -> String {
+    let b = bytes as f64;
+    if b >= 1024.0 * 1024.0 * 1024.0 {
+        format!("{:.1} GB", b / (1024.0 * 1024.0 * 1024.0))
+    } else if b >= 1024.0 * 1024.0 {
+        format!("{:.1} MB", b / (1024.0 * 1024.0))
+    } else {
+        format!("{:.0} KB", b / 1024.0)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::graph::types::GraphData;
+
+    fn node(id: &str, content: Option<&str>) -> GraphNode {
+        GraphNode {
+            content_preview: "preview".to_string(),
+            full_content: content.map(str::to_string),
+            session_short: "session-".to_string(),
+            project: "~/proj".to_string(),
+            timestamp: Some("2025-06-01T10:00:00Z".to_string()),
+            ..GraphNode::stub(id, "session-1")
+        }
+    }
+
+    #[test]
+    fn test_estimate_counts_strings_and_structs() {
+        let content = "x".repeat(1000);
+        let nodes = vec![node("1", Some(&content)), node("2", None)];
+        let edges = vec![GraphEdge::conversation("1".into(), "2".into(), "session-1".into(), None)];
+        let estimate = MemoryEstimate::of_data(&nodes, &edges);
+
+        assert_eq!(estimate.content, 1000);
+        // id + preview + session + short + project + timestamp
+        let strings = 1 + 7 + 9 + 8 + 6 + 20;
+        assert_eq!(estimate.nodes, 2 * (size_of::<GraphNode>() + strings));
+        assert_eq!(estimate.edges, size_of::<GraphEdge>() + 1 + 1 + 9);
+        assert_eq!(estimate.layout, 0);
+    }
+
+    #[test]
+    fn test_graph_estimate_includes_layout_maps() {
+        let mut graph = GraphState::new();
+        let nodes = vec![node("1", Some("hello")), node("22", Some("world"))];
+        graph.load(GraphData::new(nodes, Vec::new()), egui::Rect::from_min_size(egui::Pos2::ZERO, egui::vec2(100.0, 100.0)));
+        let estimate = MemoryEstimate::of_graph(&graph);
+
+        assert_eq!(estimate.content, 10);
+        let per_key = |len: usize| size_of::<String>() + MAP_ENTRY_OVERHEAD + len;
+        let expected = graph.positions.keys().map(|k| per_key(k.len()) + size_of::<egui::Pos2>()).sum::<usize>()
+            + graph.velocities.keys().map(|k| per_key(k.len()) + size_of::<egui::Vec2>()).sum::<usize>();
+        assert_eq!(estimate.layout, expected);
+        assert!(estimate.layout > 0);
+    }
+
+    #[test]
+    fn test_projection_tracks_actual_estimate() {
+        // A hand-built load: 200 messages of 2 KB each in one session
+        let content = "y".repeat(2048);
+        let nodes: Vec<GraphNode> = (0..200).map(|i| node(&format!("{:06}", i), Some(&content))).collect();
+        let edges: Vec<GraphEdge> = nodes.windows(2)
+            .map(|w| GraphEdge::conversation(w[0].id.clone(), w[1].id.clone(), "session-1".into(), w[1].timestamp.clone()))
+            .collect();
+        let actual = MemoryEstimate::of_data(&nodes, &edges);
+        let projected = MemoryEstimate::projected(200, 200 * 2048, false, 0);
+
+        assert_eq!(projected.content, actual.content);
+        let (a, p) = (actual.nodes + actual.edges, projected.nodes + projected.edges);
+        assert!(p >= a && p < a * 2, "projected {} vs actual {}", p, a);
+        assert_eq!(MemoryEstimate::projected(200, 200 * 2048, true, 0).content, 0);
+    }
+
+    #[test]
+    fn test_fit_to_budget() {
+        let caps = [10_000, 50_000, 100_000];
+        let mb = 1024 * 1024;
+        // Small loads need nothing
+        assert_eq!(fit_to_budget(1_000, mb, false, 100_000, &caps, 1024 * mb), None);
+        // Heavy content: dropping it is enough
+        assert_eq!(fit_to_budget(1_000, 2048 * mb, false, 10_000, &caps, 512 * mb), Some((true, 10_000)));
+        // Edges dominate: lazy content plus a smaller cap
+        let edge_bytes = size_of::<GraphEdge>() + PROJECTED_EDGE_STRINGS;
+        let budget = MemoryEstimate::projected(1_000, 0, true, 0).total() + 60_000 * edge_bytes;
+        assert_eq!(fit_to_budget(1_000, 0, false, 100_000, &caps, budget), Some((true, 50_000)));
+        // Nothing fits: fall back to the smallest cap
+        assert_eq!(fit_to_budget(1_000_000, 0, false, 100_000, &caps, mb), Some((true, 10_000)));
+    }
+
+    #[test]
+    fn test_format_bytes() {
+        assert_eq!(format_bytes(512 * 1024), "512 KB");
+        assert_eq!(format_bytes(1536 * 1024), "1.5 MB");
+        assert_eq!(format_bytes(3 * 1024 * 1024 * 1024), "3.0 GB");
+    }
+}
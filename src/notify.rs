@@ -0,0 +1,186 @@
+//! Desktop notifications for new sessions and high-importance messages.
+
+use crate::graph::types::{GraphNode, Role};
+use std::collections::HashSet;
+use std::time::{Duration, Instant};
+
+/// Why a node is worth a nudge
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ActivityKind {
+    NewSession,
+    Important,
+}
+
+/// A node that appeared since the previous load
+#[derive(Debug, Clone, PartialEq)]
+pub struct NewActivity {
+    pub kind: ActivityKind,
+    pub node_id: String,
+    pub project: String,
+    pub preview: String,
+}
+
+/// Session ids and high-importance node ids seen in earlier loads
+#[derive(Debug, Default)]
+pub struct ActivityWatch {
+    sessions: HashSet<String>,
+    important: HashSet<String>,
+    primed: bool,
+}
+
+impl ActivityWatch {
+    /// Remember the current data without reporting anything (first or full load)
+    pub fn prime(&mut self, nodes: &[GraphNode], threshold: f32) {
+        self.observe(nodes, threshold);
+        self.primed = true;
+    }
+
+    /// Sessions and important messages not present in earlier loads.
+    /// Each new session is reported once, through its first message.
+    pub fn diff(&mut self, nodes: &[GraphNode], threshold: f32) -> Vec<NewActivity> {
+        if !self.primed {
+            self.prime(nodes, threshold);
+            return Vec::new();
+        }
+        let mut fresh = Vec::new();
+        let mut new_sessions: HashSet<&str> = HashSet::new();
+        for node in nodes {
+            // Vault notes and summary topics are derived nodes, not activity
+            if matches!(node.role, Role::Obsidian | Role::Topic) {
+                continue;
+            }
+            let kind = if !self.sessions.contains(&node.session_id) {
+                if !new_sessions.insert(node.session_id.as_str()) {
+                    continue;
+                }
+                ActivityKind::NewSession
+            } else if is_important(node, threshold) && !self.important.contains(&node.id) {
+                ActivityKind::Important
+            } else {
+                continue;
+            };
+            fresh.push(NewActivity {
+                kind,
+                node_id: node.id.clone(),
+                project: node.project.clone(),
+                preview: node.content_preview.clone(),
+            });
+        }
+        self.observe(nodes, threshold);
+        fresh
+    }
+
+    fn observe(&mut self, nodes: &[GraphNode], threshold: f32) {
+        for node in nodes {
+            self.sessions.insert(node.session_id.clone());
+            if is_important(node, threshold) {
+                self.important.insert(node.id.clone());
+            }
+        }
+    }
+}
+
+fn is_important(node: &GraphNode, threshold: f32) -> bool {
+    node.importance_score.is_some_and(|score| score >= threshold)
+}
+
+/// Sends at most one desktop notification per interval; anything in between
+/// is only counted in the in-app badge
+#[derive(Debug, Default)]
+pub struct Notifier {
+    last_sent: Option<Instant>,
+}
+
+impl Notifier {
+    /// Whether a notification may go out at `now`
+    pub fn ready(&self, now: Instant, min_interval: Duration) -> bool {
+        self.last_sent.is_none_or(|sent| now.duration_since(sent) >= min_interval)
+    }
+
+    /// Notify about `fresh` unless rate-limited. Returns true if one was sent.
+    pub fn notify(&mut self, fresh: &[NewActivity], min_interval: Duration) -> bool {
+        let now = Instant::now();
+        if fresh.is_empty() || !self.ready(now, min_interval) {
+            return false;
+        }
+        self.last_sent = Some(now);
+        let (summary, body) = notification_text(fresh);
+        // Delivery talks to the desktop's notification service; keep it off the UI thread
+        std::thread::spawn(move || {
+            if let Err(e) = notify_rust::Notification::new()
+                .appname("Claude Activity Dashboard")
+                .summary(&summary)
+                .body(&body)
+                .show()
+            {
+                tracing::warn!("Desktop notification failed: {}", e);
+            }
+        });
+        true
+    }
+}
+
+/// Title and body for a batch of new activity
+pub fn notification_text(fresh: &[NewActivity]) -> (String, String) {
+    let first = &fresh[0];
+    let project = if first.project.is_empty() { "(no project)" } else { first.project.as_str() };
+    let what = match first.kind {
+        ActivityKind::NewSession => "New session",
+        ActivityKind::Important => "Important message",
+    };
+    let summary = if fresh.len() == 1 {
+        format!("{} · {}", what, project)
+    } else {
+        format!("{} · {} (+{} more)", what, project, fresh.len() - 1)
+    };
+    (summary, crate::text::truncate(&first.preview, 160))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::graph::types::Role;
+
+    fn node(id: &str, session: &str, score: Option<f32>) -> GraphNode {
+        GraphNode {
+            role: Role::Assistant,
+            content_preview: format!("preview {}", id),
+            project: "proj".to_string(),
+            importance_score: score,
+            ..GraphNode::stub(id, session)
+        }
+    }
+
+    #[test]
+    fn test_diff_reports_new_sessions_and_important_nodes_once() {
+        let mut watch = ActivityWatch::default();
+        let first = vec![node("1", "a", Some(0.9))];
+        assert!(watch.diff(&first, 0.8).is_empty(), "first load only primes");
+
+        let second = vec![
+            node("1", "a", Some(0.9)),
+            node("2", "a", Some(0.85)),
+            node("3", "a", Some(0.2)),
+            node("4", "b", Some(0.95)),
+            node("5", "b", None),
+        ];
+        let fresh = watch.diff(&second, 0.8);
+        let kinds: Vec<(&str, ActivityKind)> = fresh.iter().map(|a| (a.node_id.as_str(), a.kind)).collect();
+        assert_eq!(kinds, vec![("2", ActivityKind::Important), ("4", ActivityKind::NewSession)]);
+
+        assert!(watch.diff(&second, 0.8).is_empty());
+    }
+
+    #[test]
+    fn test_rate_limit_and_text() {
+        let notifier = Notifier { last_sent: Some(Instant::now()) };
+        assert!(!notifier.ready(Instant::now(), Duration::from_secs(60)));
+        assert!(Notifier::default().ready(Instant::now(), Duration::from_secs(60)));
+
+        let fresh = vec![
+            NewActivity { kind: ActivityKind::NewSession, node_id: "1".into(), project: "proj".into(), preview: "hello".into() },
+            NewActivity { kind: ActivityKind::Important, node_id: "2".into(), project: "proj".into(), preview: "x".into() },
+        ];
+        assert_eq!(notification_text(&fresh), ("New session · proj (+1 more)".to_string(), "hello".to_string()));
+    }
+}
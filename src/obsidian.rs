@@ -0,0 +1,368 @@
+//! Obsidian vault integration: notes modified inside the loaded window become
+//! `Role::Obsidian` nodes, linked to the messages that mention them.
+
+use crate::graph::ids;
+use crate::graph::types::{GraphData, GraphEdge, GraphNode, Role};
+use regex::Regex;
+use std::collections::{HashMap, HashSet};
+use std::path::{Path, PathBuf};
+use std::time::UNIX_EPOCH;
+
+/// Session id shared by every note node
+pub const NOTE_SESSION: &str = "obsidian";
+
+/// Notes read per scan, newest first, so a huge vault can't flood the graph
+const MAX_NOTES: usize = 500;
+
+/// Titles and aliases shorter than this are too ambiguous to match on
+const MIN_NAME_CHARS: usize = 3;
+
+/// A markdown note read from the vault
+#[derive(Debug, Clone, PartialEq)]
+pub struct Note {
+    /// Path relative to the vault root
+    pub path: String,
+    /// Frontmatter `title`, else the file stem
+    pub title: String,
+    pub aliases: Vec<String>,
+    /// Modified time as epoch seconds
+    pub modified: f64,
+    /// Markdown after the frontmatter
+    pub body: String,
+}
+
+impl Note {
+    pub fn parse(path: &str, text: &str, modified: f64) -> Self {
+        let (frontmatter, body) = split_frontmatter(text);
+        let (title, aliases) = parse_frontmatter(frontmatter);
+        let stem = Path::new(path).file_stem().and_then(|s| s.to_str()).unwrap_or(path);
+        Self {
+            path: path.to_string(),
+            title: title.unwrap_or_else(|| stem.to_string()),
+            aliases,
+            modified,
+            body: body.trim_start().to_string(),
+        }
+    }
+
+    /// First paragraph of prose, skipping headings
+    pub fn first_paragraph(&self) -> String {
+        let mut lines = Vec::new();
+        for line in self.body.lines().map(str::trim) {
+            if line.is_empty() || line.starts_with('#') {
+                if !lines.is_empty() {
+                    break;
+                }
+                continue;
+            }
+            lines.push(line);
+        }
+        lines.join(" ")
+    }
+
+    pub fn node_id(&self) -> String {
+        ids::note(&self.path)
+    }
+
+    /// Title and aliases long enough to match on
+    fn names(&self) -> impl Iterator<Item = &str> {
+        std::iter::once(self.title.as_str())
+            .chain(self.aliases.iter().map(String::as_str))
+            .filter(|name| name.chars().count() >= MIN_NAME_CHARS)
+    }
+}
+
+/// The parts of a message the linker looks at
+#[derive(Debug, Clone)]
+pub struct MessageRef {
+    pub id: String,
+    pub session_id: String,
+    pub session_short: String,
+    pub text: String,
+}
+
+impl MessageRef {
+    pub fn from_node(node: &GraphNode) -> Self {
+        Self {
+            id: node.id.clone(),
+            session_id: node.session_id.clone(),
+            session_short: node.session_short.clone(),
+            text: node.full_content.clone().unwrap_or_else(|| node.content_preview.clone()),
+        }
+    }
+}
+
+/// Read notes under `vault` modified at or after `since` (epoch seconds).
+/// Hidden directories (`.obsidian`, `.trash`) are skipped.
+pub fn scan_vault(vault: &Path, since: f64) -> Result<Vec<Note>, String> {
+    if !vault.is_dir() {
+        return Err(format!("Vault not found: {}", vault.display()));
+    }
+    let mut found: Vec<(f64, PathBuf)> = Vec::new();
+    let mut pending = vec![vault.to_path_buf()];
+    while let Some(dir) = pending.pop() {
+        let Ok(entries) = std::fs::read_dir(&dir) else {
+            continue;
+        };
+        for entry in entries.flatten() {
+            let path = entry.path();
+            let hidden = entry.file_name().to_string_lossy().starts_with('.');
+            let Ok(meta) = entry.metadata() else {
+                continue;
+            };
+            if meta.is_dir() {
+                if !hidden {
+                    pending.push(path);
+                }
+                continue;
+            }
+            if hidden || path.extension().and_then(|e| e.to_str()) != Some("md") {
+                continue;
+            }
+            let modified = meta.modified().ok()
+                .and_then(|t| t.duration_since(UNIX_EPOCH).ok())
+                .map_or(0.0, |d| d.as_secs_f64());
+            if modified >= since {
+                found.push((modified, path));
+            }
+        }
+    }
+
+    found.sort_by(|a, b| b.0.total_cmp(&a.0));
+    found.truncate(MAX_NOTES);
+    Ok(found.into_iter()
+        .filter_map(|(modified, path)| {
+            let text = std::fs::read_to_string(&path).ok()?;
+            let relative = path.strip_prefix(vault).unwrap_or(&path).to_string_lossy().replace('\\', "/");
+            Some(Note::parse(&relative, &text, modified))
+        })
+        .collect())
+}
+
+/// Project label for the vault's notes, with the home directory shortened to `~`
+/// like conversation projects
+pub fn vault_project(vault: &Path) -> String {
+    let path = vault.display().to_string();
+    match dirs::home_dir() {
+        Some(home) => path.replace(&format!("{}/", home.display()), "~/"),
+        None => path,
+    }
+}
+
+/// Vault-relative path of each note by node id, since ids only carry a hash
+pub fn note_paths(notes: &[Note]) -> HashMap<String, String> {
+    notes.iter().map(|note| (note.node_id(), note.path.clone())).collect()
+}
+
+/// Note nodes plus `is_obsidian` edges. A message that mentions a note's title or
+/// alias (as a whole word, any case) links to it, and a note that mentions a
+/// session id links to that session's first message.
+pub fn link_notes(notes: &[Note], messages: &[MessageRef], project: &str) -> GraphData {
+    let mut first_in_session: HashMap<&str, &str> = HashMap::new();
+    for message in messages {
+        first_in_session.entry(message.session_id.as_str()).or_insert(message.id.as_str());
+        first_in_session.entry(message.session_short.as_str()).or_insert(message.id.as_str());
+    }
+
+    let mut data = GraphData::default();
+    for note in notes {
+        let note_id = note.node_id();
+        let mut linked: HashSet<&str> = HashSet::new();
+
+        if let Some(pattern) = names_pattern(note) {
+            for message in messages {
+                if pattern.is_match(&message.text) {
+                    linked.insert(message.id.as_str());
+                }
+            }
+        }
+        for (session, first) in &first_in_session {
+            if session.chars().count() >= MIN_NAME_CHARS && note.body.contains(*session) {
+                linked.insert(*first);
+            }
+        }
+
+        let mut targets: Vec<&str> = linked.into_iter().collect();
+        targets.sort_unstable();
+        data.edges.extend(targets.into_iter().map(|target| GraphEdge::obsidian(note_id.clone(), target.to_string())));
+        data.nodes.push(note_node(note, note_id, project));
+    }
+    data
+}
+
+/// Swap the note nodes and edges in `data` for `notes`, dropping edges to
+/// messages that are no longer loaded
+pub fn attach(data: &mut GraphData, notes: &GraphData) {
+    data.nodes.retain(|n| n.role != Role::Obsidian);
+    data.edges.retain(|e| !e.is_obsidian);
+    let ids: HashSet<&str> = data.nodes.iter().map(|n| n.id.as_str()).collect();
+    let edges: Vec<GraphEdge> = notes.edges.iter()
+        .filter(|e| ids.contains(e.target.as_str()))
+        .cloned()
+        .collect();
+    data.edges.extend(edges);
+    data.nodes.extend(notes.nodes.iter().cloned());
+}
+
+fn note_node(note: &Note, id: String, project: &str) -> GraphNode {
+    let paragraph = note.first_paragraph();
+    let preview = if paragraph.is_empty() { note.title.clone() } else { paragraph };
+    let content_preview = if preview.chars().count() > 100 {
+        format!("{}...", preview.chars().take(100).collect::<String>())
+    } else {
+        preview
+    };
+    GraphNode {
+        id,
+        role: Role::Obsidian,
+        content_preview,
+        full_content: Some(note.body.clone()),
+        session_id: NOTE_SESSION.to_string(),
+        session_short: NOTE_SESSION.to_string(),
+        project: project.to_string(),
+        timestamp: chrono::DateTime::from_timestamp(note.modified.floor() as i64, 0)
+            .map(|t| t.format("%Y-%m-%dT%H:%M:%SZ").to_string()),
+        importance_score: None,
+        importance_reason: None,
+        output_tokens: None,
+        input_tokens: None,
+        cache_read_tokens: None,
+        cache_creation_tokens: None,
+        has_tool_usage: false,
+    }
+}
+
+/// Case-insensitive whole-word match on any of the note's names
+fn names_pattern(note: &Note) -> Option<Regex> {
+    let names: Vec<String> = note.names().map(regex::escape).collect();
+    if names.is_empty() {
+        return None;
+    }
+    Regex::new(&format!(r"(?i)\b(?:{})\b", names.join("|"))).ok()
+}
+
+/// Split a leading `---` block off the note
+fn split_frontmatter(text: &str) -> (&str, &str) {
+    let Some(rest) = text.strip_prefix("---").and_then(|r| r.strip_prefix('\n').or_else(|| r.strip_prefix("\r\n"))) else {
+        return ("", text);
+    };
+    let mut offset = 0;
+    for line in rest.split_inclusive('\n') {
+        if line.trim_end() == "---" {
+            return (&rest[..offset], &rest[offset + line.len()..]);
+        }
+        offset += line.len();
+    }
+    ("", text)
+}
+
+/// `title` and `aliases` (or `alias`) from frontmatter. Lists may be inline
+/// (`[a, b]`), block (`- a`) or a single value.
+fn parse_frontmatter(frontmatter: &str) -> (Option<String>, Vec<String>) {
+    let mut title = None;
+    let mut aliases = Vec::new();
+    let mut in_aliases = false;
+    for line in frontmatter.lines() {
+        if in_aliases {
+            if let Some(item) = line.trim_start().strip_prefix("- ") {
+                aliases.push(unquote(item));
+                continue;
+            }
+            in_aliases = false;
+        }
+        let Some((key, value)) = line.split_once(':') else {
+            continue;
+        };
+        let value = value.trim();
+        match key.trim() {
+            "title" if !value.is_empty() => title = Some(unquote(value)),
+            "aliases" | "alias" => {
+                if let Some(inline) = value.strip_prefix('[').and_then(|v| v.strip_suffix(']')) {
+                    aliases.extend(inline.split(',').map(unquote).filter(|a| !a.is_empty()));
+                } else if value.is_empty() {
+                    in_aliases = true;
+                } else {
+                    aliases.push(unquote(value));
+                }
+            }
+            _ => {}
+        }
+    }
+    (title, aliases)
+}
+
+fn unquote(value: &str) -> String {
+    value.trim().trim_matches(|c| c == '"' || c == '\'').to_string()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn message(id: &str, session: &str, text: &str) -> MessageRef {
+        MessageRef {
+            id: id.to_string(),
+            session_id: session.to_string(),
+            session_short: session.chars().take(8).collect(),
+            text: text.to_string(),
+        }
+    }
+
+    #[test]
+    fn test_parse_frontmatter_and_preview() {
+        let text = "---\ntitle: \"Graph Layout\"\naliases:\n  - force sim\n  - FDL\ntags: [rust]\n---\n# Heading\n\nFirst line\nof the paragraph.\n\nSecond paragraph.\n";
+        let note = Note::parse("notes/layout.md", text, 100.0);
+        assert_eq!(note.title, "Graph Layout");
+        assert_eq!(note.aliases, vec!["force sim".to_string(), "FDL".to_string()]);
+        assert_eq!(note.first_paragraph(), "First line of the paragraph.");
+        assert!(note.body.starts_with("# Heading"));
+
+        let inline = Note::parse("Daily.md", "---\naliases: [one, 'two']\n---\nBody", 0.0);
+        assert_eq!(inline.title, "Daily");
+        assert_eq!(inline.aliases, vec!["one".to_string(), "two".to_string()]);
+
+        let plain = Note::parse("plain.md", "No frontmatter here", 0.0);
+        assert_eq!((plain.title.as_str(), plain.body.as_str()), ("plain", "No frontmatter here"));
+    }
+
+    #[test]
+    fn test_link_notes() {
+        let notes = vec![
+            Note::parse("layout.md", "---\naliases: [FDL]\n---\nSee session abcdef12 for details.", 50.0),
+            Note::parse("ab.md", "Too short to match", 50.0),
+        ];
+        let messages = vec![
+            message("1", "abcdef12-0000", "start"),
+            message("2", "abcdef12-0000", "the fdl is jittery"),
+            message("3", "other", "tweak the LAYOUT constants"),
+            message("4", "other", "layouts are plural"),
+        ];
+        let data = link_notes(&notes, &messages, "~/vault");
+
+        assert_eq!(data.nodes.len(), 2);
+        assert_eq!(data.nodes[0].id, ids::note("layout.md"));
+        assert_eq!(data.nodes[0].role, Role::Obsidian);
+        assert_eq!(data.nodes[0].content_preview, "See session abcdef12 for details.");
+        assert_eq!(data.nodes[0].timestamp.as_deref(), Some("1970-01-01T00:00:50Z"));
+        let targets: Vec<&str> = data.edges.iter().map(|e| e.target.as_str()).collect();
+        assert_eq!(targets, vec!["1", "2", "3"]);
+        assert!(data.edges.iter().all(|e| e.is_obsidian && e.source == ids::note("layout.md")));
+    }
+
+    #[test]
+    fn test_attach_replaces_notes() {
+        let notes = vec![Note::parse("layout.md", "layout", 0.0)];
+        let linked = link_notes(&notes, &[message("1", "s", "layout"), message("2", "s", "layout")], "");
+        let mut data = GraphData::default();
+        attach(&mut data, &linked);
+        // Messages aren't loaded, so no edges survive
+        assert_eq!((data.nodes.len(), data.edges.len()), (1, 0));
+
+        let mut node = data.nodes[0].clone();
+        node.id = "1".to_string();
+        node.role = Role::User;
+        data.nodes.push(node);
+        attach(&mut data, &linked);
+        assert_eq!((data.nodes.len(), data.edges.len()), (2, 1));
+    }
+}
@@ -0,0 +1,277 @@
+//! Session-level overview: one node per session, linked to sessions from the
+//! same project, sessions that overlap in time, and sessions whose messages
+//! are joined by similarity edges.
+
+use crate::graph::ids;
+use crate::graph::types::{GraphData, GraphEdge, GraphNode, Role};
+use crate::graph::{ForceLayout, GraphState};
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, HashMap};
+
+/// What a session node's size reflects
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+pub enum OverviewSizing {
+    #[default]
+    Messages,
+    Tokens,
+}
+
+impl OverviewSizing {
+    pub fn all() -> &'static [OverviewSizing] {
+        &[OverviewSizing::Messages, OverviewSizing::Tokens]
+    }
+
+    pub fn label(&self) -> &'static str {
+        match self {
+            OverviewSizing::Messages => "Messages",
+            OverviewSizing::Tokens => "Tokens",
+        }
+    }
+}
+
+/// Derived session graph
+#[derive(Debug, Clone, Default)]
+pub struct Overview {
+    /// Node ids are `session:<id>`; tokens and tool use are summed over the session
+    pub data: GraphData,
+    /// Messages per session id
+    pub messages: HashMap<String, usize>,
+}
+
+/// The overview as shown: its own layout, pan and zoom
+pub struct OverviewState {
+    pub graph: GraphState,
+    pub layout: ForceLayout,
+    pub messages: HashMap<String, usize>,
+    pub pan: egui::Vec2,
+    pub zoom: f32,
+}
+
+#[derive(Default)]
+struct SessionAcc<'a> {
+    first: Option<&'a GraphNode>,
+    first_user: Option<(f64, &'a GraphNode)>,
+    start: Option<f64>,
+    end: Option<f64>,
+    messages: usize,
+    output: i32,
+    input: i32,
+    cache_read: i32,
+    cache_creation: i32,
+    tools: bool,
+}
+
+/// Build the session graph from loaded messages. Sessions in a project are
+/// chained in start order; sessions overlapping by at least `min_overlap_secs`
+/// get a temporal edge weighted by the overlap's share of the shorter session;
+/// similarity edges between two sessions' messages sum into one edge,
+/// normalized to the strongest pair. Labels prefer the summary's detected
+/// project, then the first user message.
+pub fn build(
+    nodes: &[GraphNode],
+    edges: &[GraphEdge],
+    detected_projects: &HashMap<String, String>,
+    min_overlap_secs: f64,
+) -> Overview {
+    let mut order: Vec<&str> = Vec::new();
+    let mut sessions: HashMap<&str, SessionAcc> = HashMap::new();
+    let mut session_of: HashMap<&str, &str> = HashMap::new();
+
+    for node in nodes.iter().filter(|n| !matches!(n.role, Role::Topic | Role::Obsidian)) {
+        session_of.insert(&node.id, &node.session_id);
+        let acc = sessions.entry(&node.session_id).or_insert_with(|| {
+            order.push(&node.session_id);
+            SessionAcc::default()
+        });
+        acc.first.get_or_insert(node);
+        acc.messages += 1;
+        acc.output = acc.output.saturating_add(node.output_tokens.unwrap_or(0));
+        acc.input = acc.input.saturating_add(node.input_tokens.unwrap_or(0));
+        acc.cache_read = acc.cache_read.saturating_add(node.cache_read_tokens.unwrap_or(0));
+        acc.cache_creation = acc.cache_creation.saturating_add(node.cache_creation_tokens.unwrap_or(0));
+        acc.tools |= node.has_tool_usage;
+
+        let t = node.timestamp_secs();
+        if let Some(t) = t {
+            acc.start = Some(acc.start.map_or(t, |s| s.min(t)));
+            acc.end = Some(acc.end.map_or(t, |e| e.max(t)));
+        }
+        if node.role == Role::User {
+            let t = t.unwrap_or(f64::INFINITY);
+            if acc.first_user.is_none_or(|(best, _)| t < best) {
+                acc.first_user = Some((t, node));
+            }
+        }
+    }
+
+    let mut overview = Overview::default();
+    for &session_id in &order {
+        let acc = &sessions[session_id];
+        let Some(first) = acc.first else {
+            continue;
+        };
+        let label = detected_projects.get(session_id)
+            .filter(|p| !p.trim().is_empty())
+            .cloned()
+            .or_else(|| acc.first_user.map(|(_, n)| n.content_preview.clone()))
+            .unwrap_or_else(|| first.session_short.clone());
+        overview.messages.insert(session_id.to_string(), acc.messages);
+        overview.data.nodes.push(GraphNode {
+            id: ids::session(session_id),
+            role: Role::User,
+            content_preview: label,
+            full_content: None,
+            session_id: session_id.to_string(),
+            session_short: first.session_short.clone(),
+            project: first.project.clone(),
+            timestamp: acc.start
+                .and_then(|t| chrono::DateTime::from_timestamp(t.floor() as i64, 0))
+                .map(|t| t.format("%Y-%m-%dT%H:%M:%SZ").to_string()),
+            importance_score: None,
+            importance_reason: None,
+            output_tokens: Some(acc.output),
+            input_tokens: Some(acc.input),
+            cache_read_tokens: Some(acc.cache_read),
+            cache_creation_tokens: Some(acc.cache_creation),
+            has_tool_usage: acc.tools,
+        });
+    }
+
+    // Same project: a chain in start order rather than a clique
+    let mut by_project: BTreeMap<&str, Vec<(f64, &str)>> = BTreeMap::new();
+    for node in &overview.data.nodes {
+        if !node.project.is_empty() {
+            let start = sessions[node.session_id.as_str()].start.unwrap_or(f64::INFINITY);
+            by_project.entry(&node.project).or_default().push((start, &node.id));
+        }
+    }
+    let mut edges_out = Vec::new();
+    for members in by_project.values_mut() {
+        members.sort_by(|a, b| a.0.total_cmp(&b.0).then_with(|| a.1.cmp(b.1)));
+        for pair in members.windows(2) {
+            edges_out.push(GraphEdge::conversation(pair[0].1.to_string(), pair[1].1.to_string(), String::new(), None));
+        }
+    }
+
+    // Overlapping time spans
+    let mut spans: Vec<(&str, f64, f64)> = order.iter()
+        .filter_map(|&s| Some((s, sessions[s].start?, sessions[s].end?)))
+        .collect();
+    spans.sort_by(|a, b| a.1.total_cmp(&b.1).then_with(|| a.0.cmp(b.0)));
+    for (i, &(a, a_start, a_end)) in spans.iter().enumerate() {
+        for &(b, b_start, b_end) in &spans[i + 1..] {
+            if b_start >= a_end {
+                break;
+            }
+            let overlap = a_end.min(b_end) - b_start;
+            if overlap > 0.0 && overlap >= min_overlap_secs {
+                let shorter = (a_end - a_start).min(b_end - b_start).max(1.0);
+                edges_out.push(GraphEdge::temporal(ids::session(a), ids::session(b), (overlap / shorter).min(1.0) as f32));
+            }
+        }
+    }
+
+    // Similarity between members, summed per session pair
+    let mut similarity: BTreeMap<(&str, &str), f32> = BTreeMap::new();
+    for edge in edges.iter().filter(|e| e.is_similarity) {
+        let (Some(&a), Some(&b)) = (session_of.get(edge.source.as_str()), session_of.get(edge.target.as_str())) else {
+            continue;
+        };
+        if a != b {
+            *similarity.entry((a.min(b), a.max(b))).or_default() += edge.similarity.unwrap_or(1.0);
+        }
+    }
+    let strongest = similarity.values().copied().fold(f32::EPSILON, f32::max);
+    for ((a, b), weight) in similarity {
+        edges_out.push(GraphEdge::similarity(ids::session(a), ids::session(b), weight / strongest, None));
+    }
+
+    overview.data.edges = edges_out;
+    overview
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn node(id: &str, session: &str, project: &str, role: Role, timestamp: &str, tokens: i32) -> GraphNode {
+        GraphNode {
+            role,
+            content_preview: format!("message {}", id),
+            project: project.to_string(),
+            timestamp: Some(timestamp.to_string()),
+            output_tokens: Some(tokens),
+            ..GraphNode::stub(id, session)
+        }
+    }
+
+    /// Sessions a and b share a project; c overlaps a by 30 minutes; d is alone
+    fn fixture() -> (Vec<GraphNode>, Vec<GraphEdge>) {
+        let nodes = vec![
+            node("a1", "a", "~/alpha", Role::Assistant, "2025-06-01T09:00:00Z", 100),
+            node("a2", "a", "~/alpha", Role::User, "2025-06-01T09:10:00Z", 0),
+            node("a3", "a", "~/alpha", Role::Assistant, "2025-06-01T10:00:00Z", 50),
+            node("b1", "b", "~/alpha", Role::User, "2025-06-02T09:00:00Z", 0),
+            node("c1", "c", "~/beta", Role::User, "2025-06-01T09:30:00Z", 0),
+            node("c2", "c", "~/beta", Role::Assistant, "2025-06-01T11:00:00Z", 10),
+            node("d1", "d", "~/gamma", Role::User, "2025-06-01T09:59:00Z", 0),
+            node("d2", "d", "~/gamma", Role::User, "2025-06-01T10:01:00Z", 0),
+        ];
+        let edges = vec![
+            GraphEdge::conversation("a1".into(), "a2".into(), "a".into(), None),
+            GraphEdge::similarity("a1".into(), "c1".into(), 0.5, None),
+            GraphEdge::similarity("c2".into(), "a3".into(), 0.5, None),
+            GraphEdge::similarity("b1".into(), "d1".into(), 0.25, None),
+            // Within one session: ignored
+            GraphEdge::similarity("a1".into(), "a3".into(), 1.0, None),
+        ];
+        (nodes, edges)
+    }
+
+    #[test]
+    fn test_session_nodes() {
+        let (nodes, edges) = fixture();
+        let detected = HashMap::from([("c".to_string(), "Beta service".to_string())]);
+        let overview = build(&nodes, &edges, &detected, 300.0);
+
+        let node_ids: Vec<&str> = overview.data.nodes.iter().map(|n| n.id.as_str()).collect();
+        assert_eq!(node_ids, vec!["session:a", "session:b", "session:c", "session:d"]);
+        let a = &overview.data.nodes[0];
+        assert_eq!(a.content_preview, "message a2");
+        assert_eq!(a.project, "~/alpha");
+        assert_eq!(a.timestamp.as_deref(), Some("2025-06-01T09:00:00Z"));
+        assert_eq!(a.output_tokens, Some(150));
+        assert_eq!(overview.data.nodes[2].content_preview, "Beta service");
+        assert_eq!(overview.messages["a"], 3);
+        assert_eq!(overview.messages["d"], 2);
+    }
+
+    #[test]
+    fn test_session_edges() {
+        let (nodes, edges) = fixture();
+        let overview = build(&nodes, &edges, &HashMap::new(), 300.0);
+        let find = |pred: fn(&GraphEdge) -> bool| -> Vec<(String, String, Option<f32>)> {
+            overview.data.edges.iter()
+                .filter(|e| pred(e))
+                .map(|e| (e.source.clone(), e.target.clone(), e.similarity))
+                .collect()
+        };
+
+        let project = find(|e| !e.is_temporal && !e.is_similarity);
+        assert_eq!(project, vec![(ids::session("a"), ids::session("b"), None)]);
+
+        // a and c overlap 09:30-10:00 (half of a's hour); d's 2-minute overlap is under the threshold
+        let temporal = find(|e| e.is_temporal);
+        assert_eq!(temporal, vec![(ids::session("a"), ids::session("c"), Some(0.5))]);
+
+        let similarity = find(|e| e.is_similarity);
+        assert_eq!(similarity, vec![
+            (ids::session("a"), ids::session("c"), Some(1.0)),
+            (ids::session("b"), ids::session("d"), Some(0.25)),
+        ]);
+
+        // A lower threshold picks up the short overlaps too
+        let loose = build(&nodes, &edges, &HashMap::new(), 60.0);
+        assert_eq!(loose.data.edges.iter().filter(|e| e.is_temporal).count(), 3);
+    }
+}
@@ -0,0 +1,255 @@
+//! Per-phase frame timing. Cheap enough to stay compiled in: each phase is an
+//! `Instant` pair added into a fixed array, with no allocation per frame.
+
+use std::time::Instant;
+
+/// A frame longer than this logs a warning with the phase breakdown
+pub const SLOW_FRAME_MS: f32 = 50.0;
+
+/// Weight of the newest frame in the rolling averages
+const SMOOTHING: f32 = 0.1;
+/// Frame intervals kept for the sparkline, about five seconds at 60 fps
+pub const FRAME_HISTORY: usize = 300;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Phase {
+    Physics,
+    Edges,
+    Nodes,
+    Hover,
+    Timeline,
+    Panels,
+}
+
+impl Phase {
+    pub const ALL: [Phase; 6] = [Phase::Physics, Phase::Edges, Phase::Nodes, Phase::Hover, Phase::Timeline, Phase::Panels];
+
+    pub fn label(&self) -> &'static str {
+        match self {
+            Phase::Physics => "Physics",
+            Phase::Edges => "Edges",
+            Phase::Nodes => "Nodes",
+            Phase::Hover => "Hover",
+            Phase::Timeline => "Timeline",
+            Phase::Panels => "Panels",
+        }
+    }
+
+    fn index(self) -> usize {
+        self as usize
+    }
+}
+
+#[derive(Debug)]
+pub struct FrameProfiler {
+    frame_start: Instant,
+    /// Milliseconds per phase in the frame being measured
+    current: [f32; Phase::ALL.len()],
+    /// Rolling average milliseconds per phase
+    averages: [f32; Phase::ALL.len()],
+    /// Rolling average of the whole frame
+    frame_avg: f32,
+}
+
+impl Default for FrameProfiler {
+    fn default() -> Self {
+        Self {
+            frame_start: Instant::now(),
+            current: [0.0; Phase::ALL.len()],
+            averages: [0.0; Phase::ALL.len()],
+            frame_avg: 0.0,
+        }
+    }
+}
+
+impl FrameProfiler {
+    /// Start measuring a new frame
+    pub fn begin_frame(&mut self) {
+        self.frame_start = Instant::now();
+        self.current = [0.0; Phase::ALL.len()];
+    }
+
+    /// Add the time since `started` to `phase` (a phase may be recorded several times per frame)
+    pub fn record(&mut self, phase: Phase, started: Instant) {
+        self.add_ms(phase, started.elapsed().as_secs_f32() * 1000.0);
+    }
+
+    fn add_ms(&mut self, phase: Phase, ms: f32) {
+        self.current[phase.index()] += ms;
+    }
+
+    /// Fold the frame into the averages. Logs a warning for slow frames.
+    pub fn end_frame(&mut self) {
+        let total = self.frame_start.elapsed().as_secs_f32() * 1000.0;
+        self.fold(total);
+        if total > SLOW_FRAME_MS {
+            tracing::warn!("Slow frame: {:.1}ms ({})", total, self.breakdown());
+        }
+    }
+
+    fn fold(&mut self, total_ms: f32) {
+        for (avg, ms) in self.averages.iter_mut().zip(self.current) {
+            *avg += (ms - *avg) * SMOOTHING;
+        }
+        self.frame_avg += (total_ms - self.frame_avg) * SMOOTHING;
+    }
+
+    /// Rolling average milliseconds for a phase
+    pub fn average_ms(&self, phase: Phase) -> f32 {
+        self.averages[phase.index()]
+    }
+
+    /// Rolling average milliseconds for whole frames
+    pub fn frame_average_ms(&self) -> f32 {
+        self.frame_avg
+    }
+
+    /// Phase with the highest rolling average (None before anything was measured)
+    pub fn slowest(&self) -> Option<Phase> {
+        Phase::ALL.into_iter()
+            .filter(|p| self.average_ms(*p) > 0.0)
+            .max_by(|a, b| self.average_ms(*a).total_cmp(&self.average_ms(*b)))
+    }
+
+    /// Slowest phase of the frame just measured, with its milliseconds
+    pub fn frame_slowest(&self) -> Option<(Phase, f32)> {
+        Phase::ALL.into_iter()
+            .map(|p| (p, self.current[p.index()]))
+            .filter(|(_, ms)| *ms > 0.0)
+            .max_by(|a, b| a.1.total_cmp(&b.1))
+    }
+
+    /// "Physics 12.0ms, Edges 3.1ms, ..." for the frame just measured
+    fn breakdown(&self) -> String {
+        Phase::ALL.iter()
+            .map(|p| format!("{} {:.1}ms", p.label(), self.current[p.index()]))
+            .collect::<Vec<_>>()
+            .join(", ")
+    }
+}
+
+/// Ring buffer of recent frame intervals in milliseconds. Pushing overwrites
+/// the oldest sample in place, so keeping it up costs nothing per frame.
+#[derive(Debug, Clone)]
+pub struct FrameTimes {
+    samples: [f32; FRAME_HISTORY],
+    /// Slot the next sample goes into
+    next: usize,
+    len: usize,
+}
+
+impl Default for FrameTimes {
+    fn default() -> Self {
+        Self { samples: [0.0; FRAME_HISTORY], next: 0, len: 0 }
+    }
+}
+
+impl FrameTimes {
+    pub fn push(&mut self, ms: f32) {
+        self.samples[self.next] = ms;
+        self.next = (self.next + 1) % FRAME_HISTORY;
+        self.len = (self.len + 1).min(FRAME_HISTORY);
+    }
+
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    /// Samples oldest first
+    pub fn iter(&self) -> impl Iterator<Item = f32> + '_ {
+        let start = (self.next + FRAME_HISTORY - self.len) % FRAME_HISTORY;
+        (0..self.len).map(move |i| self.samples[(start + i) % FRAME_HISTORY])
+    }
+
+    /// Sample `i`, counting from the oldest
+    pub fn get(&self, i: usize) -> Option<f32> {
+        (i < self.len).then(|| self.samples[(self.next + FRAME_HISTORY - self.len + i) % FRAME_HISTORY])
+    }
+
+    /// Mean of the newest `n` samples
+    pub fn recent_average(&self, n: usize) -> Option<f32> {
+        let n = n.min(self.len);
+        (n > 0).then(|| self.iter().skip(self.len - n).sum::<f32>() / n as f32)
+    }
+
+    pub fn worst(&self) -> Option<f32> {
+        self.iter().reduce(f32::max)
+    }
+
+    /// Nearest-rank percentile (`p` in 0..=1), sorted in a stack copy
+    pub fn percentile(&self, p: f32) -> Option<f32> {
+        if self.len == 0 {
+            return None;
+        }
+        let mut sorted = [0.0; FRAME_HISTORY];
+        for (slot, ms) in sorted.iter_mut().zip(self.iter()) {
+            *slot = ms;
+        }
+        let sorted = &mut sorted[..self.len];
+        sorted.sort_unstable_by(f32::total_cmp);
+        let rank = ((p.clamp(0.0, 1.0) * self.len as f32).ceil() as usize).clamp(1, self.len);
+        Some(sorted[rank - 1])
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_phase_accumulation_and_slowest() {
+        let mut profiler = FrameProfiler::default();
+        assert_eq!(profiler.slowest(), None);
+
+        profiler.begin_frame();
+        profiler.add_ms(Phase::Physics, 4.0);
+        profiler.add_ms(Phase::Edges, 10.0);
+        profiler.add_ms(Phase::Physics, 2.0);
+        profiler.fold(20.0);
+
+        assert!((profiler.average_ms(Phase::Physics) - 0.6).abs() < 1e-6);
+        assert!((profiler.average_ms(Phase::Edges) - 1.0).abs() < 1e-6);
+        assert_eq!(profiler.average_ms(Phase::Nodes), 0.0);
+        assert!((profiler.frame_average_ms() - 2.0).abs() < 1e-6);
+        assert_eq!(profiler.slowest(), Some(Phase::Edges));
+        assert_eq!(profiler.frame_slowest(), Some((Phase::Edges, 10.0)));
+        assert_eq!(
+            profiler.breakdown(),
+            "Physics 6.0ms, Edges 10.0ms, Nodes 0.0ms, Hover 0.0ms, Timeline 0.0ms, Panels 0.0ms"
+        );
+
+        // A new frame starts from zero but the averages carry over
+        profiler.begin_frame();
+        profiler.fold(0.0);
+        assert!((profiler.average_ms(Phase::Edges) - 0.9).abs() < 1e-6);
+    }
+
+    #[test]
+    fn test_frame_times_wrap_around() {
+        let mut times = FrameTimes::default();
+        assert_eq!((times.worst(), times.percentile(0.95), times.recent_average(60)), (None, None, None));
+
+        for i in 0..FRAME_HISTORY + 5 {
+            times.push(i as f32);
+        }
+        assert_eq!(times.len(), FRAME_HISTORY);
+        // The five oldest were overwritten
+        assert_eq!(times.get(0), Some(5.0));
+        assert_eq!(times.get(FRAME_HISTORY - 1), Some((FRAME_HISTORY + 4) as f32));
+        assert_eq!(times.get(FRAME_HISTORY), None);
+        assert!(times.iter().zip(times.iter().skip(1)).all(|(a, b)| b == a + 1.0));
+        assert_eq!(times.worst(), Some((FRAME_HISTORY + 4) as f32));
+        assert_eq!(times.recent_average(2), Some((FRAME_HISTORY + 3) as f32 + 0.5));
+    }
+
+    #[test]
+    fn test_frame_time_percentile() {
+        let mut times = FrameTimes::default();
+        for ms in [16.0, 17.0, 16.0, 90.0, 15.0, 16.0, 16.0, 17.0, 16.0, 16.0] {
+            times.push(ms);
+        }
+        assert_eq!(times.percentile(0.5), Some(16.0));
+        assert_eq!(times.percentile(0.95), Some(90.0));
+        assert_eq!(times.percentile(0.0), Some(15.0));
+    }
+}
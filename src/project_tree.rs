@@ -3,7 +3,7 @@
 //! Converts flat project paths like `~/Documents/GitHub/foo` into a tree
 //! structure with shared-prefix grouping and single-child collapsing.
 
-use std::collections::HashSet;
+use std::collections::{HashMap, HashSet};
 
 use egui::{Color32, Pos2, Rect, Response, Sense, Ui, Vec2};
 
@@ -60,6 +60,17 @@ impl ProjectTreeNode {
         out
     }
 
+    /// Sum the per-project counts over every leaf under this node.
+    pub fn count_under(&self, counts: &HashMap<String, usize>) -> usize {
+        self.leaf_paths().iter().filter_map(|p| counts.get(p)).sum()
+    }
+
+    /// Whether any project under this node contains `needle` (already lowercased).
+    /// Leaf paths carry every ancestor segment, so matches inside merged segments count.
+    pub fn matches(&self, needle: &str) -> bool {
+        needle.is_empty() || self.leaf_paths().iter().any(|p| p.to_lowercase().contains(needle))
+    }
+
     /// Determine the check state of this node given the set of selected projects.
     pub fn check_state(&self, selected: &HashSet<String>) -> CheckState {
         let leaves = self.leaf_paths();
@@ -149,6 +160,19 @@ fn collect_leaves(node: &ProjectTreeNode, out: &mut Vec<String>) {
     }
 }
 
+/// Byte range of the first case-insensitive occurrence of `needle` (lowercased) in `name`
+pub fn match_range(name: &str, needle: &str) -> Option<std::ops::Range<usize>> {
+    if needle.is_empty() {
+        return None;
+    }
+    let lower = name.to_lowercase();
+    // Only byte-aligned when lowercasing preserved the length
+    if lower.len() != name.len() {
+        return None;
+    }
+    lower.find(needle).map(|start| start..start + needle.len())
+}
+
 // ---------------------------------------------------------------------------
 // Tri-state checkbox widget
 // ---------------------------------------------------------------------------
@@ -167,7 +191,7 @@ pub fn tri_state_checkbox(ui: &mut Ui, state: CheckState) -> Option<bool> {
         match state {
             CheckState::Checked => {
                 // Filled green square with a white checkmark
-                painter.rect_filled(rect, rounding, theme::accent::GREEN);
+                painter.rect_filled(rect, rounding, theme::current(ui.ctx()).accent.green);
                 // Checkmark path
                 let cx = rect.center().x;
                 let cy = rect.center().y;
@@ -188,7 +212,7 @@ pub fn tri_state_checkbox(ui: &mut Ui, state: CheckState) -> Option<bool> {
             }
             CheckState::Mixed => {
                 // Orange filled square with a white dash
-                painter.rect_filled(rect, rounding, theme::accent::ORANGE);
+                painter.rect_filled(rect, rounding, theme::current(ui.ctx()).accent.orange);
                 let y = rect.center().y;
                 let inset = rect.width() * 0.25;
                 painter.line_segment(
@@ -213,3 +237,28 @@ pub fn tri_state_checkbox(ui: &mut Ui, state: CheckState) -> Option<bool> {
         None
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_search_matches_inside_collapsed_segments() {
+        let tree = ProjectTreeNode::build(&[
+            "~/Documents/GitHub/alpha".to_string(),
+            "~/Documents/GitHub/beta".to_string(),
+            "~/scratch".to_string(),
+        ]);
+        let github = tree
+            .children
+            .iter()
+            .find(|c| c.name.contains("GitHub"))
+            .expect("merged Documents/GitHub node");
+        assert_eq!(github.name, "Documents/GitHub");
+        assert!(github.matches("docu"));
+        assert!(github.matches("beta"));
+        assert!(!github.matches("scratch"));
+        assert_eq!(match_range(&github.name, "docu"), Some(0..4));
+        assert_eq!(match_range("alpha", "zzz"), None);
+    }
+}
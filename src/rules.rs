@@ -0,0 +1,391 @@
+//! Local evaluation of rule-based filters against loaded nodes.
+//!
+//! A rule's query text is either `regex:<pattern>` (matched against message
+//! content) or whitespace-separated terms. Field predicates must all hold;
+//! plain keywords match if any of them appears in the content (case-insensitive).
+//!
+//! Predicates: `role:user`, `project:~/Documents/app` (prefix, `~` expands),
+//! `tokens>5000`, `importance>=0.5`, `has_tool:true`, plus the backend's
+//! `has_tools`, `long` and `short` shorthands. Double quotes group a phrase.
+
+use crate::graph::types::{GraphNode, Role};
+use regex::Regex;
+use std::collections::HashSet;
+use std::fmt;
+
+/// Fields accepted before `:` or a comparison operator, for error messages
+const FIELDS: &str = "role, project, tokens, importance, has_tool";
+
+/// Content length thresholds matching the backend's `long`/`short` rules
+const LONG_CONTENT_CHARS: usize = 500;
+const SHORT_CONTENT_CHARS: usize = 100;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Cmp {
+    Lt,
+    Le,
+    Eq,
+    Ge,
+    Gt,
+}
+
+impl Cmp {
+    fn symbol(&self) -> &'static str {
+        match self {
+            Cmp::Lt => "<",
+            Cmp::Le => "<=",
+            Cmp::Eq => "=",
+            Cmp::Ge => ">=",
+            Cmp::Gt => ">",
+        }
+    }
+
+    fn holds(&self, lhs: f64, rhs: f64) -> bool {
+        match self {
+            Cmp::Lt => lhs < rhs,
+            Cmp::Le => lhs <= rhs,
+            Cmp::Eq => lhs == rhs,
+            Cmp::Ge => lhs >= rhs,
+            Cmp::Gt => lhs > rhs,
+        }
+    }
+}
+
+/// A single term of a rule
+#[derive(Debug, Clone, PartialEq)]
+pub enum Predicate {
+    Role(Role),
+    /// Project prefix (with `~` already expanded)
+    Project(String),
+    /// Total of input, output and cache tokens
+    Tokens(Cmp, i64),
+    Importance(Cmp, f32),
+    HasTool(bool),
+    Long,
+    Short,
+}
+
+impl fmt::Display for Predicate {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Predicate::Role(role) => write!(f, "role:{}", role_name(role)),
+            Predicate::Project(prefix) => write!(f, "project:{}", quote(prefix)),
+            Predicate::Tokens(cmp, n) => write!(f, "tokens{}{}", cmp.symbol(), n),
+            Predicate::Importance(cmp, v) => write!(f, "importance{}{}", cmp.symbol(), v),
+            Predicate::HasTool(b) => write!(f, "has_tool:{}", b),
+            Predicate::Long => write!(f, "long"),
+            Predicate::Short => write!(f, "short"),
+        }
+    }
+}
+
+/// A parsed rule
+#[derive(Debug, Clone)]
+pub enum Rule {
+    Regex(Regex),
+    Terms {
+        predicates: Vec<Predicate>,
+        /// Lowercased keywords; any one matching is enough
+        keywords: Vec<String>,
+    },
+}
+
+impl fmt::Display for Rule {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Rule::Regex(re) => write!(f, "regex:{}", re.as_str()),
+            Rule::Terms { predicates, keywords } => {
+                let terms: Vec<String> = predicates
+                    .iter()
+                    .map(|p| p.to_string())
+                    .chain(keywords.iter().map(|k| quote(k)))
+                    .collect();
+                write!(f, "{}", terms.join(" "))
+            }
+        }
+    }
+}
+
+impl Rule {
+    /// Parse a rule's query text, with errors phrased for display next to the filter
+    pub fn parse(text: &str) -> Result<Self, String> {
+        let text = text.trim();
+        if let Some(pattern) = text.strip_prefix("regex:") {
+            return Regex::new(pattern.trim())
+                .map(Rule::Regex)
+                .map_err(|e| format!("invalid regex: {}", e));
+        }
+
+        let mut predicates = Vec::new();
+        let mut keywords = Vec::new();
+        for (term, quoted) in split_terms(text)? {
+            if quoted {
+                keywords.push(term.to_lowercase());
+                continue;
+            }
+            match parse_predicate(&term)? {
+                Some(predicate) => predicates.push(predicate),
+                None => keywords.push(term.to_lowercase()),
+            }
+        }
+        if predicates.is_empty() && keywords.is_empty() {
+            return Err("rule is empty".to_string());
+        }
+        Ok(Rule::Terms { predicates, keywords })
+    }
+
+    /// Whether a node satisfies this rule
+    pub fn matches(&self, node: &GraphNode) -> bool {
+        let content = node.full_content.as_deref().unwrap_or(&node.content_preview);
+        match self {
+            Rule::Regex(re) => re.is_match(content),
+            Rule::Terms { predicates, keywords } => {
+                if !predicates.iter().all(|p| predicate_matches(p, node, content)) {
+                    return false;
+                }
+                if keywords.is_empty() {
+                    return true;
+                }
+                let content = content.to_lowercase();
+                keywords.iter().any(|k| content.contains(k.as_str()))
+            }
+        }
+    }
+
+    /// IDs of the nodes matching this rule
+    pub fn evaluate(&self, nodes: &[GraphNode]) -> HashSet<String> {
+        nodes
+            .iter()
+            .filter(|n| self.matches(n))
+            .map(|n| n.id.clone())
+            .collect()
+    }
+}
+
+fn predicate_matches(predicate: &Predicate, node: &GraphNode, content: &str) -> bool {
+    match predicate {
+        Predicate::Role(role) => node.role == *role,
+        Predicate::Project(prefix) => node.project.starts_with(prefix.as_str()),
+        Predicate::Tokens(cmp, n) => {
+            let total: i64 = [node.input_tokens, node.output_tokens, node.cache_read_tokens, node.cache_creation_tokens]
+                .iter()
+                .map(|t| t.unwrap_or(0) as i64)
+                .sum();
+            cmp.holds(total as f64, *n as f64)
+        }
+        Predicate::Importance(cmp, v) => node.importance_score.is_some_and(|s| cmp.holds(s as f64, *v as f64)),
+        Predicate::HasTool(b) => node.has_tool_usage == *b,
+        Predicate::Long => content.chars().count() > LONG_CONTENT_CHARS,
+        Predicate::Short => content.chars().count() < SHORT_CONTENT_CHARS,
+    }
+}
+
+/// Split on whitespace, keeping double-quoted phrases together.
+/// Returns (term, was_quoted) pairs.
+fn split_terms(text: &str) -> Result<Vec<(String, bool)>, String> {
+    let mut terms = Vec::new();
+    let mut chars = text.chars().peekable();
+    while let Some(&c) = chars.peek() {
+        if c.is_whitespace() {
+            chars.next();
+        } else if c == '"' {
+            chars.next();
+            let phrase: String = chars.by_ref().take_while(|c| *c != '"').collect();
+            if !text.matches('"').count().is_multiple_of(2) {
+                return Err(format!("unclosed quote before '{}'", phrase));
+            }
+            if !phrase.trim().is_empty() {
+                terms.push((phrase, true));
+            }
+        } else {
+            let mut term = String::new();
+            while let Some(&c) = chars.peek() {
+                if c.is_whitespace() {
+                    break;
+                }
+                term.push(c);
+                chars.next();
+                // `project:"My Project"` keeps its quoted value in one term
+                if c == ':' && chars.peek() == Some(&'"') {
+                    chars.next();
+                    let value: String = chars.by_ref().take_while(|c| *c != '"').collect();
+                    term.push_str(&value);
+                }
+            }
+            terms.push((term, false));
+        }
+    }
+    Ok(terms)
+}
+
+/// Parse a field predicate. Returns Ok(None) for plain keywords.
+fn parse_predicate(term: &str) -> Result<Option<Predicate>, String> {
+    match term.to_lowercase().as_str() {
+        "has_tools" => return Ok(Some(Predicate::HasTool(true))),
+        "long" => return Ok(Some(Predicate::Long)),
+        "short" => return Ok(Some(Predicate::Short)),
+        _ => {}
+    }
+
+    // Comparison: field followed by an operator
+    if let Some(pos) = term.find(['<', '>', '=']) {
+        if pos > 0 && !term[..pos].contains(':') {
+            let field = term[..pos].to_lowercase();
+            let rest = &term[pos..];
+            let (cmp, value) = [("<=", Cmp::Le), (">=", Cmp::Ge), ("<", Cmp::Lt), (">", Cmp::Gt), ("=", Cmp::Eq)]
+                .iter()
+                .find_map(|(sym, cmp)| rest.strip_prefix(sym).map(|v| (*cmp, v)))
+                .unwrap_or((Cmp::Eq, rest));
+            return match field.as_str() {
+                "tokens" => value
+                    .replace('_', "")
+                    .parse::<i64>()
+                    .map(|n| Some(Predicate::Tokens(cmp, n)))
+                    .map_err(|_| format!("in '{}': expected a whole number of tokens after '{}'", term, cmp.symbol())),
+                "importance" => value
+                    .parse::<f32>()
+                    .ok()
+                    .filter(|v| (0.0..=1.0).contains(v))
+                    .map(|v| Some(Predicate::Importance(cmp, v)))
+                    .ok_or_else(|| format!("in '{}': importance must be a number from 0 to 1", term)),
+                _ => Err(format!("in '{}': '{}' can't be compared (numeric fields: tokens, importance)", term, field)),
+            };
+        }
+    }
+
+    let Some((field, value)) = term.split_once(':') else {
+        return Ok(None);
+    };
+    if value.is_empty() {
+        return Err(format!("in '{}': missing a value after ':'", term));
+    }
+    match field.to_lowercase().as_str() {
+        "role" => Role::all()
+            .iter()
+            .find(|r| role_name(r) == value.to_lowercase())
+            .map(|r| Some(Predicate::Role(r.clone())))
+            .ok_or_else(|| format!("in '{}': unknown role (expected user, assistant, agent, obsidian, topic)", term)),
+        "project" => Ok(Some(Predicate::Project(expand_home(value)))),
+        "has_tool" => match value.to_lowercase().as_str() {
+            "true" | "yes" => Ok(Some(Predicate::HasTool(true))),
+            "false" | "no" => Ok(Some(Predicate::HasTool(false))),
+            _ => Err(format!("in '{}': has_tool must be true or false", term)),
+        },
+        "tool" => Err(format!("'{}' needs per-tool data and is evaluated by the backend", term)),
+        "regex" => Err("regex: must start the rule and cover all of it".to_string()),
+        _ => Err(format!("in '{}': unknown field '{}' (expected {})", term, field, FIELDS)),
+    }
+}
+
+/// Lowercase role name used in rule text
+fn role_name(role: &Role) -> &'static str {
+    match role {
+        Role::User => "user",
+        Role::Assistant => "assistant",
+        Role::Agent => "agent",
+        Role::Obsidian => "obsidian",
+        Role::Topic => "topic",
+    }
+}
+
+fn quote(value: &str) -> String {
+    if value.contains(char::is_whitespace) {
+        format!("\"{}\"", value)
+    } else {
+        value.to_string()
+    }
+}
+
+/// Expand a leading `~` to the home directory
+fn expand_home(path: &str) -> String {
+    match (path.strip_prefix('~'), dirs::home_dir()) {
+        (Some(rest), Some(home)) => format!("{}{}", home.to_string_lossy(), rest),
+        _ => path.to_string(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rand::rngs::StdRng;
+    use rand::{Rng, SeedableRng};
+
+    fn node(role: Role, project: &str, content: &str, tokens: i32, tool: bool) -> GraphNode {
+        GraphNode {
+            role,
+            content_preview: content.to_string(),
+            project: project.to_string(),
+            importance_score: Some(0.6),
+            output_tokens: Some(tokens),
+            has_tool_usage: tool,
+            ..GraphNode::stub(content, "s1")
+        }
+    }
+
+    #[test]
+    fn test_rules_match_nodes() {
+        let nodes = vec![
+            node(Role::User, "/work/app", "Fix the Login bug", 10, false),
+            node(Role::Assistant, "/work/app", "Ran the tests", 9000, true),
+            node(Role::Assistant, "/play/game", "login screen drafted", 20, false),
+        ];
+        let ids = |text: &str| {
+            let mut ids: Vec<String> = Rule::parse(text).unwrap().evaluate(&nodes).into_iter().collect();
+            ids.sort();
+            ids
+        };
+        assert_eq!(ids("login"), vec!["Fix the Login bug", "login screen drafted"]);
+        assert_eq!(ids("role:assistant project:/work"), vec!["Ran the tests"]);
+        assert_eq!(ids("tokens>5000 has_tool:true"), vec!["Ran the tests"]);
+        assert_eq!(ids("has_tools"), vec!["Ran the tests"]);
+        assert_eq!(ids("importance>=0.5 \"screen drafted\""), vec!["login screen drafted"]);
+        assert_eq!(ids(r"regex:^(Fix|Ran)\b"), vec!["Fix the Login bug", "Ran the tests"]);
+    }
+
+    #[test]
+    fn test_parse_errors_name_the_term() {
+        let err = |text: &str| Rule::parse(text).unwrap_err();
+        assert!(err("rol:user").contains("unknown field 'rol'"));
+        assert!(err("role:robot").contains("unknown role"));
+        assert!(err("tokens>lots").contains("whole number"));
+        assert!(err("importance>2").contains("from 0 to 1"));
+        assert!(err("project:").contains("missing a value"));
+        assert!(err("tool:Bash").contains("backend"));
+        assert!(err("regex:(").contains("invalid regex"));
+        assert!(err("\"open").contains("unclosed quote"));
+        assert!(err("   ").contains("empty"));
+    }
+
+    /// Property: any generated rule prints to text that parses back to the same rule
+    #[test]
+    fn test_predicate_grammar_round_trips() {
+        let mut rng = StdRng::seed_from_u64(4920);
+        let cmps = [Cmp::Lt, Cmp::Le, Cmp::Eq, Cmp::Ge, Cmp::Gt];
+        let words = ["deploy", "login", "cache miss", "v2"];
+        for _ in 0..500 {
+            let mut predicates = Vec::new();
+            for _ in 0..rng.gen_range(0..4) {
+                let cmp = cmps[rng.gen_range(0..cmps.len())];
+                predicates.push(match rng.gen_range(0..7) {
+                    0 => Predicate::Role(Role::all()[rng.gen_range(0..Role::all().len())].clone()),
+                    1 => Predicate::Project(format!("/src/p{}", rng.gen_range(0..100))),
+                    2 => Predicate::Tokens(cmp, rng.gen_range(0..10_000_000)),
+                    3 => Predicate::Importance(cmp, rng.gen_range(0..=100) as f32 / 100.0),
+                    4 => Predicate::HasTool(rng.gen()),
+                    5 => Predicate::Long,
+                    _ => Predicate::Short,
+                });
+            }
+            let keywords: Vec<String> = (0..rng.gen_range(0..3))
+                .map(|_| words[rng.gen_range(0..words.len())].to_string())
+                .collect();
+            if predicates.is_empty() && keywords.is_empty() {
+                continue;
+            }
+            let rule = Rule::Terms { predicates, keywords };
+            let text = rule.to_string();
+            let parsed = Rule::parse(&text).unwrap_or_else(|e| panic!("{:?} failed to parse: {}", text, e));
+            assert_eq!(parsed.to_string(), text, "round trip of {:?}", rule);
+        }
+    }
+}
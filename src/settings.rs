@@ -1,9 +1,16 @@
 //! Persistent settings for the dashboard app.
 
-use crate::graph::types::{ColorMode, FilterMode};
+use crate::graph::scene::EdgeStyles;
+use crate::graph::types::{ColorMode, FilterMode, HueAssignment, HueOverrides, Role, SemanticFilterMode};
+use crate::theme::{RoleColors, Theme};
+use egui::Color32;
+use crate::cost::TokenRates;
+use crate::overview::OverviewSizing;
+use crate::tooltip::TooltipConfig;
 use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
-use std::path::PathBuf;
+use std::io::Write;
+use std::path::{Path, PathBuf};
 
 /// Sidebar tab selection
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, Default)]
@@ -15,6 +22,163 @@ pub enum SidebarTab {
     Filters,
 }
 
+/// User-chosen role colors; `None` keeps the active theme's color
+#[derive(Debug, Clone, Copy, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct RoleColorOverrides {
+    pub user: Option<Color32>,
+    pub assistant: Option<Color32>,
+    pub agent: Option<Color32>,
+    pub obsidian: Option<Color32>,
+    pub topic: Option<Color32>,
+}
+
+impl RoleColorOverrides {
+    /// Override slot for a role
+    pub fn get_mut(&mut self, role: &Role) -> &mut Option<Color32> {
+        match role {
+            Role::User => &mut self.user,
+            Role::Assistant => &mut self.assistant,
+            Role::Agent => &mut self.agent,
+            Role::Obsidian => &mut self.obsidian,
+            Role::Topic => &mut self.topic,
+        }
+    }
+
+    /// Apply the overrides on top of the theme's role colors
+    pub fn apply(&self, colors: &mut RoleColors) {
+        let slots = [
+            (self.user, &mut colors.user),
+            (self.assistant, &mut colors.assistant),
+            (self.agent, &mut colors.agent),
+            (self.obsidian, &mut colors.obsidian),
+            (self.topic, &mut colors.topic),
+        ];
+        for (color, slot) in slots {
+            if let Some(color) = color {
+                *slot = color;
+            }
+        }
+    }
+}
+
+/// Modifier key that must be held for graph hover to scrub the timeline
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, Default)]
+pub enum HoverScrubModifier {
+    #[default]
+    Alt,
+    Shift,
+}
+
+impl HoverScrubModifier {
+    /// Display label for the modifier
+    pub fn label(&self) -> &'static str {
+        match self {
+            HoverScrubModifier::Alt => "Alt",
+            HoverScrubModifier::Shift => "Shift",
+        }
+    }
+
+    /// All modifiers for UI iteration
+    pub fn all() -> &'static [HoverScrubModifier] {
+        &[HoverScrubModifier::Alt, HoverScrubModifier::Shift]
+    }
+}
+
+/// Which node the sidebar's node section describes
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, Default)]
+pub enum NodeInfoSource {
+    /// The node nearest the timeline scrubber
+    #[default]
+    Scrubber,
+    /// The selected node
+    Selection,
+}
+
+impl NodeInfoSource {
+    pub fn label(&self) -> &'static str {
+        match self {
+            NodeInfoSource::Scrubber => "Scrubber",
+            NodeInfoSource::Selection => "Selection",
+        }
+    }
+
+    pub fn all() -> &'static [NodeInfoSource] {
+        &[NodeInfoSource::Scrubber, NodeInfoSource::Selection]
+    }
+}
+
+/// Which side of the conversation the graph shows
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, Default)]
+pub enum RoleFilter {
+    #[default]
+    All,
+    /// Only user messages
+    Users,
+    /// Only assistant and subagent messages
+    Claude,
+}
+
+impl RoleFilter {
+    pub fn label(&self) -> &'static str {
+        match self {
+            RoleFilter::All => "All",
+            RoleFilter::Users => "Users",
+            RoleFilter::Claude => "Claude",
+        }
+    }
+
+    pub fn all() -> &'static [RoleFilter] {
+        &[RoleFilter::All, RoleFilter::Users, RoleFilter::Claude]
+    }
+
+    /// Whether a node with this role passes the filter (topics and notes only pass All)
+    pub fn admits(&self, role: &Role) -> bool {
+        match self {
+            RoleFilter::All => true,
+            RoleFilter::Users => *role == Role::User,
+            RoleFilter::Claude => matches!(role, Role::Assistant | Role::Agent),
+        }
+    }
+}
+
+/// A toggle offered in the pill bar above the graph
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum FilterPill {
+    UsersOnly,
+    ClaudeOnly,
+    ToolCalls,
+    HighImportance,
+    /// One pill per scored semantic filter
+    SemanticFilters,
+}
+
+impl FilterPill {
+    pub fn label(&self) -> &'static str {
+        match self {
+            FilterPill::UsersOnly => "Users only",
+            FilterPill::ClaudeOnly => "Claude only",
+            FilterPill::ToolCalls => "Tool calls",
+            FilterPill::HighImportance => "High importance",
+            FilterPill::SemanticFilters => "Semantic filters",
+        }
+    }
+
+    pub fn all() -> &'static [FilterPill] {
+        &[
+            FilterPill::UsersOnly,
+            FilterPill::ClaudeOnly,
+            FilterPill::ToolCalls,
+            FilterPill::HighImportance,
+            FilterPill::SemanticFilters,
+        ]
+    }
+}
+
+fn default_filter_pills() -> Vec<FilterPill> {
+    FilterPill::all().to_vec()
+}
+
 /// Preset configurations for node sizing formula
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, Default)]
 pub enum SizingPreset {
@@ -64,10 +228,205 @@ impl SizingPreset {
     }
 }
 
+/// Graph view mode. Each mode keeps its own physics and sizing parameters.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize, Default)]
+pub enum ViewMode {
+    #[default]
+    ForceDirected,
+    Timeline,
+}
+
+impl ViewMode {
+    /// Get display label for the view mode
+    pub fn label(&self) -> &'static str {
+        match self {
+            ViewMode::ForceDirected => "Force",
+            ViewMode::Timeline => "Timeline",
+        }
+    }
+
+    /// All view modes for UI iteration
+    pub fn all() -> &'static [ViewMode] {
+        &[ViewMode::ForceDirected, ViewMode::Timeline]
+    }
+}
+
+/// Camera, selection and timeline window from the previous session
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct LastView {
+    pub pan: [f32; 2],
+    pub zoom: f32,
+    pub selected_node: Option<String>,
+    /// Timeline window as absolute epoch seconds (None = timeline disabled)
+    pub timeline_start: Option<f64>,
+    pub timeline_end: Option<f64>,
+}
+
+impl Default for LastView {
+    fn default() -> Self {
+        Self {
+            pan: [0.0, 0.0],
+            zoom: 1.0,
+            selected_node: None,
+            timeline_start: None,
+            timeline_end: None,
+        }
+    }
+}
+
+/// A named data selection (time range, projects, session) paired with a
+/// preset; clicking it in the sidebar applies the preset and reloads
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct SavedView {
+    pub name: String,
+    pub time_range_hours: f32,
+    /// Selected project paths, sorted; empty selects every project
+    #[serde(default)]
+    pub projects: Vec<String>,
+    #[serde(default)]
+    pub session: Option<String>,
+    #[serde(default)]
+    pub preset: Option<String>,
+}
+
+impl SavedView {
+    /// Same selection and preset as `other`, ignoring the name. Ranges within
+    /// half an hour count as equal, since the slider snaps to coarse steps;
+    /// a view without a preset accepts any.
+    pub fn matches(&self, other: &SavedView) -> bool {
+        (self.time_range_hours - other.time_range_hours).abs() < 0.5
+            && self.projects == other.projects
+            && self.session == other.session
+            && (self.preset.is_none() || self.preset == other.preset)
+    }
+}
+
+/// Physics and sizing parameters stored per view mode, swapped into the
+/// flat `Settings` fields when the active view changes
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ViewSettings {
+    // Physics
+    pub physics_enabled: bool,
+    pub repulsion: f32,
+    pub attraction: f32,
+    pub centering: f32,
+    pub size_physics_weight: f32,
+    pub temporal_strength: f32,
+    pub temporal_attraction_enabled: bool,
+    pub directed_stiffness: f32,
+    pub recency_centering: f32,
+    pub momentum: f32,
+
+    // Node Sizing
+    pub sizing_preset: SizingPreset,
+    pub w_importance: f32,
+    pub w_tokens: f32,
+    pub w_time: f32,
+    pub max_node_multiplier: f32,
+
+    /// Preset applied automatically when switching into this view
+    #[serde(default)]
+    pub default_preset: Option<String>,
+}
+
+impl ViewSettings {
+    /// Tuned defaults for each view mode
+    pub fn defaults_for(mode: ViewMode) -> Self {
+        match mode {
+            ViewMode::ForceDirected => {
+                let (w_importance, w_tokens, w_time) = SizingPreset::Balanced.weights();
+                Self {
+                    physics_enabled: true,
+                    repulsion: 10000.0,
+                    attraction: 0.1,
+                    centering: 0.0001,
+                    size_physics_weight: 0.0,
+                    temporal_strength: 0.5,
+                    temporal_attraction_enabled: true,
+                    directed_stiffness: 1.0,
+                    recency_centering: 0.0,
+                    momentum: 0.0,
+                    sizing_preset: SizingPreset::Balanced,
+                    w_importance,
+                    w_tokens,
+                    w_time,
+                    max_node_multiplier: 10.0,
+                    default_preset: None,
+                }
+            }
+            ViewMode::Timeline => {
+                // Time drives the x axis, so temporal springs and recency pull are off
+                let (w_importance, w_tokens, w_time) = SizingPreset::ImportanceFocused.weights();
+                Self {
+                    physics_enabled: true,
+                    repulsion: 2000.0,
+                    attraction: 0.05,
+                    centering: 0.0,
+                    size_physics_weight: 0.0,
+                    temporal_strength: 0.0,
+                    temporal_attraction_enabled: false,
+                    directed_stiffness: 0.0,
+                    recency_centering: 0.0,
+                    momentum: 0.0,
+                    sizing_preset: SizingPreset::ImportanceFocused,
+                    w_importance,
+                    w_tokens,
+                    w_time,
+                    max_node_multiplier: 5.0,
+                    default_preset: None,
+                }
+            }
+        }
+    }
+
+    /// Copy the flat settings fields into this view (keeps the default preset)
+    fn capture(&mut self, settings: &Settings) {
+        self.physics_enabled = settings.physics_enabled;
+        self.repulsion = settings.repulsion;
+        self.attraction = settings.attraction;
+        self.centering = settings.centering;
+        self.size_physics_weight = settings.size_physics_weight;
+        self.temporal_strength = settings.temporal_strength;
+        self.temporal_attraction_enabled = settings.temporal_attraction_enabled;
+        self.directed_stiffness = settings.directed_stiffness;
+        self.recency_centering = settings.recency_centering;
+        self.momentum = settings.momentum;
+        self.sizing_preset = settings.sizing_preset;
+        self.w_importance = settings.w_importance;
+        self.w_tokens = settings.w_tokens;
+        self.w_time = settings.w_time;
+        self.max_node_multiplier = settings.max_node_multiplier;
+    }
+
+    /// Write this view's values into the flat settings fields
+    fn restore(&self, settings: &mut Settings) {
+        settings.physics_enabled = self.physics_enabled;
+        settings.repulsion = self.repulsion;
+        settings.attraction = self.attraction;
+        settings.centering = self.centering;
+        settings.size_physics_weight = self.size_physics_weight;
+        settings.temporal_strength = self.temporal_strength;
+        settings.temporal_attraction_enabled = self.temporal_attraction_enabled;
+        settings.directed_stiffness = self.directed_stiffness;
+        settings.recency_centering = self.recency_centering;
+        settings.momentum = self.momentum;
+        settings.sizing_preset = self.sizing_preset;
+        settings.w_importance = self.w_importance;
+        settings.w_tokens = self.w_tokens;
+        settings.w_time = self.w_time;
+        settings.max_node_multiplier = self.max_node_multiplier;
+    }
+}
+
 /// A saved preset of display/physics settings (excludes data selection)
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub struct Preset {
     pub name: String,
+    /// View the preset was saved under; its physics/sizing belong to that view.
+    /// None in presets saved before views tracked their own parameters.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub view_mode: Option<ViewMode>,
 
     // Display
     pub node_size: f32,
@@ -93,6 +452,10 @@ pub struct Preset {
     #[serde(default)]
     pub tool_use_filter: FilterMode,
     #[serde(default)]
+    pub code_only_filter: bool,
+    #[serde(default)]
+    pub role_filter: RoleFilter,
+    #[serde(default)]
     pub project_filter: FilterMode,
 
     // Physics
@@ -107,6 +470,8 @@ pub struct Preset {
     pub temporal_window_mins: f32,
     pub temporal_edge_opacity: f32,
     pub max_temporal_edges: usize,
+    #[serde(default = "default_max_temporal_neighbors")]
+    pub max_temporal_neighbors: usize,
 
     // Layout shaping
     #[serde(default = "default_directed_stiffness")]
@@ -153,6 +518,17 @@ pub struct Preset {
     pub project_colors: HashMap<String, f32>,
     #[serde(default)]
     pub session_colors: HashMap<String, f32>,
+    /// Pinned hues; None in presets saved before overrides existed (left as is on apply)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub hue_overrides: Option<HueOverrides>,
+
+    // Analysis state (opt-in at save time; visual-only presets leave it off)
+    #[serde(default)]
+    pub include_analysis: bool,
+    #[serde(default)]
+    pub semantic_filter_modes: HashMap<i32, SemanticFilterMode>,
+    #[serde(default)]
+    pub proximity_queries: Vec<String>,
 }
 
 impl Preset {
@@ -164,6 +540,7 @@ impl Preset {
     ) -> Self {
         Self {
             name,
+            view_mode: Some(settings.view_mode),
             node_size: settings.node_size,
             show_arrows: settings.show_arrows,
             timeline_enabled: settings.timeline_enabled,
@@ -178,6 +555,8 @@ impl Preset {
             importance_filter_enabled: false,
             importance_filter: settings.importance_filter,
             tool_use_filter: settings.tool_use_filter,
+            code_only_filter: settings.code_only_filter,
+            role_filter: settings.role_filter,
             project_filter: settings.project_filter,
             physics_enabled: settings.physics_enabled,
             repulsion: settings.repulsion,
@@ -189,6 +568,7 @@ impl Preset {
             temporal_window_mins: settings.temporal_window_mins,
             temporal_edge_opacity: settings.temporal_edge_opacity,
             max_temporal_edges: settings.max_temporal_edges,
+            max_temporal_neighbors: settings.max_temporal_neighbors,
             directed_stiffness: settings.directed_stiffness,
             recency_centering: settings.recency_centering,
             momentum: settings.momentum,
@@ -211,10 +591,32 @@ impl Preset {
             hue_offset: graph.hue_offset,
             project_colors: graph.project_colors.clone(),
             session_colors: graph.session_colors.clone(),
+            hue_overrides: Some(settings.hue_overrides.clone()),
+            include_analysis: false,
+            semantic_filter_modes: HashMap::new(),
+            proximity_queries: Vec::new(),
         }
     }
 
-    /// Apply this preset to settings and restore colors to graph
+    /// Attach semantic filter modes and proximity query strings to this preset
+    pub fn with_analysis(
+        mut self,
+        semantic_filter_modes: &HashMap<i32, SemanticFilterMode>,
+        proximity_queries: Vec<String>,
+    ) -> Self {
+        self.include_analysis = true;
+        // Off is the implicit default, so only store filters that do something
+        self.semantic_filter_modes = semantic_filter_modes.iter()
+            .filter(|(_, mode)| **mode != SemanticFilterMode::Off)
+            .map(|(&id, &mode)| (id, mode))
+            .collect();
+        self.proximity_queries = proximity_queries;
+        self
+    }
+
+    /// Apply this preset to settings and restore colors to graph.
+    /// Physics/sizing are stored in the view the preset was saved under; when
+    /// that isn't the active view, the active view keeps its own values.
     pub fn apply_to(&self, settings: &mut Settings, graph: &mut crate::graph::types::GraphState) {
         settings.node_size = self.node_size;
         settings.show_arrows = self.show_arrows;
@@ -229,6 +631,8 @@ impl Preset {
         settings.importance_threshold = self.importance_threshold;
         settings.importance_filter = self.importance_filter;
         settings.tool_use_filter = self.tool_use_filter;
+        settings.code_only_filter = self.code_only_filter;
+        settings.role_filter = self.role_filter;
         settings.project_filter = self.project_filter;
         settings.physics_enabled = self.physics_enabled;
         settings.repulsion = self.repulsion;
@@ -240,6 +644,7 @@ impl Preset {
         settings.temporal_window_mins = self.temporal_window_mins;
         settings.temporal_edge_opacity = self.temporal_edge_opacity;
         settings.max_temporal_edges = self.max_temporal_edges;
+        settings.max_temporal_neighbors = self.max_temporal_neighbors;
         settings.directed_stiffness = self.directed_stiffness;
         settings.recency_centering = self.recency_centering;
         settings.momentum = self.momentum;
@@ -258,6 +663,15 @@ impl Preset {
         settings.histogram_split_ratio = self.histogram_split_ratio;
         settings.sidebar_tab = self.sidebar_tab;
 
+        // Keep the per-view copy in step so a view switch doesn't revert it
+        let target = self.view_mode.unwrap_or(settings.view_mode);
+        let mut view = settings.view_settings_for(target);
+        view.capture(settings);
+        settings.view_settings.insert(target, view);
+        if target != settings.view_mode {
+            settings.apply_active_view_settings();
+        }
+
         // Restore colors (merge: saved colors take precedence over current)
         graph.hue_offset = self.hue_offset;
         for (k, v) in &self.project_colors {
@@ -266,6 +680,10 @@ impl Preset {
         for (k, v) in &self.session_colors {
             graph.session_colors.insert(k.clone(), *v);
         }
+        if let Some(ref overrides) = self.hue_overrides {
+            settings.hue_overrides = overrides.clone();
+            graph.hue_overrides = overrides.clone();
+        }
     }
 }
 
@@ -281,6 +699,51 @@ pub struct Settings {
     pub timeline_enabled: bool,
     #[serde(default)]
     pub color_mode: ColorMode,
+    #[serde(default)]
+    pub hue_assignment: HueAssignment,
+    #[serde(default)]
+    pub colorblind_safe: bool,
+    /// High-contrast presentation mode (thicker strokes, pure text colors)
+    #[serde(default)]
+    pub high_contrast: bool,
+    /// Reduced motion: no shimmer or pulses, physics stops after a one-shot settle
+    #[serde(default)]
+    pub reduce_motion: bool,
+    /// Outer arc on scored nodes, swept in proportion to the importance score
+    #[serde(default = "default_importance_rings")]
+    pub importance_rings: bool,
+    /// `</>` glyph beside code-heavy nodes when zoomed in far enough
+    #[serde(default = "default_content_badges")]
+    pub content_badges: bool,
+    /// At low zoom draw only the most important nodes per screen cell
+    #[serde(default)]
+    pub smart_declutter: bool,
+    /// Terminus marker on each session's last message, ticks on the timeline
+    #[serde(default = "default_session_end_markers")]
+    pub session_end_markers: bool,
+    /// "+23m" on zoomed-in conversation edges whose messages are far apart
+    #[serde(default)]
+    pub idle_gap_labels: bool,
+    /// Gap, in minutes, past which conversation edges are labelled and greyed
+    #[serde(default = "default_idle_gap_minutes")]
+    pub idle_gap_minutes: f32,
+    #[serde(default)]
+    pub role_colors: RoleColorOverrides,
+    /// Project and session hues pinned from the legend or node menu
+    #[serde(default)]
+    pub hue_overrides: HueOverrides,
+    /// UI scale (egui zoom factor, on top of the system scaling)
+    #[serde(default = "default_scale")]
+    pub ui_scale: f32,
+    /// Extra text size multiplier for the sidebar
+    #[serde(default = "default_scale")]
+    pub sidebar_text_scale: f32,
+    /// Extra text size multiplier for tooltips
+    #[serde(default = "default_scale")]
+    pub tooltip_text_scale: f32,
+    /// Fields shown in node tooltips, in order
+    #[serde(default)]
+    pub tooltip: TooltipConfig,
 
     // Node Sizing (unified formula)
     #[serde(default)]
@@ -299,6 +762,12 @@ pub struct Settings {
     pub timeline_speed: f32,
     #[serde(default = "default_hover_scrubs_timeline")]
     pub hover_scrubs_timeline: bool,
+    #[serde(default)]
+    pub hover_scrub_modifier: HoverScrubModifier,
+    #[serde(default)]
+    pub node_info_source: NodeInfoSource,
+    #[serde(default)]
+    pub theme: Theme,
 
     // Filtering
     pub importance_threshold: f32,
@@ -308,8 +777,33 @@ pub struct Settings {
     pub importance_filter: FilterMode,
     #[serde(default)]
     pub tool_use_filter: FilterMode,
+    /// Show only code-heavy messages
+    #[serde(default)]
+    pub code_only_filter: bool,
+    /// Show only user or only assistant messages
+    #[serde(default)]
+    pub role_filter: RoleFilter,
+    /// Fold runs of consecutive tool-output messages into one node
+    #[serde(default)]
+    pub collapse_tool_results: bool,
+    /// Pills shown in the bar above the graph (empty hides the bar)
+    #[serde(default = "default_filter_pills")]
+    pub filter_pills: Vec<FilterPill>,
     #[serde(default)]
     pub project_filter: FilterMode,
+    /// Semantic filter modes by filter id (Off entries are not stored)
+    #[serde(default)]
+    pub semantic_filter_modes: HashMap<i32, SemanticFilterMode>,
+    /// Selected project paths (empty = all projects); may include paths missing from the current data
+    #[serde(default)]
+    pub selected_projects: Vec<String>,
+    /// Expanded project tree paths
+    #[serde(default)]
+    pub project_tree_expanded: Vec<String>,
+    /// Project patterns never loaded: exact `~/...` paths, or globs where
+    /// `*` stays in one segment, `**` spans several and a trailing `/` takes the subtree
+    #[serde(default)]
+    pub excluded_projects: Vec<String>,
 
     // Physics
     pub physics_enabled: bool,
@@ -324,6 +818,8 @@ pub struct Settings {
     pub temporal_edge_opacity: f32,
     #[serde(default = "default_max_temporal_edges")]
     pub max_temporal_edges: usize,
+    #[serde(default = "default_max_temporal_neighbors")]
+    pub max_temporal_neighbors: usize,
 
     // Layout shaping
     #[serde(default = "default_directed_stiffness")]
@@ -333,6 +829,10 @@ pub struct Settings {
     #[serde(default)]
     pub momentum: f32,
 
+    /// Line pattern, width and opacity per edge kind
+    #[serde(default)]
+    pub edge_styles: EdgeStyles,
+
     // Score-proximity edges
     #[serde(default = "default_proximity_edge_opacity")]
     pub proximity_edge_opacity: f32,
@@ -354,28 +854,112 @@ pub struct Settings {
     // Saved presets
     #[serde(default)]
     pub presets: Vec<Preset>,
+    /// Saved data selections shown at the top of the sidebar
+    #[serde(default)]
+    pub saved_views: Vec<SavedView>,
 
     // Refresh & sync
     #[serde(default = "default_auto_refresh_enabled")]
     pub auto_refresh_enabled: bool,
     #[serde(default = "default_auto_refresh_interval_secs")]
     pub auto_refresh_interval_secs: f32,
+    /// Mark sessions with a message in the last `live_window_minutes` while auto-refreshing
+    #[serde(default = "default_live_highlight")]
+    pub live_highlight: bool,
+    #[serde(default = "default_live_window_minutes")]
+    pub live_window_minutes: f32,
+
+    // Desktop notifications for new sessions and important messages
+    #[serde(default)]
+    pub notifications_enabled: bool,
+    #[serde(default = "default_notify_importance_threshold")]
+    pub notify_importance_threshold: f32,
+    #[serde(default = "default_notify_min_interval_secs")]
+    pub notify_min_interval_secs: f32,
 
     // Panel visibility (collapsible side panels)
     #[serde(default = "default_beads_panel_open")]
     pub beads_panel_open: bool,
     #[serde(default = "default_mail_panel_open")]
     pub mail_panel_open: bool,
+    #[serde(default)]
+    pub activity_panel_open: bool,
+    /// Scroll the activity feed to keep the timeline playhead in view
+    #[serde(default = "default_activity_follow_playhead")]
+    pub activity_follow_playhead: bool,
+
+    /// The first-run overlay was dismissed. Files written before it existed
+    /// count as done; only a fresh install (no file) starts without it.
+    #[serde(default = "default_onboarding_completed")]
+    pub onboarding_completed: bool,
+
+    // Panel sizes (restored on launch)
+    #[serde(default = "default_sidebar_width")]
+    pub sidebar_width: f32,
+    #[serde(default = "default_timeline_panel_height")]
+    pub timeline_panel_height: f32,
+    #[serde(default = "default_side_panel_width")]
+    pub beads_panel_width: f32,
+    #[serde(default = "default_side_panel_width")]
+    pub mail_panel_width: f32,
+    #[serde(default = "default_side_panel_width")]
+    pub activity_panel_width: f32,
+
+    // Beads directory override (None = search upward from the working directory)
+    #[serde(default)]
+    pub beads_path: Option<String>,
+
+    // Obsidian vault whose recently modified notes join the graph (None = off)
+    #[serde(default)]
+    pub obsidian_vault: Option<String>,
+
+    // One node per session-summary topic, linked to the sessions that list it
+    #[serde(default)]
+    pub topic_nodes_enabled: bool,
+
+    // Session overview
+    #[serde(default)]
+    pub overview_sizing: OverviewSizing,
+    #[serde(default = "default_overview_overlap_mins")]
+    pub overview_overlap_mins: f32,
+
+    // Restored on launch once the first load completes
+    #[serde(default)]
+    pub last_view: LastView,
+
+    // Cost estimates (USD per million tokens)
+    #[serde(default)]
+    pub token_rates: TokenRates,
+
+    // Memory guardrails: load previews only when a load would exceed the budget
+    #[serde(default)]
+    pub content_lazy: bool,
+    #[serde(default = "default_memory_budget_mb")]
+    pub memory_budget_mb: f32,
 
     // Token histogram panel
     #[serde(default = "default_histogram_panel_enabled")]
     pub histogram_panel_enabled: bool,
     #[serde(default = "default_histogram_split_ratio")]
     pub histogram_split_ratio: f32,
+    #[serde(default)]
+    pub token_usage_panel_open: bool,
+    #[serde(default)]
+    pub token_histogram_log_scale: bool,
 
     // Sidebar tab
     #[serde(default)]
     pub sidebar_tab: SidebarTab,
+
+    // View mode and per-view physics/sizing
+    #[serde(default)]
+    pub view_mode: ViewMode,
+    #[serde(default)]
+    pub view_settings: HashMap<ViewMode, ViewSettings>,
+
+    /// Preset applied at launch, before the first data load
+    #[serde(default)]
+    pub startup_preset: Option<String>,
 }
 
 fn default_timeline_speed() -> f32 {
@@ -406,6 +990,22 @@ fn default_max_node_multiplier() -> f32 {
     10.0
 }
 
+fn default_importance_rings() -> bool {
+    true
+}
+
+fn default_content_badges() -> bool {
+    true
+}
+
+fn default_session_end_markers() -> bool {
+    true
+}
+
+fn default_idle_gap_minutes() -> f32 {
+    5.0
+}
+
 fn default_auto_refresh_enabled() -> bool {
     false
 }
@@ -414,6 +1014,30 @@ fn default_auto_refresh_interval_secs() -> f32 {
     5.0
 }
 
+fn default_live_highlight() -> bool {
+    true
+}
+
+fn default_live_window_minutes() -> f32 {
+    5.0
+}
+
+fn default_notify_importance_threshold() -> f32 {
+    0.8
+}
+
+fn default_notify_min_interval_secs() -> f32 {
+    60.0
+}
+
+fn default_overview_overlap_mins() -> f32 {
+    5.0
+}
+
+fn default_memory_budget_mb() -> f32 {
+    2048.0
+}
+
 fn default_beads_panel_open() -> bool {
     false
 }
@@ -422,6 +1046,19 @@ fn default_mail_panel_open() -> bool {
     false
 }
 
+fn default_activity_follow_playhead() -> bool {
+    true
+}
+
+fn default_onboarding_completed() -> bool {
+    true
+}
+
+fn default_sidebar_width() -> f32 { 240.0 }
+fn default_timeline_panel_height() -> f32 { 130.0 }
+fn default_side_panel_width() -> f32 { 300.0 }
+fn default_scale() -> f32 { 1.0 }
+
 fn default_directed_stiffness() -> f32 { 1.0 }
 
 fn default_proximity_edge_opacity() -> f32 { 0.3 }
@@ -431,6 +1068,8 @@ fn default_proximity_strength() -> f32 { 0.5 }
 fn default_max_proximity_edges() -> usize { 100_000 }
 fn default_max_neighbors_per_node() -> usize { 0 }
 
+fn default_max_temporal_neighbors() -> usize { crate::graph::types::DEFAULT_MAX_TEMPORAL_NEIGHBORS }
+
 fn default_proximity_quick_tags() -> Vec<String> {
     vec![
         "frustrated".into(), "decisions".into(), "errors".into(),
@@ -452,9 +1091,28 @@ impl Default for Settings {
             show_arrows: true,
             timeline_enabled: true,
             color_mode: ColorMode::Project,
+            hue_assignment: HueAssignment::default(),
+            colorblind_safe: false,
+            high_contrast: false,
+            reduce_motion: false,
+            importance_rings: default_importance_rings(),
+            content_badges: default_content_badges(),
+            smart_declutter: false,
+            session_end_markers: default_session_end_markers(),
+            idle_gap_labels: false,
+            idle_gap_minutes: default_idle_gap_minutes(),
+            role_colors: RoleColorOverrides::default(),
+            hue_overrides: HueOverrides::default(),
+            ui_scale: 1.0,
+            sidebar_text_scale: 1.0,
+            tooltip_text_scale: 1.0,
+            tooltip: TooltipConfig::default(),
             timeline_spacing_even: false,
             timeline_speed: 1.0,
             hover_scrubs_timeline: true,
+            hover_scrub_modifier: HoverScrubModifier::default(),
+            node_info_source: NodeInfoSource::default(),
+            theme: Theme::default(),
 
             // Node Sizing
             sizing_preset: SizingPreset::Balanced,
@@ -468,7 +1126,15 @@ impl Default for Settings {
             importance_filter_enabled: false,
             importance_filter: FilterMode::Off,
             tool_use_filter: FilterMode::Off,
+            code_only_filter: false,
+            role_filter: RoleFilter::default(),
+            collapse_tool_results: false,
+            filter_pills: default_filter_pills(),
             project_filter: FilterMode::Off,
+            semantic_filter_modes: HashMap::new(),
+            selected_projects: Vec::new(),
+            project_tree_expanded: Vec::new(),
+            excluded_projects: Vec::new(),
 
             // Physics
             physics_enabled: true,
@@ -481,12 +1147,15 @@ impl Default for Settings {
             temporal_window_mins: 5.0,
             temporal_edge_opacity: 0.3,
             max_temporal_edges: 100_000,
+            max_temporal_neighbors: default_max_temporal_neighbors(),
 
             // Layout shaping
             directed_stiffness: 1.0,
             recency_centering: 0.0,
             momentum: 0.0,
 
+            edge_styles: EdgeStyles::default(),
+
             // Score-proximity edges
             proximity_edge_opacity: 0.3,
             proximity_stiffness: 1.0,
@@ -500,25 +1169,70 @@ impl Default for Settings {
 
             // Presets
             presets: Vec::new(),
+            saved_views: Vec::new(),
 
             // Refresh & sync
             auto_refresh_enabled: false,
             auto_refresh_interval_secs: 5.0,
+            live_highlight: default_live_highlight(),
+            live_window_minutes: default_live_window_minutes(),
+
+            // Notifications
+            notifications_enabled: false,
+            notify_importance_threshold: default_notify_importance_threshold(),
+            notify_min_interval_secs: default_notify_min_interval_secs(),
 
             // Panel visibility
             beads_panel_open: false,
             mail_panel_open: false,
+            activity_panel_open: false,
+            activity_follow_playhead: default_activity_follow_playhead(),
+            onboarding_completed: false,
+
+            // Panel sizes
+            sidebar_width: default_sidebar_width(),
+            timeline_panel_height: default_timeline_panel_height(),
+            beads_panel_width: default_side_panel_width(),
+            mail_panel_width: default_side_panel_width(),
+            activity_panel_width: default_side_panel_width(),
+            beads_path: None,
+            obsidian_vault: None,
+            topic_nodes_enabled: false,
+            overview_sizing: OverviewSizing::default(),
+            overview_overlap_mins: default_overview_overlap_mins(),
+            last_view: LastView::default(),
+            token_rates: TokenRates::default(),
+            content_lazy: false,
+            memory_budget_mb: default_memory_budget_mb(),
 
             // Token histogram panel
             histogram_panel_enabled: false,
             histogram_split_ratio: 0.65,
+            token_usage_panel_open: false,
+            token_histogram_log_scale: false,
 
             // Sidebar tab
             sidebar_tab: SidebarTab::Data,
+
+            // View mode
+            view_mode: ViewMode::ForceDirected,
+            view_settings: HashMap::new(),
+
+            // Startup preset
+            startup_preset: None,
         }
     }
 }
 
+/// A settings file that failed to parse at startup
+#[derive(Debug, Clone, PartialEq)]
+pub struct CorruptSettings {
+    /// Where the unreadable file was moved; None if it couldn't be
+    pub backup: Option<PathBuf>,
+    /// Parse error, with line and column
+    pub error: String,
+}
+
 impl Settings {
     /// Get the path to the settings file
     fn config_path() -> Option<PathBuf> {
@@ -531,29 +1245,63 @@ impl Settings {
 
     /// Load settings from disk, returning defaults if file doesn't exist or is invalid
     pub fn load() -> Self {
+        Self::load_recovering().0
+    }
+
+    /// Load settings from disk. A file that doesn't parse is moved aside and
+    /// reported so the UI can say what happened.
+    pub fn load_recovering() -> (Self, Option<CorruptSettings>) {
         let Some(path) = Self::config_path() else {
-            eprintln!("Could not determine config directory, using defaults");
-            return Self::default();
+            tracing::warn!("Could not determine config directory, using defaults");
+            return (Self::default(), None);
         };
+        Self::load_from(&path)
+    }
 
-        match std::fs::read_to_string(&path) {
-            Ok(contents) => {
-                match serde_json::from_str(&contents) {
-                    Ok(mut settings) => {
-                        eprintln!("Loaded settings from {:?}", path);
-                        // Migrate old bool -> new FilterMode
-                        Self::migrate_filter_bools(&mut settings);
-                        settings
-                    }
-                    Err(e) => {
-                        eprintln!("Failed to parse settings file: {}, using defaults", e);
-                        Self::default()
-                    }
+    pub fn load_from(path: &Path) -> (Self, Option<CorruptSettings>) {
+        let contents = match std::fs::read_to_string(path) {
+            Ok(contents) => contents,
+            // File doesn't exist yet, that's fine
+            Err(_) => return (Self::default(), None),
+        };
+        match serde_json::from_str(&contents) {
+            Ok(mut settings) => {
+                tracing::debug!("Loaded settings from {:?}", path);
+                // Migrate old bool -> new FilterMode
+                Self::migrate_filter_bools(&mut settings);
+                settings.tooltip = std::mem::take(&mut settings.tooltip).normalized();
+                // Seed per-view settings from the flat fields for older files
+                if !settings.view_settings.contains_key(&settings.view_mode) {
+                    settings.save_to_active_view_settings();
                 }
+                (settings, None)
             }
-            Err(_) => {
-                // File doesn't exist yet, that's fine
-                Self::default()
+            Err(e) => {
+                tracing::error!("Failed to parse settings file {:?} at line {}, column {}: {}", path, e.line(), e.column(), e);
+                let backup = Self::move_aside(path);
+                // Someone has run the app before, so skip the first-run overlay
+                let settings = Self { onboarding_completed: true, ..Self::default() };
+                (settings, Some(CorruptSettings { backup, error: e.to_string() }))
+            }
+        }
+    }
+
+    /// Rename an unreadable settings file to `settings.json.corrupt-<timestamp>`
+    /// so the next save doesn't overwrite it. Falls back to copying.
+    fn move_aside(path: &Path) -> Option<PathBuf> {
+        let stamp = chrono::Local::now().format("%Y%m%d-%H%M%S");
+        let mut name = path.file_name()?.to_os_string();
+        name.push(format!(".corrupt-{}", stamp));
+        let backup = path.with_file_name(name);
+        let moved = std::fs::rename(path, &backup).or_else(|_| std::fs::copy(path, &backup).map(|_| ()));
+        match moved {
+            Ok(()) => {
+                tracing::warn!("Moved unreadable settings to {:?}, using defaults", backup);
+                Some(backup)
+            }
+            Err(e) => {
+                tracing::warn!("Failed to back up unreadable settings {:?}: {}", path, e);
+                None
             }
         }
     }
@@ -564,36 +1312,416 @@ impl Settings {
     fn migrate_filter_bools(settings: &mut Settings) {
         if settings.importance_filter_enabled && settings.importance_filter == FilterMode::Off {
             settings.importance_filter = FilterMode::Filtered;
-            eprintln!("Migrated importance_filter_enabled=true -> FilterMode::Filtered");
+            tracing::info!("Migrated importance_filter_enabled=true -> FilterMode::Filtered");
         }
     }
 
-    /// Save settings to disk
-    pub fn save(&self) {
-        let Some(path) = Self::config_path() else {
-            eprintln!("Could not determine config directory, settings not saved");
-            return;
-        };
+    /// Find a saved preset by name
+    pub fn preset_index(&self, name: &str) -> Option<usize> {
+        self.presets.iter().position(|p| p.name == name)
+    }
 
-        // Ensure config directory exists
-        if let Some(parent) = path.parent() {
-            if let Err(e) = std::fs::create_dir_all(parent) {
-                eprintln!("Failed to create config directory: {}", e);
-                return;
+    /// Per-view settings for a mode, falling back to that mode's defaults
+    pub fn view_settings_for(&self, mode: ViewMode) -> ViewSettings {
+        self.view_settings
+            .get(&mode)
+            .cloned()
+            .unwrap_or_else(|| ViewSettings::defaults_for(mode))
+    }
+
+    /// Default preset name configured for a view mode
+    pub fn view_default_preset(&self, mode: ViewMode) -> Option<&str> {
+        self.view_settings.get(&mode).and_then(|v| v.default_preset.as_deref())
+    }
+
+    /// Set or clear the default preset for a view mode
+    pub fn set_view_default_preset(&mut self, mode: ViewMode, name: Option<String>) {
+        let mut view = self.view_settings_for(mode);
+        view.default_preset = name;
+        self.view_settings.insert(mode, view);
+    }
+
+    /// Copy the flat physics/sizing fields into the active view's settings
+    pub fn save_to_active_view_settings(&mut self) {
+        let mut view = self.view_settings_for(self.view_mode);
+        view.capture(self);
+        self.view_settings.insert(self.view_mode, view);
+    }
+
+    /// Load the active view's physics/sizing into the flat fields.
+    /// Returns the view's default preset name, if one is configured.
+    pub fn apply_active_view_settings(&mut self) -> Option<String> {
+        let view = self.view_settings_for(self.view_mode);
+        view.restore(self);
+        view.default_preset
+    }
+
+    /// Switch the active view: the outgoing view keeps the current flat values,
+    /// then the incoming view's values are loaded. Returns the incoming view's
+    /// default preset name, if one is configured.
+    pub fn switch_view_mode(&mut self, mode: ViewMode) -> Option<String> {
+        self.save_to_active_view_settings();
+        self.view_mode = mode;
+        self.apply_active_view_settings()
+    }
+
+    /// Update startup/per-view/saved-view references after a preset is renamed
+    pub fn rename_preset_references(&mut self, old: &str, new: &str) {
+        if self.startup_preset.as_deref() == Some(old) {
+            self.startup_preset = Some(new.to_string());
+        }
+        for view in self.view_settings.values_mut() {
+            if view.default_preset.as_deref() == Some(old) {
+                view.default_preset = Some(new.to_string());
+            }
+        }
+        for view in &mut self.saved_views {
+            if view.preset.as_deref() == Some(old) {
+                view.preset = Some(new.to_string());
             }
         }
+    }
 
-        match serde_json::to_string_pretty(self) {
-            Ok(json) => {
-                if let Err(e) = std::fs::write(&path, json) {
-                    eprintln!("Failed to write settings file: {}", e);
-                } else {
-                    eprintln!("Saved settings to {:?}", path);
-                }
+    /// Clear startup/per-view/saved-view references to a deleted preset
+    pub fn remove_preset_references(&mut self, name: &str) {
+        if self.startup_preset.as_deref() == Some(name) {
+            self.startup_preset = None;
+        }
+        for view in self.view_settings.values_mut() {
+            if view.default_preset.as_deref() == Some(name) {
+                view.default_preset = None;
             }
-            Err(e) => {
-                eprintln!("Failed to serialize settings: {}", e);
+        }
+        for view in &mut self.saved_views {
+            if view.preset.as_deref() == Some(name) {
+                view.preset = None;
             }
         }
     }
+
+    /// Save settings to disk
+    pub fn save(&self) -> Result<(), String> {
+        let path = Self::config_path()
+            .ok_or_else(|| "Could not determine config directory, settings not saved".to_string())?;
+        self.save_to(&path)
+    }
+
+    /// Write to a temp file beside `path`, then rename over it, so a crash
+    /// mid-write leaves the previous file intact
+    pub fn save_to(&self, path: &Path) -> Result<(), String> {
+        // Ensure config directory exists
+        if let Some(parent) = path.parent() {
+            std::fs::create_dir_all(parent)
+                .map_err(|e| format!("Failed to create config directory: {}", e))?;
+        }
+
+        let json = serde_json::to_string_pretty(self)
+            .map_err(|e| format!("Failed to serialize settings: {}", e))?;
+        let tmp = path.with_extension("json.tmp");
+        let written = std::fs::File::create(&tmp)
+            .and_then(|mut file| {
+                file.write_all(json.as_bytes())?;
+                file.sync_all()
+            })
+            .and_then(|()| std::fs::rename(&tmp, path));
+        if let Err(e) = written {
+            let _ = std::fs::remove_file(&tmp);
+            return Err(format!("Failed to write settings file: {}", e));
+        }
+        tracing::debug!("Saved settings to {:?}", path);
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_last_view_round_trips() {
+        let settings = Settings {
+            last_view: LastView {
+                pan: [-310.5, 42.0],
+                zoom: 0.35,
+                selected_node: Some("9041".to_string()),
+                timeline_start: Some(1_750_000_000.0),
+                timeline_end: Some(1_750_007_200.5),
+            },
+            ..Default::default()
+        };
+        let json = serde_json::to_string(&settings).unwrap();
+        let restored: Settings = serde_json::from_str(&json).unwrap();
+        assert_eq!(restored.last_view, settings.last_view);
+
+        // Files written before the field existed get the default view
+        let mut value = serde_json::to_value(&settings).unwrap();
+        value.as_object_mut().unwrap().remove("last_view");
+        let legacy: Settings = serde_json::from_value(value).unwrap();
+        assert_eq!(legacy.last_view, LastView::default());
+    }
+
+    #[test]
+    fn test_view_mode_round_trip() {
+        let mut settings = Settings {
+            repulsion: 1234.0,
+            w_tokens: 2.5,
+            temporal_attraction_enabled: true,
+            ..Default::default()
+        };
+
+        // Switching to timeline loads its tuned defaults
+        settings.switch_view_mode(ViewMode::Timeline);
+        let timeline = ViewSettings::defaults_for(ViewMode::Timeline);
+        assert_eq!(settings.view_mode, ViewMode::Timeline);
+        assert_eq!(settings.repulsion, timeline.repulsion);
+        assert_eq!(settings.temporal_attraction_enabled, timeline.temporal_attraction_enabled);
+
+        // Edits in timeline view stay with the timeline view
+        settings.repulsion = 777.0;
+
+        // Switching back restores the force view's values
+        settings.switch_view_mode(ViewMode::ForceDirected);
+        assert_eq!(settings.repulsion, 1234.0);
+        assert_eq!(settings.w_tokens, 2.5);
+        assert!(settings.temporal_attraction_enabled);
+
+        settings.switch_view_mode(ViewMode::Timeline);
+        assert_eq!(settings.repulsion, 777.0);
+    }
+
+    #[test]
+    fn test_preset_physics_survive_view_switch() {
+        let graph = crate::graph::types::GraphState::default();
+        let mut graph_mut = crate::graph::types::GraphState::default();
+        let mut settings = Settings { repulsion: 4321.0, w_tokens: 3.0, ..Default::default() };
+        let preset = Preset::from_settings("Tight".to_string(), &settings, &graph);
+
+        settings.repulsion = 100.0;
+        settings.save_to_active_view_settings();
+        preset.apply_to(&mut settings, &mut graph_mut);
+        assert_eq!(settings.repulsion, 4321.0);
+
+        settings.switch_view_mode(ViewMode::Timeline);
+        settings.switch_view_mode(ViewMode::ForceDirected);
+        assert_eq!(settings.repulsion, 4321.0);
+        assert_eq!(settings.w_tokens, 3.0);
+    }
+
+    #[test]
+    fn test_preset_from_other_view_applies_to_that_view() {
+        let graph = crate::graph::types::GraphState::default();
+        let mut graph_mut = crate::graph::types::GraphState::default();
+        let mut settings = Settings::default();
+        settings.switch_view_mode(ViewMode::Timeline);
+        settings.repulsion = 999.0;
+        let preset = Preset::from_settings("Timeline tuned".to_string(), &settings, &graph);
+        assert_eq!(preset.view_mode, Some(ViewMode::Timeline));
+
+        settings.switch_view_mode(ViewMode::ForceDirected);
+        let force_repulsion = settings.repulsion;
+        preset.apply_to(&mut settings, &mut graph_mut);
+        assert_eq!(settings.repulsion, force_repulsion);
+
+        settings.switch_view_mode(ViewMode::Timeline);
+        assert_eq!(settings.repulsion, 999.0);
+    }
+
+    #[test]
+    fn test_view_default_preset_returned_on_switch() {
+        let mut settings = Settings::default();
+        settings.set_view_default_preset(ViewMode::Timeline, Some("Overview".to_string()));
+        assert_eq!(settings.switch_view_mode(ViewMode::Timeline).as_deref(), Some("Overview"));
+        assert_eq!(settings.switch_view_mode(ViewMode::ForceDirected), None);
+    }
+
+    #[test]
+    fn test_preset_analysis_round_trip() {
+        let graph = crate::graph::types::GraphState::default();
+        let modes = HashMap::from([
+            (1, SemanticFilterMode::Include),
+            (2, SemanticFilterMode::Off),
+        ]);
+        let preset = Preset::from_settings("Triage".to_string(), &Settings::default(), &graph)
+            .with_analysis(&modes, vec!["auth bugs".to_string()]);
+        assert_eq!(preset.semantic_filter_modes.len(), 1);
+
+        let json = serde_json::to_string(&preset).unwrap();
+        let loaded: Preset = serde_json::from_str(&json).unwrap();
+        assert_eq!(loaded, preset);
+
+        // Presets saved before analysis state existed still load
+        let mut value: serde_json::Value = serde_json::from_str(&json).unwrap();
+        for key in ["include_analysis", "semantic_filter_modes", "proximity_queries"] {
+            value.as_object_mut().unwrap().remove(key);
+        }
+        let legacy: Preset = serde_json::from_value(value).unwrap();
+        assert!(!legacy.include_analysis);
+        assert!(legacy.proximity_queries.is_empty());
+    }
+
+    #[test]
+    fn test_role_color_overrides_apply_and_persist() {
+        let mut settings = Settings::default();
+        *settings.role_colors.get_mut(&Role::User) = Some(Color32::from_rgb(10, 20, 30));
+
+        let json = serde_json::to_string(&settings).unwrap();
+        let loaded: Settings = serde_json::from_str(&json).unwrap();
+        assert_eq!(loaded.role_colors, settings.role_colors);
+
+        let mut colors = crate::theme::DARK.role;
+        loaded.role_colors.apply(&mut colors);
+        assert_eq!(colors.user, Color32::from_rgb(10, 20, 30));
+        assert_eq!(colors.assistant, crate::theme::DARK.role.assistant);
+    }
+
+    #[test]
+    fn test_semantic_filter_modes_persist() {
+        let settings = Settings {
+            semantic_filter_modes: HashMap::from([(7, SemanticFilterMode::Exclude), (12, SemanticFilterMode::IncludePlus1)]),
+            ..Default::default()
+        };
+        let json = serde_json::to_string(&settings).unwrap();
+        let loaded: Settings = serde_json::from_str(&json).unwrap();
+        assert_eq!(loaded.semantic_filter_modes, settings.semantic_filter_modes);
+
+        // Settings files written before the field existed still load
+        let mut value: serde_json::Value = serde_json::from_str(&json).unwrap();
+        value.as_object_mut().unwrap().remove("semantic_filter_modes");
+        let old: Settings = serde_json::from_value(value).unwrap();
+        assert!(old.semantic_filter_modes.is_empty());
+    }
+
+    #[test]
+    fn test_project_selection_round_trips_through_collapsed_paths() {
+        use crate::project_tree::{CheckState, ProjectTreeNode};
+        use std::collections::HashSet;
+
+        let projects = vec![
+            "~/Documents/GitHub/alpha".to_string(),
+            "~/Documents/GitHub/beta".to_string(),
+            "~/scratch".to_string(),
+        ];
+        let tree = ProjectTreeNode::build(&projects);
+        let merged = tree.children.iter().find(|c| c.name == "Documents/GitHub").unwrap();
+
+        let settings = Settings {
+            selected_projects: merged.leaf_paths(),
+            project_tree_expanded: vec![merged.full_path.clone()],
+            ..Default::default()
+        };
+        let loaded: Settings = serde_json::from_str(&serde_json::to_string(&settings).unwrap()).unwrap();
+
+        let rebuilt = ProjectTreeNode::build(&projects);
+        let merged = rebuilt.children.iter().find(|c| c.name == "Documents/GitHub").unwrap();
+        let selected: HashSet<String> = loaded.selected_projects.into_iter().collect();
+        assert_eq!(merged.check_state(&selected), CheckState::Checked);
+        assert_eq!(rebuilt.check_state(&selected), CheckState::Mixed);
+        assert_eq!(loaded.project_tree_expanded, vec!["~/Documents/GitHub".to_string()]);
+    }
+
+    fn temp_settings_dir(name: &str) -> PathBuf {
+        let dir = std::env::temp_dir().join(format!("settings-test-{}-{}", name, std::process::id()));
+        let _ = std::fs::remove_dir_all(&dir);
+        dir
+    }
+
+    #[test]
+    fn test_onboarding_only_without_settings_file() {
+        let dir = temp_settings_dir("onboarding");
+        let path = dir.join("settings.json");
+        let (fresh, _) = Settings::load_from(&path);
+        assert!(!fresh.onboarding_completed);
+
+        // A file from before onboarding existed has no flag and skips it
+        let mut json = serde_json::to_value(Settings::default()).unwrap();
+        json.as_object_mut().unwrap().remove("onboarding_completed");
+        std::fs::create_dir_all(&dir).unwrap();
+        std::fs::write(&path, json.to_string()).unwrap();
+        let (existing, _) = Settings::load_from(&path);
+        assert!(existing.onboarding_completed);
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+
+    #[test]
+    fn test_corrupt_file_is_moved_aside() {
+        let dir = temp_settings_dir("corrupt");
+        let path = dir.join("settings.json");
+        std::fs::create_dir_all(&dir).unwrap();
+        std::fs::write(&path, "{\n  \"node_size\": 12.0,\n  \"presets\": [").unwrap();
+
+        let (settings, corrupt) = Settings::load_from(&path);
+        assert_eq!(settings.node_size, Settings::default().node_size);
+        let corrupt = corrupt.expect("parse failure is reported");
+        assert!(corrupt.error.contains("line 3"), "{}", corrupt.error);
+
+        // The bad file survives under a new name; the real path is free for a save
+        let backup = corrupt.backup.expect("file was moved aside");
+        assert!(backup.file_name().unwrap().to_string_lossy().starts_with("settings.json.corrupt-"));
+        assert!(std::fs::read_to_string(&backup).unwrap().contains("presets"));
+        assert!(!path.exists());
+        assert_eq!(Settings::load_from(&path).1, None);
+        let _ = std::fs::remove_dir_all(dir);
+    }
+
+    #[test]
+    fn test_save_is_atomic() {
+        let dir = temp_settings_dir("atomic");
+        let path = dir.join("settings.json");
+        let settings = Settings { node_size: 21.0, ..Default::default() };
+        settings.save_to(&path).unwrap();
+        assert_eq!(Settings::load_from(&path).0.node_size, 21.0);
+
+        // A leftover temp file from an interrupted save doesn't affect the real one
+        std::fs::write(path.with_extension("json.tmp"), "{ trunc").unwrap();
+        assert_eq!(Settings::load_from(&path).0.node_size, 21.0);
+        Settings { node_size: 9.0, ..Default::default() }.save_to(&path).unwrap();
+        assert_eq!(Settings::load_from(&path).0.node_size, 9.0);
+        let files: Vec<_> = std::fs::read_dir(&dir).unwrap().map(|e| e.unwrap().file_name()).collect();
+        assert_eq!(files, vec![std::ffi::OsString::from("settings.json")]);
+        let _ = std::fs::remove_dir_all(dir);
+    }
+
+    #[test]
+    fn test_role_filter_admits() {
+        assert!(RoleFilter::All.admits(&Role::Topic));
+        assert!(RoleFilter::Users.admits(&Role::User));
+        assert!(!RoleFilter::Users.admits(&Role::Assistant));
+        assert!(RoleFilter::Claude.admits(&Role::Agent));
+        assert!(!RoleFilter::Claude.admits(&Role::Obsidian));
+    }
+
+    #[test]
+    fn test_filter_pills_default_to_all() {
+        let settings: Settings = serde_json::from_str(&serde_json::to_string(&Settings::default()).unwrap()).unwrap();
+        assert_eq!(settings.filter_pills, FilterPill::all());
+    }
+
+    #[test]
+    fn test_saved_views_follow_preset_changes() {
+        let view = SavedView {
+            name: "Last week".to_string(),
+            time_range_hours: 168.0,
+            projects: vec!["/work/api".to_string()],
+            session: None,
+            preset: Some("Dense".to_string()),
+        };
+        let mut settings = Settings { saved_views: vec![view.clone()], ..Default::default() };
+        let json = serde_json::to_string(&settings).unwrap();
+        let restored: Settings = serde_json::from_str(&json).unwrap();
+        assert_eq!(restored.saved_views, settings.saved_views);
+
+        // Name doesn't matter; small range differences don't either
+        let current = SavedView { name: String::new(), time_range_hours: 168.2, ..view.clone() };
+        assert!(view.matches(&current));
+        assert!(!view.matches(&SavedView { time_range_hours: 24.0, ..current.clone() }));
+        assert!(!view.matches(&SavedView { projects: Vec::new(), ..current.clone() }));
+        assert!(!view.matches(&SavedView { session: Some("s1".to_string()), ..current.clone() }));
+        assert!(!view.matches(&SavedView { preset: None, ..current.clone() }));
+        assert!(SavedView { preset: None, ..view.clone() }.matches(&current));
+
+        settings.rename_preset_references("Dense", "Compact");
+        assert_eq!(settings.saved_views[0].preset.as_deref(), Some("Compact"));
+        settings.remove_preset_references("Compact");
+        assert_eq!(settings.saved_views[0].preset, None);
+    }
 }
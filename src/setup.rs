@@ -0,0 +1,182 @@
+//! First-run checks for where session data can come from.
+//!
+//! `detect_environment` looks, in order, for a database that already has
+//! messages, an API that answers, and raw `.jsonl` transcripts under
+//! `~/.claude/projects`. The onboarding overlay shows whichever it finds with
+//! the matching next step, and the helpers here perform those steps.
+
+use std::path::{Path, PathBuf};
+use std::process::Command;
+use std::time::Duration;
+
+use crate::db::DbClient;
+
+/// How long the API gets to answer `/health` before it counts as down
+const API_PROBE_TIMEOUT: Duration = Duration::from_secs(2);
+
+/// Port uvicorn is started on when the API URL doesn't name one
+const DEFAULT_API_PORT: u16 = 8000;
+
+/// Where session data is available, best source first
+#[derive(Debug, Clone, PartialEq)]
+pub enum Environment {
+    /// The database already holds messages: use it directly
+    DatabaseReady { path: String, messages: i64 },
+    /// The database is empty, but the API answers and can import sessions
+    ApiAvailable { url: String },
+    /// Only raw transcripts: run the ingest to fill the database
+    TranscriptsOnly { dir: PathBuf, files: usize },
+    /// No data anywhere yet
+    NothingFound { url: String, dir: PathBuf },
+}
+
+/// Where Claude Code writes session transcripts
+pub fn projects_dir() -> PathBuf {
+    dirs::home_dir().unwrap_or_else(|| PathBuf::from(".")).join(".claude").join("projects")
+}
+
+/// Check the database, then the API, then the transcripts directory, and
+/// report the first source with data
+pub fn detect_environment(db_path: &str, api_url: &str, projects_dir: &Path) -> Environment {
+    if let Some(messages) = database_messages(db_path).filter(|&n| n > 0) {
+        return Environment::DatabaseReady { path: db_path.to_string(), messages };
+    }
+    if api_responds(api_url) {
+        return Environment::ApiAvailable { url: api_url.to_string() };
+    }
+    match count_transcripts(projects_dir) {
+        0 => Environment::NothingFound { url: api_url.to_string(), dir: projects_dir.to_path_buf() },
+        files => Environment::TranscriptsOnly { dir: projects_dir.to_path_buf(), files },
+    }
+}
+
+/// Messages in the database at `path`; None when there's no file or it can't be read
+fn database_messages(path: &str) -> Option<i64> {
+    if !Path::new(path).is_file() {
+        return None;
+    }
+    DbClient::new(Some(path)).ok()?.message_count().ok()
+}
+
+fn api_responds(url: &str) -> bool {
+    let Ok(client) = reqwest::blocking::Client::builder().timeout(API_PROBE_TIMEOUT).build() else {
+        return false;
+    };
+    client
+        .get(format!("{}/health", url.trim_end_matches('/')))
+        .send()
+        .is_ok_and(|resp| resp.status().is_success())
+}
+
+/// `.jsonl` files one level inside each project directory, where Claude Code keeps them
+fn count_transcripts(dir: &Path) -> usize {
+    let Ok(projects) = std::fs::read_dir(dir) else {
+        return 0;
+    };
+    projects
+        .flatten()
+        .filter_map(|project| std::fs::read_dir(project.path()).ok())
+        .flat_map(|entries| entries.flatten())
+        .filter(|entry| entry.path().extension().is_some_and(|ext| ext == "jsonl"))
+        .count()
+}
+
+/// Port named in an API URL like `http://127.0.0.1:8000/`
+fn api_port(url: &str) -> u16 {
+    url.trim_end_matches('/')
+        .rsplit(':')
+        .next()
+        .and_then(|port| port.parse().ok())
+        .unwrap_or(DEFAULT_API_PORT)
+}
+
+/// A file shipped in the repository (`ingest.py`, `api/main.py`), looked up
+/// from the working directory, then beside the executable, then the source tree
+fn repo_file(relative: &str) -> Option<PathBuf> {
+    let exe_dirs = std::env::current_exe().ok().into_iter().flat_map(|exe| {
+        exe.ancestors().skip(1).take(4).map(Path::to_path_buf).collect::<Vec<_>>()
+    });
+    std::env::current_dir().ok().into_iter()
+        .chain(exe_dirs)
+        .chain(std::iter::once(PathBuf::from(env!("CARGO_MANIFEST_DIR"))))
+        .map(|dir| dir.join(relative))
+        .find(|path| path.is_file())
+}
+
+/// Run `ingest.py` into `db_path` and wait for it. Returns the script's last
+/// line of output, or its error output on failure.
+pub fn run_ingest(db_path: &str) -> Result<String, String> {
+    let script = repo_file("ingest.py").ok_or("ingest.py not found next to the app")?;
+    let output = Command::new("python3")
+        .arg(&script)
+        .arg("--db")
+        .arg(db_path)
+        .output()
+        .map_err(|e| format!("Failed to run python3: {}", e))?;
+    let last_line = |bytes: &[u8]| String::from_utf8_lossy(bytes).lines().last().unwrap_or_default().to_string();
+    if output.status.success() {
+        Ok(last_line(&output.stdout))
+    } else {
+        Err(format!("Ingest failed: {}", last_line(&output.stderr)))
+    }
+}
+
+/// Start the Python API in the background on the port `api_url` names
+pub fn start_backend(api_url: &str) -> Result<(), String> {
+    let main = repo_file("api/main.py").ok_or("api/main.py not found next to the app")?;
+    let dir = main.parent().unwrap_or(Path::new("."));
+    Command::new("python3")
+        .args(["-m", "uvicorn", "main:app", "--port", &api_port(api_url).to_string()])
+        .current_dir(dir)
+        .spawn()
+        .map(|_| ())
+        .map_err(|e| format!("Failed to start the backend: {}", e))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Nothing listens on the discard port, so the probe fails fast
+    const DEAD_API: &str = "http://127.0.0.1:9";
+
+    fn temp_dir(name: &str) -> PathBuf {
+        let dir = std::env::temp_dir().join(format!("dashboard-setup-{}-{}", name, std::process::id()));
+        let _ = std::fs::remove_dir_all(&dir);
+        std::fs::create_dir_all(&dir).unwrap();
+        dir
+    }
+
+    #[test]
+    fn test_nothing_found() {
+        let dir = temp_dir("empty");
+        let db = dir.join("missing.db");
+        let env = detect_environment(db.to_str().unwrap(), DEAD_API, &dir.join("projects"));
+        assert_eq!(env, Environment::NothingFound { url: DEAD_API.to_string(), dir: dir.join("projects") });
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+
+    #[test]
+    fn test_transcripts_found_past_an_empty_database() {
+        let dir = temp_dir("transcripts");
+        let projects = dir.join("projects");
+        std::fs::create_dir_all(projects.join("-home-me-repo")).unwrap();
+        std::fs::write(projects.join("-home-me-repo").join("a.jsonl"), "{}\n").unwrap();
+        std::fs::write(projects.join("-home-me-repo").join("notes.txt"), "").unwrap();
+        std::fs::write(projects.join("stray.jsonl"), "").unwrap();
+
+        // A database with no messages doesn't count as ready
+        let db = dir.join("dashboard.db");
+        drop(DbClient::new(Some(db.to_str().unwrap())).unwrap());
+        let env = detect_environment(db.to_str().unwrap(), DEAD_API, &projects);
+        assert_eq!(env, Environment::TranscriptsOnly { dir: projects, files: 1 });
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+
+    #[test]
+    fn test_api_port() {
+        assert_eq!(api_port("http://127.0.0.1:8123"), 8123);
+        assert_eq!(api_port("http://localhost:9000/"), 9000);
+        assert_eq!(api_port("http://localhost"), DEFAULT_API_PORT);
+    }
+}
@@ -0,0 +1,177 @@
+//! Keyboard shortcut registry. The `?` overlay lists these entries and the
+//! input handlers look up their keys here, so the two can't drift apart.
+
+use eframe::egui::{self, Key, Modifiers};
+
+/// Area a binding is listed under in the help overlay
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Category {
+    General,
+    Timeline,
+    Graph,
+    Panels,
+    Presets,
+}
+
+impl Category {
+    /// Overlay order
+    pub fn all() -> &'static [Category] {
+        &[Category::General, Category::Timeline, Category::Graph, Category::Panels, Category::Presets]
+    }
+
+    pub fn label(&self) -> &'static str {
+        match self {
+            Category::General => "General",
+            Category::Timeline => "Timeline",
+            Category::Graph => "Graph",
+            Category::Panels => "Panels",
+            Category::Presets => "Presets",
+        }
+    }
+}
+
+/// What a keyboard binding does
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Action {
+    ToggleHelp,
+    /// Close the help overlay or drop an in-progress histogram brush
+    Cancel,
+    Undo,
+    Redo,
+    ToggleBeadsPanel,
+    ToggleMailPanel,
+    ToggleActivityPanel,
+    ToggleHighContrast,
+    /// Pull in the selected node's semantic neighbours
+    FindSimilar,
+}
+
+/// How a binding is triggered
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Trigger {
+    Key { key: Key, modifiers: Modifiers, action: Action },
+    /// Pointer gesture handled by the widget itself; listed for discoverability.
+    /// `command` prefixes Ctrl (⌘ on macOS).
+    Pointer { gesture: &'static str, command: bool },
+}
+
+#[derive(Debug, Clone, Copy)]
+pub struct Shortcut {
+    pub trigger: Trigger,
+    pub description: &'static str,
+    pub category: Category,
+}
+
+const fn key(key: Key, modifiers: Modifiers, action: Action, category: Category, description: &'static str) -> Shortcut {
+    Shortcut { trigger: Trigger::Key { key, modifiers, action }, description, category }
+}
+
+const fn pointer(gesture: &'static str, command: bool, category: Category, description: &'static str) -> Shortcut {
+    Shortcut { trigger: Trigger::Pointer { gesture, command }, description, category }
+}
+
+/// Every binding, in overlay order within each category.
+/// Shift is ignored unless the binding includes it or another binding claims
+/// the shifted chord (Cmd+Z vs Cmd+Shift+Z).
+pub const SHORTCUTS: &[Shortcut] = &[
+    key(Key::Questionmark, Modifiers::NONE, Action::ToggleHelp, Category::General, "Show or hide this overlay"),
+    key(Key::Escape, Modifiers::NONE, Action::Cancel, Category::General, "Close overlay / cancel histogram or canvas brush"),
+    key(Key::Z, Modifiers::COMMAND, Action::Undo, Category::General, "Undo settings change"),
+    key(Key::Z, Modifiers::COMMAND.plus(Modifiers::SHIFT), Action::Redo, Category::General, "Redo settings change"),
+    pointer("Click track", false, Category::Timeline, "Jump the playhead"),
+    pointer("Drag histogram", false, Category::Timeline, "Set the timeline window"),
+    pointer("Drag / scroll", false, Category::Graph, "Pan"),
+    pointer("Drag", true, Category::Graph, "Select a time window (Escape cancels)"),
+    pointer("Pinch / Ctrl+scroll", false, Category::Graph, "Zoom toward the cursor"),
+    pointer("Click", false, Category::Graph, "Select node"),
+    pointer("Double-click", false, Category::Graph, "Point-in-time summary"),
+    pointer("Click", true, Category::Graph, "Neighborhood summary"),
+    pointer("Right-click", false, Category::Graph, "Compare sessions / find similar menu"),
+    key(Key::N, Modifiers::NONE, Action::FindSimilar, Category::Graph, "Find messages similar to the selected node"),
+    key(Key::B, Modifiers::NONE, Action::ToggleBeadsPanel, Category::Panels, "Toggle beads panel"),
+    key(Key::M, Modifiers::NONE, Action::ToggleMailPanel, Category::Panels, "Toggle mail panel"),
+    key(Key::A, Modifiers::NONE, Action::ToggleActivityPanel, Category::Panels, "Toggle activity feed"),
+    key(Key::P, Modifiers::NONE, Action::ToggleHighContrast, Category::Panels, "Toggle high-contrast palette"),
+];
+
+/// Whether a binding's chord matches the held modifiers (the key itself is checked separately)
+pub fn chord_matches(key: Key, binding: Modifiers, held: Modifiers) -> bool {
+    if held.command != binding.command || held.alt != binding.alt {
+        return false;
+    }
+    if binding.shift {
+        return held.shift;
+    }
+    let shifted_sibling = SHORTCUTS.iter().any(|s| matches!(
+        s.trigger,
+        Trigger::Key { key: k, modifiers: m, .. }
+            if k == key && m.shift && m.command == binding.command && m.alt == binding.alt
+    ));
+    !held.shift || !shifted_sibling
+}
+
+/// Actions whose keys were pressed this frame, in registry order
+pub fn triggered(input: &egui::InputState) -> Vec<Action> {
+    SHORTCUTS.iter()
+        .filter_map(|s| match s.trigger {
+            Trigger::Key { key, modifiers, action }
+                if input.key_pressed(key) && chord_matches(key, modifiers, input.modifiers) => Some(action),
+            _ => None,
+        })
+        .collect()
+}
+
+/// Whether any binding for `action` was pressed this frame
+pub fn pressed(input: &egui::InputState, action: Action) -> bool {
+    triggered(input).contains(&action)
+}
+
+/// Human-readable trigger, using the platform's modifier names
+pub fn trigger_label(ctx: &egui::Context, trigger: &Trigger) -> String {
+    match *trigger {
+        Trigger::Key { key, modifiers, .. } => ctx.format_shortcut(&egui::KeyboardShortcut::new(modifiers, key)),
+        Trigger::Pointer { gesture, command: false } => gesture.to_string(),
+        Trigger::Pointer { gesture, command: true } => {
+            let is_mac = ctx.os() == egui::os::OperatingSystem::Mac;
+            format!("{}{}", if is_mac { "⌘" } else { "Ctrl+" }, gesture)
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_chord_matching() {
+        let shift = Modifiers::SHIFT;
+        let cmd = Modifiers::COMMAND;
+        let cmd_shift = cmd.plus(shift);
+
+        // Plain keys ignore shift (and `?` is typed with it) but not Cmd/Alt
+        assert!(chord_matches(Key::B, Modifiers::NONE, Modifiers::NONE));
+        assert!(chord_matches(Key::B, Modifiers::NONE, shift));
+        assert!(chord_matches(Key::Questionmark, Modifiers::NONE, shift));
+        assert!(!chord_matches(Key::B, Modifiers::NONE, cmd));
+        assert!(!chord_matches(Key::B, Modifiers::NONE, Modifiers::ALT));
+
+        // Undo yields to redo when shift is held
+        assert!(chord_matches(Key::Z, cmd, cmd));
+        assert!(!chord_matches(Key::Z, cmd, cmd_shift));
+        assert!(chord_matches(Key::Z, cmd_shift, cmd_shift));
+        assert!(!chord_matches(Key::Z, cmd_shift, cmd));
+    }
+
+    #[test]
+    fn test_registry_has_no_conflicting_chords() {
+        let keys: Vec<(Key, Modifiers)> = SHORTCUTS.iter()
+            .filter_map(|s| match s.trigger {
+                Trigger::Key { key, modifiers, .. } => Some((key, modifiers)),
+                Trigger::Pointer { .. } => None,
+            })
+            .collect();
+        for (i, a) in keys.iter().enumerate() {
+            assert!(!keys[i + 1..].contains(a), "duplicate binding {:?}", a);
+        }
+    }
+}
@@ -0,0 +1,192 @@
+//! Layout snapshots: every node position, the camera, and the visibility
+//! settings, captured under a name so a good layout can be brought back.
+//! Unlike presets they carry positions. They live in memory, bounded to
+//! `MAX_SNAPSHOTS`, and can be written to and read back from a file.
+
+use crate::graph::ids;
+use crate::settings::Preset;
+use egui::Pos2;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::path::PathBuf;
+
+/// Snapshots kept before the oldest is dropped
+pub const MAX_SNAPSHOTS: usize = 20;
+/// Below this share of matching ids a restore warns
+pub const MATCH_WARN_FRACTION: f32 = 0.8;
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct LayoutSnapshot {
+    pub name: String,
+    /// Epoch seconds
+    pub taken_at: f64,
+    pub positions: HashMap<String, [f32; 2]>,
+    pub pan: [f32; 2],
+    pub zoom: f32,
+    /// Visibility-affecting settings, stored the way presets store them
+    pub settings: Preset,
+}
+
+impl LayoutSnapshot {
+    pub fn new(
+        name: String,
+        taken_at: f64,
+        positions: &HashMap<String, Pos2>,
+        pan: [f32; 2],
+        zoom: f32,
+        settings: Preset,
+    ) -> Self {
+        Self {
+            name,
+            taken_at,
+            positions: positions.iter().map(|(id, p)| (id.clone(), [p.x, p.y])).collect(),
+            pan,
+            zoom,
+            settings,
+        }
+    }
+
+    /// Share of the snapshot's ids for which `exists` holds (1.0 when empty)
+    pub fn match_fraction(&self, exists: impl Fn(&str) -> bool) -> f32 {
+        if self.positions.is_empty() {
+            return 1.0;
+        }
+        let matched = self.positions.keys().filter(|id| exists(id)).count();
+        matched as f32 / self.positions.len() as f32
+    }
+
+    /// Write the stored position of every id still in `positions`, returning
+    /// how many were restored. Ids the snapshot lacks keep their place.
+    pub fn restore_positions(&self, positions: &mut HashMap<String, Pos2>) -> usize {
+        let mut restored = 0;
+        for (id, pos) in positions.iter_mut() {
+            if let Some([x, y]) = self.positions.get(id) {
+                *pos = Pos2::new(*x, *y);
+                restored += 1;
+            }
+        }
+        restored
+    }
+}
+
+/// Named snapshots, newest last
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct SnapshotHistory {
+    pub snapshots: Vec<LayoutSnapshot>,
+}
+
+impl SnapshotHistory {
+    /// Add a snapshot, replacing one with the same name and dropping the
+    /// oldest past `MAX_SNAPSHOTS`
+    pub fn push(&mut self, snapshot: LayoutSnapshot) {
+        self.snapshots.retain(|s| s.name != snapshot.name);
+        self.snapshots.push(snapshot);
+        let excess = self.snapshots.len().saturating_sub(MAX_SNAPSHOTS);
+        self.snapshots.drain(..excess);
+    }
+
+    pub fn remove(&mut self, idx: usize) {
+        if idx < self.snapshots.len() {
+            self.snapshots.remove(idx);
+        }
+    }
+
+    /// Write every snapshot to the snapshot file
+    pub fn save(&self) -> Result<PathBuf, String> {
+        let path = path().ok_or_else(|| "Could not determine config directory".to_string())?;
+        if let Some(dir) = path.parent() {
+            std::fs::create_dir_all(dir).map_err(|e| format!("Failed to create {:?}: {}", dir, e))?;
+        }
+        let json = serde_json::to_string(self).map_err(|e| format!("Failed to serialize snapshots: {}", e))?;
+        std::fs::write(&path, json).map_err(|e| format!("Failed to write snapshots: {}", e))?;
+        Ok(path)
+    }
+
+    /// Read the snapshot file
+    pub fn load() -> Result<Self, String> {
+        let path = path().ok_or_else(|| "Could not determine config directory".to_string())?;
+        let contents = std::fs::read_to_string(&path).map_err(|e| format!("Failed to read {:?}: {}", path, e))?;
+        Self::from_json(&contents)
+    }
+
+    /// Parse snapshot file contents. Snapshots taken before ids were
+    /// namespaced have bare message ids; those are rewritten to `msg:` ids.
+    fn from_json(contents: &str) -> Result<Self, String> {
+        let mut history: Self = serde_json::from_str(contents).map_err(|e| format!("Invalid snapshot file: {}", e))?;
+        let excess = history.snapshots.len().saturating_sub(MAX_SNAPSHOTS);
+        history.snapshots.drain(..excess);
+        for snapshot in &mut history.snapshots {
+            snapshot.positions = std::mem::take(&mut snapshot.positions)
+                .into_iter()
+                .map(|(id, pos)| (ids::normalize(id), pos))
+                .collect();
+        }
+        Ok(history)
+    }
+}
+
+/// Location of the snapshot file
+pub fn path() -> Option<PathBuf> {
+    dirs::config_dir().map(|mut p| {
+        p.push("dashboard-native");
+        p.push("snapshots.json");
+        p
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::graph::types::GraphState;
+    use crate::settings::Settings;
+
+    fn snapshot(name: &str, ids: &[&str]) -> LayoutSnapshot {
+        let positions: HashMap<String, Pos2> = ids.iter()
+            .enumerate()
+            .map(|(i, id)| (id.to_string(), Pos2::new(i as f32, -(i as f32))))
+            .collect();
+        let settings = Preset::from_settings(name.to_string(), &Settings::default(), &GraphState::new());
+        LayoutSnapshot::new(name.to_string(), 0.0, &positions, [1.0, 2.0], 0.5, settings)
+    }
+
+    #[test]
+    fn test_restore_only_touches_existing_ids() {
+        let snap = snapshot("a", &["x", "y", "gone", "gone2"]);
+        let mut positions: HashMap<String, Pos2> = [("x", 9.0), ("y", 9.0), ("new", 9.0)]
+            .iter()
+            .map(|(id, v)| (id.to_string(), Pos2::new(*v, *v)))
+            .collect();
+        assert_eq!(snap.match_fraction(|id| positions.contains_key(id)), 0.5);
+        assert_eq!(snap.restore_positions(&mut positions), 2);
+        assert_eq!(positions["x"], Pos2::new(0.0, 0.0));
+        assert_eq!(positions["y"], Pos2::new(1.0, -1.0));
+        assert_eq!(positions["new"], Pos2::new(9.0, 9.0));
+    }
+
+    #[test]
+    fn test_history_is_bounded_and_names_unique() {
+        let mut history = SnapshotHistory::default();
+        for i in 0..MAX_SNAPSHOTS + 3 {
+            history.push(snapshot(&format!("s{}", i), &["x"]));
+        }
+        assert_eq!(history.snapshots.len(), MAX_SNAPSHOTS);
+        assert_eq!(history.snapshots[0].name, "s3");
+
+        history.push(snapshot("s5", &["x", "y"]));
+        assert_eq!(history.snapshots.len(), MAX_SNAPSHOTS);
+        assert_eq!(history.snapshots.last().map(|s| s.positions.len()), Some(2));
+        assert_eq!(history.snapshots.iter().filter(|s| s.name == "s5").count(), 1);
+    }
+
+    #[test]
+    fn test_loading_rewrites_bare_message_ids() {
+        let mut history = SnapshotHistory::default();
+        history.push(snapshot("old", &["7", "topic:sqlite", "msg:8"]));
+        let json = serde_json::to_string(&history).unwrap();
+        let loaded = SnapshotHistory::from_json(&json).unwrap();
+        let mut keys: Vec<&str> = loaded.snapshots[0].positions.keys().map(String::as_str).collect();
+        keys.sort();
+        assert_eq!(keys, ["msg:7", "msg:8", "topic:sqlite"]);
+        assert_eq!(loaded.snapshots[0].positions["msg:7"], [0.0, 0.0]);
+    }
+}
@@ -0,0 +1,171 @@
+//! Summary analytics over the loaded graph: daily volume, top projects,
+//! busiest hours, session length and the assistant:user ratio.
+
+use crate::cost::TokenTotals;
+use crate::graph::types::{GraphNode, Role};
+use chrono::{DateTime, NaiveDate, TimeZone, Timelike};
+use std::collections::HashMap;
+
+/// Days shown in the messages-per-day chart, ending today
+pub const DAYS: usize = 14;
+
+/// Projects listed by token volume
+pub const TOP_PROJECTS: usize = 5;
+
+/// Messages on one calendar day
+#[derive(Debug, Clone, PartialEq)]
+pub struct DayCount {
+    pub date: NaiveDate,
+    /// Local midnight at the start and end of the day, as epoch seconds
+    pub start: f64,
+    pub end: f64,
+    pub messages: usize,
+}
+
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct ActivityStats {
+    /// The last `DAYS` days, oldest first
+    pub daily: Vec<DayCount>,
+    /// Up to `TOP_PROJECTS` projects with the most tokens, largest first
+    pub top_projects: Vec<(String, u64)>,
+    /// Messages per local hour of day
+    pub hourly: [usize; 24],
+    /// Mean first-to-last message span over sessions with timestamps
+    pub avg_session_secs: Option<f64>,
+    /// Assistant messages per user message
+    pub assistant_per_user: Option<f64>,
+}
+
+impl ActivityStats {
+    /// Compute everything in one pass, bucketing days and hours in `tz`
+    pub fn compute<Tz: TimeZone>(nodes: &[GraphNode], tz: &Tz, today: NaiveDate) -> Self {
+        let mut stats = Self {
+            daily: (0..DAYS)
+                .rev()
+                .filter_map(|ago| today.checked_sub_days(chrono::Days::new(ago as u64)))
+                .map(|date| DayCount {
+                    date,
+                    start: local_midnight(tz, date),
+                    end: date.succ_opt().map_or(f64::MAX, |next| local_midnight(tz, next)),
+                    messages: 0,
+                })
+                .collect(),
+            ..Self::default()
+        };
+
+        let mut project_tokens: HashMap<&str, TokenTotals> = HashMap::new();
+        let mut session_spans: HashMap<&str, (f64, f64)> = HashMap::new();
+        let (mut users, mut assistants) = (0usize, 0usize);
+
+        for node in nodes {
+            match node.role {
+                Role::User => users += 1,
+                Role::Assistant => assistants += 1,
+                // Vault notes and summary topics aren't messages
+                Role::Obsidian | Role::Topic => continue,
+                _ => {}
+            }
+            project_tokens.entry(node.project.as_str()).or_default().add_node(node);
+
+            let Some(t) = node.timestamp_secs() else {
+                continue;
+            };
+            let span = session_spans.entry(node.session_id.as_str()).or_insert((t, t));
+            span.0 = span.0.min(t);
+            span.1 = span.1.max(t);
+
+            let Some(local) = DateTime::from_timestamp(t.floor() as i64, 0).map(|utc| utc.with_timezone(tz)) else {
+                continue;
+            };
+            stats.hourly[local.hour() as usize] += 1;
+            let date = local.date_naive();
+            if let Some(day) = stats.daily.iter_mut().find(|day| day.date == date) {
+                day.messages += 1;
+            }
+        }
+
+        let mut projects: Vec<(String, u64)> = project_tokens.into_iter()
+            .map(|(project, totals)| (project.to_string(), totals.total()))
+            .filter(|(_, tokens)| *tokens > 0)
+            .collect();
+        projects.sort_by(|a, b| b.1.cmp(&a.1).then_with(|| a.0.cmp(&b.0)));
+        projects.truncate(TOP_PROJECTS);
+        stats.top_projects = projects;
+
+        if !session_spans.is_empty() {
+            let total: f64 = session_spans.values().map(|(start, end)| end - start).sum();
+            stats.avg_session_secs = Some(total / session_spans.len() as f64);
+        }
+        if users > 0 {
+            stats.assistant_per_user = Some(assistants as f64 / users as f64);
+        }
+        stats
+    }
+}
+
+/// Midnight of `date` in `tz` as epoch seconds (the earlier instant if ambiguous)
+fn local_midnight<Tz: TimeZone>(tz: &Tz, date: NaiveDate) -> f64 {
+    let midnight = date.and_hms_opt(0, 0, 0).unwrap_or_default();
+    tz.from_local_datetime(&midnight)
+        .earliest()
+        .map_or_else(|| midnight.and_utc().timestamp() as f64, |dt| dt.timestamp() as f64)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::Utc;
+
+    fn node(id: &str, session: &str, project: &str, role: Role, timestamp: &str, tokens: i32) -> GraphNode {
+        GraphNode {
+            role,
+            project: project.to_string(),
+            timestamp: Some(timestamp.to_string()),
+            output_tokens: Some(tokens),
+            ..GraphNode::stub(id, session)
+        }
+    }
+
+    #[test]
+    fn test_activity_stats() {
+        let nodes = vec![
+            node("1", "a", "alpha", Role::User, "2025-06-14T09:00:00Z", 0),
+            node("2", "a", "alpha", Role::Assistant, "2025-06-14T09:30:00Z", 500),
+            node("3", "a", "alpha", Role::Assistant, "2025-06-14T10:00:00Z", 100),
+            node("4", "b", "beta", Role::User, "2025-06-10T23:00:00Z", 0),
+            node("5", "b", "beta", Role::Assistant, "2025-06-10T23:00:00Z", 50),
+            // Outside the 14-day chart but still counted elsewhere
+            node("6", "c", "gamma", Role::Assistant, "2025-05-01T09:00:00Z", 10),
+        ];
+        let today = NaiveDate::from_ymd_opt(2025, 6, 14).unwrap();
+        let stats = ActivityStats::compute(&nodes, &Utc, today);
+
+        assert_eq!(stats.daily.len(), DAYS);
+        assert_eq!(stats.daily[0].date, NaiveDate::from_ymd_opt(2025, 6, 1).unwrap());
+        let last = stats.daily.last().unwrap();
+        assert_eq!((last.date, last.messages), (today, 3));
+        assert_eq!(last.end - last.start, 86_400.0);
+        assert_eq!(stats.daily[DAYS - 5].messages, 2);
+        assert_eq!(stats.daily.iter().map(|d| d.messages).sum::<usize>(), 5);
+
+        assert_eq!(stats.hourly[9], 3);
+        assert_eq!(stats.hourly[23], 2);
+        assert_eq!(
+            stats.top_projects,
+            vec![("alpha".to_string(), 600), ("beta".to_string(), 50), ("gamma".to_string(), 10)]
+        );
+        // Sessions of 3600s, 0s and 0s
+        assert_eq!(stats.avg_session_secs, Some(1200.0));
+        assert_eq!(stats.assistant_per_user, Some(2.0));
+    }
+
+    #[test]
+    fn test_empty_stats() {
+        let today = NaiveDate::from_ymd_opt(2025, 6, 14).unwrap();
+        let stats = ActivityStats::compute(&[], &Utc, today);
+        assert_eq!(stats.daily.len(), DAYS);
+        assert!(stats.top_projects.is_empty());
+        assert_eq!(stats.avg_session_secs, None);
+        assert_eq!(stats.assistant_per_user, None);
+    }
+}
@@ -0,0 +1,369 @@
+//! Machine-readable dump of the loaded graph's numbers for external tooling.
+//! `StatsExport` is the schema: field names and map keys are stable, maps are
+//! sorted, and `SCHEMA_VERSION` is bumped whenever a field changes meaning.
+
+use crate::cost::TokenTotals;
+use crate::graph::types::{GraphData, GraphNode, IssueStatus, MailItem, Role};
+use crate::tags::SessionTags;
+use serde::Serialize;
+use std::collections::{BTreeMap, HashSet};
+
+pub const SCHEMA_VERSION: u32 = 1;
+
+/// A span of time as epoch seconds plus the same instants in RFC 3339 (UTC)
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct TimeRange {
+    pub start: f64,
+    pub end: f64,
+    pub start_iso: String,
+    pub end_iso: String,
+}
+
+impl TimeRange {
+    pub fn new(start: f64, end: f64) -> Self {
+        Self { start, end, start_iso: iso(start), end_iso: iso(end) }
+    }
+}
+
+/// Which filters narrowed the export. Everything false/empty means the
+/// export covers every loaded message.
+#[derive(Debug, Clone, Default, PartialEq, Serialize)]
+pub struct ActiveFilters {
+    pub timeline_window: bool,
+    /// Minimum importance score, when the importance filter is on
+    pub min_importance: Option<f32>,
+    /// Projects left visible by the project filter; empty when it's off
+    pub projects: Vec<String>,
+    /// Names of semantic filters that aren't off
+    pub semantic: Vec<String>,
+    pub hide_tool_use: bool,
+    /// Session isolated from the token histogram
+    pub session: Option<String>,
+    /// Session tags the tag filter keeps; empty when it's off
+    pub tags: Vec<String>,
+}
+
+/// Token sums by class
+#[derive(Debug, Clone, Copy, Default, PartialEq, Serialize)]
+pub struct TokenSums {
+    pub input: u64,
+    pub output: u64,
+    pub cache_read: u64,
+    pub cache_creation: u64,
+    pub total: u64,
+}
+
+impl From<TokenTotals> for TokenSums {
+    fn from(t: TokenTotals) -> Self {
+        Self {
+            input: t.input,
+            output: t.output,
+            cache_read: t.cache_read,
+            cache_creation: t.cache_creation,
+            total: t.total(),
+        }
+    }
+}
+
+/// Totals over a set of messages
+#[derive(Debug, Clone, Default, PartialEq, Serialize)]
+pub struct Aggregate {
+    /// Message counts keyed by role ("user", "assistant", ...)
+    pub messages: BTreeMap<String, usize>,
+    pub tokens: TokenSums,
+    /// Messages that used at least one tool
+    pub tool_use_messages: usize,
+    pub first: Option<String>,
+    pub last: Option<String>,
+    #[serde(skip)]
+    span: Option<(f64, f64)>,
+    #[serde(skip)]
+    totals: TokenTotals,
+}
+
+impl Aggregate {
+    fn add(&mut self, node: &GraphNode) {
+        *self.messages.entry(role_key(&node.role).to_string()).or_default() += 1;
+        self.totals.add_node(node);
+        if node.has_tool_usage {
+            self.tool_use_messages += 1;
+        }
+        if let Some(t) = node.timestamp_secs() {
+            let span = self.span.get_or_insert((t, t));
+            span.0 = span.0.min(t);
+            span.1 = span.1.max(t);
+        }
+    }
+
+    fn finish(&mut self) {
+        self.tokens = self.totals.into();
+        if let Some((first, last)) = self.span {
+            self.first = Some(iso(first));
+            self.last = Some(iso(last));
+        }
+    }
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Serialize)]
+pub struct SessionStats {
+    pub project: String,
+    /// User-defined tags, sorted
+    pub tags: Vec<String>,
+    #[serde(flatten)]
+    pub totals: Aggregate,
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Serialize)]
+pub struct MailTotals {
+    pub messages: usize,
+    pub unread: usize,
+    pub threads: usize,
+    pub senders: usize,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct StatsExport {
+    pub schema_version: u32,
+    pub exported_at: String,
+    /// Hours of history loaded from the backend
+    pub loaded_hours: f32,
+    /// First to last timestamp among all loaded messages
+    pub loaded_range: Option<TimeRange>,
+    /// The timeline window, when it narrows the export
+    pub window: Option<TimeRange>,
+    pub filters: ActiveFilters,
+    pub totals: Aggregate,
+    pub projects: BTreeMap<String, Aggregate>,
+    /// Keyed by full session id
+    pub sessions: BTreeMap<String, SessionStats>,
+    /// Bead counts keyed by status ("open", "in_progress", ...), created in the window
+    pub beads: BTreeMap<String, usize>,
+    pub mail: MailTotals,
+}
+
+impl StatsExport {
+    /// Aggregate the messages `include` keeps. Beads and mail have no project
+    /// or session, so only `window` narrows them. Vault notes and topics
+    /// aren't messages and are left out.
+    pub fn build(
+        data: &GraphData,
+        include: impl Fn(&GraphNode) -> bool,
+        window: Option<(f64, f64)>,
+        filters: ActiveFilters,
+        loaded_hours: f32,
+        exported_at: f64,
+    ) -> Self {
+        let mut totals = Aggregate::default();
+        let mut projects: BTreeMap<String, Aggregate> = BTreeMap::new();
+        let mut sessions: BTreeMap<String, SessionStats> = BTreeMap::new();
+        let mut loaded: Option<(f64, f64)> = None;
+
+        for node in &data.nodes {
+            if matches!(node.role, Role::Obsidian | Role::Topic) {
+                continue;
+            }
+            if let Some(t) = node.timestamp_secs() {
+                let span = loaded.get_or_insert((t, t));
+                span.0 = span.0.min(t);
+                span.1 = span.1.max(t);
+            }
+            if !include(node) {
+                continue;
+            }
+            totals.add(node);
+            projects.entry(node.project.clone()).or_default().add(node);
+            sessions.entry(node.session_id.clone())
+                .or_insert_with(|| SessionStats { project: node.project.clone(), ..Default::default() })
+                .totals
+                .add(node);
+        }
+        totals.finish();
+        projects.values_mut().for_each(Aggregate::finish);
+        sessions.values_mut().for_each(|s| s.totals.finish());
+
+        let in_window = |t: Option<f64>| match (window, t) {
+            (None, _) => true,
+            (Some((start, end)), Some(t)) => t >= start && t <= end,
+            (Some(_), None) => false,
+        };
+
+        let mut beads: BTreeMap<String, usize> = BTreeMap::new();
+        for bead in data.beads.iter().filter(|b| in_window(b.timestamp_secs())) {
+            *beads.entry(status_key(bead.status).to_string()).or_default() += 1;
+        }
+
+        let mail_items: Vec<&MailItem> = data.mail.iter().filter(|m| in_window(m.timestamp_secs())).collect();
+        let mail = MailTotals {
+            messages: mail_items.len(),
+            unread: mail_items.iter().filter(|m| m.is_unread).count(),
+            threads: mail_items.iter()
+                .map(|m| m.thread_id.as_deref().unwrap_or(m.id.as_str()))
+                .collect::<HashSet<_>>()
+                .len(),
+            senders: mail_items.iter().map(|m| m.sender.as_str()).collect::<HashSet<_>>().len(),
+        };
+
+        Self {
+            schema_version: SCHEMA_VERSION,
+            exported_at: iso(exported_at),
+            loaded_hours,
+            loaded_range: loaded.map(|(start, end)| TimeRange::new(start, end)),
+            window: window.map(|(start, end)| TimeRange::new(start, end)),
+            filters,
+            totals,
+            projects,
+            sessions,
+            beads,
+            mail,
+        }
+    }
+
+    /// Fill in each exported session's tags
+    pub fn with_session_tags(mut self, tags: &SessionTags) -> Self {
+        for (session_id, stats) in &mut self.sessions {
+            stats.tags = tags.tags_for(session_id).to_vec();
+        }
+        self
+    }
+
+    pub fn to_json(&self) -> Result<String, String> {
+        serde_json::to_string_pretty(self).map_err(|e| format!("Failed to serialize stats: {}", e))
+    }
+}
+
+fn iso(secs: f64) -> String {
+    chrono::DateTime::from_timestamp(secs.floor() as i64, 0)
+        .map(|t| t.to_rfc3339_opts(chrono::SecondsFormat::Secs, true))
+        .unwrap_or_default()
+}
+
+fn role_key(role: &Role) -> &'static str {
+    match role {
+        Role::User => "user",
+        Role::Assistant => "assistant",
+        Role::Agent => "agent",
+        Role::Obsidian => "obsidian",
+        Role::Topic => "topic",
+    }
+}
+
+fn status_key(status: IssueStatus) -> &'static str {
+    match status {
+        IssueStatus::Open => "open",
+        IssueStatus::InProgress => "in_progress",
+        IssueStatus::Blocked => "blocked",
+        IssueStatus::Closed => "closed",
+        IssueStatus::Deferred => "deferred",
+        IssueStatus::Hooked => "hooked",
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::graph::types::BeadItem;
+
+    fn node(id: &str, session: &str, project: &str, role: Role, timestamp: &str, tokens: (i32, i32), tools: bool) -> GraphNode {
+        GraphNode {
+            role,
+            project: project.to_string(),
+            timestamp: Some(timestamp.to_string()),
+            output_tokens: Some(tokens.1),
+            input_tokens: Some(tokens.0),
+            cache_read_tokens: Some(tokens.0 * 10),
+            has_tool_usage: tools,
+            ..GraphNode::stub(id, session)
+        }
+    }
+
+    fn bead(id: &str, status: IssueStatus, created_at: &str) -> BeadItem {
+        BeadItem {
+            id: id.to_string(),
+            title: id.to_string(),
+            status,
+            labels: Vec::new(),
+            priority: 2,
+            created_at: Some(created_at.to_string()),
+            updated_at: None,
+            issue_type: None,
+            description: None,
+            assignee: None,
+        }
+    }
+
+    fn mail(id: &str, thread: &str, sender: &str, unread: bool, timestamp: &str) -> MailItem {
+        MailItem {
+            id: id.to_string(),
+            subject: String::new(),
+            sender: sender.to_string(),
+            recipient: "mayor".to_string(),
+            timestamp: Some(timestamp.to_string()),
+            thread_id: Some(thread.to_string()),
+            is_unread: unread,
+            preview: None,
+        }
+    }
+
+    #[test]
+    fn test_matches_golden_file() {
+        let mut data = GraphData::new(vec![
+            node("1", "s1", "~/alpha", Role::User, "2025-06-01T09:00:00Z", (100, 0), false),
+            node("2", "s1", "~/alpha", Role::Assistant, "2025-06-01T09:01:00Z", (0, 400), true),
+            node("3", "s2", "~/beta", Role::User, "2025-06-01T10:00:00Z", (50, 0), false),
+            node("4", "s2", "~/beta", Role::Assistant, "2025-06-01T10:05:00Z", (0, 200), false),
+            // Outside the window
+            node("5", "s3", "~/beta", Role::User, "2025-05-30T10:00:00Z", (999, 0), false),
+            // Hidden by the project filter
+            node("6", "s4", "~/gamma", Role::User, "2025-06-01T11:00:00Z", (7, 0), false),
+            node("n", "vault", "vault", Role::Obsidian, "2025-06-01T09:30:00Z", (0, 0), false),
+        ], Vec::new());
+        data.beads = vec![
+            bead("b1", IssueStatus::Open, "2025-06-01T08:00:00Z"),
+            bead("b2", IssueStatus::Open, "2025-06-01T12:00:00Z"),
+            bead("b3", IssueStatus::InProgress, "2025-06-01T12:30:00Z"),
+            bead("b4", IssueStatus::Closed, "2025-05-01T00:00:00Z"),
+        ];
+        data.mail = vec![
+            mail("m1", "t1", "witness", true, "2025-06-01T09:00:00Z"),
+            mail("m2", "t1", "refinery", false, "2025-06-01T09:10:00Z"),
+            mail("m3", "t2", "witness", false, "2025-06-01T13:00:00Z"),
+            mail("m4", "t3", "witness", true, "2025-05-01T00:00:00Z"),
+        ];
+
+        let window = (1748736000.0, 1748822399.0); // 2025-06-01, UTC
+        let filters = ActiveFilters {
+            timeline_window: true,
+            projects: vec!["~/alpha".to_string(), "~/beta".to_string()],
+            ..Default::default()
+        };
+        let export = StatsExport::build(
+            &data,
+            |n| n.project != "~/gamma" && n.timestamp_secs().is_some_and(|t| t >= window.0 && t <= window.1),
+            Some(window),
+            filters,
+            168.0,
+            1748822400.0,
+        )
+        .with_session_tags(&SessionTags::from_rows([("s1".to_string(), "demo-prep".to_string())]));
+        assert_ne!(export.filters, ActiveFilters::default());
+        let json = export.to_json().unwrap();
+        assert_eq!(json.trim(), include_str!("../testdata/stats_export.json").trim());
+    }
+
+    #[test]
+    fn test_unfiltered_export_counts_everything() {
+        let data = GraphData::new(vec![
+            node("1", "s1", "~/alpha", Role::User, "2025-06-01T09:00:00Z", (1, 0), false),
+            node("2", "s2", "~/beta", Role::Agent, "2025-06-02T09:00:00Z", (0, 2), true),
+        ], Vec::new());
+        let export = StatsExport::build(&data, |_| true, None, ActiveFilters::default(), 24.0, 0.0);
+        assert_eq!(export.filters, ActiveFilters::default());
+        assert_eq!(export.totals.messages.values().sum::<usize>(), 2);
+        assert_eq!(export.totals.tokens.total, 1 + 10 + 2);
+        assert_eq!(export.totals.tool_use_messages, 1);
+        assert_eq!(export.loaded_range, Some(TimeRange::new(1748768400.0, 1748854800.0)));
+        assert_eq!(export.sessions["s2"].project, "~/beta");
+        assert!(export.window.is_none());
+        assert_eq!(export.mail, MailTotals::default());
+    }
+}
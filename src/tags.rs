@@ -0,0 +1,178 @@
+//! User-defined session tags such as "demo-prep" or "billing-bug".
+//!
+//! Tags are stored in the `session_tags` table, so they survive reloads.
+//! `SessionTags` is the copy loaded for the UI, and `TagFilter` limits the
+//! graph to sessions that carry a selected tag.
+
+use std::collections::{BTreeMap, BTreeSet, HashMap};
+
+/// Suggestions offered while typing a tag
+pub const MAX_SUGGESTIONS: usize = 6;
+
+/// Canonical form of a tag: trimmed, lowercase, inner whitespace as `-`.
+/// None for blank input.
+pub fn normalize(tag: &str) -> Option<String> {
+    let words: Vec<String> = tag.split_whitespace().map(str::to_lowercase).collect();
+    (!words.is_empty()).then(|| words.join("-"))
+}
+
+/// Tags per session, each list sorted
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct SessionTags {
+    by_session: HashMap<String, Vec<String>>,
+}
+
+impl SessionTags {
+    /// Build from `(session_id, tag)` rows
+    pub fn from_rows(rows: impl IntoIterator<Item = (String, String)>) -> Self {
+        let mut tags = Self::default();
+        for (session, tag) in rows {
+            tags.insert(&session, &tag);
+        }
+        tags
+    }
+
+    pub fn tags_for(&self, session_id: &str) -> &[String] {
+        self.by_session.get(session_id).map_or(&[], Vec::as_slice)
+    }
+
+    /// Add a tag to a session. False if it was already there.
+    pub fn insert(&mut self, session_id: &str, tag: &str) -> bool {
+        let tags = self.by_session.entry(session_id.to_string()).or_default();
+        match tags.binary_search_by(|t| t.as_str().cmp(tag)) {
+            Ok(_) => false,
+            Err(at) => {
+                tags.insert(at, tag.to_string());
+                true
+            }
+        }
+    }
+
+    /// Remove a tag from a session. False if it wasn't there.
+    pub fn remove(&mut self, session_id: &str, tag: &str) -> bool {
+        let Some(tags) = self.by_session.get_mut(session_id) else {
+            return false;
+        };
+        let Some(at) = tags.iter().position(|t| t == tag) else {
+            return false;
+        };
+        tags.remove(at);
+        if tags.is_empty() {
+            self.by_session.remove(session_id);
+        }
+        true
+    }
+
+    /// Every tag in use with the number of sessions carrying it
+    pub fn counts(&self) -> BTreeMap<&str, usize> {
+        let mut counts = BTreeMap::new();
+        for tag in self.by_session.values().flatten() {
+            *counts.entry(tag.as_str()).or_default() += 1;
+        }
+        counts
+    }
+
+    /// Existing tags containing `input`, prefix matches first, leaving out
+    /// the ones `session_id` already has
+    pub fn suggestions(&self, input: &str, session_id: &str) -> Vec<&str> {
+        let needle = normalize(input).unwrap_or_default();
+        let have = self.tags_for(session_id);
+        let mut matches: Vec<&str> = self.counts().into_keys()
+            .filter(|tag| tag.contains(needle.as_str()) && !have.iter().any(|h| h == tag))
+            .collect();
+        matches.sort_by_key(|tag| !tag.starts_with(needle.as_str()));
+        matches.truncate(MAX_SUGGESTIONS);
+        matches
+    }
+}
+
+/// Sessions to show by tag; empty shows everything
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct TagFilter {
+    pub tags: BTreeSet<String>,
+}
+
+impl TagFilter {
+    pub fn is_active(&self) -> bool {
+        !self.tags.is_empty()
+    }
+
+    pub fn toggle(&mut self, tag: &str) {
+        if !self.tags.remove(tag) {
+            self.tags.insert(tag.to_string());
+        }
+    }
+
+    /// A session passes when it has any selected tag
+    pub fn admits(&self, tags: &SessionTags, session_id: &str) -> bool {
+        !self.is_active() || tags.tags_for(session_id).iter().any(|t| self.tags.contains(t))
+    }
+
+    /// Drop selected tags no session carries any more
+    pub fn retain_existing(&mut self, tags: &SessionTags) {
+        let counts = tags.counts();
+        self.tags.retain(|t| counts.contains_key(t.as_str()));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn sample() -> SessionTags {
+        SessionTags::from_rows([
+            ("s1".to_string(), "demo-prep".to_string()),
+            ("s1".to_string(), "billing-bug".to_string()),
+            ("s2".to_string(), "billing-bug".to_string()),
+            ("s2".to_string(), "billing-bug".to_string()),
+        ])
+    }
+
+    #[test]
+    fn test_normalize() {
+        assert_eq!(normalize("  Demo Prep "), Some("demo-prep".to_string()));
+        assert_eq!(normalize("billing-bug"), Some("billing-bug".to_string()));
+        assert_eq!(normalize("   "), None);
+    }
+
+    #[test]
+    fn test_insert_remove_and_counts() {
+        let mut tags = sample();
+        assert_eq!(tags.tags_for("s1"), ["billing-bug", "demo-prep"]);
+        assert_eq!(tags.tags_for("s2"), ["billing-bug"]);
+        assert_eq!(tags.counts(), BTreeMap::from([("billing-bug", 2), ("demo-prep", 1)]));
+
+        assert!(!tags.insert("s2", "billing-bug"));
+        assert!(tags.remove("s2", "billing-bug"));
+        assert!(!tags.remove("s2", "billing-bug"));
+        assert!(tags.tags_for("s2").is_empty());
+        assert_eq!(tags.counts().get("billing-bug"), Some(&1));
+    }
+
+    #[test]
+    fn test_suggestions_skip_present_tags() {
+        let mut tags = sample();
+        tags.insert("s3", "prep-notes");
+        assert_eq!(tags.suggestions("prep", "s2"), vec!["prep-notes", "demo-prep"]);
+        assert_eq!(tags.suggestions("", "s1"), vec!["prep-notes"]);
+    }
+
+    #[test]
+    fn test_filter_admits_any_selected_tag() {
+        let tags = sample();
+        let mut filter = TagFilter::default();
+        assert!(filter.admits(&tags, "untagged"));
+
+        filter.toggle("demo-prep");
+        assert!(filter.admits(&tags, "s1"));
+        assert!(!filter.admits(&tags, "s2"));
+        assert!(!filter.admits(&tags, "untagged"));
+
+        filter.toggle("billing-bug");
+        assert!(filter.admits(&tags, "s2"));
+        filter.toggle("demo-prep");
+        filter.toggle("gone");
+        filter.retain_existing(&tags);
+        assert_eq!(filter.tags, BTreeSet::from(["billing-bug".to_string()]));
+    }
+}
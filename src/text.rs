@@ -0,0 +1,94 @@
+//! Character-safe shortening for labels, tooltips and previews.
+
+/// Marker appended to shortened text; it counts toward the limit
+pub const ELLIPSIS: char = '…';
+
+/// Shorten `s` to at most `max_chars` characters, ending in `…` when cut.
+/// Never splits a UTF-8 character, and keeps combining marks, variation
+/// selectors and zero-width-joiner sequences with their base character.
+pub fn truncate(s: &str, max_chars: usize) -> String {
+    if s.chars().count() <= max_chars {
+        return s.to_string();
+    }
+    if max_chars == 0 {
+        return String::new();
+    }
+
+    let mut end = s.char_indices().nth(max_chars - 1).map_or(s.len(), |(i, _)| i);
+    // Back off while the cut would separate a cluster from its extenders
+    while end > 0 {
+        let next_extends = s[end..].chars().next().is_some_and(extends_cluster);
+        let prev_joins = s[..end].ends_with('\u{200D}');
+        if !next_extends && !prev_joins {
+            break;
+        }
+        end = s[..end].char_indices().next_back().map_or(0, |(i, _)| i);
+    }
+
+    format!("{}{}", s[..end].trim_end(), ELLIPSIS)
+}
+
+/// Text up to and including the first sentence-ending `.`, `!` or `?` that's
+/// followed by whitespace or the end; the whole (trimmed) text if there's none
+pub fn first_sentence(s: &str) -> &str {
+    let s = s.trim();
+    let mut chars = s.char_indices().peekable();
+    while let Some((i, c)) = chars.next() {
+        if matches!(c, '.' | '!' | '?') && chars.peek().is_none_or(|(_, next)| next.is_whitespace()) {
+            return &s[..i + c.len_utf8()];
+        }
+    }
+    s
+}
+
+/// Characters that attach to the preceding one within a single visible glyph
+fn extends_cluster(c: char) -> bool {
+    matches!(c,
+        '\u{0300}'..='\u{036F}'       // combining diacritics
+        | '\u{200D}'                  // zero-width joiner
+        | '\u{20D0}'..='\u{20FF}'     // combining marks for symbols
+        | '\u{FE00}'..='\u{FE0F}'     // variation selectors
+        | '\u{1F3FB}'..='\u{1F3FF}'   // skin tone modifiers
+        | '\u{E0020}'..='\u{E007F}'   // emoji tag sequences
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_truncate_counts_ellipsis_and_respects_char_boundaries() {
+        assert_eq!(truncate("short", 10), "short");
+        assert_eq!(truncate("exactly10!", 10), "exactly10!");
+        assert_eq!(truncate("hello world", 8), "hello w…");
+        assert_eq!(truncate("hello world", 0), "");
+
+        // Multi-byte characters at and around the cut point
+        assert_eq!(truncate("日本語のテキスト", 4), "日本語…");
+        assert_eq!(truncate("ab🎉🎉🎉", 3), "ab…");
+        assert_eq!(truncate("ab🎉🎉🎉", 4), "ab🎉…");
+        for n in 0..12 {
+            let out = truncate("ça va? 🎉 日本 ok", n);
+            assert!(out.chars().count() <= n, "{} -> {:?}", n, out);
+        }
+    }
+
+    #[test]
+    fn test_first_sentence() {
+        assert_eq!(first_sentence("  Fixed the parser. Then added tests."), "Fixed the parser.");
+        assert_eq!(first_sentence("Bumped v1.2 again! Done"), "Bumped v1.2 again!");
+        assert_eq!(first_sentence("No full stop here"), "No full stop here");
+        assert_eq!(first_sentence(""), "");
+    }
+
+    #[test]
+    fn test_truncate_keeps_clusters_whole() {
+        // "e" + combining acute: never leave the bare "e"
+        assert_eq!(truncate("cafe\u{301} latte", 5), "caf…");
+        // Family emoji joined with ZWJs is dropped entirely rather than split
+        assert_eq!(truncate("hi 👨\u{200D}👩\u{200D}👧 there", 6), "hi…");
+        // Thumbs up with a skin tone stays together
+        assert_eq!(truncate("ok 👍\u{1F3FD} yes", 5), "ok…");
+    }
+}
@@ -0,0 +1,177 @@
+//! Line diff of two messages' contents, for retry pairs where an agent
+//! re-sent a nearly identical prompt. Common leading and trailing lines are
+//! matched directly and only the middle goes through an LCS table.
+
+/// Combined line count above which the diff runs on a worker thread
+pub const LAZY_DIFF_LINES: usize = 2000;
+/// Largest LCS table (cells) built; past it the middle counts as replaced
+const MAX_LCS_CELLS: usize = 4_000_000;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum LineChange {
+    Same,
+    Added,
+    Removed,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DiffLine {
+    pub change: LineChange,
+    pub text: String,
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct TextDiff {
+    pub lines: Vec<DiffLine>,
+    pub added: usize,
+    pub removed: usize,
+}
+
+impl TextDiff {
+    fn push(&mut self, change: LineChange, text: &str) {
+        match change {
+            LineChange::Added => self.added += 1,
+            LineChange::Removed => self.removed += 1,
+            LineChange::Same => {}
+        }
+        self.lines.push(DiffLine { change, text: text.to_string() });
+    }
+
+    /// "+3 / −1 lines", or "No differences"
+    pub fn summary(&self) -> String {
+        if self.added == 0 && self.removed == 0 {
+            "No differences".to_string()
+        } else {
+            format!("+{} / −{} lines", self.added, self.removed)
+        }
+    }
+}
+
+/// Two messages and the diff between them
+#[derive(Debug, Clone)]
+pub struct ContentDiff {
+    pub ids: [String; 2],
+    pub texts: [String; 2],
+    pub diff: TextDiff,
+}
+
+impl ContentDiff {
+    pub fn new(ids: [String; 2], texts: [String; 2]) -> Self {
+        let diff = diff_lines(&texts[0], &texts[1]);
+        Self { ids, texts, diff }
+    }
+}
+
+/// Whether diffing `a` against `b` is big enough to keep off the UI thread
+pub fn is_large(a: &str, b: &str) -> bool {
+    a.lines().count() + b.lines().count() > LAZY_DIFF_LINES
+}
+
+/// Line diff turning `a` into `b`. Within a changed run, removals come before additions.
+pub fn diff_lines(a: &str, b: &str) -> TextDiff {
+    let a: Vec<&str> = a.lines().collect();
+    let b: Vec<&str> = b.lines().collect();
+    let prefix = a.iter().zip(&b).take_while(|(x, y)| x == y).count();
+    let suffix = a[prefix..].iter().rev()
+        .zip(b[prefix..].iter().rev())
+        .take_while(|(x, y)| x == y)
+        .count();
+
+    let mut diff = TextDiff::default();
+    for line in &a[..prefix] {
+        diff.push(LineChange::Same, line);
+    }
+    diff_middle(&a[prefix..a.len() - suffix], &b[prefix..b.len() - suffix], &mut diff);
+    for line in &a[a.len() - suffix..] {
+        diff.push(LineChange::Same, line);
+    }
+    diff
+}
+
+fn diff_middle(a: &[&str], b: &[&str], diff: &mut TextDiff) {
+    let (n, m) = (a.len(), b.len());
+    if (n + 1).saturating_mul(m + 1) > MAX_LCS_CELLS {
+        a.iter().for_each(|line| diff.push(LineChange::Removed, line));
+        b.iter().for_each(|line| diff.push(LineChange::Added, line));
+        return;
+    }
+    // lcs[i * (m + 1) + j]: longest common subsequence of a[i..] and b[j..]
+    let width = m + 1;
+    let mut lcs = vec![0u32; (n + 1) * width];
+    for i in (0..n).rev() {
+        for j in (0..m).rev() {
+            lcs[i * width + j] = if a[i] == b[j] {
+                lcs[(i + 1) * width + j + 1] + 1
+            } else {
+                lcs[(i + 1) * width + j].max(lcs[i * width + j + 1])
+            };
+        }
+    }
+
+    let (mut i, mut j) = (0, 0);
+    while i < n && j < m {
+        if a[i] == b[j] {
+            diff.push(LineChange::Same, a[i]);
+            i += 1;
+            j += 1;
+        } else if lcs[(i + 1) * width + j] >= lcs[i * width + j + 1] {
+            diff.push(LineChange::Removed, a[i]);
+            i += 1;
+        } else {
+            diff.push(LineChange::Added, b[j]);
+            j += 1;
+        }
+    }
+    a[i..].iter().for_each(|line| diff.push(LineChange::Removed, line));
+    b[j..].iter().for_each(|line| diff.push(LineChange::Added, line));
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn changes(diff: &TextDiff) -> Vec<(LineChange, &str)> {
+        diff.lines.iter().map(|l| (l.change, l.text.as_str())).collect()
+    }
+
+    #[test]
+    fn test_identical() {
+        let diff = diff_lines("fix the parser\nthen run tests", "fix the parser\nthen run tests");
+        assert!(diff.lines.iter().all(|l| l.change == LineChange::Same));
+        assert_eq!((diff.lines.len(), diff.added, diff.removed), (2, 0, 0));
+        assert_eq!(diff.summary(), "No differences");
+        assert_eq!(diff_lines("", ""), TextDiff::default());
+    }
+
+    #[test]
+    fn test_disjoint() {
+        let diff = diff_lines("a\nb", "c\nd");
+        use LineChange::*;
+        assert_eq!(changes(&diff), vec![(Removed, "a"), (Removed, "b"), (Added, "c"), (Added, "d")]);
+        assert_eq!(diff.summary(), "+2 / −2 lines");
+
+        let diff = diff_lines("", "only\nnew");
+        assert_eq!((diff.added, diff.removed), (2, 0));
+    }
+
+    #[test]
+    fn test_interleaved_changes() {
+        let diff = diff_lines("1\n2\n3\n4\n5", "1\nx\n3\n5\ny");
+        use LineChange::*;
+        assert_eq!(
+            changes(&diff),
+            vec![(Same, "1"), (Removed, "2"), (Added, "x"), (Same, "3"), (Removed, "4"), (Same, "5"), (Added, "y")]
+        );
+        assert_eq!((diff.added, diff.removed), (2, 2));
+    }
+
+    #[test]
+    fn test_retry_with_one_line_changed() {
+        let a = "Run the migration.\nUse --dry-run first.\nReport the row counts.";
+        let b = "Run the migration.\nSkip the dry run, it timed out.\nReport the row counts.";
+        let diff = diff_lines(a, b);
+        assert_eq!((diff.added, diff.removed, diff.lines.len()), (1, 1, 4));
+        assert_eq!(diff.lines[1].change, LineChange::Removed);
+        assert_eq!(diff.lines[2].text, "Skip the dry run, it timed out.");
+    }
+}
@@ -0,0 +1,214 @@
+//! Cache of laid-out text for painter-drawn labels.
+//!
+//! Tooltips, node badges and axis labels are laid out every frame with
+//! mostly the same strings. `TextLayoutCache` keeps the resulting galleys
+//! keyed by text hash, font, color and wrap width, evicting the least
+//! recently used entry once full. It is cleared when pixels-per-point or the
+//! theme changes, since both affect glyph rasterization.
+
+use std::collections::HashMap;
+use std::hash::{DefaultHasher, Hash, Hasher};
+use std::sync::Arc;
+
+use eframe::egui::{self, Color32, FontFamily, FontId, Galley, Painter, Pos2, Rect};
+
+/// Galleys kept before the least recently used one is evicted
+pub const TEXT_CACHE_CAPACITY: usize = 1024;
+
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+struct LayoutKey {
+    text: u64,
+    size_bits: u32,
+    family: FontFamily,
+    color: Color32,
+    /// `f32::INFINITY` for unwrapped text
+    wrap_bits: u32,
+}
+
+impl LayoutKey {
+    fn new(text: &str, font: &FontId, color: Color32, wrap_width: f32) -> Self {
+        let mut hasher = DefaultHasher::new();
+        text.hash(&mut hasher);
+        Self {
+            text: hasher.finish(),
+            size_bits: font.size.to_bits(),
+            family: font.family.clone(),
+            color,
+            wrap_bits: wrap_width.to_bits(),
+        }
+    }
+}
+
+#[derive(Debug)]
+pub struct TextLayoutCache {
+    entries: HashMap<LayoutKey, (Arc<Galley>, u64)>,
+    capacity: usize,
+    /// Bumped on every lookup; entries remember the tick they were last used
+    tick: u64,
+    hits: u64,
+    misses: u64,
+    pixels_per_point: f32,
+    theme: egui::Theme,
+}
+
+impl Default for TextLayoutCache {
+    fn default() -> Self {
+        Self::with_capacity(TEXT_CACHE_CAPACITY)
+    }
+}
+
+impl TextLayoutCache {
+    pub fn with_capacity(capacity: usize) -> Self {
+        Self {
+            entries: HashMap::new(),
+            capacity: capacity.max(1),
+            tick: 0,
+            hits: 0,
+            misses: 0,
+            pixels_per_point: 0.0,
+            theme: egui::Theme::Dark,
+        }
+    }
+
+    /// Drop every galley if the scale or theme changed since the last frame
+    pub fn begin_frame(&mut self, pixels_per_point: f32, theme: egui::Theme) {
+        if pixels_per_point != self.pixels_per_point || theme != self.theme {
+            self.clear();
+            self.pixels_per_point = pixels_per_point;
+            self.theme = theme;
+        }
+    }
+
+    pub fn clear(&mut self) {
+        self.entries.clear();
+        self.hits = 0;
+        self.misses = 0;
+    }
+
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    /// Fraction of lookups served from the cache since it was last cleared
+    pub fn hit_rate(&self) -> Option<f32> {
+        let total = self.hits + self.misses;
+        (total > 0).then(|| self.hits as f32 / total as f32)
+    }
+
+    /// Lay out `text`, wrapping at `wrap_width` (`f32::INFINITY` for none)
+    pub fn layout(&mut self, painter: &Painter, text: &str, font: FontId, color: Color32, wrap_width: f32) -> Arc<Galley> {
+        let key = LayoutKey::new(text, &font, color, wrap_width);
+        self.tick += 1;
+        if let Some((galley, used)) = self.entries.get_mut(&key) {
+            *used = self.tick;
+            self.hits += 1;
+            return galley.clone();
+        }
+        self.misses += 1;
+        let galley = painter.layout(text.to_string(), font, color, wrap_width);
+        if self.entries.len() >= self.capacity {
+            self.evict_oldest();
+        }
+        self.entries.insert(key, (galley.clone(), self.tick));
+        galley
+    }
+
+    pub fn layout_no_wrap(&mut self, painter: &Painter, text: &str, font: FontId, color: Color32) -> Arc<Galley> {
+        self.layout(painter, text, font, color, f32::INFINITY)
+    }
+
+    /// Cached counterpart of `Painter::text`: paints `text` anchored at `pos`
+    /// and returns the rect it covers
+    pub fn text(
+        &mut self,
+        painter: &Painter,
+        pos: Pos2,
+        anchor: egui::Align2,
+        text: &str,
+        font: FontId,
+        color: Color32,
+    ) -> Rect {
+        let galley = self.layout_no_wrap(painter, text, font, color);
+        let rect = anchor.anchor_size(pos, galley.size());
+        if !galley.is_empty() {
+            painter.galley(rect.min, galley, color);
+        }
+        rect
+    }
+
+    fn evict_oldest(&mut self) {
+        let oldest = self.entries.iter()
+            .min_by_key(|(_, (_, used))| *used)
+            .map(|(key, _)| key.clone());
+        if let Some(key) = oldest {
+            self.entries.remove(&key);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Run `f` with a painter inside a frame, so fonts are available
+    fn with_painter(f: impl FnOnce(&Painter)) {
+        let ctx = egui::Context::default();
+        let mut f = Some(f);
+        let _ = ctx.run(egui::RawInput::default(), |ctx| {
+            if let Some(f) = f.take() {
+                f(&ctx.layer_painter(egui::LayerId::background()));
+            }
+        });
+    }
+
+    #[test]
+    fn test_repeated_layout_hits() {
+        with_painter(|painter| {
+            let mut cache = TextLayoutCache::default();
+            let font = FontId::proportional(12.0);
+            let first = cache.layout_no_wrap(painter, "hello", font.clone(), Color32::WHITE);
+            let second = cache.layout_no_wrap(painter, "hello", font.clone(), Color32::WHITE);
+            assert!(Arc::ptr_eq(&first, &second));
+            assert_eq!(cache.hit_rate(), Some(0.5));
+
+            // A different color or size is a separate galley
+            cache.layout_no_wrap(painter, "hello", font, Color32::RED);
+            cache.layout_no_wrap(painter, "hello", FontId::proportional(14.0), Color32::WHITE);
+            assert_eq!(cache.len(), 3);
+        });
+    }
+
+    #[test]
+    fn test_evicts_least_recently_used() {
+        with_painter(|painter| {
+            let mut cache = TextLayoutCache::with_capacity(2);
+            let font = FontId::proportional(12.0);
+            cache.layout_no_wrap(painter, "a", font.clone(), Color32::WHITE);
+            cache.layout_no_wrap(painter, "b", font.clone(), Color32::WHITE);
+            cache.layout_no_wrap(painter, "a", font.clone(), Color32::WHITE);
+            cache.layout_no_wrap(painter, "c", font.clone(), Color32::WHITE);
+            assert_eq!(cache.len(), 2);
+
+            // "a" was used more recently than "b", so it survived
+            cache.layout_no_wrap(painter, "a", font, Color32::WHITE);
+            assert_eq!(cache.hits, 2);
+        });
+    }
+
+    #[test]
+    fn test_scale_or_theme_change_clears() {
+        with_painter(|painter| {
+            let mut cache = TextLayoutCache::default();
+            cache.begin_frame(1.0, egui::Theme::Dark);
+            cache.layout_no_wrap(painter, "a", FontId::proportional(12.0), Color32::WHITE);
+            cache.begin_frame(1.0, egui::Theme::Dark);
+            assert_eq!(cache.len(), 1);
+            cache.begin_frame(2.0, egui::Theme::Dark);
+            assert_eq!(cache.len(), 0);
+            cache.layout_no_wrap(painter, "a", FontId::proportional(12.0), Color32::WHITE);
+            cache.begin_frame(2.0, egui::Theme::Light);
+            assert_eq!(cache.len(), 0);
+            assert_eq!(cache.hit_rate(), None);
+        });
+    }
+}
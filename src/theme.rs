@@ -1,180 +1,594 @@
-//! Unified theme and color constants for the dashboard.
+//! Unified theme and color palettes for the dashboard.
 //!
-//! This module provides a consistent color palette that bridges
-//! the graph visualization and UI panels. All colors should be
+//! Theme-dependent colors (backgrounds, text, borders, accents, timeline)
+//! live in a `Palette` with dark and light instances; look one up with
+//! `palette()`, or `current()` from a context. A user theme file (see
+//! `theme_file`) may override entries via `install()`. All colors should be
 //! sourced from here to maintain visual consistency.
 
 use egui::Color32;
+use serde::{Deserialize, Serialize};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::RwLock;
+
+/// User-selected color theme
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+pub enum Theme {
+    #[default]
+    Dark,
+    Light,
+    /// Follow the operating system setting
+    System,
+}
 
-/// Background colors for different layers
-pub mod bg {
-    use super::*;
+impl Theme {
+    /// Display label for the theme
+    pub fn label(&self) -> &'static str {
+        match self {
+            Theme::Dark => "Dark",
+            Theme::Light => "Light",
+            Theme::System => "System",
+        }
+    }
 
-    /// Main graph area background - darkest layer
-    pub const GRAPH: Color32 = Color32::from_rgb(14, 17, 23);
+    /// All themes for UI iteration
+    pub fn all() -> &'static [Theme] {
+        &[Theme::Dark, Theme::Light, Theme::System]
+    }
 
-    /// Panel backgrounds - slightly lighter than graph
-    pub const PANEL: Color32 = Color32::from_rgb(20, 22, 28);
+    /// The egui preference to install for this theme
+    pub fn preference(&self) -> egui::ThemePreference {
+        match self {
+            Theme::Dark => egui::ThemePreference::Dark,
+            Theme::Light => egui::ThemePreference::Light,
+            Theme::System => egui::ThemePreference::System,
+        }
+    }
+}
 
+/// Background colors for different layers
+#[derive(Debug, Clone, Copy)]
+pub struct BgColors {
+    /// Main graph area background - deepest layer
+    pub graph: Color32,
+    /// Panel backgrounds
+    pub panel: Color32,
     /// Card/elevated surface backgrounds
-    pub const SURFACE: Color32 = Color32::from_rgb(28, 30, 38);
-
+    pub surface: Color32,
     /// Interactive element backgrounds (buttons, inputs)
-    pub const INTERACTIVE: Color32 = Color32::from_rgb(35, 38, 48);
-
+    pub interactive: Color32,
     /// Hover state for interactive elements
-    pub const INTERACTIVE_HOVER: Color32 = Color32::from_rgb(45, 48, 58);
-
+    pub interactive_hover: Color32,
     /// Active/pressed state for interactive elements
-    pub const INTERACTIVE_ACTIVE: Color32 = Color32::from_rgb(55, 58, 68);
-
+    pub interactive_active: Color32,
     /// Timeline track background
-    pub const TIMELINE_TRACK: Color32 = Color32::from_rgb(30, 33, 40);
+    pub timeline_track: Color32,
 }
 
-/// Accent colors that match the graph node roles
-pub mod accent {
-    use super::*;
+/// Text colors at different emphasis levels
+#[derive(Debug, Clone, Copy)]
+pub struct TextColors {
+    /// Primary text - high contrast
+    pub primary: Color32,
+    /// Secondary text - medium contrast
+    pub secondary: Color32,
+    /// Muted text - low contrast for less important info
+    pub muted: Color32,
+    /// Disabled text
+    pub disabled: Color32,
+}
 
-    /// Claude/Assistant orange - primary accent
-    pub const ORANGE: Color32 = Color32::from_rgb(255, 149, 0);
+/// Border colors
+#[derive(Debug, Clone, Copy)]
+pub struct BorderColors {
+    /// Subtle border for separators
+    pub subtle: Color32,
+    /// Default border for cards/panels
+    pub default: Color32,
+    /// Emphasized border for focused elements
+    pub focus: Color32,
+}
 
+/// Accent colors that match the graph node roles
+#[derive(Debug, Clone, Copy)]
+pub struct AccentColors {
+    /// Claude/Assistant orange - primary accent
+    pub orange: Color32,
     /// Orange with reduced opacity for highlights/selections
-    pub fn orange_subtle() -> Color32 {
-        Color32::from_rgba_unmultiplied(255, 149, 0, 80)
-    }
-
+    pub orange_subtle: Color32,
     /// Cyan for connections/links/references
-    pub const CYAN: Color32 = Color32::from_rgb(6, 182, 212);
-
+    pub cyan: Color32,
     /// Green for topics/success states
-    pub const GREEN: Color32 = Color32::from_rgb(34, 197, 94);
-
+    pub green: Color32,
     /// Purple for obsidian/notes
-    pub const PURPLE: Color32 = Color32::from_rgb(155, 89, 182);
-
+    pub purple: Color32,
     /// Blue for info/secondary actions
-    pub const BLUE: Color32 = Color32::from_rgb(59, 130, 246);
-
+    pub blue: Color32,
     /// Red for errors/exclusions
-    pub const RED: Color32 = Color32::from_rgb(239, 68, 68);
-
-    /// Yellow for selection highlighting
-    pub const YELLOW: Color32 = Color32::from_rgb(255, 220, 80);
+    pub red: Color32,
 }
 
-/// Text colors at different emphasis levels
-pub mod text {
-    use super::*;
-
-    /// Primary text - high contrast
-    pub const PRIMARY: Color32 = Color32::from_rgb(240, 240, 245);
+/// Hover and selection outlines
+#[derive(Debug, Clone, Copy)]
+pub struct HighlightColors {
+    /// Hover state border/outline
+    pub hover: Color32,
+    /// Selected state border/outline
+    pub selected: Color32,
+}
 
-    /// Secondary text - medium contrast
-    pub const SECONDARY: Color32 = Color32::from_rgb(180, 180, 190);
+/// Timeline scrubber colors
+#[derive(Debug, Clone, Copy)]
+pub struct TimelineColors {
+    /// Inactive histogram bar
+    pub bar_inactive: Color32,
+    /// Highlighted/hovered histogram bar
+    pub bar_highlight: Color32,
+    /// Bar inside the selected timeline window
+    pub bar_selected: Color32,
+    /// Tick marks/notches
+    pub notch: Color32,
+    /// Start handle
+    pub handle_start: Color32,
+    /// End handle
+    pub handle_end: Color32,
+}
 
-    /// Muted text - low contrast for less important info
-    pub const MUTED: Color32 = Color32::from_rgb(120, 125, 135);
+/// Skeleton loading placeholder colors
+#[derive(Debug, Clone, Copy)]
+pub struct SkeletonColors {
+    /// Base skeleton background
+    pub base: Color32,
+    /// Animated shimmer highlight
+    pub shimmer: Color32,
+}
 
-    /// Disabled text
-    pub const DISABLED: Color32 = Color32::from_rgb(80, 85, 95);
+/// Semantic filter button colors
+#[derive(Debug, Clone, Copy)]
+pub struct FilterColors {
+    /// Inactive filter button
+    pub inactive: Color32,
+    /// Active but neutral state
+    pub active: Color32,
+    /// Include mode
+    pub include: Color32,
+    /// Include+1 mode
+    pub include_plus1: Color32,
+    /// Include+2 mode
+    pub include_plus2: Color32,
+    /// Exclude mode
+    pub exclude: Color32,
 }
 
-/// Border colors
-pub mod border {
-    use super::*;
+/// Painted hover cards (graph nodes, mail network)
+#[derive(Debug, Clone, Copy)]
+pub struct TooltipColors {
+    pub bg: Color32,
+    pub text: Color32,
+    pub border: Color32,
+}
 
-    /// Subtle border for separators
-    pub const SUBTLE: Color32 = Color32::from_rgb(45, 48, 55);
+/// Node colors per message role
+#[derive(Debug, Clone, Copy)]
+pub struct RoleColors {
+    pub user: Color32,
+    pub assistant: Color32,
+    pub agent: Color32,
+    pub obsidian: Color32,
+    pub topic: Color32,
+}
 
-    /// Default border for cards/panels
-    pub const DEFAULT: Color32 = Color32::from_rgb(55, 58, 65);
+/// Saturation/lightness used to turn project and session hues into node colors
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct NodeShading {
+    pub project_saturation: f32,
+    pub project_lightness: f32,
+    pub session_saturation: f32,
+    pub session_lightness: f32,
+}
 
-    /// Emphasized border for focused elements
-    pub const FOCUS: Color32 = Color32::from_rgb(80, 85, 95);
+/// Token histogram colors for stacked session bars
+#[derive(Debug, Clone, Copy)]
+pub struct HistogramColors {
+    pub input: Color32,
+    pub output: Color32,
+    pub cache_read: Color32,
+    pub cache_create: Color32,
 }
 
-/// State colors for interactive elements
-pub mod state {
-    use super::*;
+/// Issue (bead) status colors
+#[derive(Debug, Clone, Copy)]
+pub struct StatusColors {
+    pub open: Color32,
+    pub in_progress: Color32,
+    pub blocked: Color32,
+    pub closed: Color32,
+    pub deferred: Color32,
+    pub hooked: Color32,
+}
 
-    /// Hover state border/outline
-    pub const HOVER: Color32 = Color32::WHITE;
+/// Every theme-dependent color
+#[derive(Debug, Clone, Copy)]
+pub struct Palette {
+    pub bg: BgColors,
+    pub text: TextColors,
+    pub border: BorderColors,
+    pub accent: AccentColors,
+    pub highlight: HighlightColors,
+    pub timeline: TimelineColors,
+    pub skeleton: SkeletonColors,
+    pub filter: FilterColors,
+    pub tooltip: TooltipColors,
+    pub role: RoleColors,
+    pub shading: NodeShading,
+    pub histogram: HistogramColors,
+    pub status: StatusColors,
+    /// Edge opacity multiplier; light backgrounds need stronger edges
+    pub edge_gamma: f32,
+    /// Multiplier for edge and node border stroke widths
+    pub stroke_scale: f32,
+    /// Multiplier for painted tooltip text
+    pub tooltip_text_scale: f32,
+    /// Whether nodes after the playhead are drawn desaturated
+    pub desaturate_future: bool,
+}
 
-    /// Selected state border/outline
-    pub const SELECTED: Color32 = super::accent::YELLOW;
+/// Role-matched accents, shared by both palettes
+const ACCENT: AccentColors = AccentColors {
+    orange: Color32::from_rgb(255, 149, 0),
+    orange_subtle: Color32::from_rgba_premultiplied(80, 47, 0, 80),
+    cyan: Color32::from_rgb(6, 182, 212),
+    green: Color32::from_rgb(34, 197, 94),
+    purple: Color32::from_rgb(155, 89, 182),
+    blue: Color32::from_rgb(59, 130, 246),
+    red: Color32::from_rgb(239, 68, 68),
+};
+
+/// Role colors, shared by both palettes
+const ROLE: RoleColors = RoleColors {
+    user: Color32::WHITE,
+    assistant: ACCENT.orange,
+    agent: Color32::from_rgb(0, 191, 255),
+    obsidian: ACCENT.purple,
+    topic: ACCENT.green,
+};
+
+/// Node shading, shared by both palettes
+const SHADING: NodeShading = NodeShading {
+    project_saturation: 0.7,
+    project_lightness: 0.55,
+    session_saturation: 0.7,
+    session_lightness: 0.5,
+};
+
+/// Token histogram colors, shared by both palettes
+const HISTOGRAM: HistogramColors = HistogramColors {
+    input: ACCENT.blue,
+    output: ACCENT.orange,
+    cache_read: ACCENT.green,
+    cache_create: ACCENT.purple,
+};
+
+/// Filter mode colors, shared by both palettes
+const FILTER_MODES: FilterColors = FilterColors {
+    inactive: Color32::from_rgb(50, 50, 60),
+    active: Color32::from_rgb(100, 100, 120),
+    include: ACCENT.green,
+    include_plus1: ACCENT.blue,
+    include_plus2: Color32::from_rgb(139, 92, 246),
+    exclude: ACCENT.red,
+};
+
+/// Issue status colors, shared by both palettes
+const STATUS: StatusColors = StatusColors {
+    open: ACCENT.blue,
+    in_progress: ACCENT.orange,
+    blocked: ACCENT.red,
+    closed: ACCENT.green,
+    deferred: Color32::from_rgb(120, 125, 135),
+    hooked: ACCENT.purple,
+};
+
+/// Dark palette (the original look)
+pub const DARK: Palette = Palette {
+    bg: BgColors {
+        graph: Color32::from_rgb(14, 17, 23),
+        panel: Color32::from_rgb(20, 22, 28),
+        surface: Color32::from_rgb(28, 30, 38),
+        interactive: Color32::from_rgb(35, 38, 48),
+        interactive_hover: Color32::from_rgb(45, 48, 58),
+        interactive_active: Color32::from_rgb(55, 58, 68),
+        timeline_track: Color32::from_rgb(30, 33, 40),
+    },
+    text: TextColors {
+        primary: Color32::from_rgb(240, 240, 245),
+        secondary: Color32::from_rgb(180, 180, 190),
+        muted: Color32::from_rgb(120, 125, 135),
+        disabled: Color32::from_rgb(80, 85, 95),
+    },
+    border: BorderColors {
+        subtle: Color32::from_rgb(45, 48, 55),
+        default: Color32::from_rgb(55, 58, 65),
+        focus: Color32::from_rgb(80, 85, 95),
+    },
+    accent: ACCENT,
+    highlight: HighlightColors {
+        hover: Color32::WHITE,
+        selected: Color32::from_rgb(255, 220, 80),
+    },
+    timeline: TimelineColors {
+        bar_inactive: Color32::from_rgb(80, 90, 110),
+        bar_highlight: Color32::from_rgb(100, 120, 150),
+        bar_selected: ACCENT.orange,
+        notch: Color32::from_rgb(60, 65, 75),
+        handle_start: Color32::from_rgb(100, 100, 120),
+        handle_end: ACCENT.orange,
+    },
+    skeleton: SkeletonColors {
+        base: Color32::from_rgb(35, 38, 48),
+        shimmer: Color32::from_rgb(50, 53, 63),
+    },
+    filter: FILTER_MODES,
+    tooltip: TooltipColors {
+        bg: Color32::from_rgba_premultiplied(18, 18, 27, 230),
+        text: Color32::WHITE,
+        border: Color32::from_rgb(80, 85, 95),
+    },
+    role: ROLE,
+    shading: SHADING,
+    histogram: HISTOGRAM,
+    status: STATUS,
+    edge_gamma: 1.0,
+    stroke_scale: 1.0,
+    tooltip_text_scale: 1.0,
+    desaturate_future: true,
+};
+
+/// Light palette
+pub const LIGHT: Palette = Palette {
+    bg: BgColors {
+        graph: Color32::from_rgb(246, 247, 250),
+        panel: Color32::from_rgb(236, 238, 242),
+        surface: Color32::from_rgb(255, 255, 255),
+        interactive: Color32::from_rgb(225, 228, 234),
+        interactive_hover: Color32::from_rgb(212, 216, 224),
+        interactive_active: Color32::from_rgb(198, 203, 213),
+        timeline_track: Color32::from_rgb(222, 226, 232),
+    },
+    text: TextColors {
+        primary: Color32::from_rgb(20, 22, 28),
+        secondary: Color32::from_rgb(70, 74, 84),
+        muted: Color32::from_rgb(115, 120, 130),
+        disabled: Color32::from_rgb(165, 170, 178),
+    },
+    border: BorderColors {
+        subtle: Color32::from_rgb(215, 218, 224),
+        default: Color32::from_rgb(196, 200, 208),
+        focus: Color32::from_rgb(150, 155, 165),
+    },
+    accent: ACCENT,
+    highlight: HighlightColors {
+        hover: Color32::from_rgb(20, 22, 28),
+        selected: Color32::from_rgb(202, 138, 4),
+    },
+    timeline: TimelineColors {
+        bar_inactive: Color32::from_rgb(170, 180, 198),
+        bar_highlight: Color32::from_rgb(110, 130, 165),
+        bar_selected: ACCENT.orange,
+        notch: Color32::from_rgb(180, 185, 195),
+        handle_start: Color32::from_rgb(120, 120, 140),
+        handle_end: ACCENT.orange,
+    },
+    skeleton: SkeletonColors {
+        base: Color32::from_rgb(226, 229, 235),
+        shimmer: Color32::from_rgb(240, 242, 246),
+    },
+    filter: FilterColors {
+        inactive: Color32::from_rgb(215, 218, 224),
+        active: Color32::from_rgb(170, 172, 190),
+        ..FILTER_MODES
+    },
+    tooltip: TooltipColors {
+        bg: Color32::from_rgba_premultiplied(240, 240, 240, 240),
+        text: Color32::from_rgb(20, 22, 28),
+        border: Color32::from_rgb(196, 200, 208),
+    },
+    role: RoleColors {
+        user: Color32::from_rgb(70, 74, 84),
+        ..ROLE
+    },
+    shading: SHADING,
+    histogram: HISTOGRAM,
+    status: STATUS,
+    edge_gamma: 1.6,
+    stroke_scale: 1.0,
+    tooltip_text_scale: 1.0,
+    desaturate_future: true,
+};
+
+/// Okabe-Ito categorical colors, distinguishable under the common color vision
+/// deficiencies (black swapped for grey so it shows on dark backgrounds)
+pub const OKABE_ITO: [Color32; 8] = [
+    Color32::from_rgb(230, 159, 0),   // Orange
+    Color32::from_rgb(86, 180, 233),  // Sky blue
+    Color32::from_rgb(0, 158, 115),   // Bluish green
+    Color32::from_rgb(240, 228, 66),  // Yellow
+    Color32::from_rgb(0, 114, 178),   // Blue
+    Color32::from_rgb(213, 94, 0),    // Vermillion
+    Color32::from_rgb(204, 121, 167), // Reddish purple
+    Color32::from_rgb(153, 153, 153), // Grey
+];
+
+/// Fixed 8-color palette for proximity query edges, by query index
+pub const QUERY_COLORS: [Color32; 8] = [
+    Color32::from_rgb(6, 182, 212),    // Cyan (original)
+    Color32::from_rgb(249, 115, 22),   // Orange
+    Color32::from_rgb(168, 85, 247),   // Purple
+    Color32::from_rgb(34, 197, 94),    // Green
+    Color32::from_rgb(239, 68, 68),    // Red
+    Color32::from_rgb(234, 179, 8),    // Yellow
+    Color32::from_rgb(236, 72, 153),   // Pink
+    Color32::from_rgb(59, 130, 246),   // Blue
+];
+
+/// Edge color for the `index`-th proximity query
+pub fn query_color(index: usize, colorblind_safe: bool) -> Color32 {
+    if colorblind_safe {
+        categorical(index)
+    } else {
+        QUERY_COLORS[index % QUERY_COLORS.len()]
+    }
+}
 
-    /// Active/focused state (e.g., summary node)
-    pub const ACTIVE: Color32 = super::accent::CYAN;
+/// Categorical color for the `slot`-th project/session. Past 8 slots the
+/// palette repeats, alternately lightened and darkened.
+pub fn categorical(slot: usize) -> Color32 {
+    let base = OKABE_ITO[slot % OKABE_ITO.len()];
+    let (target, amount) = match (slot / OKABE_ITO.len()) % 3 {
+        0 => return base,
+        1 => (255.0, 0.4),
+        _ => (0.0, 0.35),
+    };
+    let mix = |c: u8| (c as f32 + (target - c as f32) * amount).round() as u8;
+    Color32::from_rgb(mix(base.r()), mix(base.g()), mix(base.b()))
+}
 
-    /// Success indicator
-    pub const SUCCESS: Color32 = super::accent::GREEN;
+/// Replace red/green-dependent colors with colorblind-safe alternatives
+fn colorblind_safe_variant(mut p: Palette) -> Palette {
+    let [orange, sky_blue, bluish_green, _, blue, vermillion, reddish_purple, _] = OKABE_ITO;
+    p.accent.orange = orange;
+    p.accent.cyan = sky_blue;
+    p.accent.green = bluish_green;
+    p.accent.blue = blue;
+    p.accent.red = vermillion;
+    p.accent.purple = reddish_purple;
+    p.filter.include = blue;
+    p.filter.include_plus1 = sky_blue;
+    p.filter.include_plus2 = reddish_purple;
+    p.filter.exclude = orange;
+    p.role.assistant = orange;
+    p.role.agent = sky_blue;
+    p.role.topic = bluish_green;
+    p.role.obsidian = reddish_purple;
+    p.status.open = blue;
+    p.status.in_progress = orange;
+    p.status.blocked = reddish_purple;
+    p.status.closed = bluish_green;
+    p.status.hooked = sky_blue;
+    p
+}
 
-    /// Error indicator
-    pub const ERROR: Color32 = super::accent::RED;
+/// Whether the colorblind-safe variant is applied to every palette lookup
+static COLORBLIND_SAFE: AtomicBool = AtomicBool::new(false);
 
-    /// Warning indicator
-    pub const WARNING: Color32 = Color32::from_rgb(245, 158, 11);
+/// Turn the colorblind-safe variant on or off
+pub fn set_colorblind_safe(enabled: bool) {
+    COLORBLIND_SAFE.store(enabled, Ordering::Relaxed);
 }
 
-/// Timeline-specific colors
-pub mod timeline {
-    use super::*;
+/// Presentation variant: pure text colors, stronger borders and edges, no subtle fades
+fn high_contrast_variant(mut p: Palette, theme: egui::Theme) -> Palette {
+    let (text, muted, border) = match theme {
+        egui::Theme::Dark => (Color32::WHITE, Color32::from_rgb(200, 200, 210), Color32::from_rgb(150, 155, 165)),
+        egui::Theme::Light => (Color32::BLACK, Color32::from_rgb(60, 60, 70), Color32::from_rgb(90, 95, 105)),
+    };
+    p.text.primary = text;
+    p.text.secondary = text;
+    p.text.muted = muted;
+    p.border.subtle = border;
+    p.border.default = border;
+    p.border.focus = text;
+    p.highlight.hover = text;
+    p.tooltip.bg = p.bg.surface;
+    p.tooltip.text = text;
+    p.tooltip.border = text;
+    p.edge_gamma *= 2.0;
+    p.stroke_scale = 2.0;
+    p.tooltip_text_scale = 1.3;
+    p.desaturate_future = false;
+    p
+}
 
-    /// Inactive histogram bar
-    pub const BAR_INACTIVE: Color32 = Color32::from_rgb(80, 90, 110);
+/// Whether the high-contrast variant is applied to every palette lookup
+static HIGH_CONTRAST: AtomicBool = AtomicBool::new(false);
 
-    /// Highlighted/hovered histogram bar
-    pub const BAR_HIGHLIGHT: Color32 = Color32::from_rgb(100, 120, 150);
+/// Turn the high-contrast (presentation) variant on or off
+pub fn set_high_contrast(enabled: bool) {
+    HIGH_CONTRAST.store(enabled, Ordering::Relaxed);
+}
 
-    /// Bar within selected range
-    pub const BAR_SELECTED: Color32 = super::accent::ORANGE;
+static REDUCE_MOTION: AtomicBool = AtomicBool::new(false);
 
-    /// Tick marks/notches
-    pub const NOTCH: Color32 = Color32::from_rgb(60, 65, 75);
+/// Turn the reduced-motion mode on or off
+pub fn set_reduce_motion(enabled: bool) {
+    REDUCE_MOTION.store(enabled, Ordering::Relaxed);
+}
 
-    /// Start handle
-    pub const HANDLE_START: Color32 = Color32::from_rgb(100, 100, 120);
+/// Whether decorative animation may run. Anything that animates for its own
+/// sake (shimmer, pulses, easing, idle repaints) should check this.
+pub fn motion_enabled() -> bool {
+    !REDUCE_MOTION.load(Ordering::Relaxed)
+}
 
-    /// End handle
-    pub const HANDLE_END: Color32 = super::accent::ORANGE;
+/// Installed (dark, light) palettes: the built-ins plus any user overrides
+static INSTALLED: RwLock<(Palette, Palette)> = RwLock::new((DARK, LIGHT));
 
-    /// Playhead/current position
-    pub const PLAYHEAD: Color32 = super::accent::CYAN;
+/// Replace the installed palettes (e.g. after loading a theme file)
+pub fn install(dark: Palette, light: Palette) {
+    if let Ok(mut installed) = INSTALLED.write() {
+        *installed = (dark, light);
+    }
 }
 
-/// Skeleton loading placeholder colors
-pub mod skeleton {
-    use super::*;
-
-    /// Base skeleton background
-    pub const BASE: Color32 = Color32::from_rgb(35, 38, 48);
+/// Palette for a resolved egui theme
+pub fn palette(theme: egui::Theme) -> Palette {
+    let installed = INSTALLED.read().map(|p| *p).unwrap_or((DARK, LIGHT));
+    let palette = match theme {
+        egui::Theme::Dark => installed.0,
+        egui::Theme::Light => installed.1,
+    };
+    let palette = if COLORBLIND_SAFE.load(Ordering::Relaxed) {
+        colorblind_safe_variant(palette)
+    } else {
+        palette
+    };
+    if HIGH_CONTRAST.load(Ordering::Relaxed) {
+        high_contrast_variant(palette, theme)
+    } else {
+        palette
+    }
+}
 
-    /// Animated shimmer highlight
-    pub const SHIMMER: Color32 = Color32::from_rgb(50, 53, 63);
+/// Palette for the theme currently active in `ctx`
+pub fn current(ctx: &egui::Context) -> Palette {
+    palette(ctx.theme())
 }
 
-/// Semantic filter button colors
-pub mod filter {
+/// State colors for interactive elements (same in every theme)
+pub mod state {
     use super::*;
 
-    /// Inactive filter button
-    pub const INACTIVE: Color32 = Color32::from_rgb(50, 50, 60);
-
-    /// Active but neutral state
-    pub const ACTIVE: Color32 = Color32::from_rgb(100, 100, 120);
+    /// Active/focused state (e.g., summary node)
+    pub const ACTIVE: Color32 = super::ACCENT.cyan;
 
-    /// Include mode (green)
-    pub const INCLUDE: Color32 = super::accent::GREEN;
+    /// Success indicator
+    pub const SUCCESS: Color32 = super::ACCENT.green;
 
-    /// Include+1 mode (blue)
-    pub const INCLUDE_PLUS1: Color32 = super::accent::BLUE;
+    /// Error indicator
+    pub const ERROR: Color32 = super::ACCENT.red;
 
-    /// Include+2 mode (purple)
-    pub const INCLUDE_PLUS2: Color32 = Color32::from_rgb(139, 92, 246);
+    /// Warning indicator
+    pub const WARNING: Color32 = Color32::from_rgb(245, 158, 11);
+}
 
-    /// Exclude mode (red)
-    pub const EXCLUDE: Color32 = super::accent::RED;
+/// Scale every text style in `style` (sidebar/tooltip font-size bumps)
+pub fn scale_text(style: &mut egui::Style, factor: f32) {
+    if (factor - 1.0).abs() < f32::EPSILON {
+        return;
+    }
+    for font in style.text_styles.values_mut() {
+        font.size *= factor;
+    }
 }
 
 /// Helper to create a stroke with consistent styling
@@ -204,15 +618,21 @@ pub fn skeleton_rect(ui: &mut egui::Ui, width: f32, height: f32) {
         egui::Sense::hover(),
     );
 
+    let skeleton = current(ui.ctx()).skeleton;
+    if !motion_enabled() {
+        ui.painter().rect_filled(rect, 4.0, skeleton.base);
+        return;
+    }
+
     // Animate the shimmer effect
     let time = ui.ctx().input(|i| i.time);
     let phase = (time * 2.0).sin() * 0.5 + 0.5; // 0 to 1 oscillation
 
     // Interpolate between base and shimmer colors
     let color = Color32::from_rgb(
-        lerp_u8(skeleton::BASE.r(), skeleton::SHIMMER.r(), phase as f32),
-        lerp_u8(skeleton::BASE.g(), skeleton::SHIMMER.g(), phase as f32),
-        lerp_u8(skeleton::BASE.b(), skeleton::SHIMMER.b(), phase as f32),
+        lerp_u8(skeleton.base.r(), skeleton.shimmer.r(), phase as f32),
+        lerp_u8(skeleton.base.g(), skeleton.shimmer.g(), phase as f32),
+        lerp_u8(skeleton.base.b(), skeleton.shimmer.b(), phase as f32),
     );
 
     ui.painter().rect_filled(rect, 4.0, color);
@@ -245,24 +665,44 @@ fn lerp_u8(a: u8, b: u8, t: f32) -> u8 {
     result.clamp(0.0, 255.0) as u8
 }
 
-/// Token histogram colors for stacked session bars
-pub mod histogram {
-    use super::*;
-    pub const INPUT: Color32 = Color32::from_rgb(59, 130, 246);
-    pub const OUTPUT: Color32 = Color32::from_rgb(255, 149, 0);
-    pub const CACHE_READ: Color32 = Color32::from_rgb(34, 197, 94);
-    pub const CACHE_CREATE: Color32 = Color32::from_rgb(155, 89, 182);
-}
-
 #[cfg(test)]
 mod tests {
     use super::*;
 
     #[test]
     fn test_accent_colors_match_role_colors() {
-        // Verify accent colors match the Role::color() values in types.rs
-        assert_eq!(accent::ORANGE, Color32::from_rgb(255, 149, 0)); // Assistant
-        assert_eq!(accent::PURPLE, Color32::from_rgb(155, 89, 182)); // Obsidian
-        assert_eq!(accent::GREEN, Color32::from_rgb(34, 197, 94)); // Topic
+        // Verify accent colors match the Role::color() values in types.rs, in every palette
+        for palette in [&DARK, &LIGHT] {
+            assert_eq!(palette.accent.orange, Color32::from_rgb(255, 149, 0)); // Assistant
+            assert_eq!(palette.accent.purple, Color32::from_rgb(155, 89, 182)); // Obsidian
+            assert_eq!(palette.accent.green, Color32::from_rgb(34, 197, 94)); // Topic
+        }
+    }
+
+    #[test]
+    fn test_categorical_cycles_with_lightness_variation() {
+        let first: Vec<Color32> = (0..8).map(categorical).collect();
+        for (i, a) in first.iter().enumerate() {
+            assert!(first[i + 1..].iter().all(|b| a != b), "slot {} repeats within the first 8", i);
+        }
+        // Past 8 slots the hue repeats but lightness changes
+        assert_ne!(categorical(8), categorical(0));
+        assert_ne!(categorical(16), categorical(8));
+        assert_eq!(categorical(24), categorical(0));
+        let luma = |c: Color32| c.r() as u32 + c.g() as u32 + c.b() as u32;
+        assert!(luma(categorical(9)) > luma(categorical(1)));
+        assert!(luma(categorical(17)) < luma(categorical(1)));
+    }
+
+    #[test]
+    fn test_high_contrast_composes_with_theme() {
+        let dark = high_contrast_variant(DARK, egui::Theme::Dark);
+        let light = high_contrast_variant(LIGHT, egui::Theme::Light);
+        assert_eq!(dark.text.primary, Color32::WHITE);
+        assert_eq!(light.text.primary, Color32::BLACK);
+        // Backgrounds still come from the underlying theme
+        assert_eq!(light.bg.graph, LIGHT.bg.graph);
+        assert!(light.stroke_scale > LIGHT.stroke_scale);
+        assert!(!dark.desaturate_future);
     }
 }
@@ -0,0 +1,375 @@
+//! User theme file: palette overrides loaded from `theme.toml` in the config directory.
+//!
+//! The format is a small TOML subset: `[group]` headers followed by
+//! `key = "#RRGGBB"` (or `#RRGGBBAA`) colors, or plain numbers for the
+//! `shading` group. Prefix a group with `dark.` or `light.` to override only
+//! that palette. Missing keys keep their defaults; bad lines are reported
+//! with their line number and skipped. See `assets/theme.sample.toml`.
+
+use crate::theme::{self, Palette};
+use egui::Color32;
+use std::path::PathBuf;
+
+/// Which built-in palette(s) an override applies to
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Scope {
+    Both,
+    Dark,
+    Light,
+}
+
+/// A parsed override value
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Value {
+    Color(Color32),
+    Number(f32),
+}
+
+#[derive(Debug, Clone, PartialEq)]
+struct Override {
+    scope: Scope,
+    group: String,
+    key: String,
+    value: Value,
+}
+
+/// A problem on one line of the theme file
+#[derive(Debug, Clone, PartialEq)]
+pub struct LineError {
+    pub line: usize,
+    pub message: String,
+}
+
+impl std::fmt::Display for LineError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "line {}: {}", self.line, self.message)
+    }
+}
+
+/// Parsed theme file: the valid overrides plus any per-line errors
+#[derive(Debug, Clone, Default)]
+pub struct ThemeFile {
+    overrides: Vec<Override>,
+    pub errors: Vec<LineError>,
+}
+
+impl ThemeFile {
+    /// Parse theme file contents, collecting every error rather than stopping at the first
+    pub fn parse(text: &str) -> Self {
+        let mut file = Self::default();
+        let mut section: Option<(Scope, String)> = None;
+
+        for (idx, raw) in text.lines().enumerate() {
+            let line = idx + 1;
+            let content = strip_comment(raw).trim();
+            if content.is_empty() {
+                continue;
+            }
+            let mut error = |message: String| file.errors.push(LineError { line, message });
+
+            if let Some(header) = content.strip_prefix('[') {
+                let Some(name) = header.strip_suffix(']') else {
+                    error("unterminated section header".to_string());
+                    section = None;
+                    continue;
+                };
+                let name = name.trim();
+                let (scope, group) = match name.split_once('.') {
+                    Some(("dark", group)) => (Scope::Dark, group),
+                    Some(("light", group)) => (Scope::Light, group),
+                    _ => (Scope::Both, name),
+                };
+                if !GROUPS.contains(&group) {
+                    error(format!("unknown section [{}] (expected one of: {})", name, GROUPS.join(", ")));
+                    section = None;
+                } else {
+                    section = Some((scope, group.to_string()));
+                }
+                continue;
+            }
+
+            let Some((key, raw_value)) = content.split_once('=') else {
+                error(format!("expected `key = value`, found `{}`", content));
+                continue;
+            };
+            let key = key.trim();
+            let Some((scope, group)) = &section else {
+                error(format!("`{}` is outside a valid [section]", key));
+                continue;
+            };
+
+            let mut scratch = theme::DARK;
+            let value = if color_slot(&mut scratch, group, key).is_some() {
+                match parse_color(raw_value.trim()) {
+                    Ok(color) => Value::Color(color),
+                    Err(message) => {
+                        error(format!("{}.{}: {}", group, key, message));
+                        continue;
+                    }
+                }
+            } else if number_slot(&mut scratch, group, key).is_some() {
+                match raw_value.trim().parse::<f32>() {
+                    Ok(n) if (0.0..=1.0).contains(&n) => Value::Number(n),
+                    _ => {
+                        error(format!("{}.{}: expected a number between 0 and 1", group, key));
+                        continue;
+                    }
+                }
+            } else {
+                error(format!("unknown key `{}` in [{}]", key, group));
+                continue;
+            };
+
+            file.overrides.push(Override {
+                scope: *scope,
+                group: group.clone(),
+                key: key.to_string(),
+                value,
+            });
+        }
+
+        file
+    }
+
+    /// Number of entries that will be applied
+    pub fn override_count(&self) -> usize {
+        self.overrides.len()
+    }
+
+    /// Apply the overrides on top of a built-in palette
+    fn apply(&self, palette: &mut Palette, dark: bool) {
+        for o in &self.overrides {
+            let applies = match o.scope {
+                Scope::Both => true,
+                Scope::Dark => dark,
+                Scope::Light => !dark,
+            };
+            if !applies {
+                continue;
+            }
+            match o.value {
+                Value::Color(color) => {
+                    if let Some(slot) = color_slot(palette, &o.group, &o.key) {
+                        *slot = color;
+                    }
+                }
+                Value::Number(n) => {
+                    if let Some(slot) = number_slot(palette, &o.group, &o.key) {
+                        *slot = n;
+                    }
+                }
+            }
+        }
+    }
+
+    /// Build the (dark, light) palettes with the overrides applied
+    pub fn palettes(&self) -> (Palette, Palette) {
+        let mut dark = theme::DARK;
+        let mut light = theme::LIGHT;
+        self.apply(&mut dark, true);
+        self.apply(&mut light, false);
+        (dark, light)
+    }
+}
+
+/// Location of the user theme file
+pub fn path() -> Option<PathBuf> {
+    dirs::config_dir().map(|mut p| {
+        p.push("dashboard-native");
+        p.push("theme.toml");
+        p
+    })
+}
+
+/// Load the theme file and install the resulting palettes.
+/// Returns `Ok(None)` when there is no theme file (built-in palettes are restored).
+pub fn load_and_install() -> Result<Option<ThemeFile>, String> {
+    let Some(path) = path() else {
+        theme::install(theme::DARK, theme::LIGHT);
+        return Err("Could not determine config directory".to_string());
+    };
+    match std::fs::read_to_string(&path) {
+        Ok(contents) => {
+            let file = ThemeFile::parse(&contents);
+            let (dark, light) = file.palettes();
+            theme::install(dark, light);
+            tracing::info!(
+                "Loaded theme from {:?} ({} overrides, {} errors)",
+                path,
+                file.override_count(),
+                file.errors.len()
+            );
+            Ok(Some(file))
+        }
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => {
+            theme::install(theme::DARK, theme::LIGHT);
+            Ok(None)
+        }
+        Err(e) => {
+            theme::install(theme::DARK, theme::LIGHT);
+            Err(format!("Failed to read {:?}: {}", path, e))
+        }
+    }
+}
+
+/// Section names accepted in the theme file
+const GROUPS: &[&str] = &[
+    "bg", "text", "border", "accent", "highlight", "timeline", "skeleton", "filter", "tooltip", "role",
+    "histogram", "status", "shading",
+];
+
+/// Mutable reference to the color named `group.key`, if it exists
+fn color_slot<'a>(p: &'a mut Palette, group: &str, key: &str) -> Option<&'a mut Color32> {
+    Some(match (group, key) {
+        ("bg", "graph") => &mut p.bg.graph,
+        ("bg", "panel") => &mut p.bg.panel,
+        ("bg", "surface") => &mut p.bg.surface,
+        ("bg", "interactive") => &mut p.bg.interactive,
+        ("bg", "interactive_hover") => &mut p.bg.interactive_hover,
+        ("bg", "interactive_active") => &mut p.bg.interactive_active,
+        ("bg", "timeline_track") => &mut p.bg.timeline_track,
+        ("text", "primary") => &mut p.text.primary,
+        ("text", "secondary") => &mut p.text.secondary,
+        ("text", "muted") => &mut p.text.muted,
+        ("text", "disabled") => &mut p.text.disabled,
+        ("border", "subtle") => &mut p.border.subtle,
+        ("border", "default") => &mut p.border.default,
+        ("border", "focus") => &mut p.border.focus,
+        ("accent", "orange") => &mut p.accent.orange,
+        ("accent", "orange_subtle") => &mut p.accent.orange_subtle,
+        ("accent", "cyan") => &mut p.accent.cyan,
+        ("accent", "green") => &mut p.accent.green,
+        ("accent", "purple") => &mut p.accent.purple,
+        ("accent", "blue") => &mut p.accent.blue,
+        ("accent", "red") => &mut p.accent.red,
+        ("highlight", "hover") => &mut p.highlight.hover,
+        ("highlight", "selected") => &mut p.highlight.selected,
+        ("timeline", "bar_inactive") => &mut p.timeline.bar_inactive,
+        ("timeline", "bar_highlight") => &mut p.timeline.bar_highlight,
+        ("timeline", "bar_selected") => &mut p.timeline.bar_selected,
+        ("timeline", "notch") => &mut p.timeline.notch,
+        ("timeline", "handle_start") => &mut p.timeline.handle_start,
+        ("timeline", "handle_end") => &mut p.timeline.handle_end,
+        ("skeleton", "base") => &mut p.skeleton.base,
+        ("skeleton", "shimmer") => &mut p.skeleton.shimmer,
+        ("filter", "inactive") => &mut p.filter.inactive,
+        ("filter", "active") => &mut p.filter.active,
+        ("filter", "include") => &mut p.filter.include,
+        ("filter", "include_plus1") => &mut p.filter.include_plus1,
+        ("filter", "include_plus2") => &mut p.filter.include_plus2,
+        ("filter", "exclude") => &mut p.filter.exclude,
+        ("tooltip", "bg") => &mut p.tooltip.bg,
+        ("tooltip", "text") => &mut p.tooltip.text,
+        ("tooltip", "border") => &mut p.tooltip.border,
+        ("role", "user") => &mut p.role.user,
+        ("role", "assistant") => &mut p.role.assistant,
+        ("role", "agent") => &mut p.role.agent,
+        ("role", "obsidian") => &mut p.role.obsidian,
+        ("role", "topic") => &mut p.role.topic,
+        ("histogram", "input") => &mut p.histogram.input,
+        ("histogram", "output") => &mut p.histogram.output,
+        ("histogram", "cache_read") => &mut p.histogram.cache_read,
+        ("histogram", "cache_create") => &mut p.histogram.cache_create,
+        ("status", "open") => &mut p.status.open,
+        ("status", "in_progress") => &mut p.status.in_progress,
+        ("status", "blocked") => &mut p.status.blocked,
+        ("status", "closed") => &mut p.status.closed,
+        ("status", "deferred") => &mut p.status.deferred,
+        ("status", "hooked") => &mut p.status.hooked,
+        _ => return None,
+    })
+}
+
+/// Mutable reference to the number named `group.key`, if it exists
+fn number_slot<'a>(p: &'a mut Palette, group: &str, key: &str) -> Option<&'a mut f32> {
+    Some(match (group, key) {
+        ("shading", "project_saturation") => &mut p.shading.project_saturation,
+        ("shading", "project_lightness") => &mut p.shading.project_lightness,
+        ("shading", "session_saturation") => &mut p.shading.session_saturation,
+        ("shading", "session_lightness") => &mut p.shading.session_lightness,
+        _ => return None,
+    })
+}
+
+/// Drop a trailing `# comment`, ignoring `#` inside quotes (colors start with one)
+fn strip_comment(line: &str) -> &str {
+    let mut in_quotes = false;
+    for (i, c) in line.char_indices() {
+        match c {
+            '"' => in_quotes = !in_quotes,
+            '#' if !in_quotes => return &line[..i],
+            _ => {}
+        }
+    }
+    line
+}
+
+/// Parse a quoted `"#RRGGBB"` or `"#RRGGBBAA"` color
+fn parse_color(raw: &str) -> Result<Color32, String> {
+    let unquoted = raw
+        .strip_prefix('"')
+        .and_then(|s| s.strip_suffix('"'))
+        .ok_or_else(|| format!("expected a quoted color like \"#RRGGBB\", found {}", raw))?;
+    let hex = unquoted
+        .strip_prefix('#')
+        .filter(|h| (h.len() == 6 || h.len() == 8) && h.chars().all(|c| c.is_ascii_hexdigit()))
+        .ok_or_else(|| format!("invalid color \"{}\" (expected #RRGGBB or #RRGGBBAA)", unquoted))?;
+    let byte = |i: usize| u8::from_str_radix(&hex[i..i + 2], 16).unwrap_or(0);
+    let alpha = if hex.len() == 8 { byte(6) } else { 255 };
+    Ok(Color32::from_rgba_unmultiplied(byte(0), byte(2), byte(4), alpha))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_overrides_and_scopes() {
+        let file = ThemeFile::parse(
+            "# My theme\n\
+             [bg]\n\
+             graph = \"#101820\"  # deep blue\n\
+             [light.text]\n\
+             primary = \"#00000080\"\n\
+             [shading]\n\
+             project_saturation = 0.4\n",
+        );
+        assert!(file.errors.is_empty(), "{:?}", file.errors);
+        assert_eq!(file.override_count(), 3);
+
+        let (dark, light) = file.palettes();
+        assert_eq!(dark.bg.graph, Color32::from_rgb(0x10, 0x18, 0x20));
+        assert_eq!(light.bg.graph, Color32::from_rgb(0x10, 0x18, 0x20));
+        assert_eq!(dark.text.primary, theme::DARK.text.primary);
+        assert_eq!(light.text.primary, Color32::from_rgba_unmultiplied(0, 0, 0, 0x80));
+        assert_eq!(dark.shading.project_saturation, 0.4);
+        // Untouched keys keep their defaults
+        assert_eq!(dark.accent.orange, theme::DARK.accent.orange);
+    }
+
+    #[test]
+    fn test_errors_report_lines_and_skip_bad_entries() {
+        let file = ThemeFile::parse(
+            "orphan = \"#ffffff\"\n\
+             [bg]\n\
+             graph = \"#12345\"\n\
+             panel = #123456\n\
+             nope = \"#123456\"\n\
+             surface = \"#ABCDEF\"\n\
+             [shading]\n\
+             project_lightness = 2\n\
+             [sparkles]\n",
+        );
+        let lines: Vec<usize> = file.errors.iter().map(|e| e.line).collect();
+        assert_eq!(lines, vec![1, 3, 4, 5, 8, 9]);
+        assert_eq!(file.override_count(), 1);
+        assert_eq!(file.palettes().0.bg.surface, Color32::from_rgb(0xAB, 0xCD, 0xEF));
+    }
+
+    #[test]
+    fn test_sample_theme_parses_cleanly() {
+        let file = ThemeFile::parse(include_str!("../assets/theme.sample.toml"));
+        assert!(file.errors.is_empty(), "{:?}", file.errors);
+        assert!(file.override_count() > 0);
+    }
+}
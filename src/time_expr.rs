@@ -0,0 +1,169 @@
+//! Times typed into the timeline's "jump to" box: ISO datetimes, `now`,
+//! `today`/`yesterday` with an optional clock time, and offsets from now such
+//! as `-3h`, `+30m` or `2d ago`. Times without a zone are local to `now`.
+
+use crate::graph::types::parse_iso_timestamp;
+use chrono::{DateTime, Duration, NaiveDate, NaiveDateTime, NaiveTime, TimeZone};
+
+/// Parse `input` against `now`, returning epoch seconds
+pub fn parse<Tz: TimeZone>(input: &str, now: &DateTime<Tz>) -> Result<f64, String> {
+    let raw = input.trim();
+    let text = raw.to_ascii_lowercase();
+    if text.is_empty() {
+        return Err("Type a time, e.g. \"yesterday 3pm\" or \"-2h\"".to_string());
+    }
+    if text == "now" {
+        return Ok(epoch(now));
+    }
+    if let Some(offset) = parse_offset(&text) {
+        return Ok(epoch(now) + offset);
+    }
+    for (word, days_back) in [("today", 0), ("yesterday", 1)] {
+        if let Some(rest) = text.strip_prefix(word) {
+            let date = now.date_naive() - Duration::days(days_back);
+            let rest = rest.trim();
+            let rest = rest.strip_prefix("at ").unwrap_or(rest).trim();
+            let time = if rest.is_empty() {
+                NaiveTime::MIN
+            } else {
+                parse_clock(rest).ok_or_else(|| format!("Couldn't read the time \"{}\"", rest))?
+            };
+            return local(now, date.and_time(time));
+        }
+    }
+    // Wall-clock forms are local; anything with a zone goes to the shared ISO parser
+    for fmt in ["%Y-%m-%d %H:%M:%S", "%Y-%m-%d %H:%M", "%Y-%m-%dT%H:%M:%S", "%Y-%m-%dT%H:%M"] {
+        if let Ok(dt) = NaiveDateTime::parse_from_str(raw, fmt) {
+            return local(now, dt);
+        }
+    }
+    if let Ok(date) = NaiveDate::parse_from_str(raw, "%Y-%m-%d") {
+        return local(now, date.and_time(NaiveTime::MIN));
+    }
+    parse_iso_timestamp(raw).ok_or_else(|| format!("Couldn't read \"{}\"", raw))
+}
+
+fn epoch<Tz: TimeZone>(dt: &DateTime<Tz>) -> f64 {
+    dt.timestamp_millis() as f64 / 1000.0
+}
+
+/// A local wall-clock time in `now`'s zone
+fn local<Tz: TimeZone>(now: &DateTime<Tz>, naive: NaiveDateTime) -> Result<f64, String> {
+    now.timezone()
+        .from_local_datetime(&naive)
+        .earliest()
+        .map(|dt| epoch(&dt))
+        .ok_or_else(|| "That time is skipped by a clock change".to_string())
+}
+
+/// Seconds for "-3h", "+30m", "1.5d ago"; None if `text` isn't an offset
+fn parse_offset(text: &str) -> Option<f64> {
+    let (sign, body) = if let Some(rest) = text.strip_suffix("ago") {
+        (-1.0, rest.trim())
+    } else if let Some(rest) = text.strip_prefix('-') {
+        (-1.0, rest)
+    } else if let Some(rest) = text.strip_prefix('+') {
+        (1.0, rest)
+    } else {
+        return None;
+    };
+    let (unit_at, unit) = body.char_indices().last()?;
+    let unit_secs = match unit {
+        's' => 1.0,
+        'm' => 60.0,
+        'h' => 3600.0,
+        'd' => 86_400.0,
+        'w' => 7.0 * 86_400.0,
+        _ => return None,
+    };
+    let amount: f64 = body[..unit_at].trim().parse().ok()?;
+    (amount.is_finite() && amount >= 0.0).then_some(sign * amount * unit_secs)
+}
+
+/// "3pm", "3:30 pm", "15:00", "noon"
+fn parse_clock(text: &str) -> Option<NaiveTime> {
+    match text {
+        "noon" => return NaiveTime::from_hms_opt(12, 0, 0),
+        "midnight" => return Some(NaiveTime::MIN),
+        _ => {}
+    }
+    let compact: String = text.chars().filter(|c| !c.is_whitespace()).collect();
+    let (clock, pm) = if let Some(clock) = compact.strip_suffix("am") {
+        (clock, Some(false))
+    } else if let Some(clock) = compact.strip_suffix("pm") {
+        (clock, Some(true))
+    } else {
+        (compact.as_str(), None)
+    };
+    let mut parts = clock.split(':');
+    let hour: u32 = parts.next()?.parse().ok()?;
+    let minute: u32 = match parts.next() {
+        Some(m) => m.parse().ok()?,
+        None => 0,
+    };
+    if parts.next().is_some() {
+        return None;
+    }
+    let hour = match pm {
+        Some(pm) if (1..=12).contains(&hour) => hour % 12 + if pm { 12 } else { 0 },
+        Some(_) => return None,
+        None => hour,
+    };
+    NaiveTime::from_hms_opt(hour, minute, 0)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::{FixedOffset, Utc};
+
+    fn now() -> DateTime<Utc> {
+        Utc.with_ymd_and_hms(2025, 6, 15, 10, 0, 0).unwrap()
+    }
+
+    fn at(y: i32, mo: u32, d: u32, h: u32, mi: u32) -> f64 {
+        Utc.with_ymd_and_hms(y, mo, d, h, mi, 0).unwrap().timestamp() as f64
+    }
+
+    #[test]
+    fn test_relative_offsets() {
+        let base = at(2025, 6, 15, 10, 0);
+        assert_eq!(parse("now", &now()), Ok(base));
+        assert_eq!(parse("-3h", &now()), Ok(base - 3.0 * 3600.0));
+        assert_eq!(parse("-2d", &now()), Ok(base - 2.0 * 86_400.0));
+        assert_eq!(parse("+30m", &now()), Ok(base + 1800.0));
+        assert_eq!(parse(" -1.5h ", &now()), Ok(base - 5400.0));
+        assert_eq!(parse("2d ago", &now()), Ok(base - 2.0 * 86_400.0));
+        assert_eq!(parse("-1w", &now()), Ok(base - 7.0 * 86_400.0));
+    }
+
+    #[test]
+    fn test_days_with_clock_times() {
+        assert_eq!(parse("yesterday 3pm", &now()), Ok(at(2025, 6, 14, 15, 0)));
+        assert_eq!(parse("Yesterday at 3:30 PM", &now()), Ok(at(2025, 6, 14, 15, 30)));
+        assert_eq!(parse("today 09:30", &now()), Ok(at(2025, 6, 15, 9, 30)));
+        assert_eq!(parse("today 12am", &now()), Ok(at(2025, 6, 15, 0, 0)));
+        assert_eq!(parse("today noon", &now()), Ok(at(2025, 6, 15, 12, 0)));
+        assert_eq!(parse("today", &now()), Ok(at(2025, 6, 15, 0, 0)));
+    }
+
+    #[test]
+    fn test_absolute_times_are_local_unless_zoned() {
+        assert_eq!(parse("2025-06-14 09:30", &now()), Ok(at(2025, 6, 14, 9, 30)));
+        assert_eq!(parse("2025-06-14", &now()), Ok(at(2025, 6, 14, 0, 0)));
+
+        // Two hours ahead of UTC: wall-clock input shifts, explicit zones don't
+        let plus_two = now().with_timezone(&FixedOffset::east_opt(2 * 3600).unwrap());
+        assert_eq!(parse("2025-06-14 09:30", &plus_two), Ok(at(2025, 6, 14, 7, 30)));
+        assert_eq!(parse("2025-06-14T09:30", &plus_two), Ok(at(2025, 6, 14, 7, 30)));
+        assert_eq!(parse("2025-06-14T09:30:00Z", &plus_two), Ok(at(2025, 6, 14, 9, 30)));
+        assert_eq!(parse("yesterday 3pm", &plus_two), Ok(at(2025, 6, 14, 13, 0)));
+    }
+
+    #[test]
+    fn test_rejects_nonsense() {
+        for input in ["", "banana", "today 25:00", "yesterday 13pm", "-3x", "--3h", "3h", "todayish"] {
+            assert!(parse(input, &now()).is_err(), "{:?} parsed", input);
+        }
+    }
+}
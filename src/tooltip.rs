@@ -0,0 +1,309 @@
+//! Node tooltip content: which fields appear, in what order, and how each is
+//! rendered into lines. The defaults reproduce the original fixed layout.
+
+use crate::content_kind::{ContentClass, ContentKind};
+use crate::graph::types::GraphNode;
+use crate::text::{self, truncate};
+use serde::{Deserialize, Serialize};
+
+/// Characters per wrapped tooltip line
+pub const LINE_CHARS: usize = 50;
+/// Importance reason lines shown before it's cut off
+const REASON_LINES: usize = 3;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum TooltipField {
+    Preview,
+    Role,
+    Project,
+    Session,
+    Timestamp,
+    Tokens,
+    Tools,
+    Content,
+    Importance,
+    FilterMatches,
+}
+
+impl TooltipField {
+    pub fn label(&self) -> &'static str {
+        match self {
+            TooltipField::Preview => "Content preview",
+            TooltipField::Role => "Role",
+            TooltipField::Project => "Project",
+            TooltipField::Session => "Session",
+            TooltipField::Timestamp => "Time",
+            TooltipField::Tokens => "Tokens in / out",
+            TooltipField::Tools => "Tool use",
+            TooltipField::Content => "Code / tool output",
+            TooltipField::Importance => "Importance",
+            TooltipField::FilterMatches => "Filter matches",
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct TooltipEntry {
+    pub field: TooltipField,
+    pub enabled: bool,
+}
+
+/// Ordered tooltip fields; every field is listed once, disabled ones are hidden
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct TooltipConfig {
+    pub entries: Vec<TooltipEntry>,
+    /// Preview length in characters, wrapped at `LINE_CHARS`
+    #[serde(default = "default_preview_chars")]
+    pub preview_chars: usize,
+}
+
+fn default_preview_chars() -> usize {
+    4 * LINE_CHARS
+}
+
+impl Default for TooltipConfig {
+    fn default() -> Self {
+        let entry = |field, enabled| TooltipEntry { field, enabled };
+        Self {
+            entries: vec![
+                entry(TooltipField::Preview, true),
+                entry(TooltipField::Project, true),
+                entry(TooltipField::Timestamp, true),
+                entry(TooltipField::Tokens, true),
+                entry(TooltipField::Tools, true),
+                entry(TooltipField::Content, true),
+                entry(TooltipField::Importance, true),
+                entry(TooltipField::Role, false),
+                entry(TooltipField::Session, false),
+                entry(TooltipField::FilterMatches, false),
+            ],
+            preview_chars: default_preview_chars(),
+        }
+    }
+}
+
+impl TooltipConfig {
+    /// Fill in fields missing from an older saved config (disabled, at the end)
+    /// and drop duplicates
+    pub fn normalized(mut self) -> Self {
+        let mut seen = Vec::new();
+        self.entries.retain(|e| {
+            let first = !seen.contains(&e.field);
+            seen.push(e.field);
+            first
+        });
+        for entry in Self::default().entries {
+            if !seen.contains(&entry.field) {
+                self.entries.push(TooltipEntry { field: entry.field, enabled: false });
+            }
+        }
+        self
+    }
+
+    /// Move the entry at `from` to position `to`
+    pub fn move_entry(&mut self, from: usize, to: usize) {
+        if from >= self.entries.len() || from == to {
+            return;
+        }
+        let entry = self.entries.remove(from);
+        self.entries.insert(to.min(self.entries.len()), entry);
+    }
+
+    /// Tooltip lines for `node`. `content` is its detected content class;
+    /// `format_time` renders a timestamp the way the timeline does; `filters`
+    /// names the filters the node matches.
+    pub fn lines(
+        &self,
+        node: &GraphNode,
+        content: Option<&ContentClass>,
+        format_time: impl Fn(f64) -> String,
+        filters: &[String],
+    ) -> Vec<String> {
+        let mut lines = Vec::new();
+        for field in self.entries.iter().filter(|e| e.enabled).map(|e| e.field) {
+            match field {
+                TooltipField::Preview => {
+                    wrap_preview(&node.content_preview, self.preview_chars, &mut lines);
+                    lines.push(String::new());
+                }
+                TooltipField::Role => lines.push(format!("Role: {}", node.role.label())),
+                TooltipField::Project => lines.push(format!("Project: {}", node.project)),
+                TooltipField::Session => lines.push(format!("Session: {}", node.session_short)),
+                TooltipField::Timestamp => {
+                    // Relative "3 hours ago", "Yesterday at 2:30 PM", etc.
+                    if let Some(secs) = node.timestamp_secs() {
+                        lines.push(format!("Time: {}", format_time(secs)));
+                    }
+                }
+                TooltipField::Tokens => {
+                    let in_tok = node.input_tokens.unwrap_or(0);
+                    let out_tok = node.output_tokens.unwrap_or(0);
+                    if in_tok > 0 || out_tok > 0 {
+                        lines.push(format!("Tokens: {} in / {} out", compact_tokens(in_tok), compact_tokens(out_tok)));
+                    }
+                }
+                TooltipField::Tools => {
+                    if node.has_tool_usage {
+                        lines.push("Tools used".to_string());
+                    }
+                }
+                TooltipField::Content => {
+                    // Prose is the unremarkable case
+                    if let Some(content) = content.filter(|c| c.kind != ContentKind::Prose) {
+                        lines.push(content.describe());
+                    }
+                }
+                TooltipField::Importance => {
+                    if let Some(score) = node.importance_score {
+                        lines.push(format!("Importance: {:.0}%", score * 100.0));
+                    }
+                    if let Some(ref reason) = node.importance_reason {
+                        wrap_reason(reason, &mut lines);
+                    }
+                }
+                TooltipField::FilterMatches => {
+                    if !filters.is_empty() {
+                        lines.push(format!("Filters: {}", filters.join(", ")));
+                    }
+                }
+            }
+        }
+        // A preview at the end shouldn't leave a trailing gap
+        if lines.last().is_some_and(|l| l.is_empty()) {
+            lines.pop();
+        }
+        lines
+    }
+}
+
+/// Compact "1.2k" token count
+fn compact_tokens(t: i32) -> String {
+    if t >= 1000 {
+        format!("{:.1}k", t as f64 / 1000.0)
+    } else {
+        format!("{}", t)
+    }
+}
+
+/// Up to `max_chars` of the preview in `LINE_CHARS` lines, ellipsized when cut
+fn wrap_preview(preview: &str, max_chars: usize, lines: &mut Vec<String>) {
+    let max_lines = max_chars.div_ceil(LINE_CHARS);
+    let first = lines.len();
+    let mut chars = preview.chars().peekable();
+    let mut taken = 0;
+    for _ in 0..max_lines {
+        if chars.peek().is_none() {
+            break;
+        }
+        let chunk: String = chars.by_ref().take(LINE_CHARS.min(max_chars - taken)).collect();
+        taken += chunk.chars().count();
+        lines.push(chunk.trim_end().to_string());
+    }
+    if chars.peek().is_some() && lines.len() > first {
+        if let Some(last) = lines.last_mut() {
+            *last = truncate(&format!("{}{}", last, text::ELLIPSIS), LINE_CHARS);
+        }
+    }
+}
+
+/// Indented importance reason, cut after a few lines
+fn wrap_reason(reason: &str, lines: &mut Vec<String>) {
+    let mut chars = reason.chars().peekable();
+    for _ in 0..REASON_LINES {
+        if chars.peek().is_none() {
+            break;
+        }
+        let chunk: String = chars.by_ref().take(LINE_CHARS).collect();
+        lines.push(format!("  {}", chunk.trim()));
+    }
+    if chars.peek().is_some() {
+        if let Some(last) = lines.last_mut() {
+            last.push(text::ELLIPSIS);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::graph::types::Role;
+
+    fn node() -> GraphNode {
+        GraphNode {
+            role: Role::Assistant,
+            content_preview: "x".repeat(230),
+            session_short: "abcdef01".to_string(),
+            project: "~/proj".to_string(),
+            timestamp: Some("2025-06-01T10:00:00Z".to_string()),
+            importance_score: Some(0.8),
+            importance_reason: Some("Key decision".to_string()),
+            output_tokens: Some(3400),
+            input_tokens: Some(120),
+            has_tool_usage: true,
+            ..GraphNode::stub("1", "abcdef0123456789")
+        }
+    }
+
+    #[test]
+    fn test_default_layout() {
+        let lines = TooltipConfig::default().lines(&node(), None, |_| "1 hour ago".to_string(), &["Bugs".to_string()]);
+        let cut = format!("{}{}", "x".repeat(LINE_CHARS - 1), text::ELLIPSIS);
+        let full = "x".repeat(LINE_CHARS);
+        assert_eq!(lines, vec![
+            full.clone(), full.clone(), full, cut,
+            String::new(),
+            "Project: ~/proj".to_string(),
+            "Time: 1 hour ago".to_string(),
+            "Tokens: 120 in / 3.4k out".to_string(),
+            "Tools used".to_string(),
+            "Importance: 80%".to_string(),
+            "  Key decision".to_string(),
+        ]);
+    }
+
+    #[test]
+    fn test_custom_order_and_length() {
+        let mut config = TooltipConfig::default();
+        for entry in &mut config.entries {
+            entry.enabled = matches!(entry.field, TooltipField::Preview | TooltipField::Session | TooltipField::FilterMatches);
+        }
+        let session = config.entries.iter().position(|e| e.field == TooltipField::Session).unwrap();
+        config.move_entry(session, 0);
+        config.preview_chars = 60;
+        let lines = config.lines(&node(), None, |_| unreachable!(), &["Bugs".to_string(), "Perf".to_string()]);
+        assert_eq!(lines, vec![
+            "Session: abcdef01".to_string(),
+            "x".repeat(LINE_CHARS),
+            format!("{}{}", "x".repeat(10), text::ELLIPSIS),
+            String::new(),
+            "Filters: Bugs, Perf".to_string(),
+        ]);
+    }
+
+    #[test]
+    fn test_content_class_line() {
+        let mut config = TooltipConfig::default();
+        for entry in &mut config.entries {
+            entry.enabled = entry.field == TooltipField::Content;
+        }
+        let code = ContentClass { kind: ContentKind::Code, language: Some("rust".to_string()) };
+        assert_eq!(config.lines(&node(), Some(&code), |_| unreachable!(), &[]), vec!["Code-heavy (rust)".to_string()]);
+        assert!(config.lines(&node(), Some(&ContentClass::default()), |_| unreachable!(), &[]).is_empty());
+        assert!(config.lines(&node(), None, |_| unreachable!(), &[]).is_empty());
+    }
+
+    #[test]
+    fn test_normalized_fills_missing_fields() {
+        let config = TooltipConfig {
+            entries: vec![
+                TooltipEntry { field: TooltipField::Tokens, enabled: true },
+                TooltipEntry { field: TooltipField::Tokens, enabled: false },
+            ],
+            preview_chars: 100,
+        }
+        .normalized();
+        assert_eq!(config.entries.len(), TooltipConfig::default().entries.len());
+        assert_eq!(config.entries[0], TooltipEntry { field: TooltipField::Tokens, enabled: true });
+        assert!(config.entries[1..].iter().all(|e| !e.enabled));
+    }
+}
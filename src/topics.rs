@@ -0,0 +1,191 @@
+//! Topic nodes derived from session summaries. Each distinct topic becomes one
+//! `Role::Topic` hub linked to the first and last message of every session
+//! that lists it, so themes shared across sessions stand out.
+
+use crate::graph::ids;
+use crate::graph::types::{GraphData, GraphEdge, GraphNode, Role};
+use std::collections::{BTreeMap, HashMap};
+
+/// Session id shared by every topic node
+pub const TOPIC_SESSION: &str = "topics";
+
+/// First and last message of a session, by timestamp (load order breaks ties;
+/// untimed messages sort last)
+struct SessionEnds<'a> {
+    first: (&'a GraphNode, f64),
+    last: (&'a GraphNode, f64),
+}
+
+struct Topic<'a> {
+    label: &'a str,
+    /// (session id, first message id, last message id)
+    sessions: Vec<(&'a str, &'a str, &'a str)>,
+    earliest: Option<f64>,
+}
+
+/// Topic nodes and `is_topic` edges for the loaded messages. Topics match
+/// across sessions ignoring case and surrounding whitespace.
+pub fn build(nodes: &[GraphNode], session_topics: &HashMap<String, Vec<String>>) -> GraphData {
+    let mut ends: HashMap<&str, SessionEnds> = HashMap::new();
+    for node in nodes {
+        if matches!(node.role, Role::Topic | Role::Obsidian) || !session_topics.contains_key(&node.session_id) {
+            continue;
+        }
+        let t = node.timestamp_secs().unwrap_or(f64::INFINITY);
+        ends.entry(node.session_id.as_str())
+            .and_modify(|e| {
+                if t < e.first.1 {
+                    e.first = (node, t);
+                }
+                if t >= e.last.1 {
+                    e.last = (node, t);
+                }
+            })
+            .or_insert(SessionEnds { first: (node, t), last: (node, t) });
+    }
+
+    let mut topics: BTreeMap<String, Topic> = BTreeMap::new();
+    for (session_id, labels) in session_topics {
+        let Some(ends) = ends.get(session_id.as_str()) else {
+            continue;
+        };
+        for label in labels.iter().map(|t| t.trim()).filter(|t| !t.is_empty()) {
+            let topic = topics.entry(topic_key(label)).or_insert(Topic { label, sessions: Vec::new(), earliest: None });
+            if topic.sessions.iter().any(|(s, _, _)| *s == session_id.as_str()) {
+                continue;
+            }
+            // Prefer the label from the earliest session so it doesn't flip between loads
+            let started = ends.first.1;
+            if started.is_finite() && topic.earliest.is_none_or(|e| started < e) {
+                topic.earliest = Some(started);
+                topic.label = label;
+            }
+            topic.sessions.push((session_id.as_str(), ends.first.0.id.as_str(), ends.last.0.id.as_str()));
+        }
+    }
+
+    let mut data = GraphData::default();
+    for (key, mut topic) in topics {
+        let id = ids::topic(&key);
+        topic.sessions.sort_unstable();
+        for (session_id, first, last) in &topic.sessions {
+            data.edges.push(GraphEdge::topic(id.clone(), first.to_string(), session_id.to_string()));
+            if last != first {
+                data.edges.push(GraphEdge::topic(id.clone(), last.to_string(), session_id.to_string()));
+            }
+        }
+        data.nodes.push(topic_node(id, &topic));
+    }
+    data
+}
+
+/// Replace the topic nodes in `data` with ones derived from `session_topics`
+/// (an empty map just removes them)
+pub fn attach(data: &mut GraphData, session_topics: &HashMap<String, Vec<String>>) {
+    data.nodes.retain(|n| n.role != Role::Topic);
+    data.edges.retain(|e| !e.is_topic);
+    if session_topics.is_empty() {
+        return;
+    }
+    let topics = build(&data.nodes, session_topics);
+    data.nodes.extend(topics.nodes);
+    data.edges.extend(topics.edges);
+}
+
+fn topic_node(id: String, topic: &Topic) -> GraphNode {
+    let count = topic.sessions.len();
+    GraphNode {
+        id,
+        role: Role::Topic,
+        content_preview: topic.label.to_string(),
+        full_content: Some(format!(
+            "{} ({} session{})",
+            topic.label,
+            count,
+            if count == 1 { "" } else { "s" }
+        )),
+        session_id: TOPIC_SESSION.to_string(),
+        session_short: TOPIC_SESSION.to_string(),
+        project: String::new(),
+        timestamp: topic.earliest
+            .and_then(|t| chrono::DateTime::from_timestamp(t.floor() as i64, 0))
+            .map(|t| t.format("%Y-%m-%dT%H:%M:%SZ").to_string()),
+        importance_score: None,
+        importance_reason: None,
+        output_tokens: None,
+        input_tokens: None,
+        cache_read_tokens: None,
+        cache_creation_tokens: None,
+        has_tool_usage: false,
+    }
+}
+
+/// Lowercased with internal whitespace collapsed
+fn topic_key(topic: &str) -> String {
+    topic.split_whitespace().collect::<Vec<_>>().join(" ").to_lowercase()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn node(id: &str, session: &str, timestamp: &str) -> GraphNode {
+        GraphNode {
+            project: "~/proj".to_string(),
+            timestamp: Some(timestamp.to_string()),
+            ..GraphNode::stub(id, session)
+        }
+    }
+
+    fn topics(entries: &[(&str, &[&str])]) -> HashMap<String, Vec<String>> {
+        entries.iter()
+            .map(|(session, list)| (session.to_string(), list.iter().map(|t| t.to_string()).collect()))
+            .collect()
+    }
+
+    #[test]
+    fn test_shared_topics_become_hubs() {
+        let nodes = vec![
+            node("2", "a", "2025-06-01T10:05:00Z"),
+            node("1", "a", "2025-06-01T10:00:00Z"),
+            node("3", "a", "2025-06-01T10:10:00Z"),
+            node("4", "b", "2025-06-02T09:00:00Z"),
+        ];
+        let session_topics = topics(&[
+            ("a", &["Graph layout", "SQLite"]),
+            ("b", &["  graph   LAYOUT ", "graph layout"]),
+            // Not loaded
+            ("c", &["Elsewhere"]),
+        ]);
+        let data = build(&nodes, &session_topics);
+
+        let ids: Vec<&str> = data.nodes.iter().map(|n| n.id.as_str()).collect();
+        assert_eq!(ids, vec!["topic:graph layout", "topic:sqlite"]);
+        let hub = &data.nodes[0];
+        assert_eq!(hub.role, Role::Topic);
+        assert_eq!(hub.content_preview, "Graph layout");
+        assert_eq!(hub.timestamp.as_deref(), Some("2025-06-01T10:00:00Z"));
+        assert_eq!(hub.full_content.as_deref(), Some("Graph layout (2 sessions)"));
+
+        let hub_edges: Vec<(&str, &str)> = data.edges.iter()
+            .filter(|e| e.source == hub.id)
+            .map(|e| (e.target.as_str(), e.session_id.as_str()))
+            .collect();
+        // First and last of session a; session b's single message once
+        assert_eq!(hub_edges, vec![("1", "a"), ("3", "a"), ("4", "b")]);
+        assert!(data.edges.iter().all(|e| e.is_topic));
+    }
+
+    #[test]
+    fn test_attach_replaces_and_removes() {
+        let mut data = GraphData::new(vec![node("1", "a", "2025-06-01T10:00:00Z")], Vec::new());
+        let session_topics = topics(&[("a", &["Rust"])]);
+        attach(&mut data, &session_topics);
+        attach(&mut data, &session_topics);
+        assert_eq!((data.nodes.len(), data.edges.len()), (2, 1));
+
+        attach(&mut data, &HashMap::new());
+        assert_eq!((data.nodes.len(), data.edges.len()), (1, 0));
+        assert_eq!(data.nodes[0].id, "1");
+    }
+}
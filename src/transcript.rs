@@ -0,0 +1,216 @@
+//! Session transcripts dropped onto the window. A raw Claude Code `.jsonl`
+//! transcript is parsed the way `parse_transcript` in ingest.py does it and
+//! kept in memory as its own session, re-attached on every reload like the
+//! other derived nodes. Nothing is written to the database.
+
+use crate::graph::ids;
+use crate::graph::types::{GraphData, GraphEdge, GraphNode, Role};
+use serde_json::Value;
+use std::collections::HashSet;
+
+/// Characters kept in a node's preview, matching database loads
+const PREVIEW_CHARS: usize = 100;
+
+/// One dropped transcript as a session of nodes and conversation edges
+#[derive(Debug, Clone, Default)]
+pub struct Transcript {
+    /// File name the transcript was dropped as
+    pub name: String,
+    pub session_id: String,
+    pub nodes: Vec<GraphNode>,
+    pub edges: Vec<GraphEdge>,
+    /// Non-empty lines that weren't valid JSON
+    pub failed_lines: usize,
+}
+
+impl Transcript {
+    /// "12 messages" plus how many lines failed, if any
+    pub fn describe(&self) -> String {
+        let mut text = format!("{}: {} messages", self.name, self.nodes.len());
+        if self.failed_lines > 0 {
+            text.push_str(&format!(", {} line{} failed", self.failed_lines, if self.failed_lines == 1 { "" } else { "s" }));
+        }
+        text
+    }
+}
+
+/// Session id for a dropped file, unique among `taken` so dropping the same
+/// file twice gives two sessions
+pub fn session_id_for(name: &str, taken: &HashSet<&str>) -> String {
+    let base = ids::dropped_session(name);
+    if !taken.contains(base.as_str()) {
+        return base;
+    }
+    (2..)
+        .map(|n| format!("{}-{}", base, n))
+        .find(|id| !taken.contains(id.as_str()))
+        .unwrap_or(base)
+}
+
+/// Parse transcript `text` into session `session_id` under a `dropped/<name>`
+/// project. Bad lines are counted and skipped rather than failing the file.
+pub fn parse(text: &str, name: &str, session_id: &str) -> Transcript {
+    let project = format!("dropped/{}", name);
+    let mut transcript = Transcript {
+        name: name.to_string(),
+        session_id: session_id.to_string(),
+        ..Default::default()
+    };
+
+    for line in text.lines().map(str::trim).filter(|l| !l.is_empty()) {
+        let Ok(entry) = serde_json::from_str::<Value>(line) else {
+            transcript.failed_lines += 1;
+            continue;
+        };
+        let entry_type = entry.get("type").and_then(Value::as_str).unwrap_or_default();
+        if entry_type != "user" && entry_type != "assistant" {
+            continue;
+        }
+        let message = entry.get("message");
+        let field = |key: &str| message.and_then(|m| m.get(key));
+        let role = field("role").and_then(Value::as_str).unwrap_or(entry_type);
+
+        let mut text_parts: Vec<&str> = Vec::new();
+        let mut tools: Vec<&str> = Vec::new();
+        match field("content") {
+            Some(Value::String(s)) => text_parts.push(s),
+            Some(Value::Array(blocks)) => {
+                for block in blocks {
+                    match block.get("type").and_then(Value::as_str) {
+                        Some("text") => text_parts.push(block.get("text").and_then(Value::as_str).unwrap_or_default()),
+                        Some("tool_use") => tools.push(block.get("name").and_then(Value::as_str).unwrap_or_default()),
+                        // Thinking, tool results and the like aren't messages
+                        _ => {}
+                    }
+                }
+            }
+            _ => {}
+        }
+        let mut content = text_parts.join("\n").trim().to_string();
+        if content.is_empty() {
+            if tools.is_empty() {
+                continue;
+            }
+            content = format!("[Used tools: {}]", tools.join(", "));
+        }
+
+        let usage = field("usage");
+        let tokens = |key: &str| usage.and_then(|u| u.get(key)).and_then(Value::as_i64).map(|t| t as i32);
+        let seq = transcript.nodes.len();
+        let content_preview = if content.chars().count() > PREVIEW_CHARS {
+            format!("{}...", content.chars().take(PREVIEW_CHARS).collect::<String>())
+        } else {
+            content.clone()
+        };
+        let node = GraphNode {
+            id: ids::dropped_message(session_id, seq),
+            role: match role {
+                "assistant" => Role::Assistant,
+                _ => Role::User,
+            },
+            content_preview,
+            full_content: Some(content),
+            session_id: session_id.to_string(),
+            session_short: name.chars().take(8).collect(),
+            project: project.clone(),
+            timestamp: entry.get("timestamp").and_then(Value::as_str).filter(|t| !t.is_empty()).map(str::to_string),
+            importance_score: None,
+            importance_reason: None,
+            output_tokens: tokens("output_tokens"),
+            input_tokens: tokens("input_tokens"),
+            cache_read_tokens: tokens("cache_read_input_tokens"),
+            cache_creation_tokens: tokens("cache_creation_input_tokens"),
+            has_tool_usage: !tools.is_empty(),
+        };
+        if let Some(prev) = transcript.nodes.last() {
+            transcript.edges.push(GraphEdge::conversation(
+                prev.id.clone(),
+                node.id.clone(),
+                session_id.to_string(),
+                node.timestamp.clone(),
+            ));
+        }
+        transcript.nodes.push(node);
+    }
+    transcript
+}
+
+/// Add every dropped session's nodes and edges that `data` lacks
+pub fn attach(data: &mut GraphData, transcripts: &[Transcript]) {
+    if transcripts.is_empty() {
+        return;
+    }
+    let mut present: HashSet<String> = data.nodes.iter().map(|n| n.id.clone()).collect();
+    for transcript in transcripts {
+        if !transcript.nodes.iter().all(|n| present.insert(n.id.clone())) {
+            // Already attached (an incremental merge keeps them)
+            continue;
+        }
+        data.nodes.extend(transcript.nodes.iter().cloned());
+        data.edges.extend(transcript.edges.iter().cloned());
+    }
+}
+
+/// Take dropped sessions back out of `data`
+pub fn detach(data: &mut GraphData, transcripts: &[Transcript]) {
+    let sessions: HashSet<&str> = transcripts.iter().map(|t| t.session_id.as_str()).collect();
+    data.nodes.retain(|n| !sessions.contains(n.session_id.as_str()));
+    data.edges.retain(|e| !sessions.contains(e.session_id.as_str()));
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const SAMPLE: &str = r#"
+{"type":"user","timestamp":"2025-06-01T10:00:00Z","message":{"role":"user","content":"Fix the parser"}}
+{"type":"summary","summary":"ignored"}
+not json at all
+{"type":"assistant","timestamp":"2025-06-01T10:00:05Z","message":{"role":"assistant","content":[{"type":"thinking","thinking":"hmm"},{"type":"text","text":"On it."},{"type":"tool_use","name":"Edit","input":{}}],"usage":{"input_tokens":10,"output_tokens":20,"cache_read_input_tokens":300}}}
+{"type":"assistant","timestamp":"2025-06-01T10:00:09Z","message":{"role":"assistant","content":[{"type":"tool_use","name":"Bash","input":{}},{"type":"tool_use","name":"Read","input":{}}]}}
+{"type":"assistant","message":{"role":"assistant","content":[{"type":"thinking","thinking":"only thoughts"}]}}
+{"type":"user","timestamp":"2025-06-01T10:01:00Z","message":{"role":"user","content":"Thanks
+"#;
+
+    #[test]
+    fn test_parse_transcript() {
+        let t = parse(SAMPLE, "session.jsonl", "dropped:session.jsonl");
+        assert_eq!(t.failed_lines, 2);
+        assert_eq!(t.nodes.len(), 3);
+        assert_eq!(t.edges.len(), 2);
+
+        let first = &t.nodes[0];
+        assert_eq!(first.id, "dropped:session.jsonl#0");
+        assert_eq!((first.role.clone(), first.content_preview.as_str()), (Role::User, "Fix the parser"));
+        assert_eq!(first.project, "dropped/session.jsonl");
+
+        let reply = &t.nodes[1];
+        assert_eq!(reply.full_content.as_deref(), Some("On it."));
+        assert!(reply.has_tool_usage);
+        assert_eq!((reply.input_tokens, reply.output_tokens, reply.cache_read_tokens), (Some(10), Some(20), Some(300)));
+
+        assert_eq!(t.nodes[2].content_preview, "[Used tools: Bash, Read]");
+        assert_eq!((t.edges[1].source.as_str(), t.edges[1].target.as_str()), ("dropped:session.jsonl#1", "dropped:session.jsonl#2"));
+        assert_eq!(t.describe(), "session.jsonl: 3 messages, 2 lines failed");
+    }
+
+    #[test]
+    fn test_each_drop_is_its_own_session() {
+        let mut taken: HashSet<&str> = HashSet::new();
+        let a = session_id_for("s.jsonl", &taken);
+        taken.insert(&a);
+        let b = session_id_for("s.jsonl", &taken);
+        assert_eq!((a.as_str(), b.as_str()), ("dropped:s.jsonl", "dropped:s.jsonl-2"));
+
+        let transcripts = vec![parse(SAMPLE, "s.jsonl", &a), parse(SAMPLE, "s.jsonl", &b)];
+        let mut data = GraphData::default();
+        attach(&mut data, &transcripts);
+        attach(&mut data, &transcripts);
+        assert_eq!(data.nodes.len(), 6);
+        assert_eq!(data.edges.len(), 4);
+
+        detach(&mut data, &transcripts[..1]);
+        assert!(data.nodes.iter().all(|n| n.session_id == b));
+        assert_eq!(data.edges.len(), 2);
+    }
+}
@@ -0,0 +1,158 @@
+//! Compact, shareable encoding of "exactly what I'm looking at".
+//!
+//! A view string is JSON, deflated and base64url-encoded behind a short
+//! prefix. Unknown fields are ignored so older builds can read newer strings.
+
+use crate::graph::types::{ColorMode, FilterMode, SemanticFilterMode};
+use base64::engine::general_purpose::URL_SAFE_NO_PAD;
+use base64::Engine;
+use flate2::read::DeflateDecoder;
+use flate2::write::DeflateEncoder;
+use flate2::Compression;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::io::{Read, Write};
+
+/// Prefix identifying a view string (and its format version)
+const PREFIX: &str = "cdv1:";
+
+/// Largest decompressed view accepted from the clipboard, so a pasted
+/// string can't inflate into a decompression bomb
+const MAX_DECODED_BYTES: u64 = 1024 * 1024;
+
+/// Snapshot of the current view, applied on top of the settings/preset machinery
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct ViewString {
+    // Data selection
+    pub time_range_hours: f32,
+    pub session: Option<String>,
+
+    // Timeline window as absolute epoch seconds (None = timeline disabled)
+    pub timeline_start: Option<f64>,
+    pub timeline_end: Option<f64>,
+
+    // Camera and selection
+    pub selected_node: Option<String>,
+    pub pan: [f32; 2],
+    pub zoom: f32,
+
+    // Appearance
+    pub color_mode: ColorMode,
+    pub preset: Option<String>,
+
+    // Active filters
+    pub importance_threshold: f32,
+    pub importance_filter: FilterMode,
+    pub tool_use_filter: FilterMode,
+    pub project_filter: FilterMode,
+    pub hidden_projects: Vec<String>,
+    pub semantic_filter_modes: HashMap<i32, SemanticFilterMode>,
+}
+
+impl Default for ViewString {
+    fn default() -> Self {
+        Self {
+            time_range_hours: 24.0,
+            session: None,
+            timeline_start: None,
+            timeline_end: None,
+            selected_node: None,
+            pan: [0.0, 0.0],
+            zoom: 1.0,
+            color_mode: ColorMode::default(),
+            preset: None,
+            importance_threshold: 0.0,
+            importance_filter: FilterMode::default(),
+            tool_use_filter: FilterMode::default(),
+            project_filter: FilterMode::default(),
+            hidden_projects: Vec::new(),
+            semantic_filter_modes: HashMap::new(),
+        }
+    }
+}
+
+impl ViewString {
+    /// Encode as `cdv1:<base64url(deflate(json))>`
+    pub fn encode(&self) -> Result<String, String> {
+        let json = serde_json::to_vec(self).map_err(|e| format!("Failed to serialize view: {}", e))?;
+        let mut encoder = DeflateEncoder::new(Vec::new(), Compression::best());
+        encoder
+            .write_all(&json)
+            .and_then(|_| encoder.finish())
+            .map(|compressed| format!("{}{}", PREFIX, URL_SAFE_NO_PAD.encode(compressed)))
+            .map_err(|e| format!("Failed to compress view: {}", e))
+    }
+
+    /// Decode a view string, with errors phrased for the user
+    pub fn decode(text: &str) -> Result<Self, String> {
+        let payload = text
+            .trim()
+            .strip_prefix(PREFIX)
+            .ok_or_else(|| "That doesn't look like a view string (expected it to start with \"cdv1:\")".to_string())?;
+        let damaged = || "The view string is damaged or incomplete — try copying it again".to_string();
+        let compressed = URL_SAFE_NO_PAD.decode(payload.trim()).map_err(|_| damaged())?;
+        let mut json = Vec::new();
+        DeflateDecoder::new(compressed.as_slice())
+            .take(MAX_DECODED_BYTES + 1)
+            .read_to_end(&mut json)
+            .map_err(|_| damaged())?;
+        if json.len() as u64 > MAX_DECODED_BYTES {
+            return Err(damaged());
+        }
+        serde_json::from_slice(&json).map_err(|e| format!("The view string couldn't be read: {}", e))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_round_trip() {
+        let view = ViewString {
+            time_range_hours: 72.0,
+            timeline_start: Some(1_750_000_000.0),
+            timeline_end: Some(1_750_003_600.0),
+            selected_node: Some("4812".to_string()),
+            pan: [120.0, -40.5],
+            zoom: 2.25,
+            color_mode: ColorMode::Session,
+            preset: Some("Triage".to_string()),
+            hidden_projects: vec!["scratch".to_string()],
+            semantic_filter_modes: HashMap::from([(3, SemanticFilterMode::Exclude)]),
+            ..Default::default()
+        };
+        let encoded = view.encode().unwrap();
+        assert!(encoded.starts_with(PREFIX));
+        assert_eq!(ViewString::decode(&format!("  {}\n", encoded)).unwrap(), view);
+    }
+
+    #[test]
+    fn test_unknown_fields_ignored() {
+        let json = br#"{"zoom": 3.0, "added_in_a_later_version": [1, 2]}"#;
+        let mut encoder = DeflateEncoder::new(Vec::new(), Compression::default());
+        encoder.write_all(json).unwrap();
+        let text = format!("{}{}", PREFIX, URL_SAFE_NO_PAD.encode(encoder.finish().unwrap()));
+
+        let view = ViewString::decode(&text).unwrap();
+        assert_eq!(view.zoom, 3.0);
+        assert_eq!(view.time_range_hours, ViewString::default().time_range_hours);
+    }
+
+    #[test]
+    fn test_bad_strings_rejected() {
+        assert!(ViewString::decode("hello").is_err());
+        assert!(ViewString::decode("cdv1:!!!").is_err());
+        assert!(ViewString::decode("cdv1:aGVsbG8").is_err());
+    }
+
+    #[test]
+    fn test_oversized_payload_rejected() {
+        let mut encoder = DeflateEncoder::new(Vec::new(), Compression::best());
+        encoder.write_all(&vec![b' '; MAX_DECODED_BYTES as usize + 1]).unwrap();
+        let bomb = format!("{}{}", PREFIX, URL_SAFE_NO_PAD.encode(encoder.finish().unwrap()));
+        let err = ViewString::decode(&bomb).unwrap_err();
+        assert!(err.contains("damaged"), "{}", err);
+    }
+}
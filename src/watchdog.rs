@@ -0,0 +1,176 @@
+//! UI stall watchdog. The UI thread marks each frame's start and end; a
+//! background thread warns while a frame has been running past
+//! `STALL_THRESHOLD`, and the finished frame is kept as a `StallEvent` with
+//! the profiler's slowest phase so it can be pasted into a bug report.
+//!
+//! Frames are what's timed, not the gaps between them: egui doesn't repaint
+//! while idle, so a long gap between frames is normal.
+
+use crate::profiler::{FrameProfiler, Phase};
+use std::collections::VecDeque;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+
+/// A frame running longer than this is a stall
+pub const STALL_THRESHOLD: Duration = Duration::from_millis(500);
+/// Stall events kept, newest first
+pub const MAX_STALL_EVENTS: usize = 8;
+/// How often the watchdog thread looks at the running frame
+const POLL_INTERVAL: Duration = Duration::from_millis(100);
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct StallEvent {
+    /// Local wall-clock time the stalled frame ended
+    pub at: chrono::DateTime<chrono::Local>,
+    pub duration_ms: f32,
+    /// Slowest profiled phase of the frame and its milliseconds
+    pub phase: Option<(Phase, f32)>,
+}
+
+impl StallEvent {
+    /// "14:02:31  1.8s  Physics (1.6s)". When the profiled phases account for
+    /// under half the stall, the time went elsewhere (loads, file I/O).
+    pub fn describe(&self) -> String {
+        let phase = match self.phase {
+            Some((phase, ms)) if ms >= self.duration_ms * 0.5 => format!("{} ({})", phase.label(), format_ms(ms)),
+            _ => "outside profiled phases".to_string(),
+        };
+        format!("{}  {}  {}", self.at.format("%H:%M:%S"), format_ms(self.duration_ms), phase)
+    }
+}
+
+fn format_ms(ms: f32) -> String {
+    if ms >= 1000.0 {
+        format!("{:.1}s", ms / 1000.0)
+    } else {
+        format!("{:.0}ms", ms)
+    }
+}
+
+/// State shared with the watchdog thread
+#[derive(Debug)]
+struct Shared {
+    epoch: Instant,
+    /// Milliseconds since `epoch` (plus one) when the running frame began; 0 between frames
+    frame_started: AtomicU64,
+}
+
+impl Shared {
+    fn now_ms(&self) -> u64 {
+        self.epoch.elapsed().as_millis() as u64
+    }
+}
+
+#[derive(Debug)]
+pub struct Watchdog {
+    shared: Arc<Shared>,
+    frame_start: Option<Instant>,
+    events: VecDeque<StallEvent>,
+}
+
+impl Watchdog {
+    /// Start the watchdog thread. It exits once the `Watchdog` is dropped.
+    pub fn start() -> Self {
+        let watchdog = Self::new();
+        let shared = Arc::clone(&watchdog.shared);
+        let spawned = std::thread::Builder::new()
+            .name("ui-watchdog".to_string())
+            .spawn(move || watch(shared));
+        if let Err(e) = spawned {
+            tracing::info!("UI watchdog not started: {}", e);
+        }
+        watchdog
+    }
+
+    fn new() -> Self {
+        Self {
+            shared: Arc::new(Shared { epoch: Instant::now(), frame_started: AtomicU64::new(0) }),
+            frame_start: None,
+            events: VecDeque::new(),
+        }
+    }
+
+    pub fn frame_started(&mut self) {
+        self.frame_start = Some(Instant::now());
+        self.shared.frame_started.store(self.shared.now_ms() + 1, Ordering::Relaxed);
+    }
+
+    /// Close the frame, recording a stall if it ran past the threshold
+    pub fn frame_finished(&mut self, profiler: &FrameProfiler) {
+        self.shared.frame_started.store(0, Ordering::Relaxed);
+        if let Some(start) = self.frame_start.take() {
+            self.record(start.elapsed(), profiler.frame_slowest());
+        }
+    }
+
+    fn record(&mut self, duration: Duration, phase: Option<(Phase, f32)>) {
+        if duration < STALL_THRESHOLD {
+            return;
+        }
+        self.events.push_front(StallEvent {
+            at: chrono::Local::now(),
+            duration_ms: duration.as_secs_f32() * 1000.0,
+            phase,
+        });
+        self.events.truncate(MAX_STALL_EVENTS);
+    }
+
+    /// Recorded stalls, newest first
+    pub fn events(&self) -> impl Iterator<Item = &StallEvent> {
+        self.events.iter()
+    }
+
+    pub fn clear(&mut self) {
+        self.events.clear();
+    }
+}
+
+/// Warn once per frame that runs past the threshold, while it's still running
+fn watch(shared: Arc<Shared>) {
+    let mut warned = 0;
+    // The UI side holds the other reference; alone means the app is gone
+    while Arc::strong_count(&shared) > 1 {
+        std::thread::sleep(POLL_INTERVAL);
+        let started = shared.frame_started.load(Ordering::Relaxed);
+        if started == 0 || started == warned {
+            continue;
+        }
+        let running = shared.now_ms().saturating_sub(started - 1);
+        if running >= STALL_THRESHOLD.as_millis() as u64 {
+            tracing::warn!(
+                "UI thread stalled: frame running for {}ms and counting. It's listed under UI stalls in the sidebar once it ends.",
+                running
+            );
+            warned = started;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_only_long_frames_are_kept() {
+        let mut watchdog = Watchdog::new();
+        watchdog.record(Duration::from_millis(120), Some((Phase::Nodes, 100.0)));
+        assert_eq!(watchdog.events().count(), 0);
+
+        for ms in 0..MAX_STALL_EVENTS as u64 + 2 {
+            watchdog.record(Duration::from_millis(600 + ms), None);
+        }
+        assert_eq!(watchdog.events().count(), MAX_STALL_EVENTS);
+        assert_eq!(watchdog.events().next().map(|e| e.duration_ms.round()), Some(609.0));
+    }
+
+    #[test]
+    fn test_describe_names_the_phase_only_when_it_explains_the_stall() {
+        let at = chrono::Local::now();
+        let event = |phase| StallEvent { at, duration_ms: 1800.0, phase };
+        let time = at.format("%H:%M:%S").to_string();
+        assert_eq!(event(Some((Phase::Physics, 1600.0))).describe(), format!("{}  1.8s  Physics (1.6s)", time));
+        assert_eq!(event(Some((Phase::Edges, 40.0))).describe(), format!("{}  1.8s  outside profiled phases", time));
+        assert_eq!(event(None).describe(), format!("{}  1.8s  outside profiled phases", time));
+    }
+}
@@ -0,0 +1,310 @@
+//! Write-ahead queue for backend writes that couldn't be delivered. A write
+//! that fails because the backend is down or busy is appended here (and to
+//! `write_queue.json` in the config dir) and replayed on later refreshes with
+//! exponential backoff. Writes the backend rejects outright stop retrying and
+//! stay listed until dismissed.
+
+use serde::{Deserialize, Serialize};
+use std::path::PathBuf;
+
+/// First retry delay; doubles per failed attempt
+pub const RETRY_BASE_SECS: f64 = 30.0;
+/// Longest wait between retries
+pub const RETRY_MAX_SECS: f64 = 3600.0;
+
+/// A write with everything needed to send it again
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "op", rename_all = "snake_case")]
+pub enum WriteOp {
+    CreateSemanticFilter { name: String, query_text: String, filter_type: String },
+    DeleteSemanticFilter { filter_id: i32 },
+}
+
+impl WriteOp {
+    pub fn describe(&self) -> String {
+        match self {
+            WriteOp::CreateSemanticFilter { name, filter_type, .. } => format!("Create {} filter \"{}\"", filter_type, name),
+            WriteOp::DeleteSemanticFilter { filter_id } => format!("Delete filter #{}", filter_id),
+        }
+    }
+}
+
+/// Why a write didn't go through
+#[derive(Debug, Clone, PartialEq)]
+pub enum WriteError {
+    /// Backend unreachable, timed out or failing (5xx): retry later
+    Transient(String),
+    /// Backend refused the write (4xx or an error reply): retrying won't help
+    Permanent(String),
+}
+
+impl WriteError {
+    /// Sort an `ApiClient` error message. Transport failures and 5xx statuses
+    /// are transient; other statuses and rejections in the reply body are not.
+    pub fn from_api(message: String) -> Self {
+        let transient = message.starts_with("Request failed")
+            || message.starts_with("API error: 5")
+            || message.starts_with("Failed to parse response");
+        if transient {
+            WriteError::Transient(message)
+        } else {
+            WriteError::Permanent(message)
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PendingWrite {
+    /// Unique within the queue; never reused
+    pub id: u64,
+    pub op: WriteOp,
+    /// Delivery attempts so far, including the original one
+    pub attempts: u32,
+    /// Unix seconds before which the write isn't retried
+    pub retry_at: f64,
+    pub last_error: String,
+    /// Rejected by the backend; kept for the user to see and dismiss
+    #[serde(default)]
+    pub failed: bool,
+}
+
+/// Counts from one replay pass
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub struct Replay {
+    pub applied: usize,
+    pub rescheduled: usize,
+    pub failed: usize,
+}
+
+#[derive(Debug, Default, Serialize, Deserialize)]
+pub struct WriteQueue {
+    pub entries: Vec<PendingWrite>,
+    next_id: u64,
+    /// Where the queue is persisted; None keeps it in memory only
+    #[serde(skip)]
+    path: Option<PathBuf>,
+}
+
+/// Wait after `attempts` failed deliveries
+pub fn backoff_secs(attempts: u32) -> f64 {
+    (RETRY_BASE_SECS * 2f64.powi(attempts.saturating_sub(1).min(16) as i32)).min(RETRY_MAX_SECS)
+}
+
+/// Location of the queue file
+pub fn path() -> Option<PathBuf> {
+    dirs::config_dir().map(|mut p| {
+        p.push("dashboard-native");
+        p.push("write_queue.json");
+        p
+    })
+}
+
+impl WriteQueue {
+    /// Load the queue from the config dir (empty if missing or unreadable)
+    pub fn load() -> Self {
+        match path() {
+            Some(path) => Self::load_from(path),
+            None => Self::default(),
+        }
+    }
+
+    pub fn load_from(path: PathBuf) -> Self {
+        let mut queue = match std::fs::read_to_string(&path) {
+            Ok(contents) => serde_json::from_str(&contents).unwrap_or_else(|e| {
+                tracing::warn!("Failed to parse write queue {:?}: {}", path, e);
+                Self::default()
+            }),
+            Err(_) => Self::default(),
+        };
+        queue.path = Some(path);
+        queue
+    }
+
+    fn save(&self) {
+        let Some(ref path) = self.path else {
+            return;
+        };
+        if self.entries.is_empty() {
+            let _ = std::fs::remove_file(path);
+            return;
+        }
+        if let Some(parent) = path.parent() {
+            let _ = std::fs::create_dir_all(parent);
+        }
+        match serde_json::to_string_pretty(self) {
+            Ok(json) => {
+                if let Err(e) = std::fs::write(path, json) {
+                    tracing::warn!("Failed to save write queue: {}", e);
+                }
+            }
+            Err(e) => tracing::warn!("Failed to serialize write queue: {}", e),
+        }
+    }
+
+    /// Queue a write whose first attempt just failed; returns its id
+    pub fn push(&mut self, op: WriteOp, error: String, now: f64) -> u64 {
+        let id = self.next_id;
+        self.next_id += 1;
+        self.entries.push(PendingWrite {
+            id,
+            op,
+            attempts: 1,
+            retry_at: now + backoff_secs(1),
+            last_error: error,
+            failed: false,
+        });
+        self.save();
+        id
+    }
+
+    /// Writes still being retried
+    pub fn pending(&self) -> usize {
+        self.entries.iter().filter(|e| !e.failed).count()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+
+    /// Whether an identical write is still waiting to be delivered
+    pub fn is_pending(&self, op: &WriteOp) -> bool {
+        self.entries.iter().any(|e| !e.failed && e.op == *op)
+    }
+
+    /// Drop an entry without sending it
+    pub fn dismiss(&mut self, id: u64) {
+        self.entries.retain(|e| e.id != id);
+        self.save();
+    }
+
+    /// Make every pending write due now (manual flush)
+    pub fn make_due(&mut self) {
+        for entry in self.entries.iter_mut().filter(|e| !e.failed) {
+            entry.retry_at = 0.0;
+        }
+    }
+
+    /// Send every due write through `apply`. Delivered writes are removed,
+    /// transient failures back off, permanent ones are marked failed.
+    pub fn replay(&mut self, now: f64, mut apply: impl FnMut(&WriteOp) -> Result<(), WriteError>) -> Replay {
+        let mut replay = Replay::default();
+        let mut changed = false;
+        self.entries.retain_mut(|entry| {
+            if entry.failed || entry.retry_at > now {
+                return true;
+            }
+            changed = true;
+            entry.attempts += 1;
+            match apply(&entry.op) {
+                Ok(()) => {
+                    replay.applied += 1;
+                    false
+                }
+                Err(WriteError::Transient(e)) => {
+                    entry.retry_at = now + backoff_secs(entry.attempts);
+                    entry.last_error = e;
+                    replay.rescheduled += 1;
+                    true
+                }
+                Err(WriteError::Permanent(e)) => {
+                    entry.failed = true;
+                    entry.last_error = e;
+                    replay.failed += 1;
+                    true
+                }
+            }
+        });
+        if changed {
+            self.save();
+        }
+        replay
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::collections::HashMap;
+
+    fn create(name: &str) -> WriteOp {
+        WriteOp::CreateSemanticFilter {
+            name: name.to_string(),
+            query_text: name.to_string(),
+            filter_type: "rule".to_string(),
+        }
+    }
+
+    #[test]
+    fn test_failed_then_succeeding_writes_apply_once() {
+        let dir = std::env::temp_dir().join(format!("write-queue-test-{}", std::process::id()));
+        let path = dir.join("write_queue.json");
+        let mut queue = WriteQueue::load_from(path.clone());
+        let a = queue.push(create("a"), "Request failed: connection refused".into(), 0.0);
+        let b = queue.push(WriteOp::DeleteSemanticFilter { filter_id: 7 }, "API error: 503".into(), 0.0);
+        assert_ne!(a, b);
+        assert_eq!(queue.pending(), 2);
+
+        // Persisted: a restart sees the same queue
+        let mut queue = WriteQueue::load_from(path.clone());
+        assert_eq!(queue.pending(), 2);
+
+        // Not due yet
+        let down = |_: &WriteOp| -> Result<(), WriteError> { Err(WriteError::Transient("Request failed".into())) };
+        assert_eq!(queue.replay(1.0, down), Replay::default());
+
+        // Due, backend still down: both back off further
+        let first = backoff_secs(1);
+        assert_eq!(queue.replay(first, down).rescheduled, 2);
+        assert!(queue.entries.iter().all(|e| e.retry_at == first + backoff_secs(2)));
+
+        // Manual flush once it's back; later passes find nothing left
+        let mut applied: HashMap<String, usize> = HashMap::new();
+        let mut up = |op: &WriteOp| {
+            *applied.entry(op.describe()).or_default() += 1;
+            Ok(())
+        };
+        queue.make_due();
+        assert_eq!(queue.replay(first, &mut up).applied, 2);
+        assert_eq!(queue.replay(f64::MAX, &mut up), Replay::default());
+        assert!(queue.is_empty());
+        assert_eq!(applied.len(), 2);
+        assert!(applied.values().all(|&n| n == 1));
+
+        assert!(WriteQueue::load_from(path.clone()).is_empty());
+        let _ = std::fs::remove_dir_all(dir);
+    }
+
+    #[test]
+    fn test_permanent_failure_stops_retrying() {
+        let mut queue = WriteQueue::default();
+        let id = queue.push(create("bad"), "Request failed".into(), 0.0);
+        let mut calls = 0;
+        let result = queue.replay(f64::MAX, |_| {
+            calls += 1;
+            Err(WriteError::from_api("API error: 422 Unprocessable Entity".into()))
+        });
+        assert_eq!(result.failed, 1);
+        queue.make_due();
+        queue.replay(f64::MAX, |_| {
+            calls += 1;
+            Ok(())
+        });
+        assert_eq!(calls, 1);
+        assert_eq!(queue.pending(), 0);
+        assert!(queue.entries[0].failed);
+        assert!(queue.entries[0].last_error.contains("422"));
+
+        queue.dismiss(id);
+        assert!(queue.is_empty());
+    }
+
+    #[test]
+    fn test_error_classification() {
+        assert!(matches!(WriteError::from_api("Request failed: timed out".into()), WriteError::Transient(_)));
+        assert!(matches!(WriteError::from_api("API error: 500 Internal Server Error".into()), WriteError::Transient(_)));
+        assert!(matches!(WriteError::from_api("API error: 404 Not Found".into()), WriteError::Permanent(_)));
+        assert!(matches!(WriteError::from_api("Filter already exists".into()), WriteError::Permanent(_)));
+        assert_eq!(backoff_secs(1), RETRY_BASE_SECS);
+        assert_eq!(backoff_secs(40), RETRY_MAX_SECS);
+    }
+}